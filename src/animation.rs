@@ -0,0 +1,406 @@
+//! Frame-level structure of animated GIF, APNG and WebP files.
+//!
+//! Each format keeps its timing in different places. GIF puts a Graphic
+//! Control Extension before every image, with the delay in hundredths of a
+//! second and the disposal method; the loop count lives in a `NETSCAPE2.0`
+//! application extension. APNG adds an `acTL` chunk with the frame and play
+//! counts and one `fcTL` chunk per frame. Animated WebP has an `ANIM` chunk
+//! with the loop count and one `ANMF` chunk per frame. Everything is read
+//! from the chunk and block headers; no image data is decoded.
+
+use crate::container::{self, GifBlock};
+use crate::diagnostics::Diagnostic;
+use crate::types::{MetadataValue, RawValue};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// GIF application extensions that control playback rather than carry
+/// metadata.
+pub const GIF_LOOP_EXTENSIONS: &[&[u8; 11]] = &[b"NETSCAPE2.0", b"ANIMEXTS1.0"];
+
+/// One frame of an animation.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AnimationFrame {
+    /// Frame rectangle on the canvas.
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// How long the frame is shown, in milliseconds.
+    pub delay_ms: u32,
+    /// What happens to the frame's area before the next one is drawn:
+    /// `"None"`, `"Background"` or `"Previous"`.
+    pub disposal: String,
+    /// `"Over"` to alpha-blend onto the canvas or `"Source"` to replace it.
+    /// GIF has no blend modes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blend: Option<String>,
+    /// GIF comment extensions preceding the frame.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<String>,
+}
+
+/// Frame structure of an animated (or potentially animated) image.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Animation {
+    /// `"GIF"`, `"APNG"` or `"WebP"`.
+    pub format: String,
+    pub canvas_width: u32,
+    pub canvas_height: u32,
+    /// Number of times to play; 0 means forever. `None` if the file does
+    /// not say, in which case GIF viewers play once.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loop_count: Option<u32>,
+    /// Frame count declared in the APNG `acTL` chunk, which may disagree
+    /// with the frames actually present.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declared_frames: Option<u32>,
+    pub frames: Vec<AnimationFrame>,
+}
+
+impl Animation {
+    /// Whether the file holds more than one frame to play.
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1
+    }
+
+    /// Total playing time of one loop, in milliseconds.
+    pub fn total_duration_ms(&self) -> u64 {
+        self.frames.iter().map(|f| u64::from(f.delay_ms)).sum()
+    }
+
+    /// All GIF comments, in stream order.
+    pub fn comments(&self) -> impl Iterator<Item = &str> {
+        self.frames
+            .iter()
+            .flat_map(|f| f.comments.iter().map(String::as_str))
+    }
+}
+
+fn gif_disposal(method: u8) -> &'static str {
+    match method {
+        2 => "Background",
+        3 => "Previous",
+        // 0 (unspecified) and 1 (do not dispose) both leave the frame
+        _ => "None",
+    }
+}
+
+/// Loop count of a `NETSCAPE2.0` (or `ANIMEXTS1.0`) application extension
+/// block, or `None` for any other block.
+pub fn gif_loop_extension(block: &GifBlock<'_>) -> Option<u16> {
+    let identifier = block.bytes.get(3..14)?;
+    if block.label != 0xFF
+        || block.bytes[2] != 11
+        || !GIF_LOOP_EXTENSIONS.iter().any(|id| id[..] == *identifier)
+    {
+        return None;
+    }
+    let mut payload = Vec::new();
+    container::gif_sub_blocks(block.bytes, 14, &mut payload);
+    (payload.len() >= 3 && payload[0] == 1).then(|| u16::from_le_bytes([payload[1], payload[2]]))
+}
+
+/// Read the frames of a GIF file.
+pub fn parse_gif(data: &[u8]) -> Option<Animation> {
+    read_gif(data, &mut Vec::new())
+}
+
+fn read_gif(data: &[u8], diagnostics: &mut Vec<Diagnostic>) -> Option<Animation> {
+    container::gif_stream_start(data)?;
+    let u16_at = |bytes: &[u8], i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let mut animation = Animation {
+        format: "GIF".to_string(),
+        canvas_width: u32::from(u16_at(data, 6)),
+        canvas_height: u32::from(u16_at(data, 8)),
+        loop_count: None,
+        declared_frames: None,
+        frames: Vec::new(),
+    };
+
+    // Graphic control and comments apply to the next image
+    let mut control: Option<(u32, u8)> = None;
+    let mut comments = Vec::new();
+    let (blocks, problem) = container::gif_blocks_checked(data);
+    diagnostics.extend(problem);
+    for block in blocks {
+        match (block.introducer, block.label) {
+            (0x21, 0xF9) if block.bytes.len() >= 7 && block.bytes[2] >= 4 => {
+                let packed = block.bytes[3];
+                let delay = u32::from(u16_at(block.bytes, 4)) * 10;
+                control = Some((delay, (packed >> 2) & 0x07));
+            }
+            (0x21, 0xFE) => {
+                let text = String::from_utf8_lossy(&block.extension_data())
+                    .trim_end_matches('\0')
+                    .trim()
+                    .to_string();
+                if !text.is_empty() {
+                    comments.push(text);
+                }
+            }
+            (0x21, 0xFF) => {
+                if let Some(count) = gif_loop_extension(&block) {
+                    animation.loop_count = Some(u32::from(count));
+                }
+            }
+            (0x2C, _) if block.bytes.len() >= 9 => {
+                let (delay_ms, disposal) = control.take().unwrap_or((0, 0));
+                animation.frames.push(AnimationFrame {
+                    x: u32::from(u16_at(block.bytes, 1)),
+                    y: u32::from(u16_at(block.bytes, 3)),
+                    width: u32::from(u16_at(block.bytes, 5)),
+                    height: u32::from(u16_at(block.bytes, 7)),
+                    delay_ms,
+                    disposal: gif_disposal(disposal).to_string(),
+                    blend: None,
+                    comments: std::mem::take(&mut comments),
+                });
+            }
+            _ => {}
+        }
+    }
+    // Comments after the last image belong to the file as a whole
+    if let Some(last) = animation.frames.last_mut() {
+        last.comments.append(&mut comments);
+    }
+    Some(animation)
+}
+
+/// Read the frames of an APNG file. Plain PNGs without `acTL` give `None`.
+pub fn parse_apng(data: &[u8]) -> Option<Animation> {
+    read_apng(data, &mut Vec::new())
+}
+
+/// The chunk sequence itself is reported by the PNG text extractor, so only
+/// a frame count disagreeing with `acTL` is reported here.
+fn read_apng(data: &[u8], diagnostics: &mut Vec<Diagnostic>) -> Option<Animation> {
+    let chunks = container::png_chunks(data);
+    let be32 = |bytes: &[u8], i: usize| -> Option<u32> {
+        Some(u32::from_be_bytes(bytes.get(i..i + 4)?.try_into().ok()?))
+    };
+    let ihdr = chunks.iter().find(|c| &c.id == b"IHDR")?;
+    let actl = chunks.iter().find(|c| &c.id == b"acTL")?;
+
+    let frames = chunks
+        .iter()
+        .filter(|c| &c.id == b"fcTL" && c.data.len() >= 26)
+        .filter_map(|c| {
+            let numerator = u16::from_be_bytes([c.data[20], c.data[21]]);
+            let denominator = match u16::from_be_bytes([c.data[22], c.data[23]]) {
+                0 => 100,
+                d => d,
+            };
+            Some(AnimationFrame {
+                width: be32(c.data, 4)?,
+                height: be32(c.data, 8)?,
+                x: be32(c.data, 12)?,
+                y: be32(c.data, 16)?,
+                delay_ms: (u32::from(numerator) * 1000 + u32::from(denominator) / 2)
+                    / u32::from(denominator),
+                disposal: match c.data[24] {
+                    1 => "Background",
+                    2 => "Previous",
+                    _ => "None",
+                }
+                .to_string(),
+                blend: Some(if c.data[25] == 1 { "Over" } else { "Source" }.to_string()),
+                comments: Vec::new(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let declared_frames = be32(actl.data, 0);
+    if let Some(declared) = declared_frames
+        && declared as usize != frames.len()
+    {
+        diagnostics.push(Diagnostic::warning(
+            "APNG",
+            Some(actl.offset),
+            format!(
+                "acTL declares {} frames, but {} fcTL chunks were found",
+                declared,
+                frames.len()
+            ),
+        ));
+    }
+
+    Some(Animation {
+        format: "APNG".to_string(),
+        canvas_width: be32(ihdr.data, 0)?,
+        canvas_height: be32(ihdr.data, 4)?,
+        loop_count: be32(actl.data, 4),
+        declared_frames,
+        frames,
+    })
+}
+
+/// Read the frames of an animated WebP file. Still WebPs give `None`.
+pub fn parse_webp(data: &[u8]) -> Option<Animation> {
+    read_webp(data, &mut Vec::new())
+}
+
+fn read_webp(data: &[u8], diagnostics: &mut Vec<Diagnostic>) -> Option<Animation> {
+    if data.get(8..12) != Some(b"WEBP") {
+        return None;
+    }
+    let (chunks, problem) = container::riff_chunks_checked(data);
+    diagnostics.extend(problem);
+    let le24 = |bytes: &[u8], i: usize| -> u32 {
+        u32::from(bytes[i]) | u32::from(bytes[i + 1]) << 8 | u32::from(bytes[i + 2]) << 16
+    };
+    let vp8x = chunks
+        .iter()
+        .find(|c| &c.id == b"VP8X" && c.data.len() >= 10)?;
+    let anim = chunks
+        .iter()
+        .find(|c| &c.id == b"ANIM" && c.data.len() >= 6);
+    let frames: Vec<AnimationFrame> = chunks
+        .iter()
+        .filter(|c| &c.id == b"ANMF" && c.data.len() >= 16)
+        .map(|c| {
+            let flags = c.data[15];
+            AnimationFrame {
+                x: le24(c.data, 0) * 2,
+                y: le24(c.data, 3) * 2,
+                width: le24(c.data, 6) + 1,
+                height: le24(c.data, 9) + 1,
+                delay_ms: le24(c.data, 12),
+                disposal: if flags & 0x01 != 0 {
+                    "Background"
+                } else {
+                    "None"
+                }
+                .to_string(),
+                blend: Some(if flags & 0x02 != 0 { "Source" } else { "Over" }.to_string()),
+                comments: Vec::new(),
+            }
+        })
+        .collect();
+    if anim.is_none() && frames.is_empty() {
+        return None;
+    }
+
+    Some(Animation {
+        format: "WebP".to_string(),
+        canvas_width: le24(vp8x.data, 4) + 1,
+        canvas_height: le24(vp8x.data, 7) + 1,
+        loop_count: anim.map(|c| u32::from(u16::from_le_bytes([c.data[4], c.data[5]]))),
+        declared_frames: None,
+        frames,
+    })
+}
+
+/// Read the frame structure of a GIF, APNG or animated WebP file.
+pub fn parse_animation(data: &[u8]) -> Option<Animation> {
+    parse_animation_with_diagnostics(data, &mut Vec::new())
+}
+
+/// Read the frame structure as [`parse_animation`] does, reporting a damaged
+/// GIF block or WebP chunk sequence and APNG frame counts that disagree.
+pub fn parse_animation_with_diagnostics(
+    data: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<Animation> {
+    if container::is_gif(data) {
+        read_gif(data, diagnostics)
+    } else if container::is_png(data) {
+        read_apng(data, diagnostics)
+    } else {
+        read_webp(data, diagnostics)
+    }
+}
+
+fn integer(value: u64, display: String) -> MetadataValue {
+    MetadataValue::new(RawValue::Integer(vec![value as i64]), display)
+}
+
+/// Convert the frame structure into `Animation:` entries, plus `GIF:Comment`
+/// entries for the comments of any GIF, animated or not.
+pub fn animation_to_metadata(animation: &Animation) -> HashMap<String, MetadataValue> {
+    let mut map = HashMap::new();
+    let comments: Vec<&str> = animation.comments().collect();
+    match comments.as_slice() {
+        [] => {}
+        [comment] => {
+            map.insert("GIF:Comment".to_string(), MetadataValue::text(*comment));
+        }
+        comments => {
+            for (i, comment) in comments.iter().enumerate() {
+                map.insert(
+                    format!("GIF:Comment{}", i + 1),
+                    MetadataValue::text(*comment),
+                );
+            }
+        }
+    }
+    if !animation.is_animated() {
+        return map;
+    }
+
+    let frames = animation.frames.len() as u64;
+    map.insert(
+        "Animation:FrameCount".to_string(),
+        integer(frames, frames.to_string()),
+    );
+    let duration = animation.total_duration_ms() as f64 / 1000.0;
+    map.insert(
+        "Animation:Duration".to_string(),
+        MetadataValue::new(
+            RawValue::Float(vec![duration]),
+            format!("{:.2} s", duration),
+        )
+        .with_unit("s"),
+    );
+    if let Some(count) = animation.loop_count {
+        let display = match count {
+            0 => "Infinite".to_string(),
+            n => n.to_string(),
+        };
+        map.insert(
+            "Animation:LoopCount".to_string(),
+            integer(u64::from(count), display),
+        );
+    }
+    let delays: Vec<u32> = animation.frames.iter().map(|f| f.delay_ms).collect();
+    map.insert(
+        "Animation:FrameDelays".to_string(),
+        MetadataValue::new(
+            RawValue::Integer(delays.iter().map(|d| i64::from(*d)).collect()),
+            format!(
+                "{} ms",
+                delays
+                    .iter()
+                    .map(u32::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+        .with_unit("ms"),
+    );
+    if let Some(declared) = animation.declared_frames
+        && u64::from(declared) != frames
+    {
+        map.insert(
+            "Animation:DeclaredFrameCount".to_string(),
+            integer(u64::from(declared), declared.to_string()),
+        );
+    }
+    map
+}
+
+/// Extract the frame structure as `exif_data` entries.
+pub fn extract_animation_data(data: &[u8]) -> HashMap<String, MetadataValue> {
+    extract_animation_data_with_diagnostics(data, &mut Vec::new())
+}
+
+/// Extract the frame structure as [`extract_animation_data`] does, reporting
+/// the problems [`parse_animation_with_diagnostics`] finds.
+pub fn extract_animation_data_with_diagnostics(
+    data: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, MetadataValue> {
+    parse_animation_with_diagnostics(data, diagnostics)
+        .map(|animation| animation_to_metadata(&animation))
+        .unwrap_or_default()
+}
@@ -4,16 +4,20 @@
 //! application.
 
 use crate::components::{
+    animation_inspector::AnimationInspector,
     archive_import::ArchiveImport,
     batch_cleaner::BatchCleaner,
     batch_manager::BatchManager,
     command_palette::{CommandAction, CommandPalette},
+    content_credentials::ContentCredentialsPanel,
     duplicate_detector::DuplicateDetector,
     file_upload::FileUpload,
     image_cleaner::ImageCleaner,
     image_display::ImageDisplay,
+    jpeg_inspector::JpegInspector,
     metadata_display::MetadataDisplay,
     metadata_export::MetadataExport,
+    motion_photo::MotionPhotoPanel,
     privacy_risk::PrivacyRiskWarning,
 };
 use crate::preferences::UserPreferences;
@@ -427,13 +431,12 @@ pub fn app() -> Html {
                         preferences.include_basic_info,
                         preferences.include_gps,
                     );
-                    if let Ok(json) = serde_json::to_string_pretty(&filtered_data) {
-                        crate::utils::download_file(
-                            &json,
-                            &format!("{}_filtered_metadata.json", data.name),
-                            "application/json",
-                        );
-                    }
+                    let json = crate::export::generate_json(&filtered_data);
+                    crate::utils::download_file(
+                        &json,
+                        &format!("{}_filtered_metadata.json", data.name),
+                        "application/json",
+                    );
                 }
             }
             CommandAction::ExportCsv => {
@@ -473,9 +476,8 @@ pub fn app() -> Html {
                         preferences.include_basic_info,
                         preferences.include_gps,
                     );
-                    if let Ok(json) = serde_json::to_string_pretty(&filtered_data) {
-                        crate::utils::copy_to_clipboard(&json);
-                    }
+                    let json = crate::export::generate_json(&filtered_data);
+                    crate::utils::copy_to_clipboard(&json);
                 }
             }
             CommandAction::CopyCsv => {
@@ -665,6 +667,27 @@ pub fn app() -> Html {
                                         theme={*theme}
                                     />
 
+                                    <JpegInspector
+                                        image_data={data.clone()}
+                                        theme={*theme}
+                                    />
+
+                                    <AnimationInspector
+                                        image_data={data.clone()}
+                                        theme={*theme}
+                                    />
+
+                                    <ContentCredentialsPanel
+                                        image_data={data.clone()}
+                                        theme={*theme}
+                                    />
+
+                                    <MotionPhotoPanel
+                                        image_data={data.clone()}
+                                        batch_items={(*batch_items).clone()}
+                                        theme={*theme}
+                                    />
+
                                     <MetadataDisplay
                                         image_data={data.clone()}
                                         selected_metadata={(*selected_metadata).clone()}
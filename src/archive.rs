@@ -4,6 +4,7 @@
 //! users to upload entire archives of images for batch metadata processing
 //! without requiring server-side processing.
 
+use crate::{file_type, limits};
 use gloo_file::Blob;
 use std::io::Cursor;
 use wasm_bindgen::JsValue;
@@ -23,30 +24,70 @@ pub struct ArchivedFile {
 /// * `file` - The ZIP file to extract from
 ///
 /// # Returns
-/// A vector of [`ArchivedFile`] entries containing image files only
+/// The [`ArchivedFile`] entries of the image files, and a message for each
+/// image file that was skipped
 ///
 /// # Errors
-/// Returns JsValue error if the ZIP is corrupted or cannot be read
-pub async fn extract_images_from_zip(file: File) -> Result<Vec<ArchivedFile>, JsValue> {
+/// Returns JsValue error if the ZIP is corrupted, cannot be read or goes
+/// over one of the archive limits in [`limits`]
+pub async fn extract_images_from_zip(
+    file: File,
+) -> Result<(Vec<ArchivedFile>, Vec<String>), JsValue> {
     // Read the file as bytes
     let array_buffer = gloo_file::futures::read_as_bytes(&file.into())
         .await
         .map_err(|e| JsValue::from_str(&format!("Failed to read ZIP file: {:?}", e)))?;
 
-    // Create a cursor for the zip reader
-    let cursor = Cursor::new(array_buffer);
+    let entries = read_zip_entries(array_buffer).map_err(|e| JsValue::from_str(&e))?;
+    let files = entries
+        .files
+        .into_iter()
+        .map(|(name, contents)| {
+            // Determine MIME type from extension
+            let mime_type = get_mime_type(&name);
+            let blob = Blob::new_with_options(contents.as_slice(), Some(mime_type));
+            ArchivedFile { name, blob }
+        })
+        .collect();
+    Ok((files, entries.skipped))
+}
+
+/// Files read from a ZIP archive by [`read_zip_entries`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ZipEntries {
+    /// Names and contents of the supported files.
+    pub files: Vec<(String, Vec<u8>)>,
+    /// Why each supported file that was left out was skipped.
+    pub skipped: Vec<String>,
+}
 
+/// Read the names and contents of the supported files in a ZIP archive.
+///
+/// Declared sizes are not trusted: each entry is inflated only up to the
+/// smallest of [`limits::MAX_ARCHIVE_ENTRY_SIZE`], what is left of
+/// [`limits::MAX_ARCHIVE_SIZE`] and [`limits::MAX_COMPRESSION_RATIO`] times
+/// its compressed size, so a zip bomb fails with a limit error instead of
+/// filling memory. An entry that declares a size over the compression ratio
+/// is skipped and reported instead, since flat images such as large
+/// uncompressed TIFFs can compress that well.
+pub fn read_zip_entries(bytes: Vec<u8>) -> Result<ZipEntries, String> {
     // Open the ZIP archive
-    let mut archive = ZipArchive::new(cursor)
-        .map_err(|e| JsValue::from_str(&format!("Invalid ZIP file: {}", e)))?;
+    let mut archive =
+        ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Invalid ZIP file: {}", e))?;
+    limits::check(
+        "ZIP entry count",
+        archive.len() as u64,
+        limits::MAX_ARCHIVE_ENTRIES as u64,
+    )?;
 
-    let mut results = Vec::new();
+    let mut results = ZipEntries::default();
+    let mut total = 0u64;
 
     // Iterate through all entries in the archive
     for i in 0..archive.len() {
         let mut entry = archive
             .by_index(i)
-            .map_err(|e| JsValue::from_str(&format!("Failed to read ZIP entry: {}", e)))?;
+            .map_err(|e| format!("Failed to read ZIP entry: {}", e))?;
 
         // Skip directories
         if entry.is_dir() {
@@ -60,65 +101,42 @@ pub async fn extract_images_from_zip(file: File) -> Result<Vec<ArchivedFile>, Js
             continue;
         }
 
-        // Read the file contents
-        let mut contents = Vec::new();
-        std::io::copy(&mut entry, &mut contents)
-            .map_err(|e| JsValue::from_str(&format!("Failed to extract {}: {}", name, e)))?;
-
-        // Determine MIME type from extension
-        let mime_type = get_mime_type(&name);
-
-        // Create a Blob from the contents
-        let blob = Blob::new_with_options(contents.as_slice(), Some(mime_type));
+        let size_limit = limits::MAX_ARCHIVE_ENTRY_SIZE.min(limits::MAX_ARCHIVE_SIZE - total);
+        limits::check(&format!("size of {}", name), entry.size(), size_limit)?;
+        let ratio_limit = entry
+            .compressed_size()
+            .saturating_mul(limits::MAX_COMPRESSION_RATIO);
+        if let Err(e) = limits::check(
+            &format!("inflated size of {}", name),
+            entry.size(),
+            ratio_limit,
+        ) {
+            results.skipped.push(format!("Skipped {}: {}", name, e));
+            continue;
+        }
 
-        results.push(ArchivedFile { name, blob });
+        // Read the file contents, holding the stream to the same limits
+        let mut contents = Vec::new();
+        limits::read_to_end_limited(
+            &mut entry,
+            &mut contents,
+            size_limit.min(ratio_limit) as usize,
+        )
+        .map_err(|e| format!("Failed to extract {}: {}", name, e))?;
+        total += contents.len() as u64;
+
+        results.files.push((name, contents));
     }
 
     Ok(results)
 }
 
-/// Check if a filename represents an image file
+/// Check if a filename represents a supported image, document or video file
 pub fn is_image_file(filename: &str) -> bool {
-    let lower = filename.to_lowercase();
-    lower.ends_with(".jpg")
-        || lower.ends_with(".jpeg")
-        || lower.ends_with(".png")
-        || lower.ends_with(".gif")
-        || lower.ends_with(".webp")
-        || lower.ends_with(".tiff")
-        || lower.ends_with(".tif")
-        || lower.ends_with(".heif")
-        || lower.ends_with(".heic")
-        || lower.ends_with(".avif")
-        || lower.ends_with(".jxl")
-        || lower.ends_with(".svg")
-        || lower.ends_with(".pdf")
+    file_type::extension_mime_type(filename).is_some()
 }
 
 /// Get MIME type from filename extension
 pub fn get_mime_type(filename: &str) -> &'static str {
-    let lower = filename.to_lowercase();
-    if lower.ends_with(".jpg") || lower.ends_with(".jpeg") {
-        "image/jpeg"
-    } else if lower.ends_with(".png") {
-        "image/png"
-    } else if lower.ends_with(".gif") {
-        "image/gif"
-    } else if lower.ends_with(".webp") {
-        "image/webp"
-    } else if lower.ends_with(".tiff") || lower.ends_with(".tif") {
-        "image/tiff"
-    } else if lower.ends_with(".heif") || lower.ends_with(".heic") {
-        "image/heif"
-    } else if lower.ends_with(".avif") {
-        "image/avif"
-    } else if lower.ends_with(".jxl") {
-        "image/jxl"
-    } else if lower.ends_with(".svg") {
-        "image/svg+xml"
-    } else if lower.ends_with(".pdf") {
-        "application/pdf"
-    } else {
-        "application/octet-stream"
-    }
+    file_type::extension_mime_type(filename).unwrap_or("application/octet-stream")
 }
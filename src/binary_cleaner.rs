@@ -4,13 +4,11 @@
 //! common formats without decoding the image data. This keeps the original
 //! quality intact while removing identifying information.
 
-use little_exif::filetype::FileExtension;
-use little_exif::metadata::Metadata;
-use std::io::Cursor;
-use tiff::decoder::{Decoder, DecodingResult};
-use tiff::encoder::{TiffEncoder, colortype};
+use crate::container::ByteOrder;
+use crate::limits::{self, Budget, LimitExceeded};
+use crate::mpf::{self, MpEntry, MpIndex};
+use crate::{animation, container, file_type, jpeg, motion_photo};
 // use libheif_rs::{HeifContext, ItemId}; // TODO: Uncomment when HEIF implementation is complete
-use half::f16;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
@@ -28,28 +26,111 @@ macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
+/// VP8X feature flags announcing the metadata chunks of a WebP file.
+const VP8X_ICC_FLAG: u8 = 0x20;
+const VP8X_EXIF_FLAG: u8 = 0x08;
+const VP8X_XMP_FLAG: u8 = 0x04;
+
+/// Baseline TIFF tags describing how to display the first image: size,
+/// sample layout, compression, strips and tiles, colour map, YCbCr and
+/// resolution. The TIFF cleaner drops every other tag.
+const TIFF_IMAGE_TAGS: &[u16] = &[
+    254, 256, 257, 258, 259, 262, 266, 273, 277, 278, 279, 282, 283, 284, 296, 317, 320, 322, 323,
+    324, 325, 338, 339, 347, 529, 530, 531, 532,
+];
+
+fn tiff_u16(order: ByteOrder, value: u16) -> [u8; 2] {
+    match order {
+        ByteOrder::Little => value.to_le_bytes(),
+        ByteOrder::Big => value.to_be_bytes(),
+    }
+}
+
+fn tiff_u32(order: ByteOrder, value: u32) -> [u8; 4] {
+    match order {
+        ByteOrder::Little => value.to_le_bytes(),
+        ByteOrder::Big => value.to_be_bytes(),
+    }
+}
+
+/// Choices for [`BinaryCleaner::clean_metadata_with_options`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CleanOptions {
+    /// Drop motion photo clips stored in the image. They can record sound
+    /// and their own location, so they are removed unless the caller opts to
+    /// keep them.
+    pub remove_embedded_video: bool,
+    /// Drop the extra images of Multi-Picture (MPO) files, such as depth
+    /// maps, gain maps and second stereo views. When kept, each is cleaned
+    /// like the primary image and the MP index is rebuilt to match.
+    pub remove_extra_images: bool,
+}
+
+impl Default for CleanOptions {
+    fn default() -> Self {
+        Self {
+            remove_embedded_video: true,
+            remove_extra_images: true,
+        }
+    }
+}
+
 /// Helper type with functions for metadata stripping.
 pub struct BinaryCleaner;
 
 impl BinaryCleaner {
     /// Remove metadata from image file using direct binary manipulation
-    /// This preserves original image quality while stripping all metadata
+    /// This preserves original image quality while stripping all metadata.
+    /// The format is detected from the content; `file_extension` is only
+    /// used when the content is not recognised.
     pub fn clean_metadata(file_data: &[u8], file_extension: &str) -> Result<Vec<u8>, String> {
-        match file_extension.to_lowercase().as_str() {
-            // Formats that require mutable access for little_exif
-            "jpg" | "jpeg" => {
-                let mut data = file_data.to_vec();
-                Self::clean_jpeg_metadata(&mut data)
-            }
-            "tiff" | "tif" => {
-                let mut data = file_data.to_vec();
-                Self::clean_tiff_metadata(&mut data)
-            }
-            "heif" | "heic" => {
-                let mut data = file_data.to_vec();
-                Self::clean_heif_metadata(&mut data)
+        Self::clean_metadata_with_options(file_data, file_extension, CleanOptions::default())
+    }
+
+    /// Remove metadata as [`clean_metadata`](Self::clean_metadata) does,
+    /// with control over what else is stripped.
+    pub fn clean_metadata_with_options(
+        file_data: &[u8],
+        file_extension: &str,
+        options: CleanOptions,
+    ) -> Result<Vec<u8>, String> {
+        limits::check(
+            "file size",
+            file_data.len() as u64,
+            limits::MAX_FILE_SIZE as u64,
+        )?;
+        let without_video = options
+            .remove_embedded_video
+            .then(|| motion_photo::strip_embedded_video(file_data))
+            .flatten();
+        if without_video.is_some() {
+            console_log!("Removed embedded motion photo video");
+        }
+        let file_data = without_video.as_deref().unwrap_or(file_data);
+
+        // The content decides the format; the extension only when it is not
+        // recognised
+        let file_extension = match file_type::detect_mime_type(file_data) {
+            Some(mime) => {
+                let extension = file_type::cleaner_extension(mime)
+                    .ok_or_else(|| format!("Unsupported format for binary cleaning: {}", mime))?;
+                if file_type::extension_mime_type(&format!(".{}", file_extension)) != Some(mime) {
+                    console_log!("Cleaning .{} file as {}", file_extension, mime);
+                }
+                extension
             }
-            // Formats cleaned without mutation
+            None => file_extension,
+        };
+
+        match file_extension.to_lowercase().as_str() {
+            "jpg" | "jpeg" => match mpf::parse_mp_index(file_data) {
+                Some(index) => {
+                    Self::clean_mpf_metadata(file_data, &index, options.remove_extra_images)
+                }
+                None => Self::clean_jpeg_metadata(file_data),
+            },
+            "tiff" | "tif" => Self::clean_tiff_metadata(file_data),
+            "heif" | "heic" => Self::clean_heif_metadata(file_data),
             "png" => Self::clean_png_metadata(file_data),
             "webp" => Self::clean_webp_metadata(file_data),
             "gif" => Self::clean_gif_metadata(file_data),
@@ -65,22 +146,77 @@ impl BinaryCleaner {
     }
 
     /// Clean JPEG metadata by removing application segments (APP0-APP15)
-    // little_exif expects a mutable Vec reference
-    #[allow(clippy::ptr_arg)]
-    fn clean_jpeg_metadata(data: &mut Vec<u8>) -> Result<Vec<u8>, String> {
-        // Use little_exif to clear common metadata segments
-        match Metadata::clear_app12_segment(data, FileExtension::JPEG) {
-            Ok(_) => console_log!("Cleared APP12 segment"),
-            Err(e) => console_log!("APP12 clear warning: {:?}", e),
-        }
+    fn clean_jpeg_metadata(data: &[u8]) -> Result<Vec<u8>, String> {
+        Self::remove_jpeg_app_segments(data)
+    }
 
-        match Metadata::clear_app13_segment(data, FileExtension::JPEG) {
-            Ok(_) => console_log!("Cleared APP13 segment"),
-            Err(e) => console_log!("APP13 clear warning: {:?}", e),
+    /// Clean a Multi-Picture JPEG. The primary image and every kept extra
+    /// image are cleaned on their own, since each carries its own EXIF.
+    /// Extra images are either dropped along with the MP index, or kept behind
+    /// a rebuilt index whose offsets and sizes match the cleaned images, so
+    /// no image is left without an index entry or vice versa.
+    fn clean_mpf_metadata(
+        data: &[u8],
+        index: &MpIndex,
+        remove_extra_images: bool,
+    ) -> Result<Vec<u8>, String> {
+        let primary_end =
+            jpeg::analyze_jpeg(data).map_or(data.len(), |s| data.len() - s.trailing_bytes);
+        let clean = Self::clean_jpeg_metadata;
+        let mut cleaned = clean(&data[..primary_end])?;
+
+        // Bytes after the primary image, with each extra image cleaned or cut
+        let mut trailer = Vec::new();
+        let mut kept = Vec::new();
+        let mut position = primary_end;
+        for (entry, range) in index.extra_image_ranges(data) {
+            if range.start < primary_end {
+                continue;
+            }
+            trailer.extend_from_slice(&data[position..range.start]);
+            position = range.end;
+            if remove_extra_images {
+                console_log!("Removed MPF image {}", entry + 1);
+                continue;
+            }
+            let image = clean(&data[range])?;
+            kept.push((entry, trailer.len(), image.len()));
+            trailer.extend(image);
+        }
+        trailer.extend_from_slice(&data[position..]);
+
+        if !kept.is_empty() {
+            // New 1-based entry numbers, for the dependent image references
+            let renumber = |old: u16| {
+                std::iter::once(0)
+                    .chain(kept.iter().map(|(entry, _, _)| *entry))
+                    .position(|entry| entry + 1 == old as usize)
+                    .map_or(0, |new| new as u16 + 1)
+            };
+            let remap = |entry: &MpEntry, size: usize, offset: usize| MpEntry {
+                attributes: entry.attributes,
+                size: size as u32,
+                offset: offset as u32,
+                dependents: entry.dependents.map(renumber),
+            };
+            // The index goes right after SOI; offsets count from its header
+            let segment_len = mpf::mp_index_segment(&vec![index.entries[0]; kept.len() + 1]).len();
+            let header_offset = 2 + 4 + mpf::MPF_SIGNATURE.len();
+            let primary_size = cleaned.len() + segment_len;
+            let entries: Vec<MpEntry> = std::iter::once(remap(&index.entries[0], primary_size, 0))
+                .chain(kept.iter().map(|(entry, start, size)| {
+                    remap(
+                        &index.entries[*entry],
+                        *size,
+                        primary_size + start - header_offset,
+                    )
+                }))
+                .collect();
+            cleaned.splice(2..2, mpf::mp_index_segment(&entries));
         }
 
-        // Manual removal of common EXIF and metadata segments
-        Self::remove_jpeg_app_segments(data)
+        cleaned.extend(trailer);
+        Ok(cleaned)
     }
 
     /// Remove JPEG application segments manually for comprehensive metadata removal
@@ -96,8 +232,10 @@ impl BinaryCleaner {
 
         let mut cleaned = vec![0xFF, 0xD8]; // Keep SOI marker
         let mut i = 2;
+        let mut budget = Budget::blocks("JPEG segment count");
 
         while i < data.len() - 1 {
+            budget.spend()?;
             if data[i] != 0xFF {
                 // Not a marker, copy remaining data (we've hit image data)
                 cleaned.extend_from_slice(&data[i..]);
@@ -107,8 +245,8 @@ impl BinaryCleaner {
             let marker = data[i + 1];
 
             match marker {
-                // Start of Scan - image data follows, copy rest of file
-                0xDA => {
+                // End of image - copy it and anything after it
+                0xD9 => {
                     cleaned.extend_from_slice(&data[i..]);
                     break;
                 }
@@ -118,7 +256,7 @@ impl BinaryCleaner {
                         return Err("Truncated JPEG file".to_string());
                     }
                     // Get segment length (big-endian)
-                    let length = ((data[i + 2] as u16) << 8) | (data[i + 3] as u16);
+                    let length = u16::from_be_bytes([data[i + 2], data[i + 3]]);
                     if length < 2 {
                         return Err("Invalid segment length".to_string());
                     }
@@ -126,19 +264,27 @@ impl BinaryCleaner {
                     i += 2 + length as usize;
                     console_log!("Removed APP{} segment", marker - 0xE0);
                 }
-                // Keep other markers (quantization tables, Huffman tables, etc.)
+                // Keep other markers (quantization tables, Huffman tables,
+                // scans, etc.)
                 _ => {
                     if i + 3 >= data.len() {
                         cleaned.extend_from_slice(&data[i..]);
                         break;
                     }
-                    let length = ((data[i + 2] as u16) << 8) | (data[i + 3] as u16);
-                    if length < 2 || i + 2 + length as usize > data.len() {
+                    let length = u16::from_be_bytes([data[i + 2], data[i + 3]]);
+                    let Some(mut end) = limits::block_end(i, 2, length.into(), 0, data.len())
+                        .filter(|_| length >= 2)
+                    else {
                         cleaned.extend_from_slice(&data[i..]);
                         break;
+                    };
+                    // Start of Scan - keep the image data too, then carry on,
+                    // since progressive files have segments between scans
+                    if marker == 0xDA {
+                        end = jpeg::scan_end(data, end);
                     }
-                    cleaned.extend_from_slice(&data[i..i + 2 + length as usize]);
-                    i += 2 + length as usize;
+                    cleaned.extend_from_slice(&data[i..end]);
+                    i = end;
                 }
             }
         }
@@ -162,37 +308,38 @@ impl BinaryCleaner {
         cleaned.extend_from_slice(&data[0..8]); // Keep PNG signature
 
         let mut i = 8;
-        while i < data.len() {
-            if i + 8 > data.len() {
-                break;
-            }
+        let mut budget = Budget::blocks("PNG chunk count");
+        while i + 8 <= data.len() {
+            budget.spend()?;
 
             // Read chunk length (big-endian)
             let length = u32::from_be_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
             let chunk_type = &data[i + 4..i + 8];
             let chunk_name = String::from_utf8_lossy(chunk_type);
+            limits::check("PNG chunk size", length.into(), limits::MAX_CHUNK_SIZE)?;
 
-            let total_chunk_size = 12 + length as usize; // 4 bytes length + 4 bytes type + data + 4 bytes CRC
-            if i + total_chunk_size > data.len() {
+            // 4 bytes length + 4 bytes type + data + 4 bytes CRC
+            let Some(end) = limits::block_end(i, 8, length.into(), 4, data.len()) else {
                 break;
-            }
+            };
 
             match chunk_name.as_ref() {
                 // Critical chunks - must keep
                 "IHDR" | "PLTE" | "IDAT" | "IEND" => {
-                    cleaned.extend_from_slice(&data[i..i + total_chunk_size]);
+                    cleaned.extend_from_slice(&data[i..end]);
                 }
                 // Metadata chunks - remove these
-                "tEXt" | "zTXt" | "iTXt" | "tIME" | "pHYs" | "gAMA" | "cHRM" | "sRGB" | "iCCP" => {
+                "tEXt" | "zTXt" | "iTXt" | "tIME" | "eXIf" | "pHYs" | "gAMA" | "cHRM" | "sRGB"
+                | "iCCP" | "caBX" => {
                     console_log!("Removed PNG {} chunk", chunk_name);
                 }
                 // Other ancillary chunks - keep for safety
                 _ => {
-                    cleaned.extend_from_slice(&data[i..i + total_chunk_size]);
+                    cleaned.extend_from_slice(&data[i..end]);
                 }
             }
 
-            i += total_chunk_size;
+            i = end;
         }
 
         Ok(cleaned)
@@ -213,58 +360,62 @@ impl BinaryCleaner {
         cleaned.extend_from_slice(&data[0..12]); // Keep RIFF header and WebP signature
 
         let mut i = 12;
-        let mut new_file_size = 4u32; // Start with "WEBP" in size calculation
+        let mut new_file_size = 4u64; // Start with "WEBP" in size calculation
+        let mut budget = Budget::blocks("WebP chunk count");
 
-        while i < data.len() {
-            if i + 8 > data.len() {
-                break;
-            }
+        while i + 8 <= data.len() {
+            budget.spend()?;
 
             let chunk_id = &data[i..i + 4];
             let chunk_size =
                 u32::from_le_bytes([data[i + 4], data[i + 5], data[i + 6], data[i + 7]]);
             let chunk_name = String::from_utf8_lossy(chunk_id);
+            limits::check("WebP chunk size", chunk_size.into(), limits::MAX_CHUNK_SIZE)?;
 
-            // Ensure chunk size is reasonable
-            let padded_size = if chunk_size % 2 == 1 {
-                chunk_size + 1
-            } else {
-                chunk_size
-            };
-            let total_chunk_size = 8 + padded_size as usize;
-
-            if i + total_chunk_size > data.len() {
+            // Chunks are padded to an even size
+            let padded_size = u64::from(chunk_size) + u64::from(chunk_size % 2);
+            let Some(end) = limits::block_end(i, 8, padded_size, 0, data.len()) else {
                 break;
-            }
+            };
 
             match chunk_name.as_ref() {
                 // Image data chunks - keep
                 "VP8 " | "VP8L" | "VP8X" | "ANIM" | "ANMF" => {
-                    cleaned.extend_from_slice(&data[i..i + total_chunk_size]);
-                    new_file_size += total_chunk_size as u32;
+                    cleaned.extend_from_slice(&data[i..end]);
+                    new_file_size += (end - i) as u64;
                 }
                 // Metadata chunks - remove
-                "EXIF" | "XMP " | "ICCP" => {
+                "EXIF" | "XMP " | "ICCP" | "C2PA" => {
                     console_log!("Removed WebP {} chunk", chunk_name);
                 }
                 // Unknown chunks - keep for safety
                 _ => {
-                    cleaned.extend_from_slice(&data[i..i + total_chunk_size]);
-                    new_file_size += total_chunk_size as u32;
+                    cleaned.extend_from_slice(&data[i..end]);
+                    new_file_size += (end - i) as u64;
                 }
             }
 
-            i += total_chunk_size;
+            i = end;
         }
 
         // Update RIFF file size
+        let new_file_size = u32::try_from(new_file_size)
+            .map_err(|_| LimitExceeded::new("RIFF file size", u32::MAX.into()))?;
         let size_bytes = new_file_size.to_le_bytes();
         cleaned[4..8].copy_from_slice(&size_bytes);
 
+        // Clear the VP8X ICC, EXIF and XMP flags for the removed chunks,
+        // leaving the alpha and animation flags alone
+        if cleaned.len() > 20 && &cleaned[12..16] == b"VP8X" {
+            cleaned[20] &= !(VP8X_ICC_FLAG | VP8X_EXIF_FLAG | VP8X_XMP_FLAG);
+        }
+
         Ok(cleaned)
     }
 
-    /// Clean GIF metadata by removing extension blocks
+    /// Clean GIF metadata by removing comment and application extensions
+    /// throughout the stream. Graphic control extensions and the looping
+    /// extension (`NETSCAPE2.0`) are kept, since animations depend on them.
     fn clean_gif_metadata(data: &[u8]) -> Result<Vec<u8>, String> {
         if data.len() < 6 {
             return Err("Invalid GIF file: too short".to_string());
@@ -275,254 +426,196 @@ impl BinaryCleaner {
             return Err("Invalid GIF file: missing signature".to_string());
         }
 
-        let mut cleaned = Vec::new();
-        let mut i = 0;
-
-        // Copy header (6 bytes) and logical screen descriptor (7 bytes)
-        if data.len() >= 13 {
-            cleaned.extend_from_slice(&data[0..13]);
-            i = 13;
-
-            // Handle global color table if present
-            let packed_field = data[10];
-            if packed_field & 0x80 != 0 {
-                let global_color_table_size = 2 << (packed_field & 0x07);
-                let color_table_bytes = global_color_table_size * 3;
-                if i + color_table_bytes <= data.len() {
-                    cleaned.extend_from_slice(&data[i..i + color_table_bytes]);
-                    i += color_table_bytes;
-                }
-            }
+        // Header, logical screen descriptor and global colour table
+        let Some(start) = container::gif_stream_start(data) else {
+            return Ok(data.to_vec());
+        };
+        let mut cleaned = data[..start].to_vec();
+        let mut end = start;
+
+        // The walk stops at the block limit, and the unwalked rest would be
+        // copied unchecked
+        let blocks = container::gif_blocks(data);
+        if blocks.len() >= limits::MAX_BLOCKS {
+            return Err(LimitExceeded::new("GIF block count", limits::MAX_BLOCKS as u64).into());
         }
-
-        // Process data stream
-        while i < data.len() {
-            match data[i] {
-                // Extension introducer
-                0x21 => {
-                    if i + 1 >= data.len() {
-                        break;
-                    }
-                    let label = data[i + 1];
-                    match label {
-                        // Application extension (may contain metadata like XMP)
-                        0xFF => {
-                            console_log!("Removed GIF application extension");
-                            i += 2;
-                            // Skip sub-blocks
-                            while i < data.len() {
-                                let block_size = data[i] as usize;
-                                if block_size == 0 {
-                                    i += 1;
-                                    break;
-                                }
-                                i += 1 + block_size;
-                                if i >= data.len() {
-                                    break;
-                                }
-                            }
-                        }
-                        // Comment extension
-                        0xFE => {
-                            console_log!("Removed GIF comment extension");
-                            i += 2;
-                            // Skip sub-blocks
-                            while i < data.len() {
-                                let block_size = data[i] as usize;
-                                if block_size == 0 {
-                                    i += 1;
-                                    break;
-                                }
-                                i += 1 + block_size;
-                                if i >= data.len() {
-                                    break;
-                                }
-                            }
-                        }
-                        // Keep other extensions (graphics control, plain text)
-                        _ => {
-                            let start = i;
-                            i += 2;
-                            // Copy extension including sub-blocks
-                            while i < data.len() {
-                                let block_size = data[i] as usize;
-                                i += 1;
-                                if block_size == 0 {
-                                    break;
-                                }
-                                i += block_size;
-                                if i >= data.len() {
-                                    break;
-                                }
-                            }
-                            cleaned.extend_from_slice(&data[start..i]);
-                        }
+        for block in blocks {
+            end = block.offset + block.bytes.len();
+            match (block.introducer, block.label) {
+                (0x21, 0xFE) => console_log!("Removed GIF comment extension"),
+                (0x21, 0xFF) => match animation::gif_loop_extension(&block) {
+                    // Rewritten so extra sub-blocks cannot carry data along
+                    Some(count) => {
+                        cleaned.extend_from_slice(&block.bytes[..14]);
+                        cleaned.extend_from_slice(&[3, 1]);
+                        cleaned.extend_from_slice(&count.to_le_bytes());
+                        cleaned.push(0);
                     }
-                }
-                // Image separator or trailer
-                0x2C | 0x3B => {
-                    cleaned.extend_from_slice(&data[i..]);
-                    break;
-                }
-                _ => {
-                    cleaned.push(data[i]);
-                    i += 1;
-                }
+                    None => console_log!("Removed GIF application extension"),
+                },
+                _ => cleaned.extend_from_slice(block.bytes),
             }
         }
+        // Keep anything the walk could not parse rather than truncate frames
+        cleaned.extend_from_slice(&data[end..]);
 
         Ok(cleaned)
     }
 
-    /// Clean TIFF metadata using the tiff crate for proper IFD handling
-    // little_exif expects a mutable Vec reference
-    #[allow(clippy::ptr_arg)]
-    fn clean_tiff_metadata(data: &mut Vec<u8>) -> Result<Vec<u8>, String> {
-        console_log!("Starting proper TIFF metadata cleaning using tiff crate");
-
-        // Use the tiff crate to decode the image
-        let cursor = Cursor::new(&**data);
-        let mut decoder =
-            Decoder::new(cursor).map_err(|e| format!("Failed to create TIFF decoder: {}", e))?;
-
-        // Read the image data without metadata
-        let (width, height) = decoder
-            .dimensions()
-            .map_err(|e| format!("Failed to get TIFF dimensions: {}", e))?;
+    /// Clean TIFF metadata by rewriting the first image's IFD.
+    ///
+    /// Only the tags in [`TIFF_IMAGE_TAGS`] are kept and the strip or tile
+    /// data is copied unchanged, so EXIF, GPS, XMP, IPTC, ICC and any further
+    /// IFDs are dropped without decoding the pixels. Offsets, counts and
+    /// value sizes are checked against the file before anything is copied.
+    fn clean_tiff_metadata(data: &[u8]) -> Result<Vec<u8>, String> {
+        let (order, ifd_offset) = container::tiff_header(data)
+            .ok_or_else(|| "Invalid TIFF file: missing header".to_string())?;
+        let ifd = container::tiff_directory(data, order, ifd_offset)
+            .ok_or_else(|| format!("Invalid TIFF file: IFD at {} is cut off", ifd_offset))?;
+
+        let number = |tag: u16| ifd.get(tag).and_then(|e| e.first_unsigned(order));
+        if number(256).unwrap_or(0) == 0 || number(257).unwrap_or(0) == 0 {
+            return Err("Invalid TIFF file: missing image dimensions".to_string());
+        }
+        if number(259) == Some(6) {
+            return Err("Old-style JPEG compressed TIFF files are not supported".to_string());
+        }
 
-        let colortype = decoder
-            .colortype()
-            .map_err(|e| format!("Failed to get TIFF color type: {}", e))?;
+        // Strips, or tiles when there are no strips
+        let (offsets_tag, counts_tag) = match ifd.get(273) {
+            Some(_) => (273, 279),
+            None => (324, 325),
+        };
+        let values = |tag: u16| {
+            let entry = ifd.get(tag)?;
+            let values = entry.unsigned(order);
+            (!values.is_empty() && values.len() == entry.count as usize).then_some(values)
+        };
+        let (Some(offsets), Some(counts)) = (values(offsets_tag), values(counts_tag)) else {
+            return Err("Invalid TIFF file: missing or unreadable strip offsets".to_string());
+        };
+        if offsets.len() != counts.len() {
+            return Err(format!(
+                "Invalid TIFF file: {} strip offsets but {} byte counts",
+                offsets.len(),
+                counts.len()
+            ));
+        }
 
-        console_log!(
-            "TIFF image info: {}x{}, colortype: {:?}",
-            width,
-            height,
-            colortype
-        );
+        let mut budget = Budget::blocks("TIFF strip count");
+        let mut strips = Vec::with_capacity(offsets.len());
+        let mut total = 0u64;
+        for (offset, count) in offsets.iter().zip(&counts) {
+            budget.spend()?;
+            let start = *offset as usize;
+            let end =
+                limits::block_end(start, 0, u64::from(*count), 0, data.len()).ok_or_else(|| {
+                    format!(
+                        "TIFF strip at {} declares {} bytes, but the file ends first",
+                        start, count
+                    )
+                })?;
+            // Strips may not overlap, so together they fit in the file
+            total += u64::from(*count);
+            limits::check("TIFF strip data", total, data.len() as u64)?;
+            strips.push(&data[start..end]);
+        }
 
-        // Decode the image
-        let image_data = match decoder
-            .read_image()
-            .map_err(|e| format!("Failed to decode TIFF image: {}", e))?
-        {
-            DecodingResult::U8(data) => data,
-            DecodingResult::U16(data) => {
-                // Convert U16 to U8 for simplicity (this loses precision but removes metadata)
-                data.into_iter().map(|x| (x >> 8) as u8).collect()
-            }
-            DecodingResult::U32(data) => {
-                // Convert U32 to U8 for simplicity
-                data.into_iter().map(|x| (x >> 24) as u8).collect()
+        // Kept entries as (tag, type, count, value bytes), in tag order
+        let mut entries = Vec::new();
+        for entry in &ifd.entries {
+            if !TIFF_IMAGE_TAGS.contains(&entry.tag) || entry.count == 0 {
+                continue;
             }
-            DecodingResult::U64(data) => {
-                // Convert U64 to U8
-                data.into_iter().map(|x| (x >> 56) as u8).collect()
+            if entry.tag == counts_tag {
+                let value = counts.iter().flat_map(|c| tiff_u32(order, *c)).collect();
+                entries.push((entry.tag, 4, entry.count, value));
+                continue;
             }
-            DecodingResult::I8(data) => {
-                // Convert I8 to U8
-                data.into_iter().map(|x| x.max(0) as u8).collect()
+            if entry.tag == offsets_tag {
+                // Filled in once the layout is known
+                entries.push((entry.tag, 4, entry.count, vec![0; strips.len() * 4]));
+                continue;
             }
-            DecodingResult::I16(data) => {
-                // Convert I16 to U8
-                data.into_iter().map(|x| (x.max(0) >> 8) as u8).collect()
+            let size = container::tiff_type_size(entry.field_type) as u64 * entry.count as u64;
+            if size == 0 || entry.value.len() as u64 != size {
+                return Err(format!(
+                    "TIFF tag {} at {} points outside the file",
+                    entry.tag, entry.value_offset
+                ));
             }
-            DecodingResult::I32(data) => {
-                // Convert I32 to U8
-                data.into_iter().map(|x| (x.max(0) >> 24) as u8).collect()
-            }
-            DecodingResult::I64(data) => {
-                // Convert I64 to U8
-                data.into_iter().map(|x| (x.max(0) >> 56) as u8).collect()
-            }
-            DecodingResult::F16(data) => {
-                // Convert F16 to U8 (F16 is held in u16)
-                data.into_iter()
-                    .map(|x| {
-                        let f_val = f16::from_bits(x.to_bits()).to_f32();
-                        (f_val.clamp(0.0, 1.0) * 255.0) as u8
-                    })
-                    .collect()
+            entries.push((
+                entry.tag,
+                entry.field_type,
+                entry.count,
+                entry.value.to_vec(),
+            ));
+        }
+        entries.sort_by_key(|e| e.0);
+
+        // Header, IFD, out-of-line values, then the image data
+        let ifd_len = 2 + entries.len() * 12 + 4;
+        let mut value_offsets = Vec::with_capacity(entries.len());
+        let mut next = 8 + ifd_len;
+        for (_, _, _, value) in &entries {
+            value_offsets.push(next);
+            if value.len() > 4 {
+                next += value.len() + value.len() % 2;
             }
-            DecodingResult::F32(data) => {
-                // Convert F32 to U8
-                data.into_iter()
-                    .map(|x| (x.clamp(0.0, 1.0) * 255.0) as u8)
-                    .collect()
+        }
+        let mut strip_offset = next;
+        let mut new_offsets = Vec::with_capacity(strips.len() * 4);
+        for strip in &strips {
+            let offset = u32::try_from(strip_offset)
+                .map_err(|_| "Cleaned TIFF file is too large".to_string())?;
+            new_offsets.extend(tiff_u32(order, offset));
+            strip_offset += strip.len();
+        }
+        if let Some(entry) = entries.iter_mut().find(|e| e.0 == offsets_tag) {
+            entry.3 = new_offsets;
+        }
+
+        let mut cleaned = Vec::with_capacity(strip_offset);
+        cleaned.extend_from_slice(&data[..2]);
+        cleaned.extend(tiff_u16(order, 42));
+        cleaned.extend(tiff_u32(order, 8));
+        cleaned.extend(tiff_u16(order, entries.len() as u16));
+        for ((tag, field_type, count, value), offset) in entries.iter().zip(&value_offsets) {
+            cleaned.extend(tiff_u16(order, *tag));
+            cleaned.extend(tiff_u16(order, *field_type));
+            cleaned.extend(tiff_u32(order, *count));
+            if value.len() > 4 {
+                cleaned.extend(tiff_u32(order, *offset as u32));
+            } else {
+                let mut inline = value.clone();
+                inline.resize(4, 0);
+                cleaned.extend(inline);
             }
-            DecodingResult::F64(data) => {
-                // Convert F64 to U8
-                data.into_iter()
-                    .map(|x| (x.clamp(0.0, 1.0) * 255.0) as u8)
-                    .collect()
+        }
+        // No further IFDs
+        cleaned.extend(tiff_u32(order, 0));
+        for (_, _, _, value) in entries.iter().filter(|e| e.3.len() > 4) {
+            cleaned.extend_from_slice(value);
+            if value.len() % 2 == 1 {
+                cleaned.push(0);
             }
-        };
-
-        console_log!(
-            "Decoded TIFF image: {} bytes of pixel data",
-            image_data.len()
-        );
-
-        // Create a new TIFF file without metadata
-        let mut cleaned_data = Vec::new();
-        {
-            let mut cursor = Cursor::new(&mut cleaned_data);
-            let mut encoder = TiffEncoder::new(&mut cursor)
-                .map_err(|e| format!("Failed to create TIFF encoder: {}", e))?;
-
-            // Write the image without any metadata - use the most compatible format
-            match colortype {
-                tiff::ColorType::Gray(8) => {
-                    encoder
-                        .write_image::<colortype::Gray8>(width, height, &image_data)
-                        .map_err(|e| format!("Failed to write Gray8 TIFF: {}", e))?;
-                }
-                tiff::ColorType::Gray(16) => {
-                    // Convert to 8-bit for metadata-free output
-                    encoder
-                        .write_image::<colortype::Gray8>(width, height, &image_data)
-                        .map_err(|e| format!("Failed to write Gray8 TIFF from 16-bit: {}", e))?;
-                }
-                tiff::ColorType::RGB(8) => {
-                    encoder
-                        .write_image::<colortype::RGB8>(width, height, &image_data)
-                        .map_err(|e| format!("Failed to write RGB8 TIFF: {}", e))?;
-                }
-                tiff::ColorType::RGB(16) => {
-                    // Convert to 8-bit RGB
-                    encoder
-                        .write_image::<colortype::RGB8>(width, height, &image_data)
-                        .map_err(|e| format!("Failed to write RGB8 TIFF from 16-bit: {}", e))?;
-                }
-                tiff::ColorType::RGBA(8) => {
-                    encoder
-                        .write_image::<colortype::RGBA8>(width, height, &image_data)
-                        .map_err(|e| format!("Failed to write RGBA8 TIFF: {}", e))?;
-                }
-                _ => {
-                    // Fallback to RGB8 for unsupported formats
-                    console_log!("Converting unsupported TIFF color type to RGB8");
-                    encoder
-                        .write_image::<colortype::RGB8>(width, height, &image_data)
-                        .map_err(|e| format!("Failed to write fallback RGB8 TIFF: {}", e))?;
-                }
-            };
+        }
+        for strip in strips {
+            cleaned.extend_from_slice(strip);
         }
 
         console_log!(
-            "✅ Successfully cleaned TIFF metadata. Original: {} bytes, Cleaned: {} bytes",
+            "Cleaned TIFF metadata. Original: {} bytes, Cleaned: {} bytes",
             data.len(),
-            cleaned_data.len()
+            cleaned.len()
         );
-
-        Ok(cleaned_data)
+        Ok(cleaned)
     }
 
     /// Clean HEIF/HEIC metadata using libheif-rs library
     // TODO: Complete HEIF implementation - libheif-rs API needs more research
-    // little_exif expects a mutable Vec reference
-    #[allow(clippy::ptr_arg)]
-    fn clean_heif_metadata(_data: &mut Vec<u8>) -> Result<Vec<u8>, String> {
+    fn clean_heif_metadata(_data: &[u8]) -> Result<Vec<u8>, String> {
         console_log!("HEIF/HEIC metadata cleaning with libheif-rs");
 
         // The libheif-rs API is complex and requires more research for proper implementation
@@ -538,7 +631,7 @@ impl BinaryCleaner {
     /// Clean AVIF metadata (basic implementation)
     fn clean_avif_metadata(_data: &[u8]) -> Result<Vec<u8>, String> {
         // AVIF is based on HEIF, but might need specialized handling
-        // For now, return error as it's not fully supported by little_exif
+        // For now, return error as it's not implemented
         Err("AVIF metadata cleaning not fully implemented yet".to_string())
     }
 
@@ -550,10 +643,8 @@ impl BinaryCleaner {
 
     /// Clean PDF metadata by removing info dictionary and XMP
     fn clean_pdf_metadata(data: &[u8]) -> Result<Vec<u8>, String> {
-        let data_str = String::from_utf8_lossy(data);
-
-        // Basic PDF header check
-        if !data_str.starts_with("%PDF-") {
+        // Same header check as detection, which allows junk before it
+        if !container::is_pdf(data) {
             return Err("Invalid PDF file".to_string());
         }
 
@@ -0,0 +1,90 @@
+//! Readers for ISO base media file format box payloads.
+//!
+//! [`container::iso_boxes`](crate::container::iso_boxes) finds the boxes;
+//! the HEIF and video parsers read the fields inside them with these.
+
+/// Big-endian cursor over a box payload.
+pub(crate) struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(n)?)?;
+        self.pos += n;
+        Some(bytes)
+    }
+
+    pub(crate) fn skip(&mut self, n: usize) -> Option<()> {
+        self.take(n).map(|_| ())
+    }
+
+    pub(crate) fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_be_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    /// Read an unsigned integer of 0, 2, 4 or 8 bytes.
+    pub(crate) fn sized(&mut self, size: u8) -> Option<u64> {
+        Some(match size {
+            0 => 0,
+            2 => self.u16()? as u64,
+            4 => self.u32()? as u64,
+            8 => self.u64()?,
+            _ => return None,
+        })
+    }
+
+    /// Read a 32-bit value for version 0 boxes and a 64-bit one otherwise.
+    pub(crate) fn versioned(&mut self, version: u8) -> Option<u64> {
+        if version == 1 {
+            self.u64()
+        } else {
+            self.u32().map(u64::from)
+        }
+    }
+
+    /// Read a 16 or 32 bit item ID depending on the box version.
+    pub(crate) fn item_id(&mut self, wide: bool) -> Option<u32> {
+        if wide {
+            self.u32()
+        } else {
+            self.u16().map(u32::from)
+        }
+    }
+
+    /// Read a NUL-terminated UTF-8 string (or the rest of the data).
+    pub(crate) fn string(&mut self) -> String {
+        let rest = self.rest();
+        let end = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+        self.pos += (end + 1).min(rest.len());
+        String::from_utf8_lossy(&rest[..end]).into_owned()
+    }
+
+    pub(crate) fn rest(&self) -> &'a [u8] {
+        self.data.get(self.pos..).unwrap_or_default()
+    }
+}
+
+/// Split a FullBox payload into version, flags and the remaining payload.
+pub(crate) fn full_box(data: &[u8]) -> Option<(u8, u32, &[u8])> {
+    let header = data.get(..4)?;
+    let flags = u32::from_be_bytes([0, header[1], header[2], header[3]]);
+    Some((header[0], flags, &data[4..]))
+}
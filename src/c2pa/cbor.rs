@@ -0,0 +1,300 @@
+//! CBOR (RFC 8949) decoding and encoding for claims, assertions and COSE
+//! structures.
+
+use super::MAX_DEPTH;
+use crate::limits::{self, Budget, LimitExceeded};
+
+/// A decoded CBOR data item.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Cbor {
+    Integer(i128),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Map(Vec<(Cbor, Cbor)>),
+    Tag(u64, Box<Cbor>),
+    Bool(bool),
+    Null,
+    Undefined,
+    Float(f64),
+}
+
+impl Cbor {
+    /// The item with any tags removed.
+    pub fn untagged(&self) -> &Cbor {
+        let mut item = self;
+        while let Cbor::Tag(_, inner) = item {
+            item = inner;
+        }
+        item
+    }
+
+    /// Value of a text key in a map.
+    pub fn get(&self, key: &str) -> Option<&Cbor> {
+        self.find(|k| matches!(k, Cbor::Text(t) if t == key))
+    }
+
+    /// Value of an integer key in a map, as COSE headers use.
+    pub fn get_label(&self, key: i128) -> Option<&Cbor> {
+        self.find(|k| *k == Cbor::Integer(key))
+    }
+
+    fn find(&self, matches: impl Fn(&Cbor) -> bool) -> Option<&Cbor> {
+        match self.untagged() {
+            Cbor::Map(entries) => entries.iter().find(|(k, _)| matches(k)).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self.untagged() {
+            Cbor::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self.untagged() {
+            Cbor::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Cbor]> {
+        match self.untagged() {
+            Cbor::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i128> {
+        match self.untagged() {
+            Cbor::Integer(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Convert a JSON value, for assertions stored as JSON.
+    pub fn from_json(value: &serde_json::Value) -> Cbor {
+        match value {
+            serde_json::Value::Null => Cbor::Null,
+            serde_json::Value::Bool(b) => Cbor::Bool(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(n) => Cbor::Integer(n.into()),
+                None => Cbor::Float(n.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(s) => Cbor::Text(s.clone()),
+            serde_json::Value::Array(items) => {
+                Cbor::Array(items.iter().map(Cbor::from_json).collect())
+            }
+            serde_json::Value::Object(map) => Cbor::Map(
+                map.iter()
+                    .map(|(k, v)| (Cbor::Text(k.clone()), Cbor::from_json(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+struct CborReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    budget: Budget,
+}
+
+impl<'a> CborReader<'a> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .data
+            .get(self.pos)
+            .ok_or_else(|| "CBOR data ends early".to_string())?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn take(&mut self, length: u64) -> Result<&'a [u8], String> {
+        let end = limits::block_end(self.pos, 0, length, 0, self.data.len())
+            .ok_or_else(|| format!("CBOR item at {} runs past the end", self.pos))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    /// The argument of an initial byte, or `None` for an indefinite length.
+    fn argument(&mut self, info: u8) -> Result<Option<u64>, String> {
+        let bytes = match info {
+            0..=23 => return Ok(Some(info.into())),
+            24 => self.take(1)?,
+            25 => self.take(2)?,
+            26 => self.take(4)?,
+            27 => self.take(8)?,
+            31 => return Ok(None),
+            _ => return Err(format!("Invalid CBOR length encoding {}", info)),
+        };
+        Ok(Some(bytes.iter().fold(0, |n, b| n << 8 | u64::from(*b))))
+    }
+
+    /// Consume a break byte if one is next.
+    fn at_break(&mut self) -> bool {
+        let found = self.data.get(self.pos) == Some(&0xFF);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn item(&mut self, depth: usize) -> Result<Cbor, String> {
+        self.budget.spend()?;
+        if depth > MAX_DEPTH {
+            return Err(LimitExceeded::new("CBOR nesting depth", MAX_DEPTH as u64).into());
+        }
+        let initial = self.byte()?;
+        let (major, info) = (initial >> 5, initial & 0x1F);
+        if major == 7 {
+            return self.simple(info);
+        }
+        match (major, self.argument(info)?) {
+            (0, Some(n)) => Ok(Cbor::Integer(n.into())),
+            (1, Some(n)) => Ok(Cbor::Integer(-1 - i128::from(n))),
+            (2, Some(n)) => Ok(Cbor::Bytes(self.take(n)?.to_vec())),
+            (3, Some(n)) => text(self.take(n)?.to_vec()),
+            (2 | 3, None) => {
+                let mut joined = Vec::new();
+                while !self.at_break() {
+                    self.budget.spend()?;
+                    let chunk = self.byte()?;
+                    match (chunk >> 5 == major, self.argument(chunk & 0x1F)?) {
+                        (true, Some(n)) => joined.extend_from_slice(self.take(n)?),
+                        _ => return Err("Invalid chunk in indefinite CBOR string".to_string()),
+                    }
+                }
+                match major {
+                    2 => Ok(Cbor::Bytes(joined)),
+                    _ => text(joined),
+                }
+            }
+            (4, count) => {
+                let mut items = Vec::new();
+                while count.map_or(!self.at_break(), |n| (items.len() as u64) < n) {
+                    items.push(self.item(depth + 1)?);
+                }
+                Ok(Cbor::Array(items))
+            }
+            (5, count) => {
+                let mut entries = Vec::new();
+                while count.map_or(!self.at_break(), |n| (entries.len() as u64) < n) {
+                    let key = self.item(depth + 1)?;
+                    entries.push((key, self.item(depth + 1)?));
+                }
+                Ok(Cbor::Map(entries))
+            }
+            (6, Some(tag)) => Ok(Cbor::Tag(tag, Box::new(self.item(depth + 1)?))),
+            _ => Err(format!("Invalid CBOR item 0x{:02X}", initial)),
+        }
+    }
+
+    fn simple(&mut self, info: u8) -> Result<Cbor, String> {
+        Ok(match info {
+            20 => Cbor::Bool(false),
+            21 => Cbor::Bool(true),
+            22 => Cbor::Null,
+            24 => {
+                self.byte()?;
+                Cbor::Undefined
+            }
+            25 => {
+                let bytes = self.take(2)?;
+                Cbor::Float(half::f16::from_be_bytes([bytes[0], bytes[1]]).to_f64())
+            }
+            26 => {
+                let bytes = self.take(4)?;
+                Cbor::Float(f32::from_be_bytes(bytes.try_into().unwrap_or_default()).into())
+            }
+            27 => Cbor::Float(f64::from_be_bytes(
+                self.take(8)?.try_into().unwrap_or_default(),
+            )),
+            31 => return Err("Unexpected CBOR break".to_string()),
+            _ => Cbor::Undefined,
+        })
+    }
+}
+
+fn text(bytes: Vec<u8>) -> Result<Cbor, String> {
+    String::from_utf8(bytes)
+        .map(Cbor::Text)
+        .map_err(|_| "CBOR text is not valid UTF-8".to_string())
+}
+
+/// Decode the CBOR item at the start of `data`.
+pub fn decode_cbor(data: &[u8]) -> Result<Cbor, String> {
+    CborReader {
+        data,
+        pos: 0,
+        budget: Budget::blocks("CBOR item count"),
+    }
+    .item(0)
+}
+
+/// Encode a CBOR item with definite lengths and the shortest arguments.
+pub fn encode_cbor(item: &Cbor) -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_into(item, &mut out);
+    out
+}
+
+fn encode_head(major: u8, n: u64, out: &mut Vec<u8>) {
+    let major = major << 5;
+    match n {
+        0..=23 => out.push(major | n as u8),
+        24..=0xFF => out.extend_from_slice(&[major | 24, n as u8]),
+        0x100..=0xFFFF => {
+            out.push(major | 25);
+            out.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        0x1_0000..=0xFFFF_FFFF => {
+            out.push(major | 26);
+            out.extend_from_slice(&(n as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+}
+
+fn encode_into(item: &Cbor, out: &mut Vec<u8>) {
+    match item {
+        Cbor::Integer(n) if *n >= 0 => encode_head(0, *n as u64, out),
+        Cbor::Integer(n) => encode_head(1, (-1 - *n) as u64, out),
+        Cbor::Bytes(bytes) => {
+            encode_head(2, bytes.len() as u64, out);
+            out.extend_from_slice(bytes);
+        }
+        Cbor::Text(text) => {
+            encode_head(3, text.len() as u64, out);
+            out.extend_from_slice(text.as_bytes());
+        }
+        Cbor::Array(items) => {
+            encode_head(4, items.len() as u64, out);
+            items.iter().for_each(|item| encode_into(item, out));
+        }
+        Cbor::Map(entries) => {
+            encode_head(5, entries.len() as u64, out);
+            for (key, value) in entries {
+                encode_into(key, out);
+                encode_into(value, out);
+            }
+        }
+        Cbor::Tag(tag, inner) => {
+            encode_head(6, *tag, out);
+            encode_into(inner, out);
+        }
+        Cbor::Bool(b) => out.push(if *b { 0xF5 } else { 0xF4 }),
+        Cbor::Null => out.push(0xF6),
+        Cbor::Undefined => out.push(0xF7),
+        Cbor::Float(f) => {
+            out.push(0xFB);
+            out.extend_from_slice(&f.to_be_bytes());
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! COSE_Sign1 claim signatures and the Web Crypto form of their
+//! algorithms.
+
+use super::cbor::{Cbor, decode_cbor, encode_cbor};
+use super::x509::{
+    Certificate, KeyType, der, der_children, ecdsa_raw_signature, hash_oid, oid, parse_certificate,
+};
+
+/// A signature algorithm in the terms Web Crypto uses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignatureScheme {
+    /// ECDSA on `curve` with the signature as `r || s`.
+    Ecdsa {
+        curve: &'static str,
+        hash: &'static str,
+    },
+    RsaPss {
+        hash: &'static str,
+        salt_length: u32,
+    },
+    RsaPkcs1 {
+        hash: &'static str,
+    },
+    Ed25519,
+}
+
+/// Name of a COSE algorithm identifier, e.g. `"ES256"` for -7.
+pub fn cose_algorithm_name(alg: i128) -> String {
+    match alg {
+        -7 => "ES256".to_string(),
+        -35 => "ES384".to_string(),
+        -36 => "ES512".to_string(),
+        -37 => "PS256".to_string(),
+        -38 => "PS384".to_string(),
+        -39 => "PS512".to_string(),
+        -8 => "Ed25519".to_string(),
+        other => format!("COSE algorithm {}", other),
+    }
+}
+
+/// The scheme of a COSE algorithm used with a key of the given type.
+pub(super) fn cose_scheme(alg: i128, key: &KeyType) -> Result<SignatureScheme, String> {
+    let scheme = match (alg, key) {
+        (-7, KeyType::Ec(curve)) => SignatureScheme::Ecdsa {
+            curve,
+            hash: "SHA-256",
+        },
+        (-35, KeyType::Ec(curve)) => SignatureScheme::Ecdsa {
+            curve,
+            hash: "SHA-384",
+        },
+        (-36, KeyType::Ec(curve)) => SignatureScheme::Ecdsa {
+            curve,
+            hash: "SHA-512",
+        },
+        (-37, KeyType::Rsa) => SignatureScheme::RsaPss {
+            hash: "SHA-256",
+            salt_length: 32,
+        },
+        (-38, KeyType::Rsa) => SignatureScheme::RsaPss {
+            hash: "SHA-384",
+            salt_length: 48,
+        },
+        (-39, KeyType::Rsa) => SignatureScheme::RsaPss {
+            hash: "SHA-512",
+            salt_length: 64,
+        },
+        (-8, KeyType::Ed25519) => SignatureScheme::Ed25519,
+        (-7 | -35 | -36 | -37 | -38 | -39 | -8, key) => {
+            return Err(format!(
+                "{} does not match the signing certificate's {:?} key",
+                cose_algorithm_name(alg),
+                key
+            ));
+        }
+        _ => return Err(format!("{} is not supported", cose_algorithm_name(alg))),
+    };
+    Ok(scheme)
+}
+
+/// The scheme and Web Crypto form of the signature on `certificate`, made
+/// with a key of type `issuer_key`.
+pub(super) fn certificate_signature(
+    certificate: &Certificate,
+    issuer_key: &KeyType,
+) -> Result<(SignatureScheme, Vec<u8>), String> {
+    let algorithm = certificate.signature_algorithm.as_str();
+    let unsupported = || format!("Signature algorithm {} is not supported", algorithm);
+    let ecdsa_hash = match algorithm {
+        "1.2.840.10045.4.3.2" => Some("SHA-256"),
+        "1.2.840.10045.4.3.3" => Some("SHA-384"),
+        "1.2.840.10045.4.3.4" => Some("SHA-512"),
+        _ => None,
+    };
+    if let Some(hash) = ecdsa_hash {
+        let KeyType::Ec(curve) = issuer_key else {
+            return Err(unsupported());
+        };
+        let signature = ecdsa_raw_signature(&certificate.signature, curve)
+            .ok_or_else(|| "ECDSA signature is malformed".to_string())?;
+        return Ok((SignatureScheme::Ecdsa { curve, hash }, signature));
+    }
+    let scheme = match (algorithm, issuer_key) {
+        ("1.2.840.113549.1.1.11", KeyType::Rsa) => SignatureScheme::RsaPkcs1 { hash: "SHA-256" },
+        ("1.2.840.113549.1.1.12", KeyType::Rsa) => SignatureScheme::RsaPkcs1 { hash: "SHA-384" },
+        ("1.2.840.113549.1.1.13", KeyType::Rsa) => SignatureScheme::RsaPkcs1 { hash: "SHA-512" },
+        ("1.2.840.113549.1.1.10", KeyType::Rsa) => {
+            // RSASSA-PSS-params: [0] hash algorithm, [1] mask generation,
+            // [2] salt length
+            let params = der(&certificate.signature_parameters)
+                .map(|(p, _)| der_children(p.content))
+                .unwrap_or_default();
+            let field = |tag: u8| {
+                params
+                    .iter()
+                    .find(|p| p.tag == tag)
+                    .and_then(|p| der(p.content))
+            };
+            let hash = field(0xA0)
+                .and_then(|(algorithm, _)| {
+                    der_children(algorithm.content)
+                        .first()
+                        .map(|o| oid(o.content))
+                })
+                .and_then(|oid| hash_oid(&oid))
+                .ok_or_else(unsupported)?;
+            let salt_length = field(0xA2)
+                .map(|(n, _)| n.content.iter().fold(0, |n, b| n << 8 | u32::from(*b)))
+                .unwrap_or(20);
+            SignatureScheme::RsaPss { hash, salt_length }
+        }
+        ("1.3.101.112", KeyType::Ed25519) => SignatureScheme::Ed25519,
+        _ => return Err(unsupported()),
+    };
+    Ok((scheme, certificate.signature.clone()))
+}
+
+/// The parts of a COSE_Sign1 claim signature.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoseSignature {
+    /// Encoded protected header, which the signature covers.
+    pub protected: Vec<u8>,
+    /// COSE algorithm identifier.
+    pub algorithm: Option<i128>,
+    /// Certificate chain, signer first.
+    pub chain: Vec<Certificate>,
+    pub signature: Vec<u8>,
+}
+
+/// Parse a COSE_Sign1 structure (CBOR tag 18, which may be left out).
+pub fn parse_cose_sign1(data: &[u8]) -> Result<CoseSignature, String> {
+    let item = decode_cbor(data)?;
+    let parts = item
+        .as_array()
+        .filter(|parts| parts.len() == 4)
+        .ok_or_else(|| "Claim signature is not a COSE_Sign1 structure".to_string())?;
+    let protected = parts[0]
+        .as_bytes()
+        .ok_or_else(|| "COSE protected header is not a byte string".to_string())?
+        .to_vec();
+    let protected_header = match protected.is_empty() {
+        true => Cbor::Map(Vec::new()),
+        false => decode_cbor(&protected)?,
+    };
+    let header = |label: i128| {
+        protected_header
+            .get_label(label)
+            .or_else(|| parts[1].get_label(label))
+    };
+    // x5chain (label 33) is one certificate or an array of them
+    let chain = match header(33).map(Cbor::untagged) {
+        Some(Cbor::Bytes(der)) => vec![der.as_slice()],
+        Some(Cbor::Array(items)) => items.iter().filter_map(Cbor::as_bytes).collect(),
+        _ => Vec::new(),
+    };
+    Ok(CoseSignature {
+        algorithm: header(1).and_then(Cbor::as_integer),
+        chain: chain.into_iter().filter_map(parse_certificate).collect(),
+        signature: parts[3]
+            .as_bytes()
+            .ok_or_else(|| "COSE signature is not a byte string".to_string())?
+            .to_vec(),
+        protected,
+    })
+}
+
+/// The COSE `Sig_structure` a detached COSE_Sign1 signature covers.
+pub fn sig_structure(protected: &[u8], payload: &[u8]) -> Vec<u8> {
+    encode_cbor(&Cbor::Array(vec![
+        Cbor::Text("Signature1".to_string()),
+        Cbor::Bytes(protected.to_vec()),
+        Cbor::Bytes(Vec::new()),
+        Cbor::Bytes(payload.to_vec()),
+    ]))
+}
@@ -0,0 +1,271 @@
+//! JUMBF boxes and finding the manifest store in a file.
+//!
+//! JUMBF (ISO/IEC 19566-5) nests superboxes, each starting with a `jumd`
+//! description box that gives its content type and label.
+
+use super::MAX_DEPTH;
+use crate::container::{self, Chunk};
+use crate::jxl;
+use crate::limits::{self, Budget, LimitExceeded};
+
+/// User type of the ISO base media `uuid` box holding a manifest store.
+pub const C2PA_UUID: [u8; 16] = [
+    0xD8, 0xFE, 0xC3, 0xD6, 0x1B, 0x0E, 0x48, 0x3C, 0x92, 0x97, 0x58, 0x28, 0x87, 0xEC, 0x48, 0x81,
+];
+
+/// JUMBF content type of a manifest store superbox.
+pub const MANIFEST_STORE_TYPE: [u8; 16] = jumbf_type(*b"c2pa");
+
+/// A JUMBF content type: four characters followed by the ISO suffix.
+pub const fn jumbf_type(id: [u8; 4]) -> [u8; 16] {
+    [
+        id[0], id[1], id[2], id[3], 0x00, 0x11, 0x00, 0x10, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38,
+        0x9B, 0x71,
+    ]
+}
+
+/// A JUMBF superbox: a description box followed by content boxes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Superbox<'a> {
+    /// Content type from the description box.
+    pub content_type: [u8; 16],
+    pub label: Option<String>,
+    /// Description and content boxes without the superbox header, which is
+    /// what assertion hashes are taken over.
+    pub payload: &'a [u8],
+    pub boxes: Vec<JumbfBox<'a>>,
+}
+
+/// A box inside a JUMBF superbox.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JumbfBox<'a> {
+    Super(Superbox<'a>),
+    Content { box_type: [u8; 4], data: &'a [u8] },
+}
+
+impl<'a> Superbox<'a> {
+    /// Nested superboxes.
+    pub fn children(&self) -> impl Iterator<Item = &Superbox<'a>> {
+        self.boxes.iter().filter_map(|b| match b {
+            JumbfBox::Super(child) => Some(child),
+            JumbfBox::Content { .. } => None,
+        })
+    }
+
+    /// The nested superbox with the given label.
+    pub fn child(&self, label: &str) -> Option<&Superbox<'a>> {
+        self.children().find(|c| c.label.as_deref() == Some(label))
+    }
+
+    /// Payload of the first content box of the given type.
+    pub fn content(&self, box_type: &[u8; 4]) -> Option<&'a [u8]> {
+        self.boxes.iter().find_map(|b| match b {
+            JumbfBox::Content { box_type: t, data } if t == box_type => Some(*data),
+            _ => None,
+        })
+    }
+}
+
+/// Split `data` into `(type, payload)` boxes, failing on a box that runs
+/// past the end.
+fn jumbf_boxes(data: &[u8]) -> Result<Vec<Chunk<'_>>, String> {
+    let mut boxes = Vec::new();
+    let mut i = 0;
+    let mut budget = Budget::blocks("JUMBF box count");
+    while i < data.len() {
+        budget.spend()?;
+        let header = data
+            .get(i..i + 8)
+            .ok_or_else(|| format!("JUMBF box header at {} is cut off", i))?;
+        let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+        let box_type = [header[4], header[5], header[6], header[7]];
+        let (header_len, length) = match size {
+            0 => (8, (data.len() - i - 8) as u64),
+            1 => {
+                let large = data
+                    .get(i + 8..i + 16)
+                    .ok_or_else(|| format!("JUMBF box header at {} is cut off", i))?;
+                let size = u64::from_be_bytes(large.try_into().unwrap_or_default());
+                (16, size.saturating_sub(16))
+            }
+            size if size < 8 => {
+                return Err(format!(
+                    "JUMBF box at {} has an invalid size of {}",
+                    i, size
+                ));
+            }
+            size => (8, u64::from(size) - 8),
+        };
+        let end = limits::block_end(i, header_len, length, 0, data.len()).ok_or_else(|| {
+            format!(
+                "{} box at {} declares {} bytes, but the data ends first",
+                String::from_utf8_lossy(&box_type),
+                i,
+                length
+            )
+        })?;
+        boxes.push(Chunk {
+            id: box_type,
+            offset: i,
+            data: &data[i + header_len..end],
+        });
+        i = end;
+    }
+    Ok(boxes)
+}
+
+/// Parse the payload of a `jumb` superbox.
+pub fn parse_superbox(payload: &[u8]) -> Result<Superbox<'_>, String> {
+    superbox(payload, 0)
+}
+
+fn superbox(payload: &[u8], depth: usize) -> Result<Superbox<'_>, String> {
+    if depth > MAX_DEPTH {
+        return Err(LimitExceeded::new("JUMBF nesting depth", MAX_DEPTH as u64).into());
+    }
+    let mut parts = jumbf_boxes(payload)?.into_iter();
+    let description = match parts.next() {
+        Some(Chunk { id, data, .. }) if &id == b"jumd" && data.len() >= 17 => data,
+        _ => return Err("JUMBF superbox does not start with a description box".to_string()),
+    };
+    let mut content_type = [0; 16];
+    content_type.copy_from_slice(&description[..16]);
+    // Toggles: bit 1 says a null-terminated label follows
+    let label = (description[16] & 0x02 != 0).then(|| {
+        let rest = &description[17..];
+        let end = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+        String::from_utf8_lossy(&rest[..end]).into_owned()
+    });
+    let boxes = parts
+        .map(|chunk| match &chunk.id {
+            b"jumb" => superbox(chunk.data, depth + 1).map(JumbfBox::Super),
+            _ => Ok(JumbfBox::Content {
+                box_type: chunk.id,
+                data: chunk.data,
+            }),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Superbox {
+        content_type,
+        label,
+        payload,
+        boxes,
+    })
+}
+
+/// Payload of the `jumb` box at the start of `data`.
+fn jumb_payload(data: &[u8]) -> Option<&[u8]> {
+    jumbf_boxes(data)
+        .ok()?
+        .first()
+        .filter(|b| &b.id == b"jumb")
+        .map(|b| b.data)
+}
+
+/// Whether a superbox payload is a C2PA manifest store. Only the leading
+/// description box is looked at, so a damaged store is still found and
+/// its errors reported.
+fn is_manifest_store(payload: &[u8]) -> bool {
+    payload.get(4..8) == Some(b"jumd")
+        && payload
+            .get(8..)
+            .is_some_and(|rest| rest.starts_with(&MANIFEST_STORE_TYPE))
+}
+
+/// Reassemble the JUMBF boxes carried in JPEG APP11 segments.
+///
+/// Each segment starts with `JP`, a box instance number and a sequence
+/// number. The first segment of an instance holds the start of the box;
+/// later ones repeat its 8-byte header before the continuation.
+fn jpeg_jumbf(data: &[u8]) -> Vec<Vec<u8>> {
+    // (instance, sequence, part) for every segment
+    let mut parts = Vec::new();
+    for segment in container::jpeg_segments(data) {
+        if segment.marker != 0xEB || !segment.data.starts_with(b"JP") || segment.data.len() < 16 {
+            continue;
+        }
+        let instance = u16::from_be_bytes([segment.data[2], segment.data[3]]);
+        let sequence = u32::from_be_bytes([
+            segment.data[4],
+            segment.data[5],
+            segment.data[6],
+            segment.data[7],
+        ]);
+        parts.push((instance, sequence, &segment.data[8..]));
+    }
+    parts.sort_by_key(|(instance, sequence, _)| (*instance, *sequence));
+    let mut boxes: Vec<Vec<u8>> = Vec::new();
+    let mut previous = None;
+    for (instance, _, part) in parts {
+        if previous == Some(instance)
+            && let Some(joined) = boxes.last_mut()
+        {
+            joined.extend_from_slice(&part[8..]);
+        } else {
+            boxes.push(part.to_vec());
+        }
+        previous = Some(instance);
+    }
+    boxes
+}
+
+/// The manifest store payload in an ISO base media `uuid` box: version and
+/// flags, a purpose string, an offset to an optional Merkle tree box, then
+/// the JUMBF.
+fn isobmff_jumbf(data: &[u8]) -> Option<&[u8]> {
+    container::iso_boxes(data).into_iter().find_map(|b| {
+        if &b.id != b"uuid" || !b.data.starts_with(&C2PA_UUID) {
+            return None;
+        }
+        let rest = b.data.get(20..)?;
+        let purpose = rest.iter().position(|&c| c == 0)?;
+        (&rest[..purpose] == b"manifest").then_some(())?;
+        rest.get(purpose + 1 + 8..)
+    })
+}
+
+/// Find the manifest store in a file. Returns where it was found and the
+/// payload of its superbox.
+pub fn find_manifest_store(data: &[u8]) -> Option<(&'static str, Vec<u8>)> {
+    let chunks = |chunks: Vec<Chunk<'_>>, id: &[u8; 4]| -> Vec<Vec<u8>> {
+        chunks
+            .into_iter()
+            .filter(|c| &c.id == id)
+            .map(|c| c.data.to_vec())
+            .collect()
+    };
+    // Whole `jumb` boxes, except JPEG XL which gives their payloads
+    let (location, boxes) = if container::is_jpeg(data) {
+        ("JPEG APP11", jpeg_jumbf(data))
+    } else if container::is_png(data) {
+        (
+            "PNG caBX chunk",
+            chunks(container::png_chunks(data), b"caBX"),
+        )
+    } else if container::is_webp(data) {
+        (
+            "WebP C2PA chunk",
+            chunks(container::riff_chunks(data), b"C2PA"),
+        )
+    } else if container::is_isobmff(data) {
+        (
+            "ISOBMFF uuid box",
+            isobmff_jumbf(data)
+                .map(<[u8]>::to_vec)
+                .into_iter()
+                .collect(),
+        )
+    } else if container::is_jxl(data) {
+        return jxl::find_jumbf(data)
+            .into_iter()
+            .find(|payload| is_manifest_store(payload))
+            .map(|payload| ("JPEG XL jumb box", payload));
+    } else {
+        return None;
+    };
+    boxes
+        .iter()
+        .filter_map(|b| jumb_payload(b))
+        .find(|payload| is_manifest_store(payload))
+        .map(|payload| (location, payload.to_vec()))
+}
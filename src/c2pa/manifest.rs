@@ -0,0 +1,284 @@
+//! Manifests: reading them from the store and describing them for display.
+
+use super::ContentCredentials;
+use super::cbor::{Cbor, decode_cbor};
+use super::cose::{cose_algorithm_name, parse_cose_sign1};
+use super::jumbf::{MANIFEST_STORE_TYPE, Superbox, find_manifest_store, parse_superbox};
+use serde::Serialize;
+
+/// An assertion from a manifest's assertion store.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssertionData {
+    pub label: String,
+    /// Superbox payload, which the claim's hashed URI covers.
+    pub payload: Vec<u8>,
+    /// Decoded CBOR or JSON content, if it has either.
+    pub content: Option<Cbor>,
+}
+
+/// A manifest with the raw parts needed to verify it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManifestData {
+    pub label: String,
+    pub claim: Cbor,
+    /// The claim's CBOR encoding, which the signature covers.
+    pub claim_bytes: Vec<u8>,
+    /// The COSE_Sign1 signature box content.
+    pub signature: Option<Vec<u8>>,
+    pub assertions: Vec<AssertionData>,
+}
+
+impl ManifestData {
+    pub fn assertion(&self, label: &str) -> Option<&AssertionData> {
+        self.assertions.iter().find(|a| a.label == label)
+    }
+
+    /// Hashed URIs of the claim's assertions: `(url, algorithm, hash)`.
+    /// Version 2 claims split them into created and gathered assertions.
+    pub fn assertion_references(&self) -> Vec<(String, Option<String>, Vec<u8>)> {
+        ["assertions", "created_assertions", "gathered_assertions"]
+            .iter()
+            .filter_map(|key| self.claim.get(key)?.as_array())
+            .flatten()
+            .filter_map(|reference| {
+                Some((
+                    reference.get("url")?.as_text()?.to_string(),
+                    reference
+                        .get("alg")
+                        .and_then(Cbor::as_text)
+                        .map(str::to_string),
+                    reference.get("hash")?.as_bytes()?.to_vec(),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// A parsed manifest store.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManifestStore {
+    /// Where in the file the store was found, e.g. `"JPEG APP11"`.
+    pub location: String,
+    /// Manifests in store order; the last one is active.
+    pub manifests: Vec<ManifestData>,
+}
+
+/// Parse the payload of a manifest store superbox.
+pub fn parse_manifest_store(location: &str, payload: &[u8]) -> Result<ManifestStore, String> {
+    let store = parse_superbox(payload)?;
+    if store.content_type != MANIFEST_STORE_TYPE {
+        return Err("JUMBF superbox is not a C2PA manifest store".to_string());
+    }
+    let mut manifests = Vec::new();
+    for manifest in store.children() {
+        let label = manifest.label.clone().unwrap_or_default();
+        let claim_box = manifest
+            .child("c2pa.claim.v2")
+            .or_else(|| manifest.child("c2pa.claim"))
+            .and_then(|claim| claim.content(b"cbor"))
+            .ok_or_else(|| format!("Manifest {} has no claim", label))?;
+        let claim = decode_cbor(claim_box).map_err(|e| format!("Claim in {}: {}", label, e))?;
+        let assertions = manifest
+            .child("c2pa.assertions")
+            .into_iter()
+            .flat_map(Superbox::children)
+            .map(|assertion| AssertionData {
+                label: assertion.label.clone().unwrap_or_default(),
+                payload: assertion.payload.to_vec(),
+                content: assertion
+                    .content(b"cbor")
+                    .and_then(|data| decode_cbor(data).ok())
+                    .or_else(|| {
+                        let json = assertion.content(b"json")?;
+                        serde_json::from_slice(json)
+                            .ok()
+                            .map(|v| Cbor::from_json(&v))
+                    }),
+            })
+            .collect();
+        manifests.push(ManifestData {
+            label,
+            claim,
+            claim_bytes: claim_box.to_vec(),
+            signature: manifest
+                .child("c2pa.signature")
+                .and_then(|signature| signature.content(b"cbor"))
+                .map(<[u8]>::to_vec),
+            assertions,
+        });
+    }
+    if manifests.is_empty() {
+        return Err("C2PA manifest store holds no manifests".to_string());
+    }
+    Ok(ManifestStore {
+        location: location.to_string(),
+        manifests,
+    })
+}
+
+/// Find and parse the manifest store of a file. `Ok(None)` means the file
+/// has none.
+pub fn read_manifest_store(data: &[u8]) -> Result<Option<ManifestStore>, String> {
+    find_manifest_store(data)
+        .map(|(location, payload)| parse_manifest_store(location, &payload))
+        .transpose()
+}
+
+/// An edit or other step recorded in an actions assertion.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Action {
+    /// Action name such as `c2pa.created` or `c2pa.color_adjustments`.
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub software_agent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+    /// Last part of the IPTC digital source type, e.g.
+    /// `trainedAlgorithmicMedia` for AI generated content.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digital_source_type: Option<String>,
+}
+
+/// An ingredient the asset was made from.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Ingredient {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// `parentOf`, `componentOf` or `inputTo`.
+    pub relationship: String,
+}
+
+/// What a manifest says about the asset, for display.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Manifest {
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claim_generator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance_id: Option<String>,
+    /// Subject of the signing certificate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature_algorithm: Option<String>,
+    pub actions: Vec<Action>,
+    pub ingredients: Vec<Ingredient>,
+    /// Labels of the assertions in the assertion store.
+    pub assertions: Vec<String>,
+}
+
+/// Text of a field that may be plain text or a map with a `name`.
+fn name_or_text(value: &Cbor) -> Option<String> {
+    value
+        .as_text()
+        .or_else(|| value.get("name")?.as_text())
+        .map(str::to_string)
+}
+
+/// Base label of an assertion, without a `__N` instance suffix.
+pub(super) fn base_label(label: &str) -> &str {
+    label.split_once("__").map_or(label, |(base, _)| base)
+}
+
+/// Describe a manifest for display.
+pub fn summarize_manifest(manifest: &ManifestData) -> Manifest {
+    let claim = &manifest.claim;
+    let text = |key: &str| claim.get(key).and_then(Cbor::as_text).map(str::to_string);
+    let generator_info = claim.get("claim_generator_info").and_then(|info| {
+        let info = info.as_array().and_then(<[Cbor]>::first).unwrap_or(info);
+        let name = info.get("name")?.as_text()?;
+        Some(match info.get("version").and_then(Cbor::as_text) {
+            Some(version) => format!("{} {}", name, version),
+            None => name.to_string(),
+        })
+    });
+    let signature = manifest
+        .signature
+        .as_deref()
+        .and_then(|s| parse_cose_sign1(s).ok());
+    let leaf = signature.as_ref().and_then(|s| s.chain.first());
+
+    let mut actions = Vec::new();
+    let mut ingredients = Vec::new();
+    for assertion in &manifest.assertions {
+        let Some(content) = &assertion.content else {
+            continue;
+        };
+        let label = base_label(&assertion.label);
+        if label.starts_with("c2pa.actions") {
+            for action in content
+                .get("actions")
+                .and_then(Cbor::as_array)
+                .unwrap_or_default()
+            {
+                let Some(name) = action.get("action").and_then(Cbor::as_text) else {
+                    continue;
+                };
+                actions.push(Action {
+                    action: name.to_string(),
+                    software_agent: action.get("softwareAgent").and_then(name_or_text),
+                    when: action
+                        .get("when")
+                        .and_then(Cbor::as_text)
+                        .map(str::to_string),
+                    digital_source_type: action
+                        .get("digitalSourceType")
+                        .and_then(Cbor::as_text)
+                        .map(|t| t.rsplit('/').next().unwrap_or(t).to_string()),
+                });
+            }
+        } else if label.starts_with("c2pa.ingredient") {
+            let field = |keys: [&str; 2]| {
+                keys.iter()
+                    .find_map(|key| content.get(key)?.as_text())
+                    .map(str::to_string)
+            };
+            ingredients.push(Ingredient {
+                title: field(["dc:title", "title"]),
+                format: field(["dc:format", "format"]),
+                relationship: field(["relationship", "relationship"])
+                    .unwrap_or_else(|| "componentOf".to_string()),
+            });
+        }
+    }
+
+    Manifest {
+        label: manifest.label.clone(),
+        claim_generator: text("claim_generator").or(generator_info),
+        title: text("dc:title").or_else(|| text("title")),
+        format: text("dc:format"),
+        instance_id: text("instanceID"),
+        signer: leaf.map(|c| c.subject.clone()),
+        issuer: leaf.map(|c| c.issuer.clone()),
+        signature_algorithm: signature
+            .as_ref()
+            .and_then(|s| s.algorithm)
+            .map(cose_algorithm_name),
+        actions,
+        ingredients,
+        assertions: manifest
+            .assertions
+            .iter()
+            .map(|a| a.label.clone())
+            .collect(),
+    }
+}
+
+impl ManifestStore {
+    /// Describe the store for display, without verification results.
+    pub fn summary(&self) -> ContentCredentials {
+        ContentCredentials {
+            location: self.location.clone(),
+            manifests: self.manifests.iter().map(summarize_manifest).collect(),
+            validation: Vec::new(),
+        }
+    }
+}
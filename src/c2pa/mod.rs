@@ -0,0 +1,258 @@
+//! C2PA Content Credentials: finding, reading and checking manifest stores.
+//!
+//! A C2PA manifest store is a JUMBF superbox labelled `c2pa`. JPEG files
+//! carry it in APP11 segments, split over several when it is large; PNG in a
+//! `caBX` chunk, WebP in a `C2PA` chunk, ISO base media files (HEIF, AVIF,
+//! MP4) in a `uuid` box and JPEG XL in `jumb` boxes. Each manifest in the
+//! store holds an assertion store, a CBOR claim listing a hash of every
+//! assertion, and a COSE_Sign1 signature over the claim whose certificate
+//! chain names the signer. The last manifest is the active one; earlier ones
+//! describe ingredients.
+//!
+//! Reading is pure. Checking needs SHA-2 digests and signature verification,
+//! which come from the browser's Web Crypto API, so this module only works
+//! out the [`Check`]s to run (what to hash or verify, and the expected
+//! result) and turns their outcomes into [`ValidationStatus`] entries;
+//! [`c2pa_wasm`](crate::c2pa_wasm) runs them. Signers are trusted through a
+//! list of PEM certificates the user imports, so nothing is fetched.
+//!
+//! The formats are read in submodules: [`jumbf`] finds and splits the store,
+//! [`cbor`] decodes claims and assertions, [`x509`] and [`cose`] read the
+//! signature, [`manifest`] describes the manifests and [`verify`] plans the
+//! checks.
+
+pub mod cbor;
+pub mod cose;
+pub mod jumbf;
+pub mod manifest;
+pub mod verify;
+pub mod x509;
+
+pub use cbor::{Cbor, decode_cbor, encode_cbor};
+pub use cose::{
+    CoseSignature, SignatureScheme, cose_algorithm_name, parse_cose_sign1, sig_structure,
+};
+pub use jumbf::{
+    C2PA_UUID, JumbfBox, MANIFEST_STORE_TYPE, Superbox, find_manifest_store, jumbf_type,
+    parse_superbox,
+};
+pub use manifest::{
+    Action, AssertionData, Ingredient, Manifest, ManifestData, ManifestStore, parse_manifest_store,
+    read_manifest_store, summarize_manifest,
+};
+pub use verify::{Check, Operation, VerificationPlan, assess, plan_verification};
+pub use x509::{Certificate, KeyType, parse_certificate, parse_pem_certificates};
+
+use crate::diagnostics::Diagnostic;
+use crate::types::{MetadataValue, RawValue};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Deepest nesting of JUMBF superboxes or CBOR items that is read.
+const MAX_DEPTH: usize = 32;
+
+/// Result of one verification step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Outcome {
+    Passed,
+    Failed,
+    /// The step could not be carried out, e.g. for an unsupported algorithm.
+    Skipped,
+}
+
+/// One verification result, named by its C2PA status code.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ValidationStatus {
+    /// C2PA status code, e.g. `claimSignature.validated`.
+    pub code: String,
+    pub outcome: Outcome,
+    /// Label of the manifest the result belongs to.
+    pub manifest: String,
+    pub explanation: String,
+}
+
+impl ValidationStatus {
+    fn new(code: &str, outcome: Outcome, manifest: &str, explanation: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            outcome,
+            manifest: manifest.to_string(),
+            explanation: explanation.into(),
+        }
+    }
+
+    /// Whether the status is about who signed rather than what was signed.
+    pub fn is_trust(&self) -> bool {
+        self.code.starts_with("signingCredential")
+    }
+}
+
+/// Overall verdict on a file's Content Credentials.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum CredentialState {
+    /// Verification has not run.
+    Unverified,
+    /// A signature or hash does not match: the file or manifest was changed
+    /// after signing.
+    Invalid,
+    /// Nothing failed, but some steps could not be carried out.
+    Incomplete,
+    /// Signatures and hashes match, but the signer does not chain to an
+    /// imported trust anchor.
+    Untrusted,
+    /// Signatures and hashes match and the signer is trusted.
+    Trusted,
+}
+
+impl CredentialState {
+    pub fn label(self) -> &'static str {
+        match self {
+            CredentialState::Unverified => "Not verified",
+            CredentialState::Invalid => "Invalid",
+            CredentialState::Incomplete => "Partly verified",
+            CredentialState::Untrusted => "Valid, signer not trusted",
+            CredentialState::Trusted => "Valid and trusted",
+        }
+    }
+}
+
+/// Content Credentials found in a file and the result of checking them.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ContentCredentials {
+    /// Where in the file the manifest store was found.
+    pub location: String,
+    /// Manifests in store order; the last one is active.
+    pub manifests: Vec<Manifest>,
+    /// Verification results; empty until verified.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub validation: Vec<ValidationStatus>,
+}
+
+impl ContentCredentials {
+    /// The manifest describing the file itself.
+    pub fn active(&self) -> Option<&Manifest> {
+        self.manifests.last()
+    }
+
+    /// Overall verdict. Integrity counts for every manifest, trust only for
+    /// the active one.
+    pub fn state(&self) -> CredentialState {
+        if self.validation.is_empty() {
+            return CredentialState::Unverified;
+        }
+        let active = self.active().map(|m| m.label.as_str());
+        let relevant = || {
+            self.validation
+                .iter()
+                .filter(|s| !s.is_trust() || Some(s.manifest.as_str()) == active)
+        };
+        let any = |outcome: Outcome, trust: bool| {
+            relevant().any(|s| s.outcome == outcome && s.is_trust() == trust)
+        };
+        if any(Outcome::Failed, false) {
+            CredentialState::Invalid
+        } else if any(Outcome::Skipped, false) {
+            CredentialState::Incomplete
+        } else if any(Outcome::Failed, true) || any(Outcome::Skipped, true) {
+            CredentialState::Untrusted
+        } else {
+            CredentialState::Trusted
+        }
+    }
+}
+
+/// The active manifest as `exif_data` entries keyed `C2PA:<Field>`.
+pub fn extract_c2pa_data(data: &[u8]) -> HashMap<String, MetadataValue> {
+    extract_c2pa_data_with_diagnostics(data, &mut Vec::new())
+}
+
+/// The active manifest as [`extract_c2pa_data`] gives it, reporting a
+/// manifest store that cannot be read.
+pub fn extract_c2pa_data_with_diagnostics(
+    data: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, MetadataValue> {
+    let mut map = HashMap::new();
+    let store = match read_manifest_store(data) {
+        Ok(Some(store)) => store,
+        Ok(None) => return map,
+        Err(e) => {
+            diagnostics.push(Diagnostic::error("C2PA", None, e));
+            return map;
+        }
+    };
+    let credentials = store.summary();
+    let Some(active) = credentials.active() else {
+        return map;
+    };
+    let mut insert = |name: &str, value: Option<String>| {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            map.insert(format!("C2PA:{}", name), MetadataValue::text(value));
+        }
+    };
+    insert("ClaimGenerator", active.claim_generator.clone());
+    insert("Title", active.title.clone());
+    insert("Signer", active.signer.clone());
+    insert("SignatureAlgorithm", active.signature_algorithm.clone());
+    insert(
+        "Actions",
+        Some(
+            active
+                .actions
+                .iter()
+                .map(|a| a.action.trim_start_matches("c2pa.").to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    );
+    insert(
+        "DigitalSourceType",
+        active
+            .actions
+            .iter()
+            .find_map(|a| a.digital_source_type.clone()),
+    );
+    insert(
+        "Ingredients",
+        Some(
+            active
+                .ingredients
+                .iter()
+                .map(|i| i.title.clone().unwrap_or_else(|| "untitled".to_string()))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    );
+    insert("Assertions", Some(active.assertions.join(", ")));
+    let count = credentials.manifests.len() as i64;
+    map.insert(
+        "C2PA:ManifestCount".to_string(),
+        MetadataValue::new(RawValue::Integer(vec![count]), count.to_string()),
+    );
+    map
+}
+
+/// MIME types whose manifest store the binary cleaner removes. The cleaner
+/// cannot clean HEIF, AVIF or JPEG XL files yet, and returns PDFs unchanged.
+pub const CLEANED_STORE_TYPES: &[&str] = &["image/jpeg", "image/png", "image/webp"];
+
+/// Warning shown before cleaning a file of type `mime_type` that has Content
+/// Credentials, or `None` where the cleaner leaves the store in place.
+///
+/// The cleaner drops the manifest store along with the other metadata, and
+/// any change to the file bytes would break its hash binding anyway, so the
+/// cleaned copy cannot keep verifiable credentials.
+pub fn cleaning_warning(credentials: &ContentCredentials, mime_type: &str) -> Option<String> {
+    if !CLEANED_STORE_TYPES.contains(&mime_type) {
+        return None;
+    }
+    let signer = credentials
+        .active()
+        .and_then(|m| m.signer.as_deref())
+        .map(|signer| format!(" signed by {}", signer))
+        .unwrap_or_default();
+    Some(format!(
+        "This file has C2PA Content Credentials{}. Cleaning removes them and any change to the file invalidates them, so the cleaned copy will no longer prove where it came from or how it was edited.",
+        signer
+    ))
+}
@@ -0,0 +1,489 @@
+//! Verification plans: the hashes and signatures to check for a manifest
+//! store, and the status codes their results map to.
+
+use super::cbor::Cbor;
+use super::cose::{
+    SignatureScheme, certificate_signature, cose_scheme, parse_cose_sign1, sig_structure,
+};
+use super::manifest::{ManifestData, ManifestStore, base_label};
+use super::x509::Certificate;
+use super::{Outcome, ValidationStatus};
+use crate::limits;
+
+/// A cryptographic operation for the caller to carry out.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    /// Hash `data` with `algorithm` (a Web Crypto name such as `"SHA-256"`)
+    /// and compare the digest with `expected`.
+    Digest {
+        algorithm: &'static str,
+        data: Vec<u8>,
+        expected: Vec<u8>,
+    },
+    /// Verify `signature` over `message` with the DER `SubjectPublicKeyInfo`
+    /// in `spki`.
+    Verify {
+        scheme: SignatureScheme,
+        spki: Vec<u8>,
+        message: Vec<u8>,
+        signature: Vec<u8>,
+    },
+}
+
+/// A verification step and the status codes its result maps to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Check {
+    pub manifest: String,
+    pub passed: &'static str,
+    pub failed: &'static str,
+    /// What the step checks, e.g. `"c2pa.actions"`.
+    pub subject: String,
+    pub operation: Operation,
+}
+
+/// Web Crypto name of a C2PA hash algorithm name.
+fn hash_algorithm(name: &str) -> Option<&'static str> {
+    match name {
+        "sha256" => Some("SHA-256"),
+        "sha384" => Some("SHA-384"),
+        "sha512" => Some("SHA-512"),
+        _ => None,
+    }
+}
+
+/// Everything needed to verify a manifest store: the steps to run and the
+/// results already known without cryptography.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VerificationPlan {
+    pub checks: Vec<Check>,
+    pub statuses: Vec<ValidationStatus>,
+}
+
+impl VerificationPlan {
+    fn status(
+        &mut self,
+        code: &str,
+        outcome: Outcome,
+        manifest: &str,
+        explanation: impl Into<String>,
+    ) {
+        self.statuses
+            .push(ValidationStatus::new(code, outcome, manifest, explanation));
+    }
+
+    fn check(
+        &mut self,
+        manifest: &str,
+        codes: (&'static str, &'static str),
+        subject: impl Into<String>,
+        operation: Operation,
+    ) {
+        self.checks.push(Check {
+            manifest: manifest.to_string(),
+            passed: codes.0,
+            failed: codes.1,
+            subject: subject.into(),
+            operation,
+        });
+    }
+
+    /// Hash checks for the assertions the claim lists.
+    fn assertions(&mut self, store: &ManifestStore, manifest: &ManifestData) {
+        let default_alg = manifest
+            .claim
+            .get("alg")
+            .and_then(Cbor::as_text)
+            .unwrap_or("sha256");
+        for (url, alg, hash) in manifest.assertion_references() {
+            // self#jumbf=c2pa.assertions/<label>, or an absolute
+            // self#jumbf=/c2pa/<manifest>/c2pa.assertions/<label>
+            let path = url
+                .split_once("jumbf=")
+                .map_or(url.as_str(), |(_, path)| path);
+            let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+            let owner = match segments.as_slice() {
+                ["c2pa", owner, ..] => store.manifests.iter().find(|m| m.label == *owner),
+                _ => Some(manifest),
+            };
+            let label = segments.last().copied().unwrap_or_default();
+            let Some(assertion) = owner.and_then(|m| m.assertion(label)) else {
+                self.status(
+                    "assertion.missing",
+                    Outcome::Failed,
+                    &manifest.label,
+                    format!("The claim lists {}, which is not in the manifest", label),
+                );
+                continue;
+            };
+            let alg = alg.as_deref().unwrap_or(default_alg);
+            let Some(algorithm) = hash_algorithm(alg) else {
+                self.status(
+                    "algorithm.unsupported",
+                    Outcome::Skipped,
+                    &manifest.label,
+                    format!("Hash algorithm {} of {} is not supported", alg, label),
+                );
+                continue;
+            };
+            self.check(
+                &manifest.label,
+                ("assertion.hashedURI.match", "assertion.hashedURI.mismatch"),
+                label,
+                Operation::Digest {
+                    algorithm,
+                    data: assertion.payload.clone(),
+                    expected: hash,
+                },
+            );
+        }
+    }
+
+    /// Hash check of the file bytes outside the manifest, from the active
+    /// manifest's hard binding.
+    fn hard_binding(&mut self, manifest: &ManifestData, file: &[u8]) {
+        let binding = manifest
+            .assertions
+            .iter()
+            .find(|a| base_label(&a.label).starts_with("c2pa.hash."));
+        let Some(binding) = binding else {
+            self.status(
+                "claim.hardBindings.missing",
+                Outcome::Failed,
+                &manifest.label,
+                "The manifest has no hash binding it to the file",
+            );
+            return;
+        };
+        let label = binding.label.clone();
+        let content = match (base_label(&label), &binding.content) {
+            ("c2pa.hash.data", Some(content)) => content,
+            ("c2pa.hash.data", None) => {
+                self.status(
+                    "assertion.dataHash.mismatch",
+                    Outcome::Failed,
+                    &manifest.label,
+                    "The data hash assertion cannot be read",
+                );
+                return;
+            }
+            (other, _) => {
+                // BMFF and box hashes are not verified, so the binding to
+                // the file stays unproven and the credentials incomplete
+                self.status(
+                    "algorithm.unsupported",
+                    Outcome::Skipped,
+                    &manifest.label,
+                    format!(
+                        "{} hash bindings are not checked, so the manifest is not shown to belong to this file",
+                        other
+                    ),
+                );
+                return;
+            }
+        };
+        let default_alg = manifest
+            .claim
+            .get("alg")
+            .and_then(Cbor::as_text)
+            .unwrap_or("sha256");
+        let alg = content
+            .get("alg")
+            .and_then(Cbor::as_text)
+            .unwrap_or(default_alg);
+        let Some(algorithm) = hash_algorithm(alg) else {
+            self.status(
+                "algorithm.unsupported",
+                Outcome::Skipped,
+                &manifest.label,
+                format!("Hash algorithm {} of the data hash is not supported", alg),
+            );
+            return;
+        };
+        let mut exclusions: Vec<(u64, u64)> = content
+            .get("exclusions")
+            .and_then(Cbor::as_array)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|e| {
+                let start = u64::try_from(e.get("start")?.as_integer()?).ok()?;
+                let length = u64::try_from(e.get("length")?.as_integer()?).ok()?;
+                Some((start, length))
+            })
+            .collect();
+        exclusions.sort_unstable();
+        let mut data = Vec::with_capacity(file.len());
+        let mut position = 0;
+        for (start, length) in exclusions {
+            let range = usize::try_from(start)
+                .ok()
+                .filter(|start| *start >= position)
+                .and_then(|start| Some(start..limits::block_end(start, 0, length, 0, file.len())?));
+            let Some(range) = range else {
+                self.status(
+                    "assertion.dataHash.mismatch",
+                    Outcome::Failed,
+                    &manifest.label,
+                    format!(
+                        "Excluded range of {} bytes at {} overlaps another or lies outside the file",
+                        length, start
+                    ),
+                );
+                return;
+            };
+            data.extend_from_slice(&file[position..range.start]);
+            position = range.end;
+        }
+        data.extend_from_slice(&file[position..]);
+        let Some(expected) = content.get("hash").and_then(Cbor::as_bytes) else {
+            self.status(
+                "assertion.dataHash.mismatch",
+                Outcome::Failed,
+                &manifest.label,
+                "The data hash assertion has no hash",
+            );
+            return;
+        };
+        self.check(
+            &manifest.label,
+            ("assertion.dataHash.match", "assertion.dataHash.mismatch"),
+            label,
+            Operation::Digest {
+                algorithm,
+                data,
+                expected: expected.to_vec(),
+            },
+        );
+    }
+
+    /// Claim signature, certificate chain and trust anchor checks.
+    fn signature(&mut self, manifest: &ManifestData, anchors: &[Certificate], now: i64) {
+        let label = manifest.label.as_str();
+        let Some(signature) = &manifest.signature else {
+            self.status(
+                "claimSignature.missing",
+                Outcome::Failed,
+                label,
+                "The manifest has no claim signature",
+            );
+            return;
+        };
+        let cose = match parse_cose_sign1(signature) {
+            Ok(cose) => cose,
+            Err(e) => {
+                self.status("claimSignature.mismatch", Outcome::Failed, label, e);
+                return;
+            }
+        };
+        let Some(leaf) = cose.chain.first() else {
+            self.status(
+                "signingCredential.invalid",
+                Outcome::Failed,
+                label,
+                "The signature carries no signing certificate",
+            );
+            self.status(
+                "claimSignature.mismatch",
+                Outcome::Skipped,
+                label,
+                "The signature cannot be checked without the signing certificate",
+            );
+            return;
+        };
+        match cose
+            .algorithm
+            .ok_or_else(|| "The signature does not name its algorithm".to_string())
+            .and_then(|alg| cose_scheme(alg, &leaf.key_type))
+        {
+            Ok(scheme) => self.check(
+                label,
+                ("claimSignature.validated", "claimSignature.mismatch"),
+                format!("claim signed by {}", leaf.subject),
+                Operation::Verify {
+                    scheme,
+                    spki: leaf.spki.clone(),
+                    message: sig_structure(&cose.protected, &manifest.claim_bytes),
+                    signature: cose.signature.clone(),
+                },
+            ),
+            Err(e) => self.status("algorithm.unsupported", Outcome::Skipped, label, e),
+        }
+
+        if let Some(problem) = leaf.claim_signing_problem() {
+            self.status(
+                "signingCredential.invalid",
+                Outcome::Failed,
+                label,
+                format!("{} may not sign claims: {}", leaf.subject, problem),
+            );
+        }
+        for certificate in &cose.chain {
+            self.validity(label, certificate, now);
+        }
+        for pair in cose.chain.windows(2) {
+            self.certificate_link(
+                label,
+                &pair[0],
+                &pair[1],
+                (
+                    "signingCredential.chain.validated",
+                    "signingCredential.invalid",
+                ),
+            );
+        }
+
+        let last = cose.chain.last().unwrap_or(leaf);
+        if let Some(anchor) = anchors
+            .iter()
+            .find(|a| cose.chain.iter().any(|c| c.der == a.der))
+        {
+            self.status(
+                "signingCredential.trusted",
+                Outcome::Passed,
+                label,
+                format!("{} is an imported trust anchor", anchor.subject),
+            );
+        } else if let Some(anchor) = anchors.iter().find(|a| a.subject_der == last.issuer_der) {
+            self.validity(label, anchor, now);
+            self.certificate_link(
+                label,
+                last,
+                anchor,
+                ("signingCredential.trusted", "signingCredential.untrusted"),
+            );
+        } else {
+            self.status(
+                "signingCredential.untrusted",
+                Outcome::Failed,
+                label,
+                match anchors.len() {
+                    0 => "No trust anchors are imported".to_string(),
+                    n => format!(
+                        "{} is not issued by any of the {} imported trust anchors",
+                        last.subject, n
+                    ),
+                },
+            );
+        }
+    }
+
+    /// Report a certificate used outside its validity period.
+    fn validity(&mut self, manifest: &str, certificate: &Certificate, now: i64) {
+        if certificate.not_before.is_some_and(|t| now < t)
+            || certificate.not_after.is_some_and(|t| now > t)
+        {
+            self.status(
+                "signingCredential.expired",
+                Outcome::Failed,
+                manifest,
+                format!(
+                    "The certificate of {} is outside its validity period",
+                    certificate.subject
+                ),
+            );
+        }
+    }
+
+    /// Check that `issuer` is a CA named as the issuer of `certificate`, and
+    /// signed it.
+    fn certificate_link(
+        &mut self,
+        manifest: &str,
+        certificate: &Certificate,
+        issuer: &Certificate,
+        codes: (&'static str, &'static str),
+    ) {
+        if certificate.issuer_der != issuer.subject_der {
+            self.status(
+                codes.1,
+                Outcome::Failed,
+                manifest,
+                format!(
+                    "{} names {} as its issuer, not {}",
+                    certificate.subject, certificate.issuer, issuer.subject
+                ),
+            );
+            return;
+        }
+        if !issuer.can_issue() {
+            self.status(
+                codes.1,
+                Outcome::Failed,
+                manifest,
+                format!(
+                    "{} is not a CA certificate, so it cannot have issued {}",
+                    issuer.subject, certificate.subject
+                ),
+            );
+            return;
+        }
+        match certificate_signature(certificate, &issuer.key_type) {
+            Ok((scheme, signature)) => self.check(
+                manifest,
+                codes,
+                format!("{} issued by {}", certificate.subject, issuer.subject),
+                Operation::Verify {
+                    scheme,
+                    spki: issuer.spki.clone(),
+                    message: certificate.tbs.clone(),
+                    signature,
+                },
+            ),
+            Err(e) => self.status(codes.1, Outcome::Skipped, manifest, e),
+        }
+    }
+}
+
+/// Work out how to verify a manifest store against the file it came from.
+///
+/// Every manifest's claim signature, certificate chain and assertion hashes
+/// are checked; only the active manifest's hard binding is, since older
+/// manifests describe earlier versions of the file. `now` (seconds since
+/// 1970) is compared with the validity of every certificate in the chain.
+pub fn plan_verification(
+    store: &ManifestStore,
+    file: &[u8],
+    anchors: &[Certificate],
+    now: i64,
+) -> VerificationPlan {
+    let mut plan = VerificationPlan::default();
+    for (index, manifest) in store.manifests.iter().enumerate() {
+        plan.signature(manifest, anchors, now);
+        plan.assertions(store, manifest);
+        if index + 1 == store.manifests.len() {
+            plan.hard_binding(manifest, file);
+        }
+    }
+    plan
+}
+
+/// Combine a plan with the results of its checks: `Some(true)` for a match,
+/// `Some(false)` for a mismatch and `None` for a check that could not run.
+pub fn assess(plan: &VerificationPlan, results: &[Option<bool>]) -> Vec<ValidationStatus> {
+    let mut statuses: Vec<ValidationStatus> = plan
+        .checks
+        .iter()
+        .zip(results.iter().copied().chain(std::iter::repeat(None)))
+        .map(|(check, result)| match result {
+            Some(true) => ValidationStatus::new(
+                check.passed,
+                Outcome::Passed,
+                &check.manifest,
+                format!("{} matches", check.subject),
+            ),
+            Some(false) => ValidationStatus::new(
+                check.failed,
+                Outcome::Failed,
+                &check.manifest,
+                format!("{} does not match", check.subject),
+            ),
+            None => ValidationStatus::new(
+                "algorithm.unsupported",
+                Outcome::Skipped,
+                &check.manifest,
+                format!("{} could not be checked in this browser", check.subject),
+            ),
+        })
+        .collect();
+    statuses.extend(plan.statuses.iter().cloned());
+    statuses
+}
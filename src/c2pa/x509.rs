@@ -0,0 +1,401 @@
+//! The DER and X.509 subset needed to read signing certificates.
+
+use crate::limits;
+use crate::timestamp::days_from_civil;
+use base64::Engine as _;
+use serde::Serialize;
+
+/// A DER element.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Der<'a> {
+    pub(super) tag: u8,
+    pub(super) content: &'a [u8],
+    /// The whole encoding, header included.
+    pub(super) raw: &'a [u8],
+}
+
+/// Read the DER element at the start of `data`.
+pub(super) fn der(data: &[u8]) -> Option<(Der<'_>, &[u8])> {
+    let tag = *data.first()?;
+    let first = *data.get(1)?;
+    let (length, header) = match first {
+        0..=0x7F => (usize::from(first), 2),
+        0x81..=0x84 => {
+            let count = usize::from(first & 0x7F);
+            let bytes = data.get(2..2 + count)?;
+            (
+                bytes.iter().fold(0, |n, b| n << 8 | usize::from(*b)),
+                2 + count,
+            )
+        }
+        _ => return None,
+    };
+    let end = header
+        .checked_add(length)
+        .filter(|end| *end <= data.len())?;
+    Some((
+        Der {
+            tag,
+            content: &data[header..end],
+            raw: &data[..end],
+        },
+        &data[end..],
+    ))
+}
+
+/// The elements inside a constructed DER element.
+pub(super) fn der_children(mut content: &[u8]) -> Vec<Der<'_>> {
+    let mut children = Vec::new();
+    while children.len() < limits::MAX_BLOCKS
+        && let Some((element, rest)) = der(content)
+    {
+        children.push(element);
+        content = rest;
+    }
+    children
+}
+
+/// Dotted form of an object identifier.
+pub(super) fn oid(content: &[u8]) -> String {
+    let mut parts = Vec::new();
+    let mut value: u64 = 0;
+    for byte in content {
+        value = value.saturating_mul(128) | u64::from(byte & 0x7F);
+        if byte & 0x80 == 0 {
+            if parts.is_empty() {
+                let first = (value / 40).min(2);
+                parts.push(first);
+                parts.push(value - first * 40);
+            } else {
+                parts.push(value);
+            }
+            value = 0;
+        }
+    }
+    parts
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Readable form of a distinguished name, e.g. `CN=Signer, O=Example`.
+fn name(content: &[u8]) -> String {
+    let mut parts = Vec::new();
+    for set in der_children(content) {
+        for attribute in der_children(set.content) {
+            let fields = der_children(attribute.content);
+            let [kind, value, ..] = fields.as_slice() else {
+                continue;
+            };
+            let key = match oid(kind.content).as_str() {
+                "2.5.4.3" => "CN".to_string(),
+                "2.5.4.6" => "C".to_string(),
+                "2.5.4.7" => "L".to_string(),
+                "2.5.4.8" => "ST".to_string(),
+                "2.5.4.10" => "O".to_string(),
+                "2.5.4.11" => "OU".to_string(),
+                other => other.to_string(),
+            };
+            let text = match value.tag {
+                // BMPString
+                0x1E => String::from_utf16_lossy(
+                    &value
+                        .content
+                        .chunks_exact(2)
+                        .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                        .collect::<Vec<_>>(),
+                ),
+                _ => String::from_utf8_lossy(value.content).into_owned(),
+            };
+            parts.push(format!("{}={}", key, text));
+        }
+    }
+    parts.join(", ")
+}
+
+/// Seconds since 1970 of a UTCTime or GeneralizedTime.
+fn der_time(element: &Der) -> Option<i64> {
+    let text = std::str::from_utf8(element.content).ok()?;
+    let (year, rest) = match element.tag {
+        0x17 => {
+            let year: i32 = text.get(..2)?.parse().ok()?;
+            (
+                if year < 50 { 2000 + year } else { 1900 + year },
+                &text[2..],
+            )
+        }
+        0x18 => (text.get(..4)?.parse().ok()?, &text[4..]),
+        _ => return None,
+    };
+    let field = |at: usize| rest.get(at..at + 2)?.parse::<u32>().ok();
+    let (month, day) = (field(0)?, field(2)?);
+    let seconds = field(4)? * 3600 + field(6)? * 60 + field(8)?;
+    Some(days_from_civil(year, month, day) * 86_400 + i64::from(seconds))
+}
+
+/// Type of a certificate's public key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum KeyType {
+    /// An elliptic curve key on the named Web Crypto curve, e.g. `"P-256"`.
+    Ec(&'static str),
+    Rsa,
+    Ed25519,
+    /// A key this module cannot use, by algorithm OID.
+    Other(String),
+}
+
+/// The parts of an X.509 certificate needed to check a chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Certificate {
+    pub der: Vec<u8>,
+    /// The signed `TBSCertificate` encoding.
+    pub tbs: Vec<u8>,
+    pub subject: String,
+    pub issuer: String,
+    /// Encoded names, compared to link a certificate to its issuer.
+    pub subject_der: Vec<u8>,
+    pub issuer_der: Vec<u8>,
+    /// Validity period in seconds since 1970.
+    pub not_before: Option<i64>,
+    pub not_after: Option<i64>,
+    /// DER `SubjectPublicKeyInfo`, as Web Crypto imports it.
+    pub spki: Vec<u8>,
+    pub key_type: KeyType,
+    /// OID and encoded parameters of the issuer's signature algorithm.
+    pub signature_algorithm: String,
+    pub signature_parameters: Vec<u8>,
+    pub signature: Vec<u8>,
+    /// `cA` flag of the basic constraints extension; `false` without one.
+    pub is_ca: bool,
+    /// Key usage bits, bit 0 being `digitalSignature`, if the extension is
+    /// present.
+    pub key_usage: Option<u16>,
+    /// Extended key usage OIDs; empty without the extension.
+    pub extended_key_usage: Vec<String>,
+}
+
+/// `digitalSignature` and `keyCertSign` bits of the key usage extension.
+pub const KEY_USAGE_DIGITAL_SIGNATURE: u16 = 1 << 0;
+pub const KEY_USAGE_KEY_CERT_SIGN: u16 = 1 << 5;
+
+/// Extended key usages that allow signing C2PA claims: email protection,
+/// document signing, C2PA claim signing and Microsoft's C2PA signing.
+pub const CLAIM_SIGNING_USAGES: &[&str] = &[
+    "1.3.6.1.5.5.7.3.4",
+    "1.3.6.1.5.5.7.3.36",
+    "1.3.6.1.4.1.62558.2.1",
+    "1.3.6.1.4.1.311.76.59.1.9",
+];
+
+/// The `anyExtendedKeyUsage` OID, which a claim signer may not carry.
+const ANY_EXTENDED_KEY_USAGE: &str = "2.5.29.37.0";
+
+impl Certificate {
+    /// Whether the certificate may issue others: a CA under its basic
+    /// constraints whose key usage, if given, includes `keyCertSign`.
+    pub fn can_issue(&self) -> bool {
+        self.is_ca
+            && self
+                .key_usage
+                .is_none_or(|usage| usage & KEY_USAGE_KEY_CERT_SIGN != 0)
+    }
+
+    /// Why the certificate may not sign C2PA claims under the C2PA
+    /// certificate profile, or `None` if it may.
+    pub fn claim_signing_problem(&self) -> Option<&'static str> {
+        if self.is_ca {
+            return Some("it is a CA certificate");
+        }
+        match self.key_usage {
+            None => return Some("it has no key usage extension"),
+            Some(usage) if usage & KEY_USAGE_DIGITAL_SIGNATURE == 0 => {
+                return Some("its key usage does not include digitalSignature");
+            }
+            Some(usage) if usage & KEY_USAGE_KEY_CERT_SIGN != 0 => {
+                return Some("its key usage includes keyCertSign");
+            }
+            Some(_) => {}
+        }
+        let usages = &self.extended_key_usage;
+        if usages.is_empty() {
+            Some("it has no extended key usage extension")
+        } else if usages.iter().any(|u| u == ANY_EXTENDED_KEY_USAGE) {
+            Some("its extended key usage includes anyExtendedKeyUsage")
+        } else if !usages
+            .iter()
+            .any(|u| CLAIM_SIGNING_USAGES.contains(&u.as_str()))
+        {
+            Some("its extended key usage does not allow signing claims")
+        } else {
+            None
+        }
+    }
+}
+
+/// Basic constraints `cA` flag, key usage bits and extended key usages from
+/// the explicit `[3]` extensions field of a `TBSCertificate`.
+fn extensions(field: Option<&Der>) -> (bool, Option<u16>, Vec<String>) {
+    let mut parsed = (false, None, Vec::new());
+    let Some((list, _)) = field.and_then(|f| der(f.content)) else {
+        return parsed;
+    };
+    for extension in der_children(list.content) {
+        let parts = der_children(extension.content);
+        // extnID, optional critical flag, then the value as an OCTET STRING
+        let (Some(id), Some(value)) = (parts.first(), parts.last()) else {
+            continue;
+        };
+        let Some((value, _)) = der(value.content) else {
+            continue;
+        };
+        match oid(id.content).as_str() {
+            "2.5.29.19" => {
+                parsed.0 = der_children(value.content)
+                    .first()
+                    .is_some_and(|flag| flag.tag == 0x01 && flag.content.first() != Some(&0));
+            }
+            "2.5.29.15" => {
+                // BIT STRING: unused-bits byte, then bit 0 as the top bit
+                let bits = value.content.get(1..).unwrap_or_default();
+                parsed.1 = Some((0..16).fold(0, |usage, bit| {
+                    let set = bits
+                        .get(bit / 8)
+                        .is_some_and(|b| b & (0x80 >> (bit % 8)) != 0);
+                    usage | (u16::from(set) << bit)
+                }));
+            }
+            "2.5.29.37" => {
+                parsed.2 = der_children(value.content)
+                    .iter()
+                    .map(|usage| oid(usage.content))
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+    parsed
+}
+
+/// Parse a DER-encoded X.509 certificate.
+pub fn parse_certificate(data: &[u8]) -> Option<Certificate> {
+    let (certificate, _) = der(data)?;
+    let parts = der_children(certificate.content);
+    let [tbs, algorithm, signature, ..] = parts.as_slice() else {
+        return None;
+    };
+    let mut fields = der_children(tbs.content);
+    // Explicit [0] version
+    if fields.first()?.tag == 0xA0 {
+        fields.remove(0);
+    }
+    let [
+        _serial,
+        _algorithm,
+        issuer,
+        validity,
+        subject,
+        spki,
+        rest @ ..,
+    ] = fields.as_slice()
+    else {
+        return None;
+    };
+    let (is_ca, key_usage, extended_key_usage) =
+        extensions(rest.iter().find(|field| field.tag == 0xA3));
+    let times = der_children(validity.content);
+    let algorithm_parts = der_children(algorithm.content);
+    let spki_algorithm = der_children(der_children(spki.content).first()?.content);
+    let key_oid = oid(spki_algorithm.first()?.content);
+    let key_type = match key_oid.as_str() {
+        "1.2.840.10045.2.1" => match spki_algorithm.get(1).map(|p| oid(p.content)).as_deref() {
+            Some("1.2.840.10045.3.1.7") => KeyType::Ec("P-256"),
+            Some("1.3.132.0.34") => KeyType::Ec("P-384"),
+            Some("1.3.132.0.35") => KeyType::Ec("P-521"),
+            _ => KeyType::Other(key_oid),
+        },
+        "1.2.840.113549.1.1.1" | "1.2.840.113549.1.1.10" => KeyType::Rsa,
+        "1.3.101.112" => KeyType::Ed25519,
+        _ => KeyType::Other(key_oid),
+    };
+    Some(Certificate {
+        der: certificate.raw.to_vec(),
+        tbs: tbs.raw.to_vec(),
+        subject: name(subject.content),
+        issuer: name(issuer.content),
+        subject_der: subject.raw.to_vec(),
+        issuer_der: issuer.raw.to_vec(),
+        not_before: times.first().and_then(der_time),
+        not_after: times.get(1).and_then(der_time),
+        spki: spki.raw.to_vec(),
+        key_type,
+        signature_algorithm: oid(algorithm_parts.first()?.content),
+        signature_parameters: algorithm_parts
+            .get(1)
+            .map_or(Vec::new(), |p| p.raw.to_vec()),
+        // Skip the unused-bits byte of the BIT STRING
+        signature: signature.content.get(1..)?.to_vec(),
+        is_ca,
+        key_usage,
+        extended_key_usage,
+    })
+}
+
+/// Certificates in PEM text, such as an imported trust anchor list.
+/// Blocks that do not parse are skipped.
+pub fn parse_pem_certificates(text: &str) -> Vec<Certificate> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+    text.split(BEGIN)
+        .skip(1)
+        .filter_map(|block| {
+            let body: String = block
+                .split(END)
+                .next()?
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect();
+            let der = base64::engine::general_purpose::STANDARD
+                .decode(body)
+                .ok()?;
+            parse_certificate(&der)
+        })
+        .collect()
+}
+
+/// Web Crypto hash name of a hash algorithm OID.
+pub(super) fn hash_oid(oid: &str) -> Option<&'static str> {
+    match oid {
+        "2.16.840.1.101.3.4.2.1" => Some("SHA-256"),
+        "2.16.840.1.101.3.4.2.2" => Some("SHA-384"),
+        "2.16.840.1.101.3.4.2.3" => Some("SHA-512"),
+        _ => None,
+    }
+}
+
+/// Convert a DER ECDSA signature to the fixed-size `r || s` form Web Crypto
+/// verifies.
+pub(super) fn ecdsa_raw_signature(signature: &[u8], curve: &str) -> Option<Vec<u8>> {
+    let size = match curve {
+        "P-256" => 32,
+        "P-384" => 48,
+        _ => 66,
+    };
+    let (sequence, _) = der(signature)?;
+    let integers = der_children(sequence.content);
+    let mut out = Vec::with_capacity(size * 2);
+    for integer in integers.get(..2)? {
+        let first = integer
+            .content
+            .iter()
+            .position(|&b| b != 0)
+            .unwrap_or(integer.content.len());
+        let value = &integer.content[first..];
+        if value.len() > size {
+            return None;
+        }
+        out.resize(out.len() + size - value.len(), 0);
+        out.extend_from_slice(value);
+    }
+    Some(out)
+}
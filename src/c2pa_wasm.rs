@@ -0,0 +1,117 @@
+//! Verification of C2PA Content Credentials with the Web Crypto API.
+//!
+//! [`crate::c2pa`] works out which hashes and signatures to check;
+//! this module carries them out with `crypto.subtle` and records the
+//! results. Trust anchors come from [`TrustAnchors`], so nothing is fetched.
+
+use crate::c2pa::{self, ContentCredentials, Operation, SignatureScheme};
+use crate::preferences::TrustAnchors;
+use js_sys::{Array, Object, Reflect, Uint8Array};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{CryptoKey, SubtleCrypto};
+
+fn subtle_crypto() -> Result<SubtleCrypto, JsValue> {
+    let crypto = Reflect::get(&js_sys::global(), &JsValue::from_str("crypto"))?;
+    let crypto: web_sys::Crypto = crypto.dyn_into()?;
+    Ok(crypto.subtle())
+}
+
+/// Build a Web Crypto algorithm object from name/value pairs.
+fn algorithm(fields: &[(&str, JsValue)]) -> Result<Object, JsValue> {
+    let object = Object::new();
+    for (key, value) in fields {
+        Reflect::set(&object, &JsValue::from_str(key), value)?;
+    }
+    Ok(object)
+}
+
+/// Key import and verify parameters for a signature scheme.
+fn scheme_parameters(scheme: &SignatureScheme) -> Result<(Object, Object), JsValue> {
+    let name = |name: &str| ("name", JsValue::from_str(name));
+    let hash = |hash: &str| ("hash", JsValue::from_str(hash));
+    Ok(match scheme {
+        SignatureScheme::Ecdsa { curve, hash: h } => (
+            algorithm(&[name("ECDSA"), ("namedCurve", JsValue::from_str(curve))])?,
+            algorithm(&[name("ECDSA"), hash(h)])?,
+        ),
+        SignatureScheme::RsaPss {
+            hash: h,
+            salt_length,
+        } => (
+            algorithm(&[name("RSA-PSS"), hash(h)])?,
+            algorithm(&[name("RSA-PSS"), ("saltLength", JsValue::from(*salt_length))])?,
+        ),
+        SignatureScheme::RsaPkcs1 { hash: h } => (
+            algorithm(&[name("RSASSA-PKCS1-v1_5"), hash(h)])?,
+            algorithm(&[name("RSASSA-PKCS1-v1_5")])?,
+        ),
+        SignatureScheme::Ed25519 => (
+            algorithm(&[name("Ed25519")])?,
+            algorithm(&[name("Ed25519")])?,
+        ),
+    })
+}
+
+/// Carry out one operation, returning whether the digest or signature
+/// matches.
+async fn run(subtle: &SubtleCrypto, operation: &Operation) -> Result<bool, JsValue> {
+    match operation {
+        Operation::Digest {
+            algorithm,
+            data,
+            expected,
+        } => {
+            let digest =
+                JsFuture::from(subtle.digest_with_str_and_u8_array(algorithm, data)?).await?;
+            Ok(Uint8Array::new(&digest).to_vec() == *expected)
+        }
+        Operation::Verify {
+            scheme,
+            spki,
+            message,
+            signature,
+        } => {
+            let (import, verify) = scheme_parameters(scheme)?;
+            let usages = Array::of1(&JsValue::from_str("verify"));
+            let key = JsFuture::from(subtle.import_key_with_object(
+                "spki",
+                &Uint8Array::from(spki.as_slice()),
+                &import,
+                false,
+                &usages,
+            )?)
+            .await?;
+            let key: CryptoKey = key.dyn_into()?;
+            let verified =
+                JsFuture::from(subtle.verify_with_object_and_u8_array_and_u8_array(
+                    &verify, &key, signature, message,
+                )?)
+                .await?;
+            Ok(verified.as_bool().unwrap_or(false))
+        }
+    }
+}
+
+/// Read the Content Credentials of a file and verify them against its bytes
+/// and the imported trust anchors. `None` if the file has no manifest store
+/// or it cannot be read; the diagnostics report the latter.
+pub async fn verify_content_credentials(data: &[u8]) -> Option<ContentCredentials> {
+    let store = c2pa::read_manifest_store(data).ok()??;
+    let mut credentials = store.summary();
+    let anchors = c2pa::parse_pem_certificates(&TrustAnchors::load());
+    let now = (js_sys::Date::now() / 1000.0) as i64;
+    let plan = c2pa::plan_verification(&store, data, &anchors, now);
+
+    let mut results = Vec::with_capacity(plan.checks.len());
+    match subtle_crypto() {
+        Ok(subtle) => {
+            for check in &plan.checks {
+                results.push(run(&subtle, &check.operation).await.ok());
+            }
+        }
+        Err(e) => web_sys::console::log_1(&format!("Web Crypto unavailable: {:?}", e).into()),
+    }
+    credentials.validation = c2pa::assess(&plan, &results);
+    Some(credentials)
+}
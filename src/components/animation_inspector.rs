@@ -0,0 +1,138 @@
+//! Inspector panel listing the frames of an animated GIF, APNG or WebP.
+
+use crate::types::{ImageData, Theme};
+use yew::prelude::*;
+
+struct InspectorColors {
+    background: &'static str,
+    text: &'static str,
+    border: &'static str,
+    secondary_text: &'static str,
+    table_header: &'static str,
+}
+
+const LIGHT_INSPECTOR_COLORS: InspectorColors = InspectorColors {
+    background: "#f5f5f5",
+    text: "#333333",
+    border: "#ddd",
+    secondary_text: "#666",
+    table_header: "#e9ecef",
+};
+
+const DARK_INSPECTOR_COLORS: InspectorColors = InspectorColors {
+    background: "#1e1e1e",
+    text: "#e0e0e0",
+    border: "#444",
+    secondary_text: "#aaa",
+    table_header: "#2d2d2d",
+};
+
+/// Properties for [`AnimationInspector`].
+#[derive(Properties, PartialEq)]
+pub struct AnimationInspectorProps {
+    pub image_data: ImageData,
+    pub theme: Theme,
+}
+
+/// Collapsible panel showing the canvas, loop count and total duration of an
+/// animation, with each frame's rectangle, delay and disposal.
+#[function_component(AnimationInspector)]
+pub fn animation_inspector(props: &AnimationInspectorProps) -> Html {
+    let expanded = use_state(|| false);
+
+    let Some(animation) = &props.image_data.animation else {
+        return html! {};
+    };
+    let colors = match props.theme {
+        Theme::Light => LIGHT_INSPECTOR_COLORS,
+        Theme::Dark => DARK_INSPECTOR_COLORS,
+    };
+
+    let toggle = {
+        let expanded = expanded.clone();
+        Callback::from(move |_: MouseEvent| expanded.set(!*expanded))
+    };
+
+    let mut summary = vec![
+        ("Format", animation.format.clone()),
+        (
+            "Canvas",
+            format!("{} × {}", animation.canvas_width, animation.canvas_height),
+        ),
+        (
+            "Duration",
+            format!("{:.2} s", animation.total_duration_ms() as f64 / 1000.0),
+        ),
+        (
+            "Loops",
+            match animation.loop_count {
+                Some(0) => "Infinite".to_string(),
+                Some(n) => n.to_string(),
+                None => "Not specified".to_string(),
+            },
+        ),
+    ];
+    if let Some(declared) = animation.declared_frames
+        && declared as usize != animation.frames.len()
+    {
+        summary.push(("Declared Frames", declared.to_string()));
+    }
+
+    let cell = format!(
+        "padding: 4px 8px; border-bottom: 1px solid {};",
+        colors.border
+    );
+
+    html! {
+        <div style={format!(
+            "background: {}; padding: 15px; border-radius: 4px; margin-bottom: 20px; border: 1px solid {}; color: {};",
+            colors.background, colors.border, colors.text
+        )}>
+            <h3 onclick={toggle} style="margin: 0; cursor: pointer; user-select: none;">
+                {if *expanded { "▼ " } else { "▶ " }}{"Animation"}
+                <span style={format!("font-size: 12px; font-weight: normal; color: {}; margin-left: 8px;", colors.secondary_text)}>
+                    {format!("{} frames", animation.frames.len())}
+                </span>
+            </h3>
+
+            <div style="margin-top: 10px; font-size: 14px;">
+                {for summary.iter().map(|(label, value)| html! {
+                    <p style="margin: 4px 0;"><strong>{format!("{}: ", label)}</strong>{value}</p>
+                })}
+            </div>
+
+            {
+                if *expanded {
+                    html! {
+                        <table style="width: 100%; border-collapse: collapse; margin-top: 10px; font-size: 12px; font-family: monospace;">
+                            <thead>
+                                <tr style={format!("background: {}; text-align: left;", colors.table_header)}>
+                                    <th style={cell.clone()}>{"#"}</th>
+                                    <th style={cell.clone()}>{"Rectangle"}</th>
+                                    <th style={cell.clone()}>{"Delay"}</th>
+                                    <th style={cell.clone()}>{"Disposal"}</th>
+                                    <th style={cell.clone()}>{"Blend"}</th>
+                                    <th style={cell.clone()}>{"Comments"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {for animation.frames.iter().enumerate().map(|(i, frame)| html! {
+                                    <tr>
+                                        <td style={cell.clone()}>{i + 1}</td>
+                                        <td style={cell.clone()}>{format!("{}×{} at ({}, {})", frame.width, frame.height, frame.x, frame.y)}</td>
+                                        <td style={cell.clone()}>{format!("{} ms", frame.delay_ms)}</td>
+                                        <td style={cell.clone()}>{frame.disposal.clone()}</td>
+                                        <td style={cell.clone()}>{frame.blend.clone().unwrap_or_default()}</td>
+                                        <td style={cell.clone()}>{frame.comments.join(" · ")}</td>
+                                    </tr>
+                                })}
+                            </tbody>
+                        </table>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </div>
+    }
+}
@@ -42,7 +42,10 @@ pub fn archive_import(props: &ArchiveImportProps) -> Html {
                 wasm_bindgen_futures::spawn_local(async move {
                     // Extract images from the ZIP
                     match extract_images_from_zip(zip_file).await {
-                        Ok(archived_files) => {
+                        Ok((archived_files, skipped)) => {
+                            for message in skipped {
+                                on_error.emit(message);
+                            }
                             let total = archived_files.len();
 
                             if total == 0 {
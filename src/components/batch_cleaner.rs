@@ -2,26 +2,15 @@
 
 use crate::binary_cleaner::BinaryCleaner;
 use crate::types::{ImageData, Theme};
-use crate::utils::download_binary_file;
+use crate::utils::{download_binary_file, fetch_blob_bytes};
+use crate::{c2pa, file_type};
 use base64::{Engine as _, engine::general_purpose};
 use std::io::{Cursor, Write as _};
 use std::rc::Rc;
-use wasm_bindgen::prelude::*;
-use wasm_bindgen_futures::JsFuture;
 use yew::prelude::*;
 use zip::ZipWriter;
 use zip::write::FileOptions;
 
-/// Fetch bytes from a blob URL (same helper as in image_cleaner.rs)
-async fn fetch_blob_bytes(blob_url: &str) -> Result<Vec<u8>, JsValue> {
-    let window = web_sys::window().ok_or_else(|| JsValue::from_str("No window"))?;
-    let response = JsFuture::from(window.fetch_with_str(blob_url)).await?;
-    let response: web_sys::Response = response.dyn_into()?;
-    let array_buffer = JsFuture::from(response.array_buffer()?).await?;
-    let uint8_array = js_sys::Uint8Array::new(&array_buffer);
-    Ok(uint8_array.to_vec())
-}
-
 struct BatchCleanerColors {
     background: &'static str,
     text: &'static str,
@@ -91,8 +80,10 @@ pub fn batch_cleaner(props: &BatchCleanerProps) -> Html {
                     let data_url = &image_data.data_url;
                     let filename = &image_data.name;
 
-                    // Extract file extension and fetch file bytes
-                    if let Some(file_extension) = filename.split('.').next_back() {
+                    // Pick the cleaner from the detected type and fetch file bytes
+                    if let Some(file_extension) =
+                        file_type::cleaner_extension(&image_data.mime_type)
+                    {
                         // Fetch the blob data from the object URL (same fix as individual cleaner)
                         let file_bytes = if data_url.starts_with("blob:") {
                             // Fetch blob content
@@ -139,8 +130,8 @@ pub fn batch_cleaner(props: &BatchCleanerProps) -> Html {
                             Ok(cleaned_bytes) => {
                                 // Create cleaned filename
                                 let cleaned_filename = filename
-                                    .strip_suffix(&format!(".{}", file_extension))
-                                    .unwrap_or(filename)
+                                    .rsplit_once('.')
+                                    .map_or(filename.as_str(), |(stem, _)| stem)
                                     .to_string()
                                     + "_cleaned."
                                     + file_extension;
@@ -169,6 +160,15 @@ pub fn batch_cleaner(props: &BatchCleanerProps) -> Html {
                                 );
                             }
                         }
+                    } else {
+                        error_count += 1;
+                        web_sys::console::log_1(
+                            &format!(
+                                "Cleaning {} files is not supported: {}",
+                                image_data.mime_type, filename
+                            )
+                            .into(),
+                        );
                     }
                 }
 
@@ -211,6 +211,15 @@ pub fn batch_cleaner(props: &BatchCleanerProps) -> Html {
     };
 
     let button_disabled = *is_processing;
+    let credential_warnings: Vec<(String, String)> = props
+        .batch_items
+        .iter()
+        .filter_map(|item| {
+            let credentials = item.content_credentials.as_ref()?;
+            let warning = c2pa::cleaning_warning(credentials, &item.mime_type)?;
+            Some((item.name.clone(), warning))
+        })
+        .collect();
 
     html! {
         <div style={format!(
@@ -234,6 +243,23 @@ pub fn batch_cleaner(props: &BatchCleanerProps) -> Html {
                 </div>
             </div>
 
+            {
+                if !credential_warnings.is_empty() {
+                    html! {
+                        <div style={format!(
+                            "margin-bottom: 15px; padding: 10px; background: {}; border-radius: 4px; font-size: 14px;",
+                            colors.info_bg
+                        )}>
+                            { for credential_warnings.iter().map(|(name, warning)| html! {
+                                <div>{format!("⚠️ {}: {}", name, warning)}</div>
+                            }) }
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+
             <button
                 onclick={download_batch_cleaned_cb}
                 disabled={button_disabled}
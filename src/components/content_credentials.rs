@@ -0,0 +1,321 @@
+//! Panel showing C2PA Content Credentials and whether they verify.
+
+use crate::c2pa::{self, ContentCredentials, CredentialState, Outcome};
+use crate::c2pa_wasm::verify_content_credentials;
+use crate::preferences::TrustAnchors;
+use crate::types::{ImageData, Theme};
+use crate::utils::fetch_blob_bytes;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+struct CredentialColors {
+    background: &'static str,
+    text: &'static str,
+    border: &'static str,
+    secondary_text: &'static str,
+    table_header: &'static str,
+    passed: &'static str,
+    failed: &'static str,
+    skipped: &'static str,
+}
+
+const LIGHT_CREDENTIAL_COLORS: CredentialColors = CredentialColors {
+    background: "#eef6ff",
+    text: "#1d3557",
+    border: "#b6d4fe",
+    secondary_text: "#666",
+    table_header: "#dbe9fb",
+    passed: "#198754",
+    failed: "#dc3545",
+    skipped: "#b8860b",
+};
+
+const DARK_CREDENTIAL_COLORS: CredentialColors = CredentialColors {
+    background: "#1b2838",
+    text: "#d6e4f5",
+    border: "#34506e",
+    secondary_text: "#aaa",
+    table_header: "#243447",
+    passed: "#4caf50",
+    failed: "#cf6679",
+    skipped: "#e0b050",
+};
+
+/// Properties for [`ContentCredentialsPanel`].
+#[derive(Properties, PartialEq)]
+pub struct ContentCredentialsPanelProps {
+    pub image_data: ImageData,
+    pub theme: Theme,
+}
+
+/// Collapsible panel with the active manifest's claim generator, signer,
+/// actions, ingredients and assertions, every verification result, and an
+/// import for the trust anchor certificates signers are checked against.
+#[function_component(ContentCredentialsPanel)]
+pub fn content_credentials_panel(props: &ContentCredentialsPanelProps) -> Html {
+    let expanded = use_state(|| false);
+    // Results of re-verifying after the trust anchors change, keyed by the
+    // file they belong to
+    let reverified = use_state(|| None::<(String, ContentCredentials)>);
+    let anchor_count = use_state(|| c2pa::parse_pem_certificates(&TrustAnchors::load()).len());
+    let anchor_error = use_state(|| None::<String>);
+
+    let data = &props.image_data;
+    let credentials = match &*reverified {
+        Some((url, credentials)) if *url == data.data_url => credentials,
+        _ => match &data.content_credentials {
+            Some(credentials) => credentials,
+            None => return html! {},
+        },
+    };
+    let colors = match props.theme {
+        Theme::Light => LIGHT_CREDENTIAL_COLORS,
+        Theme::Dark => DARK_CREDENTIAL_COLORS,
+    };
+
+    let toggle = {
+        let expanded = expanded.clone();
+        Callback::from(move |_: MouseEvent| expanded.set(!*expanded))
+    };
+
+    // Store new anchors, then check the file again against them
+    let reverify = {
+        let reverified = reverified.clone();
+        let anchor_count = anchor_count.clone();
+        let data_url = data.data_url.clone();
+        move || {
+            anchor_count.set(c2pa::parse_pem_certificates(&TrustAnchors::load()).len());
+            let reverified = reverified.clone();
+            let data_url = data_url.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match fetch_blob_bytes(&data_url).await {
+                    Ok(bytes) => {
+                        if let Some(credentials) = verify_content_credentials(&bytes).await {
+                            reverified.set(Some((data_url, credentials)));
+                        }
+                    }
+                    Err(e) => web_sys::console::log_1(
+                        &format!("Failed to fetch blob data: {:?}", e).into(),
+                    ),
+                }
+            });
+        }
+    };
+
+    let on_import = {
+        let reverify = reverify.clone();
+        let anchor_error = anchor_error.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            let reverify = reverify.clone();
+            let anchor_error = anchor_error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let text =
+                    match gloo_file::futures::read_as_text(&gloo_file::File::from(file)).await {
+                        Ok(text) => text,
+                        Err(e) => {
+                            anchor_error.set(Some(format!("Failed to read file: {:?}", e)));
+                            return;
+                        }
+                    };
+                if c2pa::parse_pem_certificates(&text).is_empty() {
+                    anchor_error.set(Some("No PEM certificates found in the file".to_string()));
+                    return;
+                }
+                let combined = format!("{}\n{}", TrustAnchors::load(), text);
+                match TrustAnchors::save(combined.trim()) {
+                    Ok(()) => {
+                        anchor_error.set(None);
+                        reverify();
+                    }
+                    Err(e) => anchor_error.set(Some(e)),
+                }
+            });
+        })
+    };
+
+    let on_clear = {
+        let anchor_error = anchor_error.clone();
+        Callback::from(move |_: MouseEvent| match TrustAnchors::clear() {
+            Ok(()) => {
+                anchor_error.set(None);
+                reverify();
+            }
+            Err(e) => anchor_error.set(Some(e)),
+        })
+    };
+
+    let state = credentials.state();
+    let state_color = match state {
+        CredentialState::Trusted => colors.passed,
+        CredentialState::Invalid => colors.failed,
+        _ => colors.skipped,
+    };
+    let outcome_color = |outcome: Outcome| match outcome {
+        Outcome::Passed => colors.passed,
+        Outcome::Failed => colors.failed,
+        Outcome::Skipped => colors.skipped,
+    };
+    let cell = format!(
+        "padding: 4px 8px; border-bottom: 1px solid {};",
+        colors.border
+    );
+
+    let active = credentials.active();
+    let mut summary = vec![("Found In", credentials.location.clone())];
+    if let Some(manifest) = active {
+        for (label, value) in [
+            ("Claim Generator", &manifest.claim_generator),
+            ("Title", &manifest.title),
+            ("Format", &manifest.format),
+            ("Signed By", &manifest.signer),
+            ("Issued By", &manifest.issuer),
+            ("Signature Algorithm", &manifest.signature_algorithm),
+        ] {
+            if let Some(value) = value {
+                summary.push((label, value.clone()));
+            }
+        }
+    }
+    if credentials.manifests.len() > 1 {
+        summary.push((
+            "History",
+            format!("{} earlier manifest(s)", credentials.manifests.len() - 1),
+        ));
+    }
+
+    html! {
+        <div style={format!(
+            "background: {}; padding: 15px; border-radius: 4px; margin-bottom: 20px; border: 1px solid {}; color: {};",
+            colors.background, colors.border, colors.text
+        )}>
+            <h3 onclick={toggle} style="margin: 0; cursor: pointer; user-select: none;">
+                {if *expanded { "▼ " } else { "▶ " }}{"Content Credentials (C2PA)"}
+                <span style={format!("font-size: 12px; font-weight: bold; color: {}; margin-left: 8px;", state_color)}>
+                    {state.label()}
+                </span>
+            </h3>
+
+            <div style="margin-top: 10px; font-size: 14px;">
+                {for summary.iter().map(|(label, value)| html! {
+                    <p style="margin: 4px 0;"><strong>{format!("{}: ", label)}</strong>{value}</p>
+                })}
+            </div>
+
+            {
+                if let Some(manifest) = active.filter(|m| !m.actions.is_empty()) {
+                    html! {
+                        <table style="width: 100%; border-collapse: collapse; margin-top: 10px; font-size: 12px;">
+                            <thead>
+                                <tr style={format!("background: {}; text-align: left;", colors.table_header)}>
+                                    <th style={cell.clone()}>{"Action"}</th>
+                                    <th style={cell.clone()}>{"Software"}</th>
+                                    <th style={cell.clone()}>{"When"}</th>
+                                    <th style={cell.clone()}>{"Source Type"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {for manifest.actions.iter().map(|action| html! {
+                                    <tr>
+                                        <td style={cell.clone()}>{action.action.clone()}</td>
+                                        <td style={cell.clone()}>{action.software_agent.clone().unwrap_or_default()}</td>
+                                        <td style={cell.clone()}>{action.when.clone().unwrap_or_default()}</td>
+                                        <td style={cell.clone()}>{action.digital_source_type.clone().unwrap_or_default()}</td>
+                                    </tr>
+                                })}
+                            </tbody>
+                        </table>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+
+            {
+                if let Some(manifest) = active.filter(|m| !m.ingredients.is_empty()) {
+                    html! {
+                        <div style="margin-top: 10px; font-size: 14px;">
+                            <strong>{"Ingredients:"}</strong>
+                            <ul style="margin: 4px 0;">
+                                {for manifest.ingredients.iter().map(|ingredient| html! {
+                                    <li>
+                                        {ingredient.title.clone().unwrap_or_else(|| "Untitled".to_string())}
+                                        <span style={format!("color: {};", colors.secondary_text)}>
+                                            {format!(
+                                                " ({}{})",
+                                                ingredient.relationship,
+                                                ingredient.format.as_ref().map(|f| format!(", {}", f)).unwrap_or_default()
+                                            )}
+                                        </span>
+                                    </li>
+                                })}
+                            </ul>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+
+            {
+                if *expanded {
+                    html! {
+                        <>
+                            {
+                                if let Some(manifest) = active {
+                                    html! {
+                                        <p style="margin: 10px 0 4px 0; font-size: 13px;">
+                                            <strong>{"Assertions: "}</strong>
+                                            <span style="font-family: monospace;">{manifest.assertions.join(", ")}</span>
+                                        </p>
+                                    }
+                                } else {
+                                    html! {}
+                                }
+                            }
+                            <table style="width: 100%; border-collapse: collapse; margin-top: 10px; font-size: 12px;">
+                                <thead>
+                                    <tr style={format!("background: {}; text-align: left;", colors.table_header)}>
+                                        <th style={cell.clone()}>{"Status"}</th>
+                                        <th style={cell.clone()}>{"Details"}</th>
+                                        <th style={cell.clone()}>{"Manifest"}</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {for credentials.validation.iter().map(|status| html! {
+                                        <tr>
+                                            <td style={format!("{} color: {}; font-family: monospace;", cell, outcome_color(status.outcome))}>
+                                                {status.code.clone()}
+                                            </td>
+                                            <td style={cell.clone()}>{status.explanation.clone()}</td>
+                                            <td style={format!("{} color: {};", cell, colors.secondary_text)}>{status.manifest.clone()}</td>
+                                        </tr>
+                                    })}
+                                </tbody>
+                            </table>
+                            <div style="margin-top: 10px; font-size: 13px;">
+                                <label>
+                                    {format!("Trust anchors: {} imported. Import PEM certificates: ", *anchor_count)}
+                                    <input type="file" accept=".pem,.crt,.cer" onchange={on_import} />
+                                </label>
+                                <button onclick={on_clear} style="margin-left: 8px;">{"Clear"}</button>
+                                {
+                                    if let Some(error) = &*anchor_error {
+                                        html! { <div style={format!("color: {};", colors.failed)}>{error.clone()}</div> }
+                                    } else {
+                                        html! {}
+                                    }
+                                }
+                            </div>
+                        </>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </div>
+    }
+}
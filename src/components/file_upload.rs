@@ -93,7 +93,7 @@ pub fn file_upload(props: &FileUploadProps) -> Html {
             ref={input_ref}
             type="file"
             multiple=true
-            accept="image/*,application/pdf,image/svg+xml,image/tiff,image/heif,image/avif,image/jxl,.tiff,.tif,.heif,.heic,.avif,.jxl,.pdf,.svg"
+            accept="image/*,video/mp4,video/quicktime,video/3gpp,video/3gpp2,application/pdf,image/svg+xml,image/tiff,image/heif,image/avif,image/jxl,.tiff,.tif,.heif,.heic,.avif,.jxl,.pdf,.svg,.dng,.cr2,.nef,.nrw,.arw,.srf,.sr2,.orf,.rw2,.mp4,.m4v,.mov,.3gp,.3g2"
             onchange={on_file_change}
             style="display: none;"
             data-testid="file-input"
@@ -1,23 +1,12 @@
 //! Download a copy of the file with all metadata stripped.
 
-use crate::binary_cleaner::BinaryCleaner;
+use crate::binary_cleaner::{BinaryCleaner, CleanOptions};
 use crate::types::{ImageData, Theme};
-use crate::utils::download_binary_file;
+use crate::utils::{download_binary_file, fetch_blob_bytes, format_file_size};
+use crate::{c2pa, file_type};
 use base64::Engine as _;
-use wasm_bindgen::prelude::*;
-use wasm_bindgen_futures::JsFuture;
 use yew::prelude::*;
 
-/// Fetch bytes from a blob URL
-async fn fetch_blob_bytes(blob_url: &str) -> Result<Vec<u8>, JsValue> {
-    let window = web_sys::window().ok_or_else(|| JsValue::from_str("No window"))?;
-    let response = JsFuture::from(window.fetch_with_str(blob_url)).await?;
-    let response: web_sys::Response = response.dyn_into()?;
-    let array_buffer = JsFuture::from(response.array_buffer()?).await?;
-    let uint8_array = js_sys::Uint8Array::new(&array_buffer);
-    Ok(uint8_array.to_vec())
-}
-
 struct CleanerColors {
     background: &'static str,
     text: &'static str,
@@ -60,15 +49,40 @@ pub fn image_cleaner(props: &ImageCleanerProps) -> Html {
         Theme::Dark => DARK_CLEANER_COLORS,
     };
 
+    let keep_video = use_state(|| false);
+    let on_keep_video_change = {
+        let keep_video = keep_video.clone();
+        Callback::from(move |e: Event| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            keep_video.set(input.checked());
+        })
+    };
+
+    let keep_extra_images = use_state(|| false);
+    let on_keep_extra_images_change = {
+        let keep_extra_images = keep_extra_images.clone();
+        Callback::from(move |e: Event| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            keep_extra_images.set(input.checked());
+        })
+    };
+
     let download_cleaned_image_cb = {
         let data = props.image_data.clone();
+        let options = CleanOptions {
+            remove_embedded_video: !*keep_video,
+            remove_extra_images: !*keep_extra_images,
+        };
 
         Callback::from(move |_| {
             let data_url = data.data_url.clone();
             let filename = data.name.clone();
+            let mime_type = data.mime_type.clone();
 
             wasm_bindgen_futures::spawn_local(async move {
-                if let Some(file_extension) = filename.split('.').next_back() {
+                // The detected type picks the cleaner and the extension, so a
+                // renamed file is cleaned and saved as what it really is
+                if let Some(file_extension) = file_type::cleaner_extension(&mime_type) {
                     // Fetch the blob data from the object URL
                     let file_bytes = if data_url.starts_with("blob:") {
                         // Fetch blob content
@@ -101,7 +115,11 @@ pub fn image_cleaner(props: &ImageCleanerProps) -> Html {
                         return;
                     };
 
-                    match BinaryCleaner::clean_metadata(&file_bytes, file_extension) {
+                    match BinaryCleaner::clean_metadata_with_options(
+                        &file_bytes,
+                        file_extension,
+                        options,
+                    ) {
                         Ok(cleaned_bytes) => {
                             // Verify that cleaning actually occurred by comparing file sizes
                             // If cleaned file is identical in size, cleaning likely failed
@@ -152,14 +170,13 @@ pub fn image_cleaner(props: &ImageCleanerProps) -> Html {
 
                             // Create cleaned filename
                             let cleaned_filename = filename
-                                .strip_suffix(&format!(".{}", file_extension))
-                                .unwrap_or(&filename)
+                                .rsplit_once('.')
+                                .map_or(filename.as_str(), |(stem, _)| stem)
                                 .to_string()
                                 + "_cleaned."
                                 + file_extension;
 
                             // Download cleaned file
-                            let mime_type = format!("image/{}", file_extension);
                             download_binary_file(&cleaned_bytes, &cleaned_filename, &mime_type);
 
                             // Show success message
@@ -189,6 +206,12 @@ pub fn image_cleaner(props: &ImageCleanerProps) -> Html {
                             }
                         }
                     }
+                } else if let Some(window) = web_sys::window() {
+                    let _ = window.alert_with_message(&format!(
+                        "Error: Could not clean metadata from {}.\n\n\
+                        Cleaning {} files is not supported.",
+                        filename, mime_type
+                    ));
                 }
             });
         })
@@ -210,6 +233,71 @@ pub fn image_cleaner(props: &ImageCleanerProps) -> Html {
                 </div>
             </div>
 
+            {
+                if let Some(video) = &props.image_data.embedded_video {
+                    html! {
+                        <label style={format!("display: block; margin-bottom: 15px; font-size: 14px; color: {};", colors.text)}>
+                            <input
+                                type="checkbox"
+                                checked={*keep_video}
+                                onchange={on_keep_video_change}
+                                style="margin-right: 8px;"
+                            />
+                            {format!(
+                                "Keep the embedded video ({}, {}) - it can contain sound and location",
+                                video.kind,
+                                format_file_size(video.size)
+                            )}
+                        </label>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+
+            {
+                if props.image_data.mpf_images.len() > 1 {
+                    let extras: Vec<&str> = props.image_data.mpf_images[1..]
+                        .iter()
+                        .map(|image| image.image_type.as_str())
+                        .collect();
+                    html! {
+                        <label style={format!("display: block; margin-bottom: 15px; font-size: 14px; color: {};", colors.text)}>
+                            <input
+                                type="checkbox"
+                                checked={*keep_extra_images}
+                                onchange={on_keep_extra_images_change}
+                                style="margin-right: 8px;"
+                            />
+                            {format!(
+                                "Keep the {} additional image(s) ({}) - their metadata is removed too",
+                                extras.len(),
+                                extras.join(", ")
+                            )}
+                        </label>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+
+            {
+                if let Some(warning) = props
+                    .image_data
+                    .content_credentials
+                    .as_ref()
+                    .and_then(|c| c2pa::cleaning_warning(c, &props.image_data.mime_type))
+                {
+                    html! {
+                        <div style={format!("margin-bottom: 15px; padding: 10px; background: {}; border-radius: 4px; font-size: 14px;", colors.info_bg)}>
+                            {format!("⚠️ {}", warning)}
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+
             <button
                 onclick={download_cleaned_image_cb}
                 style={format!("background: {}; color: white; border: none; padding: 10px 20px; border-radius: 4px; cursor: pointer; font-weight: bold; font-size: 14px;", colors.button_bg)}
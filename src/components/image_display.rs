@@ -13,6 +13,7 @@ struct ImageDisplayColors {
     hash_bg: &'static str,
     secondary_text: &'static str,
     gps_bg: &'static str,
+    warning: &'static str,
 }
 
 const LIGHT_IMAGE_COLORS: ImageDisplayColors = ImageDisplayColors {
@@ -23,6 +24,7 @@ const LIGHT_IMAGE_COLORS: ImageDisplayColors = ImageDisplayColors {
     hash_bg: "#f8f9fa",
     secondary_text: "#666",
     gps_bg: "#e8f5e8",
+    warning: "#dc3545",
 };
 
 const DARK_IMAGE_COLORS: ImageDisplayColors = ImageDisplayColors {
@@ -33,6 +35,7 @@ const DARK_IMAGE_COLORS: ImageDisplayColors = ImageDisplayColors {
     hash_bg: "#2d2d2d",
     secondary_text: "#aaa",
     gps_bg: "#1a3d1a",
+    warning: "#cf6679",
 };
 
 /// Properties for [`ImageDisplay`].
@@ -86,17 +89,110 @@ pub fn image_display(props: &ImageDisplayProps) -> Html {
                                     }
                                 }
                                 <div style="text-align: center;">
-                                    <img
-                                        src={data.data_url.clone()}
-                                        alt={data.name.clone()}
-                                        style={format!("max-width: 300px; height: auto; border-radius: 4px; cursor: pointer; transition: transform 0.2s ease; {}",
-                                            if is_expanded { "" } else { "box-shadow: 0 2px 8px rgba(0,0,0,0.1);" })}
-                                        onclick={on_image_click}
-                                    />
+                                    <div style="display: flex; justify-content: center; align-items: flex-start; gap: 16px; flex-wrap: wrap;">
+                                        <img
+                                            src={data.data_url.clone()}
+                                            alt={data.name.clone()}
+                                            style={format!("max-width: 300px; height: auto; border-radius: 4px; cursor: pointer; transition: transform 0.2s ease; {}",
+                                                if is_expanded { "" } else { "box-shadow: 0 2px 8px rgba(0,0,0,0.1);" })}
+                                            onclick={on_image_click}
+                                        />
+                                        {
+                                            if let Some(thumbnail) = &data.thumbnail {
+                                                let border = if thumbnail.differs { colors.warning } else { colors.border };
+                                                html! {
+                                                    <figure style="margin: 0; max-width: 160px;">
+                                                        <img
+                                                            src={thumbnail.data_url.clone()}
+                                                            alt="Embedded EXIF thumbnail"
+                                                            style={format!("max-width: 160px; height: auto; border-radius: 4px; border: 2px solid {};", border)}
+                                                        />
+                                                        <figcaption style={format!("font-size: 12px; color: {}; margin-top: 4px;", colors.secondary_text)}>
+                                                            {"Embedded thumbnail"}
+                                                            {
+                                                                if let (Some(width), Some(height)) = (thumbnail.width, thumbnail.height) {
+                                                                    html! { <div>{format!("{}x{} pixels", width, height)}</div> }
+                                                                } else {
+                                                                    html! {}
+                                                                }
+                                                            }
+                                                            {
+                                                                if thumbnail.differs {
+                                                                    html! {
+                                                                        <div style={format!("color: {}; font-weight: bold;", colors.warning)}>
+                                                                            {"⚠️ Differs from the main image - may show pre-edit content"}
+                                                                        </div>
+                                                                    }
+                                                                } else {
+                                                                    html! {}
+                                                                }
+                                                            }
+                                                        </figcaption>
+                                                    </figure>
+                                                }
+                                            } else {
+                                                html! {}
+                                            }
+                                        }
+                                        {
+                                            for data.mpf_images.iter().filter(|image| !image.data_url.is_empty()).map(|image| {
+                                                let mut fields: Vec<(&String, &String)> = image
+                                                    .exif_data
+                                                    .iter()
+                                                    .map(|(key, value)| (key, &value.display))
+                                                    .collect();
+                                                fields.sort();
+                                                html! {
+                                                    <figure style="margin: 0; max-width: 160px; text-align: left;">
+                                                        <img
+                                                            src={image.data_url.clone()}
+                                                            alt={format!("Multi-Picture image {}", image.index)}
+                                                            style={format!("max-width: 160px; height: auto; border-radius: 4px; border: 2px solid {};", colors.border)}
+                                                        />
+                                                        <figcaption style={format!("font-size: 12px; color: {}; margin-top: 4px;", colors.secondary_text)}>
+                                                            {format!("Image {}: {}", image.index, image.image_type)}
+                                                            {
+                                                                if let (Some(width), Some(height)) = (image.width, image.height) {
+                                                                    html! { <div>{format!("{}x{} pixels", width, height)}</div> }
+                                                                } else {
+                                                                    html! {}
+                                                                }
+                                                            }
+                                                            {
+                                                                if fields.is_empty() {
+                                                                    html! {}
+                                                                } else {
+                                                                    html! {
+                                                                        <details>
+                                                                            <summary>{format!("{} metadata fields", fields.len())}</summary>
+                                                                            <ul style="padding-left: 16px; margin: 4px 0; word-break: break-all;">
+                                                                                { for fields.iter().map(|(key, value)| html! { <li>{format!("{}: {}", key, value)}</li> }) }
+                                                                            </ul>
+                                                                        </details>
+                                                                    }
+                                                                }
+                                                            }
+                                                        </figcaption>
+                                                    </figure>
+                                                }
+                                            })
+                                        }
+                                    </div>
                                     <p style="margin: 10px 0 0 0; color: #666; font-size: 14px;">{"Click image to expand"}</p>
                                 </div>
                             </>
                         }
+                    } else if data.mime_type.starts_with("video/") {
+                        html! {
+                            <div style="text-align: center;">
+                                <video
+                                    src={data.data_url.clone()}
+                                    controls=true
+                                    preload="metadata"
+                                    style="max-width: 300px; height: auto; border-radius: 4px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);"
+                                />
+                            </div>
+                        }
                     } else {
                         html! {
                             <div style="text-align: center; padding: 40px 20px; background: #f8f9fa; border-radius: 8px; border: 2px dashed #dee2e6;">
@@ -140,6 +236,22 @@ pub fn image_display(props: &ImageDisplayProps) -> Html {
                 </div>
                 <p><strong>{"Name: "}</strong>{&data.name}</p>
                 <p><strong>{"Size: "}</strong>{format_file_size(data.size)}</p>
+                <p><strong>{"Type: "}</strong>{&data.mime_type}</p>
+                {
+                    match &data.type_check {
+                        Some(check) if check.is_mismatch() => html! {
+                            <p style={format!("color: {}; font-weight: bold;", colors.warning)}>
+                                {format!("⚠️ File type mismatch: {}", check.summary())}
+                            </p>
+                        },
+                        Some(check) => html! {
+                            <p style={format!("font-size: 12px; color: {}; margin-top: -8px;", colors.secondary_text)}>
+                                {check.summary()}
+                            </p>
+                        },
+                        None => html! {},
+                    }
+                }
                 {
                     if let (Some(width), Some(height)) = (data.width, data.height) {
                         html! {
@@ -181,7 +293,8 @@ pub fn image_display(props: &ImageDisplayProps) -> Html {
             </div>
 
             {
-                if let Some((lat, lon)) = data.gps_coords {
+                if let Some(gps) = &data.gps {
+                    let (lat, lon) = gps.coords();
                     let (display_lat, display_lon) = fuzz_coordinates(lat, lon, *gps_precision);
 
                     let google_url = format!("https://maps.google.com/maps?q={},{}", display_lat, display_lon);
@@ -243,6 +356,11 @@ pub fn image_display(props: &ImageDisplayProps) -> Html {
 
                             <p><strong>{"Latitude: "}</strong>{display_lat}</p>
                             <p><strong>{"Longitude: "}</strong>{display_lon}</p>
+                            {
+                                for gps.details().into_iter().map(|(label, value)| html! {
+                                    <p><strong>{format!("{}: ", label)}</strong>{value}</p>
+                                })
+                            }
                             {
                                 if *gps_precision != GpsPrecisionLevel::Exact {
                                     html! {
@@ -0,0 +1,152 @@
+//! Inspector panel listing the marker segments and encoder settings of a JPEG.
+
+use crate::types::{ImageData, Theme};
+use crate::utils::format_file_size;
+use yew::prelude::*;
+
+struct InspectorColors {
+    background: &'static str,
+    text: &'static str,
+    border: &'static str,
+    secondary_text: &'static str,
+    table_header: &'static str,
+    warning: &'static str,
+}
+
+const LIGHT_INSPECTOR_COLORS: InspectorColors = InspectorColors {
+    background: "#f5f5f5",
+    text: "#333333",
+    border: "#ddd",
+    secondary_text: "#666",
+    table_header: "#e9ecef",
+    warning: "#dc3545",
+};
+
+const DARK_INSPECTOR_COLORS: InspectorColors = InspectorColors {
+    background: "#1e1e1e",
+    text: "#e0e0e0",
+    border: "#444",
+    secondary_text: "#aaa",
+    table_header: "#2d2d2d",
+    warning: "#cf6679",
+};
+
+/// Properties for [`JpegInspector`].
+#[derive(Properties, PartialEq)]
+pub struct JpegInspectorProps {
+    pub image_data: ImageData,
+    pub theme: Theme,
+}
+
+/// Collapsible panel showing the JPEG frame header, estimated quality and
+/// every marker segment with its offset and size.
+#[function_component(JpegInspector)]
+pub fn jpeg_inspector(props: &JpegInspectorProps) -> Html {
+    let expanded = use_state(|| false);
+
+    let Some(jpeg) = &props.image_data.jpeg else {
+        return html! {};
+    };
+    let colors = match props.theme {
+        Theme::Light => LIGHT_INSPECTOR_COLORS,
+        Theme::Dark => DARK_INSPECTOR_COLORS,
+    };
+
+    let toggle = {
+        let expanded = expanded.clone();
+        Callback::from(move |_: MouseEvent| expanded.set(!*expanded))
+    };
+
+    let mut summary = Vec::new();
+    if let Some(frame) = &jpeg.frame {
+        summary.push(("Process", frame.process.clone()));
+        summary.push(("Precision", format!("{} bits", frame.precision)));
+        summary.push((
+            "Components",
+            format!("{} ({})", frame.components.len(), frame.subsampling),
+        ));
+    }
+    if let Some(quality) = jpeg.quality {
+        let qualifier = if jpeg.standard_tables {
+            "standard IJG tables"
+        } else {
+            "estimated, custom tables"
+        };
+        summary.push(("Quality", format!("{} ({})", quality, qualifier)));
+    }
+    if let Some(interval) = jpeg.restart_interval {
+        summary.push(("Restart Interval", format!("{} MCUs", interval)));
+    }
+    summary.push(("Scans", jpeg.scans.to_string()));
+
+    let cell = format!(
+        "padding: 4px 8px; border-bottom: 1px solid {};",
+        colors.border
+    );
+
+    html! {
+        <div style={format!(
+            "background: {}; padding: 15px; border-radius: 4px; margin-bottom: 20px; border: 1px solid {}; color: {};",
+            colors.background, colors.border, colors.text
+        )}>
+            <h3 onclick={toggle} style="margin: 0; cursor: pointer; user-select: none;">
+                {if *expanded { "▼ " } else { "▶ " }}{"JPEG Structure"}
+                <span style={format!("font-size: 12px; font-weight: normal; color: {}; margin-left: 8px;", colors.secondary_text)}>
+                    {format!("{} segments", jpeg.segments.len())}
+                </span>
+            </h3>
+
+            <div style="margin-top: 10px; font-size: 14px;">
+                {for summary.iter().map(|(label, value)| html! {
+                    <p style="margin: 4px 0;"><strong>{format!("{}: ", label)}</strong>{value}</p>
+                })}
+                {
+                    if jpeg.trailing_bytes > 0 {
+                        html! {
+                            <p style={format!("margin: 4px 0; color: {}; font-weight: bold;", colors.warning)}>
+                                {format!("⚠️ {} of data after the end of the image", format_file_size(jpeg.trailing_bytes as u64))}
+                            </p>
+                        }
+                    } else if jpeg.truncated {
+                        html! {
+                            <p style={format!("margin: 4px 0; color: {};", colors.warning)}>
+                                {"⚠️ File ends before the end-of-image marker"}
+                            </p>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </div>
+
+            {
+                if *expanded {
+                    html! {
+                        <table style="width: 100%; border-collapse: collapse; margin-top: 10px; font-size: 12px; font-family: monospace;">
+                            <thead>
+                                <tr style={format!("background: {}; text-align: left;", colors.table_header)}>
+                                    <th style={cell.clone()}>{"Marker"}</th>
+                                    <th style={cell.clone()}>{"Offset"}</th>
+                                    <th style={cell.clone()}>{"Size"}</th>
+                                    <th style={cell.clone()}>{"Identifier"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {for jpeg.segments.iter().map(|segment| html! {
+                                    <tr>
+                                        <td style={cell.clone()}>{format!("{} (FF{:02X})", segment.name, segment.marker)}</td>
+                                        <td style={cell.clone()}>{format!("0x{:08X}", segment.offset)}</td>
+                                        <td style={cell.clone()}>{segment.size}</td>
+                                        <td style={cell.clone()}>{segment.identifier.clone().unwrap_or_default()}</td>
+                                    </tr>
+                                })}
+                            </tbody>
+                        </table>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </div>
+    }
+}
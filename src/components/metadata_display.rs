@@ -1,7 +1,8 @@
 //! Displays extracted metadata with selection controls.
 
+use crate::diagnostics::{Diagnostic, Severity};
 use crate::metadata_info::{get_metadata_category, get_metadata_explanation};
-use crate::types::{ImageData, Theme};
+use crate::types::{ImageData, MetadataValue, Theme};
 use std::collections::{HashMap, HashSet};
 use yew::prelude::*;
 
@@ -12,6 +13,8 @@ struct MetadataColors {
     secondary: &'static str,
     border: &'static str,
     section_bg: &'static str,
+    warning: &'static str,
+    error: &'static str,
 }
 
 const LIGHT_METADATA_COLORS: MetadataColors = MetadataColors {
@@ -21,6 +24,8 @@ const LIGHT_METADATA_COLORS: MetadataColors = MetadataColors {
     secondary: "#28a745",
     border: "#ddd",
     section_bg: "#f9f9f9",
+    warning: "#856404",
+    error: "#dc3545",
 };
 
 const DARK_METADATA_COLORS: MetadataColors = MetadataColors {
@@ -30,6 +35,8 @@ const DARK_METADATA_COLORS: MetadataColors = MetadataColors {
     secondary: "#03dac6",
     border: "#444",
     section_bg: "#2d2d2d",
+    warning: "#ffc107",
+    error: "#cf6679",
 };
 
 /// Properties for [`MetadataDisplay`].
@@ -52,6 +59,67 @@ impl PartialEq for MetadataDisplayProps {
     }
 }
 
+/// List of the problems found while parsing the file, worst first.
+fn diagnostics_list(diagnostics: &[Diagnostic], colors: &MetadataColors) -> Html {
+    if diagnostics.is_empty() {
+        return html! {};
+    }
+    let mut sorted: Vec<&Diagnostic> = diagnostics.iter().collect();
+    sorted.sort_by_key(|d| d.severity != Severity::Error);
+
+    html! {
+        <details open={sorted[0].severity == Severity::Error} style={format!("margin-bottom: 15px; padding: 8px; border-radius: 4px; background: {}; border: 1px solid {};", colors.section_bg, colors.border)}>
+            <summary style="cursor: pointer; font-weight: bold;">
+                {format!("⚠️ Parse diagnostics ({})", diagnostics.len())}
+            </summary>
+            <ul style="margin: 8px 0 0 0; padding-left: 20px; font-size: 13px;">
+                {for sorted.iter().map(|d| {
+                    let (label, color) = match d.severity {
+                        Severity::Error => ("Error", colors.error),
+                        Severity::Warning => ("Warning", colors.warning),
+                    };
+                    html! {
+                        <li style="margin-bottom: 4px;">
+                            <strong style={format!("color: {};", color)}>{format!("{} ", label)}</strong>
+                            <span style="font-family: monospace;">
+                                {match d.offset {
+                                    Some(offset) => format!("[{} @ 0x{:X}] ", d.parser, offset),
+                                    None => format!("[{}] ", d.parser),
+                                }}
+                            </span>
+                            {&d.message}
+                        </li>
+                    }
+                })}
+            </ul>
+        </details>
+    }
+}
+
+/// Group metadata into `(category, [(key, display)])` rows as shown in the
+/// UI, with categories and the rows within each sorted alphabetically.
+///
+/// Fields of the thumbnail IFD are keyed `Thumbnail:<Tag>`, so they land in
+/// their own category rather than next to the main image's field.
+pub fn categorize_metadata(
+    exif_data: &HashMap<String, MetadataValue>,
+) -> Vec<(&'static str, Vec<(String, String)>)> {
+    let mut categorized: HashMap<&'static str, Vec<(String, String)>> = HashMap::new();
+    for (key, value) in exif_data {
+        categorized
+            .entry(get_metadata_category(key))
+            .or_default()
+            .push((key.clone(), value.display.clone()));
+    }
+
+    let mut sorted: Vec<_> = categorized.into_iter().collect();
+    sorted.sort_by_key(|(category, _)| *category);
+    for (_, items) in &mut sorted {
+        items.sort_by(|(a, _), (b, _)| a.cmp(b));
+    }
+    sorted
+}
+
 /// Shows metadata grouped by category with checkboxes and explanations.
 #[function_component(MetadataDisplay)]
 pub fn metadata_display(props: &MetadataDisplayProps) -> Html {
@@ -68,30 +136,21 @@ pub fn metadata_display(props: &MetadataDisplayProps) -> Html {
         return html! {
             <div style={format!("background: {}; padding: 15px; border-radius: 4px; color: {}; border: 1px solid {};", colors.section_bg, colors.text, colors.border)}>
                 <h3>{"Metadata"}</h3>
-                <p>{"No metadata found in this file"}</p>
+                {
+                    if data.diagnostics.iter().any(|d| d.severity == Severity::Error) {
+                        html! { <p>{"No metadata could be read; the file has the problems listed below"}</p> }
+                    } else {
+                        html! { <p>{"No metadata found in this file"}</p> }
+                    }
+                }
+                {diagnostics_list(&data.diagnostics, &colors)}
             </div>
         };
     }
 
     // Memoize the expensive categorization and sorting
     let sorted_categories = use_memo(data.exif_data.clone(), |exif_data| {
-        // Group metadata by category
-        let mut categorized: HashMap<&str, Vec<(String, String)>> = HashMap::new();
-        for (key, value) in exif_data {
-            let category = get_metadata_category(key);
-            categorized
-                .entry(category)
-                .or_default()
-                .push((key.clone(), value.clone()));
-        }
-
-        // Sort categories alphabetically and items within each category
-        let mut sorted: Vec<_> = categorized.into_iter().collect();
-        sorted.sort_by_key(|(category, _)| *category);
-        for (_, items) in &mut sorted {
-            items.sort_by(|(a, _), (b, _)| a.cmp(b));
-        }
-        sorted
+        categorize_metadata(exif_data)
     });
 
     // Calculate global select/deselect state
@@ -138,6 +197,8 @@ pub fn metadata_display(props: &MetadataDisplayProps) -> Html {
                 </div>
             </div>
 
+            {diagnostics_list(&data.diagnostics, &colors)}
+
             <div>
                 {
                     sorted_categories.iter().map(|(category, items)| {
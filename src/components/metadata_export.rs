@@ -1,8 +1,8 @@
 //! Allows users to download selected metadata in various formats.
 
 use crate::export::{
-    generate_csv, generate_csv_batch, generate_json_batch, generate_md, generate_txt,
-    generate_txt_batch, generate_xml, generate_yaml,
+    generate_csv, generate_csv_batch, generate_json, generate_json_batch, generate_md,
+    generate_txt, generate_txt_batch, generate_xml, generate_yaml,
 };
 use crate::preferences::{ExportProfile, UserPreferences};
 use crate::types::{ImageData, Theme};
@@ -71,13 +71,12 @@ pub fn metadata_export(props: &MetadataExportProps) -> Html {
                 preferences.include_basic_info,
                 preferences.include_gps,
             );
-            if let Ok(json) = serde_json::to_string_pretty(&filtered_data) {
-                download_file(
-                    &json,
-                    &format!("{}_filtered_metadata.json", data.name),
-                    "application/json",
-                );
-            }
+            let json = generate_json(&filtered_data);
+            download_file(
+                &json,
+                &format!("{}_filtered_metadata.json", data.name),
+                "application/json",
+            );
         })
     };
 
@@ -192,9 +191,8 @@ pub fn metadata_export(props: &MetadataExportProps) -> Html {
                 preferences.include_basic_info,
                 preferences.include_gps,
             );
-            if let Ok(json) = serde_json::to_string_pretty(&filtered_data) {
-                copy_to_clipboard(&json);
-            }
+            let json = generate_json(&filtered_data);
+            copy_to_clipboard(&json);
         })
     };
 
@@ -280,7 +278,7 @@ pub fn metadata_export(props: &MetadataExportProps) -> Html {
 
     // Only show export section if there's metadata to export
     if data.exif_data.is_empty()
-        && data.gps_coords.is_none()
+        && data.gps.is_none()
         && data.width.is_none()
         && data.height.is_none()
     {
@@ -290,7 +288,7 @@ pub fn metadata_export(props: &MetadataExportProps) -> Html {
     // Calculate if there's anything to export
     let has_metadata = !selected_metadata.is_empty();
     let has_file_info = preferences.include_basic_info; // File info always includes at least name/size
-    let has_gps = preferences.include_gps && data.gps_coords.is_some();
+    let has_gps = preferences.include_gps && data.gps.is_some();
     let has_anything_to_export = has_metadata || has_file_info || has_gps;
 
     // Profile management state
@@ -605,7 +603,7 @@ pub fn metadata_export(props: &MetadataExportProps) -> Html {
                     }
                     {
                         // Only show GPS checkbox if GPS data exists
-                        if data.gps_coords.is_some() {
+                        if data.gps.is_some() {
                             html! {
                                 <label style="display: flex; align-items: center; gap: 5px; cursor: pointer;">
                                     <input
@@ -1,13 +1,17 @@
 //! Yew UI components used throughout the application.
 
+pub mod animation_inspector;
 pub mod archive_import;
 pub mod batch_cleaner;
 pub mod batch_manager;
 pub mod command_palette;
+pub mod content_credentials;
 pub mod duplicate_detector;
 pub mod file_upload;
 pub mod image_cleaner;
 pub mod image_display;
+pub mod jpeg_inspector;
 pub mod metadata_display;
 pub mod metadata_export;
+pub mod motion_photo;
 pub mod privacy_risk;
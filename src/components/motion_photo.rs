@@ -0,0 +1,166 @@
+//! Panel reporting motion photo clips and Live Photo pairs.
+
+use crate::motion_photo::{content_identifier, embedded_video_bytes, live_photo_partner};
+use crate::types::{ImageData, Theme};
+use crate::utils::{download_binary_file, fetch_blob_bytes, format_file_size};
+use std::rc::Rc;
+use yew::prelude::*;
+
+struct MotionColors {
+    background: &'static str,
+    text: &'static str,
+    border: &'static str,
+    secondary_text: &'static str,
+    button_bg: &'static str,
+    warning: &'static str,
+}
+
+const LIGHT_MOTION_COLORS: MotionColors = MotionColors {
+    background: "#f3e8ff",
+    text: "#4a1d7a",
+    border: "#d8b4fe",
+    secondary_text: "#666",
+    button_bg: "#7c3aed",
+    warning: "#dc3545",
+};
+
+const DARK_MOTION_COLORS: MotionColors = MotionColors {
+    background: "#2e1f42",
+    text: "#e9d5ff",
+    border: "#5b3a87",
+    secondary_text: "#aaa",
+    button_bg: "#a78bfa",
+    warning: "#cf6679",
+};
+
+/// Properties for [`MotionPhotoPanel`].
+#[derive(Properties, PartialEq)]
+pub struct MotionPhotoPanelProps {
+    pub image_data: ImageData,
+    #[prop_or_default]
+    pub batch_items: Vec<Rc<ImageData>>,
+    pub theme: Theme,
+}
+
+/// Describes a clip embedded in a motion photo, offers it as a separate
+/// download and names the other half of a Live Photo when it was loaded in
+/// the same batch.
+#[function_component(MotionPhotoPanel)]
+pub fn motion_photo_panel(props: &MotionPhotoPanelProps) -> Html {
+    let data = &props.image_data;
+    let identifier = content_identifier(data);
+    if data.embedded_video.is_none() && identifier.is_none() {
+        return html! {};
+    }
+    let colors = match props.theme {
+        Theme::Light => LIGHT_MOTION_COLORS,
+        Theme::Dark => DARK_MOTION_COLORS,
+    };
+
+    let embedded = data.embedded_video.as_ref().map(|video| {
+        let on_download = {
+            let video = video.clone();
+            let data_url = data.data_url.clone();
+            let stem = data
+                .name
+                .rsplit_once('.')
+                .map_or(data.name.as_str(), |(stem, _)| stem)
+                .to_string();
+            Callback::from(move |_: MouseEvent| {
+                let video = video.clone();
+                let data_url = data_url.clone();
+                let filename = format!(
+                    "{}_motion.{}",
+                    stem,
+                    if video.mime_type == "video/quicktime" {
+                        "mov"
+                    } else {
+                        "mp4"
+                    }
+                );
+                wasm_bindgen_futures::spawn_local(async move {
+                    let bytes = match fetch_blob_bytes(&data_url).await {
+                        Ok(bytes) => bytes,
+                        Err(e) => {
+                            web_sys::console::log_1(
+                                &format!("Failed to fetch blob data: {:?}", e).into(),
+                            );
+                            return;
+                        }
+                    };
+                    match embedded_video_bytes(&bytes, &video) {
+                        Some(clip) => download_binary_file(clip, &filename, &video.mime_type),
+                        None => web_sys::console::error_1(
+                            &"Embedded video lies outside the file".into(),
+                        ),
+                    }
+                });
+            })
+        };
+
+        let mut details = vec![format_file_size(video.size)];
+        if let Some(duration) = video.duration {
+            details.push(format!("{:.1} s", duration));
+        }
+        details.push(if video.has_audio { "with sound" } else { "no sound" }.to_string());
+        if let Some(us) = video.presentation_timestamp_us {
+            details.push(format!("still taken at {:.2} s", us as f64 / 1_000_000.0));
+        }
+
+        html! {
+            <div>
+                <p style="margin: 4px 0;">
+                    <strong>{format!("{}: ", video.kind)}</strong>{details.join(", ")}
+                </p>
+                {
+                    if video.has_location {
+                        html! {
+                            <p style={format!("margin: 4px 0; color: {}; font-weight: bold;", colors.warning)}>
+                                {"⚠️ The clip records its own GPS location"}
+                            </p>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+                <button
+                    onclick={on_download}
+                    style={format!("margin-top: 8px; background: {}; color: white; border: none; padding: 8px 16px; border-radius: 4px; cursor: pointer; font-weight: bold;", colors.button_bg)}
+                >
+                    {"⬇ Download Embedded Video"}
+                </button>
+            </div>
+        }
+    });
+
+    let live_photo = identifier.map(|id| {
+        let partner = live_photo_partner(data, &props.batch_items);
+        html! {
+            <div style="margin-top: 8px;">
+                <p style="margin: 4px 0;"><strong>{"Live Photo: "}</strong>{id}</p>
+                <p style={format!("margin: 4px 0; font-size: 14px; color: {};", colors.secondary_text)}>
+                    {
+                        match partner {
+                            Some(other) => format!("Paired with {}", other.name),
+                            None if data.mime_type.starts_with("video/") => {
+                                "Load the matching photo to see the pair".to_string()
+                            }
+                            None => "Load the matching video to see the pair".to_string(),
+                        }
+                    }
+                </p>
+            </div>
+        }
+    });
+
+    html! {
+        <div style={format!(
+            "background: {}; padding: 15px; border-radius: 4px; margin-bottom: 20px; border: 1px solid {}; color: {};",
+            colors.background, colors.border, colors.text
+        )}>
+            <h3 style="margin: 0 0 8px 0;">{"🎥 Motion Photo"}</h3>
+            {embedded.unwrap_or_default()}
+            {live_photo.unwrap_or_default()}
+        </div>
+    }
+}
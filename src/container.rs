@@ -0,0 +1,616 @@
+//! Read-only walkers for the container formats metadata is embedded in.
+//!
+//! These helpers only locate segments, chunks and directory entries; they do
+//! not interpret their payloads. Extractors for XMP, IPTC, ICC and friends
+//! build on them so each container is walked the same way everywhere.
+//!
+//! Walks stop at the first block that runs past the end of the data and
+//! after [`MAX_BLOCKS`] blocks, so crafted lengths cannot make them read out
+//! of bounds or run for ever. The `_checked` walkers also return the problem
+//! that stopped them, for the extractor that owns the container to report.
+
+use crate::diagnostics::Diagnostic;
+use crate::limits::{self, LimitExceeded, MAX_BLOCKS};
+use crate::types::Rational;
+
+/// PNG file signature.
+pub const PNG_SIGNATURE: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Returns `true` if the data starts with a JPEG SOI marker.
+pub fn is_jpeg(data: &[u8]) -> bool {
+    data.starts_with(&[0xFF, 0xD8])
+}
+
+/// Returns `true` if the data starts with the PNG signature.
+pub fn is_png(data: &[u8]) -> bool {
+    data.starts_with(PNG_SIGNATURE)
+}
+
+/// Returns `true` if the data is a RIFF container holding a WebP image.
+pub fn is_webp(data: &[u8]) -> bool {
+    data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP"
+}
+
+/// Returns `true` if the data starts with a GIF87a/GIF89a header.
+pub fn is_gif(data: &[u8]) -> bool {
+    data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a")
+}
+
+/// Returns `true` if the data starts with a classic TIFF header.
+pub fn is_tiff(data: &[u8]) -> bool {
+    data.starts_with(b"II*\0") || data.starts_with(b"MM\0*")
+}
+
+/// Signature box opening a JPEG XL container.
+pub const JXL_CONTAINER_SIGNATURE: &[u8] = &[
+    0x00, 0x00, 0x00, 0x0C, b'J', b'X', b'L', b' ', 0x0D, 0x0A, 0x87, 0x0A,
+];
+/// Signature of a bare JPEG XL codestream.
+pub const JXL_CODESTREAM_SIGNATURE: &[u8] = &[0xFF, 0x0A];
+
+/// Returns `true` if the data is a JPEG XL container or bare codestream.
+pub fn is_jxl(data: &[u8]) -> bool {
+    data.starts_with(JXL_CONTAINER_SIGNATURE) || data.starts_with(JXL_CODESTREAM_SIGNATURE)
+}
+
+/// Signature opening a PDF file header, e.g. `%PDF-1.7`.
+pub const PDF_SIGNATURE: &[u8] = b"%PDF-";
+
+/// Returns `true` if a PDF header appears within the first 1024 bytes, where
+/// readers are required to look for it.
+pub fn is_pdf(data: &[u8]) -> bool {
+    data[..data.len().min(1024)]
+        .windows(PDF_SIGNATURE.len())
+        .any(|w| w == PDF_SIGNATURE)
+}
+
+/// A JPEG marker segment preceding the entropy-coded image data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JpegSegment<'a> {
+    /// Marker byte following `0xFF` (e.g. `0xE1` for APP1).
+    pub marker: u8,
+    /// Offset of the `0xFF` marker byte in the file.
+    pub offset: usize,
+    /// Segment payload without the marker and length bytes.
+    pub data: &'a [u8],
+}
+
+/// List the marker segments of a JPEG file up to and including SOS.
+///
+/// Walking stops at the start of scan, at the first malformed segment or
+/// after [`MAX_BLOCKS`] segments.
+pub fn jpeg_segments(data: &[u8]) -> Vec<JpegSegment<'_>> {
+    let mut segments = Vec::new();
+    if !is_jpeg(data) {
+        return segments;
+    }
+
+    let mut i = 2;
+    while i + 4 <= data.len() && segments.len() < MAX_BLOCKS {
+        if data[i] != 0xFF {
+            break;
+        }
+        let marker = data[i + 1];
+        // Fill bytes and standalone markers carry no length
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        }
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            i += 2;
+            continue;
+        }
+        let length = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+        if length < 2 || i + 2 + length > data.len() {
+            break;
+        }
+        segments.push(JpegSegment {
+            marker,
+            offset: i,
+            data: &data[i + 4..i + 2 + length],
+        });
+        if marker == 0xDA {
+            break;
+        }
+        i += 2 + length;
+    }
+
+    segments
+}
+
+/// A PNG or RIFF chunk, or an ISO base media box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Chunk<'a> {
+    /// Four character chunk type.
+    pub id: [u8; 4],
+    /// Offset of the chunk header in the data it was read from.
+    pub offset: usize,
+    /// Chunk payload.
+    pub data: &'a [u8],
+}
+
+impl Chunk<'_> {
+    /// The chunk type as a string, e.g. `"iTXt"` or `"XMP "`.
+    pub fn name(&self) -> String {
+        String::from_utf8_lossy(&self.id).into_owned()
+    }
+}
+
+/// List the chunks of a PNG file. CRCs are not verified.
+pub fn png_chunks(data: &[u8]) -> Vec<Chunk<'_>> {
+    png_chunks_checked(data).0
+}
+
+/// List the chunks of a PNG file as [`png_chunks`] does, with the problem
+/// that ended the walk before `IEND`, if any.
+pub fn png_chunks_checked(data: &[u8]) -> (Vec<Chunk<'_>>, Option<Diagnostic>) {
+    let mut chunks = Vec::new();
+    if !is_png(data) {
+        return (chunks, None);
+    }
+
+    let mut i = PNG_SIGNATURE.len();
+    while i + 12 <= data.len() {
+        if chunks.len() == MAX_BLOCKS {
+            let error = LimitExceeded::new("PNG chunk count", MAX_BLOCKS as u64);
+            return (chunks, Some(Diagnostic::error("PNG", Some(i), error)));
+        }
+        let length = u32::from_be_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+        let id = [data[i + 4], data[i + 5], data[i + 6], data[i + 7]];
+        let Some(end) = limits::block_end(i, 8, length.into(), 4, data.len()).map(|end| end - 4)
+        else {
+            let message = format!(
+                "{} chunk declares {} bytes, but the file ends first",
+                String::from_utf8_lossy(&id),
+                length
+            );
+            return (chunks, Some(Diagnostic::error("PNG", Some(i), message)));
+        };
+        chunks.push(Chunk {
+            id,
+            offset: i,
+            data: &data[i + 8..end],
+        });
+        if &id == b"IEND" {
+            return (chunks, None);
+        }
+        i = end + 4;
+    }
+
+    let problem = Diagnostic::warning("PNG", Some(data.len()), "File ends before the IEND chunk");
+    (chunks, Some(problem))
+}
+
+/// List the top-level chunks of a RIFF (WebP) file.
+pub fn riff_chunks(data: &[u8]) -> Vec<Chunk<'_>> {
+    riff_chunks_checked(data).0
+}
+
+/// List the top-level chunks of a RIFF file as [`riff_chunks`] does, with
+/// the problem that ended the walk early, if any.
+pub fn riff_chunks_checked(data: &[u8]) -> (Vec<Chunk<'_>>, Option<Diagnostic>) {
+    let mut chunks = Vec::new();
+    if data.len() < 12 || &data[0..4] != b"RIFF" {
+        return (chunks, None);
+    }
+
+    let mut i = 12;
+    while i + 8 <= data.len() {
+        if chunks.len() == MAX_BLOCKS {
+            let error = LimitExceeded::new("RIFF chunk count", MAX_BLOCKS as u64);
+            return (chunks, Some(Diagnostic::error("RIFF", Some(i), error)));
+        }
+        let id = [data[i], data[i + 1], data[i + 2], data[i + 3]];
+        let size = u32::from_le_bytes([data[i + 4], data[i + 5], data[i + 6], data[i + 7]]);
+        let Some(end) = limits::block_end(i, 8, size.into(), 0, data.len()) else {
+            let message = format!(
+                "{} chunk declares {} bytes, but the file ends first",
+                String::from_utf8_lossy(&id),
+                size
+            );
+            return (chunks, Some(Diagnostic::error("RIFF", Some(i), message)));
+        };
+        chunks.push(Chunk {
+            id,
+            offset: i,
+            data: &data[i + 8..end],
+        });
+        // Chunks are padded to an even size
+        i = end + (size % 2) as usize;
+    }
+
+    (chunks, None)
+}
+
+/// Returns `true` if the data starts with an ISO base media `ftyp` box, as
+/// HEIF, AVIF, MP4 and QuickTime files do.
+pub fn is_isobmff(data: &[u8]) -> bool {
+    data.len() >= 12 && &data[4..8] == b"ftyp"
+}
+
+/// List the boxes of an ISO base media structure: a whole file or the
+/// payload of a container box.
+///
+/// A size of 0 extends the box to the end of the data and a size of 1 means
+/// a 64-bit size follows the type. A box running past the end of the data
+/// stops the walk.
+pub fn iso_boxes(data: &[u8]) -> Vec<Chunk<'_>> {
+    iso_boxes_checked(data).0
+}
+
+/// List the boxes of an ISO base media structure as [`iso_boxes`] does,
+/// with the problem that ended the walk early, if any.
+pub fn iso_boxes_checked(data: &[u8]) -> (Vec<Chunk<'_>>, Option<Diagnostic>) {
+    let mut boxes = Vec::new();
+
+    let mut i = 0;
+    while i + 8 <= data.len() {
+        if boxes.len() == MAX_BLOCKS {
+            let error = LimitExceeded::new("ISOBMFF box count", MAX_BLOCKS as u64);
+            return (boxes, Some(Diagnostic::error("ISOBMFF", Some(i), error)));
+        }
+        let size = u32::from_be_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]) as u64;
+        let id = [data[i + 4], data[i + 5], data[i + 6], data[i + 7]];
+        let name = String::from_utf8_lossy(&id);
+        let (header, size) = match size {
+            0 => (8, (data.len() - i) as u64),
+            1 => {
+                let Some(large) = data.get(i + 8..i + 16) else {
+                    let message = format!("{} box header is cut off", name);
+                    return (boxes, Some(Diagnostic::error("ISOBMFF", Some(i), message)));
+                };
+                (16, u64::from_be_bytes(large.try_into().unwrap_or_default()))
+            }
+            size => (8, size),
+        };
+        if size < header as u64 {
+            let message = format!("{} box has an invalid size of {}", name, size);
+            return (boxes, Some(Diagnostic::error("ISOBMFF", Some(i), message)));
+        }
+        let Some(end) = usize::try_from(size)
+            .ok()
+            .and_then(|size| i.checked_add(size))
+            .filter(|end| *end <= data.len())
+        else {
+            let message = format!(
+                "{} box declares {} bytes, but the file ends first",
+                name, size
+            );
+            return (boxes, Some(Diagnostic::error("ISOBMFF", Some(i), message)));
+        };
+        boxes.push(Chunk {
+            id,
+            offset: i,
+            data: &data[i + header..end],
+        });
+        i = end;
+    }
+
+    (boxes, None)
+}
+
+/// A block of a GIF data stream: an extension, an image or the trailer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GifBlock<'a> {
+    /// `0x21` for extensions, `0x2C` for images and `0x3B` for the trailer.
+    pub introducer: u8,
+    /// Extension label, such as `0xF9` for graphic control; 0 otherwise.
+    pub label: u8,
+    /// Offset of the introducer in the file.
+    pub offset: usize,
+    /// The whole block, from the introducer through its terminating
+    /// sub-block (for images, through the last LZW data sub-block).
+    pub bytes: &'a [u8],
+}
+
+impl GifBlock<'_> {
+    /// Concatenated data sub-blocks of an extension, after its label.
+    pub fn extension_data(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        gif_sub_blocks(self.bytes, 2, &mut payload);
+        payload
+    }
+}
+
+/// A GIF application extension block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GifApplicationExtension {
+    /// Eight byte application identifier followed by the three byte auth code.
+    pub identifier: [u8; 11],
+    /// Offset of the first data sub-block in the file.
+    pub offset: usize,
+    /// Concatenated sub-block payloads.
+    pub data: Vec<u8>,
+}
+
+/// Skip a chain of GIF data sub-blocks starting at `i`, returning the offset
+/// just past the terminating zero-length block.
+fn skip_gif_sub_blocks(data: &[u8], mut i: usize, mut payload: Option<&mut Vec<u8>>) -> usize {
+    while i < data.len() {
+        let size = data[i] as usize;
+        i += 1;
+        if size == 0 {
+            break;
+        }
+        let end = (i + size).min(data.len());
+        if let Some(out) = payload.as_mut() {
+            out.extend_from_slice(&data[i..end]);
+        }
+        i = end;
+    }
+    i
+}
+
+/// Offset of the first block of a GIF file, after the header, logical
+/// screen descriptor and global colour table.
+pub fn gif_stream_start(data: &[u8]) -> Option<usize> {
+    if !is_gif(data) || data.len() < 13 {
+        return None;
+    }
+    let packed = data[10];
+    let table = if packed & 0x80 != 0 {
+        3 * (2 << (packed & 0x07))
+    } else {
+        0
+    };
+    Some((13 + table).min(data.len()))
+}
+
+/// Append the payloads of the GIF data sub-blocks starting at `i` to
+/// `payload`, returning the offset just past the terminating block.
+pub fn gif_sub_blocks(data: &[u8], i: usize, payload: &mut Vec<u8>) -> usize {
+    skip_gif_sub_blocks(data, i, Some(payload))
+}
+
+/// List the blocks of a GIF file in stream order.
+///
+/// Walking stops after the trailer, at the first byte that does not start a
+/// block or after [`MAX_BLOCKS`] blocks, so callers can tell how much of the
+/// file was understood from the end of the last block.
+pub fn gif_blocks(data: &[u8]) -> Vec<GifBlock<'_>> {
+    gif_blocks_checked(data).0
+}
+
+/// List the blocks of a GIF file as [`gif_blocks`] does, with the problem
+/// that ended the walk before the trailer, if any.
+pub fn gif_blocks_checked(data: &[u8]) -> (Vec<GifBlock<'_>>, Option<Diagnostic>) {
+    let mut blocks = Vec::new();
+    let Some(mut i) = gif_stream_start(data) else {
+        return (blocks, None);
+    };
+
+    while i < data.len() {
+        if blocks.len() == MAX_BLOCKS {
+            let error = LimitExceeded::new("GIF block count", MAX_BLOCKS as u64);
+            return (blocks, Some(Diagnostic::error("GIF", Some(i), error)));
+        }
+        let introducer = data[i];
+        let (label, end) = match introducer {
+            0x21 if i + 1 < data.len() => (data[i + 1], skip_gif_sub_blocks(data, i + 2, None)),
+            0x2C if i + 10 <= data.len() => {
+                // Image descriptor, optional local colour table, LZW code size
+                let packed = data[i + 9];
+                let mut start = i + 10;
+                if packed & 0x80 != 0 {
+                    start += 3 * (2 << (packed & 0x07));
+                }
+                (0, skip_gif_sub_blocks(data, start + 1, None))
+            }
+            0x21 | 0x2C => break,
+            0x3B => (0, i + 1),
+            _ => {
+                let message = format!(
+                    "Unexpected byte 0x{:02X} where a block should start",
+                    introducer
+                );
+                return (blocks, Some(Diagnostic::error("GIF", Some(i), message)));
+            }
+        };
+        let end = end.min(data.len());
+        blocks.push(GifBlock {
+            introducer,
+            label,
+            offset: i,
+            bytes: &data[i..end],
+        });
+        if introducer == 0x3B {
+            return (blocks, None);
+        }
+        i = end;
+    }
+
+    let problem = Diagnostic::warning("GIF", Some(data.len()), "File ends before the trailer");
+    (blocks, Some(problem))
+}
+
+/// List the application extensions of a GIF file.
+pub fn gif_application_extensions(data: &[u8]) -> Vec<GifApplicationExtension> {
+    gif_blocks(data)
+        .into_iter()
+        .filter(|b| b.introducer == 0x21 && b.label == 0xFF)
+        .filter(|b| b.bytes.len() >= 14 && b.bytes[2] == 11)
+        .map(|b| {
+            let mut identifier = [0u8; 11];
+            identifier.copy_from_slice(&b.bytes[3..14]);
+            let mut data = Vec::new();
+            gif_sub_blocks(b.bytes, 14, &mut data);
+            GifApplicationExtension {
+                identifier,
+                offset: b.offset + 14,
+                data,
+            }
+        })
+        .collect()
+}
+
+/// Byte order of a TIFF structure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    Little,
+    Big,
+}
+
+impl ByteOrder {
+    /// Read a `u16` at `offset`, if in bounds.
+    pub fn u16(self, data: &[u8], offset: usize) -> Option<u16> {
+        let bytes: [u8; 2] = data.get(offset..offset.checked_add(2)?)?.try_into().ok()?;
+        Some(match self {
+            ByteOrder::Little => u16::from_le_bytes(bytes),
+            ByteOrder::Big => u16::from_be_bytes(bytes),
+        })
+    }
+
+    /// Read a `u32` at `offset`, if in bounds.
+    pub fn u32(self, data: &[u8], offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = data.get(offset..offset.checked_add(4)?)?.try_into().ok()?;
+        Some(match self {
+            ByteOrder::Little => u32::from_le_bytes(bytes),
+            ByteOrder::Big => u32::from_be_bytes(bytes),
+        })
+    }
+}
+
+/// A single entry of a TIFF image file directory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TiffEntry<'a> {
+    pub tag: u16,
+    pub field_type: u16,
+    pub count: u32,
+    /// Offset of the value bytes in the data the directory was read from.
+    pub value_offset: usize,
+    /// The entry's value bytes, resolved from the inline field or its offset.
+    pub value: &'a [u8],
+}
+
+impl TiffEntry<'_> {
+    /// The value as text, for ASCII (or byte) entries, without trailing NULs.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(self.value)
+            .trim_end_matches('\0')
+            .trim()
+            .to_string()
+    }
+
+    /// The value as unsigned integers, for BYTE, SHORT and LONG entries.
+    pub fn unsigned(&self, order: ByteOrder) -> Vec<u32> {
+        match self.field_type {
+            1 | 7 => self.value.iter().map(|b| *b as u32).collect(),
+            3 => (0..self.value.len() / 2)
+                .filter_map(|i| order.u16(self.value, i * 2).map(u32::from))
+                .collect(),
+            4 => (0..self.value.len() / 4)
+                .filter_map(|i| order.u32(self.value, i * 4))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The first unsigned integer of the value, if any.
+    pub fn first_unsigned(&self, order: ByteOrder) -> Option<u32> {
+        self.unsigned(order).first().copied()
+    }
+
+    /// The value as rationals, for RATIONAL and SRATIONAL entries.
+    pub fn rationals(&self, order: ByteOrder) -> Vec<Rational> {
+        let signed = match self.field_type {
+            5 => false,
+            10 => true,
+            _ => return Vec::new(),
+        };
+        let part = |offset: usize| {
+            order.u32(self.value, offset).map(|v| match signed {
+                true => v as i32 as i64,
+                false => v as i64,
+            })
+        };
+        (0..self.value.len() / 8)
+            .filter_map(|i| {
+                Some(Rational {
+                    num: part(i * 8)?,
+                    denom: part(i * 8 + 4)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A TIFF image file directory and the offset of the next one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiffDirectory<'a> {
+    pub entries: Vec<TiffEntry<'a>>,
+    pub next_offset: u32,
+}
+
+impl<'a> TiffDirectory<'a> {
+    /// Find the entry for a tag.
+    pub fn get(&self, tag: u16) -> Option<&TiffEntry<'a>> {
+        self.entries.iter().find(|e| e.tag == tag)
+    }
+}
+
+/// Size in bytes of one value of a TIFF field type.
+pub fn tiff_type_size(field_type: u16) -> usize {
+    match field_type {
+        1 | 2 | 6 | 7 => 1,
+        3 | 8 => 2,
+        4 | 9 | 11 | 13 => 4,
+        5 | 10 | 12 => 8,
+        _ => 0,
+    }
+}
+
+/// Parse a classic TIFF header, returning the byte order and first IFD offset.
+pub fn tiff_header(data: &[u8]) -> Option<(ByteOrder, u32)> {
+    let order = match data.get(0..2)? {
+        b"II" => ByteOrder::Little,
+        b"MM" => ByteOrder::Big,
+        _ => return None,
+    };
+    if order.u16(data, 2)? != 42 {
+        return None;
+    }
+    Some((order, order.u32(data, 4)?))
+}
+
+/// Read the image file directory at `offset`.
+///
+/// Entries whose values point outside the data are kept with an empty value
+/// so callers can still see that the tag was present.
+pub fn tiff_directory(data: &[u8], order: ByteOrder, offset: u32) -> Option<TiffDirectory<'_>> {
+    let offset = offset as usize;
+    let count = order.u16(data, offset)? as usize;
+    let mut entries = Vec::with_capacity(count);
+
+    for n in 0..count {
+        let entry = offset.checked_add(2 + n * 12)?;
+        let tag = order.u16(data, entry)?;
+        let field_type = order.u16(data, entry + 2)?;
+        let value_count = order.u32(data, entry + 4)?;
+        let size = tiff_type_size(field_type).saturating_mul(value_count as usize);
+        let value_offset = if size <= 4 {
+            entry.checked_add(8)?
+        } else {
+            order.u32(data, entry + 8)? as usize
+        };
+        let value = value_offset
+            .checked_add(size)
+            .and_then(|end| data.get(value_offset..end))
+            .unwrap_or(&[]);
+        entries.push(TiffEntry {
+            tag,
+            field_type,
+            count: value_count,
+            value_offset,
+            value,
+        });
+    }
+
+    let next_offset = offset
+        .checked_add(2 + count * 12)
+        .and_then(|end| order.u32(data, end))
+        .unwrap_or(0);
+    Some(TiffDirectory {
+        entries,
+        next_offset,
+    })
+}
@@ -0,0 +1,63 @@
+//! Problems found while parsing a file.
+//!
+//! Extractors return whatever they could read, so an empty metadata list
+//! cannot tell a clean file from a corrupt one. Each extractor therefore has
+//! a `_with_diagnostics` variant that pushes a warning or error for every
+//! problem it runs into, with the byte offset it was found at where that is
+//! known.
+
+use crate::{exif_core, jpeg};
+use serde::Serialize;
+
+/// How serious a problem is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Severity {
+    /// Something is unusual or damaged, but the data around it was read.
+    Warning,
+    /// A block could not be read, so its metadata is missing or incomplete.
+    Error,
+}
+
+/// A problem one parser found in the file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Diagnostic {
+    /// Parser or container that reported it, e.g. `"EXIF"` or `"PNG"`.
+    pub parser: String,
+    pub severity: Severity,
+    /// Offset in the file the problem was found at, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn warning(parser: &str, offset: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            parser: parser.to_string(),
+            severity: Severity::Warning,
+            offset,
+            message: message.into(),
+        }
+    }
+
+    pub fn error(parser: &str, offset: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            parser: parser.to_string(),
+            severity: Severity::Error,
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+/// Collect every problem the extractors find in a file.
+///
+/// [`process_file`](crate::exif_wasm::process_file) collects the same
+/// problems while extracting the metadata; this is for callers that only
+/// want the problems.
+pub fn diagnose(data: &[u8]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    exif_core::extract_metadata_with_diagnostics(data, &mut diagnostics);
+    jpeg::analyze_jpeg_with_diagnostics(data, &mut diagnostics);
+    diagnostics
+}
@@ -5,7 +5,8 @@
 
 // Re-export testable core functionality
 pub use crate::exif_core::{
-    determine_mime_type, extract_exif_data, is_supported_mime_type, parse_gps_coordinate,
+    determine_mime_type, extract_exif_data, extract_metadata, is_supported_mime_type,
+    parse_gps_coordinate,
 };
 
 // Re-export WASM-specific functionality
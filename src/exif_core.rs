@@ -1,40 +1,26 @@
 //! Pure Rust EXIF parsing logic that can be thoroughly tested in native environment.
 //! This module contains all testable EXIF functionality without browser dependencies.
 
-use exif::{Exif, Field, In, Reader, Tag, Value};
+use crate::container::ByteOrder;
+use crate::diagnostics::Diagnostic;
+use crate::makernotes;
+use crate::timestamp::{self, Timestamp};
+use crate::types::{GpsInfo, Ifd, MetadataSource, MetadataValue, Rational, RawValue};
+use crate::{
+    animation, c2pa, container, file_type, heif, icc, iptc, jxl, mpf, pdf, png_text, raw, svg,
+    video, xmp,
+};
+use exif::{Context, Exif, Field, In, Reader, Tag, Value};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::io::Cursor;
 
-/// Guess the MIME type of a file based on provided hints and header bytes.
+/// Determine the MIME type of a file from its content, falling back to the
+/// declared type and then the file name's extension.
 pub fn determine_mime_type(name: &str, file_type: &str, bytes: &[u8]) -> String {
-    if !file_type.is_empty() {
-        return file_type.to_string();
-    }
-
-    match image::guess_format(bytes) {
-        Ok(image::ImageFormat::Png) => "image/png".into(),
-        Ok(image::ImageFormat::Jpeg) => "image/jpeg".into(),
-        Ok(image::ImageFormat::Gif) => "image/gif".into(),
-        Ok(image::ImageFormat::WebP) => "image/webp".into(),
-        _ => {
-            let name_lower = name.to_lowercase();
-            if name_lower.ends_with(".pdf") {
-                "application/pdf".into()
-            } else if name_lower.ends_with(".svg") {
-                "image/svg+xml".into()
-            } else if name_lower.ends_with(".tiff") || name_lower.ends_with(".tif") {
-                "image/tiff".into()
-            } else if name_lower.ends_with(".heif") || name_lower.ends_with(".heic") {
-                "image/heif".into()
-            } else if name_lower.ends_with(".avif") {
-                "image/avif".into()
-            } else if name_lower.ends_with(".jxl") {
-                "image/jxl".into()
-            } else {
-                "application/octet-stream".into()
-            }
-        }
-    }
+    file_type::check_type(name, file_type, bytes)
+        .resolved()
+        .to_string()
 }
 
 /// Returns `true` if the MIME type is one the application knows how to handle.
@@ -50,81 +36,625 @@ pub fn is_supported_mime_type(mime: &str) -> bool {
         "image/heif",
         "image/avif",
         "image/jxl",
+        "video/mp4",
+        "video/quicktime",
+        "video/3gpp",
+        "video/3gpp2",
     ];
-    SUPPORTED.contains(&mime)
+    SUPPORTED.contains(&mime) || raw::is_raw_mime_type(mime)
+}
+
+/// Map a kamadak field location onto the IFD it was read from.
+///
+/// Everything below IFD1 (including its own EXIF sub-IFD) belongs to the
+/// embedded thumbnail.
+pub fn ifd_of(ifd_num: In, tag: Tag) -> Ifd {
+    if ifd_num != In::PRIMARY {
+        return Ifd::Thumbnail;
+    }
+    match tag.context() {
+        Context::Exif => Ifd::Exif,
+        Context::Gps => Ifd::Gps,
+        Context::Interop => Ifd::Interop,
+        _ => Ifd::Primary,
+    }
+}
+
+/// Build the `exif_data` key for a field.
+///
+/// Fields describing the main image keep their plain tag name, while fields
+/// from the thumbnail IFD are prefixed so they cannot overwrite the primary
+/// image's values.
+pub fn exif_field_key(ifd_num: In, tag: Tag) -> String {
+    match ifd_of(ifd_num, tag) {
+        Ifd::Thumbnail => format!("Thumbnail:{}", tag),
+        _ => format!("{}", tag),
+    }
+}
+
+/// Parse EXIF metadata and the GPS information block from a byte slice.
+pub fn extract_exif_data(bytes: &[u8]) -> (HashMap<String, MetadataValue>, Option<GpsInfo>) {
+    extract_exif_data_with_diagnostics(bytes, &mut Vec::new())
 }
 
-/// Parse EXIF metadata and GPS coordinates from a byte slice.
-pub fn extract_exif_data(bytes: &[u8]) -> (HashMap<String, String>, Option<(f64, f64)>) {
+/// Parse EXIF metadata as [`extract_exif_data`] does, reporting a block
+/// kamadak rejects as an error followed by what [`recover_exif`] found wrong
+/// with it, and MakerNotes that cannot be decoded.
+pub fn extract_exif_data_with_diagnostics(
+    bytes: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> (HashMap<String, MetadataValue>, Option<GpsInfo>) {
     let mut exif_map = HashMap::new();
-    let mut gps_coords = None;
+    let mut gps = None;
 
-    if let Ok(exifreader) = Reader::new().read_from_container(&mut Cursor::new(bytes)) {
+    if let Some(exifreader) = read_exif(bytes) {
         for f in exifreader.fields() {
-            let tag_name = format!("{}", f.tag);
-            let value = format!("{}", f.display_value().with_unit(&exifreader));
-            exif_map.insert(tag_name, value);
-            update_gps_coords(&mut gps_coords, f, &exifreader);
+            exif_map.insert(
+                exif_field_key(f.ifd_num, f.tag),
+                field_to_metadata_value(f, &exifreader),
+            );
         }
+        gps = extract_gps_info(&exifreader);
 
-        if let Some(mut coords) = gps_coords {
-            apply_gps_ref(&exifreader, &mut coords);
-            gps_coords = Some(coords);
-        }
+        let make = primary_ascii(&exifreader, Tag::Make);
+        let model = primary_ascii(&exifreader, Tag::Model);
+        exif_map.extend(makernotes::decode_maker_note_with_diagnostics(
+            exifreader.buf(),
+            &make,
+            &model,
+            diagnostics,
+        ));
+    } else if let Some((base, tiff)) = exif_block(bytes) {
+        // Keep whatever the damaged block still holds
+        let error = Reader::new()
+            .read_raw(tiff.to_vec())
+            .err()
+            .map_or_else(|| "unknown error".to_string(), |e| e.to_string());
+        let (fields, problems) = recover_exif(&tiff, base);
+        diagnostics.push(Diagnostic::error(
+            "EXIF",
+            base,
+            format!(
+                "EXIF block could not be read ({}); {} fields recovered",
+                error,
+                fields.len()
+            ),
+        ));
+        diagnostics.extend(problems);
+        exif_map = fields;
     }
 
-    (exif_map, gps_coords)
+    (exif_map, gps)
 }
 
-fn update_gps_coords(coords: &mut Option<(f64, f64)>, field: &Field, reader: &Exif) {
-    match field.tag {
-        Tag::GPSLatitude => {
-            if let Some(lat) = parse_gps_coordinate(field, reader) {
-                if let Some((_, lon)) = *coords {
-                    *coords = Some((lat, lon));
-                } else {
-                    *coords = Some((lat, 0.0));
-                }
+/// Extract the JPEG thumbnail stored in the EXIF thumbnail IFD (IFD1).
+///
+/// `JPEGInterchangeFormat` is an offset from the start of the TIFF header,
+/// which is where kamadak's buffer begins for every container.
+pub fn extract_thumbnail(bytes: &[u8]) -> Option<Vec<u8>> {
+    let exif = read_exif(bytes)?;
+    let uint = |tag| {
+        exif.get_field(tag, In::THUMBNAIL)
+            .and_then(|f| f.value.get_uint(0))
+            .map(|v| v as usize)
+    };
+    let offset = uint(Tag::JPEGInterchangeFormat)?;
+    let length = uint(Tag::JPEGInterchangeFormatLength)?;
+    let jpeg = exif.buf().get(offset..offset.checked_add(length)?)?;
+    jpeg.starts_with(&[0xFF, 0xD8]).then(|| jpeg.to_vec())
+}
+
+/// Read the EXIF block of a file.
+///
+/// HEIF and AVIF files are read through their `Exif` item, since kamadak's
+/// own HEIF support fails on many real-world files, and JPEG XL files through
+/// their (possibly Brotli-compressed) `Exif` box.
+fn read_exif(bytes: &[u8]) -> Option<Exif> {
+    let tiff = if container::is_isobmff(bytes) {
+        heif::find_exif(bytes)
+    } else if container::is_jxl(bytes) {
+        jxl::find_exif(bytes)
+    } else {
+        // ORF and RW2 only differ from TIFF in the magic number
+        raw::normalized_tiff(bytes)
+    };
+    if let Some(tiff) = tiff
+        && let Ok(exif) = Reader::new().read_raw(tiff)
+    {
+        return Some(exif);
+    }
+    Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .ok()
+}
+
+/// The TIFF structure holding a file's EXIF data, with its offset in the
+/// file when it is stored there as is. A JPEG APP1 segment cut short by the
+/// end of the file is returned up to that point.
+fn exif_block(bytes: &[u8]) -> Option<(Option<usize>, Cow<'_, [u8]>)> {
+    if container::is_isobmff(bytes) {
+        return heif::find_exif(bytes).map(|tiff| (None, Cow::Owned(tiff)));
+    }
+    if container::is_jxl(bytes) {
+        return jxl::find_exif(bytes).map(|tiff| (None, Cow::Owned(tiff)));
+    }
+    if let Some(tiff) = raw::normalized_tiff(bytes) {
+        return Some((Some(0), Cow::Owned(tiff)));
+    }
+    if container::tiff_header(bytes).is_some() {
+        return Some((Some(0), Cow::Borrowed(bytes)));
+    }
+    if container::is_png(bytes) {
+        let chunks = container::png_chunks(bytes);
+        let chunk = chunks.iter().find(|c| &c.id == b"eXIf")?;
+        return Some((Some(chunk.offset + 8), Cow::Borrowed(chunk.data)));
+    }
+    if container::is_webp(bytes) {
+        let chunks = container::riff_chunks(bytes);
+        let chunk = chunks.iter().find(|c| &c.id == b"EXIF")?;
+        let skip = if chunk.data.starts_with(b"Exif\0\0") {
+            6
+        } else {
+            0
+        };
+        return Some((
+            Some(chunk.offset + 8 + skip),
+            Cow::Borrowed(&chunk.data[skip..]),
+        ));
+    }
+
+    let mut i = 2;
+    while container::is_jpeg(bytes) && i + 4 <= bytes.len() && bytes[i] == 0xFF {
+        let marker = bytes[i + 1];
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        }
+        if marker == 0xDA || marker == 0xD9 {
+            break;
+        }
+        let length = u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize;
+        let payload = bytes.get(i + 4..(i + 2 + length).min(bytes.len()))?;
+        if marker == 0xE1 && payload.starts_with(b"Exif\0\0") {
+            return Some((Some(i + 10), Cow::Borrowed(&payload[6..])));
+        }
+        i += 2 + length;
+    }
+    None
+}
+
+/// Decode the value bytes of a TIFF entry.
+fn entry_value(field_type: u16, bytes: &[u8], order: ByteOrder, offset: usize) -> Option<Value> {
+    let u16s = || (0..bytes.len() / 2).filter_map(|i| order.u16(bytes, i * 2));
+    let u32s = || (0..bytes.len() / 4).filter_map(|i| order.u32(bytes, i * 4));
+    let pairs = || {
+        u32s()
+            .collect::<Vec<_>>()
+            .chunks_exact(2)
+            .map(|p| (p[0], p[1]))
+            .collect::<Vec<_>>()
+    };
+    Some(match field_type {
+        1 => Value::Byte(bytes.to_vec()),
+        2 => {
+            let mut parts: Vec<Vec<u8>> = bytes.split(|b| *b == 0).map(<[u8]>::to_vec).collect();
+            if bytes.last() == Some(&0) {
+                parts.pop();
             }
+            Value::Ascii(parts)
+        }
+        3 => Value::Short(u16s().collect()),
+        4 | 13 => Value::Long(u32s().collect()),
+        5 => Value::Rational(
+            pairs()
+                .into_iter()
+                .map(|(num, denom)| exif::Rational { num, denom })
+                .collect(),
+        ),
+        6 => Value::SByte(bytes.iter().map(|b| *b as i8).collect()),
+        7 => Value::Undefined(bytes.to_vec(), offset as u32),
+        8 => Value::SShort(u16s().map(|v| v as i16).collect()),
+        9 => Value::SLong(u32s().map(|v| v as i32).collect()),
+        10 => Value::SRational(
+            pairs()
+                .into_iter()
+                .map(|(num, denom)| exif::SRational {
+                    num: num as i32,
+                    denom: denom as i32,
+                })
+                .collect(),
+        ),
+        11 => Value::Float(u32s().map(f32::from_bits).collect()),
+        12 => Value::Double(
+            bytes
+                .chunks_exact(8)
+                .map(|c| {
+                    let bits: [u8; 8] = c.try_into().unwrap_or_default();
+                    f64::from_bits(match order {
+                        ByteOrder::Little => u64::from_le_bytes(bits),
+                        ByteOrder::Big => u64::from_be_bytes(bits),
+                    })
+                })
+                .collect(),
+        ),
+        _ => return None,
+    })
+}
+
+/// Recover the fields of an EXIF block kamadak rejected, entry by entry.
+///
+/// Directories are followed from IFD0 through the EXIF, GPS and
+/// interoperability pointers and on to IFD1. A directory cut short by the end
+/// of the block keeps the entries before the cut, and entries whose values
+/// lie outside the block or have an unknown type are skipped; each problem
+/// is reported with its offset in the file when `base` (the block's own
+/// offset) is known.
+pub fn recover_exif(
+    tiff: &[u8],
+    base: Option<usize>,
+) -> (HashMap<String, MetadataValue>, Vec<Diagnostic>) {
+    const EXIF_IFD_POINTER: u16 = 0x8769;
+    const GPS_IFD_POINTER: u16 = 0x8825;
+    const INTEROP_IFD_POINTER: u16 = 0xA005;
+
+    let mut fields = HashMap::new();
+    let mut problems = Vec::new();
+    let at = |offset: usize| base.map(|b| b + offset);
+    let Some((order, ifd0)) = container::tiff_header(tiff) else {
+        problems.push(Diagnostic::error(
+            "EXIF",
+            base,
+            "Missing or invalid TIFF header",
+        ));
+        return (fields, problems);
+    };
+
+    let mut pending = vec![(In::PRIMARY, Context::Tiff, ifd0 as usize, "IFD0")];
+    let mut visited = std::collections::HashSet::new();
+    while let Some((ifd_num, context, offset, name)) = pending.pop() {
+        if !visited.insert(offset) {
+            problems.push(Diagnostic::warning(
+                "EXIF",
+                at(offset),
+                format!("{} points back to an earlier directory", name),
+            ));
+            continue;
         }
-        Tag::GPSLongitude => {
-            if let Some(lon) = parse_gps_coordinate(field, reader) {
-                if let Some((lat, _)) = *coords {
-                    *coords = Some((lat, lon));
+        let Some(count) = order.u16(tiff, offset) else {
+            problems.push(Diagnostic::error(
+                "EXIF",
+                at(offset),
+                format!("{} lies outside the EXIF block", name),
+            ));
+            continue;
+        };
+        let count = count as usize;
+        for n in 0..count {
+            let entry = offset + 2 + n * 12;
+            let (Some(tag), Some(field_type), Some(value_count)) = (
+                order.u16(tiff, entry),
+                order.u16(tiff, entry + 2),
+                order.u32(tiff, entry + 4),
+            ) else {
+                problems.push(Diagnostic::warning(
+                    "EXIF",
+                    at(entry),
+                    format!("{} is cut off after {} of {} entries", name, n, count),
+                ));
+                break;
+            };
+            let exif_tag = Tag(context, tag);
+            let size = container::tiff_type_size(field_type).saturating_mul(value_count as usize);
+            let value_offset = if size <= 4 {
+                Some(entry + 8)
+            } else {
+                order.u32(tiff, entry + 8).map(|o| o as usize)
+            };
+            let value = value_offset
+                .and_then(|o| Some((o, tiff.get(o..o.checked_add(size)?)?)))
+                .and_then(|(o, bytes)| entry_value(field_type, bytes, order, o));
+            let Some(value) = value else {
+                let reason = if container::tiff_type_size(field_type) == 0 {
+                    format!("has unknown type {}", field_type)
                 } else {
-                    *coords = Some((0.0, lon));
-                }
+                    "value lies outside the EXIF block".to_string()
+                };
+                problems.push(Diagnostic::warning(
+                    "EXIF",
+                    at(entry),
+                    format!("{} {} {}", name, exif_tag, reason),
+                ));
+                continue;
+            };
+
+            let pointer = match (context, tag) {
+                (Context::Tiff, EXIF_IFD_POINTER) => Some((Context::Exif, "ExifIFD")),
+                (Context::Tiff, GPS_IFD_POINTER) => Some((Context::Gps, "GPS IFD")),
+                (Context::Exif, INTEROP_IFD_POINTER) => Some((Context::Interop, "InteropIFD")),
+                _ => None,
+            };
+            if let (Some((context, name)), Some(target)) = (pointer, value.get_uint(0)) {
+                pending.push((ifd_num, context, target as usize, name));
             }
+            let field = Field {
+                tag: exif_tag,
+                ifd_num,
+                value,
+            };
+            fields.insert(
+                exif_field_key(ifd_num, exif_tag),
+                metadata_value(&field, field.display_value().with_unit(()).to_string()),
+            );
+        }
+
+        if ifd_num == In::PRIMARY
+            && context == Context::Tiff
+            && let Some(next) = order.u32(tiff, offset + 2 + count * 12)
+            && next != 0
+        {
+            pending.push((In::THUMBNAIL, Context::Tiff, next as usize, "IFD1"));
         }
-        _ => {}
     }
+
+    (fields, problems)
+}
+
+/// Parse every supported metadata block (EXIF, XMP, IPTC, ICC, PNG text,
+/// HEIF/JPEG XL/RAW structure, video movie data and PDF and SVG document
+/// info) from a byte slice.
+///
+/// XMP properties, IPTC datasets, ICC profile fields, PNG textual chunks,
+/// HEIF/AVIF and JPEG XL details, RAW sub-IFDs, MP4/QuickTime fields and PDF
+/// and SVG fields are added under their `XMP-<prefix>:`, `IPTC:`, `ICC:`,
+/// `PNG:`, `HEIF:`, `JXL:`, `RAW:`/`DNG:`/`SubIFD<n>:`, `QuickTime:`, `PDF:`
+/// and `SVG:` keys next to the EXIF fields. The GPS block comes from EXIF,
+/// or for videos from their ISO 6709 location.
+pub fn extract_metadata(bytes: &[u8]) -> (HashMap<String, MetadataValue>, Option<GpsInfo>) {
+    extract_metadata_with_diagnostics(bytes, &mut Vec::new())
+}
+
+/// Parse every supported metadata block as [`extract_metadata`] does,
+/// pushing each problem an extractor runs into onto `diagnostics`.
+///
+/// Every container is reported by one extractor: PNG chunks by the PNG
+/// text extractor, WebP chunks and GIF blocks by the animation extractor and
+/// ISO base media boxes by the HEIF or video extractor. JPEG segments are
+/// left to [`analyze_jpeg_with_diagnostics`](crate::jpeg::analyze_jpeg_with_diagnostics),
+/// which walks them anyway.
+/// Content no format is recognised from is reported first.
+pub fn extract_metadata_with_diagnostics(
+    bytes: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> (HashMap<String, MetadataValue>, Option<GpsInfo>) {
+    if file_type::detect_mime_type(bytes).is_none() {
+        diagnostics.push(Diagnostic::error(
+            "File",
+            None,
+            "Content is not recognised as any supported format",
+        ));
+    }
+
+    let (mut metadata, gps) = extract_exif_data_with_diagnostics(bytes, diagnostics);
+    let (video_data, video_gps) = video::extract_video_data_with_diagnostics(bytes, diagnostics);
+    let others = xmp::extract_xmp_data_with_diagnostics(bytes, diagnostics)
+        .into_iter()
+        .chain(iptc::extract_iptc_data_with_diagnostics(bytes, diagnostics))
+        .chain(icc::extract_icc_data_with_diagnostics(bytes, diagnostics))
+        .chain(mpf::extract_mpf_data_with_diagnostics(bytes, diagnostics))
+        .chain(png_text::extract_png_text_data_with_diagnostics(
+            bytes,
+            diagnostics,
+        ))
+        .chain(animation::extract_animation_data_with_diagnostics(
+            bytes,
+            diagnostics,
+        ))
+        .chain(heif::extract_heif_data_with_diagnostics(bytes, diagnostics))
+        .chain(jxl::extract_jxl_data_with_diagnostics(bytes, diagnostics))
+        .chain(raw::extract_raw_data(bytes))
+        .chain(video_data)
+        .chain(pdf::extract_pdf_data_with_diagnostics(bytes, diagnostics))
+        .chain(svg::extract_svg_data_with_diagnostics(bytes, diagnostics))
+        .chain(c2pa::extract_c2pa_data_with_diagnostics(bytes, diagnostics));
+    for (key, value) in others {
+        metadata.entry(key).or_insert(value);
+    }
+    (metadata, gps.or(video_gps))
+}
+
+/// Convert an EXIF field into a typed [`MetadataValue`].
+///
+/// The display string matches kamadak's tag-aware formatting including the
+/// unit, while the raw value keeps the original integers or rationals.
+pub fn field_to_metadata_value(field: &Field, exif: &Exif) -> MetadataValue {
+    metadata_value(field, field.display_value().with_unit(exif).to_string())
 }
 
-fn apply_gps_ref(exif: &Exif, coords: &mut (f64, f64)) {
-    let (lat_ref, lon_ref) = (
-        exif.get_field(Tag::GPSLatitudeRef, In::PRIMARY),
-        exif.get_field(Tag::GPSLongitudeRef, In::PRIMARY),
+/// Convert a field given its display string with the unit applied.
+fn metadata_value(field: &Field, display: String) -> MetadataValue {
+    let plain = field.display_value().to_string();
+    let value = MetadataValue::new(raw_value(&field.value), display.clone()).with_source(
+        MetadataSource::Exif {
+            ifd: ifd_of(field.ifd_num, field.tag),
+            tag: field.tag.number(),
+        },
     );
 
-    if let Some(field) = lat_ref
-        && let Value::Ascii(ref vec) = field.value
-        && let Some(val) = vec.first()
-        && !val.is_empty()
-        && val[0] == b'S'
-    {
-        coords.0 = -coords.0;
+    match unit_from_display(&plain, &display) {
+        Some(unit) => value.with_unit(unit),
+        None => value,
     }
+}
 
-    if let Some(field) = lon_ref
-        && let Value::Ascii(ref vec) = field.value
-        && let Some(val) = vec.first()
-        && !val.is_empty()
-        && val[0] == b'W'
-    {
-        coords.1 = -coords.1;
+fn raw_value(value: &Value) -> RawValue {
+    fn ints<T: Copy + Into<i64>>(v: &[T]) -> RawValue {
+        RawValue::Integer(v.iter().map(|x| (*x).into()).collect())
+    }
+
+    match value {
+        Value::Byte(v) => ints(v),
+        Value::Short(v) => ints(v),
+        Value::Long(v) => ints(v),
+        Value::SByte(v) => ints(v),
+        Value::SShort(v) => ints(v),
+        Value::SLong(v) => ints(v),
+        Value::Ascii(v) => RawValue::Text(
+            v.iter()
+                .map(|s| String::from_utf8_lossy(s).into_owned())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        Value::Rational(v) => RawValue::Rational(
+            v.iter()
+                .map(|r| Rational {
+                    num: r.num.into(),
+                    denom: r.denom.into(),
+                })
+                .collect(),
+        ),
+        Value::SRational(v) => RawValue::Rational(
+            v.iter()
+                .map(|r| Rational {
+                    num: r.num.into(),
+                    denom: r.denom.into(),
+                })
+                .collect(),
+        ),
+        Value::Float(v) => RawValue::Float(v.iter().map(|f| *f as f64).collect()),
+        Value::Double(v) => RawValue::Float(v.clone()),
+        Value::Undefined(v, _) => RawValue::Bytes(v.clone()),
+        Value::Unknown(..) => RawValue::Bytes(Vec::new()),
     }
 }
 
+/// Recover the unit kamadak appended to (or prepended before) the plain value.
+fn unit_from_display(plain: &str, with_unit: &str) -> Option<String> {
+    if plain == with_unit || plain.is_empty() {
+        return None;
+    }
+    let unit = with_unit.replacen(plain, "", 1);
+    let unit = unit.trim();
+    (!unit.is_empty() && unit.len() < with_unit.len()).then(|| unit.to_string())
+}
+
+/// Read an ASCII field of the primary image as a plain string.
+fn primary_ascii(exif: &Exif, tag: Tag) -> String {
+    match exif.get_field(tag, In::PRIMARY).map(|f| &f.value) {
+        Some(Value::Ascii(parts)) => parts
+            .first()
+            .map(|p| String::from_utf8_lossy(p).trim().to_string())
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Build the GPS information block from the primary image's GPS IFD.
+///
+/// Returns `None` unless at least one coordinate could be parsed; a missing
+/// latitude or longitude is reported as `0.0`.
+pub fn extract_gps_info(exif: &Exif) -> Option<GpsInfo> {
+    let field = |tag| exif.get_field(tag, In::PRIMARY);
+    let ascii = |tag| {
+        field(tag).and_then(|f| match &f.value {
+            Value::Ascii(parts) => parts
+                .first()
+                .map(|p| String::from_utf8_lossy(p).trim().to_string())
+                .filter(|s| !s.is_empty()),
+            _ => None,
+        })
+    };
+    let rational = |tag| {
+        field(tag).and_then(|f| match &f.value {
+            Value::Rational(v) => v.first().map(|r| r.to_f64()).filter(|n| n.is_finite()),
+            _ => None,
+        })
+    };
+    let reference = |tag| ascii(tag).and_then(|s| s.chars().next());
+    let coordinate = |tag, ref_tag, negative_ref| {
+        let value = parse_gps_coordinate(field(tag)?, exif)?;
+        Some(if reference(ref_tag) == Some(negative_ref) {
+            -value
+        } else {
+            value
+        })
+    };
+
+    let latitude = coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef, 'S');
+    let longitude = coordinate(Tag::GPSLongitude, Tag::GPSLongitudeRef, 'W');
+    if latitude.is_none() && longitude.is_none() {
+        return None;
+    }
+
+    let below_sea_level = field(Tag::GPSAltitudeRef).and_then(|f| f.value.get_uint(0)) == Some(1);
+    let speed_kmh = rational(Tag::GPSSpeed).map(|speed| match reference(Tag::GPSSpeedRef) {
+        Some('M') => speed * 1.609_344,
+        Some('N') => speed * 1.852,
+        _ => speed,
+    });
+
+    Some(GpsInfo {
+        latitude: latitude.unwrap_or(0.0),
+        longitude: longitude.unwrap_or(0.0),
+        altitude: rational(Tag::GPSAltitude).map(|a| if below_sea_level { -a } else { a }),
+        img_direction: rational(Tag::GPSImgDirection),
+        img_direction_ref: reference(Tag::GPSImgDirectionRef).and_then(|r| match r {
+            'T' => Some("True North".to_string()),
+            'M' => Some("Magnetic North".to_string()),
+            _ => None,
+        }),
+        speed_kmh,
+        timestamp: gps_timestamp(ascii(Tag::GPSDateStamp), field(Tag::GPSTimeStamp)),
+        horizontal_error: rational(Tag::GPSHPositioningError),
+        map_datum: ascii(Tag::GPSMapDatum),
+        processing_method: field(Tag::GPSProcessingMethod).and_then(|f| match &f.value {
+            Value::Undefined(bytes, _) | Value::Byte(bytes) => encoded_string(bytes),
+            Value::Ascii(parts) => parts
+                .first()
+                .map(|p| String::from_utf8_lossy(p).trim().to_string()),
+            _ => None,
+        }),
+    })
+}
+
+/// Combine `GPSDateStamp` and `GPSTimeStamp` (three rationals) into the
+/// UTC fix time, or `None` if either is missing or out of range.
+fn gps_timestamp(date: Option<String>, time: Option<&Field>) -> Option<Timestamp> {
+    let Value::Rational(hms) = &time?.value else {
+        return None;
+    };
+    if hms.len() < 3 || hms.iter().any(|r| r.denom == 0) {
+        return None;
+    }
+    timestamp::gps_datetime(
+        date?.trim(),
+        [hms[0].to_f64(), hms[1].to_f64(), hms[2].to_f64()],
+    )
+}
+
+/// Decode an EXIF "encoded string" (`GPSProcessingMethod`, `UserComment`),
+/// which starts with an eight-byte character code.
+fn encoded_string(bytes: &[u8]) -> Option<String> {
+    let (code, text) = if bytes.len() >= 8 {
+        bytes.split_at(8)
+    } else {
+        (&[][..], bytes)
+    };
+    let text = if code.starts_with(b"UNICODE") {
+        let units: Vec<u16> = text
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else if code.is_empty() || code.starts_with(b"ASCII") || code.iter().all(|&b| b == 0) {
+        String::from_utf8_lossy(text).into_owned()
+    } else {
+        // JIS and unknown encodings; keep the bytes as they are
+        String::from_utf8_lossy(bytes).into_owned()
+    };
+    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    (!text.is_empty()).then(|| text.to_string())
+}
+
 /// Convert a GPS coordinate field into decimal degrees.
 pub fn parse_gps_coordinate(field: &Field, _exifreader: &Exif) -> Option<f64> {
     if let Value::Rational(ref rationals) = field.value {
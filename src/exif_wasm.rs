@@ -1,9 +1,12 @@
 //! WASM-specific EXIF functionality that requires browser APIs.
 //! This module contains all browser-dependent functions that cannot be tested in native environment.
 
-use crate::exif_core;
+use crate::animation::{self, Animation};
 use crate::types::ImageData;
 use crate::utils_hash::calculate_sha256_hash_idle;
+use crate::{
+    c2pa_wasm, exif_core, file_type, heif, jpeg, jxl, motion_photo, mpf, raw, thumbnail, video,
+};
 use gloo_file::Blob;
 use image::GenericImageView;
 use js_sys::Uint8Array;
@@ -33,10 +36,27 @@ pub fn create_object_url(mime: &str, bytes: &[u8]) -> Result<String, JsValue> {
     web_sys::Url::create_object_url_with_blob(&blob)
 }
 
+/// Create the object URL shown as the preview. RAW files are shown through
+/// their embedded JPEG preview, since browsers cannot decode sensor data.
+fn preview_object_url(mime: &str, bytes: &[u8]) -> Result<String, JsValue> {
+    match raw::preview_jpeg(bytes) {
+        Some(preview) if raw::is_raw_mime_type(mime) => create_object_url("image/jpeg", preview),
+        _ => create_object_url(mime, bytes),
+    }
+}
+
 /// Attempt to read image width and height from the byte stream.
 /// This function requires WASM APIs and cannot be tested in native environment.
 pub fn get_dimensions(mime: &str, bytes: &[u8]) -> (Option<u32>, Option<u32>) {
-    if mime.starts_with("image/") && mime != "image/svg+xml" {
+    // The image crate cannot decode HEIF/AVIF, JPEG XL, RAW sensor data or
+    // video, so read their headers
+    if let Some((width, height)) = heif::dimensions(bytes)
+        .or_else(|| jxl::dimensions(bytes))
+        .or_else(|| raw::dimensions(bytes))
+        .or_else(|| video::dimensions(bytes))
+    {
+        (Some(width), Some(height))
+    } else if mime.starts_with("image/") && mime != "image/svg+xml" {
         match get_image_dimensions(bytes) {
             Ok(dims) => (Some(dims.0), Some(dims.1)),
             Err(_) => (None, None),
@@ -63,14 +83,22 @@ pub async fn process_file(file: File) -> Result<ImageData, JsValue> {
     let size = file.size() as u64;
 
     let bytes = file_bytes(&file).await?;
-    let mime_type = exif_core::determine_mime_type(&name, &file.type_(), &bytes);
+    let type_check = file_type::check_type(&name, &file.type_(), &bytes);
+    let mime_type = type_check.resolved().to_string();
     if !exif_core::is_supported_mime_type(&mime_type) {
         return Err(JsValue::from_str("Unsupported file type"));
     }
 
-    let data_url = create_object_url(&mime_type, &bytes)?;
+    let data_url = preview_object_url(&mime_type, &bytes)?;
     let (width, height) = get_dimensions(&mime_type, &bytes);
-    let (exif_data, gps_coords) = exif_core::extract_exif_data(&bytes);
+    let mut diagnostics = Vec::new();
+    let (exif_data, gps) = exif_core::extract_metadata_with_diagnostics(&bytes, &mut diagnostics);
+    let thumbnail = thumbnail::analyze_thumbnail(&bytes);
+    let jpeg = jpeg::analyze_jpeg_with_diagnostics(&bytes, &mut diagnostics);
+    let embedded_video = motion_photo::find_embedded_video(&bytes);
+    let mpf_images = mpf::extract_images(&bytes);
+    let animation = animation::parse_animation(&bytes).filter(Animation::is_animated);
+    let content_credentials = c2pa_wasm::verify_content_credentials(&bytes).await;
 
     // Calculate SHA-256 hash during browser idle time for better perceived performance
     let sha256_hash = calculate_sha256_hash_idle(&bytes).await.ok();
@@ -83,8 +111,16 @@ pub async fn process_file(file: File) -> Result<ImageData, JsValue> {
         width,
         height,
         exif_data,
-        gps_coords,
+        gps,
         sha256_hash,
+        thumbnail,
+        jpeg,
+        embedded_video,
+        mpf_images,
+        animation,
+        type_check: Some(type_check),
+        diagnostics,
+        content_credentials,
     })
 }
 
@@ -97,15 +133,23 @@ pub async fn process_blob(blob: Blob, name: String) -> Result<ImageData, JsValue
 
     let size = bytes.len() as u64;
     let blob_type = blob.raw_mime_type();
-    let mime_type = exif_core::determine_mime_type(&name, &blob_type, &bytes);
+    let type_check = file_type::check_type(&name, &blob_type, &bytes);
+    let mime_type = type_check.resolved().to_string();
 
     if !exif_core::is_supported_mime_type(&mime_type) {
         return Err(JsValue::from_str("Unsupported file type"));
     }
 
-    let data_url = create_object_url(&mime_type, &bytes)?;
+    let data_url = preview_object_url(&mime_type, &bytes)?;
     let (width, height) = get_dimensions(&mime_type, &bytes);
-    let (exif_data, gps_coords) = exif_core::extract_exif_data(&bytes);
+    let mut diagnostics = Vec::new();
+    let (exif_data, gps) = exif_core::extract_metadata_with_diagnostics(&bytes, &mut diagnostics);
+    let thumbnail = thumbnail::analyze_thumbnail(&bytes);
+    let jpeg = jpeg::analyze_jpeg_with_diagnostics(&bytes, &mut diagnostics);
+    let embedded_video = motion_photo::find_embedded_video(&bytes);
+    let mpf_images = mpf::extract_images(&bytes);
+    let animation = animation::parse_animation(&bytes).filter(Animation::is_animated);
+    let content_credentials = c2pa_wasm::verify_content_credentials(&bytes).await;
 
     // Calculate SHA-256 hash during browser idle time for better perceived performance
     let sha256_hash = calculate_sha256_hash_idle(&bytes).await.ok();
@@ -118,7 +162,15 @@ pub async fn process_blob(blob: Blob, name: String) -> Result<ImageData, JsValue
         width,
         height,
         exif_data,
-        gps_coords,
+        gps,
         sha256_hash,
+        thumbnail,
+        jpeg,
+        embedded_video,
+        mpf_images,
+        animation,
+        type_check: Some(type_check),
+        diagnostics,
+        content_credentials,
     })
 }
@@ -1,6 +1,6 @@
 //! Helpers for exporting selected metadata in various formats.
 
-use crate::types::ImageData;
+use crate::types::{Ifd, ImageData, MetadataSource, MetadataValue};
 use crate::utils::format_file_size;
 use std::collections::BTreeSet;
 use std::fmt::Write as _;
@@ -20,20 +20,54 @@ fn xml_escape(s: &str) -> String {
     out
 }
 
-fn sorted_exif_pairs(data: &ImageData) -> Vec<(String, String)> {
-    let mut v: Vec<(String, String)> = data
-        .exif_data
-        .iter()
-        .map(|(k, val)| (k.clone(), val.clone()))
-        .collect();
-    v.sort_by(|a, b| a.0.cmp(&b.0));
+fn sorted_exif_pairs(data: &ImageData) -> Vec<(&String, &MetadataValue)> {
+    let mut v: Vec<(&String, &MetadataValue)> = data.exif_data.iter().collect();
+    v.sort_by(|a, b| a.0.cmp(b.0));
     v
 }
 
+/// Metadata fields read from one IFD, sorted by key.
+pub type IfdGroup<'a> = (Option<Ifd>, Vec<(&'a String, &'a MetadataValue)>);
+
+/// Group metadata fields by the EXIF IFD they were read from.
+///
+/// Fields without an IFD origin (other sources, or values created by hand)
+/// are returned first under `None`. Each group is sorted by key.
+pub fn group_fields_by_ifd(data: &ImageData) -> Vec<IfdGroup<'_>> {
+    let pairs = sorted_exif_pairs(data);
+    std::iter::once(None)
+        .chain(Ifd::ALL.into_iter().map(Some))
+        .map(|ifd| {
+            let fields: Vec<_> = pairs
+                .iter()
+                .copied()
+                .filter(|(_, v)| v.ifd() == ifd)
+                .collect();
+            (ifd, fields)
+        })
+        .filter(|(_, fields)| !fields.is_empty())
+        .collect()
+}
+
+/// Metadata fields in [`group_fields_by_ifd`] order, each with its IFD.
+fn grouped_exif_pairs(data: &ImageData) -> Vec<(Option<Ifd>, &String, &MetadataValue)> {
+    group_fields_by_ifd(data)
+        .into_iter()
+        .flat_map(|(ifd, fields)| fields.into_iter().map(move |(k, v)| (ifd, k, v)))
+        .collect()
+}
+
+/// Position of an IFD group in exports: fields without an IFD first, then
+/// the IFDs in [`Ifd::ALL`] order.
+fn group_rank(ifd: Option<Ifd>) -> usize {
+    ifd.and_then(|ifd| Ifd::ALL.iter().position(|i| *i == ifd))
+        .map_or(0, |position| position + 1)
+}
+
 /// Create a CSV representation of the provided [`ImageData`].
 pub fn generate_csv(data: &ImageData) -> String {
     let mut csv = String::new();
-    csv.push_str("Property,Value\n");
+    csv.push_str("Property,Value,IFD\n");
 
     // Basic file info
     csv.push_str(&format!("Filename,\"{}\"\n", data.name));
@@ -46,13 +80,21 @@ pub fn generate_csv(data: &ImageData) -> String {
     }
 
     // GPS data
-    if let Some((lat, lon)) = data.gps_coords {
-        csv.push_str(&format!("GPS Latitude,{}\n", lat));
-        csv.push_str(&format!("GPS Longitude,{}\n", lon));
+    if let Some(gps) = &data.gps {
+        csv.push_str(&format!("GPS Latitude,{}\n", gps.latitude));
+        csv.push_str(&format!("GPS Longitude,{}\n", gps.longitude));
+        for (label, value) in gps.details() {
+            let _ = writeln!(csv, "GPS {},\"{}\"", label, value.replace('"', "\"\""));
+        }
     }
 
-    // EXIF data (sorted for deterministic output)
-    for (key, value) in sorted_exif_pairs(data) {
+    // Capture timestamps as ISO 8601
+    for timestamp in data.timestamps() {
+        let _ = writeln!(csv, "Timestamp ({}),{}", timestamp.kind.label(), timestamp);
+    }
+
+    // Metadata grouped by IFD, sorted within each group
+    for (ifd, key, value) in grouped_exif_pairs(data) {
         let mut esc_key = String::new();
         let mut esc_val = String::new();
         for ch in key.chars() {
@@ -61,13 +103,20 @@ pub fn generate_csv(data: &ImageData) -> String {
             }
             esc_key.push(ch);
         }
-        for ch in value.chars() {
+        for ch in value.display.chars() {
             if ch == '"' {
                 esc_val.push('"');
             }
             esc_val.push(ch);
         }
-        let _ = writeln!(csv, "\"{}\",\"{}\"", esc_key, esc_val);
+        match ifd {
+            Some(ifd) => {
+                let _ = writeln!(csv, "\"{}\",\"{}\",{}", esc_key, esc_val, ifd.id());
+            }
+            None => {
+                let _ = writeln!(csv, "\"{}\",\"{}\"", esc_key, esc_val);
+            }
+        }
     }
 
     csv
@@ -91,11 +140,15 @@ pub fn generate_txt(data: &ImageData) -> String {
     txt.push('\n');
 
     // GPS data
-    if let Some((lat, lon)) = data.gps_coords {
+    if let Some(gps) = &data.gps {
+        let (lat, lon) = gps.coords();
         txt.push_str("GPS LOCATION\n");
         txt.push_str("------------\n");
         txt.push_str(&format!("Latitude: {}\n", lat));
         txt.push_str(&format!("Longitude: {}\n", lon));
+        for (label, value) in gps.details() {
+            txt.push_str(&format!("{}: {}\n", label, value));
+        }
         txt.push_str(&format!(
             "Google Maps: https://maps.google.com/maps?q={},{}\n",
             lat, lon
@@ -110,12 +163,27 @@ pub fn generate_txt(data: &ImageData) -> String {
         ));
     }
 
+    let timestamps = data.timestamps();
+    if !timestamps.is_empty() {
+        txt.push_str("TIMESTAMPS (ISO 8601)\n");
+        txt.push_str("---------------------\n");
+        for timestamp in &timestamps {
+            txt.push_str(&format!("{}: {}\n", timestamp.kind.label(), timestamp));
+        }
+        txt.push('\n');
+    }
+
     // EXIF data
     if !data.exif_data.is_empty() {
         txt.push_str("METADATA\n");
         txt.push_str("--------\n");
-        for (key, value) in sorted_exif_pairs(data) {
-            txt.push_str(&format!("{}: {}\n", key, value));
+        for (ifd, fields) in group_fields_by_ifd(data) {
+            if let Some(ifd) = ifd {
+                txt.push_str(&format!("\n[{}]\n", ifd.label()));
+            }
+            for (key, value) in fields {
+                txt.push_str(&format!("{}: {}\n", key, value));
+            }
         }
     } else {
         txt.push_str("METADATA\n");
@@ -138,10 +206,14 @@ pub fn generate_md(data: &ImageData) -> String {
     }
     out.push('\n');
 
-    if let Some((lat, lon)) = data.gps_coords {
+    if let Some(gps) = &data.gps {
+        let (lat, lon) = gps.coords();
         out.push_str("## GPS Location\n");
         let _ = writeln!(out, "- Latitude: {}", lat);
         let _ = writeln!(out, "- Longitude: {}", lon);
+        for (label, value) in gps.details() {
+            let _ = writeln!(out, "- {}: {}", label, value);
+        }
         let _ = writeln!(
             out,
             "- Google Maps: https://maps.google.com/maps?q={},{}",
@@ -160,9 +232,23 @@ pub fn generate_md(data: &ImageData) -> String {
         out.push('\n');
     }
 
+    let timestamps = data.timestamps();
+    if !timestamps.is_empty() {
+        out.push_str("## Timestamps\n");
+        for timestamp in &timestamps {
+            let _ = writeln!(out, "- {}: {}", timestamp.kind.label(), timestamp);
+        }
+        out.push('\n');
+    }
+
     out.push_str("## Metadata\n");
-    for (k, v) in sorted_exif_pairs(data) {
-        let _ = writeln!(out, "- {}: {}", k, v);
+    for (ifd, fields) in group_fields_by_ifd(data) {
+        if let Some(ifd) = ifd {
+            let _ = writeln!(out, "\n### {}", ifd.label());
+        }
+        for (k, v) in fields {
+            let _ = writeln!(out, "- {}: {}", k, v);
+        }
     }
     out
 }
@@ -175,19 +261,65 @@ pub fn generate_yaml(data: &ImageData) -> String {
     if let (Some(w), Some(h)) = (data.width, data.height) {
         let _ = writeln!(out, "dimensions: \"{}x{}\"", w, h);
     }
-    if let Some((lat, lon)) = data.gps_coords {
+    if let Some(gps) = &data.gps {
         let _ = writeln!(out, "gps:");
-        let _ = writeln!(out, "  lat: {}", lat);
-        let _ = writeln!(out, "  lon: {}", lon);
+        let _ = writeln!(out, "  lat: {}", gps.latitude);
+        let _ = writeln!(out, "  lon: {}", gps.longitude);
+        if let Some(altitude) = gps.altitude {
+            let _ = writeln!(out, "  altitude: {}", altitude);
+        }
+        if let Some(direction) = gps.img_direction {
+            let _ = writeln!(out, "  direction: {}", direction);
+        }
+        if let Some(speed) = gps.speed_kmh {
+            let _ = writeln!(out, "  speed_kmh: {}", speed);
+        }
+        if let Some(timestamp) = &gps.timestamp {
+            let _ = writeln!(out, "  timestamp: \"{}\"", timestamp);
+        }
+        if let Some(error) = gps.horizontal_error {
+            let _ = writeln!(out, "  horizontal_error: {}", error);
+        }
+        if let Some(datum) = &gps.map_datum {
+            let _ = writeln!(out, "  datum: \"{}\"", datum.replace('"', "\\\""));
+        }
     }
-    if !data.exif_data.is_empty() {
-        out.push_str("exif:\n");
-        for (k, v) in sorted_exif_pairs(data) {
+    let timestamps = data.timestamps();
+    if !timestamps.is_empty() {
+        out.push_str("timestamps:\n");
+        for timestamp in &timestamps {
+            let _ = writeln!(
+                out,
+                "  {}: \"{}\"",
+                timestamp.kind.label().to_lowercase(),
+                timestamp
+            );
+        }
+    }
+    // Fields without an IFD under `exif`, the rest under `ifds.<id>`
+    let mut in_ifds = false;
+    for (ifd, fields) in group_fields_by_ifd(data) {
+        let indent = match ifd {
+            None => {
+                out.push_str("exif:\n");
+                "  "
+            }
+            Some(ifd) => {
+                if !in_ifds {
+                    out.push_str("ifds:\n");
+                    in_ifds = true;
+                }
+                let _ = writeln!(out, "  {}:", ifd.id());
+                "    "
+            }
+        };
+        for (k, v) in fields {
             let _ = writeln!(
                 out,
-                "  \"{}\": \"{}\"",
+                "{}\"{}\": \"{}\"",
+                indent,
                 k.replace('"', "\\\""),
-                v.replace('"', "\\\"")
+                v.display.replace('"', "\\\"")
             );
         }
     }
@@ -204,20 +336,101 @@ pub fn generate_xml(data: &ImageData) -> String {
     if let (Some(w), Some(h)) = (data.width, data.height) {
         let _ = writeln!(out, "  <dimensions>{}x{}</dimensions>", w, h);
     }
-    if let Some((lat, lon)) = data.gps_coords {
+    if let Some(gps) = &data.gps {
         out.push_str("  <gps>\n");
-        let _ = writeln!(out, "    <lat>{}</lat>", lat);
-        let _ = writeln!(out, "    <lon>{}</lon>", lon);
+        let _ = writeln!(out, "    <lat>{}</lat>", gps.latitude);
+        let _ = writeln!(out, "    <lon>{}</lon>", gps.longitude);
+        if let Some(altitude) = gps.altitude {
+            let _ = writeln!(out, "    <altitude unit=\"m\">{}</altitude>", altitude);
+        }
+        if let Some(direction) = gps.img_direction {
+            let reference = gps
+                .img_direction_ref
+                .as_deref()
+                .map(|r| format!(" ref=\"{}\"", xml_escape(r)))
+                .unwrap_or_default();
+            let _ = writeln!(out, "    <direction{}>{}</direction>", reference, direction);
+        }
+        if let Some(speed) = gps.speed_kmh {
+            let _ = writeln!(out, "    <speed unit=\"km/h\">{}</speed>", speed);
+        }
+        if let Some(timestamp) = &gps.timestamp {
+            let _ = writeln!(
+                out,
+                "    <timestamp>{}</timestamp>",
+                xml_escape(&timestamp.to_iso8601())
+            );
+        }
+        if let Some(error) = gps.horizontal_error {
+            let _ = writeln!(out, "    <accuracy unit=\"m\">{}</accuracy>", error);
+        }
+        if let Some(datum) = &gps.map_datum {
+            let _ = writeln!(out, "    <datum>{}</datum>", xml_escape(datum));
+        }
+        if let Some(method) = &gps.processing_method {
+            let _ = writeln!(out, "    <method>{}</method>", xml_escape(method));
+        }
         out.push_str("  </gps>\n");
     }
+    let timestamps = data.timestamps();
+    if !timestamps.is_empty() {
+        out.push_str("  <timestamps>\n");
+        for timestamp in &timestamps {
+            let _ = writeln!(
+                out,
+                "    <timestamp kind=\"{}\" sources=\"{}\">{}</timestamp>",
+                timestamp.kind.label(),
+                xml_escape(&timestamp.sources.join(" ")),
+                timestamp
+            );
+        }
+        out.push_str("  </timestamps>\n");
+    }
     if !data.exif_data.is_empty() {
         out.push_str("  <exif>\n");
-        for (k, v) in sorted_exif_pairs(data) {
+        for (_, k, v) in grouped_exif_pairs(data) {
+            // Numeric values also carry their real number(s) and unit as attributes
+            let mut attrs = String::new();
+            if let Some(numbers) = v.as_f64_vec().filter(|_| v.is_numeric()) {
+                let joined: Vec<String> = numbers.iter().map(|n| n.to_string()).collect();
+                let _ = write!(attrs, " value=\"{}\"", joined.join(" "));
+                if let Some(unit) = &v.unit {
+                    let _ = write!(attrs, " unit=\"{}\"", xml_escape(unit));
+                }
+            }
+            match &v.source {
+                Some(MetadataSource::Exif { ifd, tag }) => {
+                    let _ = write!(attrs, " ifd=\"{}\" id=\"0x{:04X}\"", ifd.id(), tag);
+                }
+                Some(MetadataSource::Tiff { ifd, tag }) => {
+                    let _ = write!(attrs, " ifd=\"{}\" id=\"0x{:04X}\"", xml_escape(ifd), tag);
+                }
+                Some(MetadataSource::Iptc { record, dataset }) => {
+                    let _ = write!(attrs, " id=\"{}:{}\"", record, dataset);
+                }
+                Some(MetadataSource::MakerNote { tag, .. }) => {
+                    let _ = write!(attrs, " id=\"0x{:04X}\"", tag);
+                }
+                Some(MetadataSource::Icc { tag })
+                | Some(MetadataSource::Png { chunk: tag })
+                | Some(MetadataSource::Bmff { box_type: tag })
+                | Some(MetadataSource::Svg { node: tag }) => {
+                    let _ = write!(attrs, " id=\"{}\"", xml_escape(tag));
+                }
+                Some(MetadataSource::Pdf { object }) => {
+                    let _ = write!(attrs, " id=\"{}\"", object);
+                }
+                Some(MetadataSource::Xmp { namespace }) => {
+                    let _ = write!(attrs, " ns=\"{}\"", xml_escape(namespace));
+                }
+                None => {}
+            }
             let _ = writeln!(
                 out,
-                "    <tag name=\"{}\">{}</tag>",
-                xml_escape(&k),
-                xml_escape(&v)
+                "    <tag name=\"{}\"{}>{}</tag>",
+                xml_escape(k),
+                attrs,
+                xml_escape(&v.display)
             );
         }
         out.push_str("  </exif>\n");
@@ -226,22 +439,67 @@ pub fn generate_xml(data: &ImageData) -> String {
     out
 }
 
+/// JSON value of an image's metadata, with its capture timestamps added as
+/// ISO 8601 under `timestamps`. Fields read from an EXIF IFD move from
+/// `exif_data` to `ifds.<id>`.
+fn json_value(data: &ImageData) -> serde_json::Value {
+    let mut value = serde_json::to_value(data).unwrap_or_default();
+    let Some(object) = value.as_object_mut() else {
+        return value;
+    };
+    let timestamps = data.timestamps();
+    if !timestamps.is_empty() {
+        object.insert(
+            "timestamps".to_string(),
+            serde_json::to_value(timestamps).unwrap_or_default(),
+        );
+    }
+    let mut exif_data = serde_json::Map::new();
+    let mut ifds = serde_json::Map::new();
+    for (ifd, fields) in group_fields_by_ifd(data) {
+        let fields: serde_json::Map<_, _> = fields
+            .into_iter()
+            .map(|(k, v)| (k.clone(), serde_json::to_value(v).unwrap_or_default()))
+            .collect();
+        match ifd {
+            Some(ifd) => {
+                ifds.insert(ifd.id().to_string(), fields.into());
+            }
+            None => exif_data = fields,
+        }
+    }
+    if !ifds.is_empty() {
+        object.insert("exif_data".to_string(), exif_data.into());
+        object.insert("ifds".to_string(), ifds.into());
+    }
+    value
+}
+
+/// Generate a pretty-printed JSON export of one image.
+pub fn generate_json(data: &ImageData) -> String {
+    serde_json::to_string_pretty(&json_value(data)).unwrap_or_else(|_| "{}".to_string())
+}
+
 /// Generate a combined JSON export of multiple images as a single JSON array.
 pub fn generate_json_batch(items: &[std::rc::Rc<ImageData>]) -> String {
-    let items_refs: Vec<&ImageData> = items.iter().map(|rc| &**rc).collect();
-    serde_json::to_string_pretty(&items_refs).unwrap_or_else(|_| "[]".to_string())
+    let values: Vec<serde_json::Value> = items.iter().map(|item| json_value(item)).collect();
+    serde_json::to_string_pretty(&values).unwrap_or_else(|_| "[]".to_string())
 }
 
 /// Generate a combined CSV table for multiple images.
-/// Columns: Filename, File Size (human), Width, Height, GPS Latitude, GPS Longitude, then sorted EXIF keys (union across items).
+/// Columns: Filename, File Size (human), Width, Height, GPS Latitude, GPS Longitude, GPS Altitude, GPS Direction, then the union of metadata keys across items, grouped by IFD and sorted within each group.
 pub fn generate_csv_batch(items: &[std::rc::Rc<ImageData>]) -> String {
-    // Collect union of EXIF keys for stable header ordering
-    let mut exif_keys: BTreeSet<String> = BTreeSet::new();
+    // Union of metadata keys, grouped by the IFD they were first seen in
+    let mut columns: BTreeSet<(usize, &String)> = BTreeSet::new();
+    let mut seen: BTreeSet<&String> = BTreeSet::new();
     for item in items.iter() {
-        for k in item.exif_data.keys() {
-            exif_keys.insert(k.clone());
+        for (ifd, k, _) in grouped_exif_pairs(item) {
+            if seen.insert(k) {
+                columns.insert((group_rank(ifd), k));
+            }
         }
     }
+    let exif_keys: Vec<&String> = columns.into_iter().map(|(_, k)| k).collect();
 
     // Build header
     let mut out = String::new();
@@ -252,8 +510,10 @@ pub fn generate_csv_batch(items: &[std::rc::Rc<ImageData>]) -> String {
         "Height".into(),
         "GPS Latitude".into(),
         "GPS Longitude".into(),
+        "GPS Altitude".into(),
+        "GPS Direction".into(),
     ];
-    header.extend(exif_keys.iter().cloned());
+    header.extend(exif_keys.iter().map(|k| k.to_string()));
 
     // Write header row with CSV quoting
     for (i, col) in header.iter().enumerate() {
@@ -274,8 +534,16 @@ pub fn generate_csv_batch(items: &[std::rc::Rc<ImageData>]) -> String {
             Some(format_file_size(item.size)),
             item.width.map(|w| w.to_string()),
             item.height.map(|h| h.to_string()),
-            item.gps_coords.map(|(lat, _)| lat.to_string()),
-            item.gps_coords.map(|(_, lon)| lon.to_string()),
+            item.gps.as_ref().map(|g| g.latitude.to_string()),
+            item.gps.as_ref().map(|g| g.longitude.to_string()),
+            item.gps
+                .as_ref()
+                .and_then(|g| g.altitude)
+                .map(|a| a.to_string()),
+            item.gps
+                .as_ref()
+                .and_then(|g| g.img_direction)
+                .map(|d| d.to_string()),
         ];
 
         // Emit basic cols
@@ -292,7 +560,11 @@ pub fn generate_csv_batch(items: &[std::rc::Rc<ImageData>]) -> String {
         // Emit EXIF cells in header order
         for key in &exif_keys {
             out.push(',');
-            let val = item.exif_data.get(key).cloned().unwrap_or_default();
+            let val = item
+                .exif_data
+                .get(*key)
+                .map(|v| v.display.clone())
+                .unwrap_or_default();
             out.push('"');
             out.push_str(&val.replace('"', "\"\""));
             out.push('"');
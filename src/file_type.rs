@@ -0,0 +1,224 @@
+//! Content-based file type detection.
+//!
+//! Browsers derive a file's MIME type from its extension, so a PNG renamed
+//! to `.jpg` arrives labelled `image/jpeg`. The type is instead detected from
+//! the file's own signature: magic bytes for JPEG, PNG, GIF, WebP, JPEG XL
+//! and PDF, the byte order mark and first directory for TIFF and camera RAW,
+//! the `ftyp` brands for HEIF, AVIF and movies, and the root element for
+//! SVG. The declared type and the extension are only used when the content
+//! is not recognised.
+
+use crate::{container, raw, svg, video};
+use serde::Serialize;
+
+/// `ftyp` brands of AVIF images and image sequences.
+const AVIF_BRANDS: &[&[u8; 4]] = &[b"avif", b"avis"];
+/// `ftyp` brands of HEIF images and image sequences. `mif1` and `msf1` are
+/// the generic HEIF brands that AVIF files may also use as their major
+/// brand, so the compatible brands decide between the two.
+const HEIF_BRANDS: &[&[u8; 4]] = &[
+    b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx", b"mif1", b"msf1", b"mif2",
+];
+
+/// Returns `true` if the data starts with a BigTIFF header.
+fn is_big_tiff(data: &[u8]) -> bool {
+    data.starts_with(b"II+\0") || data.starts_with(b"MM\0+")
+}
+
+/// MIME type of an ISO base media file from its `ftyp` brands.
+fn isobmff_mime_type(data: &[u8]) -> Option<&'static str> {
+    if !container::is_isobmff(data) {
+        // QuickTime files from older cameras have no `ftyp` box
+        return video::mime_type(data);
+    }
+    let ftyp = container::iso_boxes(data)
+        .into_iter()
+        .find(|b| &b.id == b"ftyp")?;
+    let major = ftyp.data.get(0..4)?;
+    // Compatible brands follow the major brand and minor version
+    let brands: Vec<&[u8]> = std::iter::once(major)
+        .chain(ftyp.data.get(8..).unwrap_or_default().chunks_exact(4))
+        .collect();
+    let has = |table: &[&[u8; 4]]| brands.iter().any(|b| table.iter().any(|t| t[..] == **b));
+
+    if AVIF_BRANDS.iter().any(|b| b[..] == *major)
+        || (matches!(major, b"mif1" | b"msf1") && has(AVIF_BRANDS))
+    {
+        Some("image/avif")
+    } else if HEIF_BRANDS.iter().any(|b| b[..] == *major) {
+        Some("image/heif")
+    } else if let Some(mime) = video::mime_type(data) {
+        Some(mime)
+    } else if has(AVIF_BRANDS) {
+        Some("image/avif")
+    } else if has(HEIF_BRANDS) {
+        Some("image/heif")
+    } else {
+        None
+    }
+}
+
+/// Detect a supported file type from the file's content alone.
+pub fn detect_mime_type(data: &[u8]) -> Option<&'static str> {
+    if let Some(format) = raw::detect_format(data) {
+        return Some(format.mime_type());
+    }
+    if container::is_jpeg(data) {
+        Some("image/jpeg")
+    } else if container::is_png(data) {
+        Some("image/png")
+    } else if container::is_gif(data) {
+        Some("image/gif")
+    } else if container::is_webp(data) {
+        Some("image/webp")
+    } else if container::is_tiff(data) || is_big_tiff(data) {
+        Some("image/tiff")
+    } else if container::is_jxl(data) {
+        Some("image/jxl")
+    } else if data.starts_with(container::PDF_SIGNATURE) {
+        Some("application/pdf")
+    } else if let Some(mime) = isobmff_mime_type(data) {
+        Some(mime)
+    } else if svg::is_svg(data) {
+        Some("image/svg+xml")
+    } else if container::is_pdf(data) {
+        // Some writers put junk before the header, which readers tolerate
+        Some("application/pdf")
+    } else {
+        None
+    }
+}
+
+/// MIME type implied by a file name's extension.
+pub fn extension_mime_type(name: &str) -> Option<&'static str> {
+    let lower = name.to_lowercase();
+    let (_, extension) = lower.rsplit_once('.')?;
+    Some(match extension {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "tiff" | "tif" => "image/tiff",
+        "heif" | "heic" => "image/heif",
+        "avif" => "image/avif",
+        "jxl" => "image/jxl",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "mp4" | "m4v" => "video/mp4",
+        "mov" => "video/quicktime",
+        "3gp" => "video/3gpp",
+        "3g2" => "video/3gpp2",
+        _ => raw::RawFormat::from_extension(&lower)?.mime_type(),
+    })
+}
+
+/// Map the non-standard MIME types some browsers report onto the ones used
+/// here, e.g. `image/heic` to `image/heif`.
+pub fn canonical_mime_type(mime: &str) -> &str {
+    match mime {
+        "image/jpg" | "image/pjpeg" => "image/jpeg",
+        "image/heic" | "image/heic-sequence" | "image/heif-sequence" => "image/heif",
+        "image/avif-sequence" => "image/avif",
+        "image/x-png" => "image/png",
+        "video/x-m4v" => "video/mp4",
+        other => other,
+    }
+}
+
+/// File extension the binary cleaner expects for a MIME type, or `None` for
+/// types it cannot clean.
+pub fn cleaner_extension(mime: &str) -> Option<&'static str> {
+    Some(match mime {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/tiff" => "tiff",
+        "image/heif" => "heic",
+        "image/avif" => "avif",
+        "image/jxl" => "jxl",
+        "image/svg+xml" => "svg",
+        "application/pdf" => "pdf",
+        _ => return None,
+    })
+}
+
+/// What a file claims to be and what its content says it is.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TypeCheck {
+    /// MIME type reported by the browser or archive, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declared: Option<String>,
+    /// MIME type implied by the file name's extension.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension: Option<String>,
+    /// MIME type detected from the content.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detected: Option<String>,
+}
+
+impl TypeCheck {
+    /// Whether the declared type or the extension disagrees with the content.
+    ///
+    /// Nothing is reported when the content is not recognised, or when the
+    /// extension of a camera RAW file names the TIFF it is built on.
+    pub fn is_mismatch(&self) -> bool {
+        let Some(detected) = &self.detected else {
+            return false;
+        };
+        [&self.declared, &self.extension]
+            .into_iter()
+            .flatten()
+            .any(|claimed| {
+                claimed != detected && !(claimed == "image/tiff" && raw::is_raw_mime_type(detected))
+            })
+    }
+
+    /// The type the file is handled as: the detected type, or failing that
+    /// the declared type, or failing that the one implied by the extension.
+    pub fn resolved(&self) -> &str {
+        self.detected
+            .as_deref()
+            .or(self.declared.as_deref())
+            .or(self.extension.as_deref())
+            .unwrap_or("application/octet-stream")
+    }
+
+    /// One-line summary such as `declared image/jpeg, detected image/png`.
+    pub fn summary(&self) -> String {
+        let claimed = match (&self.declared, &self.extension) {
+            (Some(declared), Some(extension)) if declared != extension => {
+                format!("declared {}, extension {}", declared, extension)
+            }
+            (Some(declared), _) => format!("declared {}", declared),
+            (None, Some(extension)) => format!("extension {}", extension),
+            (None, None) => "no declared type".to_string(),
+        };
+        match &self.detected {
+            Some(detected) => format!("{}, detected {}", claimed, detected),
+            None => format!("{}, content not recognised", claimed),
+        }
+    }
+}
+
+/// Declared types that say nothing about the content. Browsers and archives
+/// report these for files they cannot identify.
+const UNKNOWN_MIME_TYPES: &[&str] = &["", "application/octet-stream"];
+
+/// Compare a file's declared type and extension with its content.
+///
+/// A declared type of [`UNKNOWN_MIME_TYPES`] is treated as no declared type,
+/// so it is never reported as a mismatch. A TIFF-based file with a RAW extension is taken to be that RAW format even
+/// when its camera make is not recognised, since the header alone cannot
+/// tell the formats apart.
+pub fn check_type(name: &str, declared: &str, data: &[u8]) -> TypeCheck {
+    TypeCheck {
+        declared: Some(canonical_mime_type(declared.trim()))
+            .filter(|mime| !UNKNOWN_MIME_TYPES.contains(mime))
+            .map(str::to_string),
+        extension: extension_mime_type(name).map(str::to_string),
+        detected: raw::raw_mime_type(name, data)
+            .or_else(|| detect_mime_type(data))
+            .map(str::to_string),
+    }
+}
@@ -0,0 +1,483 @@
+//! HEIF/HEIC and AVIF item parsing.
+//!
+//! HEIF files are ISO base media files whose `meta` box describes a set of
+//! items: coded images, image grids, thumbnails, auxiliary images (alpha,
+//! depth, HDR gain maps) and metadata items such as `Exif` and XMP (`mime`
+//! items with type `application/rdf+xml`). `iinf` names the items, `iloc`
+//! says where their bytes are, `iprp` attaches properties such as the `ispe`
+//! image size and `iref` links thumbnails and auxiliary images to the image
+//! they belong to.
+
+use crate::bmff::{Cursor, full_box};
+use crate::container::{self, Chunk};
+use crate::diagnostics::Diagnostic;
+use crate::limits::Budget;
+use crate::types::{MetadataSource, MetadataValue, RawValue};
+use crate::video;
+use std::collections::HashMap;
+
+/// Content type of XMP `mime` items.
+pub const XMP_CONTENT_TYPE: &str = "application/rdf+xml";
+
+/// An item declared in the `meta` box.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HeifItem {
+    pub id: u32,
+    /// Four character item type, e.g. `hvc1`, `av01`, `grid`, `Exif`, `mime`.
+    pub item_type: String,
+    pub name: String,
+    /// Content type of `mime` items.
+    pub content_type: Option<String>,
+    /// Hidden items are not meant to be displayed on their own.
+    pub hidden: bool,
+    /// Width and height from the item's `ispe` property.
+    pub dimensions: Option<(u32, u32)>,
+    /// Auxiliary type URN from the item's `auxC` property.
+    pub aux_type: Option<String>,
+    /// `iloc` construction method: 0 for file offsets, 1 for `idat` offsets.
+    construction_method: u8,
+    /// `(offset, length)` extents from `iloc`, with the base offset applied.
+    extents: Vec<(u64, u64)>,
+}
+
+/// A typed reference between items from the `iref` box.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemReference {
+    /// Reference type, e.g. `thmb`, `auxl`, `cdsc` or `dimg`.
+    pub kind: String,
+    pub from: u32,
+    pub to: Vec<u32>,
+}
+
+/// The parsed structure of a HEIF or AVIF file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HeifFile {
+    pub major_brand: String,
+    pub compatible_brands: Vec<String>,
+    pub primary_item: Option<u32>,
+    pub items: Vec<HeifItem>,
+    pub references: Vec<ItemReference>,
+    /// Payload of the `idat` box, holding items with construction method 1.
+    idat: Option<Vec<u8>>,
+}
+
+fn fourcc(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn find_box<'a>(boxes: &[Chunk<'a>], id: &[u8; 4]) -> Option<Chunk<'a>> {
+    boxes.iter().find(|b| &b.id == id).copied()
+}
+
+/// Parse `iinf` item info entries (`infe` version 2 and 3).
+fn parse_iinf(data: &[u8]) -> Vec<HeifItem> {
+    let Some((version, _, payload)) = full_box(data) else {
+        return Vec::new();
+    };
+    let entries = payload.get(if version == 0 { 2 } else { 4 }..);
+    container::iso_boxes(entries.unwrap_or_default())
+        .into_iter()
+        .filter(|b| &b.id == b"infe")
+        .filter_map(|infe| {
+            let (version, flags, payload) = full_box(infe.data)?;
+            if version < 2 {
+                return None;
+            }
+            let mut cursor = Cursor::new(payload);
+            let id = cursor.item_id(version >= 3)?;
+            cursor.u16()?; // protection index
+            let item_type = fourcc(cursor.take(4)?);
+            let name = cursor.string();
+            let content_type = (item_type == "mime").then(|| cursor.string());
+            Some(HeifItem {
+                id,
+                item_type,
+                name,
+                content_type,
+                hidden: flags & 1 != 0,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// An `iloc` entry: item ID, construction method and `(offset, length)` extents.
+type ItemLocation = (u32, u8, Vec<(u64, u64)>);
+
+/// Parse the item locations of an `iloc` box.
+fn parse_iloc(data: &[u8]) -> Vec<ItemLocation> {
+    let mut locations = Vec::new();
+    let Some((version, _, payload)) = full_box(data) else {
+        return locations;
+    };
+    let mut cursor = Cursor::new(payload);
+    let (Some(sizes), Some(more_sizes)) = (cursor.u8(), cursor.u8()) else {
+        return locations;
+    };
+    let (offset_size, length_size) = (sizes >> 4, sizes & 0x0F);
+    let base_offset_size = more_sizes >> 4;
+    let index_size = if version >= 1 { more_sizes & 0x0F } else { 0 };
+    let count = match version {
+        0 | 1 => cursor.u16().map(u32::from),
+        _ => cursor.u32(),
+    };
+
+    // Zero-sized fields let extents take no bytes, so count them instead
+    let mut budget = Budget::blocks("HEIF extent count");
+    for _ in 0..count.unwrap_or(0) {
+        let parsed = (|| {
+            let id = cursor.item_id(version >= 2)?;
+            let method = if version >= 1 {
+                (cursor.u16()? & 0x0F) as u8
+            } else {
+                0
+            };
+            cursor.u16()?; // data reference index
+            let base = cursor.sized(base_offset_size)?;
+            let extent_count = cursor.u16()?;
+            let mut extents = Vec::new();
+            for _ in 0..extent_count {
+                budget.spend().ok()?;
+                cursor.sized(index_size)?;
+                let offset = cursor.sized(offset_size)?;
+                let length = cursor.sized(length_size)?;
+                extents.push((base.checked_add(offset)?, length));
+            }
+            Some((id, method, extents))
+        })();
+        match parsed {
+            Some(location) => locations.push(location),
+            None => break,
+        }
+    }
+    locations
+}
+
+/// Parse `iprp`, applying `ispe` and `auxC` properties to their items.
+fn apply_properties(data: &[u8], items: &mut [HeifItem]) {
+    let boxes = container::iso_boxes(data);
+    let properties = find_box(&boxes, b"ipco")
+        .map(|ipco| container::iso_boxes(ipco.data))
+        .unwrap_or_default();
+    let Some((version, flags, payload)) = find_box(&boxes, b"ipma").and_then(|b| full_box(b.data))
+    else {
+        return;
+    };
+
+    let mut cursor = Cursor::new(payload);
+    for _ in 0..cursor.u32().unwrap_or(0) {
+        let Some(id) = cursor.item_id(version >= 1) else {
+            break;
+        };
+        let Some(count) = cursor.u8() else {
+            break;
+        };
+        for _ in 0..count {
+            // Property indices are 1-based; the top bit marks essential properties
+            let index = if flags & 1 != 0 {
+                cursor.u16().map(|v| (v & 0x7FFF) as usize)
+            } else {
+                cursor.u8().map(|v| (v & 0x7F) as usize)
+            };
+            let Some(property) = index
+                .and_then(|i| i.checked_sub(1))
+                .and_then(|i| properties.get(i))
+            else {
+                continue;
+            };
+            let Some(item) = items.iter_mut().find(|item| item.id == id) else {
+                continue;
+            };
+            match &property.id {
+                b"ispe" => {
+                    let mut c = Cursor::new(full_box(property.data).map_or(&[][..], |f| f.2));
+                    if let (Some(w), Some(h)) = (c.u32(), c.u32()) {
+                        item.dimensions = Some((w, h));
+                    }
+                }
+                b"auxC" => {
+                    if let Some((_, _, payload)) = full_box(property.data) {
+                        item.aux_type = Some(Cursor::new(payload).string());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Parse `iref` references (16-bit IDs for version 0, 32-bit otherwise).
+fn parse_iref(data: &[u8]) -> Vec<ItemReference> {
+    let Some((version, _, payload)) = full_box(data) else {
+        return Vec::new();
+    };
+    container::iso_boxes(payload)
+        .into_iter()
+        .filter_map(|reference| {
+            let mut cursor = Cursor::new(reference.data);
+            let from = cursor.item_id(version >= 1)?;
+            let count = cursor.u16()?;
+            let to = (0..count)
+                .map(|_| cursor.item_id(version >= 1))
+                .collect::<Option<Vec<_>>>()?;
+            Some(ItemReference {
+                kind: fourcc(&reference.id),
+                from,
+                to,
+            })
+        })
+        .collect()
+}
+
+/// Parse the `ftyp` and `meta` boxes of a HEIF or AVIF file.
+pub fn parse_heif(bytes: &[u8]) -> Result<HeifFile, String> {
+    read_heif(bytes, &mut Vec::new())
+}
+
+fn read_heif(bytes: &[u8], diagnostics: &mut Vec<Diagnostic>) -> Result<HeifFile, String> {
+    if !container::is_isobmff(bytes) {
+        return Err("Not an ISO base media file".to_string());
+    }
+    let (boxes, problem) = container::iso_boxes_checked(bytes);
+    diagnostics.extend(problem);
+
+    let mut file = HeifFile::default();
+    if let Some(ftyp) = find_box(&boxes, b"ftyp") {
+        let mut cursor = Cursor::new(ftyp.data);
+        file.major_brand = cursor.take(4).map(fourcc).unwrap_or_default();
+        cursor.u32(); // minor version
+        file.compatible_brands = cursor.rest().chunks_exact(4).map(fourcc).collect();
+    }
+
+    let meta = find_box(&boxes, b"meta").ok_or("No 'meta' box")?;
+    let (_, _, payload) = full_box(meta.data).ok_or("Truncated 'meta' box")?;
+    let children = container::iso_boxes(payload);
+
+    if let Some(pitm) = find_box(&children, b"pitm")
+        && let Some((version, _, payload)) = full_box(pitm.data)
+    {
+        file.primary_item = Cursor::new(payload).item_id(version >= 1);
+    }
+    if let Some(iinf) = find_box(&children, b"iinf") {
+        file.items = parse_iinf(iinf.data);
+    }
+    if let Some(iloc) = find_box(&children, b"iloc") {
+        for (id, method, extents) in parse_iloc(iloc.data) {
+            if let Some(item) = file.items.iter_mut().find(|item| item.id == id) {
+                item.construction_method = method;
+                item.extents = extents;
+            }
+        }
+    }
+    if let Some(iprp) = find_box(&children, b"iprp") {
+        apply_properties(iprp.data, &mut file.items);
+    }
+    if let Some(iref) = find_box(&children, b"iref") {
+        file.references = parse_iref(iref.data);
+    }
+    file.idat = find_box(&children, b"idat").map(|idat| idat.data.to_vec());
+
+    Ok(file)
+}
+
+impl HeifFile {
+    /// Find an item by ID.
+    pub fn item(&self, id: u32) -> Option<&HeifItem> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    /// The primary image item.
+    pub fn primary(&self) -> Option<&HeifItem> {
+        self.item(self.primary_item?)
+    }
+
+    /// Items holding a reference of type `kind` to the item `to`.
+    pub fn referenced_from(&self, kind: &str, to: u32) -> Vec<&HeifItem> {
+        self.references
+            .iter()
+            .filter(|r| r.kind == kind && r.to.contains(&to))
+            .filter_map(|r| self.item(r.from))
+            .collect()
+    }
+
+    /// Auxiliary images (alpha, depth, gain maps) of the primary image.
+    pub fn auxiliary_images(&self) -> Vec<&HeifItem> {
+        match self.primary_item {
+            Some(primary) => self.referenced_from("auxl", primary),
+            None => self.items.iter().filter(|i| i.aux_type.is_some()).collect(),
+        }
+    }
+
+    /// Read an item's bytes by concatenating its extents. Extents that add
+    /// up to more than the source, which only overlapping ones can, are
+    /// rejected.
+    pub fn item_data(&self, bytes: &[u8], item: &HeifItem) -> Option<Vec<u8>> {
+        let source = match item.construction_method {
+            0 => bytes,
+            1 => self.idat.as_deref()?,
+            _ => return None,
+        };
+        let mut data = Vec::new();
+        for &(offset, length) in &item.extents {
+            let start = usize::try_from(offset).ok()?;
+            // A zero length extent runs to the end of the source
+            let end = match length {
+                0 => source.len(),
+                length => start.checked_add(usize::try_from(length).ok()?)?,
+            };
+            let extent = source.get(start..end)?;
+            if data.len() + extent.len() > source.len() {
+                return None;
+            }
+            data.extend_from_slice(extent);
+        }
+        Some(data)
+    }
+}
+
+/// Human readable name of an auxiliary image type URN.
+pub fn auxiliary_type_name(urn: &str) -> String {
+    let lower = urn.to_lowercase();
+    if lower.contains("alpha") || lower.ends_with("auxid:1") {
+        "Alpha".to_string()
+    } else if lower.contains("depth") || lower.contains("disparity") || lower.ends_with("auxid:2") {
+        "Depth".to_string()
+    } else if lower.contains("hdrgainmap") {
+        "HDR Gain Map".to_string()
+    } else if lower.contains("matte") {
+        "Portrait Matte".to_string()
+    } else {
+        urn.to_string()
+    }
+}
+
+/// The TIFF block of the file's `Exif` item, without its header offset
+/// prefix.
+pub fn find_exif(bytes: &[u8]) -> Option<Vec<u8>> {
+    let file = parse_heif(bytes).ok()?;
+    let item = file.items.iter().find(|item| item.item_type == "Exif")?;
+    let data = file.item_data(bytes, item)?;
+    // The payload starts with the offset of the TIFF header, usually past
+    // an "Exif\0\0" prefix
+    let skip = u32::from_be_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    Some(data.get(4usize.checked_add(skip)?..)?.to_vec())
+}
+
+/// The XMP packets stored as `mime` items.
+pub fn find_xmp(bytes: &[u8]) -> Vec<String> {
+    let Ok(file) = parse_heif(bytes) else {
+        return Vec::new();
+    };
+    file.items
+        .iter()
+        .filter(|item| item.content_type.as_deref() == Some(XMP_CONTENT_TYPE))
+        .filter_map(|item| file.item_data(bytes, item))
+        .map(|data| String::from_utf8_lossy(&data).into_owned())
+        .collect()
+}
+
+/// Width and height of the primary image.
+pub fn dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    parse_heif(bytes).ok()?.primary()?.dimensions
+}
+
+/// Convert the file structure into `exif_data` entries keyed `HEIF:<Field>`.
+pub fn heif_to_metadata(file: &HeifFile) -> HashMap<String, MetadataValue> {
+    let mut map = HashMap::new();
+    let mut insert = |name: &str, box_type: &str, value: MetadataValue| {
+        map.insert(
+            format!("HEIF:{}", name),
+            value.with_source(MetadataSource::Bmff {
+                box_type: box_type.to_string(),
+            }),
+        );
+    };
+    let integer =
+        |value: u32| MetadataValue::new(RawValue::Integer(vec![value.into()]), value.to_string());
+
+    if !file.major_brand.is_empty() {
+        insert(
+            "MajorBrand",
+            "ftyp",
+            MetadataValue::text(file.major_brand.clone()),
+        );
+    }
+    if !file.compatible_brands.is_empty() {
+        insert(
+            "CompatibleBrands",
+            "ftyp",
+            MetadataValue::text(file.compatible_brands.join(", ")),
+        );
+    }
+    insert("ItemCount", "iinf", integer(file.items.len() as u32));
+    if let Some(primary) = file.primary() {
+        insert(
+            "PrimaryItem",
+            "pitm",
+            MetadataValue::new(
+                RawValue::Integer(vec![primary.id.into()]),
+                format!("{} ({})", primary.id, primary.item_type),
+            ),
+        );
+        if let Some((width, height)) = primary.dimensions {
+            insert("ImageWidth", "ispe", integer(width));
+            insert("ImageHeight", "ispe", integer(height));
+        }
+        let thumbnails = file.referenced_from("thmb", primary.id);
+        if !thumbnails.is_empty() {
+            insert("ThumbnailCount", "iref", integer(thumbnails.len() as u32));
+        }
+    }
+
+    let auxiliary: Vec<String> = file
+        .auxiliary_images()
+        .iter()
+        .map(|item| {
+            let kind = item
+                .aux_type
+                .as_deref()
+                .map(auxiliary_type_name)
+                .unwrap_or_else(|| "Unknown".to_string());
+            match item.dimensions {
+                Some((w, h)) => format!("{} ({}x{})", kind, w, h),
+                None => kind,
+            }
+        })
+        .collect();
+    if !auxiliary.is_empty() {
+        insert(
+            "AuxiliaryImages",
+            "auxC",
+            MetadataValue::text(auxiliary.join("; ")),
+        );
+    }
+
+    map
+}
+
+/// Extract the HEIF/AVIF file structure as `exif_data` entries.
+pub fn extract_heif_data(bytes: &[u8]) -> HashMap<String, MetadataValue> {
+    parse_heif(bytes)
+        .map(|file| heif_to_metadata(&file))
+        .unwrap_or_default()
+}
+
+/// Extract the HEIF/AVIF file structure as [`extract_heif_data`] does,
+/// reporting a damaged box sequence and a file structure that cannot be
+/// read. Videos share the container and are reported by the video
+/// extractor instead.
+pub fn extract_heif_data_with_diagnostics(
+    bytes: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, MetadataValue> {
+    if !container::is_isobmff(bytes) || video::is_video(bytes) {
+        return extract_heif_data(bytes);
+    }
+    match read_heif(bytes, diagnostics) {
+        Ok(file) => heif_to_metadata(&file),
+        Err(e) => {
+            diagnostics.push(Diagnostic::error("HEIF", None, e));
+            HashMap::new()
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! ICC colour profile discovery and header/tag parsing.
+//!
+//! Embedded profiles are usually one of a handful of standard spaces, but
+//! calibrated monitor profiles carry the display model, calibration date and
+//! sometimes the machine name in their description. This module locates the
+//! profile in JPEG, PNG, WebP and TIFF files and decodes the fields worth
+//! showing to the user.
+
+use crate::diagnostics::Diagnostic;
+use crate::types::{MetadataSource, MetadataValue};
+use crate::{container, limits};
+use std::collections::HashMap;
+
+/// Signature prefixing ICC profile chunks in JPEG APP2 segments.
+pub const JPEG_ICC_SIGNATURE: &[u8] = b"ICC_PROFILE\0";
+/// TIFF tag holding an ICC profile.
+pub const TIFF_ICC_TAG: u16 = 34675;
+
+/// Size of the fixed profile header.
+const HEADER_SIZE: usize = 128;
+
+/// Descriptions of widely distributed profiles that say nothing about the
+/// machine they came from (matched case-insensitively as prefixes).
+pub static STANDARD_PROFILES: &[&str] = &[
+    "srgb",
+    "adobe rgb",
+    "display p3",
+    "dci-p3",
+    "prophoto",
+    "rommrgb",
+    "rec. 2020",
+    "rec.2020",
+    "itu-r bt.2020",
+    "rec. 709",
+    "generic",
+    "gray gamma",
+    "dot gain",
+    "linear",
+    "coated fogra",
+    "uncoated fogra",
+    "iso coated",
+    "u.s. web coated",
+    "japan color",
+    "camera rgb profile",
+    "apple rgb",
+    "colormatch rgb",
+    "wide gamut rgb",
+    "ecirgb",
+];
+
+/// Decoded header and descriptive tags of an ICC profile.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IccProfile {
+    pub size: u32,
+    pub cmm_type: String,
+    pub version: String,
+    pub device_class: String,
+    pub color_space: String,
+    pub connection_space: String,
+    pub created: Option<String>,
+    pub platform: String,
+    pub device_manufacturer: String,
+    pub device_model: String,
+    pub rendering_intent: String,
+    pub creator: String,
+    pub profile_id: Option<String>,
+    pub description: Option<String>,
+    pub copyright: Option<String>,
+    pub device_manufacturer_description: Option<String>,
+    pub device_model_description: Option<String>,
+}
+
+/// Returns `true` if a profile description names a common standard profile.
+pub fn is_standard_profile(description: &str) -> bool {
+    let description = description.trim().to_lowercase();
+    STANDARD_PROFILES
+        .iter()
+        .any(|standard| description.starts_with(standard))
+}
+
+fn signature(data: &[u8]) -> String {
+    String::from_utf8_lossy(data)
+        .trim_matches(|c: char| c == '\0' || c == ' ')
+        .to_string()
+}
+
+fn be_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn be_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
+
+/// Human readable name of a profile/device class signature.
+pub fn device_class_name(sig: &str) -> String {
+    match sig {
+        "scnr" => "Input Device".to_string(),
+        "mntr" => "Display Device".to_string(),
+        "prtr" => "Output Device".to_string(),
+        "link" => "DeviceLink".to_string(),
+        "spac" => "ColorSpace Conversion".to_string(),
+        "abst" => "Abstract".to_string(),
+        "nmcl" => "Named Color".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn rendering_intent_name(intent: u32) -> String {
+    match intent {
+        0 => "Perceptual".to_string(),
+        1 => "Media-Relative Colorimetric".to_string(),
+        2 => "Saturation".to_string(),
+        3 => "ICC-Absolute Colorimetric".to_string(),
+        other => format!("Unknown ({})", other),
+    }
+}
+
+fn platform_name(sig: &str) -> String {
+    match sig {
+        "APPL" => "Apple".to_string(),
+        "MSFT" => "Microsoft".to_string(),
+        "SGI" => "Silicon Graphics".to_string(),
+        "SUNW" => "Sun Microsystems".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Decode a `desc`, `mluc` or `text` tag into a string.
+fn decode_text_tag(data: &[u8]) -> Option<String> {
+    let text = match data.get(0..4)? {
+        b"desc" => {
+            let len = be_u32(data, 8)? as usize;
+            let bytes = 12usize
+                .checked_add(len)
+                .and_then(|end| data.get(12..end))
+                .or_else(|| data.get(12..))?;
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+        b"text" => String::from_utf8_lossy(data.get(8..)?).into_owned(),
+        b"mluc" => {
+            let count = be_u32(data, 8)? as usize;
+            let record_size = be_u32(data, 12)? as usize;
+            // Prefer English, otherwise take the first record
+            let mut chosen = None;
+            for n in 0..count.min(256) {
+                let record = n.checked_mul(record_size)?.checked_add(16)?;
+                let lang = data.get(record..record.checked_add(2)?)?;
+                let len = be_u32(data, record.checked_add(4)?)? as usize;
+                let offset = be_u32(data, record.checked_add(8)?)? as usize;
+                if chosen.is_none() || lang == b"en" {
+                    chosen = Some((offset, len));
+                }
+                if lang == b"en" {
+                    break;
+                }
+            }
+            let (offset, len) = chosen?;
+            let units: Vec<u16> = data
+                .get(offset..offset.checked_add(len)?)?
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => return None,
+    };
+    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Parse an ICC profile's header and descriptive tags.
+pub fn parse_icc_profile(data: &[u8]) -> Result<IccProfile, String> {
+    if data.len() < HEADER_SIZE {
+        return Err("ICC profile too short".to_string());
+    }
+    if &data[36..40] != b"acsp" {
+        return Err("ICC profile missing 'acsp' signature".to_string());
+    }
+
+    let year = be_u16(data, 24).unwrap_or(0);
+    let created = (year != 0).then(|| {
+        let field = |i: usize| be_u16(data, 24 + i * 2).unwrap_or(0);
+        format!(
+            "{:04}:{:02}:{:02} {:02}:{:02}:{:02}",
+            year,
+            field(1),
+            field(2),
+            field(3),
+            field(4),
+            field(5)
+        )
+    });
+    let profile_id = data[84..100]
+        .iter()
+        .any(|&b| b != 0)
+        .then(|| data[84..100].iter().map(|b| format!("{:02x}", b)).collect());
+
+    let mut profile = IccProfile {
+        size: be_u32(data, 0).unwrap_or(0),
+        cmm_type: signature(&data[4..8]),
+        version: format!("{}.{}.{}", data[8], data[9] >> 4, data[9] & 0x0F),
+        device_class: device_class_name(&signature(&data[12..16])),
+        color_space: signature(&data[16..20]),
+        connection_space: signature(&data[20..24]),
+        created,
+        platform: platform_name(&signature(&data[40..44])),
+        device_manufacturer: signature(&data[48..52]),
+        device_model: signature(&data[52..56]),
+        rendering_intent: rendering_intent_name(be_u32(data, 64).unwrap_or(0)),
+        creator: signature(&data[80..84]),
+        profile_id,
+        ..Default::default()
+    };
+
+    let tag_count = be_u32(data, HEADER_SIZE).unwrap_or(0) as usize;
+    for n in 0..tag_count.min(1024) {
+        let entry = HEADER_SIZE + 4 + n * 12;
+        let Some(sig) = data.get(entry..entry + 4) else {
+            break;
+        };
+        let offset = be_u32(data, entry + 4).unwrap_or(0) as usize;
+        let size = be_u32(data, entry + 8).unwrap_or(0) as usize;
+        let Some(tag) = offset
+            .checked_add(size)
+            .and_then(|end| data.get(offset..end))
+        else {
+            continue;
+        };
+        let slot = match sig {
+            b"desc" => &mut profile.description,
+            b"cprt" => &mut profile.copyright,
+            b"dmnd" => &mut profile.device_manufacturer_description,
+            b"dmdd" => &mut profile.device_model_description,
+            _ => continue,
+        };
+        *slot = decode_text_tag(tag);
+    }
+
+    Ok(profile)
+}
+
+/// Locate and reassemble the ICC profile embedded in a file.
+pub fn find_icc_profile(bytes: &[u8]) -> Option<Vec<u8>> {
+    if container::is_jpeg(bytes) {
+        // Profiles over 64 KiB are split across numbered APP2 segments
+        let mut chunks: Vec<(u8, &[u8])> = container::jpeg_segments(bytes)
+            .into_iter()
+            .filter(|s| s.marker == 0xE2)
+            .filter_map(|s| s.data.strip_prefix(JPEG_ICC_SIGNATURE))
+            .filter(|d| d.len() >= 2)
+            .map(|d| (d[0], &d[2..]))
+            .collect();
+        if chunks.is_empty() {
+            return None;
+        }
+        chunks.sort_by_key(|(seq, _)| *seq);
+        return Some(chunks.into_iter().flat_map(|(_, d)| d.to_vec()).collect());
+    }
+
+    if container::is_png(bytes) {
+        let chunk = container::png_chunks(bytes)
+            .into_iter()
+            .find(|c| &c.id == b"iCCP")?;
+        let name_end = chunk.data.iter().position(|&b| b == 0)?;
+        // Only zlib (method 0) is defined
+        let compressed = chunk.data.get(name_end + 2..)?;
+        let mut profile = Vec::new();
+        limits::read_to_end_limited(
+            flate2::read::ZlibDecoder::new(compressed),
+            &mut profile,
+            limits::MAX_INFLATED_SIZE,
+        )
+        .ok()?;
+        return Some(profile);
+    }
+
+    if container::is_webp(bytes) {
+        return container::riff_chunks(bytes)
+            .into_iter()
+            .find(|c| &c.id == b"ICCP")
+            .map(|c| c.data.to_vec());
+    }
+
+    if container::is_tiff(bytes) {
+        let (order, offset) = container::tiff_header(bytes)?;
+        let ifd0 = container::tiff_directory(bytes, order, offset)?;
+        return ifd0.get(TIFF_ICC_TAG).map(|e| e.value.to_vec());
+    }
+
+    None
+}
+
+/// Convert a parsed profile into `exif_data` entries keyed `ICC:<Field>`.
+pub fn icc_to_metadata(profile: &IccProfile) -> HashMap<String, MetadataValue> {
+    let header = [
+        ("ProfileCMMType", Some(&profile.cmm_type)),
+        ("ProfileVersion", Some(&profile.version)),
+        ("ProfileClass", Some(&profile.device_class)),
+        ("ColorSpaceData", Some(&profile.color_space)),
+        ("ProfileConnectionSpace", Some(&profile.connection_space)),
+        ("ProfileDateTime", profile.created.as_ref()),
+        ("PrimaryPlatform", Some(&profile.platform)),
+        ("DeviceManufacturer", Some(&profile.device_manufacturer)),
+        ("DeviceModel", Some(&profile.device_model)),
+        ("RenderingIntent", Some(&profile.rendering_intent)),
+        ("ProfileCreator", Some(&profile.creator)),
+        ("ProfileID", profile.profile_id.as_ref()),
+    ];
+    let tags = [
+        ("ProfileDescription", "desc", profile.description.as_ref()),
+        ("ProfileCopyright", "cprt", profile.copyright.as_ref()),
+        (
+            "DeviceMfgDesc",
+            "dmnd",
+            profile.device_manufacturer_description.as_ref(),
+        ),
+        (
+            "DeviceModelDesc",
+            "dmdd",
+            profile.device_model_description.as_ref(),
+        ),
+    ];
+
+    let fields = header
+        .into_iter()
+        .map(|(name, value)| (name, "head", value))
+        .chain(tags);
+
+    let mut map = HashMap::new();
+    for (name, tag, value) in fields {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            map.insert(
+                format!("ICC:{}", name),
+                MetadataValue::text(value.clone()).with_source(MetadataSource::Icc {
+                    tag: tag.to_string(),
+                }),
+            );
+        }
+    }
+    map
+}
+
+/// Extract the embedded ICC profile of a file as `exif_data` entries.
+pub fn extract_icc_data(bytes: &[u8]) -> HashMap<String, MetadataValue> {
+    extract_icc_data_with_diagnostics(bytes, &mut Vec::new())
+}
+
+/// Extract the ICC profile fields as [`extract_icc_data`] does, reporting a
+/// profile that cannot be read. The offset is known when the profile is
+/// stored uncompressed in one piece.
+pub fn extract_icc_data_with_diagnostics(
+    bytes: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, MetadataValue> {
+    let Some(profile) = find_icc_profile(bytes) else {
+        return HashMap::new();
+    };
+    match parse_icc_profile(&profile) {
+        Ok(profile) => icc_to_metadata(&profile),
+        Err(e) => {
+            let head = &profile[..profile.len().min(HEADER_SIZE)];
+            let offset = (!head.is_empty())
+                .then(|| bytes.windows(head.len()).position(|w| w == head))
+                .flatten();
+            diagnostics.push(Diagnostic::error("ICC", offset, e));
+            HashMap::new()
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! IPTC-IIM decoding from Photoshop image resource blocks.
+//!
+//! News and agency photos carry captions, bylines, keywords and location
+//! names as IPTC-IIM datasets. In JPEG files they live in an APP13
+//! `Photoshop 3.0` segment as 8BIM resource `0x0404`; TIFF files store them
+//! directly in tag 33723 or inside the Photoshop resources of tag 34377.
+
+use crate::container;
+use crate::diagnostics::Diagnostic;
+use crate::types::{MetadataSource, MetadataValue, RawValue};
+use std::collections::HashMap;
+
+/// Signature prefixing Photoshop image resources in a JPEG APP13 segment.
+pub const PHOTOSHOP_SIGNATURE: &[u8] = b"Photoshop 3.0\0";
+/// Image resource ID holding the IPTC-IIM block.
+pub const IPTC_RESOURCE_ID: u16 = 0x0404;
+/// TIFF tag holding a raw IPTC-IIM block.
+pub const TIFF_IPTC_TAG: u16 = 33723;
+/// TIFF tag holding Photoshop image resources.
+pub const TIFF_PHOTOSHOP_TAG: u16 = 34377;
+
+/// Escape sequence declaring UTF-8 in the `CodedCharacterSet` dataset.
+const UTF8_ESCAPE: &[u8] = b"\x1b%G";
+
+/// Names of the envelope (1) and application (2) record datasets.
+pub static DATASET_NAMES: &[((u8, u8), &str)] = &[
+    ((1, 0), "EnvelopeRecordVersion"),
+    ((1, 5), "Destination"),
+    ((1, 20), "FileFormat"),
+    ((1, 22), "FileVersion"),
+    ((1, 30), "ServiceIdentifier"),
+    ((1, 40), "EnvelopeNumber"),
+    ((1, 50), "ProductID"),
+    ((1, 60), "EnvelopePriority"),
+    ((1, 70), "DateSent"),
+    ((1, 80), "TimeSent"),
+    ((1, 90), "CodedCharacterSet"),
+    ((1, 100), "UniqueObjectName"),
+    ((2, 0), "ApplicationRecordVersion"),
+    ((2, 3), "ObjectTypeReference"),
+    ((2, 4), "ObjectAttributeReference"),
+    ((2, 5), "ObjectName"),
+    ((2, 7), "EditStatus"),
+    ((2, 10), "Urgency"),
+    ((2, 12), "SubjectReference"),
+    ((2, 15), "Category"),
+    ((2, 20), "SupplementalCategories"),
+    ((2, 22), "FixtureIdentifier"),
+    ((2, 25), "Keywords"),
+    ((2, 26), "ContentLocationCode"),
+    ((2, 27), "ContentLocationName"),
+    ((2, 30), "ReleaseDate"),
+    ((2, 35), "ReleaseTime"),
+    ((2, 37), "ExpirationDate"),
+    ((2, 38), "ExpirationTime"),
+    ((2, 40), "SpecialInstructions"),
+    ((2, 42), "ActionAdvised"),
+    ((2, 45), "ReferenceService"),
+    ((2, 47), "ReferenceDate"),
+    ((2, 50), "ReferenceNumber"),
+    ((2, 55), "DateCreated"),
+    ((2, 60), "TimeCreated"),
+    ((2, 62), "DigitalCreationDate"),
+    ((2, 63), "DigitalCreationTime"),
+    ((2, 65), "OriginatingProgram"),
+    ((2, 70), "ProgramVersion"),
+    ((2, 75), "ObjectCycle"),
+    ((2, 80), "By-line"),
+    ((2, 85), "By-lineTitle"),
+    ((2, 90), "City"),
+    ((2, 92), "Sub-location"),
+    ((2, 95), "Province-State"),
+    ((2, 100), "Country-PrimaryLocationCode"),
+    ((2, 101), "Country-PrimaryLocationName"),
+    ((2, 103), "OriginalTransmissionReference"),
+    ((2, 105), "Headline"),
+    ((2, 110), "Credit"),
+    ((2, 115), "Source"),
+    ((2, 116), "CopyrightNotice"),
+    ((2, 118), "Contact"),
+    ((2, 120), "Caption-Abstract"),
+    ((2, 121), "LocalCaption"),
+    ((2, 122), "Writer-Editor"),
+    ((2, 130), "ImageType"),
+    ((2, 131), "ImageOrientation"),
+    ((2, 135), "LanguageIdentifier"),
+];
+
+/// A single IPTC-IIM dataset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IptcDataSet {
+    pub record: u8,
+    pub dataset: u8,
+    pub data: Vec<u8>,
+}
+
+/// Look up the name of a dataset.
+pub fn dataset_name(record: u8, dataset: u8) -> Option<&'static str> {
+    DATASET_NAMES
+        .iter()
+        .find(|(id, _)| *id == (record, dataset))
+        .map(|(_, name)| *name)
+}
+
+/// A Photoshop image resource block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageResource<'a> {
+    pub id: u16,
+    pub name: String,
+    pub data: &'a [u8],
+}
+
+/// Parse a sequence of Photoshop image resource blocks (`8BIM` records).
+pub fn parse_image_resources(data: &[u8]) -> Vec<ImageResource<'_>> {
+    let mut resources = Vec::new();
+    let mut i = 0;
+
+    while i + 12 <= data.len() {
+        // Other signatures are used by older or non-Adobe writers
+        if !matches!(
+            &data[i..i + 4],
+            b"8BIM" | b"MeSa" | b"PHUT" | b"AgHg" | b"DCSR"
+        ) {
+            break;
+        }
+        let id = u16::from_be_bytes([data[i + 4], data[i + 5]]);
+        // Pascal string name, padded to an even length including its length byte
+        let name_len = data[i + 6] as usize;
+        let name_end = i + 7 + name_len;
+        let size_at = i + 6 + (name_len + 2) / 2 * 2;
+        if size_at + 4 > data.len() {
+            break;
+        }
+        let name = String::from_utf8_lossy(&data[i + 7..name_end.min(data.len())]).into_owned();
+        let size = u32::from_be_bytes([
+            data[size_at],
+            data[size_at + 1],
+            data[size_at + 2],
+            data[size_at + 3],
+        ]) as usize;
+        let start = size_at + 4;
+        let Some(end) = start.checked_add(size).filter(|end| *end <= data.len()) else {
+            break;
+        };
+        resources.push(ImageResource {
+            id,
+            name,
+            data: &data[start..end],
+        });
+        i = end + (size % 2);
+    }
+
+    resources
+}
+
+/// Parse an IPTC-IIM block into its datasets, in file order.
+pub fn parse_iim(data: &[u8]) -> Vec<IptcDataSet> {
+    read_iim(data).0
+}
+
+/// Parse an IPTC-IIM block as [`parse_iim`] does, with the reason the walk
+/// stopped before the end of the block, if it did. Zero padding after the
+/// last dataset is not a problem.
+fn read_iim(data: &[u8]) -> (Vec<IptcDataSet>, Option<String>) {
+    let mut datasets = Vec::new();
+    let mut i = 0;
+
+    while i + 5 <= data.len() && data[i] == 0x1C {
+        let record = data[i + 1];
+        let dataset = data[i + 2];
+        let mut length = u16::from_be_bytes([data[i + 3], data[i + 4]]) as usize;
+        let mut start = i + 5;
+        // Extended datasets give the number of length bytes that follow
+        if length & 0x8000 != 0 {
+            let count = length & 0x7FFF;
+            if count > 4 || start + count > data.len() {
+                let problem = format!(
+                    "Dataset {}:{} has an invalid extended length",
+                    record, dataset
+                );
+                return (datasets, Some(problem));
+            }
+            length = data[start..start + count]
+                .iter()
+                .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+            start += count;
+        }
+        let Some(end) = start.checked_add(length).filter(|end| *end <= data.len()) else {
+            let problem = format!(
+                "Dataset {}:{} declares {} bytes, but the IPTC block ends first",
+                record, dataset, length
+            );
+            return (datasets, Some(problem));
+        };
+        datasets.push(IptcDataSet {
+            record,
+            dataset,
+            data: data[start..end].to_vec(),
+        });
+        i = end;
+    }
+
+    let problem = data[i..].iter().any(|&b| b != 0).then(|| {
+        format!(
+            "Unexpected data after {} datasets where the next should start",
+            datasets.len()
+        )
+    });
+    (datasets, problem)
+}
+
+/// Locate the IPTC-IIM block of a JPEG or TIFF file.
+pub fn find_iim_block(bytes: &[u8]) -> Option<Vec<u8>> {
+    if container::is_jpeg(bytes) {
+        // Large resource blocks may be split across several APP13 segments
+        let mut resources = Vec::new();
+        for segment in container::jpeg_segments(bytes) {
+            if segment.marker == 0xED {
+                let data = segment.data;
+                resources.extend_from_slice(data.strip_prefix(PHOTOSHOP_SIGNATURE).unwrap_or(data));
+            }
+        }
+        return parse_image_resources(&resources)
+            .into_iter()
+            .find(|r| r.id == IPTC_RESOURCE_ID)
+            .map(|r| r.data.to_vec());
+    }
+
+    if container::is_tiff(bytes) {
+        let (order, offset) = container::tiff_header(bytes)?;
+        let ifd0 = container::tiff_directory(bytes, order, offset)?;
+        if let Some(entry) = ifd0.get(TIFF_IPTC_TAG) {
+            return Some(entry.value.to_vec());
+        }
+        let resources = ifd0.get(TIFF_PHOTOSHOP_TAG)?;
+        return parse_image_resources(resources.value)
+            .into_iter()
+            .find(|r| r.id == IPTC_RESOURCE_ID)
+            .map(|r| r.data.to_vec());
+    }
+
+    None
+}
+
+pub(crate) fn decode_latin1(data: &[u8]) -> String {
+    data.iter().map(|&b| b as char).collect()
+}
+
+/// Convert IPTC-IIM datasets into `exif_data` entries keyed `IPTC:<Name>`.
+///
+/// Text is decoded as UTF-8 when `CodedCharacterSet` declares it and as
+/// Latin-1 otherwise. Repeatable datasets such as `Keywords` are joined with
+/// `"; "`.
+pub fn iptc_to_metadata(datasets: &[IptcDataSet]) -> HashMap<String, MetadataValue> {
+    let utf8 = datasets
+        .iter()
+        .any(|d| (d.record, d.dataset) == (1, 90) && d.data.starts_with(UTF8_ESCAPE));
+
+    let mut order: Vec<(u8, u8)> = Vec::new();
+    let mut values: HashMap<(u8, u8), Vec<String>> = HashMap::new();
+    for d in datasets {
+        let id = (d.record, d.dataset);
+        if id == (1, 90) {
+            continue;
+        }
+        let text = match id {
+            // Record versions are binary big-endian integers
+            (1, 0) | (2, 0) if d.data.len() == 2 => {
+                u16::from_be_bytes([d.data[0], d.data[1]]).to_string()
+            }
+            _ if utf8 => String::from_utf8_lossy(&d.data).into_owned(),
+            _ => decode_latin1(&d.data),
+        };
+        let text = text.trim_end_matches('\0').trim().to_string();
+        if !values.contains_key(&id) {
+            order.push(id);
+        }
+        values.entry(id).or_default().push(text);
+    }
+
+    let mut map = HashMap::new();
+    for (record, dataset) in order {
+        let texts = &values[&(record, dataset)];
+        let key = match dataset_name(record, dataset) {
+            Some(name) => format!("IPTC:{}", name),
+            None => format!("IPTC:{}:{}", record, dataset),
+        };
+        let joined = texts.join("; ");
+        let raw = match (record, dataset) {
+            (1, 0) | (2, 0) => joined
+                .parse::<i64>()
+                .map(|v| RawValue::Integer(vec![v]))
+                .unwrap_or_else(|_| RawValue::Text(joined.clone())),
+            _ => RawValue::Text(joined.clone()),
+        };
+        map.insert(
+            key,
+            MetadataValue::new(raw, joined).with_source(MetadataSource::Iptc { record, dataset }),
+        );
+    }
+    map
+}
+
+/// Extract IPTC-IIM metadata from a file as `exif_data` entries.
+pub fn extract_iptc_data(bytes: &[u8]) -> HashMap<String, MetadataValue> {
+    extract_iptc_data_with_diagnostics(bytes, &mut Vec::new())
+}
+
+/// Extract IPTC-IIM metadata as [`extract_iptc_data`] does, reporting a
+/// dataset walk that stops before the end of the block.
+pub fn extract_iptc_data_with_diagnostics(
+    bytes: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, MetadataValue> {
+    let Some(block) = find_iim_block(bytes) else {
+        return HashMap::new();
+    };
+    let (datasets, problem) = read_iim(&block);
+    if let Some(problem) = problem {
+        diagnostics.push(Diagnostic::warning("IPTC", None, problem));
+    }
+    iptc_to_metadata(&datasets)
+}
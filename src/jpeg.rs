@@ -0,0 +1,451 @@
+//! JPEG file structure: marker segments, frame header and encoder settings.
+//!
+//! [`analyze_jpeg`] walks every marker from SOI to EOI, including all scans
+//! of progressive files, and decodes the segments describing how the image
+//! was encoded: the SOF frame header (precision, components, chroma
+//! subsampling, coding process), the DQT quantization tables (from which the
+//! IJG quality factor is estimated) and the DRI restart interval. Bytes after
+//! EOI are counted, since they often hold appended videos or other files.
+
+use crate::container;
+use crate::diagnostics::Diagnostic;
+use crate::limits::{LimitExceeded, MAX_BLOCKS};
+use serde::Serialize;
+
+/// IJG (libjpeg) base luminance quantization table, natural order.
+const STD_LUMINANCE_TABLE: [u16; 64] = [
+    16, 11, 10, 16, 24, 40, 51, 61, 12, 12, 14, 19, 26, 58, 60, 55, 14, 13, 16, 24, 40, 57, 69, 56,
+    14, 17, 22, 29, 51, 87, 80, 62, 18, 22, 37, 56, 68, 109, 103, 77, 24, 35, 55, 64, 81, 104, 113,
+    92, 49, 64, 78, 87, 103, 121, 120, 101, 72, 92, 95, 98, 112, 100, 103, 99,
+];
+
+/// IJG (libjpeg) base chrominance quantization table, natural order.
+const STD_CHROMINANCE_TABLE: [u16; 64] = [
+    17, 18, 24, 47, 99, 99, 99, 99, 18, 21, 26, 66, 99, 99, 99, 99, 24, 26, 56, 99, 99, 99, 99, 99,
+    47, 66, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+];
+
+/// Natural-order index of each coefficient in zigzag (file) order.
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// One marker of a JPEG file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct JpegSegmentInfo {
+    /// Marker byte following `0xFF`.
+    pub marker: u8,
+    /// Marker name, e.g. `"APP1"` or `"SOF2"`.
+    pub name: String,
+    /// Identifier string of APPn segments, e.g. `"Exif"` or `"ICC_PROFILE"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    /// Offset of the `0xFF` marker byte in the file.
+    pub offset: usize,
+    /// Total bytes including the marker; for SOS this covers the
+    /// entropy-coded data that follows the scan header.
+    pub size: usize,
+}
+
+/// One image component declared in the frame header.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct JpegComponent {
+    pub id: u8,
+    pub horizontal_sampling: u8,
+    pub vertical_sampling: u8,
+    pub quantization_table: u8,
+}
+
+/// The SOF frame header.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct JpegFrame {
+    /// Coding process, e.g. `"Baseline DCT"` or `"Progressive DCT"`.
+    pub process: String,
+    pub progressive: bool,
+    pub arithmetic_coding: bool,
+    /// Bits per sample.
+    pub precision: u8,
+    pub width: u16,
+    pub height: u16,
+    pub components: Vec<JpegComponent>,
+    /// Chroma subsampling, e.g. `"4:2:0"`.
+    pub subsampling: String,
+}
+
+/// A DQT quantization table, in natural (row-major) order.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct QuantizationTable {
+    pub id: u8,
+    /// 8 or 16 bits per entry.
+    pub precision: u8,
+    pub values: Vec<u16>,
+}
+
+/// Structure of a JPEG file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct JpegStructure {
+    pub segments: Vec<JpegSegmentInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame: Option<JpegFrame>,
+    pub quantization_tables: Vec<QuantizationTable>,
+    /// Estimated IJG quality factor (1-100).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u8>,
+    /// Whether the tables are exactly libjpeg's scaled standard tables, in
+    /// which case the quality estimate is exact.
+    pub standard_tables: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_interval: Option<u16>,
+    /// Number of SOS scans (more than one for progressive files).
+    pub scans: usize,
+    /// Bytes following the EOI marker.
+    pub trailing_bytes: usize,
+    /// `true` if the file ends before an EOI marker.
+    pub truncated: bool,
+}
+
+impl JpegStructure {
+    fn push(&mut self, marker: u8, identifier: Option<String>, offset: usize, size: usize) {
+        self.segments.push(JpegSegmentInfo {
+            marker,
+            name: marker_name(marker),
+            identifier,
+            offset,
+            size,
+        });
+    }
+}
+
+/// Name of a JPEG marker.
+pub fn marker_name(marker: u8) -> String {
+    match marker {
+        0xC4 => "DHT".to_string(),
+        0xC8 => "JPG".to_string(),
+        0xCC => "DAC".to_string(),
+        0xC0..=0xCF => format!("SOF{}", marker - 0xC0),
+        0xD0..=0xD7 => format!("RST{}", marker - 0xD0),
+        0xD8 => "SOI".to_string(),
+        0xD9 => "EOI".to_string(),
+        0xDA => "SOS".to_string(),
+        0xDB => "DQT".to_string(),
+        0xDC => "DNL".to_string(),
+        0xDD => "DRI".to_string(),
+        0xDE => "DHP".to_string(),
+        0xDF => "EXP".to_string(),
+        0xE0..=0xEF => format!("APP{}", marker - 0xE0),
+        0xFE => "COM".to_string(),
+        0x01 => "TEM".to_string(),
+        _ => format!("0x{:02X}", marker),
+    }
+}
+
+/// Identifier of an APPn segment from the signature its payload opens with.
+fn app_identifier(payload: &[u8]) -> Option<String> {
+    const KNOWN: &[(&[u8], &str)] = &[
+        (b"JFIF\0", "JFIF"),
+        (b"JFXX\0", "JFXX"),
+        (b"Exif\0", "Exif"),
+        (b"http://ns.adobe.com/xap/1.0/\0", "XMP"),
+        (b"http://ns.adobe.com/xmp/extension/\0", "Extended XMP"),
+        (b"ICC_PROFILE\0", "ICC_PROFILE"),
+        (b"MPF\0", "MPF"),
+        (b"Photoshop 3.0\0", "Photoshop IRB"),
+        (b"Adobe", "Adobe"),
+        (b"Ducky", "Ducky"),
+        (b"JP", "JUMBF"),
+    ];
+    if let Some((_, name)) = KNOWN.iter().find(|(sig, _)| payload.starts_with(sig)) {
+        return Some(name.to_string());
+    }
+    // Otherwise a printable NUL-terminated signature
+    let end = payload.iter().take(32).position(|&b| b == 0)?;
+    let signature = &payload[..end];
+    (!signature.is_empty() && signature.iter().all(|b| b.is_ascii_graphic() || *b == b' '))
+        .then(|| String::from_utf8_lossy(signature).into_owned())
+}
+
+fn parse_frame(marker: u8, payload: &[u8]) -> Option<JpegFrame> {
+    if payload.len() < 6 {
+        return None;
+    }
+    let count = payload[5] as usize;
+    let components: Vec<JpegComponent> = payload[6..]
+        .chunks_exact(3)
+        .take(count)
+        .map(|c| JpegComponent {
+            id: c[0],
+            horizontal_sampling: c[1] >> 4,
+            vertical_sampling: c[1] & 0x0F,
+            quantization_table: c[2],
+        })
+        .collect();
+    let kind = marker & 0x03;
+    let differential = marker & 0x04 != 0;
+    let mut process = match (marker, kind) {
+        (0xC0, _) => "Baseline DCT",
+        (_, 0 | 1) => "Extended sequential DCT",
+        (_, 2) => "Progressive DCT",
+        _ => "Lossless",
+    }
+    .to_string();
+    if differential {
+        process = format!("Differential {}", process.to_lowercase());
+    }
+    let arithmetic_coding = marker >= 0xC8;
+    if arithmetic_coding {
+        process.push_str(", arithmetic coding");
+    }
+
+    Some(JpegFrame {
+        process,
+        progressive: kind == 2,
+        arithmetic_coding,
+        precision: payload[0],
+        height: u16::from_be_bytes([payload[1], payload[2]]),
+        width: u16::from_be_bytes([payload[3], payload[4]]),
+        subsampling: subsampling(&components),
+        components,
+    })
+}
+
+/// Describe chroma subsampling from the components' sampling factors.
+pub fn subsampling(components: &[JpegComponent]) -> String {
+    match components {
+        [] => String::new(),
+        [_] => "Grayscale".to_string(),
+        [luma, chroma, ..]
+            if components[1..].iter().all(|c| {
+                (c.horizontal_sampling, c.vertical_sampling)
+                    == (chroma.horizontal_sampling, chroma.vertical_sampling)
+            }) && chroma.horizontal_sampling > 0
+                && chroma.vertical_sampling > 0 =>
+        {
+            let h = luma.horizontal_sampling / chroma.horizontal_sampling;
+            let v = luma.vertical_sampling / chroma.vertical_sampling;
+            let exact = luma.horizontal_sampling % chroma.horizontal_sampling == 0
+                && luma.vertical_sampling % chroma.vertical_sampling == 0;
+            match (exact, h, v) {
+                (true, 1, 1) => "4:4:4".to_string(),
+                (true, 2, 1) => "4:2:2".to_string(),
+                (true, 2, 2) => "4:2:0".to_string(),
+                (true, 1, 2) => "4:4:0".to_string(),
+                (true, 4, 1) => "4:1:1".to_string(),
+                (true, 4, 2) => "4:1:0".to_string(),
+                _ => sampling_factors(components),
+            }
+        }
+        _ => sampling_factors(components),
+    }
+}
+
+fn sampling_factors(components: &[JpegComponent]) -> String {
+    components
+        .iter()
+        .map(|c| format!("{}x{}", c.horizontal_sampling, c.vertical_sampling))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn parse_quantization_tables(payload: &[u8], tables: &mut Vec<QuantizationTable>) {
+    let mut rest = payload;
+    while let Some((&pq_tq, body)) = rest.split_first() {
+        let sixteen_bit = pq_tq >> 4 != 0;
+        let entry = if sixteen_bit { 2 } else { 1 };
+        if body.len() < 64 * entry {
+            return;
+        }
+        let mut values = vec![0u16; 64];
+        for (k, natural) in ZIGZAG.iter().enumerate() {
+            values[*natural] = if sixteen_bit {
+                u16::from_be_bytes([body[2 * k], body[2 * k + 1]])
+            } else {
+                u16::from(body[k])
+            };
+        }
+        let id = pq_tq & 0x0F;
+        tables.retain(|t| t.id != id);
+        tables.push(QuantizationTable {
+            id,
+            precision: if sixteen_bit { 16 } else { 8 },
+            values,
+        });
+        rest = &body[64 * entry..];
+    }
+}
+
+/// libjpeg's standard table scaled to the given quality.
+fn scaled_table(base: &[u16; 64], quality: u32) -> [u16; 64] {
+    let scale = if quality < 50 {
+        5000 / quality
+    } else {
+        200 - quality * 2
+    };
+    base.map(|value| ((u32::from(value) * scale + 50) / 100).clamp(1, 255) as u16)
+}
+
+/// Estimate the IJG quality factor of a set of quantization tables.
+///
+/// Every quality from 1 to 100 is tried against the luminance table (id 0)
+/// and, if present, the chrominance table (id 1); the closest match wins.
+/// Returns the quality and whether the match was exact.
+pub fn estimate_quality(tables: &[QuantizationTable]) -> Option<(u8, bool)> {
+    let luminance = tables.iter().find(|t| t.id == 0)?;
+    let chrominance = tables.iter().find(|t| t.id == 1);
+    let error = |table: &QuantizationTable, base: &[u16; 64], quality: u32| -> u64 {
+        table
+            .values
+            .iter()
+            .zip(scaled_table(base, quality))
+            .map(|(a, b)| u64::from(a.abs_diff(b)))
+            .sum()
+    };
+    (1..=100u32)
+        .map(|quality| {
+            let total = error(luminance, &STD_LUMINANCE_TABLE, quality)
+                + chrominance.map_or(0, |c| error(c, &STD_CHROMINANCE_TABLE, quality));
+            (total, quality)
+        })
+        .min_by_key(|(total, quality)| (*total, std::cmp::Reverse(*quality)))
+        .map(|(total, quality)| (quality as u8, total == 0))
+}
+
+/// Offset just past the entropy-coded data of a scan that starts at
+/// `start`: the next marker that is neither a stuffed `0xFF00` nor a restart
+/// marker, or the end of the data.
+pub fn scan_end(data: &[u8], start: usize) -> usize {
+    let mut end = start;
+    while end + 1 < data.len() {
+        let next = data[end + 1];
+        if data[end] == 0xFF && next != 0x00 && next != 0xFF && !(0xD0..=0xD7).contains(&next) {
+            return end;
+        }
+        end += 1;
+    }
+    data.len()
+}
+
+/// Walk a JPEG file and describe its structure. Returns `None` for non-JPEG
+/// data.
+pub fn analyze_jpeg(data: &[u8]) -> Option<JpegStructure> {
+    analyze_jpeg_with_diagnostics(data, &mut Vec::new())
+}
+
+/// Walk a JPEG file as [`analyze_jpeg`] does, reporting the segment that
+/// stopped the walk and a file that ends before the end-of-image marker.
+pub fn analyze_jpeg_with_diagnostics(
+    data: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<JpegStructure> {
+    if !container::is_jpeg(data) {
+        return None;
+    }
+    let mut structure = JpegStructure {
+        segments: vec![JpegSegmentInfo {
+            marker: 0xD8,
+            name: marker_name(0xD8),
+            identifier: None,
+            offset: 0,
+            size: 2,
+        }],
+        frame: None,
+        quantization_tables: Vec::new(),
+        quality: None,
+        standard_tables: false,
+        restart_interval: None,
+        scans: 0,
+        trailing_bytes: 0,
+        truncated: true,
+    };
+
+    let mut i = 2;
+    let mut problem = None;
+    while i + 1 < data.len() {
+        // A run of restart markers would otherwise list millions of segments
+        if structure.segments.len() == MAX_BLOCKS {
+            let error = LimitExceeded::new("JPEG segment count", MAX_BLOCKS as u64);
+            problem = Some(Diagnostic::error("JPEG", Some(i), error));
+            break;
+        }
+        if data[i] != 0xFF {
+            break;
+        }
+        let marker = data[i + 1];
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        }
+        if marker == 0xD9 {
+            structure.push(marker, None, i, 2);
+            structure.truncated = false;
+            structure.trailing_bytes = data.len() - (i + 2);
+            break;
+        }
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            structure.push(marker, None, i, 2);
+            i += 2;
+            continue;
+        }
+        if i + 4 > data.len() {
+            break;
+        }
+        let length = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+        if length < 2 {
+            let message = format!(
+                "{} segment has an invalid length of {}",
+                marker_name(marker),
+                length
+            );
+            problem = Some(Diagnostic::error("JPEG", Some(i), message));
+            break;
+        }
+        if i + 2 + length > data.len() {
+            let message = format!(
+                "{} segment declares {} bytes, but the file ends after {}",
+                marker_name(marker),
+                length - 2,
+                data.len() - i - 4
+            );
+            problem = Some(Diagnostic::error("JPEG", Some(i), message));
+            break;
+        }
+        let payload = &data[i + 4..i + 2 + length];
+        let mut end = i + 2 + length;
+        let mut identifier = None;
+        match marker {
+            0xC0..=0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF if structure.frame.is_none() => {
+                structure.frame = parse_frame(marker, payload);
+            }
+            0xDB => parse_quantization_tables(payload, &mut structure.quantization_tables),
+            0xDD if payload.len() >= 2 => {
+                structure.restart_interval = Some(u16::from_be_bytes([payload[0], payload[1]]));
+            }
+            0xE0..=0xEF => identifier = app_identifier(payload),
+            0xDA => {
+                structure.scans += 1;
+                end = scan_end(data, end);
+            }
+            _ => {}
+        }
+        structure.push(marker, identifier, i, end - i);
+        i = end;
+    }
+
+    match problem {
+        Some(problem) => diagnostics.push(problem),
+        None if structure.truncated => diagnostics.push(Diagnostic::warning(
+            "JPEG",
+            Some(data.len()),
+            "File ends before the end-of-image marker",
+        )),
+        None => {}
+    }
+
+    if let Some((quality, exact)) = estimate_quality(&structure.quantization_tables) {
+        structure.quality = Some(quality);
+        structure.standard_tables = exact;
+    }
+    Some(structure)
+}
@@ -0,0 +1,408 @@
+//! JPEG XL container and codestream header parsing.
+//!
+//! A JPEG XL file is either a bare codestream (`FF 0A`) or an ISO base media
+//! style container opened by a `JXL ` signature box. The container carries
+//! the codestream in `jxlc` (or split across `jxlp` boxes) next to `Exif`,
+//! `xml ` (XMP) and `jumb` (JUMBF) metadata boxes, any of which may be
+//! Brotli-compressed inside a `brob` box. A `jbrd` box means the file is a
+//! losslessly recompressed JPEG that can be turned back into the original.
+//!
+//! Dimensions, bit depth, orientation and animation are read from the start
+//! of the codestream (the `SizeHeader` and the first fields of
+//! `ImageMetadata`).
+
+use crate::diagnostics::Diagnostic;
+use crate::types::{MetadataSource, MetadataValue, RawValue};
+use crate::{container, limits};
+use std::collections::HashMap;
+
+/// A metadata box of a JPEG XL container, decompressed if it was stored in
+/// a `brob` box.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JxlBox {
+    /// Box type, e.g. `Exif`, `xml ` or `jumb`.
+    pub box_type: [u8; 4],
+    pub data: Vec<u8>,
+    /// Whether the box was Brotli-compressed in the file.
+    pub compressed: bool,
+}
+
+/// Fields decoded from the codestream header.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct JxlHeader {
+    pub width: u32,
+    pub height: u32,
+    pub bits_per_sample: u32,
+    pub float_samples: bool,
+    /// EXIF-style orientation, 1 to 8.
+    pub orientation: u32,
+    pub extra_channels: u32,
+    pub has_preview: bool,
+    pub animated: bool,
+}
+
+/// Least-significant-bit first reader for the codestream.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+/// One of the four distributions of a JPEG XL `U32` field.
+#[derive(Clone, Copy)]
+enum Dist {
+    Val(u32),
+    /// `n` bits plus an offset.
+    Bits(u32, u32),
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, n: u32) -> Option<u32> {
+        let mut value = 0u32;
+        for i in 0..n {
+            let byte = *self.data.get(self.pos / 8)?;
+            value |= (((byte >> (self.pos % 8)) & 1) as u32) << i;
+            self.pos += 1;
+        }
+        Some(value)
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        Some(self.bits(1)? == 1)
+    }
+
+    fn u32(&mut self, dists: [Dist; 4]) -> Option<u32> {
+        match dists[self.bits(2)? as usize] {
+            Dist::Val(value) => Some(value),
+            Dist::Bits(n, offset) => self.bits(n)?.checked_add(offset),
+        }
+    }
+
+    /// Read a `SizeHeader` (or intrinsic size) as `(width, height)`.
+    fn size_header(&mut self) -> Option<(u32, u32)> {
+        const DIMENSION: [Dist; 4] = [
+            Dist::Bits(9, 1),
+            Dist::Bits(13, 1),
+            Dist::Bits(18, 1),
+            Dist::Bits(30, 1),
+        ];
+        let small = self.bool()?;
+        let height = if small {
+            (self.bits(5)? + 1) * 8
+        } else {
+            self.u32(DIMENSION)?
+        };
+        let width = match self.bits(3)? {
+            0 if small => (self.bits(5)? + 1) * 8,
+            0 => self.u32(DIMENSION)?,
+            ratio => aspect_width(height, ratio),
+        };
+        Some((width, height))
+    }
+
+    fn skip_preview_header(&mut self) -> Option<()> {
+        let div8 = self.bool()?;
+        let dimension = |reader: &mut Self| {
+            if div8 {
+                reader.u32([
+                    Dist::Val(16),
+                    Dist::Val(32),
+                    Dist::Bits(5, 1),
+                    Dist::Bits(9, 33),
+                ])
+            } else {
+                reader.u32([
+                    Dist::Bits(6, 1),
+                    Dist::Bits(8, 65),
+                    Dist::Bits(10, 321),
+                    Dist::Bits(12, 1345),
+                ])
+            }
+        };
+        dimension(self)?;
+        if self.bits(3)? == 0 {
+            dimension(self)?;
+        }
+        Some(())
+    }
+
+    fn skip_animation_header(&mut self) -> Option<()> {
+        self.u32([
+            Dist::Val(100),
+            Dist::Val(1000),
+            Dist::Bits(10, 1),
+            Dist::Bits(30, 1),
+        ])?;
+        self.u32([
+            Dist::Val(1),
+            Dist::Val(1001),
+            Dist::Bits(8, 1),
+            Dist::Bits(10, 1),
+        ])?;
+        self.u32([
+            Dist::Val(0),
+            Dist::Bits(3, 0),
+            Dist::Bits(16, 0),
+            Dist::Bits(32, 0),
+        ])?;
+        self.bool()?; // have_timecodes
+        Some(())
+    }
+}
+
+/// Width implied by one of the fixed aspect ratios of a `SizeHeader`.
+fn aspect_width(height: u32, ratio: u32) -> u32 {
+    let (num, den) = match ratio {
+        1 => (1, 1),
+        2 => (12, 10),
+        3 => (4, 3),
+        4 => (3, 2),
+        5 => (16, 9),
+        6 => (5, 4),
+        _ => (2, 1),
+    };
+    (height as u64 * num / den) as u32
+}
+
+/// Decode the size and leading `ImageMetadata` fields of a codestream.
+pub fn parse_codestream_header(codestream: &[u8]) -> Result<JxlHeader, String> {
+    let data = codestream
+        .strip_prefix(container::JXL_CODESTREAM_SIGNATURE)
+        .ok_or("Missing JPEG XL codestream signature")?;
+    let mut reader = BitReader { data, pos: 0 };
+    let truncated = || "Truncated JPEG XL codestream header".to_string();
+
+    let (width, height) = reader.size_header().ok_or_else(truncated)?;
+    let mut header = JxlHeader {
+        width,
+        height,
+        bits_per_sample: 8,
+        orientation: 1,
+        ..Default::default()
+    };
+
+    let all_default = reader.bool().ok_or_else(truncated)?;
+    if all_default {
+        return Ok(header);
+    }
+    (|| {
+        if reader.bool()? {
+            // extra_fields
+            header.orientation = reader.bits(3)? + 1;
+            if reader.bool()? {
+                reader.size_header()?; // intrinsic size
+            }
+            header.has_preview = reader.bool()?;
+            if header.has_preview {
+                reader.skip_preview_header()?;
+            }
+            header.animated = reader.bool()?;
+            if header.animated {
+                reader.skip_animation_header()?;
+            }
+        }
+        header.float_samples = reader.bool()?;
+        header.bits_per_sample = if header.float_samples {
+            let bits = reader.u32([
+                Dist::Val(32),
+                Dist::Val(16),
+                Dist::Val(24),
+                Dist::Bits(6, 1),
+            ])?;
+            reader.bits(4)?; // exponent bits
+            bits
+        } else {
+            reader.u32([Dist::Val(8), Dist::Val(10), Dist::Val(12), Dist::Bits(6, 1)])?
+        };
+        reader.bool()?; // modular_16_bit_buffer_sufficient
+        header.extra_channels = reader.u32([
+            Dist::Val(0),
+            Dist::Val(1),
+            Dist::Bits(4, 2),
+            Dist::Bits(12, 1),
+        ])?;
+        Some(())
+    })()
+    .ok_or_else(truncated)?;
+
+    Ok(header)
+}
+
+fn brotli_decompress(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    limits::read_to_end_limited(
+        brotli_decompressor::Decompressor::new(data, 4096),
+        &mut out,
+        limits::MAX_INFLATED_SIZE,
+    )
+    .ok()?;
+    Some(out)
+}
+
+/// The boxes of a JPEG XL container, with `brob` boxes decompressed.
+/// Codestream boxes are left out; see [`find_codestream`].
+pub fn jxl_boxes(bytes: &[u8]) -> Vec<JxlBox> {
+    if !bytes.starts_with(container::JXL_CONTAINER_SIGNATURE) {
+        return Vec::new();
+    }
+    container::iso_boxes(bytes)
+        .into_iter()
+        .filter(|b| !matches!(&b.id, b"JXL " | b"ftyp" | b"jxlc" | b"jxlp"))
+        .filter_map(|b| {
+            if &b.id != b"brob" {
+                return Some(JxlBox {
+                    box_type: b.id,
+                    data: b.data.to_vec(),
+                    compressed: false,
+                });
+            }
+            let box_type: [u8; 4] = b.data.get(..4)?.try_into().ok()?;
+            Some(JxlBox {
+                box_type,
+                data: brotli_decompress(&b.data[4..])?,
+                compressed: true,
+            })
+        })
+        .collect()
+}
+
+/// The start of the codestream: the whole file for a bare codestream, the
+/// `jxlc` box or the first `jxlp` box of a container.
+pub fn find_codestream(bytes: &[u8]) -> Option<&[u8]> {
+    if bytes.starts_with(container::JXL_CODESTREAM_SIGNATURE) {
+        return Some(bytes);
+    }
+    if !bytes.starts_with(container::JXL_CONTAINER_SIGNATURE) {
+        return None;
+    }
+    container::iso_boxes(bytes)
+        .into_iter()
+        .find_map(|b| match &b.id {
+            b"jxlc" => Some(b.data),
+            // Partial codestream boxes start with a sequence index
+            b"jxlp" => b.data.get(4..),
+            _ => None,
+        })
+}
+
+/// The TIFF block of the `Exif` box, without its header offset prefix.
+pub fn find_exif(bytes: &[u8]) -> Option<Vec<u8>> {
+    let exif = jxl_boxes(bytes)
+        .into_iter()
+        .find(|b| &b.box_type == b"Exif")?;
+    let skip = u32::from_be_bytes(exif.data.get(..4)?.try_into().ok()?) as usize;
+    Some(exif.data.get(4usize.checked_add(skip)?..)?.to_vec())
+}
+
+/// The XMP packets stored in `xml ` boxes.
+pub fn find_xmp(bytes: &[u8]) -> Vec<String> {
+    jxl_boxes(bytes)
+        .into_iter()
+        .filter(|b| &b.box_type == b"xml ")
+        .map(|b| String::from_utf8_lossy(&b.data).into_owned())
+        .collect()
+}
+
+/// The JUMBF superboxes stored in `jumb` boxes.
+pub fn find_jumbf(bytes: &[u8]) -> Vec<Vec<u8>> {
+    jxl_boxes(bytes)
+        .into_iter()
+        .filter(|b| &b.box_type == b"jumb")
+        .map(|b| b.data)
+        .collect()
+}
+
+/// Width and height from the codestream header.
+pub fn dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let header = parse_codestream_header(find_codestream(bytes)?).ok()?;
+    Some((header.width, header.height))
+}
+
+/// Extract the codestream header and container structure as `exif_data`
+/// entries keyed `JXL:<Field>`.
+pub fn extract_jxl_data(bytes: &[u8]) -> HashMap<String, MetadataValue> {
+    extract_jxl_data_with_diagnostics(bytes, &mut Vec::new())
+}
+
+/// Extract the JPEG XL fields as [`extract_jxl_data`] does, reporting a
+/// codestream header that cannot be read.
+pub fn extract_jxl_data_with_diagnostics(
+    bytes: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, MetadataValue> {
+    let mut map = HashMap::new();
+    if !container::is_jxl(bytes) {
+        return map;
+    }
+    let mut insert = |name: &str, box_type: &str, value: MetadataValue| {
+        map.insert(
+            format!("JXL:{}", name),
+            value.with_source(MetadataSource::Bmff {
+                box_type: box_type.to_string(),
+            }),
+        );
+    };
+    let integer =
+        |value: u32| MetadataValue::new(RawValue::Integer(vec![value.into()]), value.to_string());
+    let yes_no = |value: bool| MetadataValue::text(if value { "Yes" } else { "No" });
+
+    let is_container = bytes.starts_with(container::JXL_CONTAINER_SIGNATURE);
+    let codestream_box = if is_container { "jxlc" } else { "codestream" };
+    let header = find_codestream(bytes).and_then(|codestream| {
+        parse_codestream_header(codestream)
+            .map_err(|e| {
+                let offset = (codestream.as_ptr() as usize).checked_sub(bytes.as_ptr() as usize);
+                diagnostics.push(Diagnostic::error("JXL", offset, e));
+            })
+            .ok()
+    });
+    if let Some(header) = header {
+        insert("ImageWidth", codestream_box, integer(header.width));
+        insert("ImageHeight", codestream_box, integer(header.height));
+        let depth = if header.float_samples {
+            format!("{} (float)", header.bits_per_sample)
+        } else {
+            header.bits_per_sample.to_string()
+        };
+        insert(
+            "BitsPerSample",
+            codestream_box,
+            MetadataValue::new(
+                RawValue::Integer(vec![header.bits_per_sample.into()]),
+                depth,
+            ),
+        );
+        insert("Orientation", codestream_box, integer(header.orientation));
+        insert(
+            "ExtraChannels",
+            codestream_box,
+            integer(header.extra_channels),
+        );
+        insert("Animated", codestream_box, yes_no(header.animated));
+        insert("Preview", codestream_box, yes_no(header.has_preview));
+    }
+
+    if is_container {
+        let boxes = jxl_boxes(bytes);
+        let has = |kind: &[u8; 4]| boxes.iter().any(|b| &b.box_type == kind);
+        insert("Container", "JXL ", yes_no(true));
+        insert("JPEGReconstruction", "jbrd", yes_no(has(b"jbrd")));
+        let compressed: Vec<String> = boxes
+            .iter()
+            .filter(|b| b.compressed)
+            .map(|b| String::from_utf8_lossy(&b.box_type).trim().to_string())
+            .collect();
+        if !compressed.is_empty() {
+            insert(
+                "CompressedMetadata",
+                "brob",
+                MetadataValue::text(compressed.join(", ")),
+            );
+        }
+        let jumbf = boxes.iter().filter(|b| &b.box_type == b"jumb").count();
+        if jumbf > 0 {
+            insert("JUMBFBoxes", "jumb", integer(jumbf as u32));
+        }
+    }
+
+    map
+}
@@ -4,22 +4,47 @@
 //! provides utilities for parsing metadata, presenting it to the user and
 //! exporting or cleaning the underlying file.
 
+pub mod animation;
 pub mod app;
 pub mod archive;
 pub mod binary_cleaner;
+pub mod bmff;
+pub mod c2pa;
+pub mod c2pa_wasm;
 pub mod components;
+pub mod container;
+pub mod diagnostics;
 pub mod exif;
 pub mod exif_core;
 pub mod exif_wasm;
 pub mod export;
+pub mod file_type;
 pub mod gps_privacy;
+pub mod heif;
+pub mod icc;
+pub mod iptc;
+pub mod jpeg;
+pub mod jxl;
+pub mod limits;
+pub mod makernotes;
 pub mod metadata_info;
+pub mod motion_photo;
+pub mod mpf;
+pub mod pdf;
+pub mod png_text;
 pub mod preferences;
+pub mod raw;
+pub mod svg;
+pub mod thumbnail;
+pub mod timestamp;
 pub mod types;
 pub mod utils;
 pub mod utils_core;
 pub mod utils_hash;
 pub mod utils_wasm;
+pub mod video;
+pub mod xml_tree;
+pub mod xmp;
 
 use app::App;
 use wasm_bindgen::prelude::*;
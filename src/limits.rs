@@ -0,0 +1,150 @@
+//! Limits on the work done parsing untrusted files.
+//!
+//! Every file comes from an upload, so lengths, counts and offsets read from
+//! it are not trusted: arithmetic on them is checked, declared sizes are
+//! capped, decompression stops at a fixed output size and walks stop after a
+//! fixed number of blocks. Going over a limit is reported as a
+//! [`LimitExceeded`] error instead of a panic, a wrapped offset or an
+//! unbounded allocation.
+
+use std::fmt;
+use std::io::{self, Read};
+
+/// Largest file the cleaner accepts.
+pub const MAX_FILE_SIZE: usize = 512 << 20;
+/// Largest single segment, chunk or box a cleaner will copy. PNG caps chunk
+/// lengths at 2^31 - 1; the same bound is used for RIFF chunks.
+pub const MAX_CHUNK_SIZE: u64 = (1 << 31) - 1;
+/// Most segments, chunks, boxes, blocks or entries read in one walk.
+pub const MAX_BLOCKS: usize = 100_000;
+/// Most bytes inflated from one compressed metadata stream.
+pub const MAX_INFLATED_SIZE: usize = 64 << 20;
+/// Most files read from one archive.
+pub const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+/// Largest single file extracted from an archive.
+pub const MAX_ARCHIVE_ENTRY_SIZE: u64 = 256 << 20;
+/// Most bytes extracted from one archive in total.
+pub const MAX_ARCHIVE_SIZE: u64 = 1 << 30;
+/// Most bytes of embedded images decoded and previewed from one file.
+pub const MAX_EMBEDDED_IMAGES_SIZE: usize = 64 << 20;
+/// Highest compression ratio accepted for an archive entry. Images are
+/// already compressed, so only crafted entries come close.
+pub const MAX_COMPRESSION_RATIO: u64 = 100;
+
+/// A parser stopped because the input asked for more than a limit allows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LimitExceeded {
+    /// What went over the limit, e.g. `"PNG chunk size"`.
+    pub what: String,
+    pub limit: u64,
+}
+
+impl LimitExceeded {
+    pub fn new(what: impl Into<String>, limit: u64) -> Self {
+        Self {
+            what: what.into(),
+            limit,
+        }
+    }
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Limit exceeded: {} is over {}", self.what, self.limit)
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+impl From<LimitExceeded> for String {
+    fn from(error: LimitExceeded) -> Self {
+        error.to_string()
+    }
+}
+
+/// Fail if `size` is over `limit`.
+pub fn check(what: &str, size: u64, limit: u64) -> Result<(), LimitExceeded> {
+    match size > limit {
+        true => Err(LimitExceeded::new(what, limit)),
+        false => Ok(()),
+    }
+}
+
+/// Counts the steps of a walk so crafted input cannot make it run for ever,
+/// such as a run of empty chunks or a loop of offsets. A budget can also
+/// count bytes, capping the total size of the blocks a walk decodes.
+#[derive(Clone, Debug)]
+pub struct Budget {
+    what: &'static str,
+    limit: usize,
+    used: usize,
+}
+
+impl Budget {
+    pub fn new(what: &'static str, limit: usize) -> Self {
+        Self {
+            what,
+            limit,
+            used: 0,
+        }
+    }
+
+    /// A budget of [`MAX_BLOCKS`] steps.
+    pub fn blocks(what: &'static str) -> Self {
+        Self::new(what, MAX_BLOCKS)
+    }
+
+    /// Take one step, failing once the limit is used up.
+    pub fn spend(&mut self) -> Result<(), LimitExceeded> {
+        self.spend_many(1)
+    }
+
+    /// Take `n` steps at once, such as the bytes of a block charged against
+    /// a size budget. Fails, spending nothing, if that goes over the limit.
+    pub fn spend_many(&mut self, n: usize) -> Result<(), LimitExceeded> {
+        match self.used.checked_add(n) {
+            Some(used) if used <= self.limit => {
+                self.used = used;
+                Ok(())
+            }
+            _ => Err(LimitExceeded::new(self.what, self.limit as u64)),
+        }
+    }
+}
+
+/// Offset just past a block that starts at `start` with a `header` of fixed
+/// size, a declared `length` and a `trailer` of fixed size, or `None` if it
+/// overflows or runs past `data_len`.
+pub fn block_end(
+    start: usize,
+    header: usize,
+    length: u64,
+    trailer: usize,
+    data_len: usize,
+) -> Option<usize> {
+    start
+        .checked_add(header)?
+        .checked_add(usize::try_from(length).ok()?)?
+        .checked_add(trailer)
+        .filter(|end| *end <= data_len)
+}
+
+/// Read `reader` to the end into `buf`, as [`Read::read_to_end`] does, but
+/// fail with a [`LimitExceeded`] error once more than `limit` bytes come out.
+/// On that error `buf` is left as it was.
+pub fn read_to_end_limited(
+    reader: impl Read,
+    buf: &mut Vec<u8>,
+    limit: usize,
+) -> io::Result<usize> {
+    let start = buf.len();
+    let read = reader.take(limit as u64 + 1).read_to_end(buf)?;
+    if read > limit {
+        buf.truncate(start);
+        return Err(io::Error::other(LimitExceeded::new(
+            "decompressed size",
+            limit as u64,
+        )));
+    }
+    Ok(read)
+}
@@ -0,0 +1,73 @@
+//! Apple MakerNote decoding.
+//!
+//! iPhone notes start with `Apple iOS\0`, a two byte version and a byte
+//! order mark, followed by an IFD at offset 14 whose offsets are relative to
+//! the start of the note. Besides capture details they carry identifiers
+//! that tie a photo to its Live Photo video and to the other shots of a
+//! burst.
+
+use super::{FieldSink, MakerNote};
+use crate::container::{self, ByteOrder};
+use crate::types::{MetadataValue, RawValue};
+use std::collections::HashMap;
+
+/// Signature at the start of Apple MakerNotes.
+pub const HEADER: &[u8] = b"Apple iOS\0";
+
+const MAKER_NOTE_VERSION: u16 = 0x0001;
+const HDR_IMAGE_TYPE: u16 = 0x000A;
+const BURST_UUID: u16 = 0x000B;
+const CONTENT_IDENTIFIER: u16 = 0x0011;
+const IMAGE_UNIQUE_ID: u16 = 0x0015;
+
+/// Returns `true` for notes with the Apple header.
+pub fn matches(note: &MakerNote) -> bool {
+    note.data.starts_with(HEADER)
+}
+
+fn hdr_image_type_name(value: u32) -> String {
+    match value {
+        3 => "HDR Image".to_string(),
+        4 => "Original Image".to_string(),
+        other => format!("Unknown ({})", other),
+    }
+}
+
+/// Decode the Apple MakerNote identifiers.
+pub fn decode(note: &MakerNote) -> HashMap<String, MetadataValue> {
+    let mut sink = FieldSink::new("Apple");
+    let order = match note.data.get(12..14) {
+        Some(b"II") => ByteOrder::Little,
+        Some(b"MM") => ByteOrder::Big,
+        _ => return sink.finish(),
+    };
+    let Some(ifd) = container::tiff_directory(note.data, order, 14) else {
+        return sink.finish();
+    };
+
+    // Stored as SLONG, which `TiffEntry::unsigned` does not cover
+    if let Some(version) = ifd
+        .get(MAKER_NOTE_VERSION)
+        .and_then(|e| order.u32(e.value, 0))
+    {
+        sink.insert(
+            "MakerNoteVersion",
+            MAKER_NOTE_VERSION,
+            RawValue::Integer(vec![(version as i32).into()]),
+            (version as i32).to_string(),
+        );
+    }
+    if let Some(kind) = ifd.get(HDR_IMAGE_TYPE).and_then(|e| order.u32(e.value, 0)) {
+        sink.insert(
+            "HDRImageType",
+            HDR_IMAGE_TYPE,
+            RawValue::Integer(vec![kind.into()]),
+            hdr_image_type_name(kind),
+        );
+    }
+    sink.text("BurstUUID", ifd.get(BURST_UUID));
+    sink.text("ContentIdentifier", ifd.get(CONTENT_IDENTIFIER));
+    sink.text("ImageUniqueID", ifd.get(IMAGE_UNIQUE_ID));
+
+    sink.finish()
+}
@@ -0,0 +1,101 @@
+//! Canon MakerNote decoding.
+//!
+//! Canon writes a plain IFD with no header whose offsets are relative to the
+//! start of the EXIF TIFF block, in the same byte order.
+
+use super::{FieldSink, MakerNote};
+use crate::container;
+use crate::types::RawValue;
+use std::collections::HashMap;
+
+const IMAGE_TYPE: u16 = 0x0006;
+const FIRMWARE_VERSION: u16 = 0x0007;
+const FILE_NUMBER: u16 = 0x0008;
+const OWNER_NAME: u16 = 0x0009;
+const SERIAL_NUMBER: u16 = 0x000C;
+const MODEL_ID: u16 = 0x0010;
+const IMAGE_UNIQUE_ID: u16 = 0x0028;
+const FILE_INFO: u16 = 0x0093;
+const LENS_MODEL: u16 = 0x0095;
+const INTERNAL_SERIAL_NUMBER: u16 = 0x0096;
+
+/// Returns `true` for notes written by Canon bodies.
+pub fn matches(note: &MakerNote) -> bool {
+    note.make_is("canon")
+}
+
+/// Decode the Canon MakerNote fields that identify the body, lens and owner.
+///
+/// Canon only records a true shutter count for EOS-1D series bodies (in
+/// `FileInfo`); other models expose the running `FileNumber` instead.
+pub fn decode(note: &MakerNote) -> HashMap<String, crate::types::MetadataValue> {
+    let mut sink = FieldSink::new("Canon");
+    let Some(ifd) = container::tiff_directory(note.tiff, note.order, note.offset as u32) else {
+        return sink.finish();
+    };
+    let order = note.order;
+
+    sink.text("ImageType", ifd.get(IMAGE_TYPE));
+    sink.text("FirmwareVersion", ifd.get(FIRMWARE_VERSION));
+    sink.text("OwnerName", ifd.get(OWNER_NAME));
+    sink.text("LensModel", ifd.get(LENS_MODEL));
+    sink.text("InternalSerialNumber", ifd.get(INTERNAL_SERIAL_NUMBER));
+
+    if let Some(number) = ifd.get(FILE_NUMBER).and_then(|e| e.first_unsigned(order)) {
+        // Directory and file index, e.g. 100-1234
+        let display = format!("{}-{:04}", number / 10000, number % 10000);
+        sink.insert(
+            "FileNumber",
+            FILE_NUMBER,
+            RawValue::Integer(vec![number.into()]),
+            display,
+        );
+    }
+
+    if let Some(serial) = ifd.get(SERIAL_NUMBER).and_then(|e| e.first_unsigned(order)) {
+        // Serials are printed zero-padded to ten digits, so keep them as text
+        let display = format!("{:010}", serial);
+        sink.insert(
+            "SerialNumber",
+            SERIAL_NUMBER,
+            RawValue::Text(display.clone()),
+            display,
+        );
+    }
+
+    if let Some(id) = ifd.get(MODEL_ID).and_then(|e| e.first_unsigned(order)) {
+        sink.insert(
+            "ModelID",
+            MODEL_ID,
+            RawValue::Integer(vec![id.into()]),
+            format!("0x{:08X}", id),
+        );
+    }
+
+    if let Some(entry) = ifd.get(IMAGE_UNIQUE_ID)
+        && entry.value.iter().any(|&b| b != 0)
+    {
+        let hex: String = entry.value.iter().map(|b| format!("{:02x}", b)).collect();
+        sink.insert(
+            "ImageUniqueID",
+            IMAGE_UNIQUE_ID,
+            RawValue::Bytes(entry.value.to_vec()),
+            hex,
+        );
+    }
+
+    if note.model.contains("EOS-1D")
+        && let Some(words) = ifd.get(FILE_INFO).map(|e| e.unsigned(order))
+        && words.len() > 2
+    {
+        let count = (words[1] << 16) | words[2];
+        sink.insert(
+            "ShutterCount",
+            FILE_INFO,
+            RawValue::Integer(vec![count.into()]),
+            count.to_string(),
+        );
+    }
+
+    sink.finish()
+}
@@ -0,0 +1,73 @@
+//! Fujifilm MakerNote decoding.
+//!
+//! Fujifilm notes start with `FUJIFILM` and a little-endian offset to the
+//! IFD. The IFD is always little-endian, whatever the EXIF byte order, and
+//! its offsets are relative to the start of the note.
+
+use super::{FieldSink, MakerNote};
+use crate::container::{self, ByteOrder};
+use crate::types::{MetadataValue, RawValue};
+use std::collections::HashMap;
+
+/// Signature at the start of Fujifilm MakerNotes.
+pub const HEADER: &[u8] = b"FUJIFILM";
+
+const VERSION: u16 = 0x0000;
+const INTERNAL_SERIAL_NUMBER: u16 = 0x0010;
+const MIN_FOCAL_LENGTH: u16 = 0x1404;
+const MAX_FOCAL_LENGTH: u16 = 0x1405;
+const MAX_APERTURE_AT_MIN_FOCAL: u16 = 0x1406;
+const MAX_APERTURE_AT_MAX_FOCAL: u16 = 0x1407;
+const IMAGE_COUNT: u16 = 0x1438;
+
+/// Returns `true` for notes with the Fujifilm header.
+pub fn matches(note: &MakerNote) -> bool {
+    note.data.starts_with(HEADER)
+}
+
+/// Decode the Fujifilm MakerNote fields that identify the body and lens.
+pub fn decode(note: &MakerNote) -> HashMap<String, MetadataValue> {
+    let mut sink = FieldSink::new("Fujifilm");
+    let order = ByteOrder::Little;
+    let Some(ifd) = order
+        .u32(note.data, HEADER.len())
+        .and_then(|offset| container::tiff_directory(note.data, order, offset))
+    else {
+        return sink.finish();
+    };
+
+    sink.text("Version", ifd.get(VERSION));
+    sink.text("InternalSerialNumber", ifd.get(INTERNAL_SERIAL_NUMBER));
+
+    if let Some(count) = ifd.get(IMAGE_COUNT).and_then(|e| e.first_unsigned(order)) {
+        // The top bit flags something else; the count is the low 15 bits
+        let count = count & 0x7FFF;
+        sink.insert(
+            "ImageCount",
+            IMAGE_COUNT,
+            RawValue::Integer(vec![count.into()]),
+            count.to_string(),
+        );
+    }
+
+    let rational = |tag| {
+        ifd.get(tag)
+            .and_then(|e| e.rationals(order).first().map(|r| r.to_f64()))
+            .filter(|v| v.is_finite())
+    };
+    if let (Some(min), Some(max)) = (rational(MIN_FOCAL_LENGTH), rational(MAX_FOCAL_LENGTH)) {
+        let aperture = (
+            rational(MAX_APERTURE_AT_MIN_FOCAL).unwrap_or(0.0),
+            rational(MAX_APERTURE_AT_MAX_FOCAL).unwrap_or(0.0),
+        );
+        let display = super::format_lens_range((min, max), aperture);
+        sink.insert(
+            "Lens",
+            MIN_FOCAL_LENGTH,
+            RawValue::Text(display.clone()),
+            display,
+        );
+    }
+
+    sink.finish()
+}
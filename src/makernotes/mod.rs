@@ -0,0 +1,239 @@
+//! Decoders for vendor-specific EXIF MakerNote blocks.
+//!
+//! kamadak-exif only exposes the `MakerNote` tag as opaque bytes. The vendor
+//! formats are mostly TIFF-style IFDs with their own headers and offset
+//! bases, so decoding needs the whole EXIF TIFF block rather than just the
+//! MakerNote value. Decoded fields are keyed `<Vendor>:<Field>`.
+//!
+//! Each vendor module exposes a `matches`/`decode` pair; the set of decoders
+//! tried for a file is the registry in [`DECODERS`].
+
+pub mod apple;
+pub mod canon;
+pub mod fujifilm;
+pub mod nikon;
+pub mod sony;
+
+use crate::container::{self, ByteOrder, TiffEntry};
+use crate::diagnostics::Diagnostic;
+use crate::types::{MetadataSource, MetadataValue, RawValue};
+use std::collections::HashMap;
+
+/// EXIF sub-IFD pointer tag.
+const EXIF_IFD_POINTER: u16 = 0x8769;
+/// MakerNote tag inside the EXIF sub-IFD.
+const MAKER_NOTE_TAG: u16 = 0x927C;
+
+/// A MakerNote located inside an EXIF TIFF block.
+#[derive(Debug, Clone, Copy)]
+pub struct MakerNote<'a> {
+    /// The whole EXIF TIFF block, starting at its byte order mark.
+    pub tiff: &'a [u8],
+    /// Byte order of the EXIF block.
+    pub order: ByteOrder,
+    /// Offset of the MakerNote value within `tiff`.
+    pub offset: usize,
+    /// The MakerNote value bytes.
+    pub data: &'a [u8],
+    /// Camera make from IFD0.
+    pub make: &'a str,
+    /// Camera model from IFD0.
+    pub model: &'a str,
+}
+
+/// Find the MakerNote in an EXIF TIFF block.
+pub fn locate_maker_note<'a>(
+    tiff: &'a [u8],
+    make: &'a str,
+    model: &'a str,
+) -> Option<MakerNote<'a>> {
+    let (order, ifd0_offset) = container::tiff_header(tiff)?;
+    let ifd0 = container::tiff_directory(tiff, order, ifd0_offset)?;
+    let exif_offset = ifd0.get(EXIF_IFD_POINTER)?.first_unsigned(order)?;
+    let exif_ifd = container::tiff_directory(tiff, order, exif_offset)?;
+    let entry = exif_ifd.get(MAKER_NOTE_TAG)?;
+    (!entry.value.is_empty()).then_some(MakerNote {
+        tiff,
+        order,
+        offset: entry.value_offset,
+        data: entry.value,
+        make,
+        model,
+    })
+}
+
+impl MakerNote<'_> {
+    /// Returns `true` if the camera make starts with `prefix` (ignoring case).
+    pub fn make_is(&self, prefix: &str) -> bool {
+        self.make.trim().to_lowercase().starts_with(prefix)
+    }
+}
+
+/// A vendor MakerNote decoder.
+#[derive(Debug, Clone, Copy)]
+pub struct MakerNoteDecoder {
+    /// Vendor name, also used as the key group (`<Vendor>:<Field>`).
+    pub vendor: &'static str,
+    /// Returns `true` if this decoder understands the note.
+    pub matches: fn(&MakerNote) -> bool,
+    /// Decode the note into `exif_data` entries.
+    pub decode: fn(&MakerNote) -> HashMap<String, MetadataValue>,
+}
+
+/// MakerNote decoders, tried in order until one matches. Formats recognised
+/// by their header come first so rebadged bodies are decoded by note layout
+/// rather than by the make they report.
+pub static DECODERS: &[MakerNoteDecoder] = &[
+    MakerNoteDecoder {
+        vendor: "Apple",
+        matches: apple::matches,
+        decode: apple::decode,
+    },
+    MakerNoteDecoder {
+        vendor: "Fujifilm",
+        matches: fujifilm::matches,
+        decode: fujifilm::decode,
+    },
+    MakerNoteDecoder {
+        vendor: "Nikon",
+        matches: nikon::matches,
+        decode: nikon::decode,
+    },
+    MakerNoteDecoder {
+        vendor: "Sony",
+        matches: sony::matches,
+        decode: sony::decode,
+    },
+    MakerNoteDecoder {
+        vendor: "Canon",
+        matches: canon::matches,
+        decode: canon::decode,
+    },
+];
+
+/// Decode the MakerNote of an EXIF TIFF block with the first matching
+/// decoder of [`DECODERS`].
+pub fn decode_maker_note(tiff: &[u8], make: &str, model: &str) -> HashMap<String, MetadataValue> {
+    decode_maker_note_with_diagnostics(tiff, make, model, &mut Vec::new())
+}
+
+/// Decode the MakerNote as [`decode_maker_note`] does, reporting a note that
+/// a decoder recognised but could not read any field from. Notes no decoder
+/// recognises are not a problem; they are simply not supported.
+pub fn decode_maker_note_with_diagnostics(
+    tiff: &[u8],
+    make: &str,
+    model: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, MetadataValue> {
+    let Some(note) = locate_maker_note(tiff, make, model) else {
+        return HashMap::new();
+    };
+    let Some(decoder) = DECODERS.iter().find(|decoder| (decoder.matches)(&note)) else {
+        return HashMap::new();
+    };
+    let fields = (decoder.decode)(&note);
+    if fields.is_empty() {
+        diagnostics.push(Diagnostic::warning(
+            "MakerNote",
+            None,
+            format!(
+                "{} MakerNote of {} bytes holds no readable fields",
+                decoder.vendor,
+                note.data.len()
+            ),
+        ));
+    }
+    fields
+}
+
+/// Decode the MakerNote of an EXIF TIFF block with the first matching
+/// decoder of `decoders`.
+pub fn decode_maker_note_with(
+    tiff: &[u8],
+    make: &str,
+    model: &str,
+    decoders: &[MakerNoteDecoder],
+) -> HashMap<String, MetadataValue> {
+    let Some(note) = locate_maker_note(tiff, make, model) else {
+        return HashMap::new();
+    };
+    decoders
+        .iter()
+        .find(|decoder| (decoder.matches)(&note))
+        .map(|decoder| (decoder.decode)(&note))
+        .unwrap_or_default()
+}
+
+/// Format a lens focal and aperture range, e.g. `24-70mm f/2.8` or
+/// `18-55mm f/3.5-5.6`.
+pub(crate) fn format_lens_range(focal: (f64, f64), aperture: (f64, f64)) -> String {
+    let range = |(min, max): (f64, f64), precision: usize| {
+        if (min - max).abs() < 0.05 || max == 0.0 {
+            format!("{:.*}", precision, min)
+        } else {
+            format!("{:.*}-{:.*}", precision, min, precision, max)
+        }
+    };
+    let focal = range(focal, 0);
+    if aperture.0 > 0.0 {
+        format!("{}mm f/{}", focal, range(aperture, 1))
+    } else {
+        format!("{}mm", focal)
+    }
+}
+
+/// Collects decoded fields for one vendor.
+pub(crate) struct FieldSink {
+    vendor: &'static str,
+    fields: HashMap<String, MetadataValue>,
+}
+
+impl FieldSink {
+    pub(crate) fn new(vendor: &'static str) -> Self {
+        Self {
+            vendor,
+            fields: HashMap::new(),
+        }
+    }
+
+    /// Insert a field, skipping empty displays.
+    pub(crate) fn insert(&mut self, name: &str, tag: u16, raw: RawValue, display: String) {
+        if display.is_empty() {
+            return;
+        }
+        self.fields.insert(
+            format!("{}:{}", self.vendor, name),
+            MetadataValue::new(raw, display).with_source(MetadataSource::MakerNote {
+                vendor: self.vendor,
+                tag,
+            }),
+        );
+    }
+
+    /// Insert a text entry.
+    pub(crate) fn text(&mut self, name: &str, entry: Option<&TiffEntry>) {
+        if let Some(entry) = entry {
+            let text = entry.text();
+            self.insert(name, entry.tag, RawValue::Text(text.clone()), text);
+        }
+    }
+
+    /// Insert the first unsigned integer of an entry.
+    pub(crate) fn unsigned(&mut self, name: &str, entry: Option<&TiffEntry>, order: ByteOrder) {
+        if let Some(entry) = entry
+            && let Some(value) = entry.first_unsigned(order)
+        {
+            self.insert(
+                name,
+                entry.tag,
+                RawValue::Integer(vec![value.into()]),
+                value.to_string(),
+            );
+        }
+    }
+
+    pub(crate) fn finish(self) -> HashMap<String, MetadataValue> {
+        self.fields
+    }
+}
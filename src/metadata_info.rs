@@ -678,9 +678,1346 @@ pub static METADATA_DB: &[(&str, MetadataInfo)] = &[
             category: "🔍 Lens",
             explanation: "Maximum aperture (lowest f-number) the lens can achieve",
         },
+    ), // XMP
+    (
+        "XMP-dc:creator",
+        MetadataInfo {
+            category: "📝 XMP",
+            explanation: "Author or creator recorded in XMP - directly identifies a person",
+        },
+    ),
+    (
+        "XMP-dc:rights",
+        MetadataInfo {
+            category: "📝 XMP",
+            explanation: "Copyright or usage rights statement recorded in XMP",
+        },
+    ),
+    (
+        "XMP-xmp:CreatorTool",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Application or device firmware that created the file",
+        },
+    ),
+    (
+        "XMP-xmp:CreateDate",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "When the file or original image was created",
+        },
+    ),
+    (
+        "XMP-xmp:ModifyDate",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "When the file was last modified",
+        },
+    ),
+    (
+        "XMP-xmp:MetadataDate",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "When the metadata was last changed",
+        },
+    ),
+    (
+        "XMP-photoshop:DateCreated",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "When the intellectual content of the image was created",
+        },
+    ),
+    (
+        "XMP-xmpMM:DocumentID",
+        MetadataInfo {
+            category: "📝 XMP",
+            explanation: "Unique identifier of the document - links copies and derivatives of the same file",
+        },
+    ),
+    (
+        "XMP-xmpMM:OriginalDocumentID",
+        MetadataInfo {
+            category: "📝 XMP",
+            explanation: "Identifier of the original document this file was derived from",
+        },
+    ),
+    (
+        "XMP-xmpMM:InstanceID",
+        MetadataInfo {
+            category: "📝 XMP",
+            explanation: "Identifier of this particular saved version of the document",
+        },
+    ),
+    // IPTC-IIM
+    (
+        "IPTC:ObjectName",
+        MetadataInfo {
+            category: "📰 IPTC",
+            explanation: "Short title or reference name of the image",
+        },
+    ),
+    (
+        "IPTC:Headline",
+        MetadataInfo {
+            category: "📰 IPTC",
+            explanation: "Publishable summary of the image content",
+        },
+    ),
+    (
+        "IPTC:Caption-Abstract",
+        MetadataInfo {
+            category: "📰 IPTC",
+            explanation: "Caption describing who, what and where - often names people in the photo",
+        },
+    ),
+    (
+        "IPTC:Keywords",
+        MetadataInfo {
+            category: "📰 IPTC",
+            explanation: "Keywords describing the image content",
+        },
+    ),
+    (
+        "IPTC:By-line",
+        MetadataInfo {
+            category: "📰 IPTC",
+            explanation: "Name of the photographer or creator - directly identifies a person",
+        },
+    ),
+    (
+        "IPTC:By-lineTitle",
+        MetadataInfo {
+            category: "📰 IPTC",
+            explanation: "Job title of the photographer (e.g., Staff Photographer)",
+        },
+    ),
+    (
+        "IPTC:Credit",
+        MetadataInfo {
+            category: "📰 IPTC",
+            explanation: "Provider credit line required for publication",
+        },
+    ),
+    (
+        "IPTC:Source",
+        MetadataInfo {
+            category: "📰 IPTC",
+            explanation: "Original owner of the copyright or the supplying agency",
+        },
+    ),
+    (
+        "IPTC:CopyrightNotice",
+        MetadataInfo {
+            category: "📰 IPTC",
+            explanation: "Copyright statement naming the rights holder",
+        },
+    ),
+    (
+        "IPTC:Contact",
+        MetadataInfo {
+            category: "📰 IPTC",
+            explanation: "Contact details of the creator - may include phone numbers or email addresses",
+        },
+    ),
+    (
+        "IPTC:Writer-Editor",
+        MetadataInfo {
+            category: "📰 IPTC",
+            explanation: "Person who wrote or edited the caption",
+        },
+    ),
+    (
+        "IPTC:SpecialInstructions",
+        MetadataInfo {
+            category: "📰 IPTC",
+            explanation: "Editorial instructions such as embargoes or usage restrictions",
+        },
+    ),
+    (
+        "IPTC:DateCreated",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "Date the intellectual content was created (CCYYMMDD)",
+        },
+    ),
+    (
+        "IPTC:TimeCreated",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "Time the intellectual content was created, with UTC offset",
+        },
+    ),
+    (
+        "IPTC:City",
+        MetadataInfo {
+            category: "📍 Location",
+            explanation: "City where the photo was taken",
+        },
+    ),
+    (
+        "IPTC:Sub-location",
+        MetadataInfo {
+            category: "📍 Location",
+            explanation: "Specific location within the city, such as a street or venue",
+        },
+    ),
+    (
+        "IPTC:Province-State",
+        MetadataInfo {
+            category: "📍 Location",
+            explanation: "Province or state where the photo was taken",
+        },
+    ),
+    (
+        "IPTC:Country-PrimaryLocationName",
+        MetadataInfo {
+            category: "📍 Location",
+            explanation: "Country where the photo was taken",
+        },
+    ),
+    (
+        "IPTC:Country-PrimaryLocationCode",
+        MetadataInfo {
+            category: "📍 Location",
+            explanation: "ISO country code of the location shown",
+        },
+    ),
+    // ICC profile
+    (
+        "ICC:ProfileDescription",
+        MetadataInfo {
+            category: "🎨 Color Profile",
+            explanation: "Name of the embedded color profile - calibrated monitor profiles often name the display or computer",
+        },
+    ),
+    (
+        "ICC:ProfileVersion",
+        MetadataInfo {
+            category: "🎨 Color Profile",
+            explanation: "ICC specification version the profile follows",
+        },
+    ),
+    (
+        "ICC:ProfileClass",
+        MetadataInfo {
+            category: "🎨 Color Profile",
+            explanation: "Kind of device the profile describes (display, input, output)",
+        },
+    ),
+    (
+        "ICC:ColorSpaceData",
+        MetadataInfo {
+            category: "🎨 Color Profile",
+            explanation: "Color space of the image data (e.g., RGB, CMYK, GRAY)",
+        },
+    ),
+    (
+        "ICC:ProfileConnectionSpace",
+        MetadataInfo {
+            category: "🎨 Color Profile",
+            explanation: "Intermediate space used for conversions (XYZ or Lab)",
+        },
+    ),
+    (
+        "ICC:RenderingIntent",
+        MetadataInfo {
+            category: "🎨 Color Profile",
+            explanation: "Preferred strategy for mapping out-of-gamut colors",
+        },
+    ),
+    (
+        "ICC:ProfileDateTime",
+        MetadataInfo {
+            category: "🎨 Color Profile",
+            explanation: "When the profile was created - for calibrated profiles, the calibration date",
+        },
+    ),
+    (
+        "ICC:ProfileCopyright",
+        MetadataInfo {
+            category: "🎨 Color Profile",
+            explanation: "Copyright notice of the profile",
+        },
+    ),
+    (
+        "ICC:DeviceModelDesc",
+        MetadataInfo {
+            category: "🎨 Color Profile",
+            explanation: "Model of the device the profile was made for",
+        },
+    ),
+    // Canon MakerNote
+    (
+        "Canon:OwnerName",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Owner name programmed into the camera body - directly identifies a person",
+        },
+    ),
+    (
+        "Canon:SerialNumber",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Camera body serial number - links every photo to one specific camera",
+        },
+    ),
+    (
+        "Canon:InternalSerialNumber",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Internal serial number of the camera's main board",
+        },
+    ),
+    (
+        "Canon:FirmwareVersion",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Camera firmware version at the time of capture",
+        },
+    ),
+    (
+        "Canon:ImageType",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Camera model name as recorded by the firmware",
+        },
+    ),
+    (
+        "Canon:ModelID",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Canon internal model identifier",
+        },
+    ),
+    (
+        "Canon:FileNumber",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Folder and file counter from the camera - indicates how many photos were taken",
+        },
+    ),
+    (
+        "Canon:ShutterCount",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Total shutter actuations of the body - a fingerprint that changes slowly over time",
+        },
+    ),
+    (
+        "Canon:ImageUniqueID",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Unique identifier assigned to this photo by the camera",
+        },
+    ),
+    (
+        "Canon:LensModel",
+        MetadataInfo {
+            category: "🔍 Lens",
+            explanation: "Lens model reported to the camera body",
+        },
+    ),
+    // Nikon MakerNote
+    (
+        "Nikon:MakerNoteVersion",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Version of the Nikon MakerNote format",
+        },
+    ),
+    (
+        "Nikon:SerialNumber",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Camera body serial number - links every photo to one specific camera",
+        },
+    ),
+    (
+        "Nikon:ShutterCount",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Total shutter actuations of the body - a fingerprint that changes slowly over time",
+        },
+    ),
+    (
+        "Nikon:ImageCount",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Number of images recorded by the camera",
+        },
+    ),
+    (
+        "Nikon:Lens",
+        MetadataInfo {
+            category: "🔍 Lens",
+            explanation: "Focal length and maximum aperture range of the lens",
+        },
+    ),
+    (
+        "Nikon:LensID",
+        MetadataInfo {
+            category: "🔍 Lens",
+            explanation: "Nikon lens identification bytes, decrypted from the LensData block",
+        },
+    ),
+    // Sony MakerNote
+    (
+        "Sony:SerialNumber",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Camera body serial number - links every photo to one specific camera",
+        },
+    ),
+    (
+        "Sony:SonyModelID",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Sony internal model identifier",
+        },
+    ),
+    (
+        "Sony:ShutterCount",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Total shutter actuations of the body - a fingerprint that changes slowly over time",
+        },
+    ),
+    (
+        "Sony:LensType",
+        MetadataInfo {
+            category: "🔍 Lens",
+            explanation: "Sony lens type identifier reported to the body",
+        },
+    ),
+    // Fujifilm MakerNote
+    (
+        "Fujifilm:Version",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Version of the Fujifilm MakerNote format",
+        },
+    ),
+    (
+        "Fujifilm:InternalSerialNumber",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Internal serial number of the camera, including its manufacture date",
+        },
+    ),
+    (
+        "Fujifilm:ImageCount",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Number of images taken by the camera - close to a shutter count",
+        },
+    ),
+    (
+        "Fujifilm:Lens",
+        MetadataInfo {
+            category: "🔍 Lens",
+            explanation: "Focal length and maximum aperture range of the lens",
+        },
+    ),
+    // Apple MakerNote
+    (
+        "Apple:MakerNoteVersion",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Version of the Apple MakerNote format",
+        },
+    ),
+    (
+        "Apple:HDRImageType",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Whether this is the HDR result or the original exposure",
+        },
+    ),
+    (
+        "Apple:BurstUUID",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Identifier shared by every photo of a burst - links the shots together",
+        },
+    ),
+    (
+        "Apple:ContentIdentifier",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Identifier pairing a Live Photo with its video - links the two files",
+        },
+    ),
+    (
+        "Apple:ImageUniqueID",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Unique identifier assigned to this photo by the device",
+        },
+    ),
+    // PNG textual chunks
+    (
+        "PNG:Title",
+        MetadataInfo {
+            category: "🖼️ PNG Text",
+            explanation: "Short title or caption of the image",
+        },
+    ),
+    (
+        "PNG:Author",
+        MetadataInfo {
+            category: "🖼️ PNG Text",
+            explanation: "Name of the image's creator - directly identifies a person",
+        },
+    ),
+    (
+        "PNG:Description",
+        MetadataInfo {
+            category: "🖼️ PNG Text",
+            explanation: "Description of the image, possibly long",
+        },
+    ),
+    (
+        "PNG:Copyright",
+        MetadataInfo {
+            category: "🖼️ PNG Text",
+            explanation: "Copyright notice, usually naming the owner",
+        },
+    ),
+    (
+        "PNG:Creation Time",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "Time the original image was created, as free-form text",
+        },
+    ),
+    (
+        "PNG:ModifyDate",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "Time of the last modification from the tIME chunk (UTC)",
+        },
+    ),
+    (
+        "PNG:Software",
+        MetadataInfo {
+            category: "🖼️ PNG Text",
+            explanation: "Software used to create the image",
+        },
+    ),
+    (
+        "PNG:Disclaimer",
+        MetadataInfo {
+            category: "🖼️ PNG Text",
+            explanation: "Legal disclaimer",
+        },
+    ),
+    (
+        "PNG:Warning",
+        MetadataInfo {
+            category: "🖼️ PNG Text",
+            explanation: "Warning about the nature of the content",
+        },
+    ),
+    (
+        "PNG:Source",
+        MetadataInfo {
+            category: "🖼️ PNG Text",
+            explanation: "Device used to create the image",
+        },
+    ),
+    (
+        "PNG:Comment",
+        MetadataInfo {
+            category: "🖼️ PNG Text",
+            explanation: "Free-form comment, often written by the saving software",
+        },
+    ),
+    (
+        "PNG:parameters",
+        MetadataInfo {
+            category: "🤖 AI Generation",
+            explanation: "Stable Diffusion (AUTOMATIC1111/Forge) prompt, negative prompt, seed, sampler and model",
+        },
+    ),
+    (
+        "PNG:prompt",
+        MetadataInfo {
+            category: "🤖 AI Generation",
+            explanation: "ComfyUI prompt graph as JSON, including every text prompt and model used",
+        },
+    ),
+    (
+        "PNG:workflow",
+        MetadataInfo {
+            category: "🤖 AI Generation",
+            explanation: "ComfyUI editor workflow as JSON, from which the image can be regenerated",
+        },
+    ),
+    // HEIF/AVIF structure
+    (
+        "HEIF:MajorBrand",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Main file type brand from the ftyp box (heic, mif1, avif, ...)",
+        },
+    ),
+    (
+        "HEIF:CompatibleBrands",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Other specifications the file declares conformance with",
+        },
+    ),
+    (
+        "HEIF:ItemCount",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Number of items (images, tiles, thumbnails, metadata) stored in the file",
+        },
+    ),
+    (
+        "HEIF:PrimaryItem",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "ID and coding type of the image shown by default",
+        },
+    ),
+    (
+        "HEIF:ImageWidth",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Width of the primary image in pixels",
+        },
+    ),
+    (
+        "HEIF:ImageHeight",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Height of the primary image in pixels",
+        },
+    ),
+    (
+        "HEIF:ThumbnailCount",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Number of thumbnail images attached to the primary image",
+        },
+    ),
+    (
+        "HEIF:AuxiliaryImages",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Hidden companion images such as depth maps, alpha masks, portrait mattes or HDR gain maps",
+        },
+    ),
+    // JPEG XL structure
+    (
+        "JXL:ImageWidth",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Width of the image in pixels, from the codestream header",
+        },
+    ),
+    (
+        "JXL:ImageHeight",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Height of the image in pixels, from the codestream header",
+        },
+    ),
+    (
+        "JXL:BitsPerSample",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Bit depth of each colour sample",
+        },
+    ),
+    (
+        "JXL:Orientation",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Orientation of the image (1-8, as in EXIF), from the codestream header",
+        },
+    ),
+    (
+        "JXL:ExtraChannels",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Number of channels beyond colour, such as alpha or depth",
+        },
+    ),
+    (
+        "JXL:Animated",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Whether the file holds an animation",
+        },
+    ),
+    (
+        "JXL:Preview",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Whether the codestream includes a preview image",
+        },
+    ),
+    (
+        "JXL:Container",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Whether the codestream is wrapped in the box-based container that can carry metadata",
+        },
+    ),
+    (
+        "JXL:JPEGReconstruction",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Whether this is a losslessly recompressed JPEG that can be restored bit-for-bit",
+        },
+    ),
+    (
+        "JXL:CompressedMetadata",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Metadata boxes stored Brotli-compressed (brob)",
+        },
+    ),
+    (
+        "JXL:JUMBFBoxes",
+        MetadataInfo {
+            category: "🖼️ Technical",
+            explanation: "Number of JUMBF boxes, which usually hold C2PA content credentials",
+        },
+    ),
+    // C2PA Content Credentials
+    (
+        "C2PA:ClaimGenerator",
+        MetadataInfo {
+            category: "📝 Content Credentials",
+            explanation: "Software that created the active C2PA manifest",
+        },
+    ),
+    (
+        "C2PA:Title",
+        MetadataInfo {
+            category: "📝 Content Credentials",
+            explanation: "Title the active C2PA manifest gives the asset",
+        },
+    ),
+    (
+        "C2PA:Signer",
+        MetadataInfo {
+            category: "📝 Content Credentials",
+            explanation: "Subject of the certificate that signed the Content Credentials",
+        },
+    ),
+    (
+        "C2PA:SignatureAlgorithm",
+        MetadataInfo {
+            category: "📝 Content Credentials",
+            explanation: "Algorithm of the claim signature, such as ES256",
+        },
+    ),
+    (
+        "C2PA:Actions",
+        MetadataInfo {
+            category: "📝 Content Credentials",
+            explanation: "Edits and other steps the Content Credentials record, such as created or cropped",
+        },
+    ),
+    (
+        "C2PA:DigitalSourceType",
+        MetadataInfo {
+            category: "🤖 AI Generation",
+            explanation: "How the content was made; trainedAlgorithmicMedia means it was generated by AI",
+        },
+    ),
+    (
+        "C2PA:Ingredients",
+        MetadataInfo {
+            category: "📝 Content Credentials",
+            explanation: "Earlier files the asset was made from",
+        },
+    ),
+    (
+        "C2PA:Assertions",
+        MetadataInfo {
+            category: "📝 Content Credentials",
+            explanation: "Labels of the statements the active manifest makes about the asset",
+        },
+    ),
+    (
+        "C2PA:ManifestCount",
+        MetadataInfo {
+            category: "📝 Content Credentials",
+            explanation: "Number of manifests in the store, one per signed step in the asset's history",
+        },
+    ),
+    // PDF document information
+    (
+        "PDF:Title",
+        MetadataInfo {
+            category: "📄 PDF Document",
+            explanation: "Document title from the PDF information dictionary",
+        },
+    ),
+    (
+        "PDF:Author",
+        MetadataInfo {
+            category: "📄 PDF Document",
+            explanation: "Person who created the document, often the account name of the author's computer",
+        },
+    ),
+    (
+        "PDF:Subject",
+        MetadataInfo {
+            category: "📄 PDF Document",
+            explanation: "Subject of the document",
+        },
+    ),
+    (
+        "PDF:Keywords",
+        MetadataInfo {
+            category: "📄 PDF Document",
+            explanation: "Keywords associated with the document",
+        },
+    ),
+    (
+        "PDF:Creator",
+        MetadataInfo {
+            category: "📄 PDF Document",
+            explanation: "Application that created the original document before it was converted to PDF",
+        },
+    ),
+    (
+        "PDF:Producer",
+        MetadataInfo {
+            category: "📄 PDF Document",
+            explanation: "Software that produced the PDF, such as a printer driver or export library",
+        },
+    ),
+    (
+        "PDF:CreationDate",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "When the document was created",
+        },
+    ),
+    (
+        "PDF:ModDate",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "When the document was last modified",
+        },
+    ),
+    (
+        "PDF:Trapped",
+        MetadataInfo {
+            category: "📄 PDF Document",
+            explanation: "Whether the document has been prepared for printing with trapping",
+        },
+    ),
+    (
+        "PDF:Version",
+        MetadataInfo {
+            category: "📄 PDF Document",
+            explanation: "PDF specification version the file declares",
+        },
+    ),
+    (
+        "PDF:Encrypted",
+        MetadataInfo {
+            category: "📄 PDF Document",
+            explanation: "Whether the document is encrypted; its information dictionary cannot be read if so",
+        },
+    ),
+    (
+        "PDF:PageCount",
+        MetadataInfo {
+            category: "📄 PDF Document",
+            explanation: "Number of pages in the document",
+        },
+    ),
+    (
+        "PDF:EmbeddedImages",
+        MetadataInfo {
+            category: "📄 PDF Document",
+            explanation: "Number of images embedded in the document, whose own metadata may have been kept",
+        },
+    ),
+    // SVG document
+    (
+        "SVG:Width",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Width of the drawing as declared on the root element",
+        },
+    ),
+    (
+        "SVG:Height",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Height of the drawing as declared on the root element",
+        },
+    ),
+    (
+        "SVG:ViewBox",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Coordinate system of the drawing (min-x, min-y, width, height)",
+        },
+    ),
+    (
+        "SVG:Title",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Document title from the <title> element or RDF metadata",
+        },
+    ),
+    (
+        "SVG:Description",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Document description from the <desc> element",
+        },
+    ),
+    (
+        "SVG:Creator",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Author named in the RDF metadata",
+        },
+    ),
+    (
+        "SVG:License",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "License the drawing is published under",
+        },
+    ),
+    (
+        "SVG:Generator",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Application named in the Generator comment, such as Illustrator or Sketch",
+        },
+    ),
+    (
+        "SVG:Editors",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Editors whose private namespaces are declared in the file",
+        },
+    ),
+    (
+        "SVG:DocumentName",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "File name the drawing was saved under in Inkscape",
+        },
+    ),
+    (
+        "SVG:DocumentBase",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Folder the drawing was saved in (older Inkscape versions), usually an absolute path",
+        },
+    ),
+    (
+        "SVG:InkscapeVersion",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Inkscape version that last saved the file",
+        },
+    ),
+    (
+        "SVG:ExportFilename",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "File the drawing was last exported to from Inkscape, often an absolute path",
+        },
+    ),
+    (
+        "SVG:ExportDPI",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Resolution used for the last bitmap export",
+        },
+    ),
+    (
+        "SVG:CurrentLayer",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Layer selected when the file was last saved",
+        },
+    ),
+    (
+        "SVG:AbsolutePaths",
+        MetadataInfo {
+            category: "🖼️ SVG Document",
+            explanation: "Absolute local file paths found in attributes; they reveal user names and folder structure",
+        },
+    ),
+    // Multi-Picture Format index
+    (
+        "MPF:MPFVersion",
+        MetadataInfo {
+            category: "🖼️ Multi-Picture",
+            explanation: "Version of the CIPA Multi-Picture Format index",
+        },
+    ),
+    (
+        "MPF:NumberOfImages",
+        MetadataInfo {
+            category: "🖼️ Multi-Picture",
+            explanation: "Number of images stored in the file, including the primary one",
+        },
+    ),
+    (
+        "MPF:ImageTypes",
+        MetadataInfo {
+            category: "🖼️ Multi-Picture",
+            explanation: "Type of each stored image, such as a stereo view, large preview or gain map",
+        },
+    ),
+    (
+        "MPF:ImageUIDList",
+        MetadataInfo {
+            category: "🖼️ Multi-Picture",
+            explanation: "Unique ID the camera assigned to each stored image, which can link copies of the same shot",
+        },
+    ),
+    (
+        "MPF:TotalFrames",
+        MetadataInfo {
+            category: "🖼️ Multi-Picture",
+            explanation: "Number of frames the camera captured for a panorama or multi-angle sequence",
+        },
+    ),
+    // Animated GIF, APNG and WebP
+    (
+        "Animation:FrameCount",
+        MetadataInfo {
+            category: "🖼️ Animation",
+            explanation: "Number of frames in the animation",
+        },
+    ),
+    (
+        "Animation:Duration",
+        MetadataInfo {
+            category: "🖼️ Animation",
+            explanation: "Time one pass through all frames takes to play",
+        },
+    ),
+    (
+        "Animation:LoopCount",
+        MetadataInfo {
+            category: "🖼️ Animation",
+            explanation: "How many times the animation plays; Infinite means it repeats forever",
+        },
+    ),
+    (
+        "Animation:FrameDelays",
+        MetadataInfo {
+            category: "🖼️ Animation",
+            explanation: "How long each frame is shown before the next one",
+        },
+    ),
+    (
+        "Animation:DeclaredFrameCount",
+        MetadataInfo {
+            category: "🖼️ Animation",
+            explanation: "Frame count the APNG header announces, which differs from the frames actually present",
+        },
+    ),
+    (
+        "GIF:Comment",
+        MetadataInfo {
+            category: "🖼️ GIF",
+            explanation: "Free-text comment embedded in the GIF, often naming the software or author that made it",
+        },
+    ),
+    // Camera RAW structure
+    (
+        "RAW:Format",
+        MetadataInfo {
+            category: "🖼️ RAW File",
+            explanation: "Camera RAW format, recognised from the TIFF header and first directory",
+        },
+    ),
+    (
+        "RAW:ImageCount",
+        MetadataInfo {
+            category: "🖼️ RAW File",
+            explanation: "Number of images (sensor data, previews, thumbnails) stored in the file's directories",
+        },
+    ),
+    (
+        "RAW:RawImage",
+        MetadataInfo {
+            category: "🖼️ RAW File",
+            explanation: "Size, bit depth and encoding of the full-resolution sensor data",
+        },
+    ),
+    (
+        "RAW:Preview",
+        MetadataInfo {
+            category: "🖼️ RAW File",
+            explanation: "Largest JPEG preview rendered by the camera, shown in place of the sensor data",
+        },
+    ),
+    (
+        "DNG:DNGVersion",
+        MetadataInfo {
+            category: "🖼️ RAW File",
+            explanation: "Version of the DNG specification the file was written against",
+        },
+    ),
+    (
+        "DNG:DNGBackwardVersion",
+        MetadataInfo {
+            category: "🖼️ RAW File",
+            explanation: "Oldest DNG reader version able to read the file",
+        },
+    ),
+    (
+        "DNG:UniqueCameraModel",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Non-localized camera model name used to look up color profiles",
+        },
+    ),
+    (
+        "DNG:LocalizedCameraModel",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Camera model name in the user's language",
+        },
+    ),
+    (
+        "DNG:BaselineExposure",
+        MetadataInfo {
+            category: "⚙️ Settings",
+            explanation: "Exposure compensation in EV the converter applies to reach normal brightness",
+        },
+    ),
+    (
+        "DNG:CameraSerialNumber",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Serial number of the camera body - uniquely identifies the device",
+        },
+    ),
+    (
+        "DNG:OriginalRawFileName",
+        MetadataInfo {
+            category: "🖼️ RAW File",
+            explanation: "File name of the camera's original RAW file this DNG was converted from",
+        },
+    ),
+    (
+        "DNG:ProfileName",
+        MetadataInfo {
+            category: "🖼️ RAW File",
+            explanation: "Name of the embedded camera color profile",
+        },
+    ), // Video (MP4, QuickTime, 3GP)
+    (
+        "QuickTime:MajorBrand",
+        MetadataInfo {
+            category: "🖼️ Video",
+            explanation: "File type brand from the ftyp box (isom, mp42, qt, 3gp5, ...)",
+        },
+    ),
+    (
+        "QuickTime:Duration",
+        MetadataInfo {
+            category: "🖼️ Video",
+            explanation: "Length of the recording",
+        },
+    ),
+    (
+        "QuickTime:TrackCount",
+        MetadataInfo {
+            category: "🖼️ Video",
+            explanation: "Number of video, audio and metadata streams in the file",
+        },
+    ),
+    (
+        "QuickTime:VideoCodec",
+        MetadataInfo {
+            category: "🖼️ Video",
+            explanation: "Compression format of the video stream",
+        },
+    ),
+    (
+        "QuickTime:CompressorName",
+        MetadataInfo {
+            category: "🖼️ Video",
+            explanation: "Encoder name stored with the video stream",
+        },
+    ),
+    (
+        "QuickTime:AudioCodec",
+        MetadataInfo {
+            category: "🖼️ Video",
+            explanation: "Compression format of the audio stream",
+        },
+    ),
+    (
+        "QuickTime:ImageWidth",
+        MetadataInfo {
+            category: "🖼️ Video",
+            explanation: "Coded width of the video frames in pixels",
+        },
+    ),
+    (
+        "QuickTime:ImageHeight",
+        MetadataInfo {
+            category: "🖼️ Video",
+            explanation: "Coded height of the video frames in pixels",
+        },
+    ),
+    (
+        "QuickTime:Rotation",
+        MetadataInfo {
+            category: "🖼️ Video",
+            explanation: "Clockwise rotation players apply when showing the video, set from how the phone was held",
+        },
+    ),
+    (
+        "QuickTime:FrameRate",
+        MetadataInfo {
+            category: "🖼️ Video",
+            explanation: "Average number of frames per second",
+        },
+    ),
+    (
+        "QuickTime:Encoder",
+        MetadataInfo {
+            category: "🖼️ Video",
+            explanation: "Software that encoded the file, e.g. a Lavf (FFmpeg) version",
+        },
+    ),
+    (
+        "QuickTime:Make",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Manufacturer of the recording device",
+        },
+    ),
+    (
+        "QuickTime:Model",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Model of the recording device",
+        },
+    ),
+    (
+        "QuickTime:Software",
+        MetadataInfo {
+            category: "📷 Camera",
+            explanation: "Operating system or app version of the recording device",
+        },
+    ),
+    (
+        "QuickTime:CreationDate",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "When recording started, in local time with UTC offset as written by the phone",
+        },
+    ),
+    (
+        "QuickTime:CreateDate",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "When the movie file was created (mvhd, UTC)",
+        },
+    ),
+    (
+        "QuickTime:ModifyDate",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "When the movie file was last modified (mvhd, UTC)",
+        },
+    ),
+    (
+        "QuickTime:TrackCreateDate",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "When the video track was created (tkhd, UTC)",
+        },
+    ),
+    (
+        "QuickTime:TrackModifyDate",
+        MetadataInfo {
+            category: "🕒 Date & Time",
+            explanation: "When the video track was last modified (tkhd, UTC)",
+        },
+    ),
+    (
+        "QuickTime:GPSCoordinates",
+        MetadataInfo {
+            category: "📍 Location",
+            explanation: "Recording location as an ISO 6709 string: latitude, longitude and optional altitude",
+        },
+    ),
+    (
+        "QuickTime:LocationAccuracyHorizontal",
+        MetadataInfo {
+            category: "📍 Location",
+            explanation: "Estimated horizontal accuracy of the recorded location in metres",
+        },
+    ),
+    (
+        "QuickTime:ContentIdentifier",
+        MetadataInfo {
+            category: "🖼️ Video",
+            explanation: "Identifier pairing a Live Photo video with its still image",
+        },
     ),
 ];
 
+/// Categories for keys carrying a `Group:` prefix, such as fields read from
+/// the thumbnail IFD (`Thumbnail:DateTime`).
+///
+/// Groups of the form `Family-member` (e.g. `XMP-dc`) fall back to the
+/// family's category, and numbered groups (e.g. `SubIFD1`) to that of the
+/// unnumbered name.
+pub static GROUP_CATEGORIES: &[(&str, &str)] = &[
+    ("Thumbnail", "🧩 Thumbnail (IFD1)"),
+    ("RAW", "🖼️ RAW File"),
+    ("DNG", "🖼️ RAW File"),
+    ("SubIFD", "🖼️ RAW File"),
+    ("IFD", "🖼️ RAW File"),
+    ("XMP", "📝 XMP"),
+    ("PNG", "🖼️ PNG Text"),
+    ("PDF", "📄 PDF Document"),
+    ("SVG", "🖼️ SVG Document"),
+    ("QuickTime", "🖼️ Video"),
+    ("MPF", "🖼️ Multi-Picture"),
+    ("Animation", "🖼️ Animation"),
+    ("GIF", "🖼️ GIF"),
+    ("C2PA", "📝 Content Credentials"),
+];
+
+/// Split a `Group:Name` key into its group and field name.
+pub fn split_group(key: &str) -> (Option<&str>, &str) {
+    match key.split_once(':') {
+        Some((group, name))
+            if !group.is_empty()
+                && group
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                && !name.is_empty()
+                && !name.starts_with(' ') =>
+        {
+            (Some(group), name)
+        }
+        _ => (None, key),
+    }
+}
+
 /// Build lookup map from static data
 fn build_lookup_map() -> HashMap<&'static str, &'static MetadataInfo> {
     METADATA_DB.iter().map(|(k, v)| (*k, v)).collect()
@@ -691,7 +2028,9 @@ pub fn get_metadata_explanation(key: &str) -> Option<&'static str> {
     static LOOKUP: std::sync::OnceLock<HashMap<&'static str, &'static MetadataInfo>> =
         std::sync::OnceLock::new();
     let map = LOOKUP.get_or_init(build_lookup_map);
-    map.get(key).map(|info| info.explanation)
+    map.get(key)
+        .or_else(|| map.get(split_group(key).1))
+        .map(|info| info.explanation)
 }
 
 /// Return the category for a given metadata key.
@@ -699,5 +2038,19 @@ pub fn get_metadata_category(key: &str) -> &'static str {
     static LOOKUP: std::sync::OnceLock<HashMap<&'static str, &'static MetadataInfo>> =
         std::sync::OnceLock::new();
     let map = LOOKUP.get_or_init(build_lookup_map);
-    map.get(key).map(|info| info.category).unwrap_or("📊 Other")
+    if let Some(info) = map.get(key) {
+        return info.category;
+    }
+    match split_group(key) {
+        (Some(group), _) => GROUP_CATEGORIES
+            .iter()
+            .find(|(g, _)| {
+                *g == group
+                    || group.split('-').next() == Some(*g)
+                    || group.trim_end_matches(|c: char| c.is_ascii_digit()) == *g
+            })
+            .map(|(_, category)| *category)
+            .unwrap_or("📊 Other"),
+        (None, _) => "📊 Other",
+    }
 }
@@ -0,0 +1,237 @@
+//! Motion Photos and Live Photos.
+//!
+//! Google and Samsung motion photos are stills with a short MP4 clip stored
+//! in the same file. The clip is appended after the JPEG end-of-image marker,
+//! or sits in a top-level `mpvd` box in Samsung HEIC files. Google describes
+//! the clip in XMP. Older files use `GCamera:MicroVideoOffset`, the clip's
+//! distance from the end of the file. Newer files use `Container:Directory`,
+//! which lists every appended item with its length. Samsung writes a
+//! `MotionPhoto_Data` marker before the clip and its own SEF trailer after
+//! it. Apple Live Photos keep the video in a separate MOV instead. The MOV
+//! is paired with the still through a content identifier that both files
+//! share.
+
+use crate::types::{EmbeddedVideo, ImageData};
+use crate::{container, jpeg, video, xmp};
+use std::collections::HashMap;
+
+/// Marker Samsung writes immediately before the clip.
+pub const SAMSUNG_MARKER: &[u8] = b"MotionPhoto_Data";
+
+/// Keys holding the identifier that pairs a Live Photo still with its video.
+pub const CONTENT_IDENTIFIER_KEYS: &[&str] =
+    &["Apple:ContentIdentifier", "QuickTime:ContentIdentifier"];
+
+/// Where a clip was found and which bytes to cut to remove it.
+struct Located {
+    kind: &'static str,
+    start: usize,
+    end: usize,
+    /// Range removed when cleaning, covering vendor markers and trailers
+    /// that only describe the clip. `None` if the clip cannot be cut out
+    /// without breaking the file.
+    removal: Option<(usize, usize)>,
+    presentation_timestamp_us: Option<i64>,
+}
+
+/// Flattened XMP properties keyed like `exif_data` (`XMP-GCamera:MicroVideo`).
+fn xmp_properties(bytes: &[u8]) -> HashMap<String, String> {
+    xmp::find_xmp_packets(bytes)
+        .iter()
+        .filter_map(|packet| xmp::parse_xmp(packet).ok())
+        .flatten()
+        .map(|property| (property.key(), property.value))
+        .collect()
+}
+
+/// Header size of the box at `offset`: 16 with a 64-bit size, else 8.
+fn box_header(data: &[u8], offset: usize) -> usize {
+    if data.get(offset..offset + 4) == Some(&[0, 0, 0, 1]) {
+        16
+    } else {
+        8
+    }
+}
+
+/// End of the last ISO base media box in a run starting at `data[0]`.
+fn iso_extent(data: &[u8]) -> usize {
+    container::iso_boxes(data)
+        .last()
+        .map(|b| b.offset + box_header(data, b.offset) + b.data.len())
+        .unwrap_or(0)
+}
+
+fn find_last(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+/// Google motion photo from the `Container:Directory` item list, or else
+/// from the older `MicroVideoOffset`.
+fn google(bytes: &[u8], properties: &HashMap<String, String>) -> Option<Located> {
+    let number = |key: &str| -> Option<i64> { properties.get(key)?.trim().parse().ok() };
+    let timestamp = number("XMP-GCamera:MotionPhotoPresentationTimestampUs")
+        .or_else(|| number("XMP-GCamera:MicroVideoPresentationTimestampUs"))
+        .filter(|us| *us >= 0);
+
+    // Items after the primary image are stored back to back at the end
+    let item = |i: usize, field: &str| {
+        properties
+            .get(&format!("XMP-Container:Directory[{}]/Item/{}", i, field))
+            .map(String::as_str)
+    };
+    let items: Vec<(Option<&str>, usize)> = (1..)
+        .take_while(|i| item(*i, "Mime").is_some() || item(*i, "Semantic").is_some())
+        .map(|i| {
+            let length = item(i, "Length").and_then(|l| l.trim().parse().ok());
+            (item(i, "Semantic"), length.unwrap_or(0))
+        })
+        .collect();
+    if let Some(index) = items
+        .iter()
+        .position(|(semantic, _)| *semantic == Some("MotionPhoto"))
+    {
+        let tail = items[index..]
+            .iter()
+            .try_fold(0usize, |sum, (_, length)| sum.checked_add(*length))?;
+        let start = bytes.len().checked_sub(tail)?;
+        let end = start
+            .checked_add(items[index].1)
+            .filter(|end| *end <= bytes.len())?;
+        return Some(Located {
+            kind: "Google Motion Photo",
+            start,
+            end,
+            removal: Some((start, end)),
+            presentation_timestamp_us: timestamp,
+        });
+    }
+
+    let offset = usize::try_from(number("XMP-GCamera:MicroVideoOffset")?).ok()?;
+    let start = bytes.len().checked_sub(offset)?;
+    Some(Located {
+        kind: "Google Motion Photo",
+        start,
+        end: bytes.len(),
+        removal: Some((start, bytes.len())),
+        presentation_timestamp_us: timestamp,
+    })
+}
+
+/// Samsung motion photo: the clip after the `MotionPhoto_Data` marker in a
+/// JPEG, or the `mpvd` box of a HEIC.
+fn samsung(bytes: &[u8]) -> Option<Located> {
+    if container::is_isobmff(bytes) {
+        let boxes = container::iso_boxes(bytes);
+        let (index, mpvd) = boxes.iter().enumerate().find(|(_, b)| &b.id == b"mpvd")?;
+        let start = mpvd.offset + box_header(bytes, mpvd.offset);
+        let end = start + mpvd.data.len();
+        return Some(Located {
+            kind: "Samsung Motion Photo",
+            start,
+            end,
+            // Boxes after `mpvd` may be referenced by absolute offsets
+            removal: (index + 1 == boxes.len()).then_some((mpvd.offset, bytes.len())),
+            presentation_timestamp_us: None,
+        });
+    }
+
+    if !container::is_jpeg(bytes) {
+        return None;
+    }
+    let marker = find_last(bytes, SAMSUNG_MARKER)?;
+    let start = marker + SAMSUNG_MARKER.len();
+    Some(Located {
+        kind: "Samsung Motion Photo",
+        start,
+        end: start + iso_extent(&bytes[start..]),
+        // The SEF trailer after the clip only describes Samsung extras
+        removal: Some((marker, bytes.len())),
+        presentation_timestamp_us: None,
+    })
+}
+
+/// An MP4 appended after the end of a JPEG without any vendor description.
+fn appended(bytes: &[u8]) -> Option<Located> {
+    let structure = jpeg::analyze_jpeg(bytes)?;
+    let image_end = bytes.len() - structure.trailing_bytes;
+    let trailer = &bytes[image_end..];
+    let start = image_end
+        + (4..trailer.len().saturating_sub(4))
+            .find(|&i| &trailer[i..i + 4] == b"ftyp" && video::is_video(&trailer[i - 4..]))?
+        - 4;
+    let end = start + iso_extent(&bytes[start..]);
+    Some(Located {
+        kind: "Appended video",
+        start,
+        end,
+        removal: Some((start, end)),
+        presentation_timestamp_us: None,
+    })
+}
+
+/// Locate the clip, checking that the described range really holds a video.
+fn locate(bytes: &[u8]) -> Option<Located> {
+    let valid = |found: &Located| {
+        found.start < found.end
+            && found.end <= bytes.len()
+            && video::is_video(&bytes[found.start..found.end])
+    };
+    google(bytes, &xmp_properties(bytes))
+        .filter(valid)
+        .or_else(|| samsung(bytes).filter(valid))
+        .or_else(|| appended(bytes).filter(valid))
+}
+
+/// Find and describe a motion photo clip stored in the file.
+pub fn find_embedded_video(bytes: &[u8]) -> Option<EmbeddedVideo> {
+    let found = locate(bytes)?;
+    let clip = &bytes[found.start..found.end];
+    let file = video::parse_video(clip).ok();
+    Some(EmbeddedVideo {
+        kind: found.kind.to_string(),
+        offset: found.start as u64,
+        size: clip.len() as u64,
+        mime_type: video::mime_type(clip).unwrap_or("video/mp4").to_string(),
+        duration: file.as_ref().and_then(|f| f.duration_seconds()),
+        has_audio: file.as_ref().is_some_and(|f| f.audio_track().is_some()),
+        has_location: file.as_ref().and_then(video::gps_info).is_some(),
+        presentation_timestamp_us: found.presentation_timestamp_us,
+    })
+}
+
+/// The bytes of a clip previously found by [`find_embedded_video`].
+pub fn embedded_video_bytes<'a>(bytes: &'a [u8], video: &EmbeddedVideo) -> Option<&'a [u8]> {
+    let start = usize::try_from(video.offset).ok()?;
+    bytes.get(start..start.checked_add(usize::try_from(video.size).ok()?)?)
+}
+
+/// A copy of the file with its motion photo clip cut out, or `None` if it
+/// has none or the clip cannot be removed safely.
+pub fn strip_embedded_video(bytes: &[u8]) -> Option<Vec<u8>> {
+    let (start, end) = locate(bytes)?.removal?;
+    let mut stripped = bytes[..start].to_vec();
+    stripped.extend_from_slice(&bytes[end..]);
+    Some(stripped)
+}
+
+/// The identifier pairing a Live Photo still with its video.
+pub fn content_identifier(data: &ImageData) -> Option<&str> {
+    CONTENT_IDENTIFIER_KEYS
+        .iter()
+        .find_map(|key| data.exif_data.get(*key))
+        .map(|value| value.as_text().unwrap_or(&value.display))
+        .filter(|id| !id.is_empty())
+}
+
+/// The other half of a Live Photo among `files`: the video for a still, or
+/// the still for a video.
+pub fn live_photo_partner<'a, T: AsRef<ImageData>>(
+    data: &ImageData,
+    files: &'a [T],
+) -> Option<&'a ImageData> {
+    let id = content_identifier(data)?;
+    let is_video = data.mime_type.starts_with("video/");
+    files.iter().map(AsRef::as_ref).find(|other| {
+        other.mime_type.starts_with("video/") != is_video && content_identifier(other) == Some(id)
+    })
+}
@@ -0,0 +1,345 @@
+//! Multi-Picture Format (CIPA DC-007) files.
+//!
+//! MPO files from stereo cameras, and many phone JPEGs, store further images
+//! after the primary one: the second stereo view, large preview images,
+//! depth maps or HDR gain maps. An APP2 segment starting with `MPF\0` in the
+//! primary image holds a TIFF structure whose MP Index IFD lists every image
+//! with its type, size and offset. Offsets are counted from the TIFF header
+//! inside that segment; the primary image has offset 0. Each extra image is
+//! a complete JPEG with its own EXIF.
+
+use crate::container::{self, JpegSegment};
+use crate::diagnostics::Diagnostic;
+use crate::limits::{self, Budget};
+use crate::types::{MetadataSource, MetadataValue, MpfImage, RawValue};
+use crate::{exif_core, jpeg};
+use base64::Engine as _;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Identifier at the start of the APP2 payload.
+pub const MPF_SIGNATURE: &[u8] = b"MPF\0";
+
+const MP_FORMAT_VERSION: u16 = 0xB000;
+const NUMBER_OF_IMAGES: u16 = 0xB001;
+const MP_ENTRY: u16 = 0xB002;
+const IMAGE_UID_LIST: u16 = 0xB003;
+const TOTAL_FRAMES: u16 = 0xB004;
+
+/// Size of one MP entry and of one image UID.
+const ENTRY_SIZE: usize = 16;
+const UID_SIZE: usize = 33;
+
+/// One entry of the MP Index IFD.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MpEntry {
+    /// Flags, data format and type code.
+    pub attributes: u32,
+    pub size: u32,
+    /// Offset from the MP header; 0 for the primary image.
+    pub offset: u32,
+    /// 1-based entry numbers of dependent images, 0 if none.
+    pub dependents: [u16; 2],
+}
+
+impl MpEntry {
+    /// MP type code, e.g. `0x020002` for a disparity (stereo) image.
+    pub fn type_code(&self) -> u32 {
+        self.attributes & 0x00FF_FFFF
+    }
+}
+
+/// The MP Index IFD of a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MpIndex {
+    /// MP format version, e.g. `"0100"`.
+    pub version: Option<String>,
+    /// Offset in the file of the MP header (the TIFF byte order mark).
+    pub header_offset: usize,
+    pub entries: Vec<MpEntry>,
+    /// Unique ID of each image, as 32 hex digits.
+    pub image_uids: Vec<String>,
+    pub total_frames: Option<u32>,
+}
+
+impl MpIndex {
+    /// Byte range of the image for entry `index`, if it lies within `data`
+    /// and starts with a JPEG SOI marker.
+    pub fn image_range(&self, data: &[u8], index: usize) -> Option<Range<usize>> {
+        let entry = self.entries.get(index)?;
+        let start = match entry.offset {
+            0 => 0,
+            offset => self.header_offset.checked_add(offset as usize)?,
+        };
+        let end = start.checked_add(entry.size as usize)?;
+        container::is_jpeg(data.get(start..end)?).then_some(start..end)
+    }
+
+    /// Byte ranges of the images after the primary one, in file order.
+    /// Entries pointing outside the file or at something other than a JPEG
+    /// are skipped.
+    pub fn extra_image_ranges(&self, data: &[u8]) -> Vec<(usize, Range<usize>)> {
+        let mut ranges: Vec<_> = (1..self.entries.len())
+            .filter_map(|i| Some((i, self.image_range(data, i)?)))
+            .filter(|(_, range)| range.start > 0)
+            .collect();
+        ranges.sort_by_key(|(_, range)| range.start);
+        // Overlapping entries cannot be cut out independently
+        let mut end = 0;
+        ranges.retain(|(_, range)| {
+            let separate = range.start >= end;
+            end = end.max(range.end);
+            separate
+        });
+        ranges
+    }
+}
+
+/// Name of an MP type code.
+pub fn image_type_name(type_code: u32) -> &'static str {
+    match type_code {
+        0x010001 => "Large Thumbnail (VGA)",
+        0x010002 => "Large Thumbnail (Full HD)",
+        0x010003 => "Large Thumbnail (4K)",
+        0x010004 => "Large Thumbnail (8K)",
+        0x010005 => "Large Thumbnail (16K)",
+        0x020001 => "Multi-Frame Panorama",
+        0x020002 => "Multi-Frame Disparity",
+        0x020003 => "Multi-Angle",
+        0x030000 => "Baseline MP Primary Image",
+        0x040000 => "Original Preservation Image",
+        0x050000 => "Gain Map Image",
+        _ => "Undefined",
+    }
+}
+
+/// Parse the MP Index IFD from the APP2 segment of a JPEG file.
+pub fn parse_mp_index(data: &[u8]) -> Option<MpIndex> {
+    mp_index(&find_mpf_segment(data)?)
+}
+
+fn find_mpf_segment(data: &[u8]) -> Option<JpegSegment<'_>> {
+    container::jpeg_segments(data)
+        .into_iter()
+        .find(|s| s.marker == 0xE2 && s.data.starts_with(MPF_SIGNATURE))
+}
+
+fn mp_index(segment: &JpegSegment) -> Option<MpIndex> {
+    let header = &segment.data[MPF_SIGNATURE.len()..];
+    let (order, offset) = container::tiff_header(header)?;
+    let directory = container::tiff_directory(header, order, offset)?;
+
+    let entries: Vec<MpEntry> = directory
+        .get(MP_ENTRY)?
+        .value
+        .chunks_exact(ENTRY_SIZE)
+        .take(limits::MAX_BLOCKS)
+        .filter_map(|entry| {
+            Some(MpEntry {
+                attributes: order.u32(entry, 0)?,
+                size: order.u32(entry, 4)?,
+                offset: order.u32(entry, 8)?,
+                dependents: [order.u16(entry, 12)?, order.u16(entry, 14)?],
+            })
+        })
+        .collect();
+    if entries.is_empty() {
+        return None;
+    }
+
+    let image_uids = directory
+        .get(IMAGE_UID_LIST)
+        .map(|e| {
+            e.value
+                .chunks_exact(UID_SIZE)
+                .map(|uid| String::from_utf8_lossy(&uid[..UID_SIZE - 1]).into_owned())
+                .filter(|uid| uid.bytes().any(|b| b != b'0'))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(MpIndex {
+        version: directory.get(MP_FORMAT_VERSION).map(|e| e.text()),
+        header_offset: segment.offset + 4 + MPF_SIGNATURE.len(),
+        entries,
+        image_uids,
+        total_frames: directory
+            .get(TOTAL_FRAMES)
+            .and_then(|e| e.first_unsigned(order)),
+    })
+}
+
+/// Build an APP2 segment, marker included, holding an MP Index IFD for
+/// `entries`. Entry offsets must already be relative to the MP header.
+pub fn mp_index_segment(entries: &[MpEntry]) -> Vec<u8> {
+    const IFD_OFFSET: u32 = 8;
+    const FIELDS: u16 = 3;
+    let entries_offset = IFD_OFFSET + 2 + u32::from(FIELDS) * 12 + 4;
+
+    let mut header = b"MM\0\x2A".to_vec();
+    header.extend_from_slice(&IFD_OFFSET.to_be_bytes());
+    header.extend_from_slice(&FIELDS.to_be_bytes());
+    let mut field = |tag: u16, field_type: u16, count: u32, value: [u8; 4]| {
+        header.extend_from_slice(&tag.to_be_bytes());
+        header.extend_from_slice(&field_type.to_be_bytes());
+        header.extend_from_slice(&count.to_be_bytes());
+        header.extend_from_slice(&value);
+    };
+    field(MP_FORMAT_VERSION, 7, 4, *b"0100");
+    field(NUMBER_OF_IMAGES, 4, 1, (entries.len() as u32).to_be_bytes());
+    field(
+        MP_ENTRY,
+        7,
+        (entries.len() * ENTRY_SIZE) as u32,
+        entries_offset.to_be_bytes(),
+    );
+    header.extend_from_slice(&0u32.to_be_bytes());
+    for entry in entries {
+        header.extend_from_slice(&entry.attributes.to_be_bytes());
+        header.extend_from_slice(&entry.size.to_be_bytes());
+        header.extend_from_slice(&entry.offset.to_be_bytes());
+        header.extend_from_slice(&entry.dependents[0].to_be_bytes());
+        header.extend_from_slice(&entry.dependents[1].to_be_bytes());
+    }
+
+    let mut segment = vec![0xFF, 0xE2];
+    segment.extend_from_slice(&((2 + MPF_SIGNATURE.len() + header.len()) as u16).to_be_bytes());
+    segment.extend_from_slice(MPF_SIGNATURE);
+    segment.extend(header);
+    segment
+}
+
+/// Describe an image more precisely than its MP type when the type is
+/// undefined but its XMP says what it is.
+fn describe(entry: &MpEntry, exif_data: &HashMap<String, MetadataValue>) -> String {
+    let has_namespace = |prefix: &str| exif_data.keys().any(|k| k.starts_with(prefix));
+    match image_type_name(entry.type_code()) {
+        "Undefined" if has_namespace("XMP-hdrgm:") => "Gain Map Image".to_string(),
+        "Undefined" if has_namespace("XMP-GDepth:") => "Depth Map".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// List every image of a Multi-Picture file, with each extra image's own
+/// metadata and a preview. The primary image is listed first, without
+/// metadata or preview, since it is the file itself.
+///
+/// Extra images are those of [`MpIndex::extra_image_ranges`], so entries
+/// repeating or overlapping another image are listed once. Listing stops
+/// once the images add up to more than [`limits::MAX_EMBEDDED_IMAGES_SIZE`].
+pub fn extract_images(data: &[u8]) -> Vec<MpfImage> {
+    let Some(index) = parse_mp_index(data) else {
+        return Vec::new();
+    };
+    let primary = index
+        .image_range(data, 0)
+        .filter(|range| range.start == 0)
+        .map(|range| (0, range));
+    let mut budget = Budget::new("MPF image size", limits::MAX_EMBEDDED_IMAGES_SIZE);
+    primary
+        .into_iter()
+        .chain(index.extra_image_ranges(data))
+        .map_while(|(i, range)| {
+            let entry = &index.entries[i];
+            let bytes = &data[range.clone()];
+            let frame = jpeg::analyze_jpeg(bytes).and_then(|s| s.frame);
+            let primary = range.start == 0;
+            let exif_data = if primary {
+                HashMap::new()
+            } else {
+                budget.spend_many(bytes.len()).ok()?;
+                exif_core::extract_metadata(bytes).0
+            };
+            Some(MpfImage {
+                index: i as u32 + 1,
+                image_type: describe(entry, &exif_data),
+                offset: range.start as u64,
+                size: bytes.len() as u64,
+                width: frame.as_ref().map(|f| u32::from(f.width)),
+                height: frame.as_ref().map(|f| u32::from(f.height)),
+                exif_data,
+                data_url: if primary {
+                    String::new()
+                } else {
+                    format!(
+                        "data:image/jpeg;base64,{}",
+                        base64::engine::general_purpose::STANDARD.encode(bytes)
+                    )
+                },
+            })
+        })
+        .collect()
+}
+
+/// Convert the MP index into `MPF:` metadata entries.
+pub fn mpf_to_metadata(index: &MpIndex) -> HashMap<String, MetadataValue> {
+    let mut map = HashMap::new();
+    let source = |tag: u16| MetadataSource::Tiff {
+        ifd: "MPIndex".to_string(),
+        tag,
+    };
+    if let Some(version) = &index.version {
+        map.insert(
+            "MPF:MPFVersion".to_string(),
+            MetadataValue::text(version.clone()).with_source(source(MP_FORMAT_VERSION)),
+        );
+    }
+    map.insert(
+        "MPF:NumberOfImages".to_string(),
+        MetadataValue::new(
+            RawValue::Integer(vec![index.entries.len() as i64]),
+            index.entries.len().to_string(),
+        )
+        .with_source(source(NUMBER_OF_IMAGES)),
+    );
+    let types: Vec<&str> = index
+        .entries
+        .iter()
+        .map(|e| image_type_name(e.type_code()))
+        .collect();
+    map.insert(
+        "MPF:ImageTypes".to_string(),
+        MetadataValue::text(types.join(", ")).with_source(source(MP_ENTRY)),
+    );
+    if !index.image_uids.is_empty() {
+        map.insert(
+            "MPF:ImageUIDList".to_string(),
+            MetadataValue::text(index.image_uids.join(", ")).with_source(source(IMAGE_UID_LIST)),
+        );
+    }
+    if let Some(frames) = index.total_frames {
+        map.insert(
+            "MPF:TotalFrames".to_string(),
+            MetadataValue::new(RawValue::Integer(vec![frames as i64]), frames.to_string())
+                .with_source(source(TOTAL_FRAMES)),
+        );
+    }
+    map
+}
+
+/// Extract the MP index as `exif_data` entries.
+pub fn extract_mpf_data(data: &[u8]) -> HashMap<String, MetadataValue> {
+    extract_mpf_data_with_diagnostics(data, &mut Vec::new())
+}
+
+/// Extract the MP index as [`extract_mpf_data`] does, reporting an MPF
+/// segment whose index cannot be read.
+pub fn extract_mpf_data_with_diagnostics(
+    data: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, MetadataValue> {
+    let Some(segment) = find_mpf_segment(data) else {
+        return HashMap::new();
+    };
+    match mp_index(&segment) {
+        Some(index) => mpf_to_metadata(&index),
+        None => {
+            diagnostics.push(Diagnostic::error(
+                "MPF",
+                Some(segment.offset),
+                "APP2 MPF segment holds no readable MP index",
+            ));
+            HashMap::new()
+        }
+    }
+}
@@ -0,0 +1,1248 @@
+//! PDF document metadata.
+//!
+//! Objects are located through the cross-reference data: the `startxref`
+//! offset leads to the newest xref table or xref stream, and its `/Prev`
+//! chain covers earlier incremental updates. Since PDF 1.5 objects may also
+//! be compressed inside object streams. When an object cannot be found where
+//! the cross-reference data says, the file is scanned for `N G obj` headers
+//! instead, which recovers most damaged or hand-edited files.
+//!
+//! The trailer's `/Info` dictionary is mapped to `PDF:<Key>` entries, the
+//! catalog's `/Metadata` stream is handed to the XMP parser, and the EXIF of
+//! embedded JPEG images is listed under `PDF-Image<n>:` keys.
+
+use crate::diagnostics::Diagnostic;
+use crate::types::{MetadataSource, MetadataValue, RawValue};
+use crate::{container, exif_core, limits};
+use std::cell::{Cell, OnceCell};
+use std::collections::{HashMap, HashSet};
+
+/// Nesting depth of arrays and dictionaries accepted before parsing stops.
+const MAX_DEPTH: usize = 64;
+/// Depth of indirect references followed while resolving one object.
+const MAX_RESOLVE_DEPTH: u32 = 16;
+/// Number of cross-reference sections followed through `/Prev`.
+const MAX_XREF_SECTIONS: usize = 64;
+
+/// A PDF object.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PdfObject<'a> {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Real(f64),
+    Name(String),
+    /// A literal or hexadecimal string, still in its raw encoding.
+    String(Vec<u8>),
+    Array(Vec<PdfObject<'a>>),
+    Dictionary(PdfDictionary<'a>),
+    Stream(PdfStream<'a>),
+    /// An indirect reference `N G R`.
+    Reference(u32, u16),
+}
+
+impl<'a> PdfObject<'a> {
+    /// The dictionary of a dictionary or stream object.
+    pub fn as_dict(&self) -> Option<&PdfDictionary<'a>> {
+        match self {
+            PdfObject::Dictionary(dict) => Some(dict),
+            PdfObject::Stream(stream) => Some(&stream.dict),
+            _ => None,
+        }
+    }
+
+    pub fn as_name(&self) -> Option<&str> {
+        match self {
+            PdfObject::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            PdfObject::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[PdfObject<'a>]> {
+        match self {
+            PdfObject::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+/// A dictionary, keeping its entries in file order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PdfDictionary<'a>(pub Vec<(String, PdfObject<'a>)>);
+
+impl<'a> PdfDictionary<'a> {
+    pub fn get(&self, key: &str) -> Option<&PdfObject<'a>> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Returns `true` if `key` holds the name `value`, e.g. `/Type /XRef`.
+    pub fn has_name(&self, key: &str, value: &str) -> bool {
+        self.get(key).and_then(PdfObject::as_name) == Some(value)
+    }
+}
+
+/// A stream object: its dictionary and the still-encoded data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfStream<'a> {
+    pub dict: PdfDictionary<'a>,
+    pub data: &'a [u8],
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, 0 | b'\t' | b'\n' | 0x0C | b'\r' | b' ')
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+fn is_regular(b: u8) -> bool {
+    !is_whitespace(b) && !is_delimiter(b)
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn rfind_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+/// Tokenizer and object parser over a byte slice.
+struct Lexer<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        // Offsets come from the file and may point past its end
+        Self {
+            data,
+            pos: pos.min(data.len()),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while let Some(b) = self.peek()
+                    && b != b'\n'
+                    && b != b'\r'
+                {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// A run of regular characters, such as a number or keyword.
+    fn token(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while self.peek().is_some_and(is_regular) {
+            self.pos += 1;
+        }
+        &self.data[start..self.pos]
+    }
+
+    /// Consume `keyword` if it is the next token.
+    fn keyword(&mut self, keyword: &[u8]) -> bool {
+        let start = self.pos;
+        self.skip_whitespace();
+        if self.token() == keyword {
+            true
+        } else {
+            self.pos = start;
+            false
+        }
+    }
+
+    /// Consume an unsigned integer if it is the next token.
+    fn unsigned(&mut self) -> Option<u64> {
+        let start = self.pos;
+        self.skip_whitespace();
+        let token = self.token();
+        let value = std::str::from_utf8(token)
+            .ok()
+            .filter(|t| !t.is_empty() && t.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|t| t.parse().ok());
+        if value.is_none() {
+            self.pos = start;
+        }
+        value
+    }
+
+    fn object<'o>(&mut self, depth: usize) -> Option<PdfObject<'o>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        match self.peek()? {
+            b'/' => {
+                self.pos += 1;
+                Some(PdfObject::Name(self.name()))
+            }
+            b'(' => self.literal_string().map(PdfObject::String),
+            b'<' if self.data.get(self.pos + 1) == Some(&b'<') => {
+                self.dictionary(depth).map(PdfObject::Dictionary)
+            }
+            b'<' => self.hex_string().map(PdfObject::String),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek()? == b']' {
+                        self.pos += 1;
+                        return Some(PdfObject::Array(items));
+                    }
+                    items.push(self.object(depth + 1)?);
+                }
+            }
+            b'+' | b'-' | b'.' | b'0'..=b'9' => self.number(),
+            _ => match self.token() {
+                b"true" => Some(PdfObject::Boolean(true)),
+                b"false" => Some(PdfObject::Boolean(false)),
+                b"null" => Some(PdfObject::Null),
+                _ => None,
+            },
+        }
+    }
+
+    fn dictionary<'o>(&mut self, depth: usize) -> Option<PdfDictionary<'o>> {
+        self.pos += 2;
+        let mut entries = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.data.get(self.pos..self.pos + 2)? == b">>" {
+                self.pos += 2;
+                return Some(PdfDictionary(entries));
+            }
+            let key = match self.object(depth + 1)? {
+                PdfObject::Name(key) => key,
+                _ => return None,
+            };
+            let value = self.object(depth + 1)?;
+            entries.push((key, value));
+        }
+    }
+
+    /// A number, or an indirect reference when followed by `G R`.
+    fn number<'o>(&mut self) -> Option<PdfObject<'o>> {
+        let token = std::str::from_utf8(self.token()).ok()?;
+        if token.contains('.') {
+            return token.parse().ok().map(PdfObject::Real);
+        }
+        let value: i64 = token.parse().ok()?;
+        let after_number = self.pos;
+        if let Ok(number) = u32::try_from(value)
+            && let Some(generation) = self.unsigned()
+            && self.keyword(b"R")
+        {
+            return Some(PdfObject::Reference(number, generation as u16));
+        }
+        self.pos = after_number;
+        Some(PdfObject::Integer(value))
+    }
+
+    fn name(&mut self) -> String {
+        let raw = self.token();
+        let mut bytes = Vec::with_capacity(raw.len());
+        let mut i = 0;
+        while i < raw.len() {
+            // `#xx` escapes any byte
+            if raw[i] == b'#'
+                && let Some(byte) = raw
+                    .get(i + 1..i + 3)
+                    .and_then(|h| std::str::from_utf8(h).ok())
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+            {
+                bytes.push(byte);
+                i += 3;
+            } else {
+                bytes.push(raw[i]);
+                i += 1;
+            }
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    fn literal_string(&mut self) -> Option<Vec<u8>> {
+        self.pos += 1;
+        let mut out = Vec::new();
+        let mut depth = 1;
+        loop {
+            let b = self.peek()?;
+            self.pos += 1;
+            match b {
+                b'(' => {
+                    depth += 1;
+                    out.push(b);
+                }
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(out);
+                    }
+                    out.push(b);
+                }
+                b'\\' => {
+                    let escaped = self.peek()?;
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'b' => out.push(0x08),
+                        b'f' => out.push(0x0C),
+                        b'0'..=b'7' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        value = value * 8 + (d - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            out.push(value as u8);
+                        }
+                        // A backslash before an end of line continues the string
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        other => out.push(other),
+                    }
+                }
+                // Every end-of-line marker reads as a single line feed
+                b'\r' => {
+                    if self.peek() == Some(b'\n') {
+                        self.pos += 1;
+                    }
+                    out.push(b'\n');
+                }
+                _ => out.push(b),
+            }
+        }
+    }
+
+    fn hex_string(&mut self) -> Option<Vec<u8>> {
+        self.pos += 1;
+        let mut digits = Vec::new();
+        loop {
+            let b = self.peek()?;
+            self.pos += 1;
+            match b {
+                b'>' => break,
+                _ if is_whitespace(b) => {}
+                _ => digits.push((b as char).to_digit(16)? as u8),
+            }
+        }
+        // An odd final digit is padded with zero
+        if digits.len() % 2 == 1 {
+            digits.push(0);
+        }
+        Some(digits.chunks(2).map(|p| p[0] << 4 | p[1]).collect())
+    }
+}
+
+/// Where the cross-reference data says an object is stored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum XrefEntry {
+    /// Byte offset of `N G obj` in the file.
+    Offset(usize),
+    /// Index within the object stream `stream`.
+    Compressed { stream: u32, index: u32 },
+}
+
+/// A cross-reference section: the trailer (or xref stream dictionary) and
+/// its entries, with `None` for free objects.
+type XrefSection<'a> = (PdfDictionary<'a>, Vec<(u32, Option<XrefEntry>)>);
+
+/// A parsed PDF file with lazy object lookup.
+pub struct PdfDocument<'a> {
+    data: &'a [u8],
+    /// Version from the file header, e.g. `1.7`.
+    pub header_version: Option<String>,
+    /// Trailer entries merged across incremental updates, newest first.
+    pub trailer: PdfDictionary<'a>,
+    xref: HashMap<u32, XrefEntry>,
+    /// Object offsets found by scanning, built the first time the
+    /// cross-reference data fails.
+    scanned: OnceCell<HashMap<u32, XrefEntry>>,
+    resolve_depth: Cell<u32>,
+}
+
+/// Parse the header, cross-reference data and trailer of a PDF file.
+pub fn parse_pdf(bytes: &[u8]) -> Result<PdfDocument<'_>, String> {
+    let header = find_bytes(&bytes[..bytes.len().min(1024)], container::PDF_SIGNATURE)
+        .ok_or("Missing PDF header")?;
+    let version_start = header + container::PDF_SIGNATURE.len();
+    let version: String = bytes[version_start..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit() || **b == b'.')
+        .map(|&b| b as char)
+        .collect();
+
+    let mut doc = PdfDocument {
+        data: bytes,
+        header_version: (!version.is_empty()).then_some(version),
+        trailer: PdfDictionary::default(),
+        xref: HashMap::new(),
+        scanned: OnceCell::new(),
+        resolve_depth: Cell::new(0),
+    };
+    if let Some(offset) = doc.startxref() {
+        doc.read_xref_chain(offset);
+    }
+    if doc.trailer.get("Root").is_none() {
+        doc.recover_trailer();
+    }
+    if doc.trailer.get("Root").is_none() && doc.trailer.get("Info").is_none() {
+        return Err("No PDF trailer or document catalog found".to_string());
+    }
+    Ok(doc)
+}
+
+impl<'a> PdfDocument<'a> {
+    fn startxref(&self) -> Option<usize> {
+        let at = rfind_bytes(self.data, b"startxref")?;
+        let mut lexer = Lexer::new(self.data, at + b"startxref".len());
+        usize::try_from(lexer.unsigned()?).ok()
+    }
+
+    /// Follow the `/Prev` chain from the newest cross-reference section.
+    /// Entries and trailer keys from newer sections take precedence.
+    fn read_xref_chain(&mut self, offset: usize) {
+        let mut entries: HashMap<u32, Option<XrefEntry>> = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut next = Some(offset);
+        while let Some(offset) = next
+            && visited.len() < MAX_XREF_SECTIONS
+            && visited.insert(offset)
+        {
+            let Some((trailer, section)) = self.read_xref_section(offset) else {
+                break;
+            };
+            // Hybrid files keep the entries of newer objects in a stream
+            if let Some(stream_offset) = trailer.get("XRefStm").and_then(PdfObject::as_integer)
+                && let Some((_, hidden)) = self.read_xref_section(stream_offset as usize)
+            {
+                for (number, entry) in hidden {
+                    entries.entry(number).or_insert(entry);
+                }
+            }
+            for (number, entry) in section {
+                entries.entry(number).or_insert(entry);
+            }
+            for key in ["Root", "Info", "Encrypt", "ID"] {
+                if self.trailer.get(key).is_none()
+                    && let Some(value) = trailer.get(key)
+                {
+                    self.trailer.0.push((key.to_string(), value.clone()));
+                }
+            }
+            next = trailer
+                .get("Prev")
+                .and_then(PdfObject::as_integer)
+                .and_then(|p| usize::try_from(p).ok());
+        }
+        self.xref = entries
+            .into_iter()
+            .filter_map(|(number, entry)| Some((number, entry?)))
+            .collect();
+    }
+
+    fn read_xref_section(&self, offset: usize) -> Option<XrefSection<'a>> {
+        let mut lexer = Lexer::new(self.data, offset);
+        if !lexer.keyword(b"xref") {
+            return self.read_xref_stream(offset);
+        }
+        let mut entries = Vec::new();
+        loop {
+            if lexer.keyword(b"trailer") {
+                let trailer = lexer.object(0)?.as_dict()?.clone();
+                return Some((trailer, entries));
+            }
+            let start = u32::try_from(lexer.unsigned()?).ok()?;
+            let count = lexer.unsigned()?;
+            // Each entry takes 20 bytes, which bounds a corrupt count
+            if count as usize > self.data.len() / 20 {
+                return None;
+            }
+            for i in 0..count as u32 {
+                let entry_offset = lexer.unsigned()?;
+                lexer.unsigned()?;
+                lexer.skip_whitespace();
+                let entry = match lexer.token() {
+                    b"n" => Some(XrefEntry::Offset(entry_offset as usize)),
+                    b"f" => None,
+                    _ => return None,
+                };
+                entries.push((start.checked_add(i)?, entry));
+            }
+        }
+    }
+
+    fn read_xref_stream(&self, offset: usize) -> Option<XrefSection<'a>> {
+        let (_, object) = self.object_at(offset)?;
+        let PdfObject::Stream(stream) = object else {
+            return None;
+        };
+        if !stream.dict.has_name("Type", "XRef") {
+            return None;
+        }
+        let data = decode_stream(&stream)?;
+        let widths: Vec<usize> = stream
+            .dict
+            .get("W")?
+            .as_array()?
+            .iter()
+            .map(|w| w.as_integer().and_then(|w| usize::try_from(w).ok()))
+            .collect::<Option<_>>()?;
+        if widths.len() != 3 || widths.iter().any(|&w| w > 8) {
+            return None;
+        }
+        let row = widths.iter().sum::<usize>();
+        if row == 0 {
+            return None;
+        }
+        let index: Vec<i64> = match stream.dict.get("Index").and_then(PdfObject::as_array) {
+            Some(items) => items.iter().filter_map(PdfObject::as_integer).collect(),
+            None => vec![0, stream.dict.get("Size")?.as_integer()?],
+        };
+
+        let mut entries = Vec::new();
+        let mut rows = data.chunks_exact(row);
+        for pair in index.chunks_exact(2) {
+            let start = u32::try_from(pair[0]).ok()?;
+            for i in 0..u32::try_from(pair[1]).ok()? {
+                let Some(bytes) = rows.next() else {
+                    return Some((stream.dict.clone(), entries));
+                };
+                let mut fields = [0u64; 3];
+                let mut at = 0;
+                for (field, &width) in fields.iter_mut().zip(&widths) {
+                    *field = bytes[at..at + width]
+                        .iter()
+                        .fold(0, |acc, &b| acc << 8 | b as u64);
+                    at += width;
+                }
+                // A missing type field defaults to an in-use object
+                let kind = if widths[0] == 0 { 1 } else { fields[0] };
+                let entry = match kind {
+                    1 => Some(XrefEntry::Offset(fields[1] as usize)),
+                    2 => Some(XrefEntry::Compressed {
+                        stream: u32::try_from(fields[1]).ok()?,
+                        index: u32::try_from(fields[2]).ok()?,
+                    }),
+                    _ => None,
+                };
+                entries.push((start.checked_add(i)?, entry));
+            }
+        }
+        Some((stream.dict.clone(), entries))
+    }
+
+    /// Rebuild the trailer of a file whose cross-reference data is missing,
+    /// from `trailer` dictionaries or, failing that, the catalog object.
+    fn recover_trailer(&mut self) {
+        let mut search = self.data;
+        while let Some(at) = rfind_bytes(search, b"trailer") {
+            let mut lexer = Lexer::new(self.data, at + b"trailer".len());
+            if let Some(PdfObject::Dictionary(trailer)) = lexer.object(0) {
+                for key in ["Root", "Info", "Encrypt", "ID"] {
+                    if self.trailer.get(key).is_none()
+                        && let Some(value) = trailer.get(key)
+                    {
+                        self.trailer.0.push((key.to_string(), value.clone()));
+                    }
+                }
+            }
+            search = &search[..at];
+        }
+        if self.trailer.get("Root").is_some() {
+            return;
+        }
+        let catalog = self.object_numbers().into_iter().find(|&number| {
+            self.get(number)
+                .as_ref()
+                .and_then(PdfObject::as_dict)
+                .is_some_and(|dict| dict.has_name("Type", "Catalog"))
+        });
+        if let Some(number) = catalog {
+            self.trailer
+                .0
+                .push(("Root".to_string(), PdfObject::Reference(number, 0)));
+        }
+    }
+
+    /// Parse the indirect object `N G obj` at `offset`, including stream data.
+    fn object_at(&self, offset: usize) -> Option<(u32, PdfObject<'a>)> {
+        let mut lexer = Lexer::new(self.data, offset);
+        let number = u32::try_from(lexer.unsigned()?).ok()?;
+        lexer.unsigned()?;
+        if !lexer.keyword(b"obj") {
+            return None;
+        }
+        let object = lexer.object(0)?;
+        let PdfObject::Dictionary(dict) = object else {
+            return Some((number, object));
+        };
+        if !lexer.keyword(b"stream") {
+            return Some((number, PdfObject::Dictionary(dict)));
+        }
+        // The keyword is followed by CRLF or LF (a lone CR is tolerated)
+        match self.data.get(lexer.pos..lexer.pos + 2) {
+            Some(b"\r\n") => lexer.pos += 2,
+            Some([b'\n' | b'\r', ..]) => lexer.pos += 1,
+            _ => {}
+        }
+        let start = lexer.pos;
+        let data = self.stream_data(&dict, start)?;
+        Some((number, PdfObject::Stream(PdfStream { dict, data })))
+    }
+
+    /// Stream data starting at `start`, trusting `/Length` only if
+    /// `endstream` follows it.
+    fn stream_data(&self, dict: &PdfDictionary<'a>, start: usize) -> Option<&'a [u8]> {
+        let declared = dict
+            .get("Length")
+            .and_then(|length| self.resolve(length))
+            .and_then(|length| length.as_integer())
+            .and_then(|length| usize::try_from(length).ok())
+            .and_then(|length| start.checked_add(length))
+            .filter(|&end| end <= self.data.len())
+            .filter(|&end| Lexer::new(self.data, end).keyword(b"endstream"));
+        let end = match declared {
+            Some(end) => end,
+            None => {
+                let mut end = start + find_bytes(self.data.get(start..)?, b"endstream")?;
+                while end > start && matches!(self.data[end - 1], b'\r' | b'\n') {
+                    end -= 1;
+                }
+                end
+            }
+        };
+        self.data.get(start..end)
+    }
+
+    /// Object offsets found by scanning the file for `N G obj`, plus the
+    /// objects listed in the headers of any object streams found that way.
+    fn scanned_objects(&self) -> &HashMap<u32, XrefEntry> {
+        self.scanned.get_or_init(|| {
+            let mut map = HashMap::new();
+            let mut from = 0;
+            while let Some(found) = find_bytes(&self.data[from..], b"obj") {
+                let at = from + found;
+                from = at + 3;
+                if self.data.get(at + 3).is_some_and(|&b| is_regular(b)) {
+                    continue;
+                }
+                // Walk back over "N G " to where the object header starts
+                let mut start = at;
+                for _ in 0..2 {
+                    let digits_end = start;
+                    while start > 0 && is_whitespace(self.data[start - 1]) {
+                        start -= 1;
+                    }
+                    if start == digits_end {
+                        break;
+                    }
+                    let before_digits = start;
+                    while start > 0 && self.data[start - 1].is_ascii_digit() {
+                        start -= 1;
+                    }
+                    if start == before_digits {
+                        break;
+                    }
+                }
+                let mut lexer = Lexer::new(self.data, start);
+                if let Some(number) = lexer.unsigned().and_then(|n| u32::try_from(n).ok())
+                    && lexer.unsigned().is_some()
+                    && lexer.keyword(b"obj")
+                    && lexer.pos == at + 3
+                {
+                    // Later definitions belong to incremental updates
+                    map.insert(number, XrefEntry::Offset(start));
+                }
+            }
+
+            let streams: Vec<(u32, usize)> = map
+                .iter()
+                .filter_map(|(&number, entry)| match entry {
+                    XrefEntry::Offset(offset) => Some((number, *offset)),
+                    XrefEntry::Compressed { .. } => None,
+                })
+                .collect();
+            for (stream_number, offset) in streams {
+                if let Some((_, PdfObject::Stream(stream))) = self.object_at(offset)
+                    && stream.dict.has_name("Type", "ObjStm")
+                    && let Some(numbers) = object_stream_contents(&stream)
+                {
+                    for (index, (number, _)) in numbers.into_iter().enumerate() {
+                        map.entry(number).or_insert(XrefEntry::Compressed {
+                            stream: stream_number,
+                            index: index as u32,
+                        });
+                    }
+                }
+            }
+            map
+        })
+    }
+
+    /// Look up an object through the cross-reference data, falling back to
+    /// the scanned object table.
+    pub fn get(&self, number: u32) -> Option<PdfObject<'a>> {
+        let depth = self.resolve_depth.get();
+        if depth >= MAX_RESOLVE_DEPTH {
+            return None;
+        }
+        self.resolve_depth.set(depth + 1);
+        let object = self
+            .xref
+            .get(&number)
+            .and_then(|&entry| self.lookup(number, entry))
+            .or_else(|| {
+                let entry = *self.scanned_objects().get(&number)?;
+                self.lookup(number, entry)
+            });
+        self.resolve_depth.set(depth);
+        object
+    }
+
+    fn lookup(&self, number: u32, entry: XrefEntry) -> Option<PdfObject<'a>> {
+        match entry {
+            XrefEntry::Offset(offset) => self
+                .object_at(offset)
+                .filter(|(found, _)| *found == number)
+                .map(|(_, object)| object),
+            XrefEntry::Compressed { stream, index } => {
+                let PdfObject::Stream(stream) = self.get(stream)? else {
+                    return None;
+                };
+                let data = decode_stream(&stream)?;
+                let first = usize::try_from(stream.dict.get("First")?.as_integer()?).ok()?;
+                let contents = object_stream_contents(&stream)?;
+                // Trust the index, but search the header if it disagrees
+                let offset = match contents.get(index as usize) {
+                    Some(&(found, offset)) if found == number => offset,
+                    _ => contents.iter().find(|(found, _)| *found == number)?.1,
+                };
+                Lexer::new(&data, first.checked_add(offset)?).object(0)
+            }
+        }
+    }
+
+    /// Resolve an indirect reference, or clone a direct object.
+    pub fn resolve(&self, object: &PdfObject<'a>) -> Option<PdfObject<'a>> {
+        match object {
+            PdfObject::Reference(number, _) => self.get(*number),
+            other => Some(other.clone()),
+        }
+    }
+
+    /// Resolve a trailer entry such as `/Root` or `/Info` to a dictionary,
+    /// with the object number it was stored under (`0` if direct).
+    fn trailer_dict(&self, key: &str) -> Option<(u32, PdfDictionary<'a>)> {
+        let entry = self.trailer.get(key)?;
+        let number = match entry {
+            PdfObject::Reference(number, _) => *number,
+            _ => 0,
+        };
+        Some((number, self.resolve(entry)?.as_dict()?.clone()))
+    }
+
+    /// The document catalog (`/Root`).
+    pub fn catalog(&self) -> Option<PdfDictionary<'a>> {
+        self.trailer_dict("Root").map(|(_, dict)| dict)
+    }
+
+    /// The document information dictionary (`/Info`).
+    pub fn info(&self) -> Option<PdfDictionary<'a>> {
+        self.trailer_dict("Info").map(|(_, dict)| dict)
+    }
+
+    /// Returns `true` if strings and streams are encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.trailer.get("Encrypt").is_some()
+    }
+
+    /// PDF version, preferring the catalog's `/Version` that incremental
+    /// updates use to raise the header version.
+    pub fn version(&self) -> Option<String> {
+        self.catalog()
+            .and_then(|catalog| catalog.get("Version")?.as_name().map(str::to_string))
+            .or_else(|| self.header_version.clone())
+    }
+
+    /// Number of pages from the root of the page tree.
+    pub fn page_count(&self) -> Option<i64> {
+        let pages = self.resolve(self.catalog()?.get("Pages")?)?;
+        pages.as_dict()?.get("Count")?.as_integer()
+    }
+
+    /// The XMP packet of the catalog's `/Metadata` stream.
+    pub fn metadata_xmp(&self) -> Option<String> {
+        let PdfObject::Stream(stream) = self.resolve(self.catalog()?.get("Metadata")?)? else {
+            return None;
+        };
+        let data = decode_stream(&stream)?;
+        Some(String::from_utf8_lossy(&data).into_owned())
+    }
+
+    /// Numbers of all objects stored directly in the file, in order.
+    pub fn object_numbers(&self) -> Vec<u32> {
+        let table = if self.xref.is_empty() {
+            self.scanned_objects()
+        } else {
+            &self.xref
+        };
+        let mut numbers: Vec<u32> = table
+            .iter()
+            .filter(|(_, entry)| matches!(entry, XrefEntry::Offset(_)))
+            .map(|(&number, _)| number)
+            .collect();
+        numbers.sort_unstable();
+        numbers
+    }
+
+    /// Image XObjects with their object numbers, in object order. Streams
+    /// cannot live in object streams, so only objects stored directly in the
+    /// file are considered.
+    pub fn images(&self) -> Vec<(u32, PdfStream<'a>)> {
+        self.object_numbers()
+            .into_iter()
+            .filter_map(|number| match self.get(number)? {
+                PdfObject::Stream(stream) if stream.dict.has_name("Subtype", "Image") => {
+                    Some((number, stream))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// `(object number, offset)` pairs from the header of an object stream.
+fn object_stream_contents(stream: &PdfStream) -> Option<Vec<(u32, usize)>> {
+    let data = decode_stream(stream)?;
+    let count = usize::try_from(stream.dict.get("N")?.as_integer()?).ok()?;
+    let mut lexer = Lexer::new(&data, 0);
+    let mut contents = Vec::new();
+    for _ in 0..count.min(data.len()) {
+        let number = u32::try_from(lexer.unsigned()?).ok()?;
+        let offset = usize::try_from(lexer.unsigned()?).ok()?;
+        contents.push((number, offset));
+    }
+    Some(contents)
+}
+
+/// The filters of a stream with their decode parameters.
+fn stream_filters<'s, 'a>(
+    dict: &'s PdfDictionary<'a>,
+) -> Vec<(&'s str, Option<&'s PdfDictionary<'a>>)> {
+    let names: Vec<&str> = match dict.get("Filter") {
+        Some(PdfObject::Name(name)) => vec![name],
+        Some(PdfObject::Array(items)) => items.iter().filter_map(PdfObject::as_name).collect(),
+        _ => Vec::new(),
+    };
+    let parms: Vec<Option<&PdfDictionary>> = match dict.get("DecodeParms") {
+        Some(PdfObject::Dictionary(parms)) => vec![Some(parms)],
+        Some(PdfObject::Array(items)) => items.iter().map(PdfObject::as_dict).collect(),
+        _ => Vec::new(),
+    };
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| (name, parms.get(i).copied().flatten()))
+        .collect()
+}
+
+/// Remove every filter of a stream. Returns `None` for filters that are not
+/// supported, which includes the image codecs.
+pub fn decode_stream(stream: &PdfStream) -> Option<Vec<u8>> {
+    let mut data = stream.data.to_vec();
+    for (name, parms) in stream_filters(&stream.dict) {
+        data = apply_filter(name, parms, &data)?;
+    }
+    Some(data)
+}
+
+/// The JPEG data of a `/DCTDecode` image, after any filters applied on top.
+pub fn image_jpeg_data(stream: &PdfStream) -> Option<Vec<u8>> {
+    let filters = stream_filters(&stream.dict);
+    let (last, rest) = filters.split_last()?;
+    if !matches!(last.0, "DCTDecode" | "DCT") {
+        return None;
+    }
+    let mut data = stream.data.to_vec();
+    for (name, parms) in rest {
+        data = apply_filter(name, *parms, &data)?;
+    }
+    Some(data)
+}
+
+fn apply_filter(name: &str, parms: Option<&PdfDictionary>, data: &[u8]) -> Option<Vec<u8>> {
+    match name {
+        "FlateDecode" | "Fl" => {
+            let mut inflated = Vec::new();
+            // Keep what was inflated from truncated streams
+            if limits::read_to_end_limited(
+                flate2::read::ZlibDecoder::new(data),
+                &mut inflated,
+                limits::MAX_INFLATED_SIZE,
+            )
+            .is_err()
+                && inflated.is_empty()
+            {
+                return None;
+            }
+            match parms {
+                Some(parms) => apply_predictor(parms, inflated),
+                None => Some(inflated),
+            }
+        }
+        "ASCIIHexDecode" | "AHx" => {
+            let end = data.iter().position(|&b| b == b'>').unwrap_or(data.len());
+            let mut hex = b"<".to_vec();
+            hex.extend_from_slice(&data[..end]);
+            hex.push(b'>');
+            Lexer::new(&hex, 0).hex_string()
+        }
+        "ASCII85Decode" | "A85" => ascii85_decode(data),
+        _ => None,
+    }
+}
+
+/// Undo the PNG row predictors (`/Predictor` 10-15) that usually accompany
+/// Flate-compressed cross-reference streams.
+fn apply_predictor(parms: &PdfDictionary, data: Vec<u8>) -> Option<Vec<u8>> {
+    let parameter = |key, default| {
+        parms
+            .get(key)
+            .and_then(PdfObject::as_integer)
+            .and_then(|v| usize::try_from(v).ok())
+            .unwrap_or(default)
+    };
+    match parameter("Predictor", 1) {
+        1 => return Some(data),
+        p if p < 10 => return None,
+        _ => {}
+    }
+    let bits = parameter("Colors", 1).checked_mul(parameter("BitsPerComponent", 8))?;
+    let bpp = bits.div_ceil(8).max(1);
+    let row = bits.checked_mul(parameter("Columns", 1))?.div_ceil(8);
+    if row == 0 {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(data.len());
+    // Rows never hold more than the data, whatever `/Columns` claims
+    let mut previous = vec![0u8; row.min(data.len())];
+    for chunk in data.chunks(row + 1) {
+        let (&kind, raw) = chunk.split_first()?;
+        let mut current = raw.to_vec();
+        for i in 0..current.len() {
+            let left = if i >= bpp { current[i - bpp] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= bpp { previous[i - bpp] } else { 0 };
+            let prediction = match kind {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return None,
+            };
+            current[i] = current[i].wrapping_add(prediction);
+        }
+        previous[..current.len()].copy_from_slice(&current);
+        out.extend_from_slice(&current);
+    }
+    Some(out)
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let distance = |v: u8| (estimate - v as i16).abs();
+    if distance(left) <= distance(up) && distance(left) <= distance(up_left) {
+        left
+    } else if distance(up) <= distance(up_left) {
+        up
+    } else {
+        up_left
+    }
+}
+
+fn ascii85_decode(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut group = Vec::with_capacity(5);
+    for &b in data {
+        match b {
+            b'~' => break,
+            b'z' if group.is_empty() => out.extend_from_slice(&[0; 4]),
+            b'!'..=b'u' => {
+                group.push(b - b'!');
+                if group.len() == 5 {
+                    let value = group.iter().fold(0u64, |acc, &d| acc * 85 + d as u64);
+                    out.extend_from_slice(&u32::try_from(value).ok()?.to_be_bytes());
+                    group.clear();
+                }
+            }
+            _ if is_whitespace(b) => {}
+            _ => return None,
+        }
+    }
+    // A final partial group is padded with `u` and truncated again
+    if !group.is_empty() {
+        let len = group.len();
+        group.resize(5, 84);
+        let value = group.iter().fold(0u64, |acc, &d| acc * 85 + d as u64);
+        out.extend_from_slice(&u32::try_from(value).ok()?.to_be_bytes()[..len - 1]);
+    }
+    Some(out)
+}
+
+/// Characters of PDFDocEncoding that differ from Latin-1.
+fn pdf_doc_char(byte: u8) -> char {
+    const LOW: [char; 8] = ['˘', 'ˇ', 'ˆ', '˙', '˝', '˛', '˚', '˜'];
+    const HIGH: [char; 33] = [
+        '•', '†', '‡', '…', '—', '–', 'ƒ', '⁄', '‹', '›', '−', '‰', '„', '“', '”', '‘', '’', '‚',
+        '™', 'ﬁ', 'ﬂ', 'Ł', 'Œ', 'Š', 'Ÿ', 'Ž', 'ı', 'ł', 'œ', 'š', 'ž', '\u{FFFD}', '€',
+    ];
+    match byte {
+        0x18..=0x1F => LOW[(byte - 0x18) as usize],
+        0x80..=0xA0 => HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+/// Decode a PDF text string: UTF-16BE or UTF-8 when marked with a byte
+/// order mark, PDFDocEncoding otherwise.
+pub fn decode_text_string(bytes: &[u8]) -> String {
+    let text = if let Some(utf16) = bytes.strip_prefix(b"\xFE\xFF") {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else if let Some(utf8) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        String::from_utf8_lossy(utf8).into_owned()
+    } else {
+        bytes.iter().map(|&b| pdf_doc_char(b)).collect()
+    };
+    text.trim_end_matches('\0').to_string()
+}
+
+/// Convert a PDF date (`D:YYYYMMDDHHmmSSOHH'mm'`) to `YYYY:MM:DD HH:MM:SS`,
+/// followed by the UTC offset as `+HH:MM` when the date has one.
+///
+/// Every field after the year is optional, as the specification allows.
+pub fn parse_pdf_date(value: &str) -> Option<String> {
+    let value = value.trim();
+    let value = value.strip_prefix("D:").unwrap_or(value);
+    let digits = value.bytes().take_while(u8::is_ascii_digit).count();
+    if !matches!(digits, 4 | 6 | 8 | 10 | 12 | 14) {
+        return None;
+    }
+    let field = |start: usize, len: usize, default: u32| -> Option<u32> {
+        if digits >= start + len {
+            value[start..start + len].parse().ok()
+        } else {
+            Some(default)
+        }
+    };
+    let year = field(0, 4, 0)?;
+    let month = field(4, 2, 1)?;
+    let day = field(6, 2, 1)?;
+    let hour = field(8, 2, 0)?;
+    let minute = field(10, 2, 0)?;
+    let second = field(12, 2, 0)?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+    // Leap seconds are allowed
+    if second > 60 {
+        return None;
+    }
+
+    let rest = &value[digits..];
+    let offset = match rest.chars().next() {
+        None => String::new(),
+        Some('Z') => "+00:00".to_string(),
+        Some(sign @ ('+' | '-')) => {
+            let zone: String = rest[1..].chars().filter(|c| *c != '\'').collect();
+            let hours: u32 = zone.get(0..2)?.parse().ok()?;
+            let minutes: u32 = match zone.get(2..4) {
+                Some(m) => m.parse().ok()?,
+                None => 0,
+            };
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            format!("{}{:02}:{:02}", sign, hours, minutes)
+        }
+        Some(_) => return None,
+    };
+
+    Some(format!(
+        "{:04}:{:02}:{:02} {:02}:{:02}:{:02}{}",
+        year, month, day, hour, minute, second, offset
+    ))
+}
+
+/// Map the entries of an `/Info` dictionary to `PDF:<Key>` entries.
+///
+/// `CreationDate` and `ModDate` are converted with [`parse_pdf_date`]; dates
+/// that do not parse are kept as written.
+pub fn info_to_metadata(
+    doc: &PdfDocument,
+    info: &PdfDictionary,
+    object: u32,
+) -> HashMap<String, MetadataValue> {
+    let mut map = HashMap::new();
+    for (key, value) in &info.0 {
+        let text = match doc.resolve(value) {
+            Some(PdfObject::String(bytes)) => decode_text_string(&bytes),
+            Some(PdfObject::Name(name)) => name,
+            Some(PdfObject::Integer(value)) => value.to_string(),
+            Some(PdfObject::Real(value)) => value.to_string(),
+            Some(PdfObject::Boolean(value)) => value.to_string(),
+            _ => continue,
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+        let value = match key.as_str() {
+            "CreationDate" | "ModDate" => match parse_pdf_date(&text) {
+                Some(date) => MetadataValue::text(date),
+                None => MetadataValue::text(text),
+            },
+            _ => MetadataValue::text(text),
+        };
+        map.insert(
+            format!("PDF:{}", key),
+            value.with_source(MetadataSource::Pdf { object }),
+        );
+    }
+    map
+}
+
+/// The XMP packet of the document's `/Metadata` stream, unless it is
+/// encrypted.
+pub fn find_xmp(bytes: &[u8]) -> Vec<String> {
+    let Ok(doc) = parse_pdf(bytes) else {
+        return Vec::new();
+    };
+    let metadata_encrypted = doc.trailer_dict("Encrypt").is_some_and(|(_, encrypt)| {
+        encrypt.get("EncryptMetadata") != Some(&PdfObject::Boolean(false))
+    });
+    if doc.is_encrypted() && metadata_encrypted {
+        return Vec::new();
+    }
+    doc.metadata_xmp().into_iter().collect()
+}
+
+/// Extract the document information, structure and embedded image EXIF of
+/// a PDF as `exif_data` entries.
+///
+/// Info strings of encrypted documents cannot be read without decrypting
+/// them, so only `PDF:Encrypted` and the structural fields are reported.
+pub fn extract_pdf_data(bytes: &[u8]) -> HashMap<String, MetadataValue> {
+    extract_pdf_data_with_diagnostics(bytes, &mut Vec::new())
+}
+
+/// Extract the PDF fields as [`extract_pdf_data`] does, reporting a document
+/// whose structure cannot be read.
+pub fn extract_pdf_data_with_diagnostics(
+    bytes: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, MetadataValue> {
+    let mut map = HashMap::new();
+    if !container::is_pdf(bytes) {
+        return map;
+    }
+    let doc = match parse_pdf(bytes) {
+        Ok(doc) => doc,
+        Err(e) => {
+            diagnostics.push(Diagnostic::error("PDF", None, e));
+            return map;
+        }
+    };
+    let trailer_source = MetadataSource::Pdf { object: 0 };
+
+    if let Some(version) = doc.version() {
+        map.insert(
+            "PDF:Version".to_string(),
+            MetadataValue::text(version).with_source(trailer_source.clone()),
+        );
+    }
+    map.insert(
+        "PDF:Encrypted".to_string(),
+        MetadataValue::text(if doc.is_encrypted() { "Yes" } else { "No" })
+            .with_source(trailer_source.clone()),
+    );
+    if let Some(count) = doc.page_count() {
+        map.insert(
+            "PDF:PageCount".to_string(),
+            MetadataValue::new(RawValue::Integer(vec![count]), count.to_string())
+                .with_source(trailer_source.clone()),
+        );
+    }
+    if !doc.is_encrypted()
+        && let Some((object, info)) = doc.trailer_dict("Info")
+    {
+        for (key, value) in info_to_metadata(&doc, &info, object) {
+            map.entry(key).or_insert(value);
+        }
+    }
+
+    let images = doc.images();
+    if !images.is_empty() {
+        map.insert(
+            "PDF:EmbeddedImages".to_string(),
+            MetadataValue::new(
+                RawValue::Integer(vec![images.len() as i64]),
+                images.len().to_string(),
+            )
+            .with_source(trailer_source),
+        );
+    }
+    for (index, (object, stream)) in images.iter().enumerate() {
+        let Some(jpeg) = image_jpeg_data(stream) else {
+            continue;
+        };
+        let (exif, _) = exif_core::extract_exif_data(&jpeg);
+        for (key, value) in exif {
+            map.insert(
+                format!("PDF-Image{}:{}", index + 1, key),
+                value.with_source(MetadataSource::Pdf { object: *object }),
+            );
+        }
+    }
+
+    map
+}
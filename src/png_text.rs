@@ -0,0 +1,198 @@
+//! PNG textual chunk extraction.
+//!
+//! PNG files carry free-form keyword/value pairs in `tEXt`, `zTXt` and `iTXt`
+//! chunks and a last-modification time in `tIME`. Besides the registered
+//! keywords (`Author`, `Comment`, `Creation Time`, ...) image generators store
+//! their prompts and settings here, e.g. Stable Diffusion's `parameters` and
+//! ComfyUI's `prompt` and `workflow` JSON. Every pair is surfaced as a
+//! `PNG:<Keyword>` entry.
+
+use crate::container::{self, Chunk};
+use crate::diagnostics::Diagnostic;
+use crate::iptc::decode_latin1;
+use crate::limits;
+use crate::types::{MetadataSource, MetadataValue, RawValue};
+use crate::xmp::PNG_XMP_KEYWORD;
+use std::collections::HashMap;
+
+/// A decoded `tEXt`, `zTXt` or `iTXt` chunk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PngText {
+    /// Chunk type the pair was read from.
+    pub chunk: String,
+    pub keyword: String,
+    /// Language tag of an `iTXt` chunk, if given.
+    pub language: Option<String>,
+    /// Keyword translated into `language`, if given.
+    pub translated_keyword: Option<String>,
+    pub text: String,
+    /// Whether the text was zlib compressed in the file.
+    pub compressed: bool,
+}
+
+fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut inflated = Vec::new();
+    limits::read_to_end_limited(
+        flate2::read::ZlibDecoder::new(data),
+        &mut inflated,
+        limits::MAX_INFLATED_SIZE,
+    )
+    .map_err(|e| format!("text could not be inflated: {}", e))?;
+    Ok(inflated)
+}
+
+/// Split off a NUL-terminated field.
+fn split_nul(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let end = data.iter().position(|&b| b == 0)?;
+    Some((&data[..end], &data[end + 1..]))
+}
+
+/// Decode a textual chunk. Returns `None` for other chunk types and for
+/// chunks that are malformed or fail to decompress.
+pub fn parse_text_chunk(chunk: &Chunk) -> Option<PngText> {
+    read_text_chunk(chunk).ok().flatten()
+}
+
+/// Decode a textual chunk, saying why a malformed one could not be read.
+/// Other chunk types give `Ok(None)`.
+fn read_text_chunk(chunk: &Chunk) -> Result<Option<PngText>, String> {
+    if !matches!(&chunk.id, b"tEXt" | b"zTXt" | b"iTXt") {
+        return Ok(None);
+    }
+    let truncated = || "is cut off inside its header".to_string();
+    let (keyword, rest) = split_nul(chunk.data).ok_or_else(truncated)?;
+    let keyword = decode_latin1(keyword);
+    let mut text = PngText {
+        chunk: chunk.name(),
+        keyword,
+        language: None,
+        translated_keyword: None,
+        text: String::new(),
+        compressed: false,
+    };
+
+    match &chunk.id {
+        b"zTXt" => {
+            // Compression method byte; only zlib (0) is defined
+            text.text = decode_latin1(&inflate(rest.get(1..).ok_or_else(truncated)?)?);
+            text.compressed = true;
+        }
+        b"iTXt" => {
+            let compressed = *rest.first().ok_or_else(truncated)? == 1;
+            let (language, rest) =
+                split_nul(rest.get(2..).ok_or_else(truncated)?).ok_or_else(truncated)?;
+            let (translated, body) = split_nul(rest).ok_or_else(truncated)?;
+            let body = if compressed {
+                inflate(body)?
+            } else {
+                body.to_vec()
+            };
+            let non_empty = |bytes: &[u8]| {
+                let s = String::from_utf8_lossy(bytes).into_owned();
+                (!s.is_empty()).then_some(s)
+            };
+            text.language = non_empty(language);
+            text.translated_keyword = non_empty(translated);
+            text.text = String::from_utf8_lossy(&body).into_owned();
+            text.compressed = compressed;
+        }
+        _ => text.text = decode_latin1(rest),
+    }
+    Ok(Some(text))
+}
+
+/// Decode a `tIME` chunk as `YYYY:MM:DD HH:MM:SS` (always UTC).
+pub fn parse_time_chunk(data: &[u8]) -> Option<String> {
+    let [y0, y1, month, day, hour, minute, second] = *data.get(..7)? else {
+        return None;
+    };
+    Some(format!(
+        "{:04}:{:02}:{:02} {:02}:{:02}:{:02}",
+        u16::from_be_bytes([y0, y1]),
+        month,
+        day,
+        hour,
+        minute,
+        second
+    ))
+}
+
+/// All textual chunks of a PNG file, in file order. The XMP packet is left to
+/// the XMP extractor.
+pub fn find_png_text(bytes: &[u8]) -> Vec<PngText> {
+    container::png_chunks(bytes)
+        .iter()
+        .filter_map(parse_text_chunk)
+        .filter(|t| t.keyword.as_bytes() != PNG_XMP_KEYWORD)
+        .collect()
+}
+
+/// Extract PNG textual chunks and `tIME` as `exif_data` entries.
+///
+/// Keywords become `PNG:<Keyword>`; a keyword repeated across chunks has its
+/// values joined with a blank line. `tIME` becomes `PNG:ModifyDate`.
+pub fn extract_png_text_data(bytes: &[u8]) -> HashMap<String, MetadataValue> {
+    extract_png_text_data_with_diagnostics(bytes, &mut Vec::new())
+}
+
+/// Extract PNG textual chunks as [`extract_png_text_data`] does, reporting
+/// a damaged chunk sequence and textual chunks that cannot be decoded.
+pub fn extract_png_text_data_with_diagnostics(
+    bytes: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, MetadataValue> {
+    let mut map: HashMap<String, MetadataValue> = HashMap::new();
+    if !container::is_png(bytes) {
+        return map;
+    }
+
+    let (chunks, problem) = container::png_chunks_checked(bytes);
+    diagnostics.extend(problem);
+    let mut texts = Vec::new();
+    for chunk in &chunks {
+        match read_text_chunk(chunk) {
+            Ok(Some(text)) if text.keyword.as_bytes() != PNG_XMP_KEYWORD => texts.push(text),
+            Ok(_) => {}
+            Err(e) => diagnostics.push(Diagnostic::error(
+                "PNG",
+                Some(chunk.offset),
+                format!("{} chunk {}", chunk.name(), e),
+            )),
+        }
+    }
+
+    for text in texts {
+        if text.keyword.is_empty() || text.text.is_empty() {
+            continue;
+        }
+        let key = format!("PNG:{}", text.keyword);
+        match map.get_mut(&key) {
+            Some(existing) => {
+                existing.display = format!("{}\n\n{}", existing.display, text.text);
+                existing.raw = RawValue::Text(existing.display.clone());
+            }
+            None => {
+                map.insert(
+                    key,
+                    MetadataValue::text(text.text)
+                        .with_source(MetadataSource::Png { chunk: text.chunk }),
+                );
+            }
+        }
+    }
+
+    if let Some(time) = chunks
+        .iter()
+        .find(|c| &c.id == b"tIME")
+        .and_then(|c| parse_time_chunk(c.data))
+    {
+        map.insert(
+            "PNG:ModifyDate".to_string(),
+            MetadataValue::text(time).with_source(MetadataSource::Png {
+                chunk: "tIME".to_string(),
+            }),
+        );
+    }
+
+    map
+}
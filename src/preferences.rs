@@ -129,7 +129,7 @@ impl ExportProfile {
     /// Get preset profiles for common use cases
     pub fn get_presets() -> Vec<ExportProfile> {
         vec![
-            // Journalism: Location, camera settings, and timestamps
+            // Journalism: Location, camera settings, timestamps and IPTC captions/credits
             ExportProfile::new(
                 "Journalism".to_string(),
                 "Essential metadata for news and photojournalism".to_string(),
@@ -144,6 +144,20 @@ impl ExportProfile {
                     "ISOSpeedRatings",
                     "ExposureTime",
                     "Software",
+                    "IPTC:ObjectName",
+                    "IPTC:Headline",
+                    "IPTC:Caption-Abstract",
+                    "IPTC:Keywords",
+                    "IPTC:By-line",
+                    "IPTC:Credit",
+                    "IPTC:Source",
+                    "IPTC:CopyrightNotice",
+                    "IPTC:DateCreated",
+                    "IPTC:TimeCreated",
+                    "IPTC:City",
+                    "IPTC:Sub-location",
+                    "IPTC:Province-State",
+                    "IPTC:Country-PrimaryLocationName",
                 ]
                 .iter()
                 .map(|s| s.to_string())
@@ -211,3 +225,39 @@ impl ExportProfile {
         ]
     }
 }
+
+const TRUST_ANCHORS_KEY: &str = "file_metadata_extractor_trust_anchors";
+
+/// PEM certificates imported as trust anchors for C2PA signers, kept in
+/// localStorage so verification works offline.
+pub struct TrustAnchors;
+
+impl TrustAnchors {
+    /// Load the stored PEM text, or an empty string if none was imported
+    pub fn load() -> String {
+        window()
+            .and_then(|w| w.local_storage().ok().flatten())
+            .and_then(|storage| storage.get_item(TRUST_ANCHORS_KEY).ok().flatten())
+            .unwrap_or_default()
+    }
+
+    /// Replace the stored PEM text
+    pub fn save(pem: &str) -> Result<(), String> {
+        let storage = window()
+            .and_then(|w| w.local_storage().ok().flatten())
+            .ok_or_else(|| "Failed to access localStorage".to_string())?;
+        storage
+            .set_item(TRUST_ANCHORS_KEY, pem)
+            .map_err(|_| "Failed to save trust anchors".to_string())
+    }
+
+    /// Remove all imported trust anchors
+    pub fn clear() -> Result<(), String> {
+        let storage = window()
+            .and_then(|w| w.local_storage().ok().flatten())
+            .ok_or_else(|| "Failed to access localStorage".to_string())?;
+        storage
+            .remove_item(TRUST_ANCHORS_KEY)
+            .map_err(|_| "Failed to clear trust anchors".to_string())
+    }
+}
@@ -0,0 +1,643 @@
+//! TIFF-based camera RAW files: DNG, CR2, NEF, ARW, ORF and RW2.
+//!
+//! All of these are TIFF files at heart. Olympus ORF and Panasonic RW2 only
+//! change the TIFF magic number, Canon CR2 adds a `CR` marker after the
+//! header and DNG, NEF and ARW are plain TIFFs. The sensor data and the
+//! camera's JPEG previews live in a chain of IFDs and in `SubIFDs` hanging off
+//! them; `NewSubfileType` tells the full-resolution image from reduced
+//! previews. [`parse_raw`] walks every directory so dimensions come from the
+//! real raw image and the largest embedded JPEG can stand in for the sensor
+//! data, which browsers cannot display.
+
+use crate::container::{self, ByteOrder, TiffDirectory, TiffEntry};
+use crate::jpeg;
+use crate::types::{MetadataSource, MetadataValue, RawValue};
+use exif::{Context, Tag};
+use std::collections::{HashMap, HashSet};
+
+/// TIFF-based RAW format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RawFormat {
+    Dng,
+    Cr2,
+    Nef,
+    Arw,
+    Orf,
+    Rw2,
+}
+
+impl RawFormat {
+    /// Every supported format.
+    pub const ALL: [RawFormat; 6] = [
+        RawFormat::Dng,
+        RawFormat::Cr2,
+        RawFormat::Nef,
+        RawFormat::Arw,
+        RawFormat::Orf,
+        RawFormat::Rw2,
+    ];
+
+    /// Human readable format name.
+    pub fn name(self) -> &'static str {
+        match self {
+            RawFormat::Dng => "Adobe DNG",
+            RawFormat::Cr2 => "Canon CR2",
+            RawFormat::Nef => "Nikon NEF",
+            RawFormat::Arw => "Sony ARW",
+            RawFormat::Orf => "Olympus ORF",
+            RawFormat::Rw2 => "Panasonic RW2",
+        }
+    }
+
+    /// MIME type used for the format.
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            RawFormat::Dng => "image/x-adobe-dng",
+            RawFormat::Cr2 => "image/x-canon-cr2",
+            RawFormat::Nef => "image/x-nikon-nef",
+            RawFormat::Arw => "image/x-sony-arw",
+            RawFormat::Orf => "image/x-olympus-orf",
+            RawFormat::Rw2 => "image/x-panasonic-rw2",
+        }
+    }
+
+    /// File extensions of the format, lowercase and without the dot.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            RawFormat::Dng => &["dng"],
+            RawFormat::Cr2 => &["cr2"],
+            RawFormat::Nef => &["nef", "nrw"],
+            RawFormat::Arw => &["arw", "srf", "sr2"],
+            RawFormat::Orf => &["orf"],
+            RawFormat::Rw2 => &["rw2"],
+        }
+    }
+
+    /// The format a file name's extension belongs to.
+    pub fn from_extension(name: &str) -> Option<RawFormat> {
+        let extension = name.rsplit_once('.')?.1.to_lowercase();
+        RawFormat::ALL
+            .into_iter()
+            .find(|format| format.extensions().contains(&extension.as_str()))
+    }
+}
+
+/// Returns `true` if the MIME type is one of the RAW types.
+pub fn is_raw_mime_type(mime: &str) -> bool {
+    RawFormat::ALL.iter().any(|f| f.mime_type() == mime)
+}
+
+const NEW_SUBFILE_TYPE: u16 = 0x00FE;
+const IMAGE_WIDTH: u16 = 0x0100;
+const IMAGE_LENGTH: u16 = 0x0101;
+const BITS_PER_SAMPLE: u16 = 0x0102;
+const COMPRESSION: u16 = 0x0103;
+const PHOTOMETRIC_INTERPRETATION: u16 = 0x0106;
+const MAKE: u16 = 0x010F;
+const STRIP_OFFSETS: u16 = 0x0111;
+const STRIP_BYTE_COUNTS: u16 = 0x0117;
+const SUB_IFDS: u16 = 0x014A;
+const JPEG_INTERCHANGE_FORMAT: u16 = 0x0201;
+const JPEG_INTERCHANGE_FORMAT_LENGTH: u16 = 0x0202;
+const DNG_VERSION: u16 = 0xC612;
+/// Panasonic sensor size and borders in the RW2 IFD0.
+const RW2_SENSOR_WIDTH: u16 = 0x0002;
+const RW2_SENSOR_HEIGHT: u16 = 0x0003;
+const RW2_SENSOR_BORDERS: [u16; 4] = [0x0004, 0x0005, 0x0006, 0x0007];
+/// Panasonic full-size JPEG preview in the RW2 IFD0.
+const RW2_JPG_FROM_RAW: u16 = 0x002E;
+
+/// Tags locating image data or lookup tables, which are not shown as metadata.
+const LAYOUT_TAGS: &[u16] = &[
+    STRIP_OFFSETS,
+    STRIP_BYTE_COUNTS,
+    0x0144, // TileOffsets
+    0x0145, // TileByteCounts
+    SUB_IFDS,
+    JPEG_INTERCHANGE_FORMAT,
+    JPEG_INTERCHANGE_FORMAT_LENGTH,
+    0x8769, // ExifIFDPointer
+    0x8825, // GPSInfoIFDPointer
+    0x927C, // MakerNote
+    0x02BC, // ApplicationNotes (XMP)
+    0x83BB, // IPTC-NAA
+    0x8773, // InterColorProfile
+    0xC634, // DNGPrivateData
+];
+
+/// DNG-specific IFD0 tags, shown under `DNG:<Name>`.
+pub static DNG_TAGS: &[(u16, &str)] = &[
+    (0xC612, "DNGVersion"),
+    (0xC613, "DNGBackwardVersion"),
+    (0xC614, "UniqueCameraModel"),
+    (0xC615, "LocalizedCameraModel"),
+    (0xC62A, "BaselineExposure"),
+    (0xC62F, "CameraSerialNumber"),
+    (0xC68B, "OriginalRawFileName"),
+    (0xC6F8, "ProfileName"),
+];
+
+/// TIFF/EP and DNG image tags the EXIF reader has no names for.
+static RAW_TAG_NAMES: &[(u16, &str)] = &[
+    (0x00FE, "NewSubfileType"),
+    (0x00FF, "SubfileType"),
+    (0x0142, "TileWidth"),
+    (0x0143, "TileLength"),
+    (0x0153, "SampleFormat"),
+    (0x828D, "CFARepeatPatternDim"),
+    (0x828E, "CFAPattern"),
+    (0xC617, "CFALayout"),
+    (0xC619, "BlackLevelRepeatDim"),
+    (0xC61A, "BlackLevel"),
+    (0xC61D, "WhiteLevel"),
+    (0xC61E, "DefaultScale"),
+    (0xC61F, "DefaultCropOrigin"),
+    (0xC620, "DefaultCropSize"),
+    (0xC68D, "ActiveArea"),
+];
+
+/// One image stored in a RAW file: the sensor data, a preview or a
+/// thumbnail.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawImage {
+    /// Directory the image is described by, e.g. `IFD0` or `SubIFD1`.
+    pub ifd: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// `NewSubfileType`; bit 0 marks a reduced-resolution image.
+    pub subfile_type: u32,
+    pub compression: Option<u32>,
+    pub photometric: Option<u32>,
+    pub bits_per_sample: Option<u32>,
+    /// Byte range of a displayable (baseline or progressive) JPEG.
+    pub jpeg: Option<(usize, usize)>,
+}
+
+impl RawImage {
+    /// Whether this is sensor data rather than a rendered preview.
+    pub fn is_sensor_data(&self) -> bool {
+        matches!(self.photometric, Some(32803 | 34892))
+            || matches!(self.compression, Some(32767 | 32769 | 34713))
+    }
+
+    fn area(&self) -> u64 {
+        u64::from(self.width.unwrap_or(0)) * u64::from(self.height.unwrap_or(0))
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            parts.push(format!("{}x{}", width, height));
+        }
+        if let Some(bits) = self.bits_per_sample {
+            parts.push(format!("{}-bit", bits));
+        }
+        if let Some(photometric) = self.photometric {
+            parts.push(photometric_name(photometric));
+        }
+        if let Some(compression) = self.compression {
+            parts.push(compression_name(compression));
+        }
+        format!("{} ({})", parts.join(", "), self.ifd)
+    }
+}
+
+/// Structure of a RAW file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawFile {
+    pub format: RawFormat,
+    pub byte_order: ByteOrder,
+    pub images: Vec<RawImage>,
+}
+
+impl RawFile {
+    /// The full-resolution sensor image, falling back to the largest
+    /// full-resolution image of any kind.
+    pub fn main_image(&self) -> Option<&RawImage> {
+        let full = || self.images.iter().filter(|i| i.subfile_type & 1 == 0);
+        full()
+            .filter(|i| i.is_sensor_data())
+            .max_by_key(|i| i.area())
+            .filter(|i| i.area() > 0)
+            .or_else(|| full().max_by_key(|i| i.area()).filter(|i| i.area() > 0))
+    }
+
+    /// The largest embedded JPEG preview.
+    pub fn preview(&self) -> Option<&RawImage> {
+        self.images
+            .iter()
+            .filter(|i| i.jpeg.is_some())
+            .max_by_key(|i| (i.area(), i.jpeg.map_or(0, |(_, len)| len)))
+    }
+}
+
+fn compression_name(compression: u32) -> String {
+    match compression {
+        1 => "Uncompressed".to_string(),
+        6 => "JPEG (old-style)".to_string(),
+        7 => "JPEG".to_string(),
+        8 => "Deflate".to_string(),
+        32767 => "Sony ARW Compressed".to_string(),
+        32769 => "Packed RAW".to_string(),
+        34713 => "Nikon NEF Compressed".to_string(),
+        34892 => "Lossy JPEG".to_string(),
+        52546 => "JPEG XL".to_string(),
+        other => format!("Compression {}", other),
+    }
+}
+
+fn photometric_name(photometric: u32) -> String {
+    match photometric {
+        0 | 1 => "Grayscale".to_string(),
+        2 => "RGB".to_string(),
+        6 => "YCbCr".to_string(),
+        32803 => "Color Filter Array".to_string(),
+        34892 => "Linear Raw".to_string(),
+        other => format!("Photometric {}", other),
+    }
+}
+
+/// Parse the TIFF header of a RAW file, accepting the ORF (`RO`, `SR`) and
+/// RW2 (`U`) magic numbers next to TIFF's 42.
+pub fn raw_header(data: &[u8]) -> Option<(ByteOrder, u32)> {
+    let order = match data.get(0..2)? {
+        b"II" => ByteOrder::Little,
+        b"MM" => ByteOrder::Big,
+        _ => return None,
+    };
+    match order.u16(data, 2)? {
+        42 | 0x4F52 | 0x5352 | 0x0055 => Some((order, order.u32(data, 4)?)),
+        _ => None,
+    }
+}
+
+/// Copy of the file with ORF/RW2 magic numbers replaced by TIFF's 42, so
+/// standard TIFF readers accept it. `None` for files that need no change.
+pub fn normalized_tiff(data: &[u8]) -> Option<Vec<u8>> {
+    let (order, _) = raw_header(data)?;
+    if order.u16(data, 2)? == 42 {
+        return None;
+    }
+    let mut tiff = data.to_vec();
+    let magic = match order {
+        ByteOrder::Little => 42u16.to_le_bytes(),
+        ByteOrder::Big => 42u16.to_be_bytes(),
+    };
+    tiff[2..4].copy_from_slice(&magic);
+    Some(tiff)
+}
+
+fn first(directory: &TiffDirectory<'_>, tag: u16, order: ByteOrder) -> Option<u32> {
+    directory.get(tag)?.first_unsigned(order)
+}
+
+/// Whether `range` holds a JPEG a browser can display: lossless JPEG (used
+/// for sensor data in CR2 and DNG) has the same SOI signature but an SOF3
+/// frame.
+fn displayable_jpeg(data: &[u8], offset: usize, length: usize) -> Option<(usize, usize)> {
+    let bytes = data.get(offset..offset.checked_add(length)?)?;
+    let structure = jpeg::analyze_jpeg(bytes)?;
+    structure
+        .segments
+        .iter()
+        .any(|s| matches!(s.marker, 0xC0..=0xC2))
+        .then_some((offset, length))
+}
+
+fn describe_image(
+    data: &[u8],
+    format: RawFormat,
+    order: ByteOrder,
+    name: &str,
+    directory: &TiffDirectory<'_>,
+) -> RawImage {
+    let mut image = RawImage {
+        ifd: name.to_string(),
+        width: first(directory, IMAGE_WIDTH, order),
+        height: first(directory, IMAGE_LENGTH, order),
+        subfile_type: first(directory, NEW_SUBFILE_TYPE, order).unwrap_or(0),
+        compression: first(directory, COMPRESSION, order),
+        photometric: first(directory, PHOTOMETRIC_INTERPRETATION, order),
+        bits_per_sample: first(directory, BITS_PER_SAMPLE, order),
+        jpeg: None,
+    };
+
+    if format == RawFormat::Rw2 && name == "IFD0" {
+        let borders: Vec<Option<u32>> = RW2_SENSOR_BORDERS
+            .iter()
+            .map(|tag| first(directory, *tag, order))
+            .collect();
+        if let [Some(top), Some(left), Some(bottom), Some(right)] = borders[..]
+            && bottom > top
+            && right > left
+        {
+            image.width = Some(right - left);
+            image.height = Some(bottom - top);
+        } else {
+            image.width = image.width.or(first(directory, RW2_SENSOR_WIDTH, order));
+            image.height = image.height.or(first(directory, RW2_SENSOR_HEIGHT, order));
+        }
+        // The sensor data itself is not a TIFF strip image
+        image.photometric = image.photometric.or(Some(32803));
+    }
+
+    let located = |offset_tag, length_tag| {
+        let offsets = directory.get(offset_tag)?.unsigned(order);
+        let lengths = directory.get(length_tag)?.unsigned(order);
+        match (&offsets[..], &lengths[..]) {
+            ([offset], [length]) => Some((*offset as usize, *length as usize)),
+            _ => None,
+        }
+    };
+    image.jpeg = located(JPEG_INTERCHANGE_FORMAT, JPEG_INTERCHANGE_FORMAT_LENGTH)
+        .or_else(|| {
+            matches!(image.compression, Some(6 | 7))
+                .then(|| located(STRIP_OFFSETS, STRIP_BYTE_COUNTS))
+                .flatten()
+        })
+        .and_then(|(offset, length)| displayable_jpeg(data, offset, length));
+    image
+}
+
+/// Identify a RAW file from its header and IFD0.
+///
+/// NEF and ARW files are plain TIFFs, so they are only recognised when a
+/// Nikon or Sony camera wrote them and they carry sensor data.
+pub fn detect_format(data: &[u8]) -> Option<RawFormat> {
+    let (order, offset) = raw_header(data)?;
+    match order.u16(data, 2)? {
+        0x4F52 | 0x5352 => return Some(RawFormat::Orf),
+        0x0055 => return Some(RawFormat::Rw2),
+        _ => {}
+    }
+    if data.get(8..10) == Some(b"CR") && data.get(10) == Some(&2) {
+        return Some(RawFormat::Cr2);
+    }
+    let ifd0 = container::tiff_directory(data, order, offset)?;
+    if ifd0.get(DNG_VERSION).is_some() {
+        return Some(RawFormat::Dng);
+    }
+    let make = ifd0
+        .get(MAKE)
+        .map(TiffEntry::text)
+        .unwrap_or_default()
+        .to_uppercase();
+    let format = if make.starts_with("NIKON") {
+        RawFormat::Nef
+    } else if make.starts_with("SONY") {
+        RawFormat::Arw
+    } else {
+        return None;
+    };
+    walk(data, format, order, offset)
+        .images
+        .iter()
+        .any(RawImage::is_sensor_data)
+        .then_some(format)
+}
+
+/// MIME type of a RAW file, from its content or, for TIFF-structured files
+/// the content does not identify, its extension.
+pub fn raw_mime_type(name: &str, data: &[u8]) -> Option<&'static str> {
+    detect_format(data)
+        .or_else(|| {
+            raw_header(data)?;
+            RawFormat::from_extension(name)
+        })
+        .map(RawFormat::mime_type)
+}
+
+/// Every directory of a RAW file in walk order: the IFD chain and, after
+/// each IFD, its SubIFDs.
+fn directories(data: &[u8], order: ByteOrder, offset: u32) -> Vec<(String, TiffDirectory<'_>)> {
+    const MAX_DIRECTORIES: usize = 32;
+    let mut out = Vec::new();
+    let mut visited = HashSet::new();
+    let mut sub_count = 0;
+    let mut next = offset;
+    let mut index = 0;
+
+    while next != 0 && out.len() < MAX_DIRECTORIES && visited.insert(next) {
+        let Some(directory) = container::tiff_directory(data, order, next) else {
+            break;
+        };
+        next = directory.next_offset;
+        let sub_offsets = directory
+            .get(SUB_IFDS)
+            .map(|e| e.unsigned(order))
+            .unwrap_or_default();
+        out.push((format!("IFD{}", index), directory));
+        index += 1;
+
+        for sub in sub_offsets {
+            if out.len() >= MAX_DIRECTORIES || !visited.insert(sub) {
+                continue;
+            }
+            if let Some(directory) = container::tiff_directory(data, order, sub) {
+                sub_count += 1;
+                out.push((format!("SubIFD{}", sub_count), directory));
+            }
+        }
+    }
+    out
+}
+
+fn walk(data: &[u8], format: RawFormat, order: ByteOrder, offset: u32) -> RawFile {
+    let mut images: Vec<RawImage> = directories(data, order, offset)
+        .iter()
+        .map(|(name, directory)| describe_image(data, format, order, name, directory))
+        .collect();
+
+    if format == RawFormat::Rw2
+        && let Some(ifd0) = container::tiff_directory(data, order, offset)
+        && let Some(entry) = ifd0.get(RW2_JPG_FROM_RAW)
+        && let Some(range) = displayable_jpeg(data, entry.value_offset, entry.value.len())
+    {
+        images.push(RawImage {
+            ifd: "JpgFromRaw".to_string(),
+            width: None,
+            height: None,
+            subfile_type: 1,
+            compression: Some(7),
+            photometric: Some(6),
+            bits_per_sample: Some(8),
+            jpeg: Some(range),
+        });
+    }
+
+    // JPEG images often leave the size to the JPEG frame header
+    for image in &mut images {
+        if let Some((offset, length)) = image.jpeg
+            && (image.width.is_none() || image.height.is_none())
+            && let Some(frame) = jpeg::analyze_jpeg(&data[offset..offset + length])
+                .and_then(|structure| structure.frame)
+        {
+            image.width = Some(frame.width.into());
+            image.height = Some(frame.height.into());
+        }
+    }
+
+    RawFile {
+        format,
+        byte_order: order,
+        images,
+    }
+}
+
+/// Parse the IFD structure of a RAW file.
+pub fn parse_raw(data: &[u8]) -> Option<RawFile> {
+    let format = detect_format(data)?;
+    let (order, offset) = raw_header(data)?;
+    Some(walk(data, format, order, offset))
+}
+
+/// Width and height of the full-resolution image.
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let file = parse_raw(data)?;
+    let image = file.main_image()?;
+    Some((image.width?, image.height?))
+}
+
+/// The largest embedded JPEG preview, for display in place of the sensor
+/// data.
+pub fn preview_jpeg(data: &[u8]) -> Option<&[u8]> {
+    let (offset, length) = parse_raw(data)?.preview()?.jpeg?;
+    data.get(offset..offset + length)
+}
+
+/// Name of a TIFF tag for `<IFD>:<Name>` keys.
+fn tag_name(tag: u16) -> String {
+    if let Some((_, name)) = DNG_TAGS
+        .iter()
+        .chain(RAW_TAG_NAMES)
+        .find(|(t, _)| *t == tag)
+    {
+        return name.to_string();
+    }
+    let known = Tag(Context::Tiff, tag);
+    match known.description() {
+        Some(_) => known.to_string(),
+        None => format!("Tag0x{:04X}", tag),
+    }
+}
+
+/// Convert a TIFF entry into a metadata value.
+fn entry_value(entry: &TiffEntry<'_>, order: ByteOrder) -> Option<MetadataValue> {
+    const MAX_VALUES: usize = 16;
+    let value = match entry.field_type {
+        2 => MetadataValue::text(entry.text()),
+        5 | 10 => {
+            let rationals = entry.rationals(order);
+            if rationals.is_empty() || rationals.len() > MAX_VALUES {
+                return None;
+            }
+            let display = rationals
+                .iter()
+                .map(|r| {
+                    let value = r.to_f64();
+                    if value.fract() == 0.0 {
+                        format!("{}", value)
+                    } else {
+                        format!("{:.4}", value)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            MetadataValue::new(RawValue::Rational(rationals), display)
+        }
+        1 | 3 | 4 | 7 => {
+            let numbers = entry.unsigned(order);
+            if numbers.is_empty() || numbers.len() > MAX_VALUES {
+                return None;
+            }
+            let display = numbers
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(" ");
+            MetadataValue::new(
+                RawValue::Integer(numbers.into_iter().map(i64::from).collect()),
+                display,
+            )
+        }
+        _ => return None,
+    };
+    (!value.display.is_empty()).then_some(value)
+}
+
+/// Convert the RAW structure into `exif_data` entries: `RAW:` summary keys,
+/// `DNG:` keys for DNG-specific tags and `<IFD>:<Tag>` keys for directories
+/// the EXIF reader does not visit (SubIFDs and IFDs after IFD1).
+pub fn raw_to_metadata(data: &[u8], file: &RawFile) -> HashMap<String, MetadataValue> {
+    let mut map = HashMap::new();
+    let order = file.byte_order;
+    let source = |ifd: &str, tag: u16| MetadataSource::Tiff {
+        ifd: ifd.to_string(),
+        tag,
+    };
+
+    map.insert(
+        "RAW:Format".to_string(),
+        MetadataValue::text(file.format.name()),
+    );
+    map.insert(
+        "RAW:ImageCount".to_string(),
+        MetadataValue::new(
+            RawValue::Integer(vec![file.images.len() as i64]),
+            file.images.len().to_string(),
+        ),
+    );
+    if let Some(image) = file.main_image() {
+        map.insert(
+            "RAW:RawImage".to_string(),
+            MetadataValue::text(image.describe()),
+        );
+    }
+    if let Some(preview) = file.preview()
+        && let Some((_, length)) = preview.jpeg
+    {
+        let size = match (preview.width, preview.height) {
+            (Some(w), Some(h)) => format!("{}x{} ", w, h),
+            _ => String::new(),
+        };
+        map.insert(
+            "RAW:Preview".to_string(),
+            MetadataValue::text(format!("{}JPEG, {} bytes ({})", size, length, preview.ifd)),
+        );
+    }
+
+    let Some((_, offset)) = raw_header(data) else {
+        return map;
+    };
+    for (name, directory) in directories(data, order, offset) {
+        let top_level = name == "IFD0" || name == "IFD1";
+        for entry in &directory.entries {
+            if LAYOUT_TAGS.contains(&entry.tag) {
+                continue;
+            }
+            let key = if name == "IFD0" && DNG_TAGS.iter().any(|(t, _)| *t == entry.tag) {
+                format!("DNG:{}", tag_name(entry.tag))
+            } else if top_level {
+                // IFD0 and IFD1 are read by the EXIF reader
+                continue;
+            } else {
+                format!("{}:{}", name, tag_name(entry.tag))
+            };
+            let Some(mut value) = entry_value(entry, order) else {
+                continue;
+            };
+            if matches!(entry.tag, DNG_VERSION | 0xC613) {
+                value.display = value.display.replace(' ', ".");
+            }
+            map.insert(key, value.with_source(source(&name, entry.tag)));
+        }
+    }
+    map
+}
+
+/// Extract the RAW file structure as `exif_data` entries.
+pub fn extract_raw_data(data: &[u8]) -> HashMap<String, MetadataValue> {
+    parse_raw(data)
+        .map(|file| raw_to_metadata(data, &file))
+        .unwrap_or_default()
+}
@@ -0,0 +1,240 @@
+//! SVG document metadata.
+//!
+//! SVG files are XML, so everything is read from the [`xml_tree`] of the
+//! document: the root element's size, the `<title>` and `<desc>` elements,
+//! the RDF block inside `<metadata>` (via the XMP parser) and the private
+//! namespaces that editors such as Inkscape and Illustrator leave behind.
+//! Those editor attributes often hold the document's file name and absolute
+//! export paths, which reveal the author's user name and folder layout.
+
+use crate::diagnostics::Diagnostic;
+use crate::types::{MetadataSource, MetadataValue};
+use crate::xml_tree::{self, XmlElement};
+use crate::xmp;
+use std::collections::HashMap;
+
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
+const INKSCAPE_NS: &str = "http://www.inkscape.org/namespaces/inkscape";
+const SODIPODI_NS: &str = "http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+const CC_NS: &str = "http://creativecommons.org/ns#";
+
+/// Namespaces declared by SVG editors, mapped to the editor's name.
+pub static EDITOR_NAMESPACES: &[(&str, &str)] = &[
+    (INKSCAPE_NS, "Inkscape"),
+    (SODIPODI_NS, "Sodipodi"),
+    (
+        "http://ns.adobe.com/AdobeIllustrator/10.0/",
+        "Adobe Illustrator",
+    ),
+    (
+        "http://ns.adobe.com/Extensibility/1.0/",
+        "Adobe Illustrator",
+    ),
+    ("http://ns.adobe.com/Graphs/1.0/", "Adobe Illustrator"),
+    ("http://ns.adobe.com/SaveForWeb/1.0/", "Adobe Illustrator"),
+    ("http://www.bohemiancoding.com/sketch/ns", "Sketch"),
+    ("http://www.figma.com/figma/ns", "Figma"),
+    ("http://www.serif.com/", "Affinity Designer"),
+    ("http://krita.org/namespaces/svg/krita", "Krita"),
+    ("http://www.vectornator.io", "Vectornator"),
+    ("http://www.boxy-svg.com/bx", "Boxy SVG"),
+];
+
+/// Editor attributes on the root element or `sodipodi:namedview`, mapped to
+/// their `SVG:` field names.
+static EDITOR_ATTRIBUTES: &[(&str, &str, &str)] = &[
+    (SODIPODI_NS, "docname", "DocumentName"),
+    (SODIPODI_NS, "docbase", "DocumentBase"),
+    (INKSCAPE_NS, "version", "InkscapeVersion"),
+    (INKSCAPE_NS, "export-filename", "ExportFilename"),
+    (INKSCAPE_NS, "export-xdpi", "ExportDPI"),
+    (INKSCAPE_NS, "current-layer", "CurrentLayer"),
+];
+
+/// Directories whose absolute paths point into a user's machine.
+const UNIX_PATH_PREFIXES: &[&str] = &[
+    "/home/",
+    "/Users/",
+    "/root/",
+    "/tmp/",
+    "/var/",
+    "/mnt/",
+    "/media/",
+    "/Volumes/",
+    "/private/",
+    "/opt/",
+];
+
+/// Returns `true` if the data is an XML document with an `<svg>` root.
+pub fn is_svg(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(4096)]);
+    let trimmed = head.trim_start_matches('\u{feff}').trim_start();
+    trimmed.starts_with('<') && head.contains("<svg")
+}
+
+/// Returns `true` if an attribute value is an absolute local file path or a
+/// `file:` URL.
+pub fn is_absolute_path(value: &str) -> bool {
+    let value = value.trim();
+    let bytes = value.as_bytes();
+    let windows_drive = bytes.len() > 2
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/');
+    value.starts_with("file:")
+        || value.starts_with("\\\\")
+        || windows_drive
+        || UNIX_PATH_PREFIXES.iter().any(|p| value.starts_with(p))
+}
+
+/// Text of the first child element of `root` in the SVG namespace with the
+/// given name.
+fn child_text(root: &XmlElement, name: &str) -> Option<String> {
+    root.elements()
+        .find(|e| e.name == name && matches!(e.namespace.as_deref(), Some(SVG_NS) | None))
+        .map(|e| e.text().trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+/// `Generator:` comments written by Illustrator and Sketch.
+fn generator_comment(text: &str) -> Option<String> {
+    let mut rest = text;
+    while let Some(start) = rest.find("<!--") {
+        let body = &rest[start + 4..];
+        let end = body.find("-->")?;
+        if let Some(generator) = body[..end].trim().strip_prefix("Generator:") {
+            return Some(generator.trim().to_string());
+        }
+        rest = &body[end..];
+    }
+    None
+}
+
+/// Extract SVG document fields as `exif_data` entries keyed `SVG:<Field>`.
+pub fn extract_svg_data(bytes: &[u8]) -> HashMap<String, MetadataValue> {
+    extract_svg_data_with_diagnostics(bytes, &mut Vec::new())
+}
+
+/// Extract the SVG fields as [`extract_svg_data`] does, reporting a document
+/// that is not well-formed XML.
+pub fn extract_svg_data_with_diagnostics(
+    bytes: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, MetadataValue> {
+    let mut map = HashMap::new();
+    if !is_svg(bytes) {
+        return map;
+    }
+    let text = String::from_utf8_lossy(bytes);
+    let root = match xml_tree::parse(text.trim_start_matches('\u{feff}')) {
+        Ok(root) => root,
+        Err(e) => {
+            diagnostics.push(Diagnostic::error("SVG", None, e));
+            return map;
+        }
+    };
+    if root.name != "svg" {
+        return map;
+    }
+    let mut insert = |name: &str, node: &str, value: String| {
+        map.entry(format!("SVG:{}", name)).or_insert_with(|| {
+            MetadataValue::text(value).with_source(MetadataSource::Svg {
+                node: node.to_string(),
+            })
+        });
+    };
+
+    for attribute in ["width", "height", "viewBox"] {
+        if let Some(value) = root.attr(None, attribute) {
+            let name = match attribute {
+                "viewBox" => "ViewBox",
+                "width" => "Width",
+                _ => "Height",
+            };
+            insert(name, attribute, value.to_string());
+        }
+    }
+    if let Some(title) = child_text(&root, "title") {
+        insert("Title", "title", title);
+    }
+    if let Some(description) = child_text(&root, "desc") {
+        insert("Description", "desc", description);
+    }
+    if let Some(generator) = generator_comment(&text) {
+        insert("Generator", "comment", generator);
+    }
+
+    // RDF in <metadata>, as written by Inkscape
+    let properties = xmp::parse_xmp(&text).unwrap_or_default();
+    let rdf_values = |namespace: &str, path: &str| {
+        let values: Vec<&str> = properties
+            .iter()
+            .filter(|p| {
+                p.namespace == namespace
+                    && p.path.starts_with(path)
+                    && matches!(p.path.as_bytes().get(path.len()), None | Some(b'/' | b'['))
+                    && !p.value.is_empty()
+            })
+            .map(|p| p.value.as_str())
+            .collect();
+        (!values.is_empty()).then(|| values.join("; "))
+    };
+    if let Some(title) = rdf_values(DC_NS, "title") {
+        insert("Title", "metadata", title);
+    }
+    if let Some(creator) = rdf_values(DC_NS, "creator") {
+        insert("Creator", "metadata", creator);
+    }
+    if let Some(license) = rdf_values(CC_NS, "license").or_else(|| rdf_values(DC_NS, "rights")) {
+        insert("License", "metadata", license);
+    }
+
+    let elements = root.descendants();
+    let mut editors: Vec<&str> = Vec::new();
+    for element in &elements {
+        for (_, uri) in &element.namespace_declarations {
+            if let Some((_, editor)) = EDITOR_NAMESPACES.iter().find(|(ns, _)| ns == uri)
+                && !editors.contains(editor)
+            {
+                editors.push(editor);
+            }
+        }
+    }
+    if !editors.is_empty() {
+        insert("Editors", "xmlns", editors.join(", "));
+    }
+
+    let named_view = elements.iter().find(|e| e.is(SODIPODI_NS, "namedview"));
+    for (namespace, attribute, name) in EDITOR_ATTRIBUTES {
+        let value = std::iter::once(&root)
+            .chain(named_view.copied())
+            .find_map(|e| e.attr(Some(namespace), attribute));
+        if let Some(value) = value {
+            let prefix = if *namespace == SODIPODI_NS {
+                "sodipodi"
+            } else {
+                "inkscape"
+            };
+            insert(
+                name,
+                &format!("{}:{}", prefix, attribute),
+                value.to_string(),
+            );
+        }
+    }
+
+    let mut paths: Vec<&str> = Vec::new();
+    for element in &elements {
+        for attribute in &element.attributes {
+            if is_absolute_path(&attribute.value) && !paths.contains(&attribute.value.as_str()) {
+                paths.push(&attribute.value);
+            }
+        }
+    }
+    if !paths.is_empty() {
+        insert("AbsolutePaths", "attributes", paths.join("; "));
+    }
+
+    map
+}
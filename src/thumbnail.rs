@@ -0,0 +1,105 @@
+//! Embedded EXIF thumbnails and how far they drift from the main image.
+//!
+//! The two images are compared with a difference hash: each is shrunk to a
+//! 9×8 grayscale grid and every bit records whether a cell is brighter than
+//! its right-hand neighbour. The hash survives the resampling and heavy
+//! recompression a thumbnail goes through, but changes once content has been
+//! cropped away or painted over.
+
+use crate::exif_core;
+use crate::types::EmbeddedThumbnail;
+use base64::Engine as _;
+use image::{DynamicImage, GenericImageView, ImageFormat};
+
+/// Hash distance above which the thumbnail is considered a different picture.
+pub const DIFFERENCE_THRESHOLD: u32 = 12;
+
+/// Relative aspect ratio difference above which the thumbnail is considered
+/// to show a different framing (e.g. before a crop).
+const ASPECT_TOLERANCE: f64 = 0.1;
+
+/// Luma level below which a border row or column counts as letterboxing.
+const BORDER_LUMA: u8 = 24;
+
+/// 64-bit difference hash of an image.
+pub fn difference_hash(image: &DynamicImage) -> u64 {
+    let grid = image.thumbnail_exact(9, 8).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if grid.get_pixel(x, y)[0] > grid.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+/// Number of differing bits between two hashes.
+pub fn hash_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Remove the black bars cameras add when the thumbnail's fixed size does not
+/// match the sensor's aspect ratio.
+pub fn trim_borders(image: &DynamicImage) -> DynamicImage {
+    let luma = image.to_luma8();
+    let (width, height) = luma.dimensions();
+    let row_has_content = |y: u32| (0..width).any(|x| luma.get_pixel(x, y)[0] > BORDER_LUMA);
+    let column_has_content = |x: u32| (0..height).any(|y| luma.get_pixel(x, y)[0] > BORDER_LUMA);
+
+    let (Some(top), Some(bottom)) = (
+        (0..height).find(|&y| row_has_content(y)),
+        (0..height).rev().find(|&y| row_has_content(y)),
+    ) else {
+        return image.clone();
+    };
+    let (Some(left), Some(right)) = (
+        (0..width).find(|&x| column_has_content(x)),
+        (0..width).rev().find(|&x| column_has_content(x)),
+    ) else {
+        return image.clone();
+    };
+    image.crop_imm(left, top, right - left + 1, bottom - top + 1)
+}
+
+fn aspect_ratio(image: &DynamicImage) -> f64 {
+    let (width, height) = image.dimensions();
+    width as f64 / height.max(1) as f64
+}
+
+/// Compare a thumbnail with the main image, returning the hash distance and
+/// whether the two differ significantly in content or framing.
+pub fn compare(main: &DynamicImage, thumbnail: &DynamicImage) -> (u32, bool) {
+    let thumbnail = trim_borders(thumbnail);
+    let distance = hash_distance(difference_hash(main), difference_hash(&thumbnail));
+    let main_aspect = aspect_ratio(main);
+    let reframed = (aspect_ratio(&thumbnail) - main_aspect).abs() / main_aspect > ASPECT_TOLERANCE;
+    (distance, distance > DIFFERENCE_THRESHOLD || reframed)
+}
+
+/// Extract the EXIF thumbnail of a file and compare it with the main image.
+///
+/// Files the `image` crate cannot decode (HEIF, JPEG XL, ...) still get a
+/// thumbnail, just without a difference score.
+pub fn analyze_thumbnail(bytes: &[u8]) -> Option<EmbeddedThumbnail> {
+    let jpeg = exif_core::extract_thumbnail(bytes)?;
+    let decoded = image::load_from_memory_with_format(&jpeg, ImageFormat::Jpeg).ok();
+    let comparison = decoded
+        .as_ref()
+        .zip(image::load_from_memory(bytes).ok())
+        .map(|(thumbnail, main)| compare(&main, thumbnail));
+
+    Some(EmbeddedThumbnail {
+        data_url: format!(
+            "data:image/jpeg;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(&jpeg)
+        ),
+        size: jpeg.len() as u64,
+        width: decoded.as_ref().map(|d| d.width()),
+        height: decoded.as_ref().map(|d| d.height()),
+        difference: comparison.map(|(distance, _)| distance),
+        differs: comparison.is_some_and(|(_, differs)| differs),
+    })
+}
@@ -0,0 +1,396 @@
+//! Capture timestamps normalised into offset-aware instants.
+//!
+//! EXIF spreads a single point in time over up to three fields: the
+//! `DateTime*` value itself (local wall-clock time, second precision), an
+//! optional `OffsetTime*` with its UTC offset and an optional `SubSecTime*`
+//! with the fractional seconds. The GPS block carries its own UTC time.
+//! [`extract_timestamps`] assembles these into [`Timestamp`]s that compare
+//! by instant and print as ISO 8601, so two values written in different
+//! formats are no longer mistaken for different times.
+
+use crate::types::{GpsInfo, MetadataValue};
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Which moment in a file's life a timestamp describes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimestampKind {
+    /// `DateTime`: when the file was last written.
+    Modified,
+    /// `DateTimeOriginal`: when the picture was taken.
+    Original,
+    /// `DateTimeDigitized`: when the picture was stored digitally.
+    Digitized,
+    /// `GPSDateStamp` + `GPSTimeStamp`: the satellite fix time, in UTC.
+    Gps,
+}
+
+impl TimestampKind {
+    /// Short human readable name.
+    pub fn label(self) -> &'static str {
+        match self {
+            TimestampKind::Modified => "Modified",
+            TimestampKind::Original => "Original",
+            TimestampKind::Digitized => "Digitized",
+            TimestampKind::Gps => "GPS",
+        }
+    }
+}
+
+/// The EXIF fields making up each capture timestamp: date/time, offset and
+/// sub-second keys.
+pub static EXIF_TIMESTAMP_FIELDS: &[(TimestampKind, &str, &str, &str)] = &[
+    (
+        TimestampKind::Original,
+        "DateTimeOriginal",
+        "OffsetTimeOriginal",
+        "SubSecTimeOriginal",
+    ),
+    (
+        TimestampKind::Digitized,
+        "DateTimeDigitized",
+        "OffsetTimeDigitized",
+        "SubSecTimeDigitized",
+    ),
+    (
+        TimestampKind::Modified,
+        "DateTime",
+        "OffsetTime",
+        "SubSecTime",
+    ),
+];
+
+/// Movie fields used for a timestamp kind when the EXIF one is missing:
+/// the recorded creation date written by phones, and the `mvhd` creation
+/// and modification times.
+pub static VIDEO_TIMESTAMP_FIELDS: &[(TimestampKind, &str)] = &[
+    (TimestampKind::Original, "QuickTime:CreationDate"),
+    (TimestampKind::Digitized, "QuickTime:CreateDate"),
+    (TimestampKind::Modified, "QuickTime:ModifyDate"),
+];
+
+/// A calendar date and time read from metadata.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Timestamp {
+    pub kind: TimestampKind,
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// Fractional seconds, if the file recorded them.
+    pub nanosecond: Option<u32>,
+    /// Offset from UTC in minutes; `None` for a local time of unknown zone.
+    pub offset_minutes: Option<i32>,
+    /// `exif_data` keys the value was assembled from.
+    pub sources: Vec<String>,
+}
+
+impl Timestamp {
+    /// A UTC timestamp from seconds since the Unix epoch.
+    pub fn from_unix(kind: TimestampKind, seconds: i64) -> Self {
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let time = seconds.rem_euclid(86_400) as u32;
+        Self {
+            kind,
+            year,
+            month,
+            day,
+            hour: time / 3_600,
+            minute: time / 60 % 60,
+            second: time % 60,
+            nanosecond: None,
+            offset_minutes: Some(0),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Seconds since 1970-01-01 of the wall-clock time, ignoring the offset.
+    pub fn local_seconds(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * 86_400
+            + i64::from(self.hour) * 3_600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+
+    /// Seconds since the Unix epoch, if the UTC offset is known.
+    pub fn utc_seconds(&self) -> Option<i64> {
+        self.offset_minutes
+            .map(|offset| self.local_seconds() - i64::from(offset) * 60)
+    }
+
+    /// Whether two timestamps denote the same instant.
+    ///
+    /// UTC instants are compared when both offsets are known; otherwise both
+    /// are taken to be in the same zone and compared by wall-clock time.
+    /// Fractional seconds only count when both sides recorded them.
+    pub fn same_instant(&self, other: &Timestamp) -> bool {
+        let seconds = match (self.utc_seconds(), other.utc_seconds()) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.local_seconds() == other.local_seconds(),
+        };
+        seconds
+            && match (self.nanosecond, other.nanosecond) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+    }
+
+    /// ISO 8601 representation, e.g. `2024-03-15T14:05:30.25+09:00`.
+    ///
+    /// Local times without a recorded offset are written without a zone
+    /// designator.
+    pub fn to_iso8601(&self) -> String {
+        let mut out = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        );
+        if let Some(nanos) = self.nanosecond.filter(|n| *n > 0) {
+            let fraction = format!("{:09}", nanos);
+            out.push('.');
+            out.push_str(fraction.trim_end_matches('0'));
+        }
+        match self.offset_minutes {
+            Some(0) => out.push('Z'),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                out.push_str(&format!("{}{:02}:{:02}", sign, offset / 60, offset % 60));
+            }
+            None => {}
+        }
+        out
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_iso8601())
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("kind", self.kind.label())?;
+        map.serialize_entry("iso8601", &self.to_iso8601())?;
+        if let Some(seconds) = self.utc_seconds() {
+            map.serialize_entry("unix", &seconds)?;
+        }
+        map.serialize_entry("sources", &self.sources)?;
+        map.end()
+    }
+}
+
+/// Days from 1970-01-01 to the given proleptic Gregorian date.
+pub(crate) fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Proleptic Gregorian date of the given number of days from 1970-01-01.
+fn civil_from_days(days: i64) -> (i32, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year as i32, month, day)
+}
+
+/// Parse a UTC offset such as `+09:00`, `-0530` or `Z` into minutes.
+pub fn parse_offset(text: &str) -> Option<i32> {
+    let text = text.trim().trim_end_matches('\0');
+    if text == "Z" {
+        return Some(0);
+    }
+    let (sign, rest) = match text.as_bytes().first()? {
+        b'+' => (1, &text[1..]),
+        b'-' => (-1, &text[1..]),
+        _ => return None,
+    };
+    let digits: String = rest.chars().filter(|c| *c != ':').collect();
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    (hours <= 14 && minutes < 60).then_some(sign * (hours * 60 + minutes))
+}
+
+/// Parse `SubSecTime*` digits (`"25"` = 0.25 s) into nanoseconds.
+pub fn parse_subsec(text: &str) -> Option<u32> {
+    let digits = text.trim().trim_end_matches('\0').trim();
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let mut padded: String = digits.chars().take(9).collect();
+    while padded.len() < 9 {
+        padded.push('0');
+    }
+    padded.parse().ok()
+}
+
+/// Parse an EXIF (`YYYY:MM:DD HH:MM:SS`) or ISO 8601 date and time, with
+/// optional fractional seconds and UTC offset.
+///
+/// Blank and all-zero EXIF placeholders are rejected.
+pub fn parse_datetime(kind: TimestampKind, text: &str) -> Option<Timestamp> {
+    let text = text.trim().trim_end_matches('\0').trim();
+    let number = |range: std::ops::Range<usize>| -> Option<u32> {
+        let part = text.get(range)?;
+        if part.bytes().all(|b| b.is_ascii_digit()) {
+            part.parse().ok()
+        } else {
+            None
+        }
+    };
+    let separators_ok = matches!(text.as_bytes().get(4), Some(b':' | b'-'))
+        && matches!(text.as_bytes().get(7), Some(b':' | b'-'))
+        && matches!(text.as_bytes().get(10), Some(b' ' | b'T'))
+        && text.as_bytes().get(13) == Some(&b':')
+        && text.as_bytes().get(16) == Some(&b':');
+    if !separators_ok {
+        return None;
+    }
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let mut rest = &text[19..];
+    let mut nanosecond = None;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let end = fraction
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(fraction.len());
+        nanosecond = parse_subsec(&fraction[..end]);
+        rest = &fraction[end..];
+    }
+    let rest = rest.trim();
+    let offset_minutes = if rest.is_empty() {
+        None
+    } else {
+        Some(parse_offset(rest)?)
+    };
+
+    Some(Timestamp {
+        kind,
+        year: year as i32,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+        nanosecond,
+        offset_minutes,
+        sources: Vec::new(),
+    })
+}
+
+/// The GPS fix time from `GPSDateStamp` (`YYYY:MM:DD`) and the hours,
+/// minutes and seconds of `GPSTimeStamp`, which are always UTC. Seconds keep
+/// millisecond precision.
+///
+/// Returns `None` unless the date is a real calendar day and the time of day
+/// is in range.
+pub fn gps_datetime(date: &str, [hours, minutes, seconds]: [f64; 3]) -> Option<Timestamp> {
+    let whole = |value: f64, end: f64| {
+        (value.fract() == 0.0 && (0.0..end).contains(&value)).then_some(value as u32)
+    };
+    let (hour, minute) = (whole(hours, 24.0)?, whole(minutes, 60.0)?);
+    if !(0.0..61.0).contains(&seconds) {
+        return None;
+    }
+    let millis = (seconds * 1000.0).round() as u32;
+    let mut timestamp = parse_datetime(
+        TimestampKind::Gps,
+        &format!("{} {:02}:{:02}:{:02}", date, hour, minute, millis / 1000),
+    )?;
+    // parse_datetime lets any day up to the 31st through
+    let next_month = match timestamp.month {
+        12 => days_from_civil(timestamp.year + 1, 1, 1),
+        month => days_from_civil(timestamp.year, month + 1, 1),
+    };
+    if i64::from(timestamp.day) > next_month - days_from_civil(timestamp.year, timestamp.month, 1) {
+        return None;
+    }
+    timestamp.nanosecond = Some(millis % 1000 * 1_000_000);
+    timestamp.offset_minutes = Some(0);
+    timestamp.sources = vec!["GPSDateStamp".to_string(), "GPSTimeStamp".to_string()];
+    Some(timestamp)
+}
+
+fn text_of(value: &MetadataValue) -> &str {
+    value.as_text().unwrap_or(&value.display)
+}
+
+/// Assemble one timestamp from its date/time, offset and sub-second fields.
+///
+/// An offset or fraction embedded in the date/time text itself (as XMP and
+/// hand-edited files have) wins over the separate fields.
+pub fn read_timestamp(
+    exif_data: &HashMap<String, MetadataValue>,
+    kind: TimestampKind,
+    datetime_key: &str,
+    offset_key: Option<&str>,
+    subsec_key: Option<&str>,
+) -> Option<Timestamp> {
+    let mut timestamp = parse_datetime(kind, text_of(exif_data.get(datetime_key)?))?;
+    timestamp.sources.push(datetime_key.to_string());
+
+    if timestamp.offset_minutes.is_none()
+        && let Some(key) = offset_key
+        && let Some(offset) = exif_data.get(key).and_then(|v| parse_offset(text_of(v)))
+    {
+        timestamp.offset_minutes = Some(offset);
+        timestamp.sources.push(key.to_string());
+    }
+    if timestamp.nanosecond.is_none()
+        && let Some(key) = subsec_key
+        && let Some(nanos) = exif_data.get(key).and_then(|v| parse_subsec(text_of(v)))
+    {
+        timestamp.nanosecond = Some(nanos);
+        timestamp.sources.push(key.to_string());
+    }
+    Some(timestamp)
+}
+
+/// Every capture timestamp in a file: the EXIF original, digitized and
+/// modified times (or their video equivalents) followed by the GPS fix time.
+pub fn extract_timestamps(
+    exif_data: &HashMap<String, MetadataValue>,
+    gps: Option<&GpsInfo>,
+) -> Vec<Timestamp> {
+    let mut timestamps: Vec<Timestamp> = EXIF_TIMESTAMP_FIELDS
+        .iter()
+        .filter_map(|(kind, datetime, offset, subsec)| {
+            read_timestamp(exif_data, *kind, datetime, Some(offset), Some(subsec)).or_else(|| {
+                let (_, key) = VIDEO_TIMESTAMP_FIELDS.iter().find(|(k, _)| k == kind)?;
+                read_timestamp(exif_data, *kind, key, None, None)
+            })
+        })
+        .collect();
+
+    if let Some(timestamp) = gps.and_then(|g| g.timestamp.clone()) {
+        timestamps.push(timestamp);
+    }
+    timestamps
+}
@@ -1,7 +1,15 @@
 //! Shared data structures used throughout the application.
 
-use serde::Serialize;
+use crate::animation::Animation;
+use crate::c2pa::ContentCredentials;
+use crate::diagnostics::Diagnostic;
+use crate::file_type::TypeCheck;
+use crate::jpeg::JpegStructure;
+use crate::timestamp::{self, Timestamp, TimestampKind};
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Theme {
@@ -28,8 +36,502 @@ pub struct PrivacyRisk {
     pub consistency_issues: Vec<String>,
 }
 
-/// Metadata extracted from an uploaded file.
+/// Signed rational number as stored in TIFF/EXIF `RATIONAL`/`SRATIONAL` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational {
+    pub num: i64,
+    pub denom: i64,
+}
+
+impl Rational {
+    /// Convert to a floating point value. A zero denominator yields `NaN`.
+    pub fn to_f64(&self) -> f64 {
+        if self.denom == 0 {
+            f64::NAN
+        } else {
+            self.num as f64 / self.denom as f64
+        }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.num, self.denom)
+    }
+}
+
+/// Typed payload of a metadata field before it is formatted for display.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RawValue {
+    Text(String),
+    Integer(Vec<i64>),
+    Rational(Vec<Rational>),
+    Float(Vec<f64>),
+    Bytes(Vec<u8>),
+}
+
+/// TIFF/EXIF image file directory a field was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ifd {
+    /// IFD0, describing the main image
+    Primary,
+    /// IFD1 and its sub-IFDs, describing the embedded thumbnail
+    Thumbnail,
+    /// EXIF sub-IFD of the main image
+    Exif,
+    /// GPS sub-IFD of the main image
+    Gps,
+    /// Interoperability sub-IFD of the main image
+    Interop,
+}
+
+impl Ifd {
+    /// All IFDs in file order, useful for grouping output deterministically.
+    pub const ALL: [Ifd; 5] = [
+        Ifd::Primary,
+        Ifd::Exif,
+        Ifd::Gps,
+        Ifd::Interop,
+        Ifd::Thumbnail,
+    ];
+
+    /// Short identifier used in machine-readable exports.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Ifd::Primary => "primary",
+            Ifd::Thumbnail => "thumbnail",
+            Ifd::Exif => "exif",
+            Ifd::Gps => "gps",
+            Ifd::Interop => "interop",
+        }
+    }
+
+    /// Human readable name used in reports and the UI.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Ifd::Primary => "Primary image (IFD0)",
+            Ifd::Thumbnail => "Thumbnail (IFD1)",
+            Ifd::Exif => "EXIF",
+            Ifd::Gps => "GPS",
+            Ifd::Interop => "Interoperability",
+        }
+    }
+}
+
+/// Where in the file a metadata value was found.
+///
+/// Serializes as an object whose `kind` names the variant, e.g.
+/// `{"kind":"exif","ifd":"primary","tag":306}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum MetadataSource {
+    /// A TIFF/EXIF field identified by its IFD and tag number.
+    Exif { ifd: Ifd, tag: u16 },
+    /// An XMP property identified by its namespace URI.
+    Xmp { namespace: String },
+    /// An IPTC-IIM dataset identified by its record and dataset numbers.
+    Iptc { record: u8, dataset: u8 },
+    /// An ICC profile header field (`head`) or tag signature such as `desc`.
+    Icc { tag: String },
+    /// A decoded vendor MakerNote field identified by vendor and tag number.
+    MakerNote { vendor: &'static str, tag: u16 },
+    /// A PNG chunk identified by its type, such as `tEXt` or `tIME`.
+    Png { chunk: String },
+    /// An ISO base media (HEIF, AVIF, MP4) box identified by its type.
+    Bmff { box_type: String },
+    /// A PDF object identified by its object number; `0` stands for the
+    /// file header and trailer.
+    Pdf { object: u32 },
+    /// An SVG element or attribute, such as `title` or `sodipodi:docname`.
+    Svg { node: String },
+    /// A TIFF field outside the EXIF reader's IFDs, such as a RAW file's
+    /// `SubIFD1`, identified by directory name and tag number.
+    Tiff { ifd: String, tag: u16 },
+}
+
+impl MetadataSource {
+    /// The EXIF IFD this value came from, if it is an EXIF field.
+    pub fn ifd(&self) -> Option<Ifd> {
+        match self {
+            MetadataSource::Exif { ifd, .. } => Some(*ifd),
+            MetadataSource::Xmp { .. }
+            | MetadataSource::Iptc { .. }
+            | MetadataSource::Icc { .. }
+            | MetadataSource::MakerNote { .. }
+            | MetadataSource::Png { .. }
+            | MetadataSource::Bmff { .. }
+            | MetadataSource::Pdf { .. }
+            | MetadataSource::Svg { .. }
+            | MetadataSource::Tiff { .. } => None,
+        }
+    }
+}
+
+/// A single metadata value: the typed raw value, its unit and the display string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataValue {
+    pub raw: RawValue,
+    pub unit: Option<String>,
+    pub display: String,
+    pub source: Option<MetadataSource>,
+}
+
+impl MetadataValue {
+    /// Create a value from its raw form and a pre-formatted display string.
+    pub fn new(raw: RawValue, display: impl Into<String>) -> Self {
+        Self {
+            raw,
+            unit: None,
+            display: display.into(),
+            source: None,
+        }
+    }
+
+    /// Create a plain text value whose display string is the text itself.
+    pub fn text(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self::new(RawValue::Text(text.clone()), text)
+    }
+
+    /// Attach a unit (e.g. `"mm"`, `"f/"`) to the value.
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Record where in the file the value was read from.
+    pub fn with_source(mut self, source: MetadataSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// The EXIF IFD this value came from, if known.
+    pub fn ifd(&self) -> Option<Ifd> {
+        self.source.as_ref().and_then(MetadataSource::ifd)
+    }
+
+    /// Return all numeric components as `f64`.
+    ///
+    /// Text values are parsed when they hold a single number so that values
+    /// from textual sources can still be compared numerically.
+    pub fn as_f64_vec(&self) -> Option<Vec<f64>> {
+        match &self.raw {
+            RawValue::Integer(v) if !v.is_empty() => Some(v.iter().map(|i| *i as f64).collect()),
+            RawValue::Rational(v) if !v.is_empty() => {
+                Some(v.iter().map(Rational::to_f64).collect())
+            }
+            RawValue::Float(v) if !v.is_empty() => Some(v.clone()),
+            RawValue::Text(t) => t.trim().parse::<f64>().ok().map(|n| vec![n]),
+            _ => None,
+        }
+    }
+
+    /// Return the first numeric component as `f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_f64_vec().and_then(|v| v.first().copied())
+    }
+
+    /// Return the first component as an integer when the value is integral.
+    pub fn as_i64(&self) -> Option<i64> {
+        match &self.raw {
+            RawValue::Integer(v) => v.first().copied(),
+            _ => self
+                .as_f64()
+                .filter(|n| n.fract() == 0.0 && n.is_finite())
+                .map(|n| n as i64),
+        }
+    }
+
+    /// Return the text content for textual values.
+    pub fn as_text(&self) -> Option<&str> {
+        match &self.raw {
+            RawValue::Text(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the raw value is an integer, rational or float.
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self.raw,
+            RawValue::Integer(_) | RawValue::Rational(_) | RawValue::Float(_)
+        )
+    }
+
+    /// Compare two values numerically, falling back to their display strings
+    /// when either side is not a number.
+    pub fn same_value(&self, other: &MetadataValue) -> bool {
+        match (self.as_f64_vec(), other.as_f64_vec()) {
+            (Some(a), Some(b)) => {
+                a.len() == b.len() && a.iter().zip(&b).all(|(x, y)| (x - y).abs() < 1e-9)
+            }
+            _ => self.display == other.display,
+        }
+    }
+}
+
+impl fmt::Display for MetadataValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.display)
+    }
+}
+
+impl From<String> for MetadataValue {
+    fn from(text: String) -> Self {
+        Self::text(text)
+    }
+}
+
+impl From<&str> for MetadataValue {
+    fn from(text: &str) -> Self {
+        Self::text(text)
+    }
+}
+
+impl PartialEq<str> for MetadataValue {
+    fn eq(&self, other: &str) -> bool {
+        self.display == other
+    }
+}
+
+impl PartialEq<&str> for MetadataValue {
+    fn eq(&self, other: &&str) -> bool {
+        self.display == *other
+    }
+}
+
+/// Text and byte values serialize as their display string so existing exports
+/// keep their shape, or as `value` and `source` when their origin is known;
+/// numeric values serialize as real numbers together with their unit, display
+/// string and source.
+impl Serialize for MetadataValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let numbers = match &self.raw {
+            RawValue::Text(_) | RawValue::Bytes(_) => {
+                let Some(source) = &self.source else {
+                    return serializer.serialize_str(&self.display);
+                };
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("value", &self.display)?;
+                map.serialize_entry("source", source)?;
+                return map.end();
+            }
+            RawValue::Integer(v) => v.iter().map(|i| serde_json::Value::from(*i)).collect(),
+            RawValue::Rational(v) => v
+                .iter()
+                .map(|r| serde_json::Value::from(r.to_f64()))
+                .collect::<Vec<_>>(),
+            RawValue::Float(v) => v.iter().map(|f| serde_json::Value::from(*f)).collect(),
+        };
+        let value = match <[serde_json::Value; 1]>::try_from(numbers) {
+            Ok([single]) => single,
+            Err(many) => serde_json::Value::Array(many),
+        };
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("value", &value)?;
+        if let Some(unit) = &self.unit {
+            map.serialize_entry("unit", unit)?;
+        }
+        map.serialize_entry("display", &self.display)?;
+        if let Some(source) = &self.source {
+            map.serialize_entry("source", source)?;
+        }
+        map.end()
+    }
+}
+
+fn serialize_iso8601<S: Serializer>(
+    timestamp: &Option<Timestamp>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    timestamp
+        .as_ref()
+        .map(Timestamp::to_iso8601)
+        .serialize(serializer)
+}
+
+/// Location and movement recorded in the EXIF GPS IFD.
+///
+/// Coordinates and altitude are signed (south, west and below sea level are
+/// negative) with the reference tags already applied.
+#[derive(Clone, PartialEq, Serialize, Debug, Default)]
+pub struct GpsInfo {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Metres above sea level.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub altitude: Option<f64>,
+    /// Direction the camera was pointing, in degrees.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub img_direction: Option<f64>,
+    /// `"True North"` or `"Magnetic North"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub img_direction_ref: Option<String>,
+    /// Receiver speed, converted to km/h.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed_kmh: Option<f64>,
+    /// UTC fix time from `GPSDateStamp` and `GPSTimeStamp`, written out as
+    /// ISO 8601 (`YYYY-MM-DDTHH:MM:SSZ`).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_iso8601"
+    )]
+    pub timestamp: Option<Timestamp>,
+    /// Horizontal positioning error in metres.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub horizontal_error: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub map_datum: Option<String>,
+    /// How the position was obtained (`GPS`, `CELLID`, `WLAN`, `MANUAL`, ...).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processing_method: Option<String>,
+}
+
+impl GpsInfo {
+    /// A position with no further GPS details.
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self {
+            latitude,
+            longitude,
+            ..Self::default()
+        }
+    }
+
+    /// `(latitude, longitude)` in decimal degrees.
+    pub fn coords(&self) -> (f64, f64) {
+        (self.latitude, self.longitude)
+    }
+
+    /// Eight-point compass name of the image direction, e.g. `"NE"`.
+    pub fn compass_point(&self) -> Option<&'static str> {
+        const POINTS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+        let degrees = self.img_direction?.rem_euclid(360.0);
+        Some(POINTS[((degrees + 22.5) / 45.0) as usize % 8])
+    }
+
+    /// Human readable image direction, e.g. `"135.0° SE (True North)"`.
+    pub fn heading_display(&self) -> Option<String> {
+        let degrees = self.img_direction?;
+        let mut out = format!(
+            "{:.1}° {}",
+            degrees,
+            self.compass_point().unwrap_or_default()
+        );
+        if let Some(reference) = &self.img_direction_ref {
+            out.push_str(&format!(" ({})", reference));
+        }
+        Some(out)
+    }
+
+    /// Human readable altitude, e.g. `"12.5 m below sea level"`.
+    pub fn altitude_display(&self) -> Option<String> {
+        self.altitude.map(|altitude| {
+            if altitude < 0.0 {
+                format!("{:.1} m below sea level", -altitude)
+            } else {
+                format!("{:.1} m above sea level", altitude)
+            }
+        })
+    }
+
+    /// Labelled GPS details beyond the coordinates, in display form.
+    pub fn details(&self) -> Vec<(&'static str, String)> {
+        let mut details = Vec::new();
+        if let Some(altitude) = self.altitude_display() {
+            details.push(("Altitude", altitude));
+        }
+        if let Some(heading) = self.heading_display() {
+            details.push(("Direction", heading));
+        }
+        if let Some(speed) = self.speed_kmh {
+            details.push(("Speed", format!("{:.1} km/h", speed)));
+        }
+        if let Some(timestamp) = &self.timestamp {
+            details.push(("Time (UTC)", timestamp.to_iso8601()));
+        }
+        if let Some(error) = self.horizontal_error {
+            details.push(("Accuracy", format!("±{} m", error)));
+        }
+        if let Some(datum) = &self.map_datum {
+            details.push(("Map Datum", datum.clone()));
+        }
+        if let Some(method) = &self.processing_method {
+            details.push(("Processing Method", method.clone()));
+        }
+        details
+    }
+}
+
+/// JPEG thumbnail embedded in the EXIF thumbnail IFD (IFD1).
+///
+/// Editors often rewrite the main image without regenerating this copy, so
+/// it can still show the original, uncropped or unredacted picture.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct EmbeddedThumbnail {
+    #[serde(skip)] // Base64 data URL of the JPEG, for preview only
+    pub data_url: String,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// Perceptual hash distance to the main image (0-64 differing bits), if
+    /// both images could be decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difference: Option<u32>,
+    /// Whether the thumbnail's content or framing differs significantly
+    /// from the main image.
+    pub differs: bool,
+}
+
+/// A video stored inside a still image, as Google and Samsung motion photos
+/// do.
+#[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct EmbeddedVideo {
+    /// `"Google Motion Photo"`, `"Samsung Motion Photo"` or
+    /// `"Appended video"` when no vendor marker describes it.
+    pub kind: String,
+    /// Byte range of the video within the file.
+    pub offset: u64,
+    pub size: u64,
+    pub mime_type: String,
+    /// Length of the clip in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    /// Whether the clip has a sound track.
+    pub has_audio: bool,
+    /// Whether the clip records its own location.
+    pub has_location: bool,
+    /// Position of the still within the clip, in microseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_timestamp_us: Option<i64>,
+}
+
+/// An image listed in the MP index of a Multi-Picture Format (MPO) file.
 #[derive(Clone, PartialEq, Serialize, Debug)]
+pub struct MpfImage {
+    /// 1-based position in the MP index; 1 is the primary image.
+    pub index: u32,
+    /// MP image type, e.g. `"Multi-Frame Disparity"` for a stereo view.
+    pub image_type: String,
+    /// Byte range of the image within the file.
+    pub offset: u64,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// The image's own metadata; empty for the primary image.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub exif_data: HashMap<String, MetadataValue>,
+    #[serde(skip)] // Base64 data URL of the JPEG, for preview only
+    pub data_url: String,
+}
+
+/// Metadata extracted from an uploaded file.
+#[derive(Clone, Default, PartialEq, Serialize, Debug)]
 pub struct ImageData {
     #[serde(skip_serializing_if = "String::is_empty")]
     pub name: String,
@@ -44,11 +546,27 @@ pub struct ImageData {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub height: Option<u32>,
     #[serde(skip_serializing_if = "HashMap::is_empty")]
-    pub exif_data: HashMap<String, String>,
+    pub exif_data: HashMap<String, MetadataValue>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub gps_coords: Option<(f64, f64)>, // (latitude, longitude)
+    pub gps: Option<GpsInfo>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sha256_hash: Option<String>, // SHA-256 file hash for forensics and deduplication
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<EmbeddedThumbnail>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jpeg: Option<JpegStructure>, // Marker layout and encoder settings of JPEG files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedded_video: Option<EmbeddedVideo>, // Motion photo clip stored in the file
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mpf_images: Vec<MpfImage>, // Images listed in a Multi-Picture Format index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub animation: Option<Animation>, // Frames of an animated GIF, APNG or WebP
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_check: Option<TypeCheck>, // Declared versus detected file type
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>, // Problems the parsers found in the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_credentials: Option<ContentCredentials>, // C2PA manifests and their verification
 }
 
 fn is_zero(value: &u64) -> bool {
@@ -65,6 +583,20 @@ impl ImageData {
         }
     }
 
+    /// Capture timestamps normalised to offset-aware instants.
+    pub fn timestamps(&self) -> Vec<Timestamp> {
+        timestamp::extract_timestamps(&self.exif_data, self.gps.as_ref())
+    }
+
+    /// Look up an EXIF field by the IFD and tag number it was read from.
+    pub fn exif_field(&self, ifd: Ifd, tag: u16) -> Option<(&String, &MetadataValue)> {
+        self.exif_data.iter().find(|(_, v)| {
+            v.source
+                .as_ref()
+                .is_some_and(|s| *s == MetadataSource::Exif { ifd, tag })
+        })
+    }
+
     /// Return a new `ImageData` containing only the selected metadata fields.
     pub fn filter_metadata(
         &self,
@@ -97,15 +629,75 @@ impl ImageData {
                 None
             },
             exif_data: filtered_exif,
-            gps_coords: if include_gps { self.gps_coords } else { None },
+            gps: if include_gps { self.gps.clone() } else { None },
             sha256_hash: if include_basic_info {
                 self.sha256_hash.clone()
             } else {
                 None
             },
+            thumbnail: if include_basic_info {
+                self.thumbnail.clone()
+            } else {
+                None
+            },
+            jpeg: if include_basic_info {
+                self.jpeg.clone()
+            } else {
+                None
+            },
+            embedded_video: if include_basic_info {
+                self.embedded_video.clone()
+            } else {
+                None
+            },
+            mpf_images: if include_basic_info {
+                // Extra images carry their own EXIF, filtered like the file's
+                self.mpf_images
+                    .iter()
+                    .map(|image| MpfImage {
+                        exif_data: image
+                            .exif_data
+                            .iter()
+                            .filter(|(key, _)| {
+                                selected_keys.contains(*key)
+                                    && (include_gps || !key.contains("GPS"))
+                            })
+                            .map(|(key, value)| (key.clone(), value.clone()))
+                            .collect(),
+                        ..image.clone()
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            animation: if include_basic_info {
+                self.animation.clone()
+            } else {
+                None
+            },
+            type_check: if include_basic_info {
+                self.type_check.clone()
+            } else {
+                None
+            },
+            diagnostics: if include_basic_info {
+                self.diagnostics.clone()
+            } else {
+                Vec::new()
+            },
+            content_credentials: if include_basic_info {
+                self.content_credentials.clone()
+            } else {
+                None
+            },
         }
     }
 
+    /// Returns `true` if any of the given keys is present.
+    fn has_any(&self, keys: &[&str]) -> bool {
+        keys.iter().any(|k| self.exif_data.contains_key(*k))
+    }
+
     /// Calculate privacy risk based on metadata content
     pub fn calculate_privacy_risk(&self) -> PrivacyRisk {
         let mut score = 0u32;
@@ -113,17 +705,47 @@ impl ImageData {
         let mut sensitive_fields = Vec::new();
 
         // Critical: GPS location data (40 points)
-        if self.gps_coords.is_some() {
+        if self.gps.is_some()
+            || self.has_any(&["XMP-exif:GPSLatitude"])
+            || self
+                .exif_data
+                .keys()
+                .any(|k| k.starts_with("PDF-Image") && k.ends_with(":GPSLatitude"))
+        {
             score += 40;
             warnings
                 .push("GPS coordinates reveal exact location where photo was taken".to_string());
             sensitive_fields.push("GPS Location".to_string());
         }
 
-        // High: Camera serial number and owner name (25 points each)
-        if self.exif_data.contains_key("BodySerialNumber")
-            || self.exif_data.contains_key("InternalSerialNumber")
+        // Medium: Camera heading and fix accuracy (10 points)
+        if self
+            .gps
+            .as_ref()
+            .is_some_and(|g| g.img_direction.is_some() || g.horizontal_error.is_some())
+            || self.has_any(&["XMP-exif:GPSImgDirection", "XMP-exif:GPSHPositioningError"])
         {
+            score += 10;
+            warnings.push(
+                "Camera heading and GPS accuracy reveal which way the photographer was facing and how precisely the position is known"
+                    .to_string(),
+            );
+            sensitive_fields.push("GPS Direction/Accuracy".to_string());
+        }
+
+        // High: Camera serial number and owner name (25 points each)
+        if self.has_any(&[
+            "BodySerialNumber",
+            "InternalSerialNumber",
+            "XMP-aux:SerialNumber",
+            "XMP-exifEX:BodySerialNumber",
+            "Canon:SerialNumber",
+            "Canon:InternalSerialNumber",
+            "Nikon:SerialNumber",
+            "Sony:SerialNumber",
+            "Fujifilm:InternalSerialNumber",
+            "DNG:CameraSerialNumber",
+        ]) {
             score += 25;
             warnings.push(
                 "Camera serial number can identify specific device and link photos to owner"
@@ -132,24 +754,100 @@ impl ImageData {
             sensitive_fields.push("Camera Serial Number".to_string());
         }
 
-        if self.exif_data.contains_key("Artist")
-            || self.exif_data.contains_key("Copyright")
-            || self.exif_data.contains_key("OwnerName")
-        {
+        if self.has_any(&[
+            "Artist",
+            "Copyright",
+            "OwnerName",
+            "XMP-dc:creator",
+            "XMP-dc:rights",
+            "XMP-aux:OwnerName",
+            "XMP-exifEX:CameraOwnerName",
+            "Canon:OwnerName",
+            "IPTC:By-line",
+            "IPTC:CopyrightNotice",
+            "IPTC:Writer-Editor",
+            "PNG:Author",
+            "PNG:Copyright",
+            "PDF:Author",
+            "SVG:Creator",
+            "QuickTime:Artist",
+            "QuickTime:Author",
+            "QuickTime:Copyright",
+        ]) {
             score += 25;
             warnings.push("Owner or artist name directly identifies the photographer".to_string());
             sensitive_fields.push("Owner/Artist Name".to_string());
         }
 
+        // High: Contact details (20 points)
+        if self.has_any(&["IPTC:Contact"]) {
+            score += 20;
+            warnings.push(
+                "Contact details can include phone numbers, email or postal addresses".to_string(),
+            );
+            sensitive_fields.push("Contact Information".to_string());
+        }
+
+        // Medium: Named location (15 points)
+        if self.has_any(&[
+            "IPTC:City",
+            "IPTC:Sub-location",
+            "IPTC:Province-State",
+            "IPTC:Country-PrimaryLocationName",
+        ]) {
+            score += 15;
+            warnings.push(
+                "Location names (city, sub-location) reveal where the photo was taken".to_string(),
+            );
+            sensitive_fields.push("Location Names".to_string());
+        }
+
+        // Low: Custom ICC profile (10 points)
+        let custom_profile = self
+            .exif_data
+            .get("ICC:ProfileDescription")
+            .filter(|description| !crate::icc::is_standard_profile(&description.display));
+        if let Some(description) = custom_profile {
+            score += 10;
+            warnings.push(format!(
+                "Custom color profile \"{}\" may identify the monitor or workstation used",
+                description.display
+            ));
+            sensitive_fields.push("Color Profile".to_string());
+        }
+
         // Medium: Software and timestamps (15 points each)
-        if self.exif_data.contains_key("Software") {
+        if self.has_any(&[
+            "Software",
+            "XMP-xmp:CreatorTool",
+            "PNG:Software",
+            "PDF:Creator",
+            "PDF:Producer",
+            "SVG:Generator",
+            "SVG:InkscapeVersion",
+            "QuickTime:Software",
+            "QuickTime:Encoder",
+        ]) {
             score += 10;
             warnings.push("Software information may reveal editing tools and workflow".to_string());
             sensitive_fields.push("Software".to_string());
         }
 
-        if self.exif_data.contains_key("DateTimeOriginal")
-            || self.exif_data.contains_key("DateTime")
+        if self.has_any(&[
+            "DateTimeOriginal",
+            "DateTime",
+            "XMP-photoshop:DateCreated",
+            "XMP-xmp:CreateDate",
+            "XMP-exif:DateTimeOriginal",
+            "IPTC:DateCreated",
+            "PNG:Creation Time",
+            "PNG:ModifyDate",
+            "PDF:CreationDate",
+            "PDF:ModDate",
+            "QuickTime:CreationDate",
+            "QuickTime:CreateDate",
+            "QuickTime:TrackCreateDate",
+        ]) || (custom_profile.is_some() && self.has_any(&["ICC:ProfileDateTime"]))
         {
             score += 15;
             warnings
@@ -157,8 +855,27 @@ impl ImageData {
             sensitive_fields.push("Timestamps".to_string());
         }
 
+        // Medium: Shutter counts and capture identifiers (10 points)
+        if self.has_any(&[
+            "Canon:ShutterCount",
+            "Nikon:ShutterCount",
+            "Sony:ShutterCount",
+            "Fujifilm:ImageCount",
+            "Apple:ContentIdentifier",
+            "Apple:BurstUUID",
+            "QuickTime:ContentIdentifier",
+        ]) {
+            score += 10;
+            warnings.push(
+                "Shutter counts and capture identifiers can link photos from the same device or session"
+                    .to_string(),
+            );
+            sensitive_fields.push("Device Fingerprint".to_string());
+        }
+
         // Medium: Unique camera identifiers (15 points)
-        if self.exif_data.contains_key("Make") && self.exif_data.contains_key("Model") {
+        if self.has_any(&["Make", "QuickTime:Make"]) && self.has_any(&["Model", "QuickTime:Model"])
+        {
             score += 10;
             warnings.push(
                 "Camera make and model combined with other metadata can identify photographer"
@@ -168,18 +885,109 @@ impl ImageData {
         }
 
         // Low: Lens information (5 points)
-        if self.exif_data.contains_key("LensModel") || self.exif_data.contains_key("LensMake") {
+        if self.has_any(&[
+            "LensModel",
+            "LensMake",
+            "XMP-aux:Lens",
+            "XMP-exifEX:LensModel",
+            "Canon:LensModel",
+            "Nikon:Lens",
+            "Nikon:LensID",
+            "Sony:LensType",
+            "Fujifilm:Lens",
+        ]) {
             score += 5;
             warnings
                 .push("Lens information may help identify photographer's equipment".to_string());
             sensitive_fields.push("Lens Information".to_string());
         }
 
+        // Medium: AI generation prompts and workflows (15 points)
+        if self.has_any(&["PNG:parameters", "PNG:prompt", "PNG:workflow"]) {
+            score += 15;
+            warnings.push(
+                "AI generation prompts and workflows reveal the exact text, models and local paths used"
+                    .to_string(),
+            );
+            sensitive_fields.push("AI Generation Parameters".to_string());
+        }
+
+        // High: Absolute file paths left by SVG editors (20 points)
+        if self.has_any(&["SVG:AbsolutePaths", "SVG:DocumentBase"]) {
+            score += 20;
+            warnings.push(
+                "Absolute file paths reveal the author's user name and folder structure"
+                    .to_string(),
+            );
+            sensitive_fields.push("File Paths".to_string());
+        }
+
+        // High: Embedded thumbnail that no longer matches the image (20 points)
+        if self.thumbnail.as_ref().is_some_and(|t| t.differs) {
+            score += 20;
+            warnings.push(
+                "Embedded EXIF thumbnail differs from the main image and may leak pre-edit content such as cropped or redacted areas"
+                    .to_string(),
+            );
+            sensitive_fields.push("Embedded Thumbnail".to_string());
+        }
+
+        // High: Motion photo clip hidden in the file (20 points)
+        if let Some(video) = &self.embedded_video {
+            score += 20;
+            let mut captures = vec!["moving images"];
+            if video.has_audio {
+                captures.push("sound");
+            }
+            if video.has_location {
+                captures.push("its own location");
+            }
+            warnings.push(format!(
+                "Embedded motion photo video records {} around the moment of capture",
+                captures.join(", ")
+            ));
+            sensitive_fields.push("Embedded Video".to_string());
+        }
+
+        // Medium: Extra images of a Multi-Picture file (15 points)
+        let extra_images: Vec<&MpfImage> = self.mpf_images.iter().filter(|i| i.index > 1).collect();
+        if !extra_images.is_empty() {
+            score += 15;
+            let located = extra_images
+                .iter()
+                .any(|i| i.exif_data.contains_key("GPSLatitude"));
+            warnings.push(format!(
+                "File holds {} additional image(s) that most viewers never show{}",
+                extra_images.len(),
+                if located {
+                    ", with their own GPS location"
+                } else {
+                    ""
+                }
+            ));
+            sensitive_fields.push("Extra Images".to_string());
+        }
+
+        // Low: XMP edit history (10 points)
+        if self
+            .exif_data
+            .keys()
+            .any(|k| k.starts_with("XMP-xmpMM:History"))
+        {
+            score += 10;
+            warnings.push(
+                "XMP edit history records every save, the tools used and sometimes file paths"
+                    .to_string(),
+            );
+            sensitive_fields.push("Edit History".to_string());
+        }
+
         // Metadata consistency checks
         let mut consistency_issues = Vec::new();
 
-        // Check for GPS without GPSRef fields
-        if self.gps_coords.is_some() {
+        // Check for GPS without GPSRef fields; ISO 6709 video locations carry
+        // their hemisphere in the sign
+        if self.gps.is_some() && !self.exif_data.contains_key("QuickTime:GPSCoordinates") {
             let has_lat_ref = self
                 .exif_data
                 .contains_key("GPSLatitudeRef")
@@ -209,18 +1017,48 @@ impl ImageData {
             }
         }
 
-        // Check for timestamp inconsistencies
-        if let (Some(datetime), Some(datetime_original)) = (
-            self.exif_data.get("DateTime"),
-            self.exif_data.get("DateTimeOriginal"),
-        ) && datetime != datetime_original
-        {
+        // Check for timestamp inconsistencies, comparing instants where both
+        // values parse and the raw strings otherwise
+        let read = |key: &str, offset: Option<&str>, subsec: Option<&str>| {
+            timestamp::read_timestamp(
+                &self.exif_data,
+                TimestampKind::Modified,
+                key,
+                offset,
+                subsec,
+            )
+        };
+        let modified = read("DateTime", Some("OffsetTime"), Some("SubSecTime"));
+        let differs = |key: &str, other: Option<Timestamp>| match (&modified, other) {
+            (Some(a), Some(b)) => !a.same_instant(&b),
+            _ => match (self.exif_data.get("DateTime"), self.exif_data.get(key)) {
+                (Some(a), Some(b)) => !a.same_value(b),
+                _ => false,
+            },
+        };
+
+        if differs(
+            "DateTimeOriginal",
+            read(
+                "DateTimeOriginal",
+                Some("OffsetTimeOriginal"),
+                Some("SubSecTimeOriginal"),
+            ),
+        ) {
             consistency_issues.push(
                 "DateTime and DateTimeOriginal differ - image may have been modified after capture"
                     .to_string(),
             );
         }
 
+        // Check whether the thumbnail was written at a different time than the main image
+        if differs("Thumbnail:DateTime", read("Thumbnail:DateTime", None, None)) {
+            consistency_issues.push(
+                "Thumbnail DateTime differs from main image - thumbnail may show an earlier version"
+                    .to_string(),
+            );
+        }
+
         // Check for Orientation without corresponding dimension data
         if self.exif_data.contains_key("Orientation")
             && (self.width.is_none() || self.height.is_none())
@@ -240,11 +1078,11 @@ impl ImageData {
             let has_exif_height = self.exif_data.contains_key("ExifImageHeight");
 
             if (has_pixel_x || has_pixel_y) && (has_exif_width || has_exif_height) {
-                // If we have both sets of dimensions, check if they match
+                // If we have both sets of dimensions, check if they match numerically
                 if let (Some(pixel_x), Some(exif_width)) = (
                     self.exif_data.get("PixelXDimension"),
                     self.exif_data.get("ExifImageWidth"),
-                ) && pixel_x != exif_width
+                ) && !pixel_x.same_value(exif_width)
                 {
                     consistency_issues.push(
                         "Multiple dimension fields present with different values - image may have been resized"
@@ -8,5 +8,5 @@ pub use crate::utils_core::{base64_encode_native, format_file_size};
 // Re-export WASM-specific functionality
 pub use crate::utils_wasm::{
     copy_to_clipboard, download_binary_file, download_binary_file_with_picker, download_file,
-    download_file_with_picker,
+    download_file_with_picker, fetch_blob_bytes,
 };
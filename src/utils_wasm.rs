@@ -2,6 +2,7 @@
 //! This module contains all browser-dependent utility functions that cannot be tested in native environment.
 
 use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
 use web_sys::{HtmlAnchorElement, Url};
 
 /// Check if the File System Access API (showSaveFilePicker) is available.
@@ -140,6 +141,15 @@ pub fn download_binary_file(bytes: &[u8], filename: &str, mime_type: &str) {
     Url::revoke_object_url(&url).unwrap();
 }
 
+/// Fetch the bytes behind a blob URL, such as the object URL of an upload.
+pub async fn fetch_blob_bytes(blob_url: &str) -> Result<Vec<u8>, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("No window"))?;
+    let response = JsFuture::from(window.fetch_with_str(blob_url)).await?;
+    let response: web_sys::Response = response.dyn_into()?;
+    let array_buffer = JsFuture::from(response.array_buffer()?).await?;
+    Ok(js_sys::Uint8Array::new(&array_buffer).to_vec())
+}
+
 /// Download a text file using the modern File System Access API when available,
 /// falling back to the legacy anchor element method on unsupported browsers.
 ///
@@ -0,0 +1,822 @@
+//! MP4, QuickTime and 3GP video metadata.
+//!
+//! Video files are ISO base media files like HEIF, but their metadata lives
+//! in the `moov` box. `mvhd` holds the movie creation time and duration. Each
+//! `trak` describes one stream: `tkhd` gives its dates, size and rotation,
+//! `mdhd` its timescale and duration, `hdlr` whether it is video or sound and
+//! `stsd` the codec. User data comes in two flavours. Classic QuickTime `udta` atoms are
+//! named with a leading `©`, e.g. `©xyz` for the location and `©mak` for the
+//! make. iPhones and Android phones instead write a `meta` box whose `keys`
+//! list names values such as `com.apple.quicktime.location.ISO6709` that are
+//! stored in the matching `ilst` entries.
+
+use crate::bmff::{Cursor, full_box};
+use crate::container::{self, Chunk};
+use crate::diagnostics::Diagnostic;
+use crate::timestamp::{Timestamp, TimestampKind};
+use crate::types::{GpsInfo, MetadataSource, MetadataValue, RawValue};
+use std::collections::HashMap;
+
+/// Seconds from 1904-01-01, the QuickTime epoch, to the Unix epoch.
+const QUICKTIME_EPOCH_OFFSET: i64 = 2_082_844_800;
+
+/// Major brands of HEIF and AVIF image files, which may carry a `moov` box
+/// for image sequences but are not videos.
+const IMAGE_BRANDS: &[&str] = &[
+    "heic", "heix", "heim", "heis", "hevc", "hevx", "mif1", "msf1", "avif", "avis",
+];
+
+/// Display names of sample entry codes.
+pub static CODEC_NAMES: &[(&str, &str)] = &[
+    ("avc1", "H.264/AVC"),
+    ("avc3", "H.264/AVC"),
+    ("hvc1", "H.265/HEVC"),
+    ("hev1", "H.265/HEVC"),
+    ("dvh1", "Dolby Vision HEVC"),
+    ("dvhe", "Dolby Vision HEVC"),
+    ("av01", "AV1"),
+    ("vp08", "VP8"),
+    ("vp09", "VP9"),
+    ("mp4v", "MPEG-4 Visual"),
+    ("s263", "H.263"),
+    ("h263", "H.263"),
+    ("jpeg", "Motion JPEG"),
+    ("mjpa", "Motion JPEG"),
+    ("apch", "Apple ProRes 422 HQ"),
+    ("apcn", "Apple ProRes 422"),
+    ("apcs", "Apple ProRes 422 LT"),
+    ("apco", "Apple ProRes 422 Proxy"),
+    ("ap4h", "Apple ProRes 4444"),
+    ("mp4a", "AAC"),
+    ("samr", "AMR-NB"),
+    ("sawb", "AMR-WB"),
+    ("ac-3", "AC-3"),
+    ("ec-3", "E-AC-3"),
+    ("Opus", "Opus"),
+    ("fLaC", "FLAC"),
+    ("alac", "Apple Lossless"),
+    ("lpcm", "PCM"),
+    ("sowt", "PCM"),
+    ("twos", "PCM"),
+];
+
+/// Field names of QuickTime `udta` atoms and iTunes-style `ilst` items.
+static USER_DATA_NAMES: &[(&[u8; 4], &str)] = &[
+    (b"\xA9xyz", "GPSCoordinates"),
+    (b"\xA9mak", "Make"),
+    (b"\xA9mod", "Model"),
+    (b"\xA9swr", "Software"),
+    (b"\xA9too", "Encoder"),
+    (b"\xA9enc", "EncodedBy"),
+    (b"\xA9day", "CreationDate"),
+    (b"\xA9nam", "Title"),
+    (b"\xA9ART", "Artist"),
+    (b"\xA9aut", "Author"),
+    (b"\xA9cmt", "Comment"),
+    (b"\xA9des", "Description"),
+    (b"\xA9inf", "Information"),
+    (b"\xA9cpy", "Copyright"),
+    (b"cprt", "Copyright"),
+];
+
+/// Field names of `keys` entries in the `mdta` namespace.
+static METADATA_KEY_NAMES: &[(&str, &str)] = &[
+    ("com.apple.quicktime.location.ISO6709", "GPSCoordinates"),
+    (
+        "com.apple.quicktime.location.accuracy.horizontal",
+        "LocationAccuracyHorizontal",
+    ),
+    ("com.apple.quicktime.make", "Make"),
+    ("com.apple.quicktime.model", "Model"),
+    ("com.apple.quicktime.software", "Software"),
+    ("com.apple.quicktime.creationdate", "CreationDate"),
+    (
+        "com.apple.quicktime.content.identifier",
+        "ContentIdentifier",
+    ),
+    ("com.android.manufacturer", "Make"),
+    ("com.android.model", "Model"),
+    ("com.android.version", "AndroidVersion"),
+    ("com.android.capture.fps", "CaptureFrameRate"),
+];
+
+/// One stream of a movie.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VideoTrack {
+    pub id: u32,
+    /// Handler type from `hdlr`: `vide`, `soun`, `meta`, ...
+    pub handler: String,
+    /// Sample entry code from `stsd`, e.g. `avc1` or `mp4a`.
+    pub codec: Option<String>,
+    /// Compressor name from the visual sample entry.
+    pub compressor: Option<String>,
+    /// Pixel size from the visual sample entry, or else from `tkhd`.
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Clockwise display rotation in degrees from the `tkhd` matrix.
+    pub rotation: u32,
+    /// `tkhd` creation and modification times in Unix seconds (UTC).
+    pub creation_time: Option<i64>,
+    pub modification_time: Option<i64>,
+    /// Media timescale (units per second) and duration from `mdhd`.
+    pub timescale: u32,
+    pub duration: u64,
+    /// Number of samples listed in `stts`.
+    pub sample_count: u64,
+    pub channels: Option<u16>,
+    pub sample_rate: Option<u32>,
+    /// ISO 639-2 language code from `mdhd`.
+    pub language: Option<String>,
+}
+
+impl VideoTrack {
+    pub fn is_video(&self) -> bool {
+        self.handler == "vide"
+    }
+
+    pub fn is_audio(&self) -> bool {
+        self.handler == "soun"
+    }
+
+    /// Average frames per second.
+    pub fn frame_rate(&self) -> Option<f64> {
+        (self.duration > 0 && self.sample_count > 0)
+            .then(|| self.sample_count as f64 * f64::from(self.timescale) / self.duration as f64)
+    }
+}
+
+/// A user data value with its normalised field name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoTag {
+    pub name: String,
+    pub value: String,
+    /// Atom or `keys` entry the value was read from.
+    pub source: String,
+}
+
+/// The parsed `moov` box of a video file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VideoFile {
+    pub major_brand: String,
+    pub compatible_brands: Vec<String>,
+    /// `mvhd` creation and modification times in Unix seconds (UTC).
+    pub creation_time: Option<i64>,
+    pub modification_time: Option<i64>,
+    /// Movie timescale (units per second) and duration from `mvhd`.
+    pub timescale: u32,
+    pub duration: u64,
+    pub tracks: Vec<VideoTrack>,
+    /// User data in file order.
+    pub tags: Vec<VideoTag>,
+}
+
+impl VideoFile {
+    /// Duration in seconds.
+    pub fn duration_seconds(&self) -> Option<f64> {
+        (self.timescale > 0).then(|| self.duration as f64 / f64::from(self.timescale))
+    }
+
+    /// The first video track.
+    pub fn video_track(&self) -> Option<&VideoTrack> {
+        self.tracks.iter().find(|t| t.is_video())
+    }
+
+    /// The first audio track.
+    pub fn audio_track(&self) -> Option<&VideoTrack> {
+        self.tracks.iter().find(|t| t.is_audio())
+    }
+
+    /// The first value recorded under a field name.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| t.value.as_str())
+    }
+}
+
+fn fourcc(bytes: &[u8]) -> String {
+    // Latin-1, so that `©` atom names survive
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+fn find_box<'a>(boxes: &[Chunk<'a>], id: &[u8; 4]) -> Option<Chunk<'a>> {
+    boxes.iter().find(|b| &b.id == id).copied()
+}
+
+/// Walk a path of nested container boxes.
+fn find_path<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> Option<Chunk<'a>> {
+    let (first, rest) = path.split_first()?;
+    let found = find_box(&container::iso_boxes(data), first)?;
+    if rest.is_empty() {
+        Some(found)
+    } else {
+        find_path(found.data, rest)
+    }
+}
+
+/// Convert QuickTime epoch seconds, treating 0 as "not set".
+fn quicktime_time(seconds: u64) -> Option<i64> {
+    if seconds == 0 {
+        return None;
+    }
+    i64::try_from(seconds)
+        .ok()?
+        .checked_sub(QUICKTIME_EPOCH_OFFSET)
+}
+
+/// Returns `true` for MP4, QuickTime and 3GP files.
+///
+/// HEIF and AVIF image sequences share the structure and are excluded by
+/// brand. QuickTime files from older cameras have no `ftyp` box and are
+/// recognised by a leading `moov`, `wide`, `mdat`, `free` or `skip` box
+/// followed somewhere by `moov`.
+pub fn is_video(data: &[u8]) -> bool {
+    if container::is_isobmff(data) {
+        let brand = fourcc(&data[8..12]);
+        if IMAGE_BRANDS.contains(&brand.as_str()) {
+            return false;
+        }
+    } else if !matches!(
+        data.get(4..8),
+        Some(b"moov" | b"wide" | b"mdat" | b"free" | b"skip")
+    ) {
+        return false;
+    }
+    container::iso_boxes(data).iter().any(|b| &b.id == b"moov")
+}
+
+/// MIME type of a video file from its brand: `video/quicktime`,
+/// `video/3gpp`, `video/3gpp2` or `video/mp4`.
+pub fn mime_type(data: &[u8]) -> Option<&'static str> {
+    if !is_video(data) {
+        return None;
+    }
+    if !container::is_isobmff(data) {
+        return Some("video/quicktime");
+    }
+    Some(match &data[8..12] {
+        b"qt  " => "video/quicktime",
+        brand if brand.starts_with(b"3g2") => "video/3gpp2",
+        brand if brand.starts_with(b"3g") => "video/3gpp",
+        _ => "video/mp4",
+    })
+}
+
+/// Display name of a sample entry code, e.g. `"H.264/AVC (avc1)"`.
+pub fn codec_name(code: &str) -> String {
+    match CODEC_NAMES.iter().find(|(c, _)| *c == code) {
+        Some((_, name)) => format!("{} ({})", name, code),
+        None => code.to_string(),
+    }
+}
+
+/// Parse an ISO 6709 location such as `+37.7749-122.4194+012.345/` into
+/// latitude, longitude and optional altitude.
+///
+/// Degrees may also be given as `±DDMM.M`/`±DDDMM.M` or
+/// `±DDMMSS.S`/`±DDDMMSS.S`; a trailing `CRS` designator is ignored.
+pub fn parse_iso6709(text: &str) -> Option<(f64, f64, Option<f64>)> {
+    let text = text.trim().trim_end_matches('/');
+    let text = text.split("CRS").next()?;
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (i, c) in text.char_indices().skip(1) {
+        if c == '+' || c == '-' {
+            parts.push(&text[start..i]);
+            start = i;
+        }
+    }
+    parts.push(&text[start..]);
+    if parts.len() < 2 || !parts.iter().all(|p| p.starts_with(['+', '-'])) {
+        return None;
+    }
+
+    let angle = |part: &str, degree_digits: usize| -> Option<f64> {
+        let sign = if part.starts_with('-') { -1.0 } else { 1.0 };
+        let digits = &part[1..];
+        let whole = digits.find('.').unwrap_or(digits.len());
+        let value: f64 = digits.parse().ok()?;
+        let degrees = if whole <= degree_digits {
+            value
+        } else if whole == degree_digits + 2 {
+            (value / 100.0).trunc() + value % 100.0 / 60.0
+        } else if whole == degree_digits + 4 {
+            (value / 10_000.0).trunc()
+                + (value / 100.0).trunc() % 100.0 / 60.0
+                + value % 100.0 / 3_600.0
+        } else {
+            return None;
+        };
+        Some(sign * degrees)
+    };
+
+    let latitude = angle(parts[0], 2).filter(|v| v.abs() <= 90.0)?;
+    let longitude = angle(parts[1], 3).filter(|v| v.abs() <= 180.0)?;
+    let altitude = parts.get(2).and_then(|p| p.parse().ok());
+    Some((latitude, longitude, altitude))
+}
+
+/// Parse `tkhd`: track dates, ID, size and rotation.
+fn parse_tkhd(data: &[u8], track: &mut VideoTrack) -> Option<()> {
+    let (version, _, payload) = full_box(data)?;
+    let mut cursor = Cursor::new(payload);
+    track.creation_time = quicktime_time(cursor.versioned(version)?);
+    track.modification_time = quicktime_time(cursor.versioned(version)?);
+    track.id = cursor.u32()?;
+    cursor.skip(4)?;
+    cursor.versioned(version)?; // duration
+    cursor.skip(8 + 2 + 2 + 2 + 2)?;
+    let matrix: Vec<i32> = (0..9)
+        .map(|_| cursor.u32().map(|v| v as i32))
+        .collect::<Option<_>>()?;
+    track.rotation = match (matrix[0], matrix[1]) {
+        (0, b) if b > 0 => 90,
+        (a, 0) if a < 0 => 180,
+        (0, b) if b < 0 => 270,
+        _ => 0,
+    };
+    let width = cursor.u32()? >> 16;
+    let height = cursor.u32()? >> 16;
+    if width > 0 && height > 0 {
+        track.width = Some(width);
+        track.height = Some(height);
+    }
+    Some(())
+}
+
+/// Parse `mdhd`: timescale, duration and language.
+fn parse_mdhd(data: &[u8], track: &mut VideoTrack) -> Option<()> {
+    let (version, _, payload) = full_box(data)?;
+    let mut cursor = Cursor::new(payload);
+    cursor.versioned(version)?;
+    cursor.versioned(version)?;
+    track.timescale = cursor.u32()?;
+    track.duration = cursor.versioned(version)?;
+    // Three 5-bit letters offset from 0x60
+    let packed = cursor.u16()?;
+    let language: String = [10, 5, 0]
+        .iter()
+        .map(|shift| (((packed >> shift) & 0x1F) as u8 + 0x60) as char)
+        .collect();
+    if language.chars().all(|c| c.is_ascii_lowercase()) && language != "und" {
+        track.language = Some(language);
+    }
+    Some(())
+}
+
+/// Parse the first `stsd` sample entry: codec and, for video and sound,
+/// the coded size or channel layout.
+fn parse_stsd(data: &[u8], track: &mut VideoTrack) -> Option<()> {
+    let (_, _, payload) = full_box(data)?;
+    let entries = container::iso_boxes(payload.get(4..)?);
+    let entry = entries.first()?;
+    track.codec = Some(fourcc(&entry.id));
+
+    let mut cursor = Cursor::new(entry.data);
+    cursor.skip(8)?; // reserved and data reference index
+    match track.handler.as_str() {
+        "vide" => {
+            cursor.skip(16)?;
+            let width = u32::from(cursor.u16()?);
+            let height = u32::from(cursor.u16()?);
+            if width > 0 && height > 0 {
+                track.width = Some(width);
+                track.height = Some(height);
+            }
+            cursor.skip(14)?;
+            let name = cursor.take(32)?;
+            let len = usize::from(name[0]).min(31);
+            let compressor = String::from_utf8_lossy(&name[1..=len]).trim().to_string();
+            if !compressor.is_empty() {
+                track.compressor = Some(compressor);
+            }
+        }
+        "soun" => {
+            cursor.skip(8)?;
+            track.channels = Some(cursor.u16()?);
+            cursor.skip(6)?;
+            track.sample_rate = Some(cursor.u32()? >> 16).filter(|rate| *rate > 0);
+        }
+        _ => {}
+    }
+    Some(())
+}
+
+/// Total number of samples listed in `stts`.
+fn parse_stts(data: &[u8]) -> Option<u64> {
+    let (_, _, payload) = full_box(data)?;
+    let mut cursor = Cursor::new(payload);
+    let count = cursor.u32()?;
+    let mut samples = 0u64;
+    for _ in 0..count {
+        samples += u64::from(cursor.u32()?);
+        cursor.u32()?;
+    }
+    Some(samples)
+}
+
+fn parse_trak(data: &[u8]) -> VideoTrack {
+    let mut track = VideoTrack::default();
+    let boxes = container::iso_boxes(data);
+    if let Some(tkhd) = find_box(&boxes, b"tkhd") {
+        parse_tkhd(tkhd.data, &mut track);
+    }
+    let Some(mdia) = find_box(&boxes, b"mdia") else {
+        return track;
+    };
+    let media = container::iso_boxes(mdia.data);
+    if let Some(mdhd) = find_box(&media, b"mdhd") {
+        parse_mdhd(mdhd.data, &mut track);
+    }
+    if let Some(hdlr) = find_box(&media, b"hdlr")
+        && let Some(handler) = hdlr.data.get(8..12)
+    {
+        track.handler = fourcc(handler);
+    }
+    if let Some(stbl) = find_box(&media, b"minf").and_then(|minf| find_path(minf.data, &[b"stbl"]))
+    {
+        let table = container::iso_boxes(stbl.data);
+        if let Some(stsd) = find_box(&table, b"stsd") {
+            parse_stsd(stsd.data, &mut track);
+        }
+        if let Some(count) = find_box(&table, b"stts").and_then(|stts| parse_stts(stts.data)) {
+            track.sample_count = count;
+        }
+    }
+    track
+}
+
+/// Decode the payload of an `ilst` `data` box: a type indicator, a locale
+/// and the value.
+fn data_value(data: &[u8]) -> Option<String> {
+    let kind = u32::from_be_bytes(data.get(..4)?.try_into().ok()?) & 0x00FF_FFFF;
+    let value = data.get(8..)?;
+    let number = |bytes: &[u8], signed: bool| -> Option<String> {
+        let mut padded = [0u8; 8];
+        let fill = if signed && bytes.first()? & 0x80 != 0 {
+            0xFF
+        } else {
+            0
+        };
+        padded.fill(fill);
+        padded
+            .get_mut(8usize.checked_sub(bytes.len())?..)?
+            .copy_from_slice(bytes);
+        Some(if signed {
+            i64::from_be_bytes(padded).to_string()
+        } else {
+            u64::from_be_bytes(padded).to_string()
+        })
+    };
+    match kind {
+        1 => Some(String::from_utf8_lossy(value).into_owned()),
+        2 => {
+            let units: Vec<u16> = value
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+            Some(String::from_utf16_lossy(&units))
+        }
+        21 => number(value, true),
+        22 => number(value, false),
+        23 => Some(f32::from_be_bytes(value.try_into().ok()?).to_string()),
+        24 => Some(f64::from_be_bytes(value.try_into().ok()?).to_string()),
+        _ => None,
+    }
+    .map(|text| text.trim_end_matches('\0').trim().to_string())
+    .filter(|text| !text.is_empty())
+}
+
+/// Name of a field without a table entry: the key with its vendor prefix
+/// removed, in UpperCamelCase (`com.apple.quicktime.live-photo.auto` becomes
+/// `LivePhotoAuto`).
+fn key_field_name(key: &str) -> String {
+    let key = key
+        .strip_prefix("com.apple.quicktime.")
+        .or_else(|| key.strip_prefix("com.android."))
+        .unwrap_or(key);
+    key.split(['.', '-', '_', ' '])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn atom_field_name(id: &[u8; 4]) -> String {
+    USER_DATA_NAMES
+        .iter()
+        .find(|(atom, _)| *atom == id)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| fourcc(id).trim_start_matches('\u{A9}').to_string())
+}
+
+/// Read a `meta` box: either `keys` + `ilst` (`mdta` handler) or an
+/// iTunes-style `ilst` named by atom.
+///
+/// QuickTime writes `meta` as a plain box and ISO files as a FullBox, so
+/// the version and flags are only skipped when `hdlr` does not come first.
+fn parse_meta(data: &[u8], tags: &mut Vec<VideoTag>) {
+    let payload = if data.get(4..8) == Some(b"hdlr") {
+        data
+    } else {
+        data.get(4..).unwrap_or_default()
+    };
+    let boxes = container::iso_boxes(payload);
+
+    let mut keys = Vec::new();
+    if let Some(keys_box) = find_box(&boxes, b"keys")
+        && let Some((_, _, entries)) = full_box(keys_box.data)
+    {
+        keys = container::iso_boxes(entries.get(4..).unwrap_or_default())
+            .iter()
+            .map(|entry| String::from_utf8_lossy(entry.data).into_owned())
+            .collect();
+    }
+
+    let Some(ilst) = find_box(&boxes, b"ilst") else {
+        return;
+    };
+    for item in container::iso_boxes(ilst.data) {
+        let (name, source) = if keys.is_empty() {
+            (atom_field_name(&item.id), fourcc(&item.id))
+        } else {
+            let index = u32::from_be_bytes(item.id) as usize;
+            let Some(key) = index.checked_sub(1).and_then(|i| keys.get(i)) else {
+                continue;
+            };
+            let name = METADATA_KEY_NAMES
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_else(|| key_field_name(key));
+            (name, key.clone())
+        };
+        let value = container::iso_boxes(item.data)
+            .iter()
+            .filter(|b| &b.id == b"data")
+            .find_map(|b| data_value(b.data));
+        if let Some(value) = value {
+            tags.push(VideoTag {
+                name,
+                value,
+                source,
+            });
+        }
+    }
+}
+
+/// Read QuickTime `udta` atoms. `©` atoms hold a list of (length, language,
+/// text) strings of which the first is kept; other atoms are skipped apart
+/// from an embedded `meta` box.
+fn parse_udta(data: &[u8], tags: &mut Vec<VideoTag>) {
+    for atom in container::iso_boxes(data) {
+        if &atom.id == b"meta" {
+            parse_meta(atom.data, tags);
+            continue;
+        }
+        if atom.id[0] != 0xA9 && !USER_DATA_NAMES.iter().any(|(id, _)| **id == atom.id) {
+            continue;
+        }
+        let value = if atom.data.get(4..8) == Some(b"data") {
+            container::iso_boxes(atom.data)
+                .first()
+                .and_then(|b| data_value(b.data))
+        } else {
+            let mut cursor = Cursor::new(atom.data);
+            cursor
+                .u16()
+                .and_then(|len| {
+                    cursor.skip(2)?;
+                    cursor.take(usize::from(len))
+                })
+                .map(|text| String::from_utf8_lossy(text).trim().to_string())
+                .filter(|text| !text.is_empty())
+        };
+        if let Some(value) = value {
+            tags.push(VideoTag {
+                name: atom_field_name(&atom.id),
+                value,
+                source: fourcc(&atom.id),
+            });
+        }
+    }
+}
+
+/// Parse the movie header, tracks and user data of a video file.
+pub fn parse_video(data: &[u8]) -> Result<VideoFile, String> {
+    read_video(data, &mut Vec::new())
+}
+
+fn read_video(data: &[u8], diagnostics: &mut Vec<Diagnostic>) -> Result<VideoFile, String> {
+    if !is_video(data) {
+        return Err("Not an MP4, QuickTime or 3GP file".to_string());
+    }
+    let (boxes, problem) = container::iso_boxes_checked(data);
+    diagnostics.extend(problem);
+    let mut file = VideoFile::default();
+    if let Some(ftyp) = find_box(&boxes, b"ftyp") {
+        file.major_brand = ftyp.data.get(..4).map(fourcc).unwrap_or_default();
+        // Skip the minor version
+        file.compatible_brands = ftyp
+            .data
+            .get(8..)
+            .unwrap_or_default()
+            .chunks_exact(4)
+            .map(fourcc)
+            .collect();
+    }
+
+    let moov = find_box(&boxes, b"moov").ok_or("No 'moov' box")?;
+    let children = container::iso_boxes(moov.data);
+    if let Some(mvhd) = find_box(&children, b"mvhd")
+        && let Some((version, _, payload)) = full_box(mvhd.data)
+    {
+        let mut cursor = Cursor::new(payload);
+        file.creation_time = cursor.versioned(version).and_then(quicktime_time);
+        file.modification_time = cursor.versioned(version).and_then(quicktime_time);
+        file.timescale = cursor.u32().unwrap_or_default();
+        file.duration = cursor.versioned(version).unwrap_or_default();
+    }
+    for child in &children {
+        match &child.id {
+            b"trak" => file.tracks.push(parse_trak(child.data)),
+            b"udta" => parse_udta(child.data, &mut file.tags),
+            b"meta" => parse_meta(child.data, &mut file.tags),
+            _ => {}
+        }
+    }
+    Ok(file)
+}
+
+/// Display size of the first video track, with 90° and 270° rotations
+/// applied.
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let file = parse_video(data).ok()?;
+    let track = file.video_track()?;
+    let (width, height) = (track.width?, track.height?);
+    Some(if track.rotation % 180 == 90 {
+        (height, width)
+    } else {
+        (width, height)
+    })
+}
+
+/// The recording location from `©xyz` or the ISO 6709 `keys` entry, with
+/// Apple's horizontal accuracy when present.
+pub fn gps_info(file: &VideoFile) -> Option<GpsInfo> {
+    let (latitude, longitude, altitude) = parse_iso6709(file.tag("GPSCoordinates")?)?;
+    Some(GpsInfo {
+        altitude,
+        horizontal_error: file
+            .tag("LocationAccuracyHorizontal")
+            .and_then(|v| v.parse().ok()),
+        ..GpsInfo::new(latitude, longitude)
+    })
+}
+
+/// Format with up to three decimals and no trailing zeros: `29.97`, `30`.
+fn trim_decimals(value: f64) -> String {
+    format!("{:.3}", value)
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Convert the movie into `exif_data` entries keyed `QuickTime:<Field>`.
+pub fn video_to_metadata(file: &VideoFile) -> HashMap<String, MetadataValue> {
+    let mut map = HashMap::new();
+    let mut insert = |name: &str, box_type: &str, value: MetadataValue| {
+        map.entry(format!("QuickTime:{}", name))
+            .or_insert(value.with_source(MetadataSource::Bmff {
+                box_type: box_type.to_string(),
+            }));
+    };
+    let integer =
+        |value: u32| MetadataValue::new(RawValue::Integer(vec![value.into()]), value.to_string());
+    let float = |value: f64, display: String, unit: &str| {
+        MetadataValue::new(RawValue::Float(vec![value]), display).with_unit(unit)
+    };
+    let date = |seconds: i64| {
+        MetadataValue::text(Timestamp::from_unix(TimestampKind::Digitized, seconds).to_iso8601())
+    };
+
+    if !file.major_brand.is_empty() {
+        insert(
+            "MajorBrand",
+            "ftyp",
+            MetadataValue::text(file.major_brand.trim().to_string()),
+        );
+    }
+    if let Some(seconds) = file.creation_time {
+        insert("CreateDate", "mvhd", date(seconds));
+    }
+    if let Some(seconds) = file.modification_time {
+        insert("ModifyDate", "mvhd", date(seconds));
+    }
+    if let Some(seconds) = file.duration_seconds() {
+        insert(
+            "Duration",
+            "mvhd",
+            float(seconds, format!("{:.2} s", seconds), "s"),
+        );
+    }
+    insert("TrackCount", "trak", integer(file.tracks.len() as u32));
+
+    if let Some(track) = file.video_track() {
+        if let Some(codec) = &track.codec {
+            insert("VideoCodec", "stsd", MetadataValue::text(codec_name(codec)));
+        }
+        if let Some(compressor) = &track.compressor {
+            insert(
+                "CompressorName",
+                "stsd",
+                MetadataValue::text(compressor.clone()),
+            );
+        }
+        if let Some(seconds) = track.creation_time {
+            insert("TrackCreateDate", "tkhd", date(seconds));
+        }
+        if let Some(seconds) = track.modification_time {
+            insert("TrackModifyDate", "tkhd", date(seconds));
+        }
+        if let (Some(width), Some(height)) = (track.width, track.height) {
+            insert("ImageWidth", "tkhd", integer(width));
+            insert("ImageHeight", "tkhd", integer(height));
+        }
+        if track.rotation != 0 {
+            insert(
+                "Rotation",
+                "tkhd",
+                MetadataValue::new(
+                    RawValue::Integer(vec![track.rotation.into()]),
+                    format!("{}°", track.rotation),
+                ),
+            );
+        }
+        if let Some(rate) = track.frame_rate() {
+            insert(
+                "FrameRate",
+                "stts",
+                float(rate, format!("{} fps", trim_decimals(rate)), "fps"),
+            );
+        }
+    }
+    if let Some(track) = file.audio_track() {
+        if let Some(codec) = &track.codec {
+            insert("AudioCodec", "stsd", MetadataValue::text(codec_name(codec)));
+        }
+        if let Some(channels) = track.channels {
+            insert("AudioChannels", "stsd", integer(channels.into()));
+        }
+        if let Some(rate) = track.sample_rate {
+            insert(
+                "AudioSampleRate",
+                "stsd",
+                MetadataValue::new(RawValue::Integer(vec![rate.into()]), format!("{} Hz", rate))
+                    .with_unit("Hz"),
+            );
+        }
+        if let Some(language) = &track.language {
+            insert("Language", "mdhd", MetadataValue::text(language.clone()));
+        }
+    }
+
+    for tag in &file.tags {
+        insert(
+            &tag.name,
+            &tag.source,
+            MetadataValue::text(tag.value.clone()),
+        );
+    }
+    map
+}
+
+/// Extract video metadata and location.
+pub fn extract_video_data(data: &[u8]) -> (HashMap<String, MetadataValue>, Option<GpsInfo>) {
+    extract_video_data_with_diagnostics(data, &mut Vec::new())
+}
+
+/// Extract video metadata and location as [`extract_video_data`] does,
+/// reporting a damaged box sequence and a movie that cannot be read.
+pub fn extract_video_data_with_diagnostics(
+    data: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> (HashMap<String, MetadataValue>, Option<GpsInfo>) {
+    if !is_video(data) {
+        return (HashMap::new(), None);
+    }
+    match read_video(data, diagnostics) {
+        Ok(file) => (video_to_metadata(&file), gps_info(&file)),
+        Err(e) => {
+            diagnostics.push(Diagnostic::error("QuickTime", None, e));
+            (HashMap::new(), None)
+        }
+    }
+}
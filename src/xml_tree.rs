@@ -0,0 +1,240 @@
+//! Minimal namespace-aware XML tree built on top of `quick-xml`.
+//!
+//! Metadata formats such as XMP (RDF/XML) and SVG are small documents that are
+//! easier to interpret as a tree than as an event stream. This module turns
+//! a document into [`XmlElement`] nodes with resolved namespace URIs.
+
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::ResolveResult;
+use quick_xml::{NsReader, XmlVersion};
+
+/// Maximum element nesting accepted before parsing is aborted.
+const MAX_DEPTH: usize = 256;
+
+/// An attribute with its resolved namespace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlAttribute {
+    pub namespace: Option<String>,
+    pub prefix: Option<String>,
+    pub name: String,
+    pub value: String,
+}
+
+/// Child content of an element.
+#[derive(Debug, Clone, PartialEq)]
+pub enum XmlNode {
+    Element(XmlElement),
+    Text(String),
+}
+
+/// An element with its resolved namespace, attributes and children.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlElement {
+    pub namespace: Option<String>,
+    pub prefix: Option<String>,
+    pub name: String,
+    pub attributes: Vec<XmlAttribute>,
+    pub children: Vec<XmlNode>,
+    /// Namespace declarations (`xmlns:prefix="uri"`) made on this element.
+    pub namespace_declarations: Vec<(String, String)>,
+}
+
+impl XmlElement {
+    /// Returns `true` if the element has the given namespace URI and local name.
+    pub fn is(&self, namespace: &str, name: &str) -> bool {
+        self.namespace.as_deref() == Some(namespace) && self.name == name
+    }
+
+    /// Iterate over child elements, skipping text nodes.
+    pub fn elements(&self) -> impl Iterator<Item = &XmlElement> {
+        self.children.iter().filter_map(|c| match c {
+            XmlNode::Element(e) => Some(e),
+            XmlNode::Text(_) => None,
+        })
+    }
+
+    /// Depth-first iterator over this element and all of its descendants.
+    pub fn descendants(&self) -> Vec<&XmlElement> {
+        let mut out = vec![self];
+        let mut i = 0;
+        while i < out.len() {
+            let current = out[i];
+            out.extend(current.elements());
+            i += 1;
+        }
+        out
+    }
+
+    /// Concatenated text content of the element and its descendants.
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        for child in &self.children {
+            match child {
+                XmlNode::Text(t) => out.push_str(t),
+                XmlNode::Element(e) => out.push_str(&e.text()),
+            }
+        }
+        out
+    }
+
+    /// Look up an attribute by namespace URI (or `None` for unqualified) and local name.
+    pub fn attr(&self, namespace: Option<&str>, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.namespace.as_deref() == namespace && a.name == name)
+            .map(|a| a.value.as_str())
+    }
+
+    /// Qualified name as written in the document (`prefix:name`).
+    pub fn qualified_name(&self) -> String {
+        match &self.prefix {
+            Some(p) => format!("{}:{}", p, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+fn resolved_namespace(result: ResolveResult) -> Option<String> {
+    match result {
+        ResolveResult::Bound(ns) => Some(String::from_utf8_lossy(ns.as_ref()).into_owned()),
+        _ => None,
+    }
+}
+
+fn split_prefix(qname: &[u8]) -> (Option<String>, String) {
+    let qname = String::from_utf8_lossy(qname);
+    match qname.split_once(':') {
+        Some((p, n)) => (Some(p.to_string()), n.to_string()),
+        None => (None, qname.into_owned()),
+    }
+}
+
+fn build_element(reader: &NsReader<&[u8]>, start: &BytesStart) -> XmlElement {
+    let (ns, _) = reader.resolver().resolve_element(start.name());
+    let (prefix, name) = split_prefix(start.name().as_ref());
+
+    let mut attributes = Vec::new();
+    let mut namespace_declarations = Vec::new();
+    for attr in start.attributes().with_checks(false).flatten() {
+        let key = attr.key.as_ref();
+        let value = attr
+            .normalized_value(XmlVersion::Implicit1_0)
+            .map(|v| v.into_owned())
+            .unwrap_or_else(|_| String::from_utf8_lossy(&attr.value).into_owned());
+        if key == b"xmlns" {
+            namespace_declarations.push((String::new(), value));
+            continue;
+        }
+        if let Some(declared) = key.strip_prefix(b"xmlns:") {
+            namespace_declarations.push((String::from_utf8_lossy(declared).into_owned(), value));
+            continue;
+        }
+        let (attr_ns, _) = reader.resolver().resolve_attribute(attr.key);
+        let (attr_prefix, attr_name) = split_prefix(key);
+        attributes.push(XmlAttribute {
+            namespace: resolved_namespace(attr_ns),
+            prefix: attr_prefix,
+            name: attr_name,
+            value,
+        });
+    }
+
+    XmlElement {
+        namespace: resolved_namespace(ns),
+        prefix,
+        name,
+        attributes,
+        children: Vec::new(),
+        namespace_declarations,
+    }
+}
+
+/// Append text to an element, joining it to a text node just before it so
+/// that entity and character references do not split the text.
+fn push_text(element: &mut XmlElement, text: &str) {
+    match element.children.last_mut() {
+        Some(XmlNode::Text(last)) => last.push_str(text),
+        _ => element.children.push(XmlNode::Text(text.to_string())),
+    }
+}
+
+/// Parse an XML document and return its root element.
+pub fn parse(xml: &str) -> Result<XmlElement, String> {
+    let mut reader = NsReader::from_str(xml);
+    reader.config_mut().check_end_names = false;
+
+    let mut stack: Vec<XmlElement> = Vec::new();
+    let mut root: Option<XmlElement> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("XML error at byte {}: {}", reader.buffer_position(), e))?;
+        match event {
+            Event::Start(ref start) => {
+                if stack.len() >= MAX_DEPTH {
+                    return Err("XML nesting too deep".to_string());
+                }
+                let element = build_element(&reader, start);
+                stack.push(element);
+            }
+            Event::Empty(ref start) => {
+                let element = build_element(&reader, start);
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(XmlNode::Element(element)),
+                    None if root.is_none() => root = Some(element),
+                    None => {}
+                }
+            }
+            Event::End(_) => {
+                if let Some(element) = stack.pop() {
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(XmlNode::Element(element)),
+                        None if root.is_none() => root = Some(element),
+                        None => {}
+                    }
+                }
+            }
+            Event::Text(text) => {
+                if let Some(parent) = stack.last_mut() {
+                    let value = text
+                        .xml10_content()
+                        .unwrap_or_else(|_| String::from_utf8_lossy(&text));
+                    push_text(parent, &value);
+                }
+            }
+            Event::GeneralRef(reference) => {
+                if let Some(parent) = stack.last_mut() {
+                    let name = String::from_utf8_lossy(&reference);
+                    // Entities other than the predefined ones are kept as written
+                    match reference.resolve_char_ref() {
+                        Ok(Some(c)) => push_text(parent, c.encode_utf8(&mut [0; 4])),
+                        _ => match resolve_predefined_entity(&name) {
+                            Some(value) => push_text(parent, value),
+                            None => push_text(parent, &format!("&{};", name)),
+                        },
+                    }
+                }
+            }
+            Event::CData(data) => {
+                if let Some(parent) = stack.last_mut() {
+                    push_text(parent, &String::from_utf8_lossy(&data));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    // Close any elements left open by a truncated document
+    while let Some(element) = stack.pop() {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(XmlNode::Element(element)),
+            None if root.is_none() => root = Some(element),
+            None => {}
+        }
+    }
+
+    root.ok_or_else(|| "XML document has no root element".to_string())
+}
@@ -0,0 +1,514 @@
+//! XMP packet discovery and RDF parsing.
+//!
+//! XMP is stored as an RDF/XML packet in a container-specific location. This
+//! module finds the packet in JPEG, PNG, WebP, TIFF, GIF, HEIF/AVIF, JPEG XL
+//! and PDF files (and in plain XML documents such as SVG) and flattens the
+//! RDF into properties keyed `XMP-<prefix>:<path>`, e.g. `XMP-dc:creator` or
+//! `XMP-xmpMM:History[1]/action`.
+
+use crate::diagnostics::Diagnostic;
+use crate::types::{MetadataSource, MetadataValue, Rational, RawValue};
+use crate::xml_tree::{self, XmlElement};
+use crate::{container, heif, jxl, limits, pdf};
+use std::collections::HashMap;
+
+/// Signature prefixing the XMP packet in a JPEG APP1 segment.
+pub const JPEG_XMP_SIGNATURE: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+/// Signature prefixing Extended XMP chunks in JPEG APP1 segments.
+pub const JPEG_EXTENDED_XMP_SIGNATURE: &[u8] = b"http://ns.adobe.com/xmp/extension/\0";
+/// PNG iTXt keyword used for XMP packets.
+pub const PNG_XMP_KEYWORD: &[u8] = b"XML:com.adobe.xmp";
+/// GIF application identifier and auth code used for XMP packets.
+pub const GIF_XMP_IDENTIFIER: &[u8; 11] = b"XMP DataXMP";
+/// TIFF tag holding the XMP packet.
+pub const TIFF_XMP_TAG: u16 = 700;
+
+const RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Preferred prefixes for well known XMP namespaces.
+///
+/// Files may bind any prefix to a namespace, so keys use these canonical
+/// prefixes to stay stable across producers.
+pub static NAMESPACE_PREFIXES: &[(&str, &str)] = &[
+    ("http://purl.org/dc/elements/1.1/", "dc"),
+    ("http://ns.adobe.com/xap/1.0/", "xmp"),
+    ("http://ns.adobe.com/xap/1.0/mm/", "xmpMM"),
+    ("http://ns.adobe.com/xap/1.0/rights/", "xmpRights"),
+    ("http://ns.adobe.com/xap/1.0/bj/", "xmpBJ"),
+    ("http://ns.adobe.com/xap/1.0/g/img/", "xmpGImg"),
+    ("http://ns.adobe.com/xap/1.0/sType/ResourceEvent#", "stEvt"),
+    ("http://ns.adobe.com/xap/1.0/sType/ResourceRef#", "stRef"),
+    ("http://ns.adobe.com/photoshop/1.0/", "photoshop"),
+    ("http://ns.adobe.com/camera-raw-settings/1.0/", "crs"),
+    ("http://ns.adobe.com/lightroom/1.0/", "lr"),
+    ("http://ns.adobe.com/exif/1.0/", "exif"),
+    ("http://cipa.jp/exif/1.0/", "exifEX"),
+    ("http://ns.adobe.com/tiff/1.0/", "tiff"),
+    ("http://ns.adobe.com/exif/1.0/aux/", "aux"),
+    ("http://ns.adobe.com/pdf/1.3/", "pdf"),
+    (
+        "http://iptc.org/std/Iptc4xmpCore/1.0/xmlns/",
+        "Iptc4xmpCore",
+    ),
+    ("http://iptc.org/std/Iptc4xmpExt/2008-02-29/", "Iptc4xmpExt"),
+    ("http://ns.useplus.org/ldf/xmp/1.0/", "plus"),
+    ("http://creativecommons.org/ns#", "cc"),
+    ("http://ns.google.com/photos/1.0/camera/", "GCamera"),
+    ("http://ns.google.com/photos/1.0/container/", "Container"),
+    ("http://ns.google.com/photos/1.0/container/item/", "Item"),
+    ("http://ns.google.com/photos/1.0/panorama/", "GPano"),
+    ("http://ns.google.com/photos/1.0/image/", "GImage"),
+    ("http://ns.google.com/photos/1.0/depthmap/", "GDepth"),
+    (
+        "http://ns.apple.com/adjustment-settings/1.0/",
+        "AdjustmentSettings",
+    ),
+    ("http://ns.apple.com/ImageIO/1.0/", "iio"),
+    ("http://ns.apple.com/HDRGainMap/1.0/", "HDRGainMap"),
+    ("http://ns.microsoft.com/photo/1.0/", "MicrosoftPhoto"),
+];
+
+/// A single flattened XMP property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmpProperty {
+    /// Namespace URI of the top-level property.
+    pub namespace: String,
+    /// Canonical (or document) prefix for the namespace.
+    pub prefix: String,
+    /// Property path, e.g. `creator` or `History[2]/when`.
+    pub path: String,
+    pub value: String,
+}
+
+impl XmpProperty {
+    /// The `exif_data` key for this property.
+    pub fn key(&self) -> String {
+        format!("XMP-{}:{}", self.prefix, self.path)
+    }
+}
+
+/// Look up the canonical prefix for a namespace URI.
+pub fn canonical_prefix(namespace: &str) -> Option<&'static str> {
+    NAMESPACE_PREFIXES
+        .iter()
+        .find(|(uri, _)| *uri == namespace)
+        .map(|(_, prefix)| *prefix)
+}
+
+/// Extended XMP chunks of one packet: the full length and `(offset, data)` parts.
+type ExtendedXmp<'a> = (u32, Vec<(u32, &'a [u8])>);
+
+/// Reassemble an Extended XMP packet from its chunks.
+///
+/// Writers that rewrite a file sometimes repeat a chunk, so chunks at the
+/// same offset are kept once. The rest must follow each other without gaps
+/// or overlaps and add up to the declared full length.
+fn reassemble_extended_xmp(
+    full_length: u32,
+    mut parts: Vec<(u32, &[u8])>,
+) -> Result<Vec<u8>, String> {
+    parts.sort_by_key(|(offset, _)| *offset);
+    parts.dedup_by_key(|(offset, _)| *offset);
+    let mut packet = Vec::with_capacity(full_length.min(1 << 24) as usize);
+    for (offset, part) in parts {
+        let offset = offset as usize;
+        if offset > packet.len() {
+            return Err(format!(
+                "bytes {} to {} of {} are missing",
+                packet.len(),
+                offset,
+                full_length
+            ));
+        }
+        if offset < packet.len() {
+            return Err(format!(
+                "the chunk at offset {} overlaps the one before it",
+                offset
+            ));
+        }
+        packet.extend_from_slice(part);
+    }
+    if packet.len() != full_length as usize {
+        return Err(format!(
+            "chunks hold {} bytes, but {} are declared",
+            packet.len(),
+            full_length
+        ));
+    }
+    Ok(packet)
+}
+
+/// Find every XMP packet in a file.
+///
+/// The standard packet comes first, followed by the reassembled Extended XMP
+/// packet of a JPEG if present. Unknown containers are scanned for an
+/// embedded `x:xmpmeta` packet, and XML documents are returned whole so that
+/// namespaces declared on the root element (as SVG editors do) still resolve.
+pub fn find_xmp_packets(bytes: &[u8]) -> Vec<String> {
+    find_xmp_packets_with_diagnostics(bytes, &mut Vec::new())
+}
+
+/// Find every XMP packet as [`find_xmp_packets`] does, reporting Extended
+/// XMP packets that cannot be reassembled. Those packets are skipped.
+pub fn find_xmp_packets_with_diagnostics(
+    bytes: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<String> {
+    let mut packets = Vec::new();
+
+    if container::is_jpeg(bytes) {
+        let mut extended: HashMap<String, ExtendedXmp> = HashMap::new();
+        for segment in container::jpeg_segments(bytes) {
+            if segment.marker != 0xE1 {
+                continue;
+            }
+            if let Some(packet) = segment.data.strip_prefix(JPEG_XMP_SIGNATURE) {
+                packets.push(String::from_utf8_lossy(packet).into_owned());
+            } else if let Some(chunk) = segment.data.strip_prefix(JPEG_EXTENDED_XMP_SIGNATURE)
+                && chunk.len() >= 40
+            {
+                let guid = String::from_utf8_lossy(&chunk[0..32]).into_owned();
+                let full_length = u32::from_be_bytes([chunk[32], chunk[33], chunk[34], chunk[35]]);
+                let offset = u32::from_be_bytes([chunk[36], chunk[37], chunk[38], chunk[39]]);
+                extended
+                    .entry(guid)
+                    .or_insert((full_length, Vec::new()))
+                    .1
+                    .push((offset, &chunk[40..]));
+            }
+        }
+        for (guid, (full_length, parts)) in extended {
+            match reassemble_extended_xmp(full_length, parts) {
+                Ok(packet) => packets.push(String::from_utf8_lossy(&packet).into_owned()),
+                Err(e) => diagnostics.push(Diagnostic::error(
+                    "XMP",
+                    None,
+                    format!("Extended XMP packet {} skipped: {}", guid, e),
+                )),
+            }
+        }
+    } else if container::is_png(bytes) {
+        for chunk in container::png_chunks(bytes) {
+            if &chunk.id == b"iTXt"
+                && let Some(packet) = png_itxt_xmp(chunk.data)
+            {
+                packets.push(packet);
+            }
+        }
+    } else if container::is_webp(bytes) {
+        for chunk in container::riff_chunks(bytes) {
+            if &chunk.id == b"XMP " {
+                packets.push(String::from_utf8_lossy(chunk.data).into_owned());
+            }
+        }
+    } else if container::is_tiff(bytes) {
+        if let Some((order, offset)) = container::tiff_header(bytes)
+            && let Some(ifd0) = container::tiff_directory(bytes, order, offset)
+            && let Some(entry) = ifd0.get(TIFF_XMP_TAG)
+        {
+            packets.push(String::from_utf8_lossy(entry.value).into_owned());
+        }
+    } else if container::is_isobmff(bytes) {
+        packets.extend(heif::find_xmp(bytes));
+    } else if container::is_jxl(bytes) {
+        packets.extend(jxl::find_xmp(bytes));
+    } else if container::is_pdf(bytes) {
+        packets.extend(pdf::find_xmp(bytes));
+        // Damaged files can still hold an uncompressed packet
+        if packets.is_empty()
+            && let Some(packet) = scan_for_packet(bytes)
+        {
+            packets.push(packet);
+        }
+    } else if container::is_gif(bytes) {
+        for ext in container::gif_application_extensions(bytes) {
+            // The packet is stored raw rather than in sub-blocks, followed by
+            // a "magic trailer" that lets ordinary decoders skip over it
+            if &ext.identifier == GIF_XMP_IDENTIFIER
+                && let Some(packet) = scan_for_packet(&bytes[ext.offset..])
+            {
+                packets.push(packet);
+            }
+        }
+    } else if looks_like_xml(bytes) {
+        packets.push(String::from_utf8_lossy(bytes).into_owned());
+    } else if let Some(packet) = scan_for_packet(bytes) {
+        packets.push(packet);
+    }
+
+    packets
+}
+
+/// Extract the XMP packet from a PNG iTXt chunk if it has the XMP keyword.
+fn png_itxt_xmp(data: &[u8]) -> Option<String> {
+    let keyword_end = data.iter().position(|&b| b == 0)?;
+    if &data[..keyword_end] != PNG_XMP_KEYWORD {
+        return None;
+    }
+    let compressed = *data.get(keyword_end + 1)? == 1;
+    // Skip compression method, then the language tag and translated keyword
+    let mut rest = data.get(keyword_end + 3..)?;
+    for _ in 0..2 {
+        let end = rest.iter().position(|&b| b == 0)?;
+        rest = &rest[end + 1..];
+    }
+    if compressed {
+        let mut inflated = Vec::new();
+        limits::read_to_end_limited(
+            flate2::read::ZlibDecoder::new(rest),
+            &mut inflated,
+            limits::MAX_INFLATED_SIZE,
+        )
+        .ok()?;
+        Some(String::from_utf8_lossy(&inflated).into_owned())
+    } else {
+        Some(String::from_utf8_lossy(rest).into_owned())
+    }
+}
+
+fn looks_like_xml(bytes: &[u8]) -> bool {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace());
+    start.is_some_and(|i| bytes[i] == b'<') && std::str::from_utf8(bytes).is_ok()
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Locate an `x:xmpmeta` (or bare `rdf:RDF`) packet inside arbitrary bytes.
+fn scan_for_packet(bytes: &[u8]) -> Option<String> {
+    for (open, close) in [
+        (&b"<x:xmpmeta"[..], &b"</x:xmpmeta>"[..]),
+        (&b"<rdf:RDF"[..], &b"</rdf:RDF>"[..]),
+    ] {
+        if let Some(start) = find_bytes(bytes, open)
+            && let Some(len) = find_bytes(&bytes[start..], close)
+        {
+            let end = start + len + close.len();
+            return Some(String::from_utf8_lossy(&bytes[start..end]).into_owned());
+        }
+    }
+    None
+}
+
+/// Parse an XMP packet (or any XML document containing `rdf:RDF`) into properties.
+pub fn parse_xmp(xml: &str) -> Result<Vec<XmpProperty>, String> {
+    let root = xml_tree::parse(xml.trim_start_matches('\u{feff}'))?;
+    let mut properties = Vec::new();
+
+    for rdf in root
+        .descendants()
+        .into_iter()
+        .filter(|e| e.is(RDF_NS, "RDF"))
+    {
+        for description in rdf.elements() {
+            collect_fields(description, None, &mut properties);
+        }
+    }
+
+    Ok(properties)
+}
+
+fn is_rdf(element: &XmlElement, name: &str) -> bool {
+    element.is(RDF_NS, name)
+}
+
+/// Returns `true` if an attribute is a property rather than RDF/XML syntax.
+fn is_property_attribute(namespace: Option<&str>) -> bool {
+    !matches!(namespace, Some(RDF_NS) | Some(XML_NS) | None)
+}
+
+fn prefix_for(namespace: &str, document_prefix: Option<&str>) -> String {
+    canonical_prefix(namespace)
+        .map(str::to_string)
+        .or_else(|| document_prefix.map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// The property a nested value belongs to.
+#[derive(Clone)]
+struct PropertyRoot {
+    namespace: String,
+    prefix: String,
+    path: String,
+}
+
+impl PropertyRoot {
+    fn child(&self, segment: &str) -> PropertyRoot {
+        PropertyRoot {
+            path: format!("{}/{}", self.path, segment),
+            ..self.clone()
+        }
+    }
+
+    fn push(&self, value: &str, out: &mut Vec<XmpProperty>) {
+        out.push(XmpProperty {
+            namespace: self.namespace.clone(),
+            prefix: self.prefix.clone(),
+            path: self.path.clone(),
+            value: value.to_string(),
+        });
+    }
+}
+
+/// Collect the fields of a description or struct node.
+///
+/// With no `parent` each field starts a new top-level property in its own
+/// namespace; otherwise fields are nested under the parent's path.
+fn collect_fields(node: &XmlElement, parent: Option<&PropertyRoot>, out: &mut Vec<XmpProperty>) {
+    for attr in &node.attributes {
+        if !is_property_attribute(attr.namespace.as_deref()) {
+            continue;
+        }
+        let root = match parent {
+            Some(p) => p.child(&attr.name),
+            None => PropertyRoot {
+                namespace: attr.namespace.clone().unwrap_or_default(),
+                prefix: prefix_for(
+                    attr.namespace.as_deref().unwrap_or_default(),
+                    attr.prefix.as_deref(),
+                ),
+                path: attr.name.clone(),
+            },
+        };
+        root.push(&attr.value, out);
+    }
+
+    for child in node.elements() {
+        if is_rdf(child, "Description") {
+            collect_fields(child, parent, out);
+            continue;
+        }
+        let root = match parent {
+            Some(p) => p.child(&child.name),
+            None => PropertyRoot {
+                namespace: child.namespace.clone().unwrap_or_default(),
+                prefix: prefix_for(
+                    child.namespace.as_deref().unwrap_or_default(),
+                    child.prefix.as_deref(),
+                ),
+                path: child.name.clone(),
+            },
+        };
+        collect_value(child, &root, out);
+    }
+}
+
+/// A node is simple when it holds only text (or an `rdf:resource` URI).
+fn is_simple(node: &XmlElement) -> bool {
+    node.elements().next().is_none()
+        && node.attr(Some(RDF_NS), "parseType").is_none()
+        && !node
+            .attributes
+            .iter()
+            .any(|a| is_property_attribute(a.namespace.as_deref()))
+}
+
+fn simple_value(node: &XmlElement) -> String {
+    node.attr(Some(RDF_NS), "resource")
+        .map(str::to_string)
+        .unwrap_or_else(|| node.text().trim().to_string())
+}
+
+/// Collect the value of a property element (or array item).
+fn collect_value(node: &XmlElement, root: &PropertyRoot, out: &mut Vec<XmpProperty>) {
+    let array = node
+        .elements()
+        .find(|e| is_rdf(e, "Bag") || is_rdf(e, "Seq") || is_rdf(e, "Alt"));
+
+    if let Some(array) = array {
+        let items: Vec<&XmlElement> = array.elements().filter(|e| is_rdf(e, "li")).collect();
+        if items.iter().all(|item| is_simple(item)) {
+            let value = if is_rdf(array, "Alt") {
+                items
+                    .iter()
+                    .find(|item| item.attr(Some(XML_NS), "lang") == Some("x-default"))
+                    .or(items.first())
+                    .map(|item| simple_value(item))
+                    .unwrap_or_default()
+            } else {
+                items
+                    .iter()
+                    .map(|item| simple_value(item))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            };
+            root.push(&value, out);
+        } else {
+            for (i, item) in items.iter().enumerate() {
+                let item_root = PropertyRoot {
+                    path: format!("{}[{}]", root.path, i + 1),
+                    ..root.clone()
+                };
+                collect_value(item, &item_root, out);
+            }
+        }
+        return;
+    }
+
+    if is_simple(node) {
+        root.push(&simple_value(node), out);
+    } else {
+        collect_fields(node, Some(root), out);
+    }
+}
+
+/// Convert a property's text into a typed value.
+///
+/// Only `n/d` rationals are typed; everything else stays text so values like
+/// serial numbers keep their leading zeros.
+fn xmp_value(property: &XmpProperty) -> MetadataValue {
+    let value = &property.value;
+    let rational = value.split_once('/').and_then(|(n, d)| {
+        Some(Rational {
+            num: n.trim().parse().ok()?,
+            denom: d.trim().parse().ok()?,
+        })
+    });
+    let metadata = match rational {
+        Some(r) => MetadataValue::new(RawValue::Rational(vec![r]), value.clone()),
+        None => MetadataValue::text(value.clone()),
+    };
+    metadata.with_source(MetadataSource::Xmp {
+        namespace: property.namespace.clone(),
+    })
+}
+
+/// Extract all XMP properties from a file as `exif_data` entries.
+///
+/// When several packets define the same property the first one wins, so the
+/// standard packet takes precedence over Extended XMP.
+pub fn extract_xmp_data(bytes: &[u8]) -> HashMap<String, MetadataValue> {
+    extract_xmp_data_with_diagnostics(bytes, &mut Vec::new())
+}
+
+/// Extract all XMP properties as [`extract_xmp_data`] does, reporting
+/// packets that cannot be reassembled or parsed.
+pub fn extract_xmp_data_with_diagnostics(
+    bytes: &[u8],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashMap<String, MetadataValue> {
+    let mut map = HashMap::new();
+    for packet in find_xmp_packets_with_diagnostics(bytes, diagnostics) {
+        let properties = match parse_xmp(&packet) {
+            Ok(properties) => properties,
+            // A whole XML document is reported by the SVG extractor
+            Err(_) if looks_like_xml(bytes) => continue,
+            Err(e) => {
+                let head = &packet.as_bytes()[..packet.len().min(64)];
+                let offset = (!head.is_empty())
+                    .then(|| find_bytes(bytes, head))
+                    .flatten();
+                diagnostics.push(Diagnostic::error("XMP", offset, e));
+                continue;
+            }
+        };
+        for property in properties {
+            map.entry(property.key())
+                .or_insert_with(|| xmp_value(&property));
+        }
+    }
+    map
+}
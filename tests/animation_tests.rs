@@ -0,0 +1,296 @@
+use image_metadata_extractor::animation::{
+    animation_to_metadata, parse_animation, parse_apng, parse_gif, parse_webp,
+};
+use image_metadata_extractor::binary_cleaner::BinaryCleaner;
+use image_metadata_extractor::exif_core::extract_metadata;
+
+/// Graphic control extension with a delay in hundredths of a second.
+fn gif_control(delay: u16, disposal: u8) -> Vec<u8> {
+    let mut block = vec![0x21, 0xF9, 0x04, disposal << 2];
+    block.extend_from_slice(&delay.to_le_bytes());
+    block.extend_from_slice(&[0x00, 0x00]);
+    block
+}
+
+fn gif_image(x: u16, y: u16, width: u16, height: u16) -> Vec<u8> {
+    let mut block = vec![0x2C];
+    for value in [x, y, width, height] {
+        block.extend_from_slice(&value.to_le_bytes());
+    }
+    block.push(0x00); // no local colour table
+    block.extend_from_slice(&[0x02, 0x02, 0x44, 0x01, 0x00]); // LZW data
+    block
+}
+
+fn gif_comment(text: &str) -> Vec<u8> {
+    let mut block = vec![0x21, 0xFE, text.len() as u8];
+    block.extend_from_slice(text.as_bytes());
+    block.push(0x00);
+    block
+}
+
+fn gif_loop(count: u16) -> Vec<u8> {
+    let mut block = vec![0x21, 0xFF, 0x0B];
+    block.extend_from_slice(b"NETSCAPE2.0");
+    block.extend_from_slice(&[0x03, 0x01]);
+    block.extend_from_slice(&count.to_le_bytes());
+    block.push(0x00);
+    block
+}
+
+/// Two-frame 4×4 GIF looping forever, with a comment before each frame.
+fn animated_gif() -> Vec<u8> {
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend_from_slice(&[0x04, 0x00, 0x04, 0x00, 0x80, 0x00, 0x00]);
+    gif.extend_from_slice(&[0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF]); // global colours
+    gif.extend(gif_loop(0));
+    gif.extend(gif_comment("made by Alice"));
+    gif.extend(gif_control(10, 1));
+    gif.extend(gif_image(0, 0, 4, 4));
+    gif.extend(gif_comment("second frame"));
+    gif.extend(gif_control(25, 2));
+    gif.extend(gif_image(1, 2, 2, 2));
+    gif.push(0x3B);
+    gif
+}
+
+fn png_chunk(id: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut chunk = (payload.len() as u32).to_be_bytes().to_vec();
+    chunk.extend_from_slice(id);
+    chunk.extend_from_slice(payload);
+    chunk.extend_from_slice(&[0, 0, 0, 0]); // CRC is not checked
+    chunk
+}
+
+fn fctl(
+    sequence: u32,
+    width: u32,
+    height: u32,
+    delay: (u16, u16),
+    dispose: u8,
+    blend: u8,
+) -> Vec<u8> {
+    let mut data = sequence.to_be_bytes().to_vec();
+    for value in [width, height, 0, 0] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    data.extend_from_slice(&delay.0.to_be_bytes());
+    data.extend_from_slice(&delay.1.to_be_bytes());
+    data.extend_from_slice(&[dispose, blend]);
+    png_chunk(b"fcTL", &data)
+}
+
+/// 8×8 APNG declaring three frames and playing twice.
+fn apng(declared: u32) -> Vec<u8> {
+    let mut png = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+    let mut ihdr = 8u32.to_be_bytes().to_vec();
+    ihdr.extend_from_slice(&8u32.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+    png.extend(png_chunk(b"IHDR", &ihdr));
+    let mut actl = declared.to_be_bytes().to_vec();
+    actl.extend_from_slice(&2u32.to_be_bytes());
+    png.extend(png_chunk(b"acTL", &actl));
+    png.extend(png_chunk(b"tEXt", b"Comment\0secret"));
+    png.extend(fctl(0, 8, 8, (1, 10), 0, 0));
+    png.extend(png_chunk(b"IDAT", &[0x78, 0x9C, 0x03, 0x00]));
+    png.extend(fctl(1, 4, 4, (50, 0), 1, 1));
+    png.extend(png_chunk(b"fdAT", &[0, 0, 0, 2, 0x78, 0x9C, 0x03, 0x00]));
+    png.extend(fctl(3, 4, 4, (33, 1000), 2, 0));
+    png.extend(png_chunk(b"fdAT", &[0, 0, 0, 4, 0x78, 0x9C, 0x03, 0x00]));
+    png.extend(png_chunk(b"IEND", &[]));
+    png
+}
+
+fn riff_chunk(id: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut chunk = id.to_vec();
+    chunk.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    chunk.extend_from_slice(payload);
+    if payload.len() % 2 == 1 {
+        chunk.push(0);
+    }
+    chunk
+}
+
+fn le24(value: u32) -> [u8; 3] {
+    let bytes = value.to_le_bytes();
+    [bytes[0], bytes[1], bytes[2]]
+}
+
+fn anmf(x: u32, y: u32, width: u32, height: u32, delay: u32, flags: u8) -> Vec<u8> {
+    let mut data = Vec::new();
+    for value in [x / 2, y / 2, width - 1, height - 1, delay] {
+        data.extend_from_slice(&le24(value));
+    }
+    data.push(flags);
+    data.extend(riff_chunk(b"VP8L", &[0x2F, 0x00, 0x00, 0x00, 0x00]));
+    riff_chunk(b"ANMF", &data)
+}
+
+/// 16×16 animated WebP with EXIF and XMP flagged in VP8X.
+fn animated_webp() -> Vec<u8> {
+    let mut vp8x = vec![0x02 | 0x08 | 0x04, 0, 0, 0];
+    vp8x.extend_from_slice(&le24(15));
+    vp8x.extend_from_slice(&le24(15));
+    let mut body = b"WEBP".to_vec();
+    body.extend(riff_chunk(b"VP8X", &vp8x));
+    body.extend(riff_chunk(b"ANIM", &[0, 0, 0, 0, 3, 0]));
+    body.extend(anmf(0, 0, 16, 16, 100, 0x00));
+    body.extend(anmf(4, 6, 8, 8, 40, 0x03));
+    body.extend(riff_chunk(b"EXIF", b"Exif\0\0MM"));
+    body.extend(riff_chunk(b"XMP ", b"<x:xmpmeta/>"));
+    let mut webp = b"RIFF".to_vec();
+    webp.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    webp.extend(body);
+    webp
+}
+
+#[test]
+fn gif_frames_report_delays_disposal_and_rectangles() {
+    let animation = parse_gif(&animated_gif()).unwrap();
+    assert_eq!(animation.format, "GIF");
+    assert_eq!((animation.canvas_width, animation.canvas_height), (4, 4));
+    assert_eq!(animation.loop_count, Some(0));
+    assert_eq!(animation.frames.len(), 2);
+    assert!(animation.is_animated());
+    assert_eq!(animation.total_duration_ms(), 350);
+
+    let second = &animation.frames[1];
+    assert_eq!(
+        (second.x, second.y, second.width, second.height),
+        (1, 2, 2, 2)
+    );
+    assert_eq!(second.delay_ms, 250);
+    assert_eq!(second.disposal, "Background");
+    assert_eq!(second.blend, None);
+    assert_eq!(animation.frames[0].disposal, "None");
+    assert_eq!(animation.frames[0].comments, vec!["made by Alice"]);
+    assert_eq!(second.comments, vec!["second frame"]);
+}
+
+#[test]
+fn gif_metadata_lists_every_comment_and_timing() {
+    let (metadata, _) = extract_metadata(&animated_gif());
+    assert_eq!(metadata["GIF:Comment1"].display, "made by Alice");
+    assert_eq!(metadata["GIF:Comment2"].display, "second frame");
+    assert_eq!(metadata["Animation:FrameCount"].display, "2");
+    assert_eq!(metadata["Animation:Duration"].display, "0.35 s");
+    assert_eq!(metadata["Animation:LoopCount"].display, "Infinite");
+    assert_eq!(metadata["Animation:FrameDelays"].display, "100, 250 ms");
+    assert!(!metadata.contains_key("Animation:DeclaredFrameCount"));
+}
+
+#[test]
+fn still_gif_reports_comment_without_animation_keys() {
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend_from_slice(&[0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00]);
+    gif.extend(gif_comment("only one"));
+    gif.extend(gif_image(0, 0, 1, 1));
+    gif.push(0x3B);
+
+    let animation = parse_animation(&gif).unwrap();
+    assert!(!animation.is_animated());
+    let metadata = animation_to_metadata(&animation);
+    assert_eq!(metadata["GIF:Comment"].display, "only one");
+    assert!(metadata.keys().all(|k| !k.starts_with("Animation:")));
+}
+
+#[test]
+fn apng_frames_come_from_actl_and_fctl() {
+    let animation = parse_apng(&apng(3)).unwrap();
+    assert_eq!(animation.format, "APNG");
+    assert_eq!((animation.canvas_width, animation.canvas_height), (8, 8));
+    assert_eq!(animation.loop_count, Some(2));
+    assert_eq!(animation.declared_frames, Some(3));
+
+    let delays: Vec<u32> = animation.frames.iter().map(|f| f.delay_ms).collect();
+    // A zero denominator means hundredths of a second
+    assert_eq!(delays, vec![100, 500, 33]);
+    assert_eq!(animation.frames[1].disposal, "Background");
+    assert_eq!(animation.frames[1].blend.as_deref(), Some("Over"));
+    assert_eq!(animation.frames[2].disposal, "Previous");
+    assert_eq!(animation.frames[2].blend.as_deref(), Some("Source"));
+}
+
+#[test]
+fn apng_reports_declared_frame_mismatch() {
+    let metadata = animation_to_metadata(&parse_apng(&apng(5)).unwrap());
+    assert_eq!(metadata["Animation:FrameCount"].display, "3");
+    assert_eq!(metadata["Animation:DeclaredFrameCount"].display, "5");
+    assert_eq!(metadata["Animation:LoopCount"].display, "2");
+
+    let metadata = animation_to_metadata(&parse_apng(&apng(3)).unwrap());
+    assert!(!metadata.contains_key("Animation:DeclaredFrameCount"));
+}
+
+#[test]
+fn plain_png_is_not_an_animation() {
+    let mut png = apng(1);
+    png.drain(33..33 + 20); // drop acTL, which follows the 25-byte IHDR chunk
+    assert!(parse_apng(&png).is_none());
+}
+
+#[test]
+fn webp_frames_come_from_anim_and_anmf() {
+    let animation = parse_webp(&animated_webp()).unwrap();
+    assert_eq!(animation.format, "WebP");
+    assert_eq!((animation.canvas_width, animation.canvas_height), (16, 16));
+    assert_eq!(animation.loop_count, Some(3));
+    assert_eq!(animation.frames.len(), 2);
+
+    let second = &animation.frames[1];
+    assert_eq!(
+        (second.x, second.y, second.width, second.height),
+        (4, 6, 8, 8)
+    );
+    assert_eq!(second.delay_ms, 40);
+    assert_eq!(second.disposal, "Background");
+    assert_eq!(second.blend.as_deref(), Some("Source"));
+    assert_eq!(animation.frames[0].blend.as_deref(), Some("Over"));
+}
+
+#[test]
+fn clean_gif_keeps_loop_and_timing_but_drops_comments() {
+    let cleaned = BinaryCleaner::clean_metadata(&animated_gif(), "gif").unwrap();
+    let animation = parse_gif(&cleaned).unwrap();
+    assert_eq!(animation.loop_count, Some(0));
+    assert_eq!(animation.frames.len(), 2);
+    assert_eq!(animation.total_duration_ms(), 350);
+    assert_eq!(animation.comments().count(), 0);
+    assert!(!cleaned.windows(12).any(|w| w == b"second frame"));
+    assert_eq!(cleaned.last(), Some(&0x3B));
+}
+
+#[test]
+fn clean_gif_rewrites_loop_extension_without_extra_sub_blocks() {
+    let mut gif = animated_gif();
+    let start = gif.windows(11).position(|w| w == b"NETSCAPE2.0").unwrap() + 11;
+    // Smuggle a second data sub-block into the looping extension
+    gif.splice(start + 4..start + 4, [0x05, b'h', b'i', b'd', b'e', b'n']);
+    assert_eq!(parse_gif(&gif).unwrap().loop_count, Some(0));
+
+    let cleaned = BinaryCleaner::clean_metadata(&gif, "gif").unwrap();
+    assert!(!cleaned.windows(5).any(|w| w == b"hiden"));
+    assert_eq!(parse_gif(&cleaned).unwrap().loop_count, Some(0));
+}
+
+#[test]
+fn clean_webp_clears_metadata_flags_and_keeps_frames() {
+    let cleaned = BinaryCleaner::clean_metadata(&animated_webp(), "webp").unwrap();
+    assert!(!cleaned.windows(4).any(|w| w == b"EXIF" || w == b"XMP "));
+    let flags = cleaned[20];
+    assert_eq!(flags & (0x20 | 0x08 | 0x04), 0);
+    assert_ne!(flags & 0x02, 0, "animation flag must survive");
+
+    let animation = parse_webp(&cleaned).unwrap();
+    assert_eq!(animation.frames.len(), 2);
+    assert_eq!(animation.loop_count, Some(3));
+}
+
+#[test]
+fn clean_apng_keeps_animation_chunks() {
+    let cleaned = BinaryCleaner::clean_metadata(&apng(3), "png").unwrap();
+    assert!(!cleaned.windows(6).any(|w| w == b"secret"));
+    let animation = parse_apng(&cleaned).unwrap();
+    assert_eq!(animation.frames.len(), 3);
+    assert_eq!(animation.loop_count, Some(2));
+}
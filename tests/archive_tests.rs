@@ -198,6 +198,9 @@ fn test_is_image_file_all_supported_formats() {
         "test.jxl",
         "test.svg",
         "test.pdf",
+        "test.mp4",
+        "test.mov",
+        "test.3gp",
     ];
 
     for filename in &image_files {
@@ -210,7 +213,7 @@ fn test_is_image_file_all_supported_formats() {
         "test.doc",
         "test.zip",
         "test.rar",
-        "test.mp4",
+        "test.avi",
         "test.mp3",
         "test.html",
         "test.css",
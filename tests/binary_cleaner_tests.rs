@@ -1,4 +1,7 @@
+#![allow(clippy::byte_char_slices, clippy::unnecessary_unwrap)]
+
 use image_metadata_extractor::binary_cleaner::BinaryCleaner;
+use image_metadata_extractor::file_type::detect_mime_type;
 
 // JPEG Tests
 #[test]
@@ -257,6 +260,42 @@ fn clean_tiff_alternative_extension() {
     );
 }
 
+#[test]
+fn clean_tiff_keeps_pixels_and_drops_metadata() {
+    use image_metadata_extractor::container::{tiff_directory, tiff_header};
+    use tiff::encoder::{TiffEncoder, colortype};
+    use tiff::tags::Tag;
+
+    let pixels: Vec<u8> = (0..2 * 2 * 3).map(|n| n as u8 * 20).collect();
+    let mut tiff = Vec::new();
+    {
+        let mut encoder = TiffEncoder::new(std::io::Cursor::new(&mut tiff)).unwrap();
+        let mut image = encoder.new_image::<colortype::RGB8>(2, 2).unwrap();
+        image.encoder().write_tag(Tag::Artist, "Jane Doe").unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::Unknown(700), "<x:xmpmeta/>")
+            .unwrap();
+        image.write_data(&pixels).unwrap();
+    }
+
+    let cleaned = BinaryCleaner::clean_metadata(&tiff, "tiff").unwrap();
+    assert!(!cleaned.windows(8).any(|w| w == b"Jane Doe"));
+    assert!(!cleaned.windows(9).any(|w| w == b"xmpmeta/>"));
+
+    let (order, offset) = tiff_header(&cleaned).unwrap();
+    let ifd = tiff_directory(&cleaned, order, offset).unwrap();
+    assert!(ifd.get(315).is_none() && ifd.get(700).is_none());
+    assert_eq!(ifd.next_offset, 0);
+
+    let mut decoder = tiff::decoder::Decoder::new(std::io::Cursor::new(&cleaned)).unwrap();
+    assert_eq!(decoder.dimensions().unwrap(), (2, 2));
+    match decoder.read_image().unwrap() {
+        tiff::decoder::DecodingResult::U8(decoded) => assert_eq!(decoded, pixels),
+        other => panic!("unexpected sample type: {:?}", other),
+    }
+}
+
 // HEIF Tests
 #[test]
 fn clean_heif_metadata_basic() {
@@ -327,6 +366,11 @@ fn clean_pdf_metadata_basic_validation() {
     assert_eq!(cleaned, pdf);
     let bad = BinaryCleaner::clean_metadata(b"not a pdf", "pdf");
     assert!(bad.is_err());
+
+    // Leading junk is accepted here as it is by type detection
+    let prefixed = b"\r\n\0\0%PDF-1.4\n%1234";
+    assert_eq!(detect_mime_type(prefixed), Some("application/pdf"));
+    assert!(BinaryCleaner::clean_metadata(prefixed, "pdf").is_ok());
 }
 
 // Unimplemented format tests
@@ -471,8 +515,9 @@ fn clean_png_chunk_length_extends_beyond_file() {
     ]);
     png.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]); // CRC
 
-    // Chunk with length that extends beyond file
-    png.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, b't', b'E', b'X', b't']); // Huge length
+    // Chunk with length that extends beyond file (lengths over 2^31 - 1 are
+    // rejected outright; see limits_tests)
+    png.extend_from_slice(&[0x00, 0x01, 0x00, 0x00, b't', b'E', b'X', b't']); // 64 KiB length
     png.extend_from_slice(&[0x01, 0x02]); // Only 2 bytes of data
 
     let result = BinaryCleaner::clean_metadata(&png, "png");
@@ -587,10 +632,10 @@ fn clean_gif_application_extension_with_sub_blocks() {
 
     // Application extension with multiple sub-blocks
     gif.extend_from_slice(&[0x21, 0xFF, 0x0B]); // Application extension + block size
-    gif.extend_from_slice(b"NETSCAPE2.0"); // Application identifier
+    gif.extend_from_slice(b"ICCRGBG1012"); // Application identifier
 
     // Sub-block 1
-    gif.extend_from_slice(&[0x03, 0x01, 0x00, 0x00]); // 3 bytes: loop forever
+    gif.extend_from_slice(&[0x03, 0x01, 0x00, 0x00]); // 3 bytes
 
     // Sub-block 2
     gif.extend_from_slice(&[0x05, b'h', b'e', b'l', b'l', b'o']); // 5 bytes of data
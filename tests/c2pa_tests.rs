@@ -0,0 +1,771 @@
+use base64::Engine as _;
+use image_metadata_extractor::binary_cleaner::BinaryCleaner;
+use image_metadata_extractor::c2pa::{
+    Cbor, CredentialState, KeyType, Operation, Outcome, SignatureScheme, assess, cleaning_warning,
+    decode_cbor, encode_cbor, find_manifest_store, jumbf_type, parse_pem_certificates,
+    plan_verification, read_manifest_store, sig_structure,
+};
+use image_metadata_extractor::diagnostics::diagnose;
+use image_metadata_extractor::exif_core::extract_metadata;
+
+// --- JUMBF ---------------------------------------------------------------
+
+fn jbox(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut out = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(payload);
+    out
+}
+
+fn superbox(kind: &[u8; 4], label: &str, children: &[Vec<u8>]) -> Vec<u8> {
+    let mut jumd = jumbf_type(*kind).to_vec();
+    jumd.push(0x03); // requestable, labelled
+    jumd.extend_from_slice(label.as_bytes());
+    jumd.push(0);
+    let mut payload = jbox(b"jumd", &jumd);
+    for child in children {
+        payload.extend_from_slice(child);
+    }
+    jbox(b"jumb", &payload)
+}
+
+/// Payload of a superbox, which assertion hashes cover.
+fn payload(superbox: &[u8]) -> Vec<u8> {
+    superbox[8..].to_vec()
+}
+
+fn text(s: &str) -> Cbor {
+    Cbor::Text(s.to_string())
+}
+
+fn map(entries: Vec<(&str, Cbor)>) -> Cbor {
+    Cbor::Map(entries.into_iter().map(|(k, v)| (text(k), v)).collect())
+}
+
+fn int(n: i128) -> Cbor {
+    Cbor::Integer(n)
+}
+
+// --- DER -----------------------------------------------------------------
+
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    match content.len() {
+        n if n < 0x80 => out.push(n as u8),
+        n if n < 0x100 => out.extend_from_slice(&[0x81, n as u8]),
+        n => out.extend_from_slice(&[0x82, (n >> 8) as u8, n as u8]),
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+fn seq(parts: &[Vec<u8>]) -> Vec<u8> {
+    der(0x30, &parts.concat())
+}
+
+const ECDSA_SHA256: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x02];
+const EC_PUBLIC_KEY: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01];
+const P256: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07];
+
+fn cn(name: &str) -> Vec<u8> {
+    der(
+        0x30,
+        &der(
+            0x31,
+            &seq(&[der(0x06, &[0x55, 0x04, 0x03]), der(0x0C, name.as_bytes())]),
+        ),
+    )
+}
+
+/// An extension with an `OID` in `id` and a DER `value`.
+fn extension(id: &[u8], value: Vec<u8>) -> Vec<u8> {
+    seq(&[der(0x06, id), der(0x01, &[0xFF]), der(0x04, &value)])
+}
+
+/// Basic constraints with `cA` set and key usage `keyCertSign, cRLSign`.
+fn ca_extensions() -> Vec<Vec<u8>> {
+    vec![
+        extension(&[0x55, 0x1D, 0x13], seq(&[der(0x01, &[0xFF])])),
+        extension(&[0x55, 0x1D, 0x0F], der(0x03, &[0x01, 0x06])),
+    ]
+}
+
+/// Key usage `digitalSignature` and the C2PA claim signing extended key
+/// usage, 1.3.6.1.4.1.62558.2.1.
+fn signer_extensions() -> Vec<Vec<u8>> {
+    vec![
+        extension(&[0x55, 0x1D, 0x0F], der(0x03, &[0x07, 0x80])),
+        extension(
+            &[0x55, 0x1D, 0x25],
+            seq(&[der(
+                0x06,
+                &[0x2B, 0x06, 0x01, 0x04, 0x01, 0x83, 0xE8, 0x5E, 0x02, 0x01],
+            )]),
+        ),
+    ]
+}
+
+/// A P-256 certificate for `subject` issued by `issuer`, valid 2020-2040,
+/// with an ECDSA signature whose `r` has a leading zero byte.
+fn certificate(subject: &str, issuer: &str, key_byte: u8, extensions: &[Vec<u8>]) -> Vec<u8> {
+    certificate_until(subject, issuer, key_byte, "20400101000000Z", extensions)
+}
+
+/// A certificate as [`certificate`] builds it that expires at `not_after`,
+/// a `GeneralizedTime`.
+fn certificate_until(
+    subject: &str,
+    issuer: &str,
+    key_byte: u8,
+    not_after: &str,
+    extensions: &[Vec<u8>],
+) -> Vec<u8> {
+    let mut point = vec![0x00, 0x04];
+    point.extend_from_slice(&[key_byte; 64]);
+    let tbs = seq(&[
+        der(0xA0, &der(0x02, &[2])),
+        der(0x02, &[1]),
+        seq(&[der(0x06, ECDSA_SHA256)]),
+        cn(issuer),
+        seq(&[der(0x17, b"200101000000Z"), der(0x18, not_after.as_bytes())]),
+        cn(subject),
+        seq(&[
+            seq(&[der(0x06, EC_PUBLIC_KEY), der(0x06, P256)]),
+            der(0x03, &point),
+        ]),
+        der(0xA3, &seq(extensions)),
+    ]);
+    let mut r = vec![0x00, 0x80];
+    r.extend_from_slice(&[0x11; 31]);
+    let signature = seq(&[der(0x02, &r), der(0x02, &[0x22; 20])]);
+    let mut bits = vec![0];
+    bits.extend_from_slice(&signature);
+    seq(&[tbs, seq(&[der(0x06, ECDSA_SHA256)]), der(0x03, &bits)])
+}
+
+fn pem(der: &[u8]) -> String {
+    format!(
+        "-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n",
+        base64::engine::general_purpose::STANDARD.encode(der)
+    )
+}
+
+// --- Manifest store --------------------------------------------------------
+
+const MANIFEST: &str = "urn:uuid:0d1b7c1c-3d2f-4c6a-8e5b-1f2a3b4c5d6e";
+
+struct Fixture {
+    /// Superbox of the manifest store.
+    store: Vec<u8>,
+    claim: Vec<u8>,
+    protected: Vec<u8>,
+    assertions: Vec<(String, Vec<u8>)>,
+}
+
+/// The chain the fixture manifest is signed with: a signer and the
+/// intermediate CA that issued it.
+fn signing_chain() -> Vec<Vec<u8>> {
+    vec![
+        certificate("Signer", "Test CA", 0x05, &signer_extensions()),
+        certificate("Test CA", "Test Root", 0x06, &ca_extensions()),
+    ]
+}
+
+/// The self-signed root the fixture chain leads to.
+fn root() -> Vec<u8> {
+    certificate("Test Root", "Test Root", 0x07, &ca_extensions())
+}
+
+/// A manifest store whose data hash excludes `exclusion` and whose claim
+/// lists hashes `0x01…`, `0x02…` and `0x03…` for its three assertions.
+fn manifest_store(exclusion: (i128, i128)) -> Fixture {
+    manifest_store_signed_by(exclusion, signing_chain())
+}
+
+/// A manifest store as [`manifest_store`] builds it, signed with `chain`.
+fn manifest_store_signed_by(exclusion: (i128, i128), chain: Vec<Vec<u8>>) -> Fixture {
+    let actions = superbox(
+        b"cbor",
+        "c2pa.actions.v2",
+        &[jbox(
+            b"cbor",
+            &encode_cbor(&map(vec![(
+                "actions",
+                Cbor::Array(vec![
+                    map(vec![
+                        ("action", text("c2pa.created")),
+                        ("softwareAgent", map(vec![("name", text("Image Maker"))])),
+                        (
+                            "digitalSourceType",
+                            text(
+                                "http://cv.iptc.org/newscodes/digitalsourcetype/trainedAlgorithmicMedia",
+                            ),
+                        ),
+                    ]),
+                    map(vec![
+                        ("action", text("c2pa.cropped")),
+                        ("when", text("2025-03-01T10:00:00Z")),
+                    ]),
+                ]),
+            )])),
+        )],
+    );
+    let ingredient = superbox(
+        b"json",
+        "c2pa.ingredient",
+        &[jbox(
+            b"json",
+            br#"{"dc:title":"original.jpg","dc:format":"image/jpeg","relationship":"parentOf"}"#,
+        )],
+    );
+    let hash_data = superbox(
+        b"cbor",
+        "c2pa.hash.data",
+        &[jbox(
+            b"cbor",
+            &encode_cbor(&map(vec![
+                (
+                    "exclusions",
+                    Cbor::Array(vec![map(vec![
+                        ("start", int(exclusion.0)),
+                        ("length", int(exclusion.1)),
+                    ])]),
+                ),
+                ("name", text("jumbf manifest")),
+                ("alg", text("sha256")),
+                ("hash", Cbor::Bytes(vec![0xAB; 32])),
+            ])),
+        )],
+    );
+    let assertions = vec![
+        ("c2pa.actions.v2".to_string(), payload(&actions)),
+        ("c2pa.ingredient".to_string(), payload(&ingredient)),
+        ("c2pa.hash.data".to_string(), payload(&hash_data)),
+    ];
+    let store_box = superbox(
+        b"c2as",
+        "c2pa.assertions",
+        &[actions, ingredient, hash_data],
+    );
+
+    let references = assertions
+        .iter()
+        .enumerate()
+        .map(|(n, (label, _))| {
+            map(vec![
+                (
+                    "url",
+                    text(&format!("self#jumbf=c2pa.assertions/{}", label)),
+                ),
+                ("hash", Cbor::Bytes(vec![n as u8 + 1; 32])),
+            ])
+        })
+        .collect();
+    let claim = encode_cbor(&map(vec![
+        ("claim_generator", text("Image Maker/2.1")),
+        ("dc:title", text("sunset.jpg")),
+        ("dc:format", text("image/jpeg")),
+        ("instanceID", text("xmp:iid:1234")),
+        ("signature", text("self#jumbf=c2pa.signature")),
+        ("assertions", Cbor::Array(references)),
+        ("alg", text("sha256")),
+    ]));
+    let claim_box = superbox(b"c2cl", "c2pa.claim", &[jbox(b"cbor", &claim)]);
+
+    let protected = encode_cbor(&Cbor::Map(vec![
+        (int(1), int(-7)),
+        (
+            int(33),
+            Cbor::Array(chain.into_iter().map(Cbor::Bytes).collect()),
+        ),
+    ]));
+    let cose = encode_cbor(&Cbor::Tag(
+        18,
+        Box::new(Cbor::Array(vec![
+            Cbor::Bytes(protected.clone()),
+            Cbor::Map(Vec::new()),
+            Cbor::Null,
+            Cbor::Bytes(vec![0x5A; 64]),
+        ])),
+    ));
+    let signature_box = superbox(b"c2cs", "c2pa.signature", &[jbox(b"cbor", &cose)]);
+
+    let manifest = superbox(b"c2ma", MANIFEST, &[store_box, claim_box, signature_box]);
+    Fixture {
+        store: superbox(b"c2pa", "c2pa", &[manifest]),
+        claim,
+        protected,
+        assertions,
+    }
+}
+
+/// A JPEG with the store split over two APP11 segments after SOI. Returns
+/// the file and the fixture; the data hash excludes the APP11 segments.
+fn jpeg_with_store() -> (Vec<u8>, Fixture) {
+    let build = |exclusion| {
+        let fixture = manifest_store(exclusion);
+        let (first, rest) = fixture.store.split_at(fixture.store.len() / 2);
+        let mut jpeg = vec![0xFF, 0xD8];
+        for (sequence, part) in [
+            (1u32, first.to_vec()),
+            (2, [&fixture.store[..8], rest].concat()),
+        ] {
+            let mut segment = b"JP".to_vec();
+            segment.extend_from_slice(&1u16.to_be_bytes());
+            segment.extend_from_slice(&sequence.to_be_bytes());
+            segment.extend_from_slice(&part);
+            jpeg.extend_from_slice(&[0xFF, 0xEB]);
+            jpeg.extend_from_slice(&((segment.len() + 2) as u16).to_be_bytes());
+            jpeg.extend_from_slice(&segment);
+        }
+        let app11_len = jpeg.len() - 2;
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0x34, 0xFF, 0xD9]);
+        (jpeg, fixture, app11_len)
+    };
+    // The exclusion length does not change the store size within 16 bits
+    let (_, _, app11_len) = build((2, 0x1000));
+    let (jpeg, fixture, check) = build((2, app11_len as i128));
+    assert_eq!(check, app11_len);
+    (jpeg, fixture)
+}
+
+fn png_with_store(store: &[u8]) -> Vec<u8> {
+    let mut png = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+    for (id, data) in [
+        (b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 0][..]),
+        (b"caBX", store),
+        (b"IDAT", &[0x78, 0x9C, 0x03, 0x00]),
+        (b"IEND", &[]),
+    ] {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        png.extend_from_slice(id);
+        png.extend_from_slice(data);
+        png.extend_from_slice(&[0, 0, 0, 0]);
+    }
+    png
+}
+
+// --- Tests -----------------------------------------------------------------
+
+#[test]
+fn cbor_round_trips_and_decodes_indefinite_lengths() {
+    let item = Cbor::Map(vec![
+        (text("n"), int(-500)),
+        (text("big"), int(u32::MAX as i128 + 1)),
+        (int(33), Cbor::Bytes(vec![1, 2, 3])),
+        (text("f"), Cbor::Float(1.5)),
+        (
+            text("list"),
+            Cbor::Array(vec![
+                Cbor::Bool(true),
+                Cbor::Null,
+                Cbor::Tag(0, Box::new(text("t"))),
+            ]),
+        ),
+    ]);
+    assert_eq!(decode_cbor(&encode_cbor(&item)).unwrap(), item);
+
+    // Indefinite text and array, and a half-precision float
+    let indefinite = [
+        0x9F, 0x7F, 0x62, b'a', b'b', 0x61, b'c', 0xFF, 0xF9, 0x3C, 0x00, 0xFF,
+    ];
+    assert_eq!(
+        decode_cbor(&indefinite).unwrap(),
+        Cbor::Array(vec![text("abc"), Cbor::Float(1.0)])
+    );
+}
+
+#[test]
+fn hostile_cbor_is_an_error() {
+    // Array claiming 2^64 - 1 items with none present
+    let huge = [0x9B, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+    assert!(decode_cbor(&huge).is_err());
+    // Byte string longer than the data
+    assert!(decode_cbor(&[0x5A, 0xFF, 0xFF, 0xFF, 0xFF, 0x00]).is_err());
+    // Deep nesting
+    let deep = vec![0x81; 1000];
+    let error = decode_cbor(&deep).unwrap_err();
+    assert!(
+        error.starts_with("Limit exceeded: CBOR nesting depth"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn jpeg_app11_store_is_reassembled_and_summarised() {
+    let (jpeg, _) = jpeg_with_store();
+    let (location, _) = find_manifest_store(&jpeg).unwrap();
+    assert_eq!(location, "JPEG APP11");
+
+    let credentials = read_manifest_store(&jpeg).unwrap().unwrap().summary();
+    assert_eq!(credentials.state(), CredentialState::Unverified);
+    let active = credentials.active().unwrap();
+    assert_eq!(active.label, MANIFEST);
+    assert_eq!(active.claim_generator.as_deref(), Some("Image Maker/2.1"));
+    assert_eq!(active.title.as_deref(), Some("sunset.jpg"));
+    assert_eq!(active.signer.as_deref(), Some("CN=Signer"));
+    assert_eq!(active.issuer.as_deref(), Some("CN=Test CA"));
+    assert_eq!(active.signature_algorithm.as_deref(), Some("ES256"));
+    assert_eq!(active.actions.len(), 2);
+    assert_eq!(
+        active.actions[0].software_agent.as_deref(),
+        Some("Image Maker")
+    );
+    assert_eq!(
+        active.actions[0].digital_source_type.as_deref(),
+        Some("trainedAlgorithmicMedia")
+    );
+    assert_eq!(
+        active.actions[1].when.as_deref(),
+        Some("2025-03-01T10:00:00Z")
+    );
+    assert_eq!(active.ingredients[0].title.as_deref(), Some("original.jpg"));
+    assert_eq!(active.ingredients[0].relationship, "parentOf");
+    assert_eq!(
+        active.assertions,
+        ["c2pa.actions.v2", "c2pa.ingredient", "c2pa.hash.data"]
+    );
+
+    let (metadata, _) = extract_metadata(&jpeg);
+    assert_eq!(metadata["C2PA:ClaimGenerator"].display, "Image Maker/2.1");
+    assert_eq!(metadata["C2PA:Actions"].display, "created, cropped");
+    assert_eq!(
+        metadata["C2PA:DigitalSourceType"].display,
+        "trainedAlgorithmicMedia"
+    );
+    assert_eq!(metadata["C2PA:ManifestCount"].display, "1");
+}
+
+#[test]
+fn store_is_found_in_png_webp_and_isobmff() {
+    let fixture = manifest_store((0, 0));
+    let png = png_with_store(&fixture.store);
+    assert_eq!(find_manifest_store(&png).unwrap().0, "PNG caBX chunk");
+
+    let mut webp = b"RIFF\0\0\0\0WEBPVP8 \x02\0\0\0\0\0C2PA".to_vec();
+    webp.extend_from_slice(&(fixture.store.len() as u32).to_le_bytes());
+    webp.extend_from_slice(&fixture.store);
+    assert_eq!(find_manifest_store(&webp).unwrap().0, "WebP C2PA chunk");
+
+    let mut uuid = image_metadata_extractor::c2pa::C2PA_UUID.to_vec();
+    uuid.extend_from_slice(&[0; 4]);
+    uuid.extend_from_slice(b"manifest\0");
+    uuid.extend_from_slice(&0u64.to_be_bytes());
+    uuid.extend_from_slice(&fixture.store);
+    let mut heif = jbox(b"ftyp", b"heic\0\0\0\0mif1");
+    heif.extend_from_slice(&jbox(b"uuid", &uuid));
+    let store = read_manifest_store(&heif).unwrap().unwrap();
+    assert_eq!(store.location, "ISOBMFF uuid box");
+    assert_eq!(store.manifests.len(), 1);
+}
+
+#[test]
+fn cleaning_removes_the_store() {
+    let fixture = manifest_store((0, 0));
+    let png = png_with_store(&fixture.store);
+    let cleaned = BinaryCleaner::clean_metadata(&png, "png").unwrap();
+    assert!(find_manifest_store(&cleaned).is_none());
+
+    let (jpeg, _) = jpeg_with_store();
+    let cleaned = BinaryCleaner::clean_metadata(&jpeg, "jpg").unwrap();
+    assert!(find_manifest_store(&cleaned).is_none());
+
+    let credentials = read_manifest_store(&png).unwrap().unwrap().summary();
+    let warning = cleaning_warning(&credentials, "image/png").unwrap();
+    assert!(warning.contains("signed by CN=Signer"), "{}", warning);
+    assert!(warning.contains("Cleaning removes them"));
+    // The cleaner fails on these or returns them unchanged
+    for mime in ["image/heif", "image/avif", "image/jxl", "application/pdf"] {
+        assert_eq!(cleaning_warning(&credentials, mime), None, "{}", mime);
+    }
+}
+
+#[test]
+fn certificates_parse_from_pem() {
+    let der = signing_chain().remove(0);
+    let text = format!(
+        "Imported anchors\n{}junk\n{}",
+        pem(&der),
+        pem(b"not a cert")
+    );
+    let certificates = parse_pem_certificates(&text);
+    assert_eq!(certificates.len(), 1);
+    let certificate = &certificates[0];
+    assert_eq!(certificate.der, der);
+    assert_eq!(certificate.subject, "CN=Signer");
+    assert_eq!(certificate.issuer, "CN=Test CA");
+    assert_eq!(certificate.key_type, KeyType::Ec("P-256"));
+    // 2020-01-01 and 2040-01-01
+    assert_eq!(certificate.not_before, Some(1_577_836_800));
+    assert_eq!(certificate.not_after, Some(2_208_988_800));
+    assert_eq!(certificate.spki.len(), 91);
+    assert!(!certificate.is_ca);
+    assert_eq!(certificate.key_usage, Some(1));
+    assert_eq!(certificate.extended_key_usage, ["1.3.6.1.4.1.62558.2.1"]);
+    assert_eq!(certificate.claim_signing_problem(), None);
+
+    let ca = &parse_pem_certificates(&pem(&root()))[0];
+    assert!(ca.is_ca && ca.can_issue());
+    assert_eq!(ca.key_usage, Some(0b110_0000));
+    assert!(ca.claim_signing_problem().is_some());
+}
+
+#[test]
+fn plan_covers_signature_assertions_binding_and_chain() {
+    let (jpeg, fixture) = jpeg_with_store();
+    let store = read_manifest_store(&jpeg).unwrap().unwrap();
+    let root = parse_pem_certificates(&pem(&root()));
+    let plan = plan_verification(&store, &jpeg, &root, 1_700_000_000);
+    assert!(plan.statuses.is_empty(), "{:?}", plan.statuses);
+
+    let codes: Vec<&str> = plan.checks.iter().map(|c| c.passed).collect();
+    assert_eq!(
+        codes,
+        [
+            "claimSignature.validated",
+            "signingCredential.chain.validated",
+            "signingCredential.trusted",
+            "assertion.hashedURI.match",
+            "assertion.hashedURI.match",
+            "assertion.hashedURI.match",
+            "assertion.dataHash.match",
+        ]
+    );
+
+    let Operation::Verify {
+        scheme,
+        message,
+        signature,
+        ..
+    } = &plan.checks[0].operation
+    else {
+        panic!("claim signature is not a signature check");
+    };
+    assert_eq!(
+        *scheme,
+        SignatureScheme::Ecdsa {
+            curve: "P-256",
+            hash: "SHA-256"
+        }
+    );
+    assert_eq!(*message, sig_structure(&fixture.protected, &fixture.claim));
+    assert_eq!(*signature, vec![0x5A; 64]);
+
+    // Certificate signatures become fixed-size r || s
+    let Operation::Verify { signature, .. } = &plan.checks[1].operation else {
+        panic!("chain link is not a signature check");
+    };
+    let mut expected = vec![0x80];
+    expected.extend_from_slice(&[0x11; 31]);
+    expected.extend_from_slice(&[0; 12]);
+    expected.extend_from_slice(&[0x22; 20]);
+    assert_eq!(*signature, expected);
+
+    for (n, (_, assertion)) in fixture.assertions.iter().enumerate() {
+        let Operation::Digest { data, expected, .. } = &plan.checks[3 + n].operation else {
+            panic!("assertion check is not a digest");
+        };
+        assert_eq!(data, assertion);
+        assert_eq!(*expected, vec![n as u8 + 1; 32]);
+    }
+
+    // The data hash covers the file without the APP11 segments
+    let Operation::Digest {
+        algorithm, data, ..
+    } = &plan.checks[6].operation
+    else {
+        panic!("hard binding is not a digest");
+    };
+    assert_eq!(*algorithm, "SHA-256");
+    assert_eq!(data[..2], [0xFF, 0xD8]);
+    assert_eq!(data[2..4], [0xFF, 0xDA]);
+    assert_eq!(data.len(), 2 + 8);
+}
+
+#[test]
+fn trust_and_validity_without_anchors() {
+    let (jpeg, _) = jpeg_with_store();
+    let store = read_manifest_store(&jpeg).unwrap().unwrap();
+    let plan = plan_verification(&store, &jpeg, &[], 2_300_000_000);
+    let codes: Vec<&str> = plan.statuses.iter().map(|s| s.code.as_str()).collect();
+    // Every certificate in the chain has expired
+    assert_eq!(
+        codes,
+        [
+            "signingCredential.expired",
+            "signingCredential.expired",
+            "signingCredential.untrusted"
+        ]
+    );
+    assert!(plan.statuses[1].explanation.contains("CN=Test CA"));
+    assert_eq!(
+        plan.statuses[2].explanation,
+        "No trust anchors are imported"
+    );
+
+    // An anchor that is itself in the chain is trusted without a check
+    let intermediate = parse_pem_certificates(&pem(&signing_chain()[1]));
+    let plan = plan_verification(&store, &jpeg, &intermediate, 1_700_000_000);
+    assert_eq!(plan.statuses[0].code, "signingCredential.trusted");
+    assert_eq!(plan.statuses[0].outcome, Outcome::Passed);
+}
+
+#[test]
+fn chain_needs_ca_issuers_and_a_claim_signing_signer() {
+    let anchors = parse_pem_certificates(&pem(&root()));
+    let statuses = |chain: Vec<Vec<u8>>| {
+        let png = png_with_store(&manifest_store_signed_by((0, 0), chain).store);
+        let store = read_manifest_store(&png).unwrap().unwrap();
+        let plan = plan_verification(&store, &png, &anchors, 1_700_000_000);
+        let mut credentials = store.summary();
+        credentials.validation = assess(&plan, &vec![Some(true); plan.checks.len()]);
+        (plan.statuses, credentials.state())
+    };
+
+    // An end-entity certificate cannot issue another, even when it signed it
+    let [signer, ca] = signing_chain().try_into().unwrap();
+    let (found, state) = statuses(vec![
+        certificate("Signer", "Mallory", 0x05, &signer_extensions()),
+        certificate("Mallory", "Test CA", 0x08, &signer_extensions()),
+        ca.clone(),
+    ]);
+    assert_eq!(found.len(), 1, "{:?}", found);
+    assert_eq!(found[0].code, "signingCredential.invalid");
+    assert_eq!(
+        found[0].explanation,
+        "CN=Mallory is not a CA certificate, so it cannot have issued CN=Signer"
+    );
+    assert_ne!(state, CredentialState::Trusted);
+
+    // A CA cannot sign claims, and a signer needs a claim signing usage
+    let (found, _) = statuses(vec![ca.clone(), root()]);
+    assert!(found[0].explanation.contains("it is a CA certificate"));
+    let (found, state) = statuses(vec![
+        certificate("Signer", "Test CA", 0x05, &signer_extensions()[..1]),
+        ca.clone(),
+    ]);
+    assert_eq!(found[0].code, "signingCredential.invalid");
+    assert!(found[0].explanation.contains("no extended key usage"));
+    assert_eq!(state, CredentialState::Untrusted);
+
+    // Every certificate's validity is checked, not only the signer's
+    let expired = certificate_until(
+        "Test CA",
+        "Test Root",
+        0x06,
+        "20230101000000Z",
+        &ca_extensions(),
+    );
+    let (found, state) = statuses(vec![signer, expired]);
+    assert_eq!(found.len(), 1, "{:?}", found);
+    assert_eq!(found[0].code, "signingCredential.expired");
+    assert!(found[0].explanation.contains("CN=Test CA"));
+    assert_eq!(state, CredentialState::Untrusted);
+}
+
+#[test]
+fn unchecked_bmff_binding_leaves_credentials_incomplete() {
+    let (jpeg, _) = jpeg_with_store();
+    let mut store = read_manifest_store(&jpeg).unwrap().unwrap();
+    let mut bmff = store.manifests[0].assertions[2].clone();
+    bmff.label = "c2pa.hash.bmff.v2".to_string();
+    store.manifests[0].assertions.insert(2, bmff);
+    let plan = plan_verification(
+        &store,
+        &jpeg,
+        &parse_pem_certificates(&pem(&root())),
+        1_700_000_000,
+    );
+    assert_eq!(plan.statuses.len(), 1);
+    assert_eq!(plan.statuses[0].outcome, Outcome::Skipped);
+    assert!(
+        plan.statuses[0]
+            .explanation
+            .starts_with("c2pa.hash.bmff.v2 hash bindings are not checked")
+    );
+
+    let mut credentials = store.summary();
+    credentials.validation = assess(&plan, &vec![Some(true); plan.checks.len()]);
+    assert_eq!(credentials.state(), CredentialState::Incomplete);
+}
+
+#[test]
+fn assess_maps_results_to_state() {
+    let (jpeg, _) = jpeg_with_store();
+    let store = read_manifest_store(&jpeg).unwrap().unwrap();
+    let mut credentials = store.summary();
+    let root = parse_pem_certificates(&pem(&root()));
+    let plan = plan_verification(&store, &jpeg, &root, 1_700_000_000);
+    let all = |result| vec![result; plan.checks.len()];
+
+    credentials.validation = assess(&plan, &all(Some(true)));
+    assert_eq!(credentials.state(), CredentialState::Trusted);
+
+    let mut results = all(Some(true));
+    results[2] = Some(false);
+    credentials.validation = assess(&plan, &results);
+    assert_eq!(credentials.state(), CredentialState::Untrusted);
+    assert!(
+        credentials
+            .validation
+            .iter()
+            .any(|s| s.code == "signingCredential.untrusted" && s.outcome == Outcome::Failed)
+    );
+
+    results[6] = Some(false);
+    credentials.validation = assess(&plan, &results);
+    assert_eq!(credentials.state(), CredentialState::Invalid);
+    let mismatch = credentials
+        .validation
+        .iter()
+        .find(|s| s.code == "assertion.dataHash.mismatch")
+        .unwrap();
+    assert_eq!(mismatch.manifest, MANIFEST);
+
+    // Checks the browser could not run leave the result incomplete
+    credentials.validation = assess(&plan, &[Some(true)]);
+    assert_eq!(credentials.state(), CredentialState::Incomplete);
+}
+
+#[test]
+fn bad_exclusions_and_missing_assertions_fail() {
+    let fixture = manifest_store((0, 1 << 20));
+    let png = png_with_store(&fixture.store);
+    let store = read_manifest_store(&png).unwrap().unwrap();
+    let plan = plan_verification(&store, &png, &[], 1_700_000_000);
+    let mismatch = plan
+        .statuses
+        .iter()
+        .find(|s| s.code == "assertion.dataHash.mismatch")
+        .unwrap();
+    assert!(mismatch.explanation.contains("outside the file"));
+
+    let mut store = store;
+    store.manifests[0].assertions.remove(0);
+    let plan = plan_verification(&store, &png, &[], 1_700_000_000);
+    let missing = plan
+        .statuses
+        .iter()
+        .find(|s| s.code == "assertion.missing")
+        .unwrap();
+    assert_eq!(missing.outcome, Outcome::Failed);
+    assert!(missing.explanation.contains("c2pa.actions.v2"));
+}
+
+#[test]
+fn damaged_store_is_diagnosed() {
+    let mut fixture = manifest_store((0, 0));
+    // Cut the claim box short inside the store
+    let len = fixture.store.len();
+    fixture.store.truncate(len - 40);
+    let size = (fixture.store.len() as u32).to_be_bytes();
+    fixture.store[..4].copy_from_slice(&size);
+    let png = png_with_store(&fixture.store);
+    let diagnostics = diagnose(&png);
+    assert!(
+        diagnostics.iter().any(|d| d.parser == "C2PA"),
+        "{:?}",
+        diagnostics
+    );
+}
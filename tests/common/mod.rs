@@ -0,0 +1,86 @@
+//! Byte builders for the container fixtures shared by the integration tests.
+
+#![allow(dead_code)]
+
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use std::io::Write;
+
+/// A TIFF entry: tag, type, count and value bytes (little-endian).
+pub type Entry = (u16, u16, u32, Vec<u8>);
+
+pub fn ascii(tag: u16, text: &str) -> Entry {
+    let mut bytes = text.as_bytes().to_vec();
+    bytes.push(0);
+    (tag, 2, bytes.len() as u32, bytes)
+}
+
+/// Serialise an IFD placed at absolute offset `at`, with out-of-line values
+/// following the directory and `next` as the offset of the next IFD.
+pub fn ifd(entries: &[Entry], at: usize, next: u32) -> Vec<u8> {
+    let dir_len = 2 + entries.len() * 12 + 4;
+    let mut dir = (entries.len() as u16).to_le_bytes().to_vec();
+    let mut data = Vec::new();
+    for (tag, kind, count, value) in entries {
+        dir.extend_from_slice(&tag.to_le_bytes());
+        dir.extend_from_slice(&kind.to_le_bytes());
+        dir.extend_from_slice(&count.to_le_bytes());
+        if value.len() <= 4 {
+            let mut inline = value.clone();
+            inline.resize(4, 0);
+            dir.extend_from_slice(&inline);
+        } else {
+            dir.extend_from_slice(&((at + dir_len + data.len()) as u32).to_le_bytes());
+            data.extend_from_slice(value);
+            if data.len() % 2 == 1 {
+                data.push(0);
+            }
+        }
+    }
+    dir.extend_from_slice(&next.to_le_bytes());
+    dir.extend(data);
+    dir
+}
+
+/// Little-endian TIFF whose IFD0 holds Make and Model.
+pub fn exif_tiff(make: &str, model: &str) -> Vec<u8> {
+    let mut tiff = b"II*\0\x08\0\0\0".to_vec();
+    tiff.extend(ifd(&[ascii(0x010F, make), ascii(0x0110, model)], 8, 0));
+    tiff
+}
+
+/// JPEG with `tiff` in an EXIF APP1 segment, followed by a short scan.
+pub fn jpeg_with_exif(tiff: &[u8]) -> Vec<u8> {
+    let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+    data.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+    data.extend_from_slice(b"Exif\0\0");
+    data.extend_from_slice(tiff);
+    data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x04, 0x01, 0x02, 0xFF, 0xD9]);
+    data
+}
+
+/// PNG signature followed by `chunks`, each with a zero CRC.
+pub fn png(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+    let mut png = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+    for (id, payload) in chunks {
+        png.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        png.extend_from_slice(*id);
+        png.extend_from_slice(payload);
+        png.extend_from_slice(&[0, 0, 0, 0]);
+    }
+    png
+}
+
+pub fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// ISO-BMFF box with a 32-bit size.
+pub fn bx(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut data = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(kind);
+    data.extend_from_slice(payload);
+    data
+}
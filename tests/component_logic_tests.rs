@@ -1,11 +1,11 @@
 // Unit tests for component logic that can be tested without WASM/UI framework
-use image_metadata_extractor::types::ImageData;
+use image_metadata_extractor::types::{GpsInfo, ImageData};
 use std::collections::{HashMap, HashSet};
 
 #[test]
 fn test_image_data_has_metadata() {
     let mut exif = HashMap::new();
-    exif.insert("Make".to_string(), "Canon".to_string());
+    exif.insert("Make".to_string(), "Canon".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -15,13 +15,14 @@ fn test_image_data_has_metadata() {
         width: Some(800),
         height: Some(600),
         exif_data: exif,
-        gps_coords: Some((1.0, 2.0)),
+        gps: Some(GpsInfo::new(1.0, 2.0)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     // Test logic that would be used in MetadataDisplay component
     assert!(!data.exif_data.is_empty());
-    assert!(data.gps_coords.is_some());
+    assert!(data.gps.is_some());
     assert_eq!(data.width, Some(800));
     assert_eq!(data.height, Some(600));
 }
@@ -36,21 +37,22 @@ fn test_image_data_no_metadata() {
         width: Some(100),
         height: Some(200),
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     // Test logic for empty metadata case in components
     assert!(data.exif_data.is_empty());
-    assert!(data.gps_coords.is_none());
+    assert!(data.gps.is_none());
 }
 
 #[test]
 fn test_metadata_selection_logic() {
     let mut exif = HashMap::new();
-    exif.insert("Make".to_string(), "Canon".to_string());
-    exif.insert("Model".to_string(), "EOS R5".to_string());
-    exif.insert("ISO".to_string(), "100".to_string());
+    exif.insert("Make".to_string(), "Canon".into());
+    exif.insert("Model".to_string(), "EOS R5".into());
+    exif.insert("ISO".to_string(), "100".into());
 
     let data = ImageData {
         name: "photo.jpg".to_string(),
@@ -60,8 +62,9 @@ fn test_metadata_selection_logic() {
         width: Some(4000),
         height: Some(3000),
         exif_data: exif.clone(),
-        gps_coords: Some((40.7128, -74.0060)),
+        gps: Some(GpsInfo::new(40.7128, -74.0060)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     // Test selection logic that would be used in MetadataExport component
@@ -74,7 +77,7 @@ fn test_metadata_selection_logic() {
     // Should include selected metadata plus file info and GPS
     assert!(!filtered.name.is_empty());
     assert_eq!(filtered.size, 2048);
-    assert!(filtered.gps_coords.is_some());
+    assert!(filtered.gps.is_some());
     assert_eq!(filtered.exif_data.len(), 2);
     assert!(filtered.exif_data.contains_key("Make"));
     assert!(filtered.exif_data.contains_key("Model"));
@@ -94,8 +97,9 @@ fn test_component_props_equality() {
         width: Some(800),
         height: Some(600),
         exif_data: exif.clone(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let data2 = ImageData {
@@ -106,8 +110,9 @@ fn test_component_props_equality() {
         width: Some(800),
         height: Some(600),
         exif_data: exif,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     // Test equality logic used in component prop comparison
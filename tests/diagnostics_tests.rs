@@ -0,0 +1,294 @@
+mod common;
+
+use common::{jpeg_with_exif, png};
+use image_metadata_extractor::diagnostics::{Severity, diagnose};
+use image_metadata_extractor::exif_core::{extract_exif_data, extract_metadata, recover_exif};
+
+/// Big-endian TIFF with IFD0 (Make, Model, ExifIFD pointer) and an EXIF IFD
+/// holding ISOSpeed. With `bad_model` the Model value points past the end.
+fn tiff(bad_model: bool) -> Vec<u8> {
+    let mut t = b"MM\0*\0\0\0\x08".to_vec();
+    let entry = |t: &mut Vec<u8>, tag: u16, kind: u16, count: u32, value: u32| {
+        t.extend_from_slice(&tag.to_be_bytes());
+        t.extend_from_slice(&kind.to_be_bytes());
+        t.extend_from_slice(&count.to_be_bytes());
+        t.extend_from_slice(&value.to_be_bytes());
+    };
+    // IFD0 at 8: 3 entries, ends at 8 + 2 + 36 + 4 = 50
+    t.extend_from_slice(&3u16.to_be_bytes());
+    entry(&mut t, 0x010F, 2, 6, 68); // Make -> "Canon\0"
+    entry(&mut t, 0x0110, 2, 7, if bad_model { 0xFFFF } else { 74 });
+    entry(&mut t, 0x8769, 4, 1, 50); // ExifIFD
+    t.extend_from_slice(&0u32.to_be_bytes());
+    // EXIF IFD at 50: 1 entry, ends at 50 + 2 + 12 + 4 = 68
+    t.extend_from_slice(&1u16.to_be_bytes());
+    entry(&mut t, 0x8827, 3, 1, 400 << 16); // ISO 400
+    t.extend_from_slice(&0u32.to_be_bytes());
+    t.extend_from_slice(b"Canon\0");
+    t.extend_from_slice(b"EOS R5\0");
+    t
+}
+
+const IHDR: &[u8] = &[0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 0];
+
+#[test]
+fn intact_files_have_no_diagnostics() {
+    let jpeg = jpeg_with_exif(&tiff(false));
+    assert!(diagnose(&jpeg).is_empty(), "{:?}", diagnose(&jpeg));
+    let (exif, _) = extract_exif_data(&jpeg);
+    assert_eq!(exif["Model"].display, "\"EOS R5\"");
+
+    let png = png(&[(b"IHDR", IHDR), (b"IDAT", &[0x78, 0x9C]), (b"IEND", &[])]);
+    assert!(diagnose(&png).is_empty());
+}
+
+#[test]
+fn damaged_ifd_entry_keeps_the_rest_of_the_block() {
+    let jpeg = jpeg_with_exif(&tiff(true));
+    let (exif, _) = extract_exif_data(&jpeg);
+    assert_eq!(exif["Make"].display, "\"Canon\"");
+    assert_eq!(exif["PhotographicSensitivity"].display, "400");
+    assert!(!exif.contains_key("Model"));
+
+    let diagnostics = diagnose(&jpeg);
+    let error = diagnostics
+        .iter()
+        .find(|d| d.parser == "EXIF" && d.severity == Severity::Error)
+        .unwrap();
+    // The TIFF header follows SOI, the APP1 marker and length and "Exif\0\0"
+    assert_eq!(error.offset, Some(12));
+    assert!(
+        error.message.contains("fields recovered"),
+        "{}",
+        error.message
+    );
+
+    let model = diagnostics
+        .iter()
+        .find(|d| d.severity == Severity::Warning && d.message.contains("Model"))
+        .unwrap();
+    // Second IFD0 entry: header (8) + count (2) + one entry (12)
+    assert_eq!(model.offset, Some(12 + 8 + 2 + 12));
+    assert!(model.message.contains("outside the EXIF block"));
+}
+
+#[test]
+fn truncated_app1_reports_segment_and_recovers_early_fields() {
+    let jpeg = jpeg_with_exif(&tiff(false));
+    // Cut the file inside the EXIF IFD
+    let cut = &jpeg[..12 + 56];
+
+    let diagnostics = diagnose(cut);
+    let segment = diagnostics.iter().find(|d| d.parser == "JPEG").unwrap();
+    assert_eq!(segment.severity, Severity::Error);
+    assert_eq!(segment.offset, Some(2));
+    assert!(segment.message.starts_with("APP1 segment declares"));
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d.parser == "EXIF" && d.message.contains("cut off after 0 of 1"))
+    );
+
+    let (exif, _) = extract_exif_data(cut);
+    assert!(exif.contains_key("ExifIFDPointer"));
+    assert!(!exif.contains_key("PhotographicSensitivity"));
+}
+
+#[test]
+fn recover_exif_reports_bad_headers_and_loops() {
+    let (fields, problems) = recover_exif(b"XX\0*", Some(100));
+    assert!(fields.is_empty());
+    assert_eq!(problems[0].offset, Some(100));
+    assert_eq!(problems[0].severity, Severity::Error);
+
+    // IFD0 whose next-IFD link points back at itself
+    let mut looping = b"II*\0\x08\0\0\0\0\0\x08\0\0\0".to_vec();
+    looping.extend_from_slice(&[0; 4]);
+    let (_, problems) = recover_exif(&looping, None);
+    assert!(problems.iter().any(|d| d.message.contains("points back")));
+    assert!(problems.iter().all(|d| d.offset.is_none()));
+}
+
+#[test]
+fn png_chunk_overrun_and_missing_iend() {
+    let mut truncated = png(&[(b"IHDR", IHDR), (b"tEXt", b"Comment\0hello")]);
+    truncated.truncate(truncated.len() - 6);
+    let diagnostics = diagnose(&truncated);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].parser, "PNG");
+    assert_eq!(diagnostics[0].offset, Some(33));
+    assert!(
+        diagnostics[0]
+            .message
+            .starts_with("tEXt chunk declares 13 bytes")
+    );
+
+    let unterminated = png(&[(b"IHDR", IHDR)]);
+    let diagnostics = diagnose(&unterminated);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].offset, Some(unterminated.len()));
+}
+
+#[test]
+fn webp_and_isobmff_overruns() {
+    let mut webp = b"RIFF\0\0\0\0WEBPVP8 ".to_vec();
+    webp.extend_from_slice(&100u32.to_le_bytes());
+    webp.extend_from_slice(&[0; 10]);
+    let diagnostics = diagnose(&webp);
+    assert_eq!(diagnostics[0].parser, "RIFF");
+    assert_eq!(diagnostics[0].offset, Some(12));
+
+    let mut heif = vec![0, 0, 0, 16];
+    heif.extend_from_slice(b"ftypheic\0\0\0\0");
+    heif.extend_from_slice(&[0, 0, 1, 0]);
+    heif.extend_from_slice(b"meta");
+    let diagnostics = diagnose(&heif);
+    let overrun = diagnostics.iter().find(|d| d.parser == "ISOBMFF").unwrap();
+    assert_eq!(overrun.offset, Some(16));
+    assert!(overrun.message.starts_with("meta box declares 256 bytes"));
+}
+
+#[test]
+fn unrecognised_content_is_an_error() {
+    let diagnostics = diagnose(b"definitely not an image");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].parser, "File");
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+}
+
+fn jpeg_with_segments(segments: &[(u8, &[u8])]) -> Vec<u8> {
+    let mut jpeg = vec![0xFF, 0xD8];
+    for (marker, payload) in segments {
+        jpeg.extend_from_slice(&[0xFF, *marker]);
+        jpeg.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        jpeg.extend_from_slice(payload);
+    }
+    jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x00, 0xFF, 0xD9]);
+    jpeg
+}
+
+#[test]
+fn iptc_dataset_overrun_keeps_earlier_datasets() {
+    let mut iim = vec![0x1C, 2, 5, 0, 5];
+    iim.extend_from_slice(b"Title");
+    // Keywords dataset declaring more bytes than the block holds
+    iim.extend_from_slice(&[0x1C, 2, 25, 0, 200]);
+    iim.extend_from_slice(b"sunset");
+    let mut app13 = b"Photoshop 3.0\08BIM\x04\x04\0\0".to_vec();
+    app13.extend_from_slice(&(iim.len() as u32).to_be_bytes());
+    app13.extend_from_slice(&iim);
+    let jpeg = jpeg_with_segments(&[(0xED, &app13)]);
+
+    let (metadata, _) = extract_metadata(&jpeg);
+    assert_eq!(metadata["IPTC:ObjectName"].display, "Title");
+    let diagnostics = diagnose(&jpeg);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].parser, "IPTC");
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert!(
+        diagnostics[0]
+            .message
+            .starts_with("Dataset 2:25 declares 200 bytes")
+    );
+}
+
+#[test]
+fn png_text_that_cannot_be_inflated_is_an_error() {
+    let png = png(&[
+        (b"IHDR", IHDR),
+        (b"zTXt", b"Comment\0\0not zlib"),
+        (b"iTXt", b"Title\0\x01\0\0\0not zlib either"),
+        (b"tEXt", b"Author\0Ada"),
+        (b"IEND", &[]),
+    ]);
+
+    let (metadata, _) = extract_metadata(&png);
+    assert_eq!(metadata["PNG:Author"].display, "Ada");
+    let diagnostics = diagnose(&png);
+    assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+    assert!(diagnostics.iter().all(|d| d.parser == "PNG"));
+    assert_eq!(diagnostics[0].offset, Some(33));
+    assert!(
+        diagnostics[0]
+            .message
+            .starts_with("zTXt chunk text could not be inflated")
+    );
+    assert!(diagnostics[1].message.starts_with("iTXt chunk"));
+}
+
+#[test]
+fn unreadable_maker_note_is_a_warning() {
+    let mut t = b"MM\0*\0\0\0\x08".to_vec();
+    let entry = |t: &mut Vec<u8>, tag: u16, kind: u16, count: u32, value: u32| {
+        t.extend_from_slice(&tag.to_be_bytes());
+        t.extend_from_slice(&kind.to_be_bytes());
+        t.extend_from_slice(&count.to_be_bytes());
+        t.extend_from_slice(&value.to_be_bytes());
+    };
+    // IFD0 at 8 ends at 38, the EXIF IFD at 38 ends at 56
+    t.extend_from_slice(&2u16.to_be_bytes());
+    entry(&mut t, 0x010F, 2, 6, 64); // Make -> "Canon\0"
+    entry(&mut t, 0x8769, 4, 1, 38); // ExifIFD
+    t.extend_from_slice(&0u32.to_be_bytes());
+    t.extend_from_slice(&1u16.to_be_bytes());
+    entry(&mut t, 0x927C, 7, 8, 56); // MakerNote
+    t.extend_from_slice(&0u32.to_be_bytes());
+    // A directory claiming 65535 entries
+    t.extend_from_slice(&[0xFF; 8]);
+    t.extend_from_slice(b"Canon\0");
+    let jpeg = jpeg_with_exif(&t);
+
+    let diagnostics = diagnose(&jpeg);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].parser, "MakerNote");
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert!(diagnostics[0].message.starts_with("Canon MakerNote"));
+}
+
+#[test]
+fn mpf_segment_without_an_index_is_an_error() {
+    let jpeg = jpeg_with_segments(&[(0xE2, b"MPF\0II*\0\x08\0\0\0")]);
+    let diagnostics = diagnose(&jpeg);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].parser, "MPF");
+    assert_eq!(diagnostics[0].offset, Some(2));
+}
+
+#[test]
+fn animation_structure_problems() {
+    let mut gif = b"GIF89a\x01\0\x01\0\0\0\0".to_vec();
+    // Image descriptor, LZW code size and an empty sub-block, no trailer
+    gif.extend_from_slice(&[0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0, 2, 0]);
+    let diagnostics = diagnose(&gif);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].parser, "GIF");
+    assert_eq!(diagnostics[0].offset, Some(gif.len()));
+    gif.push(0x3B);
+    assert!(diagnose(&gif).is_empty());
+
+    let mut fctl = vec![0; 26];
+    fctl[7] = 1;
+    fctl[11] = 1;
+    let actl = [0, 0, 0, 3, 0, 0, 0, 0];
+    let apng = png(&[
+        (b"IHDR", IHDR),
+        (b"acTL", &actl),
+        (b"fcTL", &fctl),
+        (b"IDAT", &[0x78, 0x9C]),
+        (b"IEND", &[]),
+    ]);
+    let diagnostics = diagnose(&apng);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].parser, "APNG");
+    assert_eq!(diagnostics[0].offset, Some(33));
+    assert!(diagnostics[0].message.contains("declares 3 frames"));
+}
+
+#[test]
+fn svg_cut_off_inside_a_tag_is_an_error() {
+    let svg = br#"<svg xmlns="http://www.w3.org/2000/svg"><title>Logo</title><desc id="d"#;
+    let diagnostics = diagnose(svg);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].parser, "SVG");
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+}
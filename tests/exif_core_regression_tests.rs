@@ -1,6 +1,8 @@
 // Regression tests for GPS coordinate edge cases and reference direction handling
 // These tests ensure proper GPS coordinate parsing and reference application
 
+#![allow(clippy::manual_range_contains)]
+
 use base64::Engine;
 use base64::engine::general_purpose;
 use image_metadata_extractor::exif_core::extract_exif_data;
@@ -20,7 +22,7 @@ fn test_gps_coordinate_update_latitude_when_longitude_exists() {
     assert!(!exif_map.is_empty(), "Should extract EXIF fields");
     assert!(gps_coords.is_some(), "Should extract GPS coordinates");
 
-    if let Some((lat, lon)) = gps_coords {
+    if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
         // Verify we got actual coordinates (this will have gone through the update paths)
         assert!(lat != 0.0 || lon != 0.0, "Should have non-zero coordinates");
     }
@@ -65,7 +67,7 @@ fn test_gps_latitude_reference_south() {
     assert!(!exif_map.is_empty(), "Should extract EXIF fields");
 
     // Test that GPS reference processing was applied
-    if let Some((lat, lon)) = gps_coords {
+    if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
         // If we have coordinates, the reference logic was exercised
         assert!(
             lat >= -90.0 && lat <= 90.0,
@@ -92,7 +94,7 @@ fn test_gps_longitude_reference_west() {
     // Verify GPS processing paths were taken
     assert!(!exif_map.is_empty(), "Should extract EXIF fields");
 
-    if let Some((lat, lon)) = gps_coords {
+    if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
         // Verify coordinate transformation was applied
         // The reference processing should have been applied to get valid coordinates
         assert!(lat.is_finite(), "Latitude should be a valid number");
@@ -124,7 +126,7 @@ fn test_extract_exif_data_comprehensive_gps_parsing() {
             "GPS coordinates should correspond to GPS fields"
         );
 
-        if let Some((lat, lon)) = gps_coords {
+        if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
             // Verify the coordinate processing completed properly
             assert!(
                 lat >= -90.0 && lat <= 90.0,
@@ -166,7 +168,7 @@ fn test_gps_coordinate_field_processing_edge_cases() {
             "Should have GPS fields if coordinates exist"
         );
 
-        if let Some((lat, lon)) = gps_coords {
+        if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
             // Test coordinate finalization
             assert!(
                 lat.is_finite() && lon.is_finite(),
@@ -1,5 +1,7 @@
 // Regression tests for GPS reference handling, image dimensions, and format detection edge cases
 
+#![allow(clippy::manual_range_contains)]
+
 use base64::Engine;
 use base64::engine::general_purpose;
 use image_metadata_extractor::exif_core::{determine_mime_type, extract_exif_data};
@@ -98,7 +100,7 @@ fn test_extract_exif_data_gps_coordinate_building() {
     assert!(!exif_map.is_empty(), "Should extract EXIF fields");
     assert!(gps_coords.is_some(), "Should extract GPS coordinates");
 
-    if let Some((lat, lon)) = gps_coords {
+    if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
         // Test that coordinates are within valid ranges after reference application
         assert!(
             lat >= -90.0 && lat <= 90.0,
@@ -2,12 +2,15 @@ use base64::Engine;
 use base64::engine::general_purpose;
 use exif::Rational;
 use exif::{Field, In, Tag, Value};
+use image_metadata_extractor::components::metadata_display::categorize_metadata;
 #[cfg(target_arch = "wasm32")]
 use image_metadata_extractor::exif::create_object_url;
 use image_metadata_extractor::exif::{
     determine_mime_type, extract_exif_data, get_dimensions, is_supported_mime_type,
     parse_gps_coordinate,
 };
+use image_metadata_extractor::exif_core::{exif_field_key, field_to_metadata_value, ifd_of};
+use image_metadata_extractor::types::{Ifd, MetadataSource, RawValue};
 use std::io::Cursor;
 
 const JPG_B64: &str = "/9j/4QCMRXhpZgAASUkqAAgAAAABACWIBAABAAAAHAAAAAAAAAAAAAQAAQACAAIAAABOAAAAAgAFAAMAAABUAAAAAwACAAIAAABXAAAABAAFAAMAAABsAAAAAAAAAAAAAQAAAAEAAAAAAAAAAQAAAAAAAAABAAAAAgAAAAEAAAAeAAAAAQAAAAAAAAABAAAA/9k=";
@@ -97,7 +100,7 @@ fn test_extract_exif_data_gps() {
     let bytes = general_purpose::STANDARD.decode(JPG_B64).unwrap();
     let (map, gps) = extract_exif_data(&bytes);
     assert!(!map.is_empty());
-    let (lat, lon) = gps.unwrap();
+    let (lat, lon) = gps.unwrap().coords();
     assert!((lat - 1.0).abs() < 1e-6);
     assert!((lon + 2.5).abs() < 1e-6);
     let (map2, gps2) = extract_exif_data(b"not exif");
@@ -105,6 +108,48 @@ fn test_extract_exif_data_gps() {
     assert!(gps2.is_none());
 }
 
+#[test]
+fn test_extract_exif_data_keeps_typed_values() {
+    let bytes = general_purpose::STANDARD.decode(JPG_B64).unwrap();
+    let (map, _) = extract_exif_data(&bytes);
+
+    let lat = map.get("GPSLatitude").expect("latitude present");
+    assert!(lat.is_numeric());
+    assert_eq!(lat.as_f64_vec(), Some(vec![1.0, 0.0, 0.0]));
+    assert!(matches!(lat.raw, RawValue::Rational(ref r) if r.len() == 3));
+    assert!(!lat.display.is_empty());
+
+    let lat_ref = map.get("GPSLatitudeRef").expect("latitude ref present");
+    assert_eq!(lat_ref.as_text(), Some("N"));
+}
+
+#[test]
+fn test_field_to_metadata_value_unit() {
+    let img = general_purpose::STANDARD.decode(JPG_B64).unwrap();
+    let exif = exif::Reader::new()
+        .read_from_container(&mut Cursor::new(&img))
+        .unwrap();
+
+    let focal = Field {
+        tag: Tag::FocalLength,
+        ifd_num: In::PRIMARY,
+        value: Value::Rational(vec![Rational { num: 50, denom: 1 }]),
+    };
+    let value = field_to_metadata_value(&focal, &exif);
+    assert_eq!(value.as_f64(), Some(50.0));
+    assert_eq!(value.unit.as_deref(), Some("mm"));
+    assert_eq!(value.display, "50 mm");
+
+    let width = Field {
+        tag: Tag::PixelXDimension,
+        ifd_num: In::PRIMARY,
+        value: Value::Long(vec![4000]),
+    };
+    let value = field_to_metadata_value(&width, &exif);
+    assert_eq!(value.as_i64(), Some(4000));
+    assert_eq!(value.unit.as_deref(), Some("pixels"));
+}
+
 // create_object_url uses web APIs - test in WASM environment only
 #[cfg(target_arch = "wasm32")]
 #[test]
@@ -153,6 +198,8 @@ fn test_is_supported_mime_type_comprehensive() {
         "image/heif",
         "image/avif",
         "image/jxl",
+        "video/mp4",
+        "video/quicktime",
     ];
 
     for mime_type in &supported_types {
@@ -168,7 +215,7 @@ fn test_is_supported_mime_type_comprehensive() {
         "text/plain",
         "application/json",
         "image/bmp",
-        "video/mp4",
+        "video/x-msvideo",
         "audio/mp3",
     ];
 
@@ -269,11 +316,15 @@ fn test_get_dimensions_non_image_types() {
 
 #[test]
 fn test_determine_mime_type_priority_order() {
-    // Test that provided file_type takes priority over detection
+    // Test that detection from the content takes priority over file_type
     let jpeg_bytes = &[0xFF, 0xD8, 0xFF, 0xE0];
 
-    // When file_type is provided, it should be used regardless of bytes
+    // When the content is recognised, the provided file_type is ignored
     let mime = determine_mime_type("test.jpg", "custom/type", jpeg_bytes);
+    assert_eq!(mime, "image/jpeg");
+
+    // When it is not, the provided file_type is used
+    let mime = determine_mime_type("test.jpg", "custom/type", b"unknown");
     assert_eq!(mime, "custom/type");
 
     // When file_type is empty, should fall back to detection
@@ -307,7 +358,7 @@ fn test_extract_exif_data_comprehensive_parsing() {
     // Should extract GPS coordinates
     assert!(gps_coords.is_some(), "Should extract GPS coordinates");
 
-    if let Some((lat, lon)) = gps_coords {
+    if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
         // Verify coordinates are reasonable (from the test image)
         assert!(lat.abs() < 90.0, "Latitude should be valid");
         assert!(lon.abs() < 180.0, "Longitude should be valid");
@@ -463,15 +514,15 @@ fn test_extract_exif_data_with_gps_coordinates() {
     // Should extract GPS coordinates
     assert!(gps_coords.is_some(), "Should extract GPS coordinates");
 
-    if let Some((lat, lon)) = gps_coords {
+    if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
         // Verify coordinate ranges
         assert!(
-            lat >= -90.0 && lat <= 90.0,
+            (-90.0..=90.0).contains(&lat),
             "Latitude out of range: {}",
             lat
         );
         assert!(
-            lon >= -180.0 && lon <= 180.0,
+            (-180.0..=180.0).contains(&lon),
             "Longitude out of range: {}",
             lon
         );
@@ -530,11 +581,12 @@ fn test_extract_exif_data_corrupted_gps() {
 
     let (exif_map, gps_coords) = extract_exif_data(&test_exif_data);
 
-    // Corrupted data should gracefully return empty results
+    // Entries read before the cut are recovered, the rest are dropped
     assert!(
-        exif_map.is_empty(),
-        "Corrupted data should return empty EXIF map"
+        exif_map.contains_key("GPSLatitudeRef"),
+        "Entries before the truncation should be recovered"
     );
+    assert!(!exif_map.contains_key("GPSLatitude"));
     assert!(
         gps_coords.is_none(),
         "Corrupted data should return no GPS coordinates"
@@ -593,3 +645,83 @@ fn test_get_dimensions_non_image_mime_types_comprehensive() {
         );
     }
 }
+
+/// Build a little-endian TIFF whose IFD0 and IFD1 both carry a `DateTime`.
+fn tiff_with_thumbnail_datetime(primary: &[u8; 19], thumbnail: &[u8; 19]) -> Vec<u8> {
+    let mut tiff = b"II*\0".to_vec();
+    tiff.extend_from_slice(&8u32.to_le_bytes());
+    // IFD0 at 8 and IFD1 at 26, string data at 44 and 64
+    for (value_offset, next_ifd) in [(44u32, 26u32), (64, 0)] {
+        tiff.extend_from_slice(&1u16.to_le_bytes());
+        tiff.extend_from_slice(&0x0132u16.to_le_bytes()); // DateTime
+        tiff.extend_from_slice(&2u16.to_le_bytes()); // ASCII
+        tiff.extend_from_slice(&20u32.to_le_bytes());
+        tiff.extend_from_slice(&value_offset.to_le_bytes());
+        tiff.extend_from_slice(&next_ifd.to_le_bytes());
+    }
+    for text in [primary, thumbnail] {
+        tiff.extend_from_slice(text);
+        tiff.push(0);
+    }
+    tiff
+}
+
+#[test]
+fn test_extract_exif_data_keeps_thumbnail_fields_separate() {
+    let tiff = tiff_with_thumbnail_datetime(b"2024:05:01 10:00:00", b"2019:01:01 08:00:00");
+    let (map, _) = extract_exif_data(&tiff);
+
+    let primary = map.get("DateTime").expect("primary DateTime");
+    let thumbnail = map.get("Thumbnail:DateTime").expect("thumbnail DateTime");
+    assert!(primary.display.contains("2024"));
+    assert!(thumbnail.display.contains("2019"));
+    assert_eq!(
+        primary.source,
+        Some(MetadataSource::Exif {
+            ifd: Ifd::Primary,
+            tag: 0x0132
+        })
+    );
+    assert_eq!(thumbnail.ifd(), Some(Ifd::Thumbnail));
+}
+
+#[test]
+fn test_display_keeps_primary_and_thumbnail_fields_apart() {
+    let tiff = tiff_with_thumbnail_datetime(b"2024:05:01 10:00:00", b"2019:01:01 08:00:00");
+    let (map, _) = extract_exif_data(&tiff);
+    let categories = categorize_metadata(&map);
+
+    let rows = |category: &str| {
+        categories
+            .iter()
+            .find(|(c, _)| *c == category)
+            .map(|(_, rows)| rows.clone())
+            .unwrap_or_default()
+    };
+    let date_rows = rows("🕒 Date & Time");
+    let thumbnail_rows = rows("🧩 Thumbnail (IFD1)");
+    assert_eq!(date_rows.len(), 1);
+    assert_eq!(thumbnail_rows.len(), 1);
+    assert_eq!(date_rows[0].0, "DateTime");
+    assert!(date_rows[0].1.contains("2024"));
+    assert_eq!(thumbnail_rows[0].0, "Thumbnail:DateTime");
+    assert!(thumbnail_rows[0].1.contains("2019"));
+}
+
+#[test]
+fn test_exif_field_key_and_ifd() {
+    assert_eq!(ifd_of(In::PRIMARY, Tag::Make), Ifd::Primary);
+    assert_eq!(ifd_of(In::PRIMARY, Tag::DateTimeOriginal), Ifd::Exif);
+    assert_eq!(ifd_of(In::PRIMARY, Tag::GPSLatitude), Ifd::Gps);
+    assert_eq!(
+        ifd_of(In::PRIMARY, Tag::InteroperabilityIndex),
+        Ifd::Interop
+    );
+    assert_eq!(ifd_of(In::THUMBNAIL, Tag::Compression), Ifd::Thumbnail);
+
+    assert_eq!(exif_field_key(In::PRIMARY, Tag::Make), "Make");
+    assert_eq!(
+        exif_field_key(In::THUMBNAIL, Tag::DateTime),
+        "Thumbnail:DateTime"
+    );
+}
@@ -1,7 +1,7 @@
 use image_metadata_extractor::export::{
     generate_csv_batch, generate_json_batch, generate_txt_batch,
 };
-use image_metadata_extractor::types::ImageData;
+use image_metadata_extractor::types::{GpsInfo, ImageData};
 use std::collections::HashMap;
 use std::rc::Rc;
 
@@ -15,7 +15,7 @@ fn sample(
 ) -> ImageData {
     let mut map = HashMap::new();
     for (k, v) in exif {
-        map.insert((*k).to_string(), (*v).to_string());
+        map.insert((*k).to_string(), (*v).into());
     }
     ImageData {
         name: name.to_string(),
@@ -25,8 +25,9 @@ fn sample(
         width: w,
         height: h,
         exif_data: map,
-        gps_coords: gps,
+        gps: gps.map(|(lat, lon)| GpsInfo::new(lat, lon)),
         sha256_hash: None,
+        ..Default::default()
     }
 }
 
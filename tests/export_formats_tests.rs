@@ -1,16 +1,22 @@
 //! Tests for additional export formats (Markdown, YAML, XML)
 
-use image_metadata_extractor::export::{generate_md, generate_xml, generate_yaml};
-use image_metadata_extractor::types::ImageData;
+use image_metadata_extractor::export::{
+    generate_csv, generate_csv_batch, generate_json, generate_md, generate_txt, generate_xml,
+    generate_yaml, group_fields_by_ifd,
+};
+use image_metadata_extractor::types::{
+    GpsInfo, Ifd, ImageData, MetadataSource, MetadataValue, Rational, RawValue,
+};
 use std::collections::HashMap;
+use std::rc::Rc;
 
 fn create_test_image_data() -> ImageData {
     let mut exif_data = HashMap::new();
-    exif_data.insert("Make".to_string(), "Canon".to_string());
-    exif_data.insert("Model".to_string(), "EOS R5".to_string());
-    exif_data.insert("DateTime".to_string(), "2024:01:01 12:00:00".to_string());
-    exif_data.insert("FNumber".to_string(), "2.8".to_string());
-    exif_data.insert("ISOSpeedRatings".to_string(), "400".to_string());
+    exif_data.insert("Make".to_string(), "Canon".into());
+    exif_data.insert("Model".to_string(), "EOS R5".into());
+    exif_data.insert("DateTime".to_string(), "2024:01:01 12:00:00".into());
+    exif_data.insert("FNumber".to_string(), "2.8".into());
+    exif_data.insert("ISOSpeedRatings".to_string(), "400".into());
 
     ImageData {
         name: "test_image.jpg".to_string(),
@@ -20,8 +26,9 @@ fn create_test_image_data() -> ImageData {
         width: Some(6000),
         height: Some(4000),
         exif_data,
-        gps_coords: Some((40.7128, -74.0060)), // NYC coordinates
+        gps: Some(GpsInfo::new(40.7128, -74.0060)), // NYC coordinates
         sha256_hash: Some("a1b2c3d4e5f6".to_string()),
+        ..Default::default()
     }
 }
 
@@ -92,8 +99,9 @@ fn test_generate_md_minimal_no_gps() {
         width: None,
         height: None,
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let md = generate_md(&data);
@@ -114,8 +122,9 @@ fn test_generate_md_with_dimensions_no_gps() {
         width: Some(800),
         height: Some(600),
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let md = generate_md(&data);
@@ -171,8 +180,9 @@ fn test_generate_yaml_minimal() {
         width: None,
         height: None,
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let yaml = generate_yaml(&data);
@@ -188,10 +198,7 @@ fn test_generate_yaml_minimal() {
 #[test]
 fn test_generate_yaml_quote_escaping() {
     let mut exif_data = HashMap::new();
-    exif_data.insert(
-        "Description".to_string(),
-        "A photo with \"quotes\"".to_string(),
-    );
+    exif_data.insert("Description".to_string(), "A photo with \"quotes\"".into());
 
     let data = ImageData {
         name: "special.jpg".to_string(),
@@ -201,8 +208,9 @@ fn test_generate_yaml_quote_escaping() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let yaml = generate_yaml(&data);
@@ -222,8 +230,9 @@ fn test_generate_yaml_no_dimensions() {
         width: None,
         height: None,
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let yaml = generate_yaml(&data);
@@ -291,8 +300,9 @@ fn test_generate_xml_minimal() {
         width: None,
         height: None,
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let xml = generate_xml(&data);
@@ -310,7 +320,7 @@ fn test_generate_xml_minimal() {
 #[test]
 fn test_generate_xml_special_characters() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("Copyright".to_string(), "<Test & Co.>".to_string());
+    exif_data.insert("Copyright".to_string(), "<Test & Co.>".into());
 
     let data = ImageData {
         name: "special<>&.jpg".to_string(),
@@ -320,8 +330,9 @@ fn test_generate_xml_special_characters() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let xml = generate_xml(&data);
@@ -340,8 +351,9 @@ fn test_generate_xml_no_dimensions() {
         width: None,
         height: None,
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let xml = generate_xml(&data);
@@ -381,8 +393,9 @@ fn test_all_formats_empty_exif() {
         width: Some(800),
         height: Some(600),
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let md = generate_md(&data);
@@ -402,9 +415,9 @@ fn test_all_formats_empty_exif() {
 #[test]
 fn test_formats_sorting_consistency() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("Zebra".to_string(), "last".to_string());
-    exif_data.insert("Apple".to_string(), "first".to_string());
-    exif_data.insert("Middle".to_string(), "middle".to_string());
+    exif_data.insert("Zebra".to_string(), "last".into());
+    exif_data.insert("Apple".to_string(), "first".into());
+    exif_data.insert("Middle".to_string(), "middle".into());
 
     let data = ImageData {
         name: "sort.jpg".to_string(),
@@ -414,8 +427,9 @@ fn test_formats_sorting_consistency() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let md = generate_md(&data);
@@ -432,3 +446,132 @@ fn test_formats_sorting_consistency() {
     assert!(xml.find("Apple").unwrap() < xml.find("Middle").unwrap());
     assert!(xml.find("Middle").unwrap() < xml.find("Zebra").unwrap());
 }
+
+#[test]
+fn test_generate_xml_numeric_value_attributes() {
+    let mut exif_data = HashMap::new();
+    exif_data.insert(
+        "FocalLength".to_string(),
+        MetadataValue::new(
+            RawValue::Rational(vec![Rational { num: 50, denom: 1 }]),
+            "50 mm",
+        )
+        .with_unit("mm"),
+    );
+    exif_data.insert("Make".to_string(), "Canon".into());
+
+    let data = ImageData {
+        name: "numeric.jpg".to_string(),
+        size: 1024,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+
+    let xml = generate_xml(&data);
+    assert!(xml.contains("<tag name=\"FocalLength\" value=\"50\" unit=\"mm\">50 mm</tag>"));
+    assert!(xml.contains("<tag name=\"Make\">Canon</tag>"));
+}
+
+#[test]
+fn test_exports_group_fields_by_ifd() {
+    let mut exif_data = HashMap::new();
+    exif_data.insert(
+        "DateTime".to_string(),
+        MetadataValue::text("2024:05:01 10:00:00").with_source(MetadataSource::Exif {
+            ifd: Ifd::Primary,
+            tag: 0x0132,
+        }),
+    );
+    exif_data.insert(
+        "Thumbnail:DateTime".to_string(),
+        MetadataValue::text("2019:01:01 08:00:00").with_source(MetadataSource::Exif {
+            ifd: Ifd::Thumbnail,
+            tag: 0x0132,
+        }),
+    );
+    exif_data.insert("Comment".to_string(), "hand written".into());
+
+    let data = ImageData {
+        name: "ifd.jpg".to_string(),
+        size: 1024,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+
+    let groups = group_fields_by_ifd(&data);
+    let order: Vec<Option<Ifd>> = groups.iter().map(|(ifd, _)| *ifd).collect();
+    assert_eq!(order, vec![None, Some(Ifd::Primary), Some(Ifd::Thumbnail)]);
+
+    let md = generate_md(&data);
+    assert!(md.find("- Comment").unwrap() < md.find("### Primary image (IFD0)").unwrap());
+    assert!(
+        md.find("### Primary image (IFD0)").unwrap() < md.find("### Thumbnail (IFD1)").unwrap()
+    );
+
+    let xml = generate_xml(&data);
+    assert!(xml.contains("<tag name=\"Thumbnail:DateTime\" ifd=\"thumbnail\" id=\"0x0132\">"));
+    assert!(xml.contains("<tag name=\"DateTime\" ifd=\"primary\" id=\"0x0132\">"));
+    assert!(xml.find("name=\"Comment\"").unwrap() < xml.find("name=\"DateTime\"").unwrap());
+
+    let txt = generate_txt(&data);
+    assert!(
+        txt.find("Comment: hand written").unwrap() < txt.find("[Primary image (IFD0)]").unwrap()
+    );
+    assert!(
+        txt.find("[Primary image (IFD0)]\nDateTime: 2024:05:01 10:00:00")
+            .unwrap()
+            < txt
+                .find("[Thumbnail (IFD1)]\nThumbnail:DateTime: 2019:01:01 08:00:00")
+                .unwrap()
+    );
+
+    let csv = generate_csv(&data);
+    assert!(csv.starts_with("Property,Value,IFD\n"));
+    assert!(csv.contains("\"Comment\",\"hand written\"\n"));
+    assert!(csv.contains("\"DateTime\",\"2024:05:01 10:00:00\",primary\n"));
+    assert!(csv.contains("\"Thumbnail:DateTime\",\"2019:01:01 08:00:00\",thumbnail\n"));
+
+    let batch = generate_csv_batch(&[Rc::new(data.clone())]);
+    assert!(
+        batch
+            .lines()
+            .next()
+            .unwrap()
+            .ends_with("\"GPS Direction\",\"Comment\",\"DateTime\",\"Thumbnail:DateTime\"")
+    );
+
+    let yaml = generate_yaml(&data);
+    assert!(yaml.contains(
+        "exif:\n  \"Comment\": \"hand written\"\nifds:\n  primary:\n    \"DateTime\": \"2024:05:01 10:00:00\"\n  thumbnail:\n"
+    ));
+
+    // JSON keeps the IFD in each value's source and nests IFD fields
+    let json: serde_json::Value = serde_json::from_str(&generate_json(&data)).unwrap();
+    assert_eq!(
+        json["exif_data"],
+        serde_json::json!({"Comment": "hand written"})
+    );
+    assert_eq!(
+        json["ifds"]["thumbnail"]["Thumbnail:DateTime"],
+        serde_json::json!({
+            "value": "2019:01:01 08:00:00",
+            "source": {"kind": "exif", "ifd": "thumbnail", "tag": 0x0132}
+        })
+    );
+    assert_eq!(
+        json["ifds"]["primary"]["DateTime"]["source"]["ifd"],
+        "primary"
+    );
+}
@@ -1,5 +1,5 @@
 use image_metadata_extractor::export::{generate_csv, generate_txt};
-use image_metadata_extractor::types::ImageData;
+use image_metadata_extractor::types::{GpsInfo, ImageData};
 use std::collections::HashMap;
 
 #[test]
@@ -13,8 +13,9 @@ fn test_generate_csv_minimal_data() {
         width: None,
         height: None,
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let csv = generate_csv(&data);
@@ -41,8 +42,9 @@ fn test_generate_txt_includes_map_links() {
         width: None,
         height: None,
         exif_data: HashMap::new(),
-        gps_coords: Some((10.0, 20.0)),
+        gps: Some(GpsInfo::new(10.0, 20.0)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let txt = generate_txt(&data);
@@ -61,8 +63,9 @@ fn test_generate_txt_no_metadata_message() {
         width: Some(100),
         height: Some(200),
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let txt = generate_txt(&data);
@@ -73,8 +76,8 @@ fn test_generate_txt_no_metadata_message() {
 #[test]
 fn test_generate_csv_includes_all_fields() {
     let mut exif = HashMap::new();
-    exif.insert("Make".to_string(), "Canon".to_string());
-    exif.insert("Model".to_string(), "EOS \"M\"".to_string());
+    exif.insert("Make".to_string(), "Canon".into());
+    exif.insert("Model".to_string(), "EOS \"M\"".into());
 
     let data = ImageData {
         name: "pic.jpg".to_string(),
@@ -84,8 +87,9 @@ fn test_generate_csv_includes_all_fields() {
         width: Some(400),
         height: Some(300),
         exif_data: exif,
-        gps_coords: Some((12.3, 45.6)),
+        gps: Some(GpsInfo::new(12.3, 45.6)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let csv = generate_csv(&data);
@@ -112,8 +116,9 @@ fn test_generate_csv_dimensions_only() {
         width: Some(1920),
         height: Some(1080),
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let csv = generate_csv(&data);
@@ -139,8 +144,9 @@ fn test_generate_csv_gps_only() {
         width: None,
         height: None,
         exif_data: HashMap::new(),
-        gps_coords: Some((37.7749, -122.4194)),
+        gps: Some(GpsInfo::new(37.7749, -122.4194)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let csv = generate_csv(&data);
@@ -159,10 +165,10 @@ fn test_generate_csv_gps_only() {
 fn test_generate_csv_quote_escaping() {
     // Test CSV quote escaping in EXIF data
     let mut exif = HashMap::new();
-    exif.insert("Description".to_string(), "Photo \"test\"".to_string());
+    exif.insert("Description".to_string(), "Photo \"test\"".into());
     exif.insert(
         "Comment".to_string(),
-        "Multiple \"quotes\" in \"text\"".to_string(),
+        "Multiple \"quotes\" in \"text\"".into(),
     );
 
     let data = ImageData {
@@ -173,8 +179,9 @@ fn test_generate_csv_quote_escaping() {
         width: None,
         height: None,
         exif_data: exif,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let csv = generate_csv(&data);
@@ -188,8 +195,8 @@ fn test_generate_csv_quote_escaping() {
 fn test_generate_txt_with_metadata() {
     // Test TXT generation with EXIF metadata (covers lines 77-82)
     let mut exif = HashMap::new();
-    exif.insert("Camera".to_string(), "Canon EOS".to_string());
-    exif.insert("ISO".to_string(), "200".to_string());
+    exif.insert("Camera".to_string(), "Canon EOS".into());
+    exif.insert("ISO".to_string(), "200".into());
 
     let data = ImageData {
         name: "meta.jpg".to_string(),
@@ -199,8 +206,9 @@ fn test_generate_txt_with_metadata() {
         width: Some(800),
         height: Some(600),
         exif_data: exif,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let txt = generate_txt(&data);
@@ -231,8 +239,9 @@ fn test_generate_txt_no_dimensions() {
         width: None,
         height: None,
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let txt = generate_txt(&data);
@@ -249,8 +258,8 @@ fn test_generate_txt_no_dimensions() {
 fn test_generate_txt_complete_report() {
     // Test TXT generation with all sections (file info, GPS, metadata)
     let mut exif = HashMap::new();
-    exif.insert("Make".to_string(), "Apple".to_string());
-    exif.insert("Model".to_string(), "iPhone 12".to_string());
+    exif.insert("Make".to_string(), "Apple".into());
+    exif.insert("Model".to_string(), "iPhone 12".into());
 
     let data = ImageData {
         name: "complete.heic".to_string(),
@@ -260,8 +269,9 @@ fn test_generate_txt_complete_report() {
         width: Some(4032),
         height: Some(3024),
         exif_data: exif,
-        gps_coords: Some((40.7128, -74.0060)),
+        gps: Some(GpsInfo::new(40.7128, -74.0060)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let txt = generate_txt(&data);
@@ -0,0 +1,192 @@
+use image_metadata_extractor::binary_cleaner::BinaryCleaner;
+use image_metadata_extractor::exif_core::determine_mime_type;
+use image_metadata_extractor::file_type::{
+    canonical_mime_type, check_type, cleaner_extension, detect_mime_type, extension_mime_type,
+};
+
+fn ftyp(major: &[u8; 4], compatible: &[&[u8; 4]]) -> Vec<u8> {
+    let size = 16 + 4 * compatible.len() as u32;
+    let mut data = size.to_be_bytes().to_vec();
+    data.extend_from_slice(b"ftyp");
+    data.extend_from_slice(major);
+    data.extend_from_slice(&[0, 0, 0, 0]); // minor version
+    for brand in compatible {
+        data.extend_from_slice(*brand);
+    }
+    data
+}
+
+fn with_moov(mut data: Vec<u8>) -> Vec<u8> {
+    data.extend_from_slice(&[0, 0, 0, 8]);
+    data.extend_from_slice(b"moov");
+    data
+}
+
+fn png_with_text() -> Vec<u8> {
+    let mut png = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+    for (id, payload) in [
+        (b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 0][..]),
+        (b"tEXt", b"Author\0Alice"),
+        (b"IDAT", &[0x78, 0x9C, 0x03, 0x00]),
+        (b"IEND", &[]),
+    ] {
+        png.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        png.extend_from_slice(id);
+        png.extend_from_slice(payload);
+        png.extend_from_slice(&[0, 0, 0, 0]);
+    }
+    png
+}
+
+#[test]
+fn detects_signature_formats() {
+    assert_eq!(
+        detect_mime_type(&[0xFF, 0xD8, 0xFF, 0xE1]),
+        Some("image/jpeg")
+    );
+    assert_eq!(detect_mime_type(&png_with_text()), Some("image/png"));
+    assert_eq!(detect_mime_type(b"GIF87a\x01\x00"), Some("image/gif"));
+    assert_eq!(
+        detect_mime_type(b"RIFF\x04\x00\x00\x00WEBPVP8 "),
+        Some("image/webp")
+    );
+    assert_eq!(detect_mime_type(&[0xFF, 0x0A, 0x00]), Some("image/jxl"));
+    assert_eq!(
+        detect_mime_type(&[
+            0x00, 0x00, 0x00, 0x0C, b'J', b'X', b'L', b' ', 0x0D, 0x0A, 0x87, 0x0A
+        ]),
+        Some("image/jxl")
+    );
+    assert_eq!(detect_mime_type(b"%PDF-1.7\n"), Some("application/pdf"));
+    assert_eq!(
+        detect_mime_type(b"\r\n\r\n%PDF-1.4\n"),
+        Some("application/pdf")
+    );
+    assert_eq!(
+        detect_mime_type(b"\xEF\xBB\xBF<?xml version=\"1.0\"?>\n<svg xmlns=\"\"/>"),
+        Some("image/svg+xml")
+    );
+    assert_eq!(detect_mime_type(b"<html><body/></html>"), None);
+    assert_eq!(detect_mime_type(b""), None);
+}
+
+#[test]
+fn detects_tiff_in_either_byte_order() {
+    assert_eq!(detect_mime_type(b"II*\0\x08\0\0\0\0\0"), Some("image/tiff"));
+    assert_eq!(detect_mime_type(b"MM\0*\0\0\0\x08\0\0"), Some("image/tiff"));
+    assert_eq!(
+        detect_mime_type(b"II+\0\x08\0\0\0\x10\0\0\0\0\0\0\0"),
+        Some("image/tiff")
+    );
+}
+
+#[test]
+fn isobmff_brands_separate_heif_avif_and_video() {
+    for major in [b"heic", b"heix", b"mif1", b"msf1", b"hevc"] {
+        assert_eq!(
+            detect_mime_type(&ftyp(major, &[b"mif1"])),
+            Some("image/heif"),
+            "{}",
+            String::from_utf8_lossy(major)
+        );
+    }
+    assert_eq!(detect_mime_type(&ftyp(b"avif", &[])), Some("image/avif"));
+    assert_eq!(
+        detect_mime_type(&ftyp(b"mif1", &[b"avif", b"miaf"])),
+        Some("image/avif")
+    );
+    assert_eq!(
+        detect_mime_type(&ftyp(b"msf1", &[b"avis"])),
+        Some("image/avif")
+    );
+    assert_eq!(
+        detect_mime_type(&with_moov(ftyp(b"isom", &[b"mp41"]))),
+        Some("video/mp4")
+    );
+    assert_eq!(
+        detect_mime_type(&with_moov(ftyp(b"qt  ", &[]))),
+        Some("video/quicktime")
+    );
+    assert_eq!(
+        detect_mime_type(&with_moov(ftyp(b"3gp4", &[]))),
+        Some("video/3gpp")
+    );
+    // An unknown major brand is recognised by its compatible brands
+    assert_eq!(
+        detect_mime_type(&ftyp(b"miaf", &[b"heic"])),
+        Some("image/heif")
+    );
+    assert_eq!(detect_mime_type(&ftyp(b"zzzz", &[b"zzzz"])), None);
+}
+
+#[test]
+fn content_wins_over_declared_type_and_extension() {
+    let png = png_with_text();
+    assert_eq!(
+        determine_mime_type("photo.jpg", "image/jpeg", &png),
+        "image/png"
+    );
+    let heic = ftyp(b"heix", &[b"mif1"]);
+    assert_eq!(determine_mime_type("IMG_0001.jpg", "", &heic), "image/heif");
+    // Unrecognised content falls back to the declared type, then the name
+    assert_eq!(
+        determine_mime_type("a.heic", "image/heic", b"????"),
+        "image/heif"
+    );
+    assert_eq!(determine_mime_type("a.heic", "", b"????"), "image/heif");
+}
+
+#[test]
+fn type_check_reports_mismatches() {
+    let check = check_type("photo.jpg", "image/jpeg", &png_with_text());
+    assert_eq!(check.declared.as_deref(), Some("image/jpeg"));
+    assert_eq!(check.extension.as_deref(), Some("image/jpeg"));
+    assert_eq!(check.detected.as_deref(), Some("image/png"));
+    assert!(check.is_mismatch());
+    assert_eq!(check.summary(), "declared image/jpeg, detected image/png");
+    assert_eq!(check.resolved(), "image/png");
+
+    // Browser aliases are not mismatches
+    let check = check_type("IMG_0001.HEIC", "image/heic", &ftyp(b"heic", &[]));
+    assert!(!check.is_mismatch());
+
+    let check = check_type("notes.txt", "", b"plain text");
+    assert!(!check.is_mismatch());
+    assert_eq!(check.summary(), "no declared type, content not recognised");
+    assert_eq!(check.resolved(), "application/octet-stream");
+
+    // A generic or missing declared type is unknown, not a mismatch
+    for declared in ["application/octet-stream", "", " "] {
+        let check = check_type("photo.png", declared, &png_with_text());
+        assert_eq!(check.declared, None);
+        assert!(!check.is_mismatch());
+        assert_eq!(check.summary(), "extension image/png, detected image/png");
+    }
+}
+
+#[test]
+fn extension_and_alias_tables() {
+    assert_eq!(extension_mime_type("A.JPEG"), Some("image/jpeg"));
+    assert_eq!(extension_mime_type("clip.m4v"), Some("video/mp4"));
+    assert_eq!(
+        extension_mime_type("IMG_0042.dng"),
+        Some("image/x-adobe-dng")
+    );
+    assert_eq!(extension_mime_type("README"), None);
+    assert_eq!(canonical_mime_type("image/jpg"), "image/jpeg");
+    assert_eq!(canonical_mime_type("image/heic-sequence"), "image/heif");
+    assert_eq!(canonical_mime_type("image/png"), "image/png");
+    assert_eq!(cleaner_extension("image/heif"), Some("heic"));
+    assert_eq!(cleaner_extension("video/mp4"), None);
+}
+
+#[test]
+fn cleaner_uses_detected_format() {
+    let cleaned = BinaryCleaner::clean_metadata(&png_with_text(), "jpg").unwrap();
+    assert!(cleaned.starts_with(&[0x89, b'P', b'N', b'G']));
+    assert!(!cleaned.windows(5).any(|w| w == b"Alice"));
+
+    let video = with_moov(ftyp(b"isom", &[]));
+    let error = BinaryCleaner::clean_metadata(&video, "jpg").unwrap_err();
+    assert!(error.contains("video/mp4"));
+}
@@ -1,6 +1,8 @@
 // Tests targeting uncovered GPS coordinate handling edge cases in exif_core.rs
 // These tests focus on specific coordinate update paths and reference handling
 
+#![allow(clippy::manual_range_contains, clippy::assertions_on_constants)]
+
 use image_metadata_extractor::exif_core::extract_exif_data;
 
 // Test data with specific GPS coordinate patterns to trigger different code paths
@@ -22,7 +24,7 @@ fn test_gps_southern_hemisphere_coordinates() {
     // If GPS coordinates are extracted, the parsing logic was exercised
     if gps_coords.is_some() {
         assert!(!exif_map.is_empty(), "Should extract EXIF fields");
-        if let Some((lat, lon)) = gps_coords {
+        if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
             // Test that coordinates are in valid range (reference was applied)
             assert!(
                 lat >= -90.0 && lat <= 90.0,
@@ -56,7 +58,7 @@ fn test_gps_western_hemisphere_coordinates() {
 
         if gps_field_count > 0 {
             // GPS fields were processed, which exercises the reference handling
-            if let Some((lat, lon)) = gps_coords {
+            if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
                 assert!(
                     lat.is_finite(),
                     "Latitude should be finite after processing"
@@ -82,7 +84,7 @@ fn test_gps_coordinate_ordering_longitude_first() {
     if gps_coords.is_some() {
         assert!(!exif_map.is_empty(), "Should extract EXIF fields");
 
-        if let Some((lat, lon)) = gps_coords {
+        if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
             // Both coordinates should be set, indicating both update paths were used
             assert!(lat != 0.0 || lon != 0.0, "Should have non-zero coordinates");
 
@@ -108,7 +110,7 @@ fn test_malformed_gps_reference_fields() {
     );
 
     // If GPS coordinates are present, they should be valid numbers
-    if let Some((lat, lon)) = gps_coords {
+    if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
         assert!(lat.is_finite(), "Latitude should be a valid number");
         assert!(lon.is_finite(), "Longitude should be a valid number");
     }
@@ -124,7 +126,7 @@ fn test_gps_reference_error_handling() {
 
     // The GPS reference parsing should complete without panicking
     // Even if references are malformed, coordinates should either be None or valid
-    match gps_coords {
+    match gps_coords.map(|g| g.coords()) {
         Some((lat, lon)) => {
             assert!(
                 lat.is_finite() && lon.is_finite(),
@@ -172,7 +174,7 @@ fn test_comprehensive_gps_field_iteration() {
     );
 
     // If GPS data exists, verify it was processed completely
-    if let Some((lat, lon)) = gps_coords {
+    if let Some((lat, lon)) = gps_coords.map(|g| g.coords()) {
         // This indicates the coordinate building and reference application completed
         assert!(
             lat >= -90.0 && lat <= 90.0,
@@ -0,0 +1,185 @@
+mod common;
+
+use common::{Entry, ascii, ifd};
+use image_metadata_extractor::exif_core::extract_exif_data;
+use image_metadata_extractor::export::{generate_csv_batch, generate_txt, generate_xml};
+use image_metadata_extractor::types::{GpsInfo, ImageData};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+fn byte(tag: u16, value: u8) -> Entry {
+    (tag, 1, 1, vec![value])
+}
+
+fn undefined(tag: u16, bytes: &[u8]) -> Entry {
+    (tag, 7, bytes.len() as u32, bytes.to_vec())
+}
+
+fn rationals(tag: u16, values: &[(u32, u32)]) -> Entry {
+    let bytes = values
+        .iter()
+        .flat_map(|(n, d)| n.to_le_bytes().into_iter().chain(d.to_le_bytes()))
+        .collect();
+    (tag, 5, values.len() as u32, bytes)
+}
+
+/// Little-endian TIFF whose IFD0 only points at a GPS IFD with `entries`.
+fn tiff_with_gps(entries: &[Entry]) -> Vec<u8> {
+    let mut tiff = b"II*\0".to_vec();
+    tiff.extend_from_slice(&8u32.to_le_bytes());
+    tiff.extend_from_slice(&1u16.to_le_bytes());
+    tiff.extend_from_slice(&[0x25, 0x88, 4, 0, 1, 0, 0, 0]);
+    tiff.extend_from_slice(&26u32.to_le_bytes());
+    tiff.extend_from_slice(&0u32.to_le_bytes());
+    tiff.extend(ifd(entries, 26, 0));
+    tiff
+}
+
+fn full_gps_block() -> Vec<Entry> {
+    vec![
+        ascii(0x0001, "S"),
+        rationals(0x0002, &[(33, 1), (52, 1), (1234, 100)]),
+        ascii(0x0003, "E"),
+        rationals(0x0004, &[(151, 1), (12, 1), (3000, 100)]),
+        byte(0x0005, 1),
+        rationals(0x0006, &[(125, 10)]),
+        rationals(0x0007, &[(14, 1), (5, 1), (3050, 100)]),
+        ascii(0x000C, "N"),
+        rationals(0x000D, &[(10, 1)]),
+        ascii(0x0010, "M"),
+        rationals(0x0011, &[(13550, 100)]),
+        ascii(0x0012, "WGS-84"),
+        undefined(0x001B, b"ASCII\0\0\0GPS"),
+        ascii(0x001D, "2024:03:15"),
+        rationals(0x001F, &[(5, 1)]),
+    ]
+}
+
+fn image_with_gps(gps: GpsInfo) -> ImageData {
+    ImageData {
+        name: "gps.jpg".to_string(),
+        size: 1024,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data: HashMap::new(),
+        gps: Some(gps),
+        sha256_hash: None,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_full_gps_block_is_parsed() {
+    let (_, gps) = extract_exif_data(&tiff_with_gps(&full_gps_block()));
+    let gps = gps.expect("GPS block");
+
+    assert!((gps.latitude + (33.0 + 52.0 / 60.0 + 12.34 / 3600.0)).abs() < 1e-9);
+    assert!((gps.longitude - (151.0 + 12.0 / 60.0 + 30.0 / 3600.0)).abs() < 1e-9);
+    assert_eq!(gps.altitude, Some(-12.5));
+    assert_eq!(gps.img_direction, Some(135.5));
+    assert_eq!(gps.img_direction_ref.as_deref(), Some("Magnetic North"));
+    assert!((gps.speed_kmh.unwrap() - 18.52).abs() < 1e-9);
+    let timestamp = gps.timestamp.unwrap();
+    assert_eq!(timestamp.to_iso8601(), "2024-03-15T14:05:30.5Z");
+    assert_eq!(timestamp.utc_seconds(), Some(1_710_511_530));
+    assert_eq!(gps.horizontal_error, Some(5.0));
+    assert_eq!(gps.map_datum.as_deref(), Some("WGS-84"));
+    assert_eq!(gps.processing_method.as_deref(), Some("GPS"));
+}
+
+#[test]
+fn test_partial_gps_block() {
+    let (_, gps) = extract_exif_data(&tiff_with_gps(&[
+        ascii(0x0001, "N"),
+        rationals(0x0002, &[(10, 1), (0, 1), (0, 1)]),
+        rationals(0x0006, &[(300, 1)]),
+        rationals(0x0007, &[(8, 1), (0, 1), (0, 1)]),
+    ]));
+    let gps = gps.expect("GPS block");
+    assert_eq!(gps.coords(), (10.0, 0.0));
+    assert_eq!(gps.altitude, Some(300.0));
+    // A time without a date is not a usable UTC instant
+    assert_eq!(gps.timestamp, None);
+    assert_eq!(gps.img_direction, None);
+
+    let (_, gps) = extract_exif_data(&tiff_with_gps(&[ascii(0x0012, "WGS-84")]));
+    assert!(gps.is_none());
+}
+
+#[test]
+fn test_heading_and_altitude_display() {
+    let gps = GpsInfo {
+        altitude: Some(-12.5),
+        img_direction: Some(135.5),
+        img_direction_ref: Some("True North".to_string()),
+        ..GpsInfo::new(1.0, 2.0)
+    };
+    assert_eq!(gps.compass_point(), Some("SE"));
+    assert_eq!(
+        gps.heading_display().as_deref(),
+        Some("135.5° SE (True North)")
+    );
+    assert_eq!(
+        gps.altitude_display().as_deref(),
+        Some("12.5 m below sea level")
+    );
+
+    let north = GpsInfo {
+        img_direction: Some(350.0),
+        ..GpsInfo::new(0.0, 0.0)
+    };
+    assert_eq!(north.compass_point(), Some("N"));
+    assert_eq!(GpsInfo::new(0.0, 0.0).compass_point(), None);
+}
+
+#[test]
+fn test_exports_render_heading_and_altitude() {
+    let (_, gps) = extract_exif_data(&tiff_with_gps(&full_gps_block()));
+    let data = image_with_gps(gps.unwrap());
+
+    let txt = generate_txt(&data);
+    assert!(txt.contains("Altitude: 12.5 m below sea level"));
+    assert!(txt.contains("Direction: 135.5° SE (Magnetic North)"));
+    assert!(txt.contains("Time (UTC): 2024-03-15T14:05:30.5Z"));
+
+    let xml = generate_xml(&data);
+    assert!(xml.contains("<altitude unit=\"m\">-12.5</altitude>"));
+    assert!(xml.contains("<direction ref=\"Magnetic North\">135.5</direction>"));
+    assert!(xml.contains("<accuracy unit=\"m\">5</accuracy>"));
+
+    let json = serde_json::to_string(&data).unwrap();
+    assert!(json.contains("\"altitude\":-12.5"));
+    assert!(json.contains("\"timestamp\":\"2024-03-15T14:05:30.5Z\""));
+
+    let csv = generate_csv_batch(&[Rc::new(data)]);
+    let header = csv.lines().next().unwrap();
+    assert!(header.contains("\"GPS Altitude\",\"GPS Direction\""));
+    assert!(csv.lines().nth(1).unwrap().contains("\"-12.5\",\"135.5\""));
+}
+
+#[test]
+fn test_direction_and_accuracy_add_privacy_exposure() {
+    let plain = image_with_gps(GpsInfo::new(40.0, -74.0)).calculate_privacy_risk();
+    assert!(
+        !plain
+            .sensitive_fields
+            .iter()
+            .any(|f| f == "GPS Direction/Accuracy")
+    );
+
+    let detailed = image_with_gps(GpsInfo {
+        img_direction: Some(90.0),
+        horizontal_error: Some(3.0),
+        ..GpsInfo::new(40.0, -74.0)
+    })
+    .calculate_privacy_risk();
+    assert!(
+        detailed
+            .sensitive_fields
+            .iter()
+            .any(|f| f == "GPS Direction/Accuracy")
+    );
+    assert_eq!(detailed.score, plain.score + 10);
+}
@@ -0,0 +1,232 @@
+mod common;
+
+use common::{bx, exif_tiff};
+use image_metadata_extractor::container;
+use image_metadata_extractor::exif_core::{extract_exif_data, extract_metadata};
+use image_metadata_extractor::heif::{
+    auxiliary_type_name, dimensions, extract_heif_data, find_exif, find_xmp, parse_heif,
+};
+use image_metadata_extractor::types::MetadataSource;
+use image_metadata_extractor::xmp::extract_xmp_data;
+
+const XMP: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:CreatorTool="17.1"/></rdf:RDF></x:xmpmeta>"#;
+
+fn full_bx(kind: &[u8; 4], version: u8, flags: u32, payload: &[u8]) -> Vec<u8> {
+    let mut body = flags.to_be_bytes().to_vec();
+    body[0] = version;
+    body.extend_from_slice(payload);
+    bx(kind, &body)
+}
+
+fn infe(id: u16, kind: &[u8; 4], hidden: bool, content_type: Option<&str>) -> Vec<u8> {
+    let mut body = id.to_be_bytes().to_vec();
+    body.extend_from_slice(&[0, 0]);
+    body.extend_from_slice(kind);
+    body.push(0); // empty name
+    if let Some(content_type) = content_type {
+        body.extend_from_slice(content_type.as_bytes());
+        body.push(0);
+    }
+    full_bx(b"infe", 2, hidden as u32, &body)
+}
+
+/// A HEIC with a primary image, Exif in `mdat`, XMP in `idat`, a depth map
+/// and a thumbnail.
+fn heic() -> Vec<u8> {
+    let image = vec![0xAB; 32];
+    let mut exif = 6u32.to_be_bytes().to_vec();
+    exif.extend_from_slice(b"Exif\0\0");
+    exif.extend(exif_tiff("Apple", "iPhone 15 Pro"));
+
+    let ftyp = bx(b"ftyp", b"heic\0\0\0\0mif1heic");
+    let meta = |mdat_start: u32| {
+        let mut iinf = 5u16.to_be_bytes().to_vec();
+        iinf.extend(infe(1, b"hvc1", false, None));
+        iinf.extend(infe(2, b"Exif", true, None));
+        iinf.extend(infe(3, b"mime", true, Some("application/rdf+xml")));
+        iinf.extend(infe(4, b"hvc1", true, None));
+        iinf.extend(infe(5, b"hvc1", true, None));
+
+        // iloc version 1: 4-byte offsets and lengths, no base offset
+        let mut iloc = vec![0x44, 0x00];
+        iloc.extend_from_slice(&3u16.to_be_bytes());
+        let locations = [
+            (1u16, 0u16, mdat_start, image.len() as u32),
+            (2, 0, mdat_start + image.len() as u32, exif.len() as u32),
+            (3, 1, 0, XMP.len() as u32),
+        ];
+        for (id, method, offset, length) in locations {
+            iloc.extend_from_slice(&id.to_be_bytes());
+            iloc.extend_from_slice(&method.to_be_bytes());
+            iloc.extend_from_slice(&0u16.to_be_bytes());
+            iloc.extend_from_slice(&1u16.to_be_bytes());
+            iloc.extend_from_slice(&offset.to_be_bytes());
+            iloc.extend_from_slice(&length.to_be_bytes());
+        }
+
+        let ispe = |w: u32, h: u32| {
+            let mut body = w.to_be_bytes().to_vec();
+            body.extend_from_slice(&h.to_be_bytes());
+            full_bx(b"ispe", 0, 0, &body)
+        };
+        let mut ipco = ispe(4032, 3024);
+        ipco.extend(ispe(768, 576));
+        ipco.extend(full_bx(b"auxC", 0, 0, b"urn:mpeg:hevc:2015:auxid:2\0"));
+        ipco.extend(ispe(320, 240));
+        let mut ipma = 3u32.to_be_bytes().to_vec();
+        for (id, props) in [(1u16, &[0x81u8][..]), (4, &[2, 3]), (5, &[4])] {
+            ipma.extend_from_slice(&id.to_be_bytes());
+            ipma.push(props.len() as u8);
+            ipma.extend_from_slice(props);
+        }
+        let mut iprp = bx(b"ipco", &ipco);
+        iprp.extend(full_bx(b"ipma", 0, 0, &ipma));
+
+        let reference = |kind: &[u8; 4], from: u16, to: u16| {
+            let mut body = from.to_be_bytes().to_vec();
+            body.extend_from_slice(&1u16.to_be_bytes());
+            body.extend_from_slice(&to.to_be_bytes());
+            bx(kind, &body)
+        };
+        let mut iref = reference(b"auxl", 4, 1);
+        iref.extend(reference(b"thmb", 5, 1));
+        iref.extend(reference(b"cdsc", 2, 1));
+
+        let mut body = full_bx(b"hdlr", 0, 0, b"\0\0\0\0pict\0\0\0\0\0\0\0\0\0\0\0\0\0");
+        body.extend(full_bx(b"pitm", 0, 0, &1u16.to_be_bytes()));
+        body.extend(full_bx(b"iinf", 0, 0, &iinf));
+        body.extend(full_bx(b"iloc", 1, 0, &iloc));
+        body.extend(bx(b"iprp", &iprp));
+        body.extend(full_bx(b"iref", 0, 0, &iref));
+        body.extend(bx(b"idat", XMP.as_bytes()));
+        full_bx(b"meta", 0, 0, &body)
+    };
+
+    let meta_len = meta(0).len();
+    let mdat_start = (ftyp.len() + meta_len + 8) as u32;
+    let mut mdat = image.clone();
+    mdat.extend_from_slice(&exif);
+
+    let mut file = ftyp;
+    file.extend(meta(mdat_start));
+    file.extend(bx(b"mdat", &mdat));
+    file
+}
+
+#[test]
+fn test_iso_boxes_sizes() {
+    let mut data = bx(b"free", b"abc");
+    // 64-bit largesize box
+    data.extend_from_slice(&1u32.to_be_bytes());
+    data.extend_from_slice(b"mdat");
+    data.extend_from_slice(&20u64.to_be_bytes());
+    data.extend_from_slice(b"data");
+    // Size 0 runs to the end
+    data.extend_from_slice(&0u32.to_be_bytes());
+    data.extend_from_slice(b"skip");
+    data.extend_from_slice(b"tail");
+
+    let boxes = container::iso_boxes(&data);
+    let names: Vec<String> = boxes.iter().map(|b| b.name()).collect();
+    assert_eq!(names, ["free", "mdat", "skip"]);
+    assert_eq!(boxes[1].data, b"data");
+    assert_eq!(boxes[2].data, b"tail");
+
+    // A box running past the end stops the walk
+    let truncated = &data[..20];
+    assert_eq!(container::iso_boxes(truncated).len(), 1);
+}
+
+#[test]
+fn test_parse_heif_structure() {
+    let file = parse_heif(&heic()).unwrap();
+    assert_eq!(file.major_brand, "heic");
+    assert_eq!(file.compatible_brands, ["mif1", "heic"]);
+    assert_eq!(file.primary_item, Some(1));
+    assert_eq!(file.items.len(), 5);
+    assert_eq!(file.primary().unwrap().dimensions, Some((4032, 3024)));
+    assert!(file.item(2).unwrap().hidden);
+    assert_eq!(
+        file.item(3).unwrap().content_type.as_deref(),
+        Some("application/rdf+xml")
+    );
+
+    let auxiliary = file.auxiliary_images();
+    assert_eq!(auxiliary.len(), 1);
+    assert_eq!(auxiliary[0].id, 4);
+    assert_eq!(auxiliary[0].dimensions, Some((768, 576)));
+    assert_eq!(
+        auxiliary[0].aux_type.as_deref(),
+        Some("urn:mpeg:hevc:2015:auxid:2")
+    );
+    assert!(parse_heif(b"not a heif file").is_err());
+}
+
+#[test]
+fn test_exif_and_xmp_items_are_located() {
+    let data = heic();
+    assert_eq!(
+        find_exif(&data).unwrap(),
+        exif_tiff("Apple", "iPhone 15 Pro")
+    );
+    assert_eq!(find_xmp(&data), [XMP]);
+    assert_eq!(dimensions(&data), Some((4032, 3024)));
+
+    let (exif, _) = extract_exif_data(&data);
+    assert_eq!(exif["Make"].as_text(), Some("Apple"));
+    assert_eq!(exif["Model"].as_text(), Some("iPhone 15 Pro"));
+    assert_eq!(extract_xmp_data(&data)["XMP-xmp:CreatorTool"], "17.1");
+}
+
+#[test]
+fn test_heif_metadata_keys() {
+    let map = extract_heif_data(&heic());
+    assert_eq!(map["HEIF:MajorBrand"], "heic");
+    assert_eq!(map["HEIF:ItemCount"].as_i64(), Some(5));
+    assert_eq!(map["HEIF:PrimaryItem"], "1 (hvc1)");
+    assert_eq!(map["HEIF:ImageWidth"].as_i64(), Some(4032));
+    assert_eq!(map["HEIF:ThumbnailCount"].as_i64(), Some(1));
+    assert_eq!(map["HEIF:AuxiliaryImages"], "Depth (768x576)");
+    assert_eq!(
+        map["HEIF:ImageWidth"].source,
+        Some(MetadataSource::Bmff {
+            box_type: "ispe".to_string()
+        })
+    );
+
+    let (all, _) = extract_metadata(&heic());
+    assert!(all.contains_key("Make"));
+    assert!(all.contains_key("XMP-xmp:CreatorTool"));
+    assert!(all.contains_key("HEIF:AuxiliaryImages"));
+}
+
+#[test]
+fn test_auxiliary_type_names() {
+    assert_eq!(
+        auxiliary_type_name("urn:mpeg:mpegB:cicp:systems:auxiliary:alpha"),
+        "Alpha"
+    );
+    assert_eq!(auxiliary_type_name("urn:mpeg:hevc:2015:auxid:1"), "Alpha");
+    assert_eq!(
+        auxiliary_type_name("urn:com:apple:photo:2020:aux:hdrgainmap"),
+        "HDR Gain Map"
+    );
+    assert_eq!(
+        auxiliary_type_name("urn:com:apple:photo:2019:aux:semanticskinmatte"),
+        "Portrait Matte"
+    );
+    assert_eq!(
+        auxiliary_type_name("urn:example:other"),
+        "urn:example:other"
+    );
+}
+
+#[test]
+fn test_truncated_heif_does_not_panic() {
+    let data = heic();
+    for len in (0..data.len()).step_by(7) {
+        let _ = parse_heif(&data[..len]);
+        let _ = extract_heif_data(&data[..len]);
+        let _ = find_exif(&data[..len]);
+    }
+}
@@ -0,0 +1,281 @@
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use image_metadata_extractor::container;
+use image_metadata_extractor::icc::{
+    extract_icc_data, find_icc_profile, is_standard_profile, parse_icc_profile,
+};
+use image_metadata_extractor::types::{ImageData, MetadataSource};
+use std::collections::HashMap;
+use std::io::Write;
+
+fn desc_tag(text: &str) -> Vec<u8> {
+    let mut tag = b"desc\0\0\0\0".to_vec();
+    tag.extend_from_slice(&((text.len() + 1) as u32).to_be_bytes());
+    tag.extend_from_slice(text.as_bytes());
+    tag.push(0);
+    tag
+}
+
+fn mluc_tag(entries: &[(&[u8; 2], &str)]) -> Vec<u8> {
+    let mut tag = b"mluc\0\0\0\0".to_vec();
+    tag.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+    tag.extend_from_slice(&12u32.to_be_bytes());
+    let mut strings = Vec::new();
+    let base = 16 + entries.len() * 12;
+    for (lang, text) in entries {
+        let utf16: Vec<u8> = text.encode_utf16().flat_map(|u| u.to_be_bytes()).collect();
+        tag.extend_from_slice(*lang);
+        tag.extend_from_slice(b"US");
+        tag.extend_from_slice(&(utf16.len() as u32).to_be_bytes());
+        tag.extend_from_slice(&((base + strings.len()) as u32).to_be_bytes());
+        strings.extend_from_slice(&utf16);
+    }
+    tag.extend_from_slice(&strings);
+    tag
+}
+
+fn profile(version: [u8; 2], tags: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let mut header = vec![0u8; 128];
+    header[4..8].copy_from_slice(b"appl");
+    header[8..10].copy_from_slice(&version);
+    header[12..16].copy_from_slice(b"mntr");
+    header[16..20].copy_from_slice(b"RGB ");
+    header[20..24].copy_from_slice(b"XYZ ");
+    for (i, v) in [2024u16, 3, 15, 9, 30, 5].iter().enumerate() {
+        header[24 + i * 2..26 + i * 2].copy_from_slice(&v.to_be_bytes());
+    }
+    header[36..40].copy_from_slice(b"acsp");
+    header[40..44].copy_from_slice(b"APPL");
+    header[64..68].copy_from_slice(&1u32.to_be_bytes());
+
+    let mut table = (tags.len() as u32).to_be_bytes().to_vec();
+    let mut data = Vec::new();
+    let data_start = 128 + 4 + tags.len() * 12;
+    for (sig, tag) in tags {
+        table.extend_from_slice(*sig);
+        table.extend_from_slice(&((data_start + data.len()) as u32).to_be_bytes());
+        table.extend_from_slice(&(tag.len() as u32).to_be_bytes());
+        data.extend_from_slice(tag);
+        while data.len() % 4 != 0 {
+            data.push(0);
+        }
+    }
+
+    let mut out = header;
+    out.extend(table);
+    out.extend(data);
+    let size = out.len() as u32;
+    out[0..4].copy_from_slice(&size.to_be_bytes());
+    out
+}
+
+fn calibrated_profile() -> Vec<u8> {
+    let mut cprt = b"text\0\0\0\0".to_vec();
+    cprt.extend_from_slice(b"Copyright Example Inc.\0");
+    profile(
+        [2, 0x10],
+        &[
+            (b"desc", desc_tag("DELL U2720Q calibrated 2024-03-15")),
+            (b"cprt", cprt),
+        ],
+    )
+}
+
+fn jpeg_with_icc(profile: &[u8], chunk_size: usize) -> Vec<u8> {
+    let chunks: Vec<&[u8]> = profile.chunks(chunk_size).collect();
+    let mut data = vec![0xFF, 0xD8];
+    // Write the chunks in reverse to check they are reordered by sequence number
+    for (i, chunk) in chunks.iter().enumerate().rev() {
+        let mut payload = b"ICC_PROFILE\0".to_vec();
+        payload.push(i as u8 + 1);
+        payload.push(chunks.len() as u8);
+        payload.extend_from_slice(chunk);
+        data.extend_from_slice(&[0xFF, 0xE2]);
+        data.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        data.extend_from_slice(&payload);
+    }
+    data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x04, 0x01, 0x02, 0xFF, 0xD9]);
+    data
+}
+
+fn png_with_icc(profile: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(profile).unwrap();
+    let mut payload = b"icc\0\0".to_vec();
+    payload.extend(encoder.finish().unwrap());
+
+    let mut data = container::PNG_SIGNATURE.to_vec();
+    for (id, body) in [
+        (b"IHDR", vec![0; 13]),
+        (b"iCCP", payload),
+        (b"IEND", vec![]),
+    ] {
+        data.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        data.extend_from_slice(id);
+        data.extend_from_slice(&body);
+        data.extend_from_slice(&[0; 4]);
+    }
+    data
+}
+
+fn webp_with_icc(profile: &[u8]) -> Vec<u8> {
+    let mut body = b"WEBP".to_vec();
+    body.extend_from_slice(b"ICCP");
+    body.extend_from_slice(&(profile.len() as u32).to_le_bytes());
+    body.extend_from_slice(profile);
+    let mut data = b"RIFF".to_vec();
+    data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    data.extend(body);
+    data
+}
+
+fn tiff_with_icc(profile: &[u8]) -> Vec<u8> {
+    let mut data = b"II*\0".to_vec();
+    data.extend_from_slice(&8u32.to_le_bytes());
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&34675u16.to_le_bytes());
+    data.extend_from_slice(&7u16.to_le_bytes());
+    data.extend_from_slice(&(profile.len() as u32).to_le_bytes());
+    data.extend_from_slice(&26u32.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(profile);
+    data
+}
+
+#[test]
+fn test_parse_v2_profile_header_and_tags() {
+    let parsed = parse_icc_profile(&calibrated_profile()).unwrap();
+    assert_eq!(parsed.version, "2.1.0");
+    assert_eq!(parsed.cmm_type, "appl");
+    assert_eq!(parsed.device_class, "Display Device");
+    assert_eq!(parsed.color_space, "RGB");
+    assert_eq!(parsed.connection_space, "XYZ");
+    assert_eq!(parsed.platform, "Apple");
+    assert_eq!(parsed.rendering_intent, "Media-Relative Colorimetric");
+    assert_eq!(parsed.created.as_deref(), Some("2024:03:15 09:30:05"));
+    assert_eq!(
+        parsed.description.as_deref(),
+        Some("DELL U2720Q calibrated 2024-03-15")
+    );
+    assert_eq!(parsed.copyright.as_deref(), Some("Copyright Example Inc."));
+    assert_eq!(parsed.profile_id, None);
+}
+
+#[test]
+fn test_parse_v4_multilocalized_description() {
+    let data = profile(
+        [4, 0x30],
+        &[(
+            b"desc",
+            mluc_tag(&[(b"de", "Farbprofil"), (b"en", "Display P3")]),
+        )],
+    );
+    let parsed = parse_icc_profile(&data).unwrap();
+    assert_eq!(parsed.version, "4.3.0");
+    assert_eq!(parsed.description.as_deref(), Some("Display P3"));
+}
+
+#[test]
+fn test_invalid_profiles_are_rejected() {
+    assert!(parse_icc_profile(&[0; 64]).is_err());
+    assert!(parse_icc_profile(&[0; 200]).is_err());
+}
+
+#[test]
+fn test_text_lengths_past_the_tag_are_cut_short() {
+    let mut desc = desc_tag("Studio monitor");
+    desc[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+    let mut mluc = mluc_tag(&[(b"en", "Display P3")]);
+    mluc[12..16].copy_from_slice(&u32::MAX.to_be_bytes());
+    let data = profile([2, 0x10], &[(b"desc", desc), (b"cprt", mluc)]);
+    let parsed = parse_icc_profile(&data).unwrap();
+    assert_eq!(parsed.description.as_deref(), Some("Studio monitor"));
+    assert_eq!(parsed.copyright.as_deref(), Some("Display P3"));
+}
+
+#[test]
+fn test_find_profile_in_each_container() {
+    let icc = calibrated_profile();
+    let containers = [
+        ("jpeg", jpeg_with_icc(&icc, 100)),
+        ("png", png_with_icc(&icc)),
+        ("webp", webp_with_icc(&icc)),
+        ("tiff", tiff_with_icc(&icc)),
+    ];
+    for (name, data) in containers {
+        assert_eq!(find_icc_profile(&data).as_deref(), Some(&icc[..]), "{name}");
+    }
+}
+
+#[test]
+fn test_extract_icc_metadata_keys() {
+    let map = extract_icc_data(&jpeg_with_icc(&calibrated_profile(), 60));
+    assert_eq!(
+        map["ICC:ProfileDescription"],
+        "DELL U2720Q calibrated 2024-03-15"
+    );
+    assert_eq!(map["ICC:ProfileClass"], "Display Device");
+    assert_eq!(map["ICC:ProfileDateTime"], "2024:03:15 09:30:05");
+    assert_eq!(
+        map["ICC:ProfileDescription"].source,
+        Some(MetadataSource::Icc {
+            tag: "desc".to_string()
+        })
+    );
+    assert!(!map.contains_key("ICC:ProfileID"));
+}
+
+#[test]
+fn test_standard_profile_detection() {
+    assert!(is_standard_profile("sRGB IEC61966-2.1"));
+    assert!(is_standard_profile("Adobe RGB (1998)"));
+    assert!(is_standard_profile("Display P3"));
+    assert!(!is_standard_profile("Color LCD"));
+    assert!(!is_standard_profile("DELL U2720Q calibrated 2024-03-15"));
+}
+
+#[test]
+fn test_custom_profile_raises_privacy_score() {
+    let mut exif_data = HashMap::new();
+    exif_data.insert("ICC:ProfileDescription".to_string(), "Color LCD".into());
+    let mut data = ImageData {
+        name: "shot.png".to_string(),
+        size: 1024,
+        mime_type: "image/png".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+    let risk = data.calculate_privacy_risk();
+    assert_eq!(risk.score, 10);
+    assert!(risk.sensitive_fields.contains(&"Color Profile".to_string()));
+
+    data.exif_data.insert(
+        "ICC:ProfileDescription".to_string(),
+        "sRGB IEC61966-2.1".into(),
+    );
+    assert_eq!(data.calculate_privacy_risk().score, 0);
+}
+
+#[test]
+fn test_custom_profile_date_counts_as_a_timestamp() {
+    let mut data = ImageData {
+        exif_data: extract_icc_data(&jpeg_with_icc(&calibrated_profile(), 60)),
+        ..Default::default()
+    };
+    // The creation date of a calibrated profile dates the calibration
+    let risk = data.calculate_privacy_risk();
+    assert_eq!(risk.score, 25);
+    assert!(risk.sensitive_fields.contains(&"Timestamps".to_string()));
+
+    // Standard profiles carry the date the standard was published
+    data.exif_data.insert(
+        "ICC:ProfileDescription".to_string(),
+        "sRGB IEC61966-2.1".into(),
+    );
+    assert_eq!(data.calculate_privacy_risk().score, 0);
+}
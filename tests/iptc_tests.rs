@@ -0,0 +1,206 @@
+use image_metadata_extractor::exif_core::extract_metadata;
+use image_metadata_extractor::iptc::{
+    dataset_name, extract_iptc_data, parse_iim, parse_image_resources,
+};
+use image_metadata_extractor::types::{ImageData, MetadataSource, RawValue};
+use std::collections::HashMap;
+
+fn dataset(record: u8, dataset: u8, data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x1C, record, dataset];
+    out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    out.extend_from_slice(data);
+    out
+}
+
+fn resource(id: u16, data: &[u8]) -> Vec<u8> {
+    let mut out = b"8BIM".to_vec();
+    out.extend_from_slice(&id.to_be_bytes());
+    out.extend_from_slice(&[0, 0]); // empty name, padded
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        out.push(0);
+    }
+    out
+}
+
+fn jpeg_with_app13(resources: &[u8]) -> Vec<u8> {
+    let mut payload = b"Photoshop 3.0\0".to_vec();
+    payload.extend_from_slice(resources);
+    let mut data = vec![0xFF, 0xD8, 0xFF, 0xED];
+    data.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+    data.extend_from_slice(&payload);
+    data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x04, 0x01, 0x02, 0xFF, 0xD9]);
+    data
+}
+
+fn sample_iim(utf8: bool) -> Vec<u8> {
+    let mut iim = Vec::new();
+    if utf8 {
+        iim.extend(dataset(1, 90, b"\x1b%G"));
+    }
+    iim.extend(dataset(2, 0, &[0, 4]));
+    iim.extend(dataset(2, 80, b"Jane Doe"));
+    iim.extend(dataset(2, 25, b"protest"));
+    iim.extend(dataset(2, 25, b"city hall"));
+    if utf8 {
+        iim.extend(dataset(2, 90, "Zürich".as_bytes()));
+    } else {
+        iim.extend(dataset(2, 90, b"Z\xfcrich"));
+    }
+    iim.extend(dataset(2, 200, b"custom"));
+    iim
+}
+
+#[test]
+fn test_parse_iim_datasets_in_order() {
+    let datasets = parse_iim(&sample_iim(false));
+    let ids: Vec<(u8, u8)> = datasets.iter().map(|d| (d.record, d.dataset)).collect();
+    assert_eq!(
+        ids,
+        vec![(2, 0), (2, 80), (2, 25), (2, 25), (2, 90), (2, 200)]
+    );
+    assert_eq!(dataset_name(2, 120), Some("Caption-Abstract"));
+    assert_eq!(dataset_name(2, 200), None);
+}
+
+#[test]
+fn test_extended_length_dataset() {
+    let mut iim = vec![0x1C, 2, 120, 0x80, 0x04];
+    iim.extend_from_slice(&5u32.to_be_bytes());
+    iim.extend_from_slice(b"hello");
+    iim.extend(dataset(2, 5, b"next"));
+
+    let datasets = parse_iim(&iim);
+    assert_eq!(datasets.len(), 2);
+    assert_eq!(datasets[0].data, b"hello");
+    assert_eq!(datasets[1].data, b"next");
+}
+
+#[test]
+fn test_truncated_iim_stops_cleanly() {
+    let mut iim = dataset(2, 80, b"Jane Doe");
+    iim.extend_from_slice(&[0x1C, 2, 25, 0x00, 0xFF, b'x']);
+    assert_eq!(parse_iim(&iim).len(), 1);
+}
+
+#[test]
+fn test_image_resources_with_odd_sizes_and_names() {
+    let mut blocks = resource(0x0425, &[1, 2, 3]);
+    // Resource with a three character name (length byte + 3 = even, no pad)
+    blocks.extend_from_slice(b"8BIM");
+    blocks.extend_from_slice(&0x0404u16.to_be_bytes());
+    blocks.extend_from_slice(&[3, b'a', b'b', b'c']);
+    blocks.extend_from_slice(&2u32.to_be_bytes());
+    blocks.extend_from_slice(&[9, 9]);
+
+    let resources = parse_image_resources(&blocks);
+    assert_eq!(resources.len(), 2);
+    assert_eq!(resources[0].data, &[1, 2, 3]);
+    assert_eq!(resources[1].id, 0x0404);
+    assert_eq!(resources[1].name, "abc");
+    assert_eq!(resources[1].data, &[9, 9]);
+}
+
+#[test]
+fn test_extract_from_jpeg_latin1() {
+    let mut resources = resource(0x0425, &[0; 16]);
+    resources.extend(resource(0x0404, &sample_iim(false)));
+    let map = extract_iptc_data(&jpeg_with_app13(&resources));
+
+    assert_eq!(map["IPTC:By-line"], "Jane Doe");
+    assert_eq!(map["IPTC:Keywords"], "protest; city hall");
+    assert_eq!(map["IPTC:City"], "Zürich");
+    assert_eq!(map["IPTC:2:200"], "custom");
+    assert_eq!(
+        map["IPTC:ApplicationRecordVersion"].raw,
+        RawValue::Integer(vec![4])
+    );
+    assert_eq!(
+        map["IPTC:By-line"].source,
+        Some(MetadataSource::Iptc {
+            record: 2,
+            dataset: 80
+        })
+    );
+}
+
+#[test]
+fn test_extract_from_jpeg_utf8() {
+    let map = extract_iptc_data(&jpeg_with_app13(&resource(0x0404, &sample_iim(true))));
+    assert_eq!(map["IPTC:City"], "Zürich");
+    assert!(!map.contains_key("IPTC:CodedCharacterSet"));
+}
+
+#[test]
+fn test_extract_from_tiff_tag() {
+    let iim = sample_iim(false);
+    let mut data = b"II*\0".to_vec();
+    data.extend_from_slice(&8u32.to_le_bytes());
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&33723u16.to_le_bytes());
+    data.extend_from_slice(&7u16.to_le_bytes()); // UNDEFINED
+    data.extend_from_slice(&(iim.len() as u32).to_le_bytes());
+    data.extend_from_slice(&26u32.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&iim);
+
+    let map = extract_iptc_data(&data);
+    assert_eq!(map["IPTC:By-line"], "Jane Doe");
+}
+
+#[test]
+fn test_no_iptc_in_other_files() {
+    assert!(extract_iptc_data(b"").is_empty());
+    assert!(extract_iptc_data(&jpeg_with_app13(&resource(0x0425, &[0; 16]))).is_empty());
+}
+
+#[test]
+fn test_extract_metadata_includes_iptc_and_scores_privacy() {
+    let (exif_data, _) = extract_metadata(&jpeg_with_app13(&resource(0x0404, &sample_iim(false))));
+    assert_eq!(exif_data["IPTC:By-line"], "Jane Doe");
+
+    let data = ImageData {
+        name: "news.jpg".to_string(),
+        size: 1024,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+    let risk = data.calculate_privacy_risk();
+    // By-line 25 + City 15
+    assert_eq!(risk.score, 40);
+    assert!(
+        risk.sensitive_fields
+            .contains(&"Location Names".to_string())
+    );
+}
+
+#[test]
+fn test_contact_details_score() {
+    let mut exif_data = HashMap::new();
+    exif_data.insert("IPTC:Contact".to_string(), "+1 555 0100".into());
+    let data = ImageData {
+        name: "news.jpg".to_string(),
+        size: 1024,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+    let risk = data.calculate_privacy_risk();
+    assert_eq!(risk.score, 20);
+    assert!(
+        risk.sensitive_fields
+            .contains(&"Contact Information".to_string())
+    );
+}
@@ -0,0 +1,187 @@
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, GrayImage, Luma, Rgb, RgbImage};
+use image_metadata_extractor::export::generate_json;
+use image_metadata_extractor::jpeg::{
+    JpegComponent, QuantizationTable, analyze_jpeg, estimate_quality, marker_name, subsampling,
+};
+use image_metadata_extractor::types::ImageData;
+use std::collections::HashMap;
+
+fn encode(image: &DynamicImage, quality: u8) -> Vec<u8> {
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, quality)
+        .encode_image(image)
+        .unwrap();
+    out
+}
+
+fn rgb(width: u32, height: u32) -> DynamicImage {
+    DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+        Rgb([(x * 8) as u8, (y * 8) as u8, ((x + y) * 4) as u8])
+    }))
+}
+
+fn component(id: u8, horizontal: u8, vertical: u8) -> JpegComponent {
+    JpegComponent {
+        id,
+        horizontal_sampling: horizontal,
+        vertical_sampling: vertical,
+        quantization_table: 0,
+    }
+}
+
+/// Segment bytes: marker, big-endian length and payload.
+fn segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+    let mut out = vec![0xFF, marker];
+    out.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+#[test]
+fn test_encoded_jpeg_structure() {
+    let file = encode(&rgb(32, 24), 75);
+    let jpeg = analyze_jpeg(&file).expect("JPEG");
+
+    let frame = jpeg.frame.as_ref().unwrap();
+    assert_eq!(frame.process, "Baseline DCT");
+    assert!(!frame.progressive);
+    assert_eq!(frame.precision, 8);
+    assert_eq!((frame.width, frame.height), (32, 24));
+    assert_eq!(frame.components.len(), 3);
+
+    assert_eq!(jpeg.quality, Some(75));
+    assert!(jpeg.standard_tables);
+    assert_eq!(jpeg.scans, 1);
+    assert_eq!(jpeg.trailing_bytes, 0);
+    assert!(!jpeg.truncated);
+
+    let names: Vec<&str> = jpeg.segments.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names.first(), Some(&"SOI"));
+    assert_eq!(names.last(), Some(&"EOI"));
+    assert!(names.contains(&"DQT") && names.contains(&"SOF0") && names.contains(&"SOS"));
+
+    // Segments tile the whole file
+    let total: usize = jpeg.segments.iter().map(|s| s.size).sum();
+    assert_eq!(total, file.len());
+    for pair in jpeg.segments.windows(2) {
+        assert_eq!(pair[0].offset + pair[0].size, pair[1].offset);
+    }
+}
+
+#[test]
+fn test_quality_estimate_across_range() {
+    let image = DynamicImage::ImageLuma8(GrayImage::from_fn(16, 16, |x, y| {
+        Luma([(x * 16 + y) as u8])
+    }));
+    for quality in [10, 50, 90, 100] {
+        let jpeg = analyze_jpeg(&encode(&image, quality)).unwrap();
+        assert_eq!(jpeg.quality, Some(quality), "quality {}", quality);
+        assert!(jpeg.standard_tables);
+    }
+
+    // A table that is not a scaled IJG table is estimated, not exact
+    let custom = QuantizationTable {
+        id: 0,
+        precision: 8,
+        values: vec![3; 64],
+    };
+    let (quality, exact) = estimate_quality(&[custom]).unwrap();
+    assert!(quality > 90);
+    assert!(!exact);
+    assert_eq!(estimate_quality(&[]), None);
+}
+
+#[test]
+fn test_subsampling_names() {
+    let y = |h, v| component(1, h, v);
+    let c = |id| component(id, 1, 1);
+    assert_eq!(subsampling(&[y(1, 1), c(2), c(3)]), "4:4:4");
+    assert_eq!(subsampling(&[y(2, 1), c(2), c(3)]), "4:2:2");
+    assert_eq!(subsampling(&[y(2, 2), c(2), c(3)]), "4:2:0");
+    assert_eq!(subsampling(&[y(1, 2), c(2), c(3)]), "4:4:0");
+    assert_eq!(subsampling(&[y(4, 1), c(2), c(3)]), "4:1:1");
+    assert_eq!(subsampling(&[y(1, 1)]), "Grayscale");
+    assert_eq!(
+        subsampling(&[y(2, 2), component(2, 1, 1), component(3, 2, 1)]),
+        "2x2 1x1 2x1"
+    );
+}
+
+#[test]
+fn test_hand_built_progressive_file() {
+    let mut file = vec![0xFF, 0xD8];
+    file.extend(segment(0xE0, b"JFIF\0\x01\x02\0\0\x01\0\x01\0\0"));
+    file.extend(segment(0xE1, b"http://ns.adobe.com/xap/1.0/\0<x/>"));
+    file.extend(segment(0xDD, &[0x00, 0x04]));
+    file.extend(segment(
+        0xC2,
+        &[8, 0, 16, 0, 16, 3, 1, 0x22, 0, 2, 0x11, 1, 3, 0x11, 1],
+    ));
+    // Two scans with stuffed bytes and a restart marker in the entropy data
+    file.extend(segment(0xDA, &[1, 1, 0, 0, 0, 0]));
+    file.extend_from_slice(&[0x12, 0xFF, 0x00, 0x34, 0xFF, 0xD0, 0x56]);
+    file.extend(segment(0xDA, &[1, 1, 0, 1, 63, 0]));
+    file.extend_from_slice(&[0x78]);
+    file.extend_from_slice(&[0xFF, 0xD9]);
+    file.extend_from_slice(b"appended");
+
+    let jpeg = analyze_jpeg(&file).unwrap();
+    let frame = jpeg.frame.as_ref().unwrap();
+    assert_eq!(frame.process, "Progressive DCT");
+    assert!(frame.progressive);
+    assert_eq!(frame.subsampling, "4:2:0");
+    assert_eq!(jpeg.restart_interval, Some(4));
+    assert_eq!(jpeg.scans, 2);
+    assert_eq!(jpeg.trailing_bytes, 8);
+    assert_eq!(jpeg.quality, None);
+
+    let identifiers: Vec<Option<&str>> = jpeg
+        .segments
+        .iter()
+        .map(|s| s.identifier.as_deref())
+        .collect();
+    assert_eq!(identifiers[1], Some("JFIF"));
+    assert_eq!(identifiers[2], Some("XMP"));
+
+    let first_scan = jpeg.segments.iter().find(|s| s.name == "SOS").unwrap();
+    assert_eq!(first_scan.size, 2 + 8 + 7);
+
+    // Cut off before EOI
+    let truncated = analyze_jpeg(&file[..file.len() - 12]).unwrap();
+    assert!(truncated.truncated);
+    assert_eq!(truncated.trailing_bytes, 0);
+
+    assert!(analyze_jpeg(b"\x89PNG\r\n\x1a\n").is_none());
+}
+
+#[test]
+fn test_marker_names() {
+    assert_eq!(marker_name(0xC0), "SOF0");
+    assert_eq!(marker_name(0xC4), "DHT");
+    assert_eq!(marker_name(0xD3), "RST3");
+    assert_eq!(marker_name(0xE1), "APP1");
+    assert_eq!(marker_name(0xFE), "COM");
+}
+
+#[test]
+fn test_json_export_includes_structure() {
+    let file = encode(&rgb(16, 16), 90);
+    let data = ImageData {
+        name: "photo.jpg".to_string(),
+        size: file.len() as u64,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: Some(16),
+        height: Some(16),
+        exif_data: HashMap::new(),
+        gps: None,
+        sha256_hash: None,
+        jpeg: analyze_jpeg(&file),
+        ..Default::default()
+    };
+    let json: serde_json::Value = serde_json::from_str(&generate_json(&data)).unwrap();
+    assert_eq!(json["jpeg"]["quality"], 90);
+    assert_eq!(json["jpeg"]["frame"]["process"], "Baseline DCT");
+    assert_eq!(json["jpeg"]["segments"][0]["name"], "SOI");
+}
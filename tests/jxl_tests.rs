@@ -0,0 +1,189 @@
+mod common;
+
+use common::{bx, exif_tiff};
+use image_metadata_extractor::container;
+use image_metadata_extractor::exif_core::{extract_exif_data, extract_metadata};
+use image_metadata_extractor::jxl::{
+    dimensions, extract_jxl_data, find_codestream, find_exif, find_jumbf, find_xmp, jxl_boxes,
+    parse_codestream_header,
+};
+use image_metadata_extractor::types::MetadataSource;
+use image_metadata_extractor::xmp::extract_xmp_data;
+use std::io::Read;
+
+const XMP: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:CreatorTool="libjxl"/></rdf:RDF></x:xmpmeta>"#;
+
+/// Least-significant-bit first writer matching the codestream bit order.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    fn put(&mut self, value: u32, bits: u32) -> &mut Self {
+        for i in 0..bits {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            let bit = ((value >> i) & 1) as u8;
+            *self.bytes.last_mut().unwrap() |= bit << (self.len % 8);
+            self.len += 1;
+        }
+        self
+    }
+
+    fn codestream(&self) -> Vec<u8> {
+        let mut data = container::JXL_CODESTREAM_SIGNATURE.to_vec();
+        data.extend_from_slice(&self.bytes);
+        // Room for the frame data that would follow
+        data.extend_from_slice(&[0; 8]);
+        data
+    }
+}
+
+/// 64x48 small-size header with default image metadata.
+fn small_codestream() -> Vec<u8> {
+    BitWriter::default()
+        .put(1, 1) // small
+        .put(5, 5) // ysize_div8 - 1
+        .put(0, 3) // no fixed ratio
+        .put(7, 5) // xsize_div8 - 1
+        .put(1, 1) // all_default
+        .codestream()
+}
+
+/// 1777x1000 (16:9) animated 16-bit image with alpha, rotated 90° clockwise.
+fn full_codestream() -> Vec<u8> {
+    BitWriter::default()
+        .put(0, 1) // not small
+        .put(1, 2)
+        .put(999, 13) // ysize - 1
+        .put(5, 3) // 16:9 ratio
+        .put(0, 1) // all_default
+        .put(1, 1) // extra_fields
+        .put(5, 3) // orientation - 1
+        .put(0, 1) // no intrinsic size
+        .put(0, 1) // no preview
+        .put(1, 1) // animation
+        .put(0, 2) // 100 ticks per second
+        .put(0, 2) // denominator 1
+        .put(0, 2) // infinite loops
+        .put(0, 1) // no timecodes
+        .put(0, 1) // integer samples
+        .put(3, 2)
+        .put(15, 6) // 16 bits
+        .put(0, 1) // modular_16_bit_buffer_sufficient
+        .put(1, 2) // one extra channel
+        .codestream()
+}
+
+fn brotli(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    brotli::CompressorReader::new(data, 4096, 5, 22)
+        .read_to_end(&mut out)
+        .unwrap();
+    out
+}
+
+/// A container with Brotli-compressed Exif, plain XMP, JPEG reconstruction
+/// data, a JUMBF box and the full codestream.
+fn jxl_container() -> Vec<u8> {
+    let mut exif = 0u32.to_be_bytes().to_vec();
+    exif.extend(exif_tiff("Canon", "Canon EOS R5"));
+    let mut brob = b"Exif".to_vec();
+    brob.extend(brotli(&exif));
+
+    let mut file = container::JXL_CONTAINER_SIGNATURE.to_vec();
+    file.extend(bx(b"ftyp", b"jxl \0\0\0\0jxl "));
+    file.extend(bx(b"jbrd", &[0; 16]));
+    file.extend(bx(b"brob", &brob));
+    file.extend(bx(b"xml ", XMP.as_bytes()));
+    file.extend(bx(b"jumb", &bx(b"jumd", b"c2pa\0")));
+    file.extend(bx(b"jxlc", &full_codestream()));
+    file
+}
+
+#[test]
+fn test_parse_small_codestream_header() {
+    let header = parse_codestream_header(&small_codestream()).unwrap();
+    assert_eq!((header.width, header.height), (64, 48));
+    assert_eq!(header.bits_per_sample, 8);
+    assert_eq!(header.orientation, 1);
+    assert!(!header.animated);
+    assert_eq!(dimensions(&small_codestream()), Some((64, 48)));
+}
+
+#[test]
+fn test_parse_full_codestream_header() {
+    let header = parse_codestream_header(&full_codestream()).unwrap();
+    assert_eq!((header.width, header.height), (1777, 1000));
+    assert_eq!(header.bits_per_sample, 16);
+    assert!(!header.float_samples);
+    assert_eq!(header.orientation, 6);
+    assert_eq!(header.extra_channels, 1);
+    assert!(header.animated);
+    assert!(!header.has_preview);
+}
+
+#[test]
+fn test_codestream_header_errors() {
+    assert!(parse_codestream_header(b"\xFF\xD8\xFF").is_err());
+    assert!(parse_codestream_header(container::JXL_CODESTREAM_SIGNATURE).is_err());
+}
+
+#[test]
+fn test_container_boxes() {
+    let file = jxl_container();
+    assert!(container::is_jxl(&file));
+    assert!(container::is_jxl(&small_codestream()));
+    assert!(!container::is_jxl(b"\xFF\xD8\xFF\xE0"));
+
+    let boxes = jxl_boxes(&file);
+    let exif = boxes.iter().find(|b| &b.box_type == b"Exif").unwrap();
+    assert!(exif.compressed);
+    assert_eq!(find_exif(&file), Some(exif_tiff("Canon", "Canon EOS R5")));
+    assert_eq!(find_xmp(&file), vec![XMP.to_string()]);
+    assert_eq!(find_jumbf(&file).len(), 1);
+    assert_eq!(find_codestream(&file), Some(&full_codestream()[..]));
+    assert_eq!(dimensions(&file), Some((1777, 1000)));
+}
+
+#[test]
+fn test_extract_jxl_data() {
+    let map = extract_jxl_data(&jxl_container());
+    assert_eq!(map["JXL:ImageWidth"].as_i64(), Some(1777));
+    assert_eq!(map["JXL:BitsPerSample"].display, "16");
+    assert_eq!(map["JXL:Orientation"].as_i64(), Some(6));
+    assert_eq!(map["JXL:Animated"].as_text(), Some("Yes"));
+    assert_eq!(map["JXL:JPEGReconstruction"].as_text(), Some("Yes"));
+    assert_eq!(map["JXL:CompressedMetadata"].as_text(), Some("Exif"));
+    assert_eq!(map["JXL:JUMBFBoxes"].as_i64(), Some(1));
+    assert_eq!(
+        map["JXL:JPEGReconstruction"].source,
+        Some(MetadataSource::Bmff {
+            box_type: "jbrd".to_string()
+        })
+    );
+
+    let bare = extract_jxl_data(&small_codestream());
+    assert_eq!(bare["JXL:ImageHeight"].as_i64(), Some(48));
+    assert!(!bare.contains_key("JXL:Container"));
+    assert!(extract_jxl_data(b"not a jxl").is_empty());
+}
+
+#[test]
+fn test_exif_and_xmp_read_from_container() {
+    let file = jxl_container();
+    let (exif, _) = extract_exif_data(&file);
+    assert_eq!(exif["Model"].as_text(), Some("Canon EOS R5"));
+    assert_eq!(
+        extract_xmp_data(&file)["XMP-xmp:CreatorTool"].as_text(),
+        Some("libjxl")
+    );
+
+    let (all, _) = extract_metadata(&file);
+    assert!(all.contains_key("Make"));
+    assert!(all.contains_key("XMP-xmp:CreatorTool"));
+    assert!(all.contains_key("JXL:ImageWidth"));
+}
@@ -0,0 +1,288 @@
+mod common;
+
+use common::{png, zlib};
+use image_metadata_extractor::archive::read_zip_entries;
+use image_metadata_extractor::binary_cleaner::BinaryCleaner;
+use image_metadata_extractor::container::{png_chunks, riff_chunks};
+use image_metadata_extractor::diagnostics::diagnose;
+use image_metadata_extractor::jpeg::analyze_jpeg;
+use image_metadata_extractor::limits::{
+    Budget, LimitExceeded, MAX_BLOCKS, block_end, read_to_end_limited,
+};
+use std::io::{Cursor, Write};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const IHDR: &[u8] = &[0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 0];
+
+fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, contents) in files {
+        writer.start_file(*name, options).unwrap();
+        writer.write_all(contents).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn block_end_rejects_overflow_and_overrun() {
+    assert_eq!(block_end(8, 8, 4, 4, 24), Some(24));
+    assert_eq!(block_end(8, 8, 5, 4, 24), None);
+    assert_eq!(block_end(usize::MAX - 4, 8, 0, 0, usize::MAX), None);
+    assert_eq!(block_end(0, 8, u64::MAX, 0, usize::MAX), None);
+}
+
+#[test]
+fn budget_runs_out() {
+    let mut budget = Budget::new("test steps", 2);
+    assert!(budget.spend().is_ok());
+    assert!(budget.spend().is_ok());
+    let error = budget.spend().unwrap_err();
+    assert_eq!(error, LimitExceeded::new("test steps", 2));
+    assert_eq!(error.to_string(), "Limit exceeded: test steps is over 2");
+}
+
+#[test]
+fn size_budget_refuses_a_block_that_does_not_fit() {
+    let mut budget = Budget::new("test bytes", 100);
+    assert!(budget.spend_many(60).is_ok());
+    assert!(budget.spend_many(50).is_err());
+    // Nothing was spent by the failed call
+    assert!(budget.spend_many(40).is_ok());
+    assert!(budget.spend().is_err());
+    assert!(budget.spend_many(usize::MAX).is_err());
+}
+
+#[test]
+fn decompression_stops_at_the_limit() {
+    let compressed = zlib(&[0; 1000]);
+    let mut out = b"kept".to_vec();
+    let error = read_to_end_limited(
+        flate2::read::ZlibDecoder::new(&compressed[..]),
+        &mut out,
+        100,
+    )
+    .unwrap_err();
+    assert!(error.to_string().starts_with("Limit exceeded"));
+    assert_eq!(out, b"kept");
+
+    let mut out = Vec::new();
+    let read = read_to_end_limited(
+        flate2::read::ZlibDecoder::new(&compressed[..]),
+        &mut out,
+        1000,
+    )
+    .unwrap();
+    assert_eq!(read, 1000);
+}
+
+#[test]
+fn huge_png_chunk_length_is_a_limit_error() {
+    let mut data = png(&[(b"IHDR", IHDR)]);
+    data.extend_from_slice(&u32::MAX.to_be_bytes());
+    data.extend_from_slice(b"tEXt");
+    data.extend_from_slice(&[0; 16]);
+
+    let error = BinaryCleaner::clean_metadata(&data, "png").unwrap_err();
+    assert!(
+        error.starts_with("Limit exceeded: PNG chunk size"),
+        "{}",
+        error
+    );
+    // The read-only walker stops before the bad chunk
+    assert_eq!(png_chunks(&data).len(), 1);
+}
+
+#[test]
+fn huge_webp_chunk_size_is_a_limit_error() {
+    let mut webp = b"RIFF\0\0\0\0WEBPEXIF".to_vec();
+    webp.extend_from_slice(&u32::MAX.to_le_bytes());
+    webp.extend_from_slice(&[0; 16]);
+
+    let error = BinaryCleaner::clean_metadata(&webp, "webp").unwrap_err();
+    assert!(
+        error.starts_with("Limit exceeded: WebP chunk size"),
+        "{}",
+        error
+    );
+    assert!(riff_chunks(&webp).is_empty());
+}
+
+#[test]
+fn endless_empty_chunks_hit_the_block_limit() {
+    let mut data = png(&[(b"IHDR", IHDR)]);
+    for _ in 0..MAX_BLOCKS {
+        data.extend_from_slice(b"\0\0\0\0abCd\0\0\0\0");
+    }
+
+    let error = BinaryCleaner::clean_metadata(&data, "png").unwrap_err();
+    assert!(
+        error.starts_with("Limit exceeded: PNG chunk count"),
+        "{}",
+        error
+    );
+    assert_eq!(png_chunks(&data).len(), MAX_BLOCKS);
+    let diagnostics = diagnose(&data);
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d.parser == "PNG" && d.message.starts_with("Limit exceeded"))
+    );
+}
+
+#[test]
+fn restart_marker_runs_are_bounded() {
+    let mut jpeg = vec![0xFF, 0xD8];
+    for n in 0..MAX_BLOCKS + 10 {
+        jpeg.extend_from_slice(&[0xFF, 0xD0 + (n % 8) as u8]);
+    }
+    let structure = analyze_jpeg(&jpeg).unwrap();
+    assert_eq!(structure.segments.len(), MAX_BLOCKS);
+    assert!(structure.truncated);
+}
+
+#[test]
+fn jpeg_with_ff_runs_in_scan_data_is_cleaned() {
+    let mut jpeg = vec![0xFF, 0xD8];
+    jpeg.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x08]);
+    jpeg.extend_from_slice(b"Exif\0\0");
+    jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0xFF, 0xFF, 0x00, 0x34]);
+    // An APP segment between scans, as progressive files may have
+    jpeg.extend_from_slice(&[0xFF, 0xED, 0x00, 0x04, 0xAA, 0xBB]);
+    jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x56, 0xFF, 0xD9]);
+
+    let cleaned = BinaryCleaner::clean_metadata(&jpeg, "jpg").unwrap();
+    let mut expected = vec![0xFF, 0xD8];
+    expected.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0xFF, 0xFF, 0x00, 0x34]);
+    expected.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x56, 0xFF, 0xD9]);
+    assert_eq!(cleaned, expected);
+}
+
+#[test]
+fn zip_entries_are_read_and_filtered() {
+    let archive = zip(&[("a.png", b"png bytes"), ("notes.txt", b"skip me")]);
+    let entries = read_zip_entries(archive).unwrap();
+    assert_eq!(
+        entries.files,
+        vec![("a.png".to_string(), b"png bytes".to_vec())]
+    );
+    assert!(entries.skipped.is_empty());
+}
+
+#[test]
+fn zip_bomb_entry_is_skipped_with_a_limit_error() {
+    let archive = zip(&[("bomb.png", &vec![0; 4 << 20])]);
+    assert!(archive.len() < 64 << 10);
+    let entries = read_zip_entries(archive).unwrap();
+    assert!(entries.files.is_empty());
+    assert_eq!(entries.skipped.len(), 1);
+    assert!(
+        entries.skipped[0]
+            .starts_with("Skipped bomb.png: Limit exceeded: inflated size of bomb.png"),
+        "{}",
+        entries.skipped[0]
+    );
+}
+
+#[test]
+fn over_ratio_entry_does_not_lose_the_rest_of_the_archive() {
+    // A large uncompressed TIFF of one colour deflates far beyond the ratio
+    // limit
+    let flat_tiff = vec![0xFF; 2 << 20];
+    let archive = zip(&[("flat.tif", &flat_tiff), ("photo.png", b"png bytes")]);
+    let entries = read_zip_entries(archive).unwrap();
+    assert_eq!(
+        entries.files,
+        vec![("photo.png".to_string(), b"png bytes".to_vec())]
+    );
+    assert_eq!(entries.skipped.len(), 1);
+    assert!(
+        entries.skipped[0].contains("flat.tif"),
+        "{:?}",
+        entries.skipped
+    );
+}
+
+#[test]
+fn oversized_input_is_refused_by_the_cleaner() {
+    let data = vec![0; image_metadata_extractor::limits::MAX_FILE_SIZE + 1];
+    let error = BinaryCleaner::clean_metadata(&data, "png").unwrap_err();
+    assert!(error.starts_with("Limit exceeded: file size"), "{}", error);
+}
+
+/// Little-endian TIFF with one IFD of inline `(tag, type, count, value)`
+/// entries at offset 8, followed by `pixels`.
+fn tiff(entries: &[(u16, u16, u32, u32)], pixels: &[u8]) -> Vec<u8> {
+    let mut tiff = b"II*\0\x08\0\0\0".to_vec();
+    tiff.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    for (tag, field_type, count, value) in entries {
+        tiff.extend_from_slice(&tag.to_le_bytes());
+        tiff.extend_from_slice(&field_type.to_le_bytes());
+        tiff.extend_from_slice(&count.to_le_bytes());
+        tiff.extend_from_slice(&value.to_le_bytes());
+    }
+    tiff.extend_from_slice(&[0; 4]);
+    tiff.extend_from_slice(pixels);
+    tiff
+}
+
+/// Entries for a 2x1 8-bit grey image whose strip starts right after a
+/// directory of `extra + 8` entries.
+fn grey_entries(extra: u32) -> Vec<(u16, u16, u32, u32)> {
+    let strip = 8 + 2 + (extra + 8) * 12 + 4;
+    vec![
+        (256, 3, 1, 2),
+        (257, 3, 1, 1),
+        (258, 3, 1, 8),
+        (262, 3, 1, 1),
+        (273, 4, 1, strip),
+        (277, 3, 1, 1),
+        (278, 3, 1, 1),
+        (279, 4, 1, 2),
+    ]
+}
+
+#[test]
+fn tiff_with_empty_sample_format_is_cleaned_without_panicking() {
+    // An empty SampleFormat made the tiff crate's decoder index out of bounds
+    let mut entries = grey_entries(1);
+    entries.push((339, 3, 0, 0));
+    let data = tiff(&entries, &[0x10, 0xF0]);
+
+    let cleaned = BinaryCleaner::clean_metadata(&data, "tif").unwrap();
+    let mut decoder = tiff::decoder::Decoder::new(Cursor::new(&cleaned)).unwrap();
+    assert_eq!(decoder.dimensions().unwrap(), (2, 1));
+    match decoder.read_image().unwrap() {
+        tiff::decoder::DecodingResult::U8(pixels) => assert_eq!(pixels, [0x10, 0xF0]),
+        other => panic!("unexpected sample type: {:?}", other),
+    }
+}
+
+#[test]
+fn tiff_strip_past_the_end_is_an_error() {
+    let mut entries = grey_entries(0);
+    entries[7] = (279, 4, 1, 1 << 20);
+    let error = BinaryCleaner::clean_metadata(&tiff(&entries, &[0x10, 0xF0]), "tif").unwrap_err();
+    assert!(error.contains("the file ends first"), "{}", error);
+}
+
+#[test]
+fn tiff_strips_repeating_the_same_bytes_are_a_limit_error() {
+    // Thousands of strips all pointing at the whole file would otherwise
+    // copy it thousands of times
+    let strips = 4000;
+    let offsets_at = 8 + 2 + 8 * 12 + 4;
+    let counts_at = offsets_at + strips * 4;
+    let mut entries = grey_entries(0);
+    entries[4] = (273, 4, strips as u32, offsets_at as u32);
+    entries[7] = (279, 4, strips as u32, counts_at as u32);
+    let mut values: Vec<u8> = (0..strips).flat_map(|_| 0u32.to_le_bytes()).collect();
+    values.extend((0..strips).flat_map(|_| ((counts_at + strips * 4) as u32).to_le_bytes()));
+    let error = BinaryCleaner::clean_metadata(&tiff(&entries, &values), "tif").unwrap_err();
+    assert!(
+        error.starts_with("Limit exceeded: TIFF strip data"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,415 @@
+mod common;
+
+use common::{Entry, ascii, ifd, jpeg_with_exif};
+use image_metadata_extractor::exif_core::extract_exif_data;
+use image_metadata_extractor::makernotes::{
+    MakerNoteDecoder, canon, decode_maker_note, decode_maker_note_with, locate_maker_note, nikon,
+    sony,
+};
+use image_metadata_extractor::metadata_info::{get_metadata_category, get_metadata_explanation};
+use image_metadata_extractor::types::{ImageData, MetadataSource, RawValue};
+use std::collections::HashMap;
+
+fn long(tag: u16, value: u32) -> Entry {
+    (tag, 4, 1, value.to_le_bytes().to_vec())
+}
+
+fn undefined(tag: u16, bytes: &[u8]) -> Entry {
+    (tag, 7, bytes.len() as u32, bytes.to_vec())
+}
+
+fn rationals(tag: u16, values: &[(u32, u32)]) -> Entry {
+    let bytes = values
+        .iter()
+        .flat_map(|(n, d)| n.to_le_bytes().into_iter().chain(d.to_le_bytes()))
+        .collect();
+    (tag, 5, values.len() as u32, bytes)
+}
+
+fn shorts(tag: u16, values: &[u16]) -> Entry {
+    let bytes = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    (tag, 3, values.len() as u32, bytes)
+}
+
+fn canon_entries() -> Vec<Entry> {
+    let mut file_info = vec![0u16; 20];
+    file_info[1] = 0x0001;
+    file_info[2] = 0x86A0;
+    vec![
+        ascii(0x0006, "Canon EOS-1D X"),
+        ascii(0x0007, "Firmware Version 2.1.0"),
+        long(0x0008, 1001234),
+        ascii(0x0009, "Jane Doe"),
+        long(0x000C, 123456789),
+        shorts(0x0093, &file_info),
+        ascii(0x0095, "EF24-70mm f/2.8L II USM"),
+    ]
+}
+
+/// Build a little-endian TIFF with Make/Model and a MakerNote in the EXIF
+/// IFD. `note` receives the note's offset in the TIFF and returns its bytes.
+fn tiff_with_note(make: &str, model: &str, note: impl FnOnce(usize) -> Vec<u8>) -> Vec<u8> {
+    let placeholder = ifd(
+        &[ascii(0x010F, make), ascii(0x0110, model), long(0x8769, 0)],
+        8,
+        0,
+    );
+    let exif_at = 8 + placeholder.len();
+    let ifd0 = ifd(
+        &[
+            ascii(0x010F, make),
+            ascii(0x0110, model),
+            long(0x8769, exif_at as u32),
+        ],
+        8,
+        0,
+    );
+
+    // The MakerNote value follows the one-entry EXIF IFD directly
+    let note_at = exif_at + 2 + 12 + 4;
+    let note = note(note_at);
+    let mut exif_ifd = 1u16.to_le_bytes().to_vec();
+    exif_ifd.extend_from_slice(&0x927Cu16.to_le_bytes());
+    exif_ifd.extend_from_slice(&7u16.to_le_bytes());
+    exif_ifd.extend_from_slice(&(note.len() as u32).to_le_bytes());
+    exif_ifd.extend_from_slice(&(note_at as u32).to_le_bytes());
+    exif_ifd.extend_from_slice(&0u32.to_le_bytes());
+
+    let mut tiff = b"II*\0".to_vec();
+    tiff.extend_from_slice(&8u32.to_le_bytes());
+    tiff.extend(ifd0);
+    tiff.extend(exif_ifd);
+    tiff.extend(note);
+    tiff
+}
+
+/// Build a TIFF whose MakerNote is a bare IFD with TIFF-relative offsets.
+fn tiff_with_maker_note(make: &str, model: &str, note_entries: &[Entry]) -> Vec<u8> {
+    tiff_with_note(make, model, |at| ifd(note_entries, at, 0))
+}
+
+#[test]
+fn test_locate_maker_note() {
+    let tiff = tiff_with_maker_note("Canon", "Canon EOS-1D X", &canon_entries());
+    let note = locate_maker_note(&tiff, "Canon", "Canon EOS-1D X").expect("maker note");
+    assert_eq!(&tiff[note.offset..note.offset + note.data.len()], note.data);
+    assert!(locate_maker_note(b"II*\0\x08\0\0\0", "Canon", "").is_none());
+}
+
+#[test]
+fn test_decode_canon_fields() {
+    let tiff = tiff_with_maker_note("Canon", "Canon EOS-1D X", &canon_entries());
+    let fields = decode_maker_note(&tiff, "Canon", "Canon EOS-1D X");
+
+    assert_eq!(fields["Canon:OwnerName"], "Jane Doe");
+    assert_eq!(fields["Canon:SerialNumber"], "0123456789");
+    assert_eq!(
+        fields["Canon:SerialNumber"].raw,
+        RawValue::Text("0123456789".to_string())
+    );
+    assert_eq!(fields["Canon:LensModel"], "EF24-70mm f/2.8L II USM");
+    assert_eq!(fields["Canon:FirmwareVersion"], "Firmware Version 2.1.0");
+    assert_eq!(fields["Canon:ImageType"], "Canon EOS-1D X");
+    assert_eq!(fields["Canon:FileNumber"], "100-1234");
+    assert_eq!(fields["Canon:ShutterCount"].as_i64(), Some(100000));
+    assert_eq!(
+        fields["Canon:OwnerName"].source,
+        Some(MetadataSource::MakerNote {
+            vendor: "Canon",
+            tag: 0x0009
+        })
+    );
+}
+
+#[test]
+fn test_shutter_count_only_for_eos_1d() {
+    let tiff = tiff_with_maker_note("Canon", "Canon EOS 5D Mark III", &canon_entries());
+    let fields = decode_maker_note(&tiff, "Canon", "Canon EOS 5D Mark III");
+    assert!(!fields.contains_key("Canon:ShutterCount"));
+    assert!(fields.contains_key("Canon:FileNumber"));
+}
+
+#[test]
+fn test_other_makes_are_not_decoded_as_canon() {
+    let tiff = tiff_with_maker_note("Pentax", "K-1", &canon_entries());
+    assert!(decode_maker_note(&tiff, "Pentax", "K-1").is_empty());
+}
+
+#[test]
+fn test_extract_exif_data_includes_canon_fields() {
+    let tiff = tiff_with_maker_note("Canon", "Canon EOS-1D X", &canon_entries());
+    for data in [tiff.clone(), jpeg_with_exif(&tiff)] {
+        let (map, _) = extract_exif_data(&data);
+        assert_eq!(map["Canon:SerialNumber"], "0123456789");
+        assert!(map.contains_key("MakerNote"));
+    }
+}
+
+#[test]
+fn test_canon_fields_metadata_info() {
+    assert_eq!(get_metadata_category("Canon:SerialNumber"), "📷 Camera");
+    assert_eq!(get_metadata_category("Canon:LensModel"), "🔍 Lens");
+    assert_eq!(get_metadata_category("Canon:ShutterCount"), "📷 Camera");
+    assert!(get_metadata_explanation("Canon:ShutterCount").is_some());
+}
+
+#[test]
+fn test_canon_serial_and_owner_feed_privacy_score() {
+    let tiff = tiff_with_maker_note("Canon", "Canon EOS-1D X", &canon_entries());
+    let (exif_data, _) = extract_exif_data(&tiff);
+    let data = ImageData {
+        name: "IMG_0001.JPG".to_string(),
+        size: tiff.len() as u64,
+        mime_type: "image/tiff".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+    let risk = data.calculate_privacy_risk();
+    assert!(
+        risk.sensitive_fields
+            .contains(&"Camera Serial Number".to_string())
+    );
+    assert!(
+        risk.sensitive_fields
+            .contains(&"Owner/Artist Name".to_string())
+    );
+    assert!(
+        risk.sensitive_fields
+            .contains(&"Lens Information".to_string())
+    );
+}
+
+fn nikon_note(lens_data: &[u8]) -> Vec<u8> {
+    let entries = [
+        undefined(0x0001, b"0210"),
+        ascii(0x001D, "3001234"),
+        (0x0083, 1, 1, vec![0x0E]),
+        rationals(0x0084, &[(24, 1), (70, 1), (28, 10), (28, 10)]),
+        undefined(0x0098, lens_data),
+        long(0x00A7, 12345),
+    ];
+    // Type 3 notes embed their own TIFF header, offsets relative to it
+    let mut note = b"Nikon\0\x02\x10\0\0II*\0".to_vec();
+    note.extend_from_slice(&8u32.to_le_bytes());
+    note.extend(ifd(&entries, 8, 0));
+    note
+}
+
+fn nikon_lens_data() -> Vec<u8> {
+    let mut lens_data = b"0204".to_vec();
+    lens_data.extend_from_slice(&[0; 8]);
+    lens_data.extend_from_slice(&[0xA0, 0x48, 0x2A, 0x5C, 0x24, 0x30, 0x4B]);
+    lens_data.resize(33, 0);
+    lens_data
+}
+
+#[test]
+fn test_decode_nikon_fields_and_encrypted_lens_data() {
+    let plain = nikon_lens_data();
+    let mut encrypted = plain.clone();
+    nikon::decrypt(&mut encrypted, 3001234, 12345, 4);
+    assert_eq!(encrypted[..4], plain[..4]);
+    assert_ne!(encrypted, plain);
+
+    let tiff = tiff_with_note("NIKON CORPORATION", "NIKON D850", |_| {
+        nikon_note(&encrypted)
+    });
+    let fields = decode_maker_note(&tiff, "NIKON CORPORATION", "NIKON D850");
+    assert_eq!(fields["Nikon:SerialNumber"], "3001234");
+    assert_eq!(fields["Nikon:ShutterCount"].as_i64(), Some(12345));
+    assert_eq!(fields["Nikon:MakerNoteVersion"], "0210");
+    assert_eq!(fields["Nikon:Lens"], "24-70mm f/2.8");
+    assert_eq!(fields["Nikon:LensID"], "A0 48 2A 5C 24 30 4B 0E");
+    assert_eq!(
+        fields["Nikon:LensID"].source,
+        Some(MetadataSource::MakerNote {
+            vendor: "Nikon",
+            tag: 0x0098
+        })
+    );
+}
+
+#[test]
+fn test_nikon_lens_data_key_depends_on_shutter_count() {
+    // Encrypted with a different shutter count, the lens bytes decode to noise
+    let mut encrypted = nikon_lens_data();
+    nikon::decrypt(&mut encrypted, 3001234, 99999, 4);
+    let tiff = tiff_with_note("NIKON CORPORATION", "NIKON D850", |_| {
+        nikon_note(&encrypted)
+    });
+    let fields = decode_maker_note(&tiff, "NIKON CORPORATION", "NIKON D850");
+    assert_ne!(
+        fields.get("Nikon:LensID").map(|v| v.display.as_str()),
+        Some("A0 48 2A 5C 24 30 4B 0E")
+    );
+    assert_eq!(fields["Nikon:SerialNumber"], "3001234");
+}
+
+fn sony_encipher(data: &[u8]) -> Vec<u8> {
+    data.iter()
+        .map(|&b| match b {
+            0..=248 => ((b as u32).pow(3) % 249) as u8,
+            _ => b,
+        })
+        .collect()
+}
+
+#[test]
+fn test_sony_decipher_round_trip() {
+    let plain: Vec<u8> = (0..=255).collect();
+    assert_eq!(sony::decipher(&sony_encipher(&plain)), plain);
+}
+
+#[test]
+fn test_decode_sony_fields() {
+    let mut block = vec![0u8; 0x100];
+    block[0x3A..0x3E].copy_from_slice(&4321u32.to_le_bytes());
+    block[0x3D] = 0x7F; // high byte is not part of the count
+    let entries = [
+        ascii(0x2031, "5012345"),
+        undefined(0x9050, &sony_encipher(&block)),
+        (0xB001, 3, 1, 0x0126u16.to_le_bytes().to_vec()),
+        long(0xB027, 32790),
+    ];
+    let tiff = tiff_with_maker_note("SONY", "ILCE-7M3", &entries);
+    let fields = decode_maker_note(&tiff, "SONY", "ILCE-7M3");
+    assert_eq!(fields["Sony:SerialNumber"], "5012345");
+    assert_eq!(fields["Sony:LensType"].as_i64(), Some(32790));
+    assert_eq!(fields["Sony:SonyModelID"].as_i64(), Some(0x0126));
+    assert_eq!(fields["Sony:ShutterCount"].as_i64(), Some(4321));
+
+    // Unknown 0x9050 layouts are not guessed at
+    let fields = decode_maker_note(&tiff, "SONY", "ILCE-7M4");
+    assert!(!fields.contains_key("Sony:ShutterCount"));
+}
+
+#[test]
+fn test_decode_sony_compact_header() {
+    let tiff = tiff_with_note("SONY", "DSC-W800", |at| {
+        let mut note = b"SONY DSC \0\0\0".to_vec();
+        note.extend(ifd(&[ascii(0x2031, "7654321")], at + 12, 0));
+        note
+    });
+    let fields = decode_maker_note(&tiff, "SONY", "DSC-W800");
+    assert_eq!(fields["Sony:SerialNumber"], "7654321");
+}
+
+#[test]
+fn test_decode_fujifilm_fields() {
+    let entries = [
+        undefined(0x0000, b"0130"),
+        ascii(0x0010, "FF02B1234567     59333231 2021:03:04"),
+        rationals(0x1404, &[(18, 1)]),
+        rationals(0x1405, &[(55, 1)]),
+        rationals(0x1406, &[(28, 10)]),
+        rationals(0x1407, &[(4, 1)]),
+        (0x1438, 3, 1, (0x8000u16 | 250).to_le_bytes().to_vec()),
+    ];
+    let tiff = tiff_with_note("FUJIFILM", "X-T4", |_| {
+        // Offsets are relative to the note, which starts with its header
+        let mut note = b"FUJIFILM".to_vec();
+        note.extend_from_slice(&12u32.to_le_bytes());
+        note.extend(ifd(&entries, 12, 0));
+        note
+    });
+    let fields = decode_maker_note(&tiff, "FUJIFILM", "X-T4");
+    assert_eq!(fields["Fujifilm:Version"], "0130");
+    assert_eq!(
+        fields["Fujifilm:InternalSerialNumber"],
+        "FF02B1234567     59333231 2021:03:04"
+    );
+    assert_eq!(fields["Fujifilm:ImageCount"].as_i64(), Some(250));
+    assert_eq!(fields["Fujifilm:Lens"], "18-55mm f/2.8-4.0");
+}
+
+#[test]
+fn test_decode_apple_identifiers() {
+    let entries = [
+        (0x0001, 9, 1, 14u32.to_le_bytes().to_vec()),
+        long(0x000A, 3),
+        ascii(0x000B, "D1F0A7E4-8C71-4A2B-9E1A-5B0C7D3E2F10"),
+        ascii(0x0011, "6A1B2C3D-4E5F-4071-8293-A4B5C6D7E8F9"),
+    ];
+    let tiff = tiff_with_note("Apple", "iPhone 15 Pro", |_| {
+        let mut note = b"Apple iOS\0\0\x01II".to_vec();
+        note.extend(ifd(&entries, 14, 0));
+        note
+    });
+    let fields = decode_maker_note(&tiff, "Apple", "iPhone 15 Pro");
+    assert_eq!(fields["Apple:MakerNoteVersion"].as_i64(), Some(14));
+    assert_eq!(fields["Apple:HDRImageType"], "HDR Image");
+    assert_eq!(
+        fields["Apple:BurstUUID"],
+        "D1F0A7E4-8C71-4A2B-9E1A-5B0C7D3E2F10"
+    );
+    assert_eq!(
+        fields["Apple:ContentIdentifier"],
+        "6A1B2C3D-4E5F-4071-8293-A4B5C6D7E8F9"
+    );
+}
+
+#[test]
+fn test_custom_decoder_registry() {
+    let tiff = tiff_with_note("NIKON CORPORATION", "NIKON D850", |_| {
+        nikon_note(&nikon_lens_data())
+    });
+    let canon_only = [MakerNoteDecoder {
+        vendor: "Canon",
+        matches: canon::matches,
+        decode: canon::decode,
+    }];
+    assert!(
+        decode_maker_note_with(&tiff, "NIKON CORPORATION", "NIKON D850", &canon_only).is_empty()
+    );
+    assert!(decode_maker_note_with(&tiff, "NIKON CORPORATION", "NIKON D850", &[]).is_empty());
+    assert!(!decode_maker_note(&tiff, "NIKON CORPORATION", "NIKON D850").is_empty());
+}
+
+#[test]
+fn test_vendor_fields_metadata_info() {
+    for key in [
+        "Nikon:SerialNumber",
+        "Nikon:ShutterCount",
+        "Sony:SerialNumber",
+        "Fujifilm:InternalSerialNumber",
+        "Apple:ContentIdentifier",
+        "Apple:BurstUUID",
+    ] {
+        assert_eq!(get_metadata_category(key), "📷 Camera", "{key}");
+        assert!(get_metadata_explanation(key).is_some(), "{key}");
+    }
+    assert_eq!(get_metadata_category("Nikon:LensID"), "🔍 Lens");
+    assert_eq!(get_metadata_category("Sony:LensType"), "🔍 Lens");
+}
+
+#[test]
+fn test_apple_identifiers_feed_privacy_score() {
+    let mut exif_data = HashMap::new();
+    exif_data.insert(
+        "Apple:ContentIdentifier".to_string(),
+        "6A1B2C3D-4E5F-4071-8293-A4B5C6D7E8F9".into(),
+    );
+    let data = ImageData {
+        name: "IMG_0001.HEIC".to_string(),
+        size: 1024,
+        mime_type: "image/heic".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+    let risk = data.calculate_privacy_risk();
+    assert_eq!(risk.score, 10);
+    assert!(
+        risk.sensitive_fields
+            .contains(&"Device Fingerprint".to_string())
+    );
+}
@@ -1,5 +1,5 @@
 use image_metadata_extractor::metadata_info::{
-    METADATA_DB, get_metadata_category, get_metadata_explanation,
+    METADATA_DB, get_metadata_category, get_metadata_explanation, split_group,
 };
 
 #[test]
@@ -73,7 +73,9 @@ fn test_database_consistency() {
 #[test]
 fn test_category_emojis_present() {
     // Ensure all categories have our specific emoji prefixes
-    let expected_emojis = ["📷", "⚙️", "🖼️", "🕒", "📍", "🔍", "📊"];
+    let expected_emojis = [
+        "📷", "⚙️", "🖼️", "🕒", "📍", "🔍", "📝", "📰", "🎨", "🤖", "📄", "📊",
+    ];
 
     for (_, info) in METADATA_DB {
         let has_expected_emoji = expected_emojis
@@ -86,3 +88,30 @@ fn test_category_emojis_present() {
         );
     }
 }
+
+#[test]
+fn test_grouped_keys() {
+    assert_eq!(
+        split_group("Thumbnail:DateTime"),
+        (Some("Thumbnail"), "DateTime")
+    );
+    assert_eq!(split_group("DateTime"), (None, "DateTime"));
+    assert_eq!(split_group("Note: free text"), (None, "Note: free text"));
+
+    assert_eq!(
+        get_metadata_category("Thumbnail:DateTime"),
+        "🧩 Thumbnail (IFD1)"
+    );
+    assert_eq!(get_metadata_category("Unknown:Field"), "📊 Other");
+    // XMP namespaces share the XMP family category unless listed explicitly
+    assert_eq!(get_metadata_category("XMP-crs:Exposure2012"), "📝 XMP");
+    assert_eq!(
+        get_metadata_category("XMP-photoshop:DateCreated"),
+        "🕒 Date & Time"
+    );
+    // Grouped keys fall back to the explanation of the plain field name
+    assert_eq!(
+        get_metadata_explanation("Thumbnail:Make"),
+        get_metadata_explanation("Make")
+    );
+}
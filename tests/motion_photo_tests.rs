@@ -0,0 +1,280 @@
+mod common;
+
+use common::bx;
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, Rgb, RgbImage};
+use image_metadata_extractor::binary_cleaner::{BinaryCleaner, CleanOptions};
+use image_metadata_extractor::motion_photo::{
+    SAMSUNG_MARKER, content_identifier, embedded_video_bytes, find_embedded_video,
+    live_photo_partner, strip_embedded_video,
+};
+use image_metadata_extractor::types::{ImageData, MetadataValue};
+use image_metadata_extractor::xmp::JPEG_XMP_SIGNATURE;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A two-second MP4 with a sound track.
+fn mp4() -> Vec<u8> {
+    let mut mvhd = vec![0; 4 + 8];
+    mvhd.extend_from_slice(&1000u32.to_be_bytes());
+    mvhd.extend_from_slice(&2000u32.to_be_bytes());
+    mvhd.extend_from_slice(&[0; 80]);
+    let mut hdlr = vec![0; 8];
+    hdlr.extend_from_slice(b"soun");
+    hdlr.extend_from_slice(&[0; 13]);
+    let trak = bx(b"trak", &bx(b"mdia", &bx(b"hdlr", &hdlr)));
+    let moov = bx(b"moov", &[bx(b"mvhd", &mvhd), trak].concat());
+    [
+        bx(b"ftyp", b"isom\0\0\x02\0isommp41"),
+        moov,
+        bx(b"mdat", &[0x42; 64]),
+    ]
+    .concat()
+}
+
+/// A small JPEG, with an XMP packet when given.
+fn jpeg(xmp: Option<&str>) -> Vec<u8> {
+    let image = DynamicImage::ImageRgb8(RgbImage::from_fn(16, 16, |x, y| {
+        Rgb([(x * 16) as u8, (y * 16) as u8, 128])
+    }));
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, 80)
+        .encode_image(&image)
+        .unwrap();
+    if let Some(xmp) = xmp {
+        let mut payload = JPEG_XMP_SIGNATURE.to_vec();
+        payload.extend_from_slice(xmp.as_bytes());
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        segment.extend(payload);
+        out.splice(2..2, segment);
+    }
+    out
+}
+
+fn xmp(attributes: &str, body: &str) -> String {
+    format!(
+        r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description xmlns:GCamera="http://ns.google.com/photos/1.0/camera/" xmlns:Container="http://ns.google.com/photos/1.0/container/" xmlns:Item="http://ns.google.com/photos/1.0/container/item/" {}>{}</rdf:Description></rdf:RDF></x:xmpmeta>"#,
+        attributes, body
+    )
+}
+
+fn directory_item(mime: &str, semantic: &str, length: usize) -> String {
+    format!(
+        r#"<rdf:li rdf:parseType="Resource"><Container:Item Item:Mime="{}" Item:Semantic="{}" Item:Length="{}"/></rdf:li>"#,
+        mime, semantic, length
+    )
+}
+
+fn image_data(name: &str, mime_type: &str, key: &str, id: &str) -> ImageData {
+    ImageData {
+        name: name.to_string(),
+        size: 0,
+        mime_type: mime_type.to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data: HashMap::from([(key.to_string(), MetadataValue::text(id))]),
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_google_micro_video_offset() {
+    let clip = mp4();
+    let packet = xmp(
+        &format!(
+            r#"GCamera:MicroVideo="1" GCamera:MicroVideoOffset="{}" GCamera:MicroVideoPresentationTimestampUs="1500000""#,
+            clip.len()
+        ),
+        "",
+    );
+    let still = jpeg(Some(&packet));
+    let file = [still.clone(), clip.clone()].concat();
+
+    let video = find_embedded_video(&file).unwrap();
+    assert_eq!(video.kind, "Google Motion Photo");
+    assert_eq!(video.offset, still.len() as u64);
+    assert_eq!(video.size, clip.len() as u64);
+    assert_eq!(video.mime_type, "video/mp4");
+    assert_eq!(video.duration, Some(2.0));
+    assert!(video.has_audio);
+    assert!(!video.has_location);
+    assert_eq!(video.presentation_timestamp_us, Some(1_500_000));
+    assert_eq!(embedded_video_bytes(&file, &video), Some(&clip[..]));
+    assert_eq!(strip_embedded_video(&file), Some(still));
+}
+
+#[test]
+fn test_google_container_directory() {
+    let clip = mp4();
+    let gain_map = vec![0xAA; 40];
+    let packet = xmp(
+        r#"GCamera:MotionPhoto="1" GCamera:MotionPhotoPresentationTimestampUs="-1""#,
+        &format!(
+            "<Container:Directory><rdf:Seq>{}{}{}</rdf:Seq></Container:Directory>",
+            directory_item("image/jpeg", "Primary", 0),
+            directory_item("video/mp4", "MotionPhoto", clip.len()),
+            directory_item("image/jpeg", "GainMap", gain_map.len()),
+        ),
+    );
+    let still = jpeg(Some(&packet));
+    let file = [still.clone(), clip.clone(), gain_map.clone()].concat();
+
+    let video = find_embedded_video(&file).unwrap();
+    assert_eq!(video.kind, "Google Motion Photo");
+    assert_eq!(video.offset, still.len() as u64);
+    assert_eq!(video.size, clip.len() as u64);
+    assert_eq!(video.presentation_timestamp_us, None);
+    // Only the clip is cut; the gain map stays
+    assert_eq!(
+        strip_embedded_video(&file),
+        Some([still, gain_map].concat())
+    );
+}
+
+#[test]
+fn test_google_container_directory_with_huge_lengths() {
+    let packet = xmp(
+        r#"GCamera:MotionPhoto="1""#,
+        &format!(
+            "<Container:Directory><rdf:Seq>{}{}{}</rdf:Seq></Container:Directory>",
+            directory_item("image/jpeg", "Primary", 0),
+            directory_item("video/mp4", "MotionPhoto", usize::MAX),
+            directory_item("image/jpeg", "GainMap", usize::MAX),
+        ),
+    );
+    let still = jpeg(Some(&packet));
+    let file = [still.clone(), mp4()].concat();
+
+    // The directory is ignored and the clip is found as an appended video
+    let video = find_embedded_video(&file).unwrap();
+    assert_eq!(video.kind, "Appended video");
+    assert_eq!(strip_embedded_video(&file), Some(still));
+    assert!(BinaryCleaner::clean_metadata(&file, "jpg").is_ok());
+}
+
+#[test]
+fn test_samsung_trailer_and_heic_box() {
+    let clip = mp4();
+    let still = jpeg(None);
+    let trailer = b"SEFH\x00\x00\x00\x00MotionPhoto_Info\x00SEFT".to_vec();
+    let file = [
+        still.clone(),
+        SAMSUNG_MARKER.to_vec(),
+        clip.clone(),
+        trailer,
+    ]
+    .concat();
+
+    let video = find_embedded_video(&file).unwrap();
+    assert_eq!(video.kind, "Samsung Motion Photo");
+    assert_eq!(video.offset, (still.len() + SAMSUNG_MARKER.len()) as u64);
+    assert_eq!(video.size, clip.len() as u64);
+    assert_eq!(strip_embedded_video(&file), Some(still));
+
+    let ftyp = bx(b"ftyp", b"heic\0\0\0\0mif1heic");
+    let meta = bx(b"meta", &[0; 4]);
+    let heic = [ftyp.clone(), meta.clone(), bx(b"mpvd", &clip)].concat();
+    let video = find_embedded_video(&heic).unwrap();
+    assert_eq!(video.kind, "Samsung Motion Photo");
+    assert_eq!(embedded_video_bytes(&heic, &video), Some(&clip[..]));
+    assert_eq!(strip_embedded_video(&heic), Some([ftyp, meta].concat()));
+
+    // A box after `mpvd` may be addressed by offset, so it is not cut
+    let shifted = [heic.clone(), bx(b"mdat", &[0; 8])].concat();
+    assert!(find_embedded_video(&shifted).is_some());
+    assert_eq!(strip_embedded_video(&shifted), None);
+}
+
+#[test]
+fn test_undescribed_appended_video() {
+    let still = jpeg(None);
+    let file = [still.clone(), b"pad".to_vec(), mp4()].concat();
+    let video = find_embedded_video(&file).unwrap();
+    assert_eq!(video.kind, "Appended video");
+    assert_eq!(video.offset, still.len() as u64 + 3);
+
+    assert_eq!(find_embedded_video(&still), None);
+    assert_eq!(
+        find_embedded_video(&[still, b"trailing".to_vec()].concat()),
+        None
+    );
+    // A wrong offset in XMP is not trusted
+    let packet = xmp(r#"GCamera:MicroVideoOffset="10""#, "");
+    assert_eq!(
+        find_embedded_video(&[jpeg(Some(&packet)), vec![0; 10]].concat()),
+        None
+    );
+}
+
+#[test]
+fn test_cleaner_drops_video_unless_kept() {
+    let clip = mp4();
+    let packet = xmp(&format!(r#"GCamera:MicroVideoOffset="{}""#, clip.len()), "");
+    let file = [jpeg(Some(&packet)), clip.clone()].concat();
+
+    let cleaned = BinaryCleaner::clean_metadata(&file, "jpg").unwrap();
+    assert!(!cleaned.windows(4).any(|w| w == b"ftyp"));
+    assert!(cleaned.ends_with(&[0xFF, 0xD9]));
+    assert!(image::load_from_memory(&cleaned).is_ok());
+
+    let kept = BinaryCleaner::clean_metadata_with_options(
+        &file,
+        "jpg",
+        CleanOptions {
+            remove_embedded_video: false,
+            ..CleanOptions::default()
+        },
+    )
+    .unwrap();
+    assert!(kept.ends_with(&clip));
+    // The XMP describing the clip is still removed
+    assert!(!kept.windows(8).any(|w| w == b"GCamera:"));
+}
+
+#[test]
+fn test_live_photo_pairing_and_risk() {
+    let id = "A1B2C3D4-0000-4000-8000-123456789ABC";
+    let still = image_data("IMG_0001.HEIC", "image/heif", "Apple:ContentIdentifier", id);
+    let movie = image_data(
+        "IMG_0001.MOV",
+        "video/quicktime",
+        "QuickTime:ContentIdentifier",
+        id,
+    );
+    let other = image_data(
+        "IMG_0002.MOV",
+        "video/quicktime",
+        "QuickTime:ContentIdentifier",
+        "other",
+    );
+    assert_eq!(content_identifier(&still), Some(id));
+
+    let batch: Vec<Rc<ImageData>> = [&still, &other, &movie]
+        .into_iter()
+        .cloned()
+        .map(Rc::new)
+        .collect();
+    assert_eq!(
+        live_photo_partner(&still, &batch).map(|d| d.name.as_str()),
+        Some("IMG_0001.MOV")
+    );
+    assert_eq!(
+        live_photo_partner(&movie, &batch).map(|d| d.name.as_str()),
+        Some("IMG_0001.HEIC")
+    );
+    assert!(live_photo_partner(&other, &batch).is_none());
+
+    let file = [jpeg(None), SAMSUNG_MARKER.to_vec(), mp4()].concat();
+    let mut data = still.clone();
+    data.embedded_video = find_embedded_video(&file);
+    let risk = data.calculate_privacy_risk();
+    assert!(
+        risk.sensitive_fields
+            .contains(&"Embedded Video".to_string())
+    );
+    assert!(risk.warnings.iter().any(|w| w.contains("sound")));
+}
@@ -0,0 +1,319 @@
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, Rgb, RgbImage};
+use image_metadata_extractor::binary_cleaner::{BinaryCleaner, CleanOptions};
+use image_metadata_extractor::exif_core::extract_metadata;
+use image_metadata_extractor::jpeg::analyze_jpeg;
+use image_metadata_extractor::mpf::{
+    MpEntry, extract_images, image_type_name, mp_index_segment, parse_mp_index,
+};
+use image_metadata_extractor::types::ImageData;
+use image_metadata_extractor::xmp::JPEG_XMP_SIGNATURE;
+use std::collections::HashMap;
+
+const PRIMARY: u32 = 0x2003_0000 | 0x8000_0000;
+const DISPARITY: u32 = 0x4002_0002;
+
+fn encode(size: u32) -> Vec<u8> {
+    let image = DynamicImage::ImageRgb8(RgbImage::from_fn(size, size, |x, y| {
+        Rgb([(x * 16) as u8, (y * 16) as u8, 64])
+    }));
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, 85)
+        .encode_image(&image)
+        .unwrap();
+    out
+}
+
+/// Insert a segment right after SOI.
+fn with_segment(jpeg: &[u8], marker: u8, payload: &[u8]) -> Vec<u8> {
+    let mut out = jpeg[..2].to_vec();
+    out.extend_from_slice(&[0xFF, marker]);
+    out.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+    out.extend_from_slice(payload);
+    out.extend_from_slice(&jpeg[2..]);
+    out
+}
+
+/// EXIF APP1 payload with a camera make and a GPS latitude.
+fn exif(make: &str) -> Vec<u8> {
+    let mut make = make.as_bytes().to_vec();
+    make.push(0);
+    // IFD0 at 8 with Make and GPSInfo, GPS IFD at 38, values after it
+    let gps_ifd = 38u32;
+    let make_offset = gps_ifd + 2 + 2 * 12 + 4;
+    let latitude_offset = make_offset + make.len() as u32;
+    let mut tiff = b"II*\0".to_vec();
+    tiff.extend_from_slice(&8u32.to_le_bytes());
+    tiff.extend_from_slice(&2u16.to_le_bytes());
+    tiff.extend_from_slice(&0x010Fu16.to_le_bytes());
+    tiff.extend_from_slice(&2u16.to_le_bytes());
+    tiff.extend_from_slice(&(make.len() as u32).to_le_bytes());
+    tiff.extend_from_slice(&make_offset.to_le_bytes());
+    tiff.extend_from_slice(&[0x25, 0x88, 4, 0, 1, 0, 0, 0]);
+    tiff.extend_from_slice(&gps_ifd.to_le_bytes());
+    tiff.extend_from_slice(&0u32.to_le_bytes());
+    tiff.extend_from_slice(&2u16.to_le_bytes());
+    tiff.extend_from_slice(&[1, 0, 2, 0, 2, 0, 0, 0, b'N', 0, 0, 0]);
+    tiff.extend_from_slice(&[2, 0, 5, 0, 3, 0, 0, 0]);
+    tiff.extend_from_slice(&latitude_offset.to_le_bytes());
+    tiff.extend_from_slice(&0u32.to_le_bytes());
+    tiff.extend(make);
+    for (num, denom) in [(48u32, 1u32), (51, 1), (30, 1)] {
+        tiff.extend_from_slice(&num.to_le_bytes());
+        tiff.extend_from_slice(&denom.to_le_bytes());
+    }
+    [b"Exif\0\0".to_vec(), tiff].concat()
+}
+
+/// Little-endian MPF APP2 payload with image UIDs, as cameras write it.
+fn mpf_payload(entries: &[MpEntry]) -> Vec<u8> {
+    let values = 8 + 2 + 4 * 12 + 4;
+    let uids = values + entries.len() * 16;
+    let mut tiff = b"II*\0".to_vec();
+    tiff.extend_from_slice(&8u32.to_le_bytes());
+    tiff.extend_from_slice(&4u16.to_le_bytes());
+    for (tag, kind, count, value) in [
+        (0xB000u16, 7u16, 4u32, u32::from_le_bytes(*b"0100")),
+        (0xB001, 4, 1, entries.len() as u32),
+        (0xB002, 7, entries.len() as u32 * 16, values as u32),
+        (0xB003, 7, entries.len() as u32 * 33, uids as u32),
+    ] {
+        tiff.extend_from_slice(&tag.to_le_bytes());
+        tiff.extend_from_slice(&kind.to_le_bytes());
+        tiff.extend_from_slice(&count.to_le_bytes());
+        tiff.extend_from_slice(&value.to_le_bytes());
+    }
+    tiff.extend_from_slice(&0u32.to_le_bytes());
+    for entry in entries {
+        tiff.extend_from_slice(&entry.attributes.to_le_bytes());
+        tiff.extend_from_slice(&entry.size.to_le_bytes());
+        tiff.extend_from_slice(&entry.offset.to_le_bytes());
+        tiff.extend_from_slice(&entry.dependents[0].to_le_bytes());
+        tiff.extend_from_slice(&entry.dependents[1].to_le_bytes());
+    }
+    for i in 0..entries.len() {
+        tiff.extend_from_slice(format!("{:032X}\0", 0xABCD0000u32 + i as u32).as_bytes());
+    }
+    [b"MPF\0".to_vec(), tiff].concat()
+}
+
+fn entry(attributes: u32, size: usize, offset: usize, dependents: [u16; 2]) -> MpEntry {
+    MpEntry {
+        attributes,
+        size: size as u32,
+        offset: offset as u32,
+        dependents,
+    }
+}
+
+/// A primary image followed by `extras`, indexed in the primary's APP2.
+fn mpo(extras: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    mpo_repeating(extras, 1)
+}
+
+/// As [`mpo`], but with every extra image listed `copies` times in the index.
+fn mpo_repeating(extras: &[(u32, Vec<u8>)], copies: usize) -> Vec<u8> {
+    let base = with_segment(&encode(16), 0xE1, &exif("PrimaryCam"));
+    // APP2 goes after the APP1 segment; its size does not depend on offsets
+    let app1_end = 4 + u16::from_be_bytes([base[4], base[5]]) as usize;
+    let header_offset = app1_end + 4 + 4;
+    let placeholder = vec![entry(0, 0, 0, [0, 0]); extras.len() * copies + 1];
+    let primary_size = base.len() + 4 + mpf_payload(&placeholder).len();
+
+    let mut entries = vec![entry(PRIMARY, primary_size, 0, [2, 0])];
+    let mut offset = primary_size;
+    for (attributes, image) in extras {
+        let copy = entry(*attributes, image.len(), offset - header_offset, [0, 0]);
+        entries.extend(std::iter::repeat_n(copy, copies));
+        offset += image.len();
+    }
+    let payload = mpf_payload(&entries);
+    let mut file = base[..app1_end].to_vec();
+    file.extend_from_slice(&[0xFF, 0xE2]);
+    file.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+    file.extend(payload);
+    file.extend_from_slice(&base[app1_end..]);
+    assert_eq!(file.len(), primary_size);
+    for (_, image) in extras {
+        file.extend_from_slice(image);
+    }
+    file
+}
+
+fn stereo() -> Vec<u8> {
+    mpo(&[(
+        DISPARITY,
+        with_segment(&encode(8), 0xE1, &exif("StereoCam")),
+    )])
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+#[test]
+fn test_mp_index_is_parsed() {
+    let file = stereo();
+    let index = parse_mp_index(&file).unwrap();
+    assert_eq!(index.version.as_deref(), Some("0100"));
+    assert_eq!(index.entries.len(), 2);
+    assert_eq!(index.entries[0].type_code(), 0x030000);
+    assert_eq!(index.entries[1].type_code(), 0x020002);
+    assert_eq!(index.image_uids[1], "000000000000000000000000ABCD0001");
+    assert_eq!(image_type_name(0x020002), "Multi-Frame Disparity");
+    assert_eq!(image_type_name(0x123456), "Undefined");
+
+    let (metadata, _) = extract_metadata(&file);
+    assert_eq!(metadata["MPF:NumberOfImages"].display, "2");
+    assert_eq!(
+        metadata["MPF:ImageTypes"].display,
+        "Baseline MP Primary Image, Multi-Frame Disparity"
+    );
+    assert_eq!(metadata["MPF:MPFVersion"].display, "0100");
+    assert!(metadata.contains_key("MPF:ImageUIDList"));
+    // The primary image's own EXIF is not replaced by the second view's
+    assert_eq!(metadata["Make"].display, "\"PrimaryCam\"");
+}
+
+#[test]
+fn test_every_image_is_listed_with_its_own_exif() {
+    let file = stereo();
+    let images = extract_images(&file);
+    assert_eq!(images.len(), 2);
+
+    let primary = &images[0];
+    assert_eq!(primary.index, 1);
+    assert_eq!(primary.offset, 0);
+    assert_eq!((primary.width, primary.height), (Some(16), Some(16)));
+    assert!(primary.exif_data.is_empty());
+    assert!(primary.data_url.is_empty());
+
+    let second = &images[1];
+    assert_eq!(second.index, 2);
+    assert_eq!(second.image_type, "Multi-Frame Disparity");
+    assert_eq!(second.offset + second.size, file.len() as u64);
+    assert_eq!((second.width, second.height), (Some(8), Some(8)));
+    assert_eq!(second.exif_data["Make"].display, "\"StereoCam\"");
+    assert!(second.exif_data.contains_key("GPSLatitude"));
+    assert!(second.data_url.starts_with("data:image/jpeg;base64,"));
+}
+
+#[test]
+fn test_gain_map_named_from_xmp_and_bad_entries_skipped() {
+    let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description xmlns:hdrgm="http://ns.adobe.com/hdr-gain-map/1.0/" hdrgm:Version="1.0"/></rdf:RDF></x:xmpmeta>"#;
+    let gain_map = with_segment(
+        &encode(8),
+        0xE1,
+        &[JPEG_XMP_SIGNATURE, xmp.as_bytes()].concat(),
+    );
+    let file = mpo(&[(0, gain_map)]);
+    let images = extract_images(&file);
+    assert_eq!(images[1].image_type, "Gain Map Image");
+
+    // An entry pointing past the end of the file is not listed
+    let mut truncated = stereo();
+    truncated.truncate(truncated.len() - 10);
+    assert_eq!(extract_images(&truncated).len(), 1);
+    assert!(analyze_jpeg(&truncated).is_some());
+}
+
+#[test]
+fn test_repeated_entries_are_listed_once() {
+    let second_view = with_segment(&encode(8), 0xE1, &exif("StereoCam"));
+    let file = mpo_repeating(&[(DISPARITY, second_view)], 1000);
+    assert_eq!(parse_mp_index(&file).unwrap().entries.len(), 1001);
+
+    let images = extract_images(&file);
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[1].index, 2);
+    assert_eq!(images[1].exif_data["Make"].display, "\"StereoCam\"");
+}
+
+#[test]
+fn test_cleaner_drops_extra_images_with_the_index() {
+    let file = stereo();
+    let cleaned = BinaryCleaner::clean_metadata(&file, "jpg").unwrap();
+
+    assert!(parse_mp_index(&cleaned).is_none());
+    assert_eq!(analyze_jpeg(&cleaned).unwrap().trailing_bytes, 0);
+    assert!(!contains(&cleaned, b"StereoCam"));
+    assert!(!contains(&cleaned, b"PrimaryCam"));
+    let decoded = image::load_from_memory(&cleaned).unwrap();
+    assert_eq!(decoded.width(), 16);
+}
+
+#[test]
+fn test_cleaner_keeps_extra_images_behind_a_rebuilt_index() {
+    let file = stereo();
+    let cleaned = BinaryCleaner::clean_metadata_with_options(
+        &file,
+        "jpg",
+        CleanOptions {
+            remove_extra_images: false,
+            ..CleanOptions::default()
+        },
+    )
+    .unwrap();
+
+    assert!(!contains(&cleaned, b"StereoCam"));
+    assert!(!contains(&cleaned, b"PrimaryCam"));
+    assert!(!contains(&cleaned, b"ABCD0001"));
+
+    let index = parse_mp_index(&cleaned).unwrap();
+    assert_eq!(index.entries.len(), 2);
+    assert_eq!(index.entries[0].attributes, PRIMARY);
+    assert_eq!(index.entries[0].dependents, [2, 0]);
+    assert_eq!(index.entries[1].attributes, DISPARITY);
+    assert!(index.image_uids.is_empty());
+
+    let images = extract_images(&cleaned);
+    assert_eq!(images.len(), 2);
+    assert_eq!(
+        images[0].size as usize + images[1].size as usize,
+        cleaned.len()
+    );
+    assert_eq!(
+        analyze_jpeg(&cleaned).unwrap().trailing_bytes,
+        images[1].size as usize
+    );
+    assert_eq!((images[1].width, images[1].height), (Some(8), Some(8)));
+    assert!(images[1].exif_data.is_empty());
+    assert!(image::load_from_memory(&cleaned).is_ok());
+}
+
+#[test]
+fn test_built_index_segment_round_trips() {
+    let entries = [
+        entry(PRIMARY, 1000, 0, [0, 0]),
+        entry(DISPARITY, 500, 990, [0, 0]),
+    ];
+    let segment = mp_index_segment(&entries);
+    assert_eq!(&segment[..2], &[0xFF, 0xE2]);
+    let file = [&[0xFF, 0xD8][..], &segment, &[0xFF, 0xD9]].concat();
+    let index = parse_mp_index(&file).unwrap();
+    assert_eq!(index.entries, entries);
+    assert_eq!(index.header_offset, 10);
+}
+
+#[test]
+fn test_extra_images_raise_privacy_risk() {
+    let data = ImageData {
+        name: "stereo.mpo".to_string(),
+        size: 0,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data: HashMap::new(),
+        gps: None,
+        sha256_hash: None,
+        mpf_images: extract_images(&stereo()),
+        ..Default::default()
+    };
+    let risk = data.calculate_privacy_risk();
+    assert!(risk.sensitive_fields.contains(&"Extra Images".to_string()));
+    assert!(risk.warnings.iter().any(|w| w.contains("own GPS location")));
+
+    let filtered = data.filter_metadata(&Default::default(), false, false);
+    assert!(filtered.mpf_images.is_empty());
+}
@@ -0,0 +1,354 @@
+mod common;
+
+use common::{ascii, ifd, jpeg_with_exif, zlib};
+use image_metadata_extractor::container;
+use image_metadata_extractor::exif_core::extract_metadata;
+use image_metadata_extractor::metadata_info::get_metadata_category;
+use image_metadata_extractor::pdf::{
+    decode_text_string, extract_pdf_data, find_xmp, parse_pdf, parse_pdf_date,
+};
+use image_metadata_extractor::types::{ImageData, MetadataSource};
+use image_metadata_extractor::xmp::extract_xmp_data;
+
+const XMP: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description xmlns:pdf="http://ns.adobe.com/pdf/1.3/" pdf:Producer="LibreOffice 7.6"/></rdf:RDF></x:xmpmeta>"#;
+
+fn stream(dict: &str, data: &[u8]) -> Vec<u8> {
+    let mut body = format!("<< {} /Length {} >>\nstream\n", dict, data.len()).into_bytes();
+    body.extend_from_slice(data);
+    body.extend_from_slice(b"\nendstream");
+    body
+}
+
+/// Append `N 0 obj ... endobj` for each object, returning their offsets.
+fn write_objects(out: &mut Vec<u8>, objects: &[(u32, Vec<u8>)]) -> Vec<(u32, usize)> {
+    let mut offsets = Vec::new();
+    for (number, body) in objects {
+        offsets.push((*number, out.len()));
+        out.extend_from_slice(format!("{} 0 obj\n", number).as_bytes());
+        out.extend_from_slice(body);
+        out.extend_from_slice(b"\nendobj\n");
+    }
+    offsets
+}
+
+/// Append a classic xref table and trailer covering `offsets`.
+fn write_xref_table(out: &mut Vec<u8>, offsets: &[(u32, usize)], trailer: &str) {
+    let xref = out.len();
+    let size = offsets.iter().map(|(n, _)| n + 1).max().unwrap_or(1);
+    out.extend_from_slice(b"xref\n0 1\n0000000000 65535 f \n");
+    for (number, offset) in offsets {
+        out.extend_from_slice(format!("{} 1\n{:010} 00000 n \n", number, offset).as_bytes());
+    }
+    out.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} {} >>\nstartxref\n{}\n%%EOF\n",
+            size, trailer, xref
+        )
+        .as_bytes(),
+    );
+}
+
+fn classic_pdf(info: &str, extra: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut objects = vec![
+        (
+            1,
+            b"<< /Type /Catalog /Pages 2 0 R /Metadata 4 0 R >>".to_vec(),
+        ),
+        (2, b"<< /Type /Pages /Kids [] /Count 3 >>".to_vec()),
+        (3, info.as_bytes().to_vec()),
+        (4, stream("/Type /Metadata /Subtype /XML", XMP.as_bytes())),
+    ];
+    objects.extend_from_slice(extra);
+    let mut out = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let offsets = write_objects(&mut out, &objects);
+    write_xref_table(&mut out, &offsets, "/Root 1 0 R /Info 3 0 R");
+    out
+}
+
+const INFO: &str = r"<< /Title <FEFF004D00FC006E006300680065006E> /Author (Jane \(J.\) Doe) /Creator (Microsoft\256 Word) /Producer (macOS Quartz PDFContext) /CreationDate (D:20240115103000+01'00') /ModDate (D:20240116) /Custom#20Key (Value) >>";
+
+/// Little-endian TIFF with Make, Model and a GPS IFD holding a latitude.
+fn tiff_with_gps() -> Vec<u8> {
+    let ifd0 = |gps: u32| {
+        ifd(
+            &[
+                ascii(0x010F, "Canon"),
+                ascii(0x0110, "Canon EOS R5"),
+                (0x8825, 4, 1, gps.to_le_bytes().to_vec()),
+            ],
+            8,
+            0,
+        )
+    };
+    let gps = 8 + ifd0(0).len();
+    let latitude = [(48u32, 1u32), (51, 1), (0, 1)]
+        .iter()
+        .flat_map(|(num, den)| [num.to_le_bytes(), den.to_le_bytes()].concat())
+        .collect();
+    let mut tiff = b"II*\0\x08\0\0\0".to_vec();
+    tiff.extend(ifd0(gps as u32));
+    tiff.extend(ifd(&[ascii(0x0001, "N"), (0x0002, 5, 3, latitude)], gps, 0));
+    tiff
+}
+
+/// A PDF 1.5 file whose catalog, pages and info live in a compressed object
+/// stream, indexed by a Flate-compressed xref stream with the PNG Up
+/// predictor.
+fn object_stream_pdf() -> Vec<u8> {
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R /Metadata 4 0 R >>",
+        "<< /Type /Pages /Kids [] /Count 12 >>",
+        "<< /Author (Object Stream Author) /Producer (pdfTeX-1.40.25) >>",
+    ];
+    let mut header = String::new();
+    let mut body = String::new();
+    for (i, object) in objects.iter().enumerate() {
+        header.push_str(&format!("{} {} ", i + 1, body.len()));
+        body.push_str(object);
+        body.push(' ');
+    }
+    let object_stream = format!("{}{}", header, body);
+
+    let mut out = b"%PDF-1.5\n".to_vec();
+    let offsets = write_objects(
+        &mut out,
+        &[
+            (
+                4,
+                stream(
+                    "/Type /Metadata /Subtype /XML /Filter /FlateDecode",
+                    &zlib(XMP.as_bytes()),
+                ),
+            ),
+            (
+                5,
+                stream(
+                    &format!(
+                        "/Type /ObjStm /N 3 /First {} /Filter /FlateDecode",
+                        header.len()
+                    ),
+                    &zlib(object_stream.as_bytes()),
+                ),
+            ),
+        ],
+    );
+
+    let xref_offset = out.len();
+    let mut rows: Vec<[u8; 7]> = vec![[0, 0, 0, 0, 0, 0xFF, 0xFF]];
+    for index in 0..3u16 {
+        let mut row = [2, 0, 0, 0, 5, 0, 0];
+        row[5..7].copy_from_slice(&index.to_be_bytes());
+        rows.push(row);
+    }
+    for offset in [offsets[0].1, offsets[1].1, xref_offset] {
+        let mut row = [1, 0, 0, 0, 0, 0, 0];
+        row[1..5].copy_from_slice(&(offset as u32).to_be_bytes());
+        rows.push(row);
+    }
+    // PNG Up predictor: each row stores its difference from the previous one
+    let mut predicted = Vec::new();
+    let mut previous = [0u8; 7];
+    for row in &rows {
+        predicted.push(2);
+        predicted.extend(row.iter().zip(previous).map(|(b, p)| b.wrapping_sub(p)));
+        previous = *row;
+    }
+    let xref = stream(
+        "/Type /XRef /Size 7 /W [1 4 2] /Root 1 0 R /Info 3 0 R /Filter /FlateDecode /DecodeParms << /Predictor 12 /Columns 7 >>",
+        &zlib(&predicted),
+    );
+    out.extend_from_slice(b"6 0 obj\n");
+    out.extend(xref);
+    out.extend_from_slice(format!("\nendobj\nstartxref\n{}\n%%EOF\n", xref_offset).as_bytes());
+    out
+}
+
+#[test]
+fn test_parse_pdf_date() {
+    assert_eq!(
+        parse_pdf_date("D:20240115103000+01'00'").as_deref(),
+        Some("2024:01:15 10:30:00+01:00")
+    );
+    assert_eq!(
+        parse_pdf_date("D:20231231235959Z").as_deref(),
+        Some("2023:12:31 23:59:59+00:00")
+    );
+    assert_eq!(
+        parse_pdf_date("D:199812231952-08'00").as_deref(),
+        Some("1998:12:23 19:52:00-08:00")
+    );
+    assert_eq!(
+        parse_pdf_date("D:2024").as_deref(),
+        Some("2024:01:01 00:00:00")
+    );
+    assert_eq!(parse_pdf_date("D:20241301"), None);
+    assert_eq!(parse_pdf_date("yesterday"), None);
+}
+
+#[test]
+fn test_decode_text_string() {
+    assert_eq!(decode_text_string(b"\xFE\xFF\x00H\x00i"), "Hi");
+    assert_eq!(decode_text_string(b"\xEF\xBB\xBFcaf\xC3\xA9"), "café");
+    // PDFDocEncoding maps 0x84 to an em dash and 0xA0 to the euro sign
+    assert_eq!(decode_text_string(b"A\x84B \xA0"), "A—B €");
+    assert_eq!(decode_text_string(b"caf\xE9\0"), "café");
+}
+
+#[test]
+fn test_classic_xref_info_dictionary() {
+    let data = classic_pdf(INFO, &[]);
+    assert!(container::is_pdf(&data));
+
+    let doc = parse_pdf(&data).unwrap();
+    assert_eq!(doc.version().as_deref(), Some("1.4"));
+    assert_eq!(doc.page_count(), Some(3));
+    assert!(!doc.is_encrypted());
+
+    let map = extract_pdf_data(&data);
+    assert_eq!(map["PDF:Title"].as_text(), Some("München"));
+    assert_eq!(map["PDF:Author"].as_text(), Some("Jane (J.) Doe"));
+    assert_eq!(map["PDF:Creator"].as_text(), Some("Microsoft® Word"));
+    assert_eq!(
+        map["PDF:CreationDate"].as_text(),
+        Some("2024:01:15 10:30:00+01:00")
+    );
+    assert_eq!(map["PDF:ModDate"].as_text(), Some("2024:01:16 00:00:00"));
+    assert_eq!(map["PDF:Custom Key"].as_text(), Some("Value"));
+    assert_eq!(map["PDF:PageCount"].as_i64(), Some(3));
+    assert_eq!(map["PDF:Encrypted"].as_text(), Some("No"));
+    assert_eq!(
+        map["PDF:Author"].source,
+        Some(MetadataSource::Pdf { object: 3 })
+    );
+}
+
+#[test]
+fn test_xref_stream_and_object_stream() {
+    let data = object_stream_pdf();
+    let doc = parse_pdf(&data).unwrap();
+    assert_eq!(doc.page_count(), Some(12));
+
+    let map = extract_pdf_data(&data);
+    assert_eq!(map["PDF:Author"].as_text(), Some("Object Stream Author"));
+    assert_eq!(map["PDF:Producer"].as_text(), Some("pdfTeX-1.40.25"));
+    assert_eq!(map["PDF:Version"].as_text(), Some("1.5"));
+
+    // The Flate-compressed metadata stream reaches the XMP parser
+    assert_eq!(find_xmp(&data), vec![XMP.to_string()]);
+    assert_eq!(
+        extract_xmp_data(&data)["XMP-pdf:Producer"].as_text(),
+        Some("LibreOffice 7.6")
+    );
+}
+
+#[test]
+fn test_damaged_xref_is_recovered_by_scanning() {
+    let mut data = classic_pdf(INFO, &[]);
+    // Point startxref at garbage and shift every object by inserting bytes
+    let start = data.windows(9).rposition(|w| w == b"startxref").unwrap();
+    data.truncate(start);
+    data.extend_from_slice(b"startxref\n999999\n%%EOF\n");
+    data.splice(9..9, b"% padding comment\n".iter().copied());
+
+    let map = extract_pdf_data(&data);
+    assert_eq!(map["PDF:Author"].as_text(), Some("Jane (J.) Doe"));
+    assert_eq!(map["PDF:PageCount"].as_i64(), Some(3));
+}
+
+#[test]
+fn test_incremental_update_overrides_info() {
+    let mut data = classic_pdf(INFO, &[]);
+    let first_xref = {
+        let start = data.windows(9).rposition(|w| w == b"startxref").unwrap();
+        let text = String::from_utf8_lossy(&data[start + 9..]).into_owned();
+        text.split_whitespace().next().unwrap().to_string()
+    };
+    let offsets = write_objects(&mut data, &[(3, b"<< /Author (Second Editor) >>".to_vec())]);
+    write_xref_table(
+        &mut data,
+        &offsets,
+        &format!("/Root 1 0 R /Info 3 0 R /Prev {}", first_xref),
+    );
+
+    let map = extract_pdf_data(&data);
+    assert_eq!(map["PDF:Author"].as_text(), Some("Second Editor"));
+    assert!(!map.contains_key("PDF:Title"));
+    assert_eq!(map["PDF:PageCount"].as_i64(), Some(3));
+}
+
+#[test]
+fn test_encrypted_pdf_hides_info() {
+    let mut data = b"%PDF-1.6\n".to_vec();
+    let offsets = write_objects(
+        &mut data,
+        &[
+            (1, b"<< /Type /Catalog /Pages 2 0 R >>".to_vec()),
+            (2, b"<< /Type /Pages /Kids [] /Count 1 >>".to_vec()),
+            (3, b"<< /Author <8A3F01> >>".to_vec()),
+            (4, b"<< /Filter /Standard /V 2 /R 3 >>".to_vec()),
+        ],
+    );
+    write_xref_table(
+        &mut data,
+        &offsets,
+        "/Root 1 0 R /Info 3 0 R /Encrypt 4 0 R",
+    );
+
+    let map = extract_pdf_data(&data);
+    assert_eq!(map["PDF:Encrypted"].as_text(), Some("Yes"));
+    assert_eq!(map["PDF:PageCount"].as_i64(), Some(1));
+    assert!(!map.contains_key("PDF:Author"));
+    assert!(find_xmp(&data).is_empty());
+}
+
+#[test]
+fn test_embedded_jpeg_exif_is_listed() {
+    let image = stream(
+        "/Type /XObject /Subtype /Image /Width 1 /Height 1 /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode",
+        &jpeg_with_exif(&tiff_with_gps()),
+    );
+    let data = classic_pdf(INFO, &[(5, image)]);
+
+    let map = extract_pdf_data(&data);
+    assert_eq!(map["PDF:EmbeddedImages"].as_i64(), Some(1));
+    assert_eq!(map["PDF-Image1:Model"].as_text(), Some("Canon EOS R5"));
+    assert!(map.contains_key("PDF-Image1:GPSLatitude"));
+    assert_eq!(
+        map["PDF-Image1:Model"].source,
+        Some(MetadataSource::Pdf { object: 5 })
+    );
+    assert_eq!(get_metadata_category("PDF-Image1:Model"), "📄 PDF Document");
+
+    let (metadata, _) = extract_metadata(&data);
+    let image_data = ImageData {
+        name: "report.pdf".to_string(),
+        size: data.len() as u64,
+        mime_type: "application/pdf".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data: metadata,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+    let risk = image_data.calculate_privacy_risk();
+    for field in [
+        "GPS Location",
+        "Owner/Artist Name",
+        "Software",
+        "Timestamps",
+    ] {
+        assert!(
+            risk.sensitive_fields.iter().any(|f| f == field),
+            "missing {}",
+            field
+        );
+    }
+}
+
+#[test]
+fn test_non_pdf_input() {
+    assert!(extract_pdf_data(b"\xFF\xD8\xFF\xE0").is_empty());
+    assert!(parse_pdf(b"%PDF-1.7\n garbage without objects").is_err());
+}
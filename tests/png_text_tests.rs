@@ -0,0 +1,199 @@
+mod common;
+
+use common::{png, zlib};
+use image_metadata_extractor::binary_cleaner::BinaryCleaner;
+use image_metadata_extractor::container;
+use image_metadata_extractor::exif_core::extract_metadata;
+use image_metadata_extractor::metadata_info::get_metadata_category;
+use image_metadata_extractor::png_text::{extract_png_text_data, find_png_text, parse_time_chunk};
+use image_metadata_extractor::types::{ImageData, MetadataSource};
+
+fn text(keyword: &str, value: &[u8]) -> Vec<u8> {
+    let mut body = keyword.as_bytes().to_vec();
+    body.push(0);
+    body.extend_from_slice(value);
+    body
+}
+
+fn ztxt(keyword: &str, value: &str) -> Vec<u8> {
+    let mut body = keyword.as_bytes().to_vec();
+    body.extend_from_slice(&[0, 0]);
+    body.extend(zlib(value.as_bytes()));
+    body
+}
+
+fn itxt(keyword: &str, language: &str, translated: &str, value: &str, compress: bool) -> Vec<u8> {
+    let mut body = keyword.as_bytes().to_vec();
+    body.extend_from_slice(&[0, compress as u8, 0]);
+    body.extend_from_slice(language.as_bytes());
+    body.push(0);
+    body.extend_from_slice(translated.as_bytes());
+    body.push(0);
+    if compress {
+        body.extend(zlib(value.as_bytes()));
+    } else {
+        body.extend_from_slice(value.as_bytes());
+    }
+    body
+}
+
+const SD_PARAMETERS: &str = "a lighthouse at dusk, oil painting\nNegative prompt: blurry\nSteps: 30, Sampler: DPM++ 2M Karras, CFG scale: 7, Seed: 1234567890, Model: sd_xl_base_1.0";
+const COMFY_WORKFLOW: &str = r#"{"last_node_id":9,"nodes":[{"id":6,"type":"CLIPTextEncode","widgets_values":["a lighthouse at dusk"]}]}"#;
+
+fn generated_png() -> Vec<u8> {
+    png(&[
+        (b"IHDR", &[0; 13]),
+        (b"tEXt", &text("parameters", SD_PARAMETERS.as_bytes())),
+        (b"zTXt", &ztxt("workflow", COMFY_WORKFLOW)),
+        (b"tEXt", &text("Author", b"Ren\xe9e Dupont")),
+        (
+            b"iTXt",
+            &itxt("Comment", "fr", "Commentaire", "Généré localement", true),
+        ),
+        (b"tIME", &[0x07, 0xE8, 3, 15, 9, 30, 5]),
+        (b"IEND", &[]),
+    ])
+}
+
+#[test]
+fn test_find_text_chunks_of_every_kind() {
+    let texts = find_png_text(&generated_png());
+    assert_eq!(texts.len(), 4);
+
+    assert_eq!(texts[0].chunk, "tEXt");
+    assert_eq!(texts[0].keyword, "parameters");
+    assert_eq!(texts[0].text, SD_PARAMETERS);
+    assert!(!texts[0].compressed);
+
+    assert_eq!(texts[1].chunk, "zTXt");
+    assert_eq!(texts[1].text, COMFY_WORKFLOW);
+    assert!(texts[1].compressed);
+
+    // tEXt is Latin-1
+    assert_eq!(texts[2].text, "Renée Dupont");
+
+    assert_eq!(texts[3].chunk, "iTXt");
+    assert_eq!(texts[3].language.as_deref(), Some("fr"));
+    assert_eq!(texts[3].translated_keyword.as_deref(), Some("Commentaire"));
+    assert_eq!(texts[3].text, "Généré localement");
+}
+
+#[test]
+fn test_extract_png_text_keys() {
+    let map = extract_png_text_data(&generated_png());
+    assert_eq!(map["PNG:parameters"], SD_PARAMETERS);
+    assert_eq!(map["PNG:workflow"], COMFY_WORKFLOW);
+    assert_eq!(map["PNG:Author"], "Renée Dupont");
+    assert_eq!(map["PNG:Comment"], "Généré localement");
+    assert_eq!(map["PNG:ModifyDate"], "2024:03:15 09:30:05");
+    assert_eq!(
+        map["PNG:workflow"].source,
+        Some(MetadataSource::Png {
+            chunk: "zTXt".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_repeated_keywords_are_joined() {
+    let data = png(&[
+        (b"IHDR", &[0; 13]),
+        (b"tEXt", &text("Comment", b"first")),
+        (b"tEXt", &text("Comment", b"second")),
+        (b"IEND", &[]),
+    ]);
+    assert_eq!(
+        extract_png_text_data(&data)["PNG:Comment"],
+        "first\n\nsecond"
+    );
+}
+
+#[test]
+fn test_xmp_and_malformed_chunks_are_skipped() {
+    let data = png(&[
+        (b"IHDR", &[0; 13]),
+        (
+            b"iTXt",
+            &itxt("XML:com.adobe.xmp", "", "", "<x:xmpmeta/>", false),
+        ),
+        (b"zTXt", &text("Broken", b"\0not zlib")),
+        (b"tEXt", b"no separator"),
+        (b"tIME", &[0x07, 0xE8]),
+        (b"IEND", &[]),
+    ]);
+    assert!(extract_png_text_data(&data).is_empty());
+    assert_eq!(parse_time_chunk(&[0x07, 0xE8, 1, 2, 3, 4]), None);
+}
+
+#[test]
+fn test_exif_chunk_and_text_are_merged() {
+    // Minimal big-endian TIFF with one Software entry
+    let mut tiff = b"MM\0*\0\0\0\x08\0\x01".to_vec();
+    tiff.extend_from_slice(&[0x01, 0x31, 0x00, 0x02, 0, 0, 0, 4]);
+    tiff.extend_from_slice(b"GIMP");
+    tiff.extend_from_slice(&[0, 0, 0, 0]);
+    let data = png(&[
+        (b"IHDR", &[0; 13]),
+        (b"eXIf", &tiff),
+        (b"tEXt", &text("Software", b"ComfyUI")),
+        (b"IEND", &[]),
+    ]);
+    let (map, _) = extract_metadata(&data);
+    assert_eq!(map["Software"].as_text(), Some("GIMP"));
+    assert_eq!(map["PNG:Software"], "ComfyUI");
+}
+
+#[test]
+fn test_png_text_categories() {
+    assert_eq!(get_metadata_category("PNG:parameters"), "🤖 AI Generation");
+    assert_eq!(get_metadata_category("PNG:Creation Time"), "🕒 Date & Time");
+    assert_eq!(get_metadata_category("PNG:Author"), "🖼️ PNG Text");
+    assert_eq!(get_metadata_category("PNG:chara"), "🖼️ PNG Text");
+}
+
+#[test]
+fn test_ai_parameters_and_author_feed_privacy_score() {
+    let data = generated_png();
+    let image = ImageData {
+        name: "ComfyUI_00001_.png".to_string(),
+        size: data.len() as u64,
+        mime_type: "image/png".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data: extract_png_text_data(&data),
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+    let risk = image.calculate_privacy_risk();
+    for field in [
+        "AI Generation Parameters",
+        "Owner/Artist Name",
+        "Timestamps",
+    ] {
+        assert!(
+            risk.sensitive_fields.contains(&field.to_string()),
+            "{field}"
+        );
+    }
+    assert_eq!(risk.score, 15 + 25 + 15);
+}
+
+#[test]
+fn test_cleaner_removes_text_and_exif_chunks() {
+    let mut data = generated_png();
+    data.truncate(data.len() - 12);
+    data.extend_from_slice(&[0, 0, 0, 4]);
+    data.extend_from_slice(b"eXIf");
+    data.extend_from_slice(b"MM\0*");
+    data.extend_from_slice(&[0; 4]);
+    data.extend_from_slice(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0, 0, 0, 0]);
+
+    let cleaned = BinaryCleaner::clean_metadata(&data, "png").unwrap();
+    let names: Vec<String> = container::png_chunks(&cleaned)
+        .iter()
+        .map(|c| c.name())
+        .collect();
+    assert_eq!(names, ["IHDR", "IEND"]);
+}
@@ -165,6 +165,8 @@ fn test_export_profile_journalism_preset() {
         assert!(profile.selected_fields.contains("DateTime"));
         assert!(profile.selected_fields.contains("Make"));
         assert!(profile.selected_fields.contains("Model"));
+        assert!(profile.selected_fields.contains("IPTC:Caption-Abstract"));
+        assert!(profile.selected_fields.contains("IPTC:By-line"));
         assert!(profile.include_basic_info);
         assert!(profile.include_gps);
     }
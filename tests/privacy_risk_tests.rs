@@ -1,6 +1,8 @@
 //! Tests for privacy risk calculation and assessment
 
-use image_metadata_extractor::types::{ImageData, PrivacyRiskLevel};
+use image_metadata_extractor::types::{
+    GpsInfo, ImageData, MetadataValue, PrivacyRiskLevel, RawValue,
+};
 use std::collections::HashMap;
 
 #[test]
@@ -13,8 +15,9 @@ fn test_privacy_risk_no_sensitive_data() {
         width: Some(800),
         height: Some(600),
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -35,8 +38,9 @@ fn test_privacy_risk_gps_critical() {
         width: Some(800),
         height: Some(600),
         exif_data: HashMap::new(),
-        gps_coords: Some((40.7128, -74.0060)), // NYC coordinates
+        gps: Some(GpsInfo::new(40.7128, -74.0060)), // NYC coordinates
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -51,7 +55,7 @@ fn test_privacy_risk_gps_critical() {
 #[test]
 fn test_privacy_risk_camera_serial_number() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("BodySerialNumber".to_string(), "12345678".to_string());
+    exif_data.insert("BodySerialNumber".to_string(), "12345678".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -61,8 +65,9 @@ fn test_privacy_risk_camera_serial_number() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -78,7 +83,7 @@ fn test_privacy_risk_camera_serial_number() {
 #[test]
 fn test_privacy_risk_owner_name() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("Artist".to_string(), "John Doe".to_string());
+    exif_data.insert("Artist".to_string(), "John Doe".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -88,8 +93,9 @@ fn test_privacy_risk_owner_name() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -101,7 +107,7 @@ fn test_privacy_risk_owner_name() {
 #[test]
 fn test_privacy_risk_copyright() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("Copyright".to_string(), "© 2024 John Doe".to_string());
+    exif_data.insert("Copyright".to_string(), "© 2024 John Doe".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -111,8 +117,9 @@ fn test_privacy_risk_copyright() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -126,7 +133,7 @@ fn test_privacy_risk_copyright() {
 #[test]
 fn test_privacy_risk_software() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("Software".to_string(), "Adobe Photoshop".to_string());
+    exif_data.insert("Software".to_string(), "Adobe Photoshop".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -136,8 +143,9 @@ fn test_privacy_risk_software() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -148,7 +156,7 @@ fn test_privacy_risk_software() {
 #[test]
 fn test_privacy_risk_timestamps() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("DateTime".to_string(), "2024:01:01 12:00:00".to_string());
+    exif_data.insert("DateTime".to_string(), "2024:01:01 12:00:00".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -158,8 +166,9 @@ fn test_privacy_risk_timestamps() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -170,8 +179,8 @@ fn test_privacy_risk_timestamps() {
 #[test]
 fn test_privacy_risk_camera_make_model() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("Make".to_string(), "Canon".to_string());
-    exif_data.insert("Model".to_string(), "EOS 5D Mark IV".to_string());
+    exif_data.insert("Make".to_string(), "Canon".into());
+    exif_data.insert("Model".to_string(), "EOS 5D Mark IV".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -181,8 +190,9 @@ fn test_privacy_risk_camera_make_model() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -193,7 +203,7 @@ fn test_privacy_risk_camera_make_model() {
 #[test]
 fn test_privacy_risk_lens_info() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("LensModel".to_string(), "EF 24-70mm f/2.8L".to_string());
+    exif_data.insert("LensModel".to_string(), "EF 24-70mm f/2.8L".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -203,8 +213,9 @@ fn test_privacy_risk_lens_info() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -215,8 +226,8 @@ fn test_privacy_risk_lens_info() {
 #[test]
 fn test_privacy_risk_critical_combined() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("Artist".to_string(), "John Doe".to_string());
-    exif_data.insert("BodySerialNumber".to_string(), "12345678".to_string());
+    exif_data.insert("Artist".to_string(), "John Doe".into());
+    exif_data.insert("BodySerialNumber".to_string(), "12345678".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -226,8 +237,9 @@ fn test_privacy_risk_critical_combined() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: Some((40.7128, -74.0060)),
+        gps: Some(GpsInfo::new(40.7128, -74.0060)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -239,13 +251,13 @@ fn test_privacy_risk_critical_combined() {
 #[test]
 fn test_privacy_risk_all_fields() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("Artist".to_string(), "John Doe".to_string());
-    exif_data.insert("BodySerialNumber".to_string(), "12345678".to_string());
-    exif_data.insert("Software".to_string(), "Lightroom".to_string());
-    exif_data.insert("DateTime".to_string(), "2024:01:01 12:00:00".to_string());
-    exif_data.insert("Make".to_string(), "Canon".to_string());
-    exif_data.insert("Model".to_string(), "EOS R5".to_string());
-    exif_data.insert("LensModel".to_string(), "RF 24-70mm".to_string());
+    exif_data.insert("Artist".to_string(), "John Doe".into());
+    exif_data.insert("BodySerialNumber".to_string(), "12345678".into());
+    exif_data.insert("Software".to_string(), "Lightroom".into());
+    exif_data.insert("DateTime".to_string(), "2024:01:01 12:00:00".into());
+    exif_data.insert("Make".to_string(), "Canon".into());
+    exif_data.insert("Model".to_string(), "EOS R5".into());
+    exif_data.insert("LensModel".to_string(), "RF 24-70mm".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -255,8 +267,9 @@ fn test_privacy_risk_all_fields() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: Some((40.7128, -74.0060)),
+        gps: Some(GpsInfo::new(40.7128, -74.0060)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -279,8 +292,9 @@ fn test_consistency_gps_missing_ref() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: Some((40.7128, -74.0060)),
+        gps: Some(GpsInfo::new(40.7128, -74.0060)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -291,11 +305,8 @@ fn test_consistency_gps_missing_ref() {
 #[test]
 fn test_consistency_timestamp_mismatch() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("DateTime".to_string(), "2024:01:01 12:00:00".to_string());
-    exif_data.insert(
-        "DateTimeOriginal".to_string(),
-        "2024:01:01 10:00:00".to_string(),
-    );
+    exif_data.insert("DateTime".to_string(), "2024:01:01 12:00:00".into());
+    exif_data.insert("DateTimeOriginal".to_string(), "2024:01:01 10:00:00".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -305,8 +316,9 @@ fn test_consistency_timestamp_mismatch() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -321,7 +333,7 @@ fn test_consistency_timestamp_mismatch() {
 #[test]
 fn test_consistency_orientation_without_dimensions() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("Orientation".to_string(), "1".to_string());
+    exif_data.insert("Orientation".to_string(), "1".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -331,8 +343,9 @@ fn test_consistency_orientation_without_dimensions() {
         width: None,
         height: None,
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -347,10 +360,10 @@ fn test_consistency_orientation_without_dimensions() {
 #[test]
 fn test_consistency_dimension_mismatch() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("Make".to_string(), "Canon".to_string());
-    exif_data.insert("LensModel".to_string(), "RF 24-70mm".to_string());
-    exif_data.insert("PixelXDimension".to_string(), "4000".to_string());
-    exif_data.insert("ExifImageWidth".to_string(), "3000".to_string());
+    exif_data.insert("Make".to_string(), "Canon".into());
+    exif_data.insert("LensModel".to_string(), "RF 24-70mm".into());
+    exif_data.insert("PixelXDimension".to_string(), "4000".into());
+    exif_data.insert("ExifImageWidth".to_string(), "3000".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -360,8 +373,9 @@ fn test_consistency_dimension_mismatch() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -373,10 +387,111 @@ fn test_consistency_dimension_mismatch() {
     );
 }
 
+#[test]
+fn test_consistency_dimension_compared_numerically() {
+    let mut exif_data = HashMap::new();
+    exif_data.insert("Make".to_string(), "Canon".into());
+    exif_data.insert("LensModel".to_string(), "RF 24-70mm".into());
+    exif_data.insert(
+        "PixelXDimension".to_string(),
+        MetadataValue::new(RawValue::Integer(vec![4000]), "4000 pixels"),
+    );
+    exif_data.insert("ExifImageWidth".to_string(), "4000".into());
+
+    let data = ImageData {
+        name: "test.jpg".to_string(),
+        size: 1024,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: Some(800),
+        height: Some(600),
+        exif_data,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+
+    // Display strings differ but the numbers match, so no issue is reported
+    let risk = data.calculate_privacy_risk();
+    assert!(
+        !risk
+            .consistency_issues
+            .iter()
+            .any(|i| i.contains("dimension"))
+    );
+}
+
+#[test]
+fn test_consistency_thumbnail_datetime_differs() {
+    let mut exif_data = HashMap::new();
+    exif_data.insert("DateTime".to_string(), "2024:05:01 10:00:00".into());
+    exif_data.insert(
+        "Thumbnail:DateTime".to_string(),
+        "2019:01:01 08:00:00".into(),
+    );
+
+    let data = ImageData {
+        name: "test.jpg".to_string(),
+        size: 1024,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: Some(800),
+        height: Some(600),
+        exif_data,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+
+    let risk = data.calculate_privacy_risk();
+    assert!(
+        risk.consistency_issues
+            .iter()
+            .any(|i| i.contains("Thumbnail DateTime differs"))
+    );
+    // The thumbnail timestamp does not count as a second timestamp warning
+    assert_eq!(risk.score, 15);
+}
+
+#[test]
+fn test_privacy_risk_xmp_only_fields() {
+    let mut exif_data = HashMap::new();
+    exif_data.insert("XMP-dc:creator".to_string(), "Jane Doe".into());
+    exif_data.insert("XMP-aux:SerialNumber".to_string(), "0012345".into());
+    exif_data.insert(
+        "XMP-photoshop:DateCreated".to_string(),
+        "2023-06-01T12:34:56".into(),
+    );
+    exif_data.insert("XMP-xmpMM:History[1]/action".to_string(), "saved".into());
+
+    let data = ImageData {
+        name: "test.jpg".to_string(),
+        size: 1024,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: Some(800),
+        height: Some(600),
+        exif_data,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+
+    let risk = data.calculate_privacy_risk();
+    // Owner 25 + serial 25 + timestamps 15 + edit history 10
+    assert_eq!(risk.score, 75);
+    assert_eq!(risk.level, PrivacyRiskLevel::Critical);
+    assert!(
+        risk.sensitive_fields
+            .contains(&"Owner/Artist Name".to_string())
+    );
+    assert!(risk.sensitive_fields.contains(&"Edit History".to_string()));
+}
+
 #[test]
 fn test_consistency_software_without_timestamp() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("Software".to_string(), "Photoshop".to_string());
+    exif_data.insert("Software".to_string(), "Photoshop".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -386,8 +501,9 @@ fn test_consistency_software_without_timestamp() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -402,11 +518,8 @@ fn test_consistency_software_without_timestamp() {
 #[test]
 fn test_consistency_timestamp_match_no_issue() {
     let mut exif_data = HashMap::new();
-    exif_data.insert("DateTime".to_string(), "2024:01:01 12:00:00".to_string());
-    exif_data.insert(
-        "DateTimeOriginal".to_string(),
-        "2024:01:01 12:00:00".to_string(),
-    );
+    exif_data.insert("DateTime".to_string(), "2024:01:01 12:00:00".into());
+    exif_data.insert("DateTimeOriginal".to_string(), "2024:01:01 12:00:00".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -416,8 +529,9 @@ fn test_consistency_timestamp_match_no_issue() {
         width: Some(800),
         height: Some(600),
         exif_data,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let risk = data.calculate_privacy_risk();
@@ -441,8 +555,9 @@ fn test_risk_level_boundaries() {
         width: Some(800),
         height: Some(600),
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
     assert_eq!(
         data_low.calculate_privacy_risk().level,
@@ -451,9 +566,9 @@ fn test_risk_level_boundaries() {
 
     // Test Medium (20-39)
     let mut exif_medium = HashMap::new();
-    exif_medium.insert("DateTime".to_string(), "2024:01:01 12:00:00".to_string());
-    exif_medium.insert("Make".to_string(), "Canon".to_string());
-    exif_medium.insert("Model".to_string(), "EOS".to_string());
+    exif_medium.insert("DateTime".to_string(), "2024:01:01 12:00:00".into());
+    exif_medium.insert("Make".to_string(), "Canon".into());
+    exif_medium.insert("Model".to_string(), "EOS".into());
     let data_medium = ImageData {
         name: "test.jpg".to_string(),
         size: 1024,
@@ -462,8 +577,9 @@ fn test_risk_level_boundaries() {
         width: Some(800),
         height: Some(600),
         exif_data: exif_medium,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
     assert_eq!(
         data_medium.calculate_privacy_risk().level,
@@ -479,8 +595,9 @@ fn test_risk_level_boundaries() {
         width: Some(800),
         height: Some(600),
         exif_data: HashMap::new(),
-        gps_coords: Some((40.0, -74.0)),
+        gps: Some(GpsInfo::new(40.0, -74.0)),
         sha256_hash: None,
+        ..Default::default()
     };
     assert_eq!(
         data_high.calculate_privacy_risk().level,
@@ -489,8 +606,8 @@ fn test_risk_level_boundaries() {
 
     // Test Critical (60+)
     let mut exif_critical = HashMap::new();
-    exif_critical.insert("Artist".to_string(), "John".to_string());
-    exif_critical.insert("BodySerialNumber".to_string(), "123".to_string());
+    exif_critical.insert("Artist".to_string(), "John".into());
+    exif_critical.insert("BodySerialNumber".to_string(), "123".into());
     let data_critical = ImageData {
         name: "test.jpg".to_string(),
         size: 1024,
@@ -499,8 +616,9 @@ fn test_risk_level_boundaries() {
         width: Some(800),
         height: Some(600),
         exif_data: exif_critical,
-        gps_coords: Some((40.0, -74.0)),
+        gps: Some(GpsInfo::new(40.0, -74.0)),
         sha256_hash: None,
+        ..Default::default()
     };
     assert_eq!(
         data_critical.calculate_privacy_risk().level,
@@ -0,0 +1,322 @@
+mod common;
+
+use common::{Entry, ascii, ifd};
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, Rgb, RgbImage};
+use image_metadata_extractor::archive::{get_mime_type, is_image_file};
+use image_metadata_extractor::exif_core::{
+    determine_mime_type, extract_exif_data, extract_metadata, is_supported_mime_type,
+};
+use image_metadata_extractor::metadata_info::get_metadata_category;
+use image_metadata_extractor::raw::{
+    RawFormat, detect_format, dimensions, parse_raw, preview_jpeg, raw_mime_type,
+};
+use image_metadata_extractor::types::ImageData;
+
+fn short(tag: u16, value: u16) -> Entry {
+    (tag, 3, 1, value.to_le_bytes().to_vec())
+}
+
+fn long(tag: u16, values: &[u32]) -> Entry {
+    let bytes = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    (tag, 4, values.len() as u32, bytes)
+}
+
+fn undefined(tag: u16, bytes: &[u8]) -> Entry {
+    (tag, 7, bytes.len() as u32, bytes.to_vec())
+}
+
+fn preview() -> Vec<u8> {
+    let image = DynamicImage::ImageRgb8(RgbImage::from_fn(32, 24, |x, y| {
+        Rgb([(x * 8) as u8, (y * 10) as u8, 128])
+    }));
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, 80)
+        .encode_image(&image)
+        .unwrap();
+    if out.len() % 2 == 1 {
+        out.push(0);
+    }
+    out
+}
+
+/// Little-endian TIFF header with `magic` (IFD0 offset still unset), followed
+/// by `extra_header` and `jpeg`. Returns the file and the JPEG's offset.
+fn tiff_file(magic: &[u8; 4], extra_header: &[u8], jpeg: &[u8]) -> (Vec<u8>, usize) {
+    let mut file = magic.to_vec();
+    file.extend_from_slice(&0u32.to_le_bytes());
+    file.extend_from_slice(extra_header);
+    let jpeg_at = file.len();
+    file.extend_from_slice(jpeg);
+    (file, jpeg_at)
+}
+
+fn set_first_ifd(file: &mut [u8], offset: usize) {
+    file[4..8].copy_from_slice(&(offset as u32).to_le_bytes());
+}
+
+/// DNG with a thumbnail in IFD0, 14-bit CFA data in SubIFD1 and a JPEG
+/// preview in SubIFD2.
+fn dng() -> (Vec<u8>, Vec<u8>) {
+    let jpeg = preview();
+    let (mut file, jpeg_at) = tiff_file(b"II*\0", &[], &jpeg);
+
+    let sub1_at = file.len();
+    file.extend(ifd(
+        &[
+            long(0x00FE, &[0]),
+            long(0x0100, &[8192]),
+            long(0x0101, &[5464]),
+            short(0x0102, 14),
+            short(0x0103, 7),
+            short(0x0106, 32803),
+        ],
+        sub1_at,
+        0,
+    ));
+    let sub2_at = file.len();
+    file.extend(ifd(
+        &[
+            long(0x00FE, &[1]),
+            long(0x0100, &[32]),
+            long(0x0101, &[24]),
+            short(0x0103, 7),
+            short(0x0106, 6),
+            long(0x0111, &[jpeg_at as u32]),
+            long(0x0117, &[jpeg.len() as u32]),
+        ],
+        sub2_at,
+        0,
+    ));
+    let ifd0_at = file.len();
+    file.extend(ifd(
+        &[
+            long(0x00FE, &[1]),
+            long(0x0100, &[256]),
+            long(0x0101, &[171]),
+            short(0x0103, 1),
+            short(0x0106, 2),
+            ascii(0x010F, "Canon"),
+            ascii(0x0110, "Canon EOS R5"),
+            long(0x014A, &[sub1_at as u32, sub2_at as u32]),
+            (0xC612, 1, 4, vec![1, 4, 0, 0]),
+            ascii(0xC614, "Canon EOS R5"),
+            ascii(0xC62F, "012345678"),
+            ascii(0xC68B, "IMG_0042.CR3"),
+        ],
+        ifd0_at,
+        0,
+    ));
+    set_first_ifd(&mut file, ifd0_at);
+    (file, jpeg)
+}
+
+#[test]
+fn test_dng_detection_and_layout() {
+    let (file, jpeg) = dng();
+    assert_eq!(detect_format(&file), Some(RawFormat::Dng));
+    assert_eq!(
+        determine_mime_type("IMG_0042.dng", "", &file),
+        "image/x-adobe-dng"
+    );
+    // Browsers often label DNG as TIFF
+    assert_eq!(
+        determine_mime_type("IMG_0042.dng", "image/tiff", &file),
+        "image/x-adobe-dng"
+    );
+    assert!(is_supported_mime_type("image/x-adobe-dng"));
+
+    let raw = parse_raw(&file).unwrap();
+    let names: Vec<&str> = raw.images.iter().map(|i| i.ifd.as_str()).collect();
+    assert_eq!(names, ["IFD0", "SubIFD1", "SubIFD2"]);
+    assert_eq!(dimensions(&file), Some((8192, 5464)));
+    assert_eq!(preview_jpeg(&file), Some(&jpeg[..]));
+}
+
+#[test]
+fn test_dng_metadata() {
+    let (file, _) = dng();
+    let (metadata, _) = extract_metadata(&file);
+    let text = |key: &str| metadata.get(key).map(|v| v.display.clone());
+
+    assert_eq!(text("Make").as_deref(), Some("\"Canon\""));
+    assert_eq!(text("RAW:Format").as_deref(), Some("Adobe DNG"));
+    assert_eq!(text("RAW:ImageCount").as_deref(), Some("3"));
+    assert_eq!(
+        text("RAW:RawImage").as_deref(),
+        Some("8192x5464, 14-bit, Color Filter Array, JPEG (SubIFD1)")
+    );
+    assert!(text("RAW:Preview").unwrap().starts_with("32x24 JPEG"));
+    assert_eq!(text("DNG:DNGVersion").as_deref(), Some("1.4.0.0"));
+    assert_eq!(
+        text("DNG:UniqueCameraModel").as_deref(),
+        Some("Canon EOS R5")
+    );
+    assert_eq!(text("DNG:CameraSerialNumber").as_deref(), Some("012345678"));
+    assert_eq!(
+        text("DNG:OriginalRawFileName").as_deref(),
+        Some("IMG_0042.CR3")
+    );
+    assert_eq!(text("SubIFD1:BitsPerSample").as_deref(), Some("14"));
+    assert_eq!(text("SubIFD2:NewSubfileType").as_deref(), Some("1"));
+    // Strip locations are layout, not metadata
+    assert!(!metadata.contains_key("SubIFD2:StripOffsets"));
+
+    assert_eq!(
+        get_metadata_category("SubIFD1:BitsPerSample"),
+        "🖼️ RAW File"
+    );
+    assert_eq!(get_metadata_category("RAW:Format"), "🖼️ RAW File");
+    assert_eq!(get_metadata_category("DNG:CameraSerialNumber"), "📷 Camera");
+
+    let image = ImageData {
+        name: "IMG_0042.dng".to_string(),
+        size: file.len() as u64,
+        mime_type: "image/x-adobe-dng".to_string(),
+        data_url: String::new(),
+        width: Some(8192),
+        height: Some(5464),
+        exif_data: metadata,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+    assert!(
+        image
+            .calculate_privacy_risk()
+            .sensitive_fields
+            .iter()
+            .any(|f| f == "Camera Serial Number")
+    );
+}
+
+#[test]
+fn test_cr2_preview_in_ifd0() {
+    let jpeg = preview();
+    let (mut file, jpeg_at) = tiff_file(b"II*\0", b"CR\x02\0\0\0\0\0", &jpeg);
+    let ifd0_at = file.len();
+    file.extend(ifd(
+        &[
+            long(0x0100, &[32]),
+            long(0x0101, &[24]),
+            short(0x0103, 6),
+            ascii(0x010F, "Canon"),
+            long(0x0111, &[jpeg_at as u32]),
+            long(0x0117, &[jpeg.len() as u32]),
+        ],
+        ifd0_at,
+        0,
+    ));
+    set_first_ifd(&mut file, ifd0_at);
+
+    assert_eq!(detect_format(&file), Some(RawFormat::Cr2));
+    assert_eq!(
+        determine_mime_type("IMG_1.CR2", "", &file),
+        "image/x-canon-cr2"
+    );
+    assert_eq!(dimensions(&file), Some((32, 24)));
+    assert_eq!(preview_jpeg(&file), Some(&jpeg[..]));
+}
+
+#[test]
+fn test_orf_and_rw2_magic_numbers() {
+    let (mut orf, _) = tiff_file(b"IIRO", &[], &[]);
+    let ifd0_at = orf.len();
+    orf.extend(ifd(
+        &[
+            long(0x0100, &[5240]),
+            long(0x0101, &[3912]),
+            short(0x0102, 12),
+            ascii(0x010F, "OLYMPUS IMAGING CORP."),
+        ],
+        ifd0_at,
+        0,
+    ));
+    set_first_ifd(&mut orf, ifd0_at);
+    assert_eq!(detect_format(&orf), Some(RawFormat::Orf));
+    assert_eq!(dimensions(&orf), Some((5240, 3912)));
+    // The EXIF reader accepts the file once the magic number is normalised
+    let (exif, _) = extract_exif_data(&orf);
+    assert_eq!(exif["Make"].display, "\"OLYMPUS IMAGING CORP.\"");
+
+    let jpeg = preview();
+    let (mut rw2, _) = tiff_file(b"IIU\0", &[], &[]);
+    let ifd0_at = rw2.len();
+    rw2.extend(ifd(
+        &[
+            short(0x0002, 5248),
+            short(0x0003, 3920),
+            short(0x0004, 4),
+            short(0x0005, 8),
+            short(0x0006, 3884),
+            short(0x0007, 5192),
+            undefined(0x002E, &jpeg),
+        ],
+        ifd0_at,
+        0,
+    ));
+    set_first_ifd(&mut rw2, ifd0_at);
+    assert_eq!(detect_format(&rw2), Some(RawFormat::Rw2));
+    assert_eq!(dimensions(&rw2), Some((5184, 3880)));
+    assert_eq!(preview_jpeg(&rw2), Some(&jpeg[..]));
+    assert_eq!(
+        determine_mime_type("P100.RW2", "", &rw2),
+        "image/x-panasonic-rw2"
+    );
+}
+
+#[test]
+fn test_nef_needs_sensor_data_or_extension() {
+    let build = |photometric: u16| {
+        let (mut file, _) = tiff_file(b"II*\0", &[], &[]);
+        let sub_at = file.len();
+        file.extend(ifd(
+            &[
+                long(0x0100, &[6048]),
+                long(0x0101, &[4024]),
+                short(0x0103, 34713),
+                short(0x0106, photometric),
+            ],
+            sub_at,
+            0,
+        ));
+        let ifd0_at = file.len();
+        file.extend(ifd(
+            &[
+                ascii(0x010F, "NIKON CORPORATION"),
+                long(0x014A, &[sub_at as u32]),
+            ],
+            ifd0_at,
+            0,
+        ));
+        set_first_ifd(&mut file, ifd0_at);
+        file
+    };
+
+    let nef = build(32803);
+    assert_eq!(detect_format(&nef), Some(RawFormat::Nef));
+    assert_eq!(dimensions(&nef), Some((6048, 4024)));
+
+    // A Nikon scan is a TIFF, not a NEF, unless its name says otherwise
+    let (mut scan, _) = tiff_file(b"II*\0", &[], &[]);
+    let ifd0_at = scan.len();
+    scan.extend(ifd(&[ascii(0x010F, "Nikon")], ifd0_at, 0));
+    set_first_ifd(&mut scan, ifd0_at);
+    assert_eq!(detect_format(&scan), None);
+    assert_eq!(raw_mime_type("scan.tif", &scan), None);
+    assert_eq!(
+        raw_mime_type("DSC_0001.NEF", &scan),
+        Some("image/x-nikon-nef")
+    );
+    assert_eq!(raw_mime_type("DSC_0001.NEF", b"not a tiff"), None);
+}
+
+#[test]
+fn test_archive_recognises_raw_extensions() {
+    for name in ["a.DNG", "b.cr2", "c.nef", "d.arw", "e.orf", "f.rw2"] {
+        assert!(is_image_file(name), "{name}");
+    }
+    assert_eq!(get_mime_type("shot.arw"), "image/x-sony-arw");
+    assert_eq!(RawFormat::from_extension("x.NRW"), Some(RawFormat::Nef));
+    assert_eq!(RawFormat::from_extension("x.raw"), None);
+}
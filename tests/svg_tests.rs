@@ -0,0 +1,143 @@
+use image_metadata_extractor::exif_core::extract_metadata;
+use image_metadata_extractor::metadata_info::get_metadata_category;
+use image_metadata_extractor::svg::{extract_svg_data, is_absolute_path, is_svg};
+use image_metadata_extractor::types::{ImageData, MetadataSource};
+
+const INKSCAPE_SVG: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<!-- Created with Inkscape (http://www.inkscape.org/) -->
+<svg
+   width="210mm"
+   height="297mm"
+   viewBox="0 0 210 297"
+   version="1.1"
+   id="svg5"
+   inkscape:version="1.3.2 (091e20e, 2023-11-25)"
+   sodipodi:docname="logo-final.svg"
+   inkscape:export-filename="/home/jdoe/Projects/client/logo.png"
+   inkscape:export-xdpi="96"
+   xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape"
+   xmlns:sodipodi="http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd"
+   xmlns="http://www.w3.org/2000/svg"
+   xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+   xmlns:cc="http://creativecommons.org/ns#"
+   xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <title id="title1">Company Logo</title>
+  <desc>Final logo for the spring campaign</desc>
+  <sodipodi:namedview id="namedview7" inkscape:current-layer="layer1" />
+  <metadata>
+    <rdf:RDF>
+      <cc:Work rdf:about="">
+        <dc:title>Logo (RDF)</dc:title>
+        <dc:creator><cc:Agent><dc:title>Jane Doe</dc:title></cc:Agent></dc:creator>
+        <cc:license rdf:resource="http://creativecommons.org/licenses/by/4.0/" />
+      </cc:Work>
+    </rdf:RDF>
+  </metadata>
+  <g inkscape:label="Layer 1" inkscape:groupmode="layer" id="layer1">
+    <image sodipodi:absref="C:\Users\jdoe\Pictures\photo.jpg" href="photo.jpg" width="10" height="10" />
+    <a href="/about/">link</a>
+  </g>
+</svg>"#;
+
+const ILLUSTRATOR_SVG: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<!-- Generator: Adobe Illustrator 27.0.0, SVG Export Plug-In . SVG Version: 6.00 Build 0)  -->
+<svg version="1.1" xmlns="http://www.w3.org/2000/svg" xmlns:x="http://ns.adobe.com/Extensibility/1.0/" xmlns:i="http://ns.adobe.com/AdobeIllustrator/10.0/" viewBox="0 0 640 480">
+  <rect width="640" height="480" />
+</svg>"#;
+
+#[test]
+fn test_inkscape_document_fields() {
+    let map = extract_svg_data(INKSCAPE_SVG.as_bytes());
+    assert_eq!(map["SVG:Title"].as_text(), Some("Company Logo"));
+    assert_eq!(
+        map["SVG:Description"].as_text(),
+        Some("Final logo for the spring campaign")
+    );
+    assert_eq!(map["SVG:Creator"].as_text(), Some("Jane Doe"));
+    assert_eq!(
+        map["SVG:License"].as_text(),
+        Some("http://creativecommons.org/licenses/by/4.0/")
+    );
+    assert_eq!(map["SVG:DocumentName"].as_text(), Some("logo-final.svg"));
+    assert_eq!(
+        map["SVG:InkscapeVersion"].as_text(),
+        Some("1.3.2 (091e20e, 2023-11-25)")
+    );
+    assert_eq!(map["SVG:CurrentLayer"].as_text(), Some("layer1"));
+    assert_eq!(map["SVG:Editors"].as_text(), Some("Inkscape, Sodipodi"));
+    assert_eq!(map["SVG:Width"].as_text(), Some("210mm"));
+    assert_eq!(
+        map["SVG:DocumentName"].source,
+        Some(MetadataSource::Svg {
+            node: "sodipodi:docname".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_absolute_paths_are_collected() {
+    let map = extract_svg_data(INKSCAPE_SVG.as_bytes());
+    assert_eq!(
+        map["SVG:AbsolutePaths"].as_text(),
+        Some("/home/jdoe/Projects/client/logo.png; C:\\Users\\jdoe\\Pictures\\photo.jpg")
+    );
+
+    assert!(is_absolute_path("/Users/jane/Desktop/a.svg"));
+    assert!(is_absolute_path("file:///tmp/export.png"));
+    assert!(is_absolute_path("D:/Work/file.png"));
+    assert!(is_absolute_path("\\\\server\\share\\img.png"));
+    assert!(!is_absolute_path("/about/"));
+    assert!(!is_absolute_path("photo.jpg"));
+    assert!(!is_absolute_path("M 10 10 L 20 20"));
+}
+
+#[test]
+fn test_illustrator_generator_and_editors() {
+    let map = extract_svg_data(ILLUSTRATOR_SVG.as_bytes());
+    assert_eq!(
+        map["SVG:Generator"].as_text(),
+        Some("Adobe Illustrator 27.0.0, SVG Export Plug-In . SVG Version: 6.00 Build 0)")
+    );
+    assert_eq!(map["SVG:Editors"].as_text(), Some("Adobe Illustrator"));
+    assert!(!map.contains_key("SVG:AbsolutePaths"));
+}
+
+#[test]
+fn test_non_svg_input() {
+    assert!(!is_svg(b"\xFF\xD8\xFF\xE0"));
+    assert!(extract_svg_data(b"<html><body>no svg</body></html>").is_empty());
+    assert!(extract_svg_data(b"\xFF\xD8\xFF\xE0").is_empty());
+}
+
+#[test]
+fn test_svg_in_metadata_pipeline() {
+    let (metadata, _) = extract_metadata(INKSCAPE_SVG.as_bytes());
+    // The RDF block also reaches the XMP parser
+    assert!(metadata.contains_key("XMP-cc:license"));
+    assert!(metadata.contains_key("SVG:ExportFilename"));
+    assert_eq!(
+        get_metadata_category("SVG:ExportFilename"),
+        "🖼️ SVG Document"
+    );
+
+    let image_data = ImageData {
+        name: "logo.svg".to_string(),
+        size: INKSCAPE_SVG.len() as u64,
+        mime_type: "image/svg+xml".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data: metadata,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+    let risk = image_data.calculate_privacy_risk();
+    assert!(risk.sensitive_fields.iter().any(|f| f == "File Paths"));
+    assert!(
+        risk.sensitive_fields
+            .iter()
+            .any(|f| f == "Owner/Artist Name")
+    );
+    assert!(risk.sensitive_fields.iter().any(|f| f == "Software"));
+}
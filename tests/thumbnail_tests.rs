@@ -0,0 +1,156 @@
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, GrayImage, Luma};
+use image_metadata_extractor::exif_core::extract_thumbnail;
+use image_metadata_extractor::thumbnail::{
+    DIFFERENCE_THRESHOLD, analyze_thumbnail, compare, difference_hash, hash_distance, trim_borders,
+};
+use image_metadata_extractor::types::{EmbeddedThumbnail, ImageData};
+use std::collections::HashMap;
+
+/// Smooth test pattern, optionally mirrored left to right.
+fn pattern(width: u32, height: u32, mirrored: bool) -> DynamicImage {
+    DynamicImage::ImageLuma8(GrayImage::from_fn(width, height, |x, y| {
+        let x = if mirrored { width - 1 - x } else { x };
+        let fx = x as f64 / width as f64 * 6.0;
+        let fy = y as f64 / height as f64 * 4.0;
+        Luma([(128.0 + 100.0 * fx.sin() * fy.cos()) as u8])
+    }))
+}
+
+fn encode_jpeg(image: &DynamicImage) -> Vec<u8> {
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, 90)
+        .encode_image(image)
+        .unwrap();
+    out
+}
+
+/// Wrap a thumbnail in a little-endian TIFF with IFD0 (Orientation) and IFD1
+/// (JPEGInterchangeFormat/Length), and insert it as APP1 into `main`.
+fn jpeg_with_thumbnail(main: &[u8], thumbnail: &[u8]) -> Vec<u8> {
+    let mut tiff = b"II*\0".to_vec();
+    tiff.extend_from_slice(&8u32.to_le_bytes());
+    // IFD0: Orientation = 1, next IFD at 26
+    tiff.extend_from_slice(&1u16.to_le_bytes());
+    tiff.extend_from_slice(&[0x12, 0x01, 3, 0, 1, 0, 0, 0, 1, 0, 0, 0]);
+    tiff.extend_from_slice(&26u32.to_le_bytes());
+    // IFD1: thumbnail offset and length, thumbnail data at 56
+    tiff.extend_from_slice(&2u16.to_le_bytes());
+    tiff.extend_from_slice(&[0x01, 0x02, 4, 0, 1, 0, 0, 0]);
+    tiff.extend_from_slice(&56u32.to_le_bytes());
+    tiff.extend_from_slice(&[0x02, 0x02, 4, 0, 1, 0, 0, 0]);
+    tiff.extend_from_slice(&(thumbnail.len() as u32).to_le_bytes());
+    tiff.extend_from_slice(&0u32.to_le_bytes());
+    assert_eq!(tiff.len(), 56);
+    tiff.extend_from_slice(thumbnail);
+
+    let mut app1 = b"Exif\0\0".to_vec();
+    app1.extend_from_slice(&tiff);
+    let mut out = main[..2].to_vec();
+    out.extend_from_slice(&[0xFF, 0xE1]);
+    out.extend_from_slice(&((app1.len() + 2) as u16).to_be_bytes());
+    out.extend_from_slice(&app1);
+    out.extend_from_slice(&main[2..]);
+    out
+}
+
+#[test]
+fn test_extract_thumbnail_from_ifd1() {
+    let thumbnail = encode_jpeg(&pattern(48, 32, false));
+    let file = jpeg_with_thumbnail(&encode_jpeg(&pattern(192, 128, false)), &thumbnail);
+    assert_eq!(extract_thumbnail(&file), Some(thumbnail));
+    assert_eq!(
+        extract_thumbnail(&encode_jpeg(&pattern(48, 32, false))),
+        None
+    );
+    assert_eq!(extract_thumbnail(b"not an image"), None);
+}
+
+#[test]
+fn test_matching_thumbnail_is_not_flagged() {
+    let main = encode_jpeg(&pattern(192, 128, false));
+    let file = jpeg_with_thumbnail(&main, &encode_jpeg(&pattern(48, 32, false)));
+    let thumbnail = analyze_thumbnail(&file).unwrap();
+    assert_eq!((thumbnail.width, thumbnail.height), (Some(48), Some(32)));
+    assert!(
+        thumbnail
+            .data_url
+            .starts_with("data:image/jpeg;base64,/9j/")
+    );
+    assert!(thumbnail.difference.unwrap() <= DIFFERENCE_THRESHOLD);
+    assert!(!thumbnail.differs);
+}
+
+#[test]
+fn test_edited_thumbnail_is_flagged() {
+    let main = encode_jpeg(&pattern(192, 128, false));
+    let file = jpeg_with_thumbnail(&main, &encode_jpeg(&pattern(48, 32, true)));
+    let thumbnail = analyze_thumbnail(&file).unwrap();
+    assert!(thumbnail.difference.unwrap() > DIFFERENCE_THRESHOLD);
+    assert!(thumbnail.differs);
+
+    // Same content but a different framing, as left behind by a crop
+    let file = jpeg_with_thumbnail(&main, &encode_jpeg(&pattern(32, 32, false)));
+    assert!(analyze_thumbnail(&file).unwrap().differs);
+}
+
+#[test]
+fn test_letterboxed_thumbnail_matches() {
+    let main = pattern(192, 128, false);
+    let mut boxed = GrayImage::new(48, 48);
+    image::imageops::replace(&mut boxed, &pattern(48, 32, false).to_luma8(), 0, 8);
+    let boxed = DynamicImage::ImageLuma8(boxed);
+
+    assert_eq!(trim_borders(&boxed).to_luma8().dimensions(), (48, 32));
+    let (distance, differs) = compare(&main, &boxed);
+    assert!(distance <= DIFFERENCE_THRESHOLD);
+    assert!(!differs);
+}
+
+#[test]
+fn test_hash_distance() {
+    let hash = difference_hash(&pattern(64, 64, false));
+    assert_eq!(hash_distance(hash, hash), 0);
+    assert_eq!(hash_distance(0, u64::MAX), 64);
+    assert_eq!(hash_distance(0b1010, 0b0110), 2);
+}
+
+#[test]
+fn test_privacy_risk_for_differing_thumbnail() {
+    let thumbnail = EmbeddedThumbnail {
+        data_url: String::new(),
+        size: 1024,
+        width: Some(160),
+        height: Some(120),
+        difference: Some(30),
+        differs: true,
+    };
+    let mut image_data = ImageData {
+        name: "edited.jpg".to_string(),
+        size: 4096,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: Some(640),
+        height: Some(480),
+        exif_data: HashMap::new(),
+        gps: None,
+        sha256_hash: None,
+        thumbnail: Some(thumbnail.clone()),
+        ..Default::default()
+    };
+    let risk = image_data.calculate_privacy_risk();
+    assert!(
+        risk.sensitive_fields
+            .iter()
+            .any(|f| f == "Embedded Thumbnail")
+    );
+    assert_eq!(risk.score, 20);
+
+    image_data.thumbnail = Some(EmbeddedThumbnail {
+        differs: false,
+        difference: Some(3),
+        ..thumbnail
+    });
+    let risk = image_data.calculate_privacy_risk();
+    assert!(risk.sensitive_fields.is_empty());
+}
@@ -0,0 +1,239 @@
+use image_metadata_extractor::export::{generate_json, generate_txt, generate_xml};
+use image_metadata_extractor::timestamp::{
+    TimestampKind, extract_timestamps, gps_datetime, parse_datetime, parse_offset, parse_subsec,
+};
+use image_metadata_extractor::types::{GpsInfo, ImageData, MetadataValue};
+use std::collections::HashMap;
+
+fn exif(pairs: &[(&str, &str)]) -> HashMap<String, MetadataValue> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), (*v).into()))
+        .collect()
+}
+
+fn image(exif_data: HashMap<String, MetadataValue>, gps: Option<GpsInfo>) -> ImageData {
+    ImageData {
+        name: "time.jpg".to_string(),
+        size: 1024,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: Some(100),
+        height: Some(100),
+        exif_data,
+        gps,
+        sha256_hash: None,
+        ..Default::default()
+    }
+}
+
+fn has_issue(data: &ImageData, needle: &str) -> bool {
+    data.calculate_privacy_risk()
+        .consistency_issues
+        .iter()
+        .any(|i| i.contains(needle))
+}
+
+#[test]
+fn test_parse_exif_and_iso_formats() {
+    let exif_style = parse_datetime(TimestampKind::Original, "2024:03:15 14:05:30").unwrap();
+    let iso_style = parse_datetime(TimestampKind::Original, "2024-03-15T14:05:30").unwrap();
+    assert_eq!(exif_style, iso_style);
+    assert_eq!(exif_style.offset_minutes, None);
+    assert_eq!(exif_style.to_iso8601(), "2024-03-15T14:05:30");
+
+    let full = parse_datetime(TimestampKind::Original, "2024-03-15T14:05:30.25+09:00").unwrap();
+    assert_eq!(full.nanosecond, Some(250_000_000));
+    assert_eq!(full.offset_minutes, Some(540));
+    assert_eq!(full.utc_seconds(), Some(1_710_479_130));
+    assert_eq!(full.to_iso8601(), "2024-03-15T14:05:30.25+09:00");
+
+    for placeholder in [
+        "    :  :     :  :  ",
+        "0000:00:00 00:00:00",
+        "",
+        "yesterday",
+    ] {
+        assert!(parse_datetime(TimestampKind::Modified, placeholder).is_none());
+    }
+}
+
+#[test]
+fn test_parse_offset_and_subsec() {
+    assert_eq!(parse_offset("+09:00"), Some(540));
+    assert_eq!(parse_offset("-05:30"), Some(-330));
+    assert_eq!(parse_offset("+0100"), Some(60));
+    assert_eq!(parse_offset("Z"), Some(0));
+    assert_eq!(parse_offset("   :  "), None);
+    assert_eq!(parse_offset("+25:00"), None);
+
+    assert_eq!(parse_subsec("5"), Some(500_000_000));
+    assert_eq!(parse_subsec("047"), Some(47_000_000));
+    assert_eq!(parse_subsec(" 12 "), Some(120_000_000));
+    assert_eq!(parse_subsec(""), None);
+    assert_eq!(parse_subsec("ab"), None);
+}
+
+#[test]
+fn test_exif_fields_are_combined_with_sources() {
+    let data = exif(&[
+        ("DateTimeOriginal", "2024:03:15 14:05:30"),
+        ("OffsetTimeOriginal", "+09:00"),
+        ("SubSecTimeOriginal", "047"),
+        ("DateTime", "2024:03:16 08:00:00"),
+    ]);
+    let gps = GpsInfo {
+        timestamp: gps_datetime("2024:03:15", [5.0, 5.0, 29.0]),
+        ..GpsInfo::new(35.0, 139.0)
+    };
+    let timestamps = extract_timestamps(&data, Some(&gps));
+    assert_eq!(timestamps.len(), 3);
+
+    let original = &timestamps[0];
+    assert_eq!(original.kind, TimestampKind::Original);
+    assert_eq!(original.to_iso8601(), "2024-03-15T14:05:30.047+09:00");
+    assert_eq!(
+        original.sources,
+        vec![
+            "DateTimeOriginal".to_string(),
+            "OffsetTimeOriginal".to_string(),
+            "SubSecTimeOriginal".to_string()
+        ]
+    );
+
+    let modified = &timestamps[1];
+    assert_eq!(modified.kind, TimestampKind::Modified);
+    assert_eq!(modified.sources, vec!["DateTime".to_string()]);
+
+    let fix = &timestamps[2];
+    assert_eq!(fix.kind, TimestampKind::Gps);
+    assert_eq!(fix.utc_seconds(), Some(1_710_479_129));
+    assert_eq!(fix.to_iso8601(), "2024-03-15T05:05:29Z");
+}
+
+#[test]
+fn test_gps_date_and_time_are_validated() {
+    let fix = gps_datetime("2024:02:29", [23.0, 59.0, 59.9996]).unwrap();
+    assert_eq!(fix.kind, TimestampKind::Gps);
+    assert_eq!(fix.offset_minutes, Some(0));
+    assert_eq!(fix.to_iso8601(), "2024-02-29T23:59:60Z");
+    assert_eq!(
+        gps_datetime("2024:03:15", [14.0, 5.0, 30.25])
+            .unwrap()
+            .to_iso8601(),
+        "2024-03-15T14:05:30.25Z"
+    );
+
+    for (date, hms) in [
+        ("2023:02:29", [12.0, 0.0, 0.0]),
+        ("2024:04:31", [12.0, 0.0, 0.0]),
+        ("2024:13:01", [12.0, 0.0, 0.0]),
+        ("2024:03:15", [24.0, 0.0, 0.0]),
+        ("2024:03:15", [12.5, 0.0, 0.0]),
+        ("2024:03:15", [12.0, 60.0, 0.0]),
+        ("2024:03:15", [12.0, 0.0, 61.0]),
+        ("2024:03:15", [12.0, 0.0, -1.0]),
+        ("2024:03:15", [1e12, 0.0, 0.0]),
+        ("2024:03:15", [12.0, 0.0, f64::NAN]),
+        ("    :  :  ", [12.0, 0.0, 0.0]),
+    ] {
+        assert_eq!(gps_datetime(date, hms), None, "{} {:?}", date, hms);
+    }
+}
+
+#[test]
+fn test_same_instant_across_offsets() {
+    let tokyo = parse_datetime(TimestampKind::Original, "2024:03:15 14:05:30+09:00").unwrap();
+    let utc = parse_datetime(TimestampKind::Modified, "2024-03-15T05:05:30Z").unwrap();
+    let new_york = parse_datetime(TimestampKind::Modified, "2024-03-15T01:05:30-04:00").unwrap();
+    assert!(tokyo.same_instant(&utc));
+    assert!(tokyo.same_instant(&new_york));
+
+    // Without an offset on one side, wall-clock times are compared
+    let local = parse_datetime(TimestampKind::Modified, "2024:03:15 14:05:30").unwrap();
+    assert!(local.same_instant(&tokyo));
+    assert!(!local.same_instant(&utc));
+
+    // Fractions only count when both sides have them
+    let fraction = parse_datetime(TimestampKind::Modified, "2024:03:15 14:05:30.5").unwrap();
+    let other_fraction = parse_datetime(TimestampKind::Modified, "2024:03:15 14:05:30.6").unwrap();
+    assert!(fraction.same_instant(&local));
+    assert!(!fraction.same_instant(&other_fraction));
+}
+
+#[test]
+fn test_consistency_check_compares_instants() {
+    let reformatted = image(
+        exif(&[
+            ("DateTime", "2024-03-15T14:05:30"),
+            ("DateTimeOriginal", "2024:03:15 14:05:30"),
+        ]),
+        None,
+    );
+    assert!(!has_issue(
+        &reformatted,
+        "DateTime and DateTimeOriginal differ"
+    ));
+
+    let other_zone = image(
+        exif(&[
+            ("DateTime", "2024:03:15 05:05:30"),
+            ("OffsetTime", "+00:00"),
+            ("DateTimeOriginal", "2024:03:15 14:05:30"),
+            ("OffsetTimeOriginal", "+09:00"),
+        ]),
+        None,
+    );
+    assert!(!has_issue(
+        &other_zone,
+        "DateTime and DateTimeOriginal differ"
+    ));
+
+    let edited = image(
+        exif(&[
+            ("DateTime", "2024:03:16 09:00:00"),
+            ("DateTimeOriginal", "2024:03:15 14:05:30"),
+        ]),
+        None,
+    );
+    assert!(has_issue(&edited, "DateTime and DateTimeOriginal differ"));
+
+    let thumbnail = image(
+        exif(&[
+            ("DateTime", "2024:03:15 14:05:30"),
+            ("Thumbnail:DateTime", "2024-03-15 14:05:30"),
+        ]),
+        None,
+    );
+    assert!(!has_issue(&thumbnail, "Thumbnail DateTime differs"));
+}
+
+#[test]
+fn test_exports_include_iso8601_timestamps() {
+    let data = image(
+        exif(&[
+            ("DateTimeOriginal", "2024:03:15 14:05:30"),
+            ("OffsetTimeOriginal", "+09:00"),
+        ]),
+        None,
+    );
+
+    let txt = generate_txt(&data);
+    assert!(txt.contains("TIMESTAMPS (ISO 8601)"));
+    assert!(txt.contains("Original: 2024-03-15T14:05:30+09:00"));
+
+    let xml = generate_xml(&data);
+    assert!(xml.contains(
+        "<timestamp kind=\"Original\" sources=\"DateTimeOriginal OffsetTimeOriginal\">2024-03-15T14:05:30+09:00</timestamp>"
+    ));
+
+    let json: serde_json::Value = serde_json::from_str(&generate_json(&data)).unwrap();
+    let timestamp = &json["timestamps"][0];
+    assert_eq!(timestamp["kind"], "Original");
+    assert_eq!(timestamp["iso8601"], "2024-03-15T14:05:30+09:00");
+    assert_eq!(timestamp["unix"], 1_710_479_130);
+
+    let empty: serde_json::Value =
+        serde_json::from_str(&generate_json(&image(HashMap::new(), None))).unwrap();
+    assert!(empty.get("timestamps").is_none());
+}
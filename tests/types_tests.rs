@@ -1,12 +1,13 @@
-use image_metadata_extractor::types::ImageData;
-use serde_json;
+use image_metadata_extractor::types::{
+    GpsInfo, Ifd, ImageData, MetadataSource, MetadataValue, MpfImage, Rational, RawValue,
+};
 use std::collections::{HashMap, HashSet};
 
 #[test]
 fn test_image_data_creation() {
     let mut exif = HashMap::new();
-    exif.insert("ISO".to_string(), "100".to_string());
-    exif.insert("F-number".to_string(), "f/1.8".to_string());
+    exif.insert("ISO".to_string(), "100".into());
+    exif.insert("F-number".to_string(), "f/1.8".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -16,8 +17,9 @@ fn test_image_data_creation() {
         width: Some(800),
         height: Some(600),
         exif_data: exif.clone(),
-        gps_coords: Some((37.7749, -122.4194)),
+        gps: Some(GpsInfo::new(37.7749, -122.4194)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     assert_eq!(data.name, "test.jpg");
@@ -26,14 +28,14 @@ fn test_image_data_creation() {
     assert_eq!(data.width, Some(800));
     assert_eq!(data.height, Some(600));
     assert_eq!(data.exif_data.len(), 2);
-    assert_eq!(data.gps_coords, Some((37.7749, -122.4194)));
+    assert_eq!(data.gps.map(|g| g.coords()), Some((37.7749, -122.4194)));
 }
 
 #[test]
 fn test_filter_metadata_all_excluded() {
     let mut exif = HashMap::new();
-    exif.insert("ISO".to_string(), "100".to_string());
-    exif.insert("Aperture".to_string(), "f/1.8".to_string());
+    exif.insert("ISO".to_string(), "100".into());
+    exif.insert("Aperture".to_string(), "f/1.8".into());
 
     let data = ImageData {
         name: "photo.jpg".to_string(),
@@ -43,8 +45,9 @@ fn test_filter_metadata_all_excluded() {
         width: Some(800),
         height: Some(600),
         exif_data: exif,
-        gps_coords: Some((1.0, 2.0)),
+        gps: Some(GpsInfo::new(1.0, 2.0)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let keys = HashSet::new(); // No keys selected
@@ -57,15 +60,15 @@ fn test_filter_metadata_all_excluded() {
     assert!(filtered.width.is_none());
     assert!(filtered.height.is_none());
     assert!(filtered.exif_data.is_empty());
-    assert!(filtered.gps_coords.is_none());
+    assert!(filtered.gps.is_none());
 }
 
 #[test]
 fn test_filter_metadata_selective_exif() {
     let mut exif = HashMap::new();
-    exif.insert("ISO".to_string(), "100".to_string());
-    exif.insert("Aperture".to_string(), "f/1.8".to_string());
-    exif.insert("Camera".to_string(), "Canon".to_string());
+    exif.insert("ISO".to_string(), "100".into());
+    exif.insert("Aperture".to_string(), "f/1.8".into());
+    exif.insert("Camera".to_string(), "Canon".into());
 
     let data = ImageData {
         name: "photo.jpg".to_string(),
@@ -75,8 +78,9 @@ fn test_filter_metadata_selective_exif() {
         width: Some(800),
         height: Some(600),
         exif_data: exif,
-        gps_coords: Some((1.0, 2.0)),
+        gps: Some(GpsInfo::new(1.0, 2.0)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let mut keys = HashSet::new();
@@ -85,9 +89,9 @@ fn test_filter_metadata_selective_exif() {
 
     let filtered = data.filter_metadata(&keys, false, false);
     assert_eq!(filtered.exif_data.len(), 2);
-    assert_eq!(filtered.exif_data.get("ISO"), Some(&"100".to_string()));
-    assert_eq!(filtered.exif_data.get("Camera"), Some(&"Canon".to_string()));
-    assert!(filtered.exif_data.get("Aperture").is_none());
+    assert_eq!(filtered.exif_data["ISO"], "100");
+    assert_eq!(filtered.exif_data["Camera"], "Canon");
+    assert!(!filtered.exif_data.contains_key("Aperture"));
 }
 
 #[test]
@@ -100,8 +104,9 @@ fn test_filter_metadata_include_basic_info() {
         width: Some(800),
         height: Some(600),
         exif_data: HashMap::new(),
-        gps_coords: Some((1.0, 2.0)),
+        gps: Some(GpsInfo::new(1.0, 2.0)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let keys = HashSet::new();
@@ -111,7 +116,7 @@ fn test_filter_metadata_include_basic_info() {
     assert_eq!(filtered.size, 1234);
     assert_eq!(filtered.width, Some(800));
     assert_eq!(filtered.height, Some(600));
-    assert!(filtered.gps_coords.is_none()); // GPS not included
+    assert!(filtered.gps.is_none()); // GPS not included
 }
 
 #[test]
@@ -124,8 +129,9 @@ fn test_filter_metadata_include_gps() {
         width: Some(800),
         height: Some(600),
         exif_data: HashMap::new(),
-        gps_coords: Some((37.7749, -122.4194)),
+        gps: Some(GpsInfo::new(37.7749, -122.4194)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let keys = HashSet::new();
@@ -135,13 +141,54 @@ fn test_filter_metadata_include_gps() {
     assert_eq!(filtered.size, 0);
     assert!(filtered.width.is_none());
     assert!(filtered.height.is_none());
-    assert_eq!(filtered.gps_coords, Some((37.7749, -122.4194))); // GPS included
+    assert_eq!(filtered.gps.map(|g| g.coords()), Some((37.7749, -122.4194))); // GPS included
+}
+
+#[test]
+fn test_filter_metadata_filters_extra_images() {
+    let mut second_view = HashMap::new();
+    second_view.insert("Make".to_string(), "StereoCam".into());
+    second_view.insert("SerialNumber".to_string(), "12345".into());
+    second_view.insert("GPSLatitude".to_string(), "48.5".into());
+    let data = ImageData {
+        name: "stereo.mpo".to_string(),
+        mime_type: "image/jpeg".to_string(),
+        mpf_images: vec![MpfImage {
+            index: 2,
+            image_type: "Multi-Frame Disparity".to_string(),
+            offset: 4096,
+            size: 2048,
+            width: None,
+            height: None,
+            exif_data: second_view,
+            data_url: String::new(),
+        }],
+        ..Default::default()
+    };
+    let keys: HashSet<String> = ["Make", "GPSLatitude"].map(String::from).into();
+
+    let filtered = data.filter_metadata(&keys, true, false);
+    let exif = &filtered.mpf_images[0].exif_data;
+    assert_eq!(exif.len(), 1);
+    assert_eq!(exif["Make"], "StereoCam");
+
+    let filtered = data.filter_metadata(&keys, true, true);
+    let exif = &filtered.mpf_images[0].exif_data;
+    assert_eq!(exif.len(), 2);
+    assert!(exif.contains_key("GPSLatitude"));
+    assert!(!exif.contains_key("SerialNumber"));
+
+    assert!(
+        data.filter_metadata(&keys, false, true)
+            .mpf_images
+            .is_empty()
+    );
 }
 
 #[test]
 fn test_filter_metadata_include_all() {
     let mut exif = HashMap::new();
-    exif.insert("ISO".to_string(), "200".to_string());
+    exif.insert("ISO".to_string(), "200".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -151,8 +198,9 @@ fn test_filter_metadata_include_all() {
         width: Some(1920),
         height: Some(1080),
         exif_data: exif,
-        gps_coords: Some((40.7128, -74.0060)),
+        gps: Some(GpsInfo::new(40.7128, -74.0060)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let mut keys = HashSet::new();
@@ -164,14 +212,14 @@ fn test_filter_metadata_include_all() {
     assert_eq!(filtered.width, Some(1920));
     assert_eq!(filtered.height, Some(1080));
     assert_eq!(filtered.exif_data.len(), 1);
-    assert_eq!(filtered.exif_data.get("ISO"), Some(&"200".to_string()));
-    assert_eq!(filtered.gps_coords, Some((40.7128, -74.0060)));
+    assert_eq!(filtered.exif_data["ISO"], "200");
+    assert_eq!(filtered.gps.map(|g| g.coords()), Some((40.7128, -74.0060)));
 }
 
 #[test]
 fn test_filter_metadata_nonexistent_keys() {
     let mut exif = HashMap::new();
-    exif.insert("ISO".to_string(), "100".to_string());
+    exif.insert("ISO".to_string(), "100".into());
 
     let data = ImageData {
         name: "photo.jpg".to_string(),
@@ -181,8 +229,9 @@ fn test_filter_metadata_nonexistent_keys() {
         width: Some(640),
         height: Some(480),
         exif_data: exif,
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let mut keys = HashSet::new();
@@ -203,8 +252,9 @@ fn test_filter_metadata_empty_exif() {
         width: Some(100),
         height: Some(100),
         exif_data: HashMap::new(),
-        gps_coords: None,
+        gps: None,
         sha256_hash: None,
+        ..Default::default()
     };
 
     let mut keys = HashSet::new();
@@ -214,7 +264,7 @@ fn test_filter_metadata_empty_exif() {
     assert_eq!(filtered.name, "empty.jpg");
     assert_eq!(filtered.size, 500);
     assert!(filtered.exif_data.is_empty());
-    assert!(filtered.gps_coords.is_none());
+    assert!(filtered.gps.is_none());
 }
 
 // Removed tests for derived traits (Clone, PartialEq) as they test compiler-guaranteed functionality
@@ -222,7 +272,7 @@ fn test_filter_metadata_empty_exif() {
 #[test]
 fn test_image_data_serialization() {
     let mut exif = HashMap::new();
-    exif.insert("ISO".to_string(), "200".to_string());
+    exif.insert("ISO".to_string(), "200".into());
 
     let data = ImageData {
         name: "test.jpg".to_string(),
@@ -232,8 +282,9 @@ fn test_image_data_serialization() {
         width: Some(800),
         height: Some(600),
         exif_data: exif,
-        gps_coords: Some((37.7749, -122.4194)),
+        gps: Some(GpsInfo::new(37.7749, -122.4194)),
         sha256_hash: None,
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&data).unwrap();
@@ -242,7 +293,7 @@ fn test_image_data_serialization() {
     assert!(json.contains("\"width\":800"));
     assert!(json.contains("\"height\":600"));
     assert!(json.contains("\"ISO\":\"200\""));
-    assert!(json.contains("\"gps_coords\":[37.7749,-122.4194]"));
+    assert!(json.contains("\"gps\":{\"latitude\":37.7749,\"longitude\":-122.4194}"));
 
     // These should be skipped in serialization
     assert!(!json.contains("mime_type"));
@@ -260,8 +311,9 @@ fn test_image_data_serialization_empty_values() {
         width: None,               // Should be skipped (None)
         height: None,              // Should be skipped (None)
         exif_data: HashMap::new(), // Should be skipped (empty)
-        gps_coords: None,          // Should be skipped (None)
+        gps: None,                 // Should be skipped (None)
         sha256_hash: None,
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&data).unwrap();
@@ -272,25 +324,26 @@ fn test_image_data_serialization_empty_values() {
 #[test]
 fn test_image_data_serialization_partial_skip() {
     let mut exif = HashMap::new();
-    exif.insert("Camera".to_string(), "Canon".to_string());
+    exif.insert("Camera".to_string(), "Canon".into());
 
     let data = ImageData {
-        name: "photo.jpg".to_string(),         // Included (non-empty)
-        size: 0,                               // Skipped (zero)
-        mime_type: "image/jpeg".to_string(),   // Always skipped
-        data_url: "data:...".to_string(),      // Always skipped
-        width: Some(1920),                     // Included (Some value)
-        height: None,                          // Skipped (None)
-        exif_data: exif,                       // Included (non-empty)
-        gps_coords: Some((40.7128, -74.0060)), // Included (Some value)
+        name: "photo.jpg".to_string(),              // Included (non-empty)
+        size: 0,                                    // Skipped (zero)
+        mime_type: "image/jpeg".to_string(),        // Always skipped
+        data_url: "data:...".to_string(),           // Always skipped
+        width: Some(1920),                          // Included (Some value)
+        height: None,                               // Skipped (None)
+        exif_data: exif,                            // Included (non-empty)
+        gps: Some(GpsInfo::new(40.7128, -74.0060)), // Included (Some value)
         sha256_hash: None,
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&data).unwrap();
     assert!(json.contains("\"name\":\"photo.jpg\""));
     assert!(json.contains("\"width\":1920"));
     assert!(json.contains("\"Camera\":\"Canon\""));
-    assert!(json.contains("\"gps_coords\"")); // Just check field exists
+    assert!(json.contains("\"gps\"")); // Just check field exists
 
     // These should be skipped
     assert!(!json.contains("size"));
@@ -298,3 +351,133 @@ fn test_image_data_serialization_partial_skip() {
     assert!(!json.contains("mime_type"));
     assert!(!json.contains("data_url"));
 }
+
+#[test]
+fn test_metadata_value_numeric_accessors() {
+    let rational = MetadataValue::new(
+        RawValue::Rational(vec![Rational { num: 28, denom: 10 }]),
+        "f/2.8",
+    )
+    .with_unit("f/");
+    assert!(rational.is_numeric());
+    assert_eq!(rational.as_f64(), Some(2.8));
+    assert_eq!(rational.as_i64(), None);
+    assert_eq!(rational.unit.as_deref(), Some("f/"));
+    assert_eq!(rational.to_string(), "f/2.8");
+
+    let ints = MetadataValue::new(RawValue::Integer(vec![4000, 3000]), "4000, 3000");
+    assert_eq!(ints.as_i64(), Some(4000));
+    assert_eq!(ints.as_f64_vec(), Some(vec![4000.0, 3000.0]));
+
+    // Text values holding a single number can still be compared numerically
+    let text: MetadataValue = " 400 ".into();
+    assert!(!text.is_numeric());
+    assert_eq!(text.as_f64(), Some(400.0));
+    assert_eq!(text.as_text(), Some(" 400 "));
+    assert_eq!(MetadataValue::text("Canon").as_f64(), None);
+}
+
+#[test]
+fn test_metadata_value_same_value() {
+    let int = MetadataValue::new(RawValue::Integer(vec![4000]), "4000");
+    let text: MetadataValue = "4000.0".into();
+    let other: MetadataValue = "3000".into();
+    assert!(int.same_value(&text));
+    assert!(!int.same_value(&other));
+
+    let a: MetadataValue = "Canon".into();
+    let b: MetadataValue = "Nikon".into();
+    assert!(a.same_value(&MetadataValue::text("Canon")));
+    assert!(!a.same_value(&b));
+}
+
+#[test]
+fn test_rational_zero_denominator() {
+    assert!(Rational { num: 1, denom: 0 }.to_f64().is_nan());
+    assert_eq!(Rational { num: -1, denom: 4 }.to_string(), "-1/4");
+}
+
+#[test]
+fn test_metadata_value_serialization() {
+    let text: MetadataValue = "Canon".into();
+    assert_eq!(serde_json::to_string(&text).unwrap(), "\"Canon\"");
+
+    let focal = MetadataValue::new(
+        RawValue::Rational(vec![Rational { num: 50, denom: 1 }]),
+        "50 mm",
+    )
+    .with_unit("mm");
+    assert_eq!(
+        serde_json::to_string(&focal).unwrap(),
+        r#"{"value":50.0,"unit":"mm","display":"50 mm"}"#
+    );
+
+    let dims = MetadataValue::new(RawValue::Integer(vec![1, 2]), "1, 2");
+    assert_eq!(
+        serde_json::to_string(&dims).unwrap(),
+        r#"{"value":[1,2],"display":"1, 2"}"#
+    );
+
+    let bytes = MetadataValue::new(RawValue::Bytes(vec![0x30, 0x32]), "0x3032");
+    assert_eq!(serde_json::to_string(&bytes).unwrap(), "\"0x3032\"");
+
+    // The source goes along once it is known
+    let focal = focal.with_source(MetadataSource::Exif {
+        ifd: Ifd::Exif,
+        tag: 0x920A,
+    });
+    assert_eq!(
+        serde_json::to_string(&focal).unwrap(),
+        r#"{"value":50.0,"unit":"mm","display":"50 mm","source":{"kind":"exif","ifd":"exif","tag":37386}}"#
+    );
+    let make = MetadataValue::text("Canon").with_source(MetadataSource::MakerNote {
+        vendor: "Canon",
+        tag: 0x0006,
+    });
+    assert_eq!(
+        serde_json::to_string(&make).unwrap(),
+        r#"{"value":"Canon","source":{"kind":"makernote","vendor":"Canon","tag":6}}"#
+    );
+}
+
+#[test]
+fn test_exif_field_lookup_by_ifd_and_tag() {
+    let mut exif = HashMap::new();
+    exif.insert(
+        "DateTime".to_string(),
+        MetadataValue::text("2024").with_source(MetadataSource::Exif {
+            ifd: Ifd::Primary,
+            tag: 0x0132,
+        }),
+    );
+    exif.insert(
+        "Thumbnail:DateTime".to_string(),
+        MetadataValue::text("2019").with_source(MetadataSource::Exif {
+            ifd: Ifd::Thumbnail,
+            tag: 0x0132,
+        }),
+    );
+
+    let data = ImageData {
+        name: "test.jpg".to_string(),
+        size: 1024,
+        mime_type: "image/jpeg".to_string(),
+        data_url: String::new(),
+        width: None,
+        height: None,
+        exif_data: exif,
+        gps: None,
+        sha256_hash: None,
+        ..Default::default()
+    };
+
+    let (key, value) = data.exif_field(Ifd::Thumbnail, 0x0132).unwrap();
+    assert_eq!(key, "Thumbnail:DateTime");
+    assert_eq!(*value, "2019");
+    assert_eq!(
+        data.exif_field(Ifd::Primary, 0x0132).unwrap().1.ifd(),
+        Some(Ifd::Primary)
+    );
+    assert!(data.exif_field(Ifd::Gps, 0x0132).is_none());
+    assert_eq!(Ifd::Thumbnail.id(), "thumbnail");
+}
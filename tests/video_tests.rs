@@ -0,0 +1,420 @@
+mod common;
+
+use common::bx;
+use image_metadata_extractor::exif_core::{
+    determine_mime_type, extract_metadata, is_supported_mime_type,
+};
+use image_metadata_extractor::timestamp::{TimestampKind, extract_timestamps};
+use image_metadata_extractor::types::{ImageData, MetadataSource};
+use image_metadata_extractor::video::{
+    codec_name, dimensions, is_video, mime_type, parse_iso6709, parse_video,
+};
+
+/// 2024-03-15T05:05:30Z in seconds since 1904.
+const MVHD_TIME: u32 = 1_710_479_130 + 2_082_844_800;
+
+fn full_bx(kind: &[u8; 4], version: u8, payload: &[u8]) -> Vec<u8> {
+    let mut body = vec![version, 0, 0, 0];
+    body.extend_from_slice(payload);
+    bx(kind, &body)
+}
+
+fn ftyp(brand: &[u8; 4]) -> Vec<u8> {
+    let mut body = brand.to_vec();
+    body.extend_from_slice(&0x200u32.to_be_bytes());
+    body.extend_from_slice(brand);
+    body.extend_from_slice(b"mp41");
+    bx(b"ftyp", &body)
+}
+
+fn mvhd(timescale: u32, duration: u32) -> Vec<u8> {
+    let mut body = Vec::new();
+    for value in [MVHD_TIME, MVHD_TIME + 60, timescale, duration] {
+        body.extend_from_slice(&value.to_be_bytes());
+    }
+    body.extend_from_slice(&[0; 80]);
+    full_bx(b"mvhd", 0, &body)
+}
+
+/// Track with a `tkhd` (rotated by `matrix_ab`), `mdhd`, `hdlr` and one
+/// sample entry. `timing` is the media timescale and the sample count and
+/// duration listed in `stts`.
+fn trak(
+    handler: &[u8; 4],
+    entry: Vec<u8>,
+    size: (u32, u32),
+    matrix_ab: (i32, i32),
+    timing: (u32, u32, u32),
+    language: &str,
+) -> Vec<u8> {
+    let (timescale, samples, delta) = timing;
+    let mut tkhd = Vec::new();
+    for value in [MVHD_TIME, MVHD_TIME + 120, 1, 0, samples * delta] {
+        tkhd.extend_from_slice(&value.to_be_bytes());
+    }
+    tkhd.extend_from_slice(&[0; 16]);
+    let (a, b) = (matrix_ab.0 << 16, matrix_ab.1 << 16);
+    for value in [a, b, 0, -b, a, 0, 0, 0, 0x4000_0000] {
+        tkhd.extend_from_slice(&value.to_be_bytes());
+    }
+    tkhd.extend_from_slice(&(size.0 << 16).to_be_bytes());
+    tkhd.extend_from_slice(&(size.1 << 16).to_be_bytes());
+
+    let mut mdhd = Vec::new();
+    for value in [0u32, 0, timescale, samples * delta] {
+        mdhd.extend_from_slice(&value.to_be_bytes());
+    }
+    let packed = language
+        .bytes()
+        .fold(0u16, |acc, c| (acc << 5) | u16::from(c - 0x60));
+    mdhd.extend_from_slice(&packed.to_be_bytes());
+    mdhd.extend_from_slice(&[0, 0]);
+
+    let mut hdlr = vec![0; 4];
+    hdlr.extend_from_slice(handler);
+    hdlr.extend_from_slice(&[0; 12]);
+    hdlr.extend_from_slice(b"Handler\0");
+
+    let mut stsd = 1u32.to_be_bytes().to_vec();
+    stsd.extend(entry);
+    let mut stts = 1u32.to_be_bytes().to_vec();
+    stts.extend_from_slice(&samples.to_be_bytes());
+    stts.extend_from_slice(&delta.to_be_bytes());
+    let stbl = [full_bx(b"stsd", 0, &stsd), full_bx(b"stts", 0, &stts)].concat();
+
+    let mdia = [
+        full_bx(b"mdhd", 0, &mdhd),
+        full_bx(b"hdlr", 0, &hdlr),
+        bx(b"minf", &bx(b"stbl", &stbl)),
+    ]
+    .concat();
+    bx(
+        b"trak",
+        &[full_bx(b"tkhd", 0, &tkhd), bx(b"mdia", &mdia)].concat(),
+    )
+}
+
+fn visual_entry(codec: &[u8; 4], width: u16, height: u16, compressor: &str) -> Vec<u8> {
+    let mut body = vec![0; 8 + 16];
+    body.extend_from_slice(&width.to_be_bytes());
+    body.extend_from_slice(&height.to_be_bytes());
+    body.extend_from_slice(&[0; 14]);
+    let mut name = [0u8; 32];
+    name[0] = compressor.len() as u8;
+    name[1..=compressor.len()].copy_from_slice(compressor.as_bytes());
+    body.extend_from_slice(&name);
+    body.extend_from_slice(&[0; 4]);
+    bx(codec, &body)
+}
+
+fn audio_entry(channels: u16, rate: u32) -> Vec<u8> {
+    let mut body = vec![0; 16];
+    body.extend_from_slice(&channels.to_be_bytes());
+    body.extend_from_slice(&[0, 16, 0, 0, 0, 0]);
+    body.extend_from_slice(&(rate << 16).to_be_bytes());
+    bx(b"mp4a", &body)
+}
+
+/// An `ilst` `data` box holding UTF-8 text.
+fn data(text: &str) -> Vec<u8> {
+    let mut body = 1u32.to_be_bytes().to_vec();
+    body.extend_from_slice(&[0; 4]);
+    body.extend_from_slice(text.as_bytes());
+    bx(b"data", &body)
+}
+
+/// A QuickTime `udta` string atom.
+fn string_atom(kind: &[u8; 4], text: &str) -> Vec<u8> {
+    let mut body = (text.len() as u16).to_be_bytes().to_vec();
+    body.extend_from_slice(&0x55C4u16.to_be_bytes());
+    body.extend_from_slice(text.as_bytes());
+    bx(kind, &body)
+}
+
+/// An MP4 as written by FFmpeg or an Android camera: a rotated H.264 track,
+/// an AAC track and `udta` location, make and iTunes-style encoder tags.
+fn android_mp4() -> Vec<u8> {
+    let video = trak(
+        b"vide",
+        visual_entry(b"avc1", 1920, 1080, "AVC Coding"),
+        (1920, 1080),
+        (0, 1),
+        (30_000, 300, 1001),
+        "und",
+    );
+    let audio = trak(
+        b"soun",
+        audio_entry(2, 48_000),
+        (0, 0),
+        (1, 0),
+        (48_000, 470, 1024),
+        "eng",
+    );
+    let mut hdlr = vec![0; 4];
+    hdlr.extend_from_slice(b"mdir");
+    hdlr.extend_from_slice(&[0; 13]);
+    let ilst = bx(b"ilst", &bx(b"\xA9too", &data("Lavf60.3.100")));
+    let meta = full_bx(b"meta", 0, &[full_bx(b"hdlr", 0, &hdlr), ilst].concat());
+    let udta = bx(
+        b"udta",
+        &[
+            string_atom(b"\xA9xyz", "+37.7749-122.4194+012.345/"),
+            string_atom(b"\xA9mak", "Google"),
+            meta,
+        ]
+        .concat(),
+    );
+    let moov = bx(b"moov", &[mvhd(1000, 10_010), video, audio, udta].concat());
+    [ftyp(b"isom"), moov, bx(b"mdat", &[0; 16])].concat()
+}
+
+/// An iPhone MOV: a HEVC track and Apple `keys`/`ilst` metadata.
+fn iphone_mov() -> Vec<u8> {
+    let video = trak(
+        b"vide",
+        visual_entry(b"hvc1", 3840, 2160, "HEVC"),
+        (3840, 2160),
+        (1, 0),
+        (600, 60, 20),
+        "und",
+    );
+    let entries = [
+        ("com.apple.quicktime.make", "Apple"),
+        ("com.apple.quicktime.model", "iPhone 15 Pro"),
+        ("com.apple.quicktime.software", "17.4"),
+        (
+            "com.apple.quicktime.creationdate",
+            "2024-03-15T14:05:30+0900",
+        ),
+        (
+            "com.apple.quicktime.location.ISO6709",
+            "+35.6586+139.7454+040.000/",
+        ),
+        ("com.apple.quicktime.location.accuracy.horizontal", "4.5"),
+        ("com.apple.quicktime.live-photo.auto", "1"),
+    ];
+    let mut keys = (entries.len() as u32).to_be_bytes().to_vec();
+    let mut ilst = Vec::new();
+    for (i, (key, value)) in entries.iter().enumerate() {
+        keys.extend(bx(b"mdta", key.as_bytes()));
+        ilst.extend(bx(&(i as u32 + 1).to_be_bytes(), &data(value)));
+    }
+    let mut hdlr = vec![0; 4];
+    hdlr.extend_from_slice(b"mdta");
+    hdlr.extend_from_slice(&[0; 13]);
+    // QuickTime writes `meta` as a plain box
+    let meta = bx(
+        b"meta",
+        &[
+            full_bx(b"hdlr", 0, &hdlr),
+            full_bx(b"keys", 0, &keys),
+            bx(b"ilst", &ilst),
+        ]
+        .concat(),
+    );
+    let moov = bx(b"moov", &[mvhd(600, 1200), video, meta].concat());
+    [ftyp(b"qt  "), bx(b"wide", &[]), moov].concat()
+}
+
+#[test]
+fn test_parse_tracks() {
+    let file = parse_video(&android_mp4()).unwrap();
+    assert_eq!(file.major_brand, "isom");
+    assert_eq!(file.compatible_brands, vec!["isom", "mp41"]);
+    assert_eq!(file.creation_time, Some(1_710_479_130));
+    assert_eq!(file.modification_time, Some(1_710_479_190));
+    assert_eq!(file.duration_seconds(), Some(10.01));
+    assert_eq!(file.tracks.len(), 2);
+
+    let video = file.video_track().unwrap();
+    assert_eq!(video.codec.as_deref(), Some("avc1"));
+    assert_eq!(video.compressor.as_deref(), Some("AVC Coding"));
+    assert_eq!((video.width, video.height), (Some(1920), Some(1080)));
+    assert_eq!(video.rotation, 90);
+    assert_eq!(video.creation_time, Some(1_710_479_130));
+    assert_eq!(video.modification_time, Some(1_710_479_250));
+    assert!((video.frame_rate().unwrap() - 29.97).abs() < 0.001);
+    assert_eq!(video.language, None);
+
+    let audio = file.audio_track().unwrap();
+    assert_eq!(audio.codec.as_deref(), Some("mp4a"));
+    assert_eq!(audio.channels, Some(2));
+    assert_eq!(audio.sample_rate, Some(48_000));
+    assert_eq!(audio.language.as_deref(), Some("eng"));
+
+    assert_eq!(file.tag("Make"), Some("Google"));
+    assert_eq!(file.tag("Encoder"), Some("Lavf60.3.100"));
+
+    // Rotated by 90°, so displayed in portrait
+    assert_eq!(dimensions(&android_mp4()), Some((1080, 1920)));
+    assert_eq!(dimensions(&iphone_mov()), Some((3840, 2160)));
+}
+
+#[test]
+fn test_metadata_keys_and_location() {
+    let (metadata, gps) = extract_metadata(&android_mp4());
+    assert_eq!(metadata["QuickTime:VideoCodec"].display, "H.264/AVC (avc1)");
+    assert_eq!(metadata["QuickTime:AudioCodec"].display, "AAC (mp4a)");
+    assert_eq!(metadata["QuickTime:FrameRate"].display, "29.97 fps");
+    assert_eq!(metadata["QuickTime:Duration"].display, "10.01 s");
+    assert_eq!(metadata["QuickTime:Rotation"].display, "90°");
+    assert_eq!(metadata["QuickTime:AudioSampleRate"].display, "48000 Hz");
+    assert_eq!(
+        metadata["QuickTime:CreateDate"].display,
+        "2024-03-15T05:05:30Z"
+    );
+    assert_eq!(
+        metadata["QuickTime:TrackModifyDate"].display,
+        "2024-03-15T05:07:30Z"
+    );
+    assert_eq!(
+        metadata["QuickTime:TrackCreateDate"].source,
+        Some(MetadataSource::Bmff {
+            box_type: "tkhd".to_string()
+        })
+    );
+    assert_eq!(metadata["QuickTime:Encoder"].display, "Lavf60.3.100");
+    assert_eq!(
+        metadata["QuickTime:GPSCoordinates"].source,
+        Some(MetadataSource::Bmff {
+            box_type: "\u{A9}xyz".to_string()
+        })
+    );
+    let gps = gps.unwrap();
+    assert_eq!(gps.coords(), (37.7749, -122.4194));
+    assert_eq!(gps.altitude, Some(12.345));
+
+    let (metadata, gps) = extract_metadata(&iphone_mov());
+    assert_eq!(metadata["QuickTime:Make"].display, "Apple");
+    assert_eq!(metadata["QuickTime:Model"].display, "iPhone 15 Pro");
+    assert_eq!(metadata["QuickTime:LivePhotoAuto"].display, "1");
+    assert_eq!(metadata["QuickTime:MajorBrand"].display, "qt");
+    assert!(!metadata.contains_key("QuickTime:AudioCodec"));
+    let gps = gps.unwrap();
+    assert_eq!(gps.coords(), (35.6586, 139.7454));
+    assert_eq!(gps.horizontal_error, Some(4.5));
+}
+
+#[test]
+fn test_iso6709_forms() {
+    assert_eq!(
+        parse_iso6709("+37.7749-122.4194/"),
+        Some((37.7749, -122.4194, None))
+    );
+    // Android does not zero-pad the longitude
+    assert_eq!(
+        parse_iso6709("+48.8577+2.2950/"),
+        Some((48.8577, 2.295, None))
+    );
+    let (lat, lon, alt) = parse_iso6709("+4030.5-07400.25+10CRSWGS_84/").unwrap();
+    assert!((lat - 40.508333).abs() < 1e-6);
+    assert!((lon + 74.004167).abs() < 1e-6);
+    assert_eq!(alt, Some(10.0));
+    let (lat, lon, _) = parse_iso6709("-334512+1511230/").unwrap();
+    assert!((lat + 33.753333).abs() < 1e-6);
+    assert!((lon - 151.208333).abs() < 1e-6);
+
+    assert_eq!(parse_iso6709("+91.0+010.0/"), None);
+    assert_eq!(parse_iso6709("37.7749"), None);
+    assert_eq!(parse_iso6709(""), None);
+}
+
+#[test]
+fn test_detection_and_mime_types() {
+    assert!(is_video(&android_mp4()));
+    assert_eq!(mime_type(&android_mp4()), Some("video/mp4"));
+    assert_eq!(mime_type(&iphone_mov()), Some("video/quicktime"));
+
+    let mut three_gp = android_mp4();
+    three_gp[8..12].copy_from_slice(b"3gp5");
+    assert_eq!(mime_type(&three_gp), Some("video/3gpp"));
+    three_gp[8..12].copy_from_slice(b"3g2a");
+    assert_eq!(mime_type(&three_gp), Some("video/3gpp2"));
+
+    // An image sequence with a `moov` box is still an image
+    let mut heic = android_mp4();
+    heic[8..12].copy_from_slice(b"msf1");
+    assert!(!is_video(&heic));
+
+    // Old QuickTime files start straight with `moov`
+    let bare: Vec<u8> = iphone_mov().split_off(ftyp(b"qt  ").len() + 8);
+    assert_eq!(mime_type(&bare), Some("video/quicktime"));
+    assert!(!is_video(b"\x00\x00\x00\x08abcd"));
+
+    assert_eq!(determine_mime_type("clip", "", &android_mp4()), "video/mp4");
+    assert_eq!(
+        determine_mime_type("clip.MOV", "", b"junk"),
+        "video/quicktime"
+    );
+    assert!(is_supported_mime_type("video/quicktime"));
+    assert!(is_supported_mime_type("video/3gpp"));
+    assert_eq!(codec_name("hvc1"), "H.265/HEVC (hvc1)");
+    assert_eq!(codec_name("xyz1"), "xyz1");
+}
+
+#[test]
+fn test_out_of_range_movie_times_are_dropped() {
+    for seconds in [1u64 << 63, u64::MAX] {
+        let mut body = Vec::new();
+        body.extend_from_slice(&seconds.to_be_bytes());
+        body.extend_from_slice(&seconds.to_be_bytes());
+        body.extend_from_slice(&1000u32.to_be_bytes());
+        body.extend_from_slice(&2000u64.to_be_bytes());
+        body.extend_from_slice(&[0; 80]);
+        let moov = bx(b"moov", &full_bx(b"mvhd", 1, &body));
+        let file = parse_video(&[ftyp(b"isom"), moov].concat()).unwrap();
+        assert_eq!(file.creation_time, None);
+        assert_eq!(file.modification_time, None);
+        assert_eq!(file.duration_seconds(), Some(2.0));
+    }
+}
+
+#[test]
+fn test_movie_timestamps_and_privacy() {
+    let bytes = iphone_mov();
+    let (exif_data, gps) = extract_metadata(&bytes);
+    let timestamps = extract_timestamps(&exif_data, gps.as_ref());
+    let original = timestamps
+        .iter()
+        .find(|t| t.kind == TimestampKind::Original)
+        .unwrap();
+    assert_eq!(original.to_iso8601(), "2024-03-15T14:05:30+09:00");
+    assert_eq!(original.sources, vec!["QuickTime:CreationDate"]);
+    let digitized = timestamps
+        .iter()
+        .find(|t| t.kind == TimestampKind::Digitized)
+        .unwrap();
+    assert!(digitized.same_instant(original));
+
+    let data = ImageData {
+        name: "IMG_0001.MOV".to_string(),
+        size: bytes.len() as u64,
+        mime_type: "video/quicktime".to_string(),
+        data_url: String::new(),
+        width: Some(3840),
+        height: Some(2160),
+        exif_data,
+        gps,
+        sha256_hash: None,
+        ..Default::default()
+    };
+    let risk = data.calculate_privacy_risk();
+    for field in [
+        "GPS Location",
+        "Camera Make/Model",
+        "Software",
+        "Timestamps",
+    ] {
+        assert!(
+            risk.sensitive_fields.contains(&field.to_string()),
+            "{}",
+            field
+        );
+    }
+    assert!(
+        !risk
+            .consistency_issues
+            .iter()
+            .any(|issue| issue.contains("reference fields"))
+    );
+}
@@ -0,0 +1,366 @@
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use image_metadata_extractor::container;
+use image_metadata_extractor::diagnostics::Severity;
+use image_metadata_extractor::exif_core::extract_metadata;
+use image_metadata_extractor::types::{MetadataSource, RawValue};
+use image_metadata_extractor::xmp::{
+    canonical_prefix, extract_xmp_data, find_xmp_packets, find_xmp_packets_with_diagnostics,
+    parse_xmp,
+};
+use std::io::Write;
+
+const PACKET: &str = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:xap="http://ns.adobe.com/xap/1.0/"
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:photoshop="http://ns.adobe.com/photoshop/1.0/"
+    xmlns:xmpMM="http://ns.adobe.com/xap/1.0/mm/"
+    xmlns:stEvt="http://ns.adobe.com/xap/1.0/sType/ResourceEvent#"
+    xmlns:stRef="http://ns.adobe.com/xap/1.0/sType/ResourceRef#"
+    xmlns:aux="http://ns.adobe.com/exif/1.0/aux/"
+    xmlns:exif="http://ns.adobe.com/exif/1.0/"
+    xap:CreatorTool="Adobe Lightroom 6.0"
+    photoshop:DateCreated="2023-06-01T12:34:56"
+    aux:SerialNumber="0012345"
+    exif:FNumber="28/10">
+   <dc:creator><rdf:Seq><rdf:li>Jane Doe</rdf:li></rdf:Seq></dc:creator>
+   <dc:subject><rdf:Bag><rdf:li>beach</rdf:li><rdf:li>holiday</rdf:li></rdf:Bag></dc:subject>
+   <dc:title><rdf:Alt>
+     <rdf:li xml:lang="de-DE">Strand</rdf:li>
+     <rdf:li xml:lang="x-default">Beach</rdf:li>
+   </rdf:Alt></dc:title>
+   <xmpMM:History><rdf:Seq>
+     <rdf:li stEvt:action="created" stEvt:when="2023-06-01T12:40:00"/>
+     <rdf:li rdf:parseType="Resource">
+      <stEvt:action>saved</stEvt:action>
+      <stEvt:softwareAgent>Adobe Photoshop 24.0</stEvt:softwareAgent>
+     </rdf:li>
+   </rdf:Seq></xmpMM:History>
+   <xmpMM:DerivedFrom stRef:documentID="xmp.did:1234"/>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+fn jpeg_with_xmp(packet: &str) -> Vec<u8> {
+    let mut payload = b"http://ns.adobe.com/xap/1.0/\0".to_vec();
+    payload.extend_from_slice(packet.as_bytes());
+    let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+    data.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+    data.extend_from_slice(&payload);
+    data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x04, 0x01, 0x02, 0xFF, 0xD9]);
+    data
+}
+
+fn png_chunk(id: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut chunk = (payload.len() as u32).to_be_bytes().to_vec();
+    chunk.extend_from_slice(id);
+    chunk.extend_from_slice(payload);
+    chunk.extend_from_slice(&[0, 0, 0, 0]); // CRC is not checked
+    chunk
+}
+
+fn png_with_xmp(packet: &str, compressed: bool) -> Vec<u8> {
+    let mut itxt = b"XML:com.adobe.xmp\0".to_vec();
+    itxt.push(compressed as u8);
+    itxt.push(0); // compression method
+    itxt.extend_from_slice(b"\0\0"); // empty language tag and translated keyword
+    if compressed {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(packet.as_bytes()).unwrap();
+        itxt.extend_from_slice(&encoder.finish().unwrap());
+    } else {
+        itxt.extend_from_slice(packet.as_bytes());
+    }
+
+    let mut data = container::PNG_SIGNATURE.to_vec();
+    data.extend_from_slice(&png_chunk(b"IHDR", &[0; 13]));
+    data.extend_from_slice(&png_chunk(b"iTXt", &itxt));
+    data.extend_from_slice(&png_chunk(b"IEND", &[]));
+    data
+}
+
+fn webp_with_xmp(packet: &str) -> Vec<u8> {
+    let mut body = b"WEBP".to_vec();
+    body.extend_from_slice(b"VP8X");
+    body.extend_from_slice(&10u32.to_le_bytes());
+    body.extend_from_slice(&[0x04, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    body.extend_from_slice(b"XMP ");
+    body.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+    body.extend_from_slice(packet.as_bytes());
+    if packet.len() % 2 == 1 {
+        body.push(0);
+    }
+    let mut data = b"RIFF".to_vec();
+    data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    data.extend_from_slice(&body);
+    data
+}
+
+fn tiff_with_xmp(packet: &str) -> Vec<u8> {
+    let mut data = b"MM\0*".to_vec();
+    data.extend_from_slice(&8u32.to_be_bytes());
+    data.extend_from_slice(&1u16.to_be_bytes());
+    data.extend_from_slice(&700u16.to_be_bytes());
+    data.extend_from_slice(&1u16.to_be_bytes()); // BYTE
+    data.extend_from_slice(&(packet.len() as u32).to_be_bytes());
+    data.extend_from_slice(&26u32.to_be_bytes());
+    data.extend_from_slice(&0u32.to_be_bytes());
+    data.extend_from_slice(packet.as_bytes());
+    data
+}
+
+fn gif_with_xmp(packet: &str) -> Vec<u8> {
+    let mut data = b"GIF89a".to_vec();
+    data.extend_from_slice(&[1, 0, 1, 0, 0x80, 0, 0]); // 1x1 with 2-entry colour table
+    data.extend_from_slice(&[0, 0, 0, 255, 255, 255]);
+    data.extend_from_slice(&[0x21, 0xFF, 11]);
+    data.extend_from_slice(b"XMP DataXMP");
+    data.extend_from_slice(packet.as_bytes());
+    // Magic trailer: 0x01, 0xFF..=0x00 descending, then the block terminator
+    data.push(0x01);
+    data.extend((0..=255u8).rev());
+    data.push(0x00);
+    data.extend_from_slice(&[
+        0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0, 0x02, 0x02, 0x44, 0x01, 0x00,
+    ]);
+    data.push(0x3B);
+    data
+}
+
+#[test]
+fn test_parse_simple_and_array_properties() {
+    let props = parse_xmp(PACKET).unwrap();
+    let get = |key: &str| {
+        props
+            .iter()
+            .find(|p| p.key() == key)
+            .map(|p| p.value.as_str())
+    };
+
+    assert_eq!(get("XMP-xmp:CreatorTool"), Some("Adobe Lightroom 6.0"));
+    assert_eq!(
+        get("XMP-photoshop:DateCreated"),
+        Some("2023-06-01T12:34:56")
+    );
+    assert_eq!(get("XMP-dc:creator"), Some("Jane Doe"));
+    assert_eq!(get("XMP-dc:subject"), Some("beach; holiday"));
+    assert_eq!(get("XMP-dc:title"), Some("Beach"));
+}
+
+#[test]
+fn test_parse_structs_and_arrays_of_structs() {
+    let props = parse_xmp(PACKET).unwrap();
+    let get = |key: &str| {
+        props
+            .iter()
+            .find(|p| p.key() == key)
+            .map(|p| p.value.as_str())
+    };
+
+    assert_eq!(get("XMP-xmpMM:History[1]/action"), Some("created"));
+    assert_eq!(
+        get("XMP-xmpMM:History[1]/when"),
+        Some("2023-06-01T12:40:00")
+    );
+    assert_eq!(get("XMP-xmpMM:History[2]/action"), Some("saved"));
+    assert_eq!(
+        get("XMP-xmpMM:History[2]/softwareAgent"),
+        Some("Adobe Photoshop 24.0")
+    );
+    assert_eq!(
+        get("XMP-xmpMM:DerivedFrom/documentID"),
+        Some("xmp.did:1234")
+    );
+}
+
+#[test]
+fn test_non_canonical_prefix_is_normalised() {
+    // The packet binds the xmp namespace to "xap"
+    assert_eq!(
+        canonical_prefix("http://ns.adobe.com/xap/1.0/"),
+        Some("xmp")
+    );
+    let props = parse_xmp(PACKET).unwrap();
+    assert!(props.iter().all(|p| p.prefix != "xap"));
+}
+
+#[test]
+fn test_unknown_namespace_keeps_document_prefix() {
+    let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+        <rdf:Description xmlns:acme="http://example.com/acme/" acme:Tag="x"/>
+    </rdf:RDF>"#;
+    let props = parse_xmp(xml).unwrap();
+    assert_eq!(props.len(), 1);
+    assert_eq!(props[0].key(), "XMP-acme:Tag");
+    assert_eq!(props[0].namespace, "http://example.com/acme/");
+}
+
+#[test]
+fn test_entity_references_are_resolved() {
+    let xml = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+        <rdf:Description xmlns:dc="http://purl.org/dc/elements/1.1/"
+            xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:CreatorTool="A &amp; B &#169;">
+            <dc:creator><rdf:Seq><rdf:li>Tom &amp; Jerry&#x2122; &lt;studio&gt;</rdf:li></rdf:Seq></dc:creator>
+        </rdf:Description>
+    </rdf:RDF>"#;
+    let props = parse_xmp(xml).unwrap();
+    let get = |key: &str| {
+        props
+            .iter()
+            .find(|p| p.key() == key)
+            .map(|p| p.value.as_str())
+    };
+    assert_eq!(get("XMP-xmp:CreatorTool"), Some("A & B ©"));
+    assert_eq!(get("XMP-dc:creator"), Some("Tom & Jerry™ <studio>"));
+}
+
+#[test]
+fn test_values_are_typed_and_sourced() {
+    let map = extract_xmp_data(&jpeg_with_xmp(PACKET));
+
+    let serial = &map["XMP-aux:SerialNumber"];
+    assert_eq!(serial.raw, RawValue::Text("0012345".to_string()));
+
+    let f_number = &map["XMP-exif:FNumber"];
+    assert!((f_number.as_f64().unwrap() - 2.8).abs() < 1e-9);
+    assert_eq!(f_number.display, "28/10");
+    assert_eq!(
+        f_number.source,
+        Some(MetadataSource::Xmp {
+            namespace: "http://ns.adobe.com/exif/1.0/".to_string()
+        })
+    );
+    assert_eq!(f_number.ifd(), None);
+}
+
+#[test]
+fn test_find_packet_in_each_container() {
+    let containers = [
+        ("jpeg", jpeg_with_xmp(PACKET)),
+        ("png", png_with_xmp(PACKET, false)),
+        ("png-compressed", png_with_xmp(PACKET, true)),
+        ("webp", webp_with_xmp(PACKET)),
+        ("tiff", tiff_with_xmp(PACKET)),
+        ("gif", gif_with_xmp(PACKET)),
+    ];
+    for (name, data) in containers {
+        let map = extract_xmp_data(&data);
+        assert_eq!(
+            map.get("XMP-dc:creator").map(|v| v.display.as_str()),
+            Some("Jane Doe"),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn test_gif_application_extension_walker() {
+    let data = gif_with_xmp(PACKET);
+    let extensions = container::gif_application_extensions(&data);
+    assert_eq!(extensions.len(), 1);
+    assert_eq!(&extensions[0].identifier, b"XMP DataXMP");
+}
+
+#[test]
+fn test_svg_with_namespaces_on_root() {
+    let svg = r#"<?xml version="1.0"?>
+<svg xmlns="http://www.w3.org/2000/svg"
+     xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+     xmlns:dc="http://purl.org/dc/elements/1.1/"
+     xmlns:cc="http://creativecommons.org/ns#">
+  <metadata>
+    <rdf:RDF>
+      <cc:Work rdf:about="">
+        <dc:creator><cc:Agent><dc:title>Jane Doe</dc:title></cc:Agent></dc:creator>
+        <cc:license rdf:resource="http://creativecommons.org/licenses/by/4.0/"/>
+      </cc:Work>
+    </rdf:RDF>
+  </metadata>
+</svg>"#;
+    let map = extract_xmp_data(svg.as_bytes());
+    assert_eq!(map["XMP-dc:creator/Agent/title"], "Jane Doe");
+    assert_eq!(
+        map["XMP-cc:license"],
+        "http://creativecommons.org/licenses/by/4.0/"
+    );
+}
+
+const EXTENDED: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description xmlns:GImage="http://ns.google.com/photos/1.0/image/" GImage:Mime="image/jpeg"/></rdf:RDF></x:xmpmeta>"#;
+
+/// A JPEG holding the standard packet followed by the given Extended XMP
+/// chunks of `EXTENDED`, as (offset, part) pairs.
+fn jpeg_with_extended_xmp(chunks: &[(u32, &[u8])]) -> Vec<u8> {
+    let mut data = jpeg_with_xmp(PACKET);
+    data.truncate(data.len() - 8); // drop SOS and EOI
+    for (offset, part) in chunks {
+        let mut payload = b"http://ns.adobe.com/xmp/extension/\0".to_vec();
+        payload.extend_from_slice(&[b'A'; 32]);
+        payload.extend_from_slice(&(EXTENDED.len() as u32).to_be_bytes());
+        payload.extend_from_slice(&offset.to_be_bytes());
+        payload.extend_from_slice(part);
+        data.extend_from_slice(&[0xFF, 0xE1]);
+        data.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        data.extend_from_slice(&payload);
+    }
+    data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x04, 0x01, 0x02, 0xFF, 0xD9]);
+    data
+}
+
+#[test]
+fn test_extended_xmp_is_reassembled() {
+    let (first, second) = EXTENDED.as_bytes().split_at(40);
+    // Chunks deliberately written out of order
+    let data = jpeg_with_extended_xmp(&[(40, second), (0, first)]);
+
+    assert_eq!(find_xmp_packets(&data).len(), 2);
+    let map = extract_xmp_data(&data);
+    assert_eq!(map["XMP-GImage:Mime"], "image/jpeg");
+    assert_eq!(map["XMP-dc:creator"], "Jane Doe");
+}
+
+#[test]
+fn test_extended_xmp_duplicate_chunk_is_kept_once() {
+    let (first, second) = EXTENDED.as_bytes().split_at(40);
+    let data = jpeg_with_extended_xmp(&[(0, first), (40, second), (40, second)]);
+
+    let packets = find_xmp_packets(&data);
+    assert_eq!(packets.len(), 2);
+    assert_eq!(packets[1], EXTENDED);
+    assert_eq!(extract_xmp_data(&data)["XMP-GImage:Mime"], "image/jpeg");
+}
+
+#[test]
+fn test_extended_xmp_missing_chunk_is_skipped_and_reported() {
+    let (first, _) = EXTENDED.as_bytes().split_at(40);
+    let data = jpeg_with_extended_xmp(&[(0, first)]);
+
+    assert_eq!(find_xmp_packets(&data).len(), 1);
+    let map = extract_xmp_data(&data);
+    assert!(!map.contains_key("XMP-GImage:Mime"));
+    assert_eq!(map["XMP-dc:creator"], "Jane Doe");
+
+    let mut diagnostics = Vec::new();
+    find_xmp_packets_with_diagnostics(&data, &mut diagnostics);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].parser, "XMP");
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert!(diagnostics[0].message.contains("are declared"));
+}
+
+#[test]
+fn test_malformed_packets_are_ignored() {
+    assert!(extract_xmp_data(&jpeg_with_xmp("<x:xmpmeta><rdf:RDF")).is_empty());
+    assert!(extract_xmp_data(b"").is_empty());
+    assert!(extract_xmp_data(&[0xFF, 0xD8, 0xFF, 0xE1, 0xFF, 0xFF]).is_empty());
+}
+
+#[test]
+fn test_extract_metadata_merges_exif_and_xmp() {
+    let (map, gps) = extract_metadata(&jpeg_with_xmp(PACKET));
+    assert!(gps.is_none());
+    assert_eq!(map["XMP-xmp:CreatorTool"], "Adobe Lightroom 6.0");
+}